@@ -0,0 +1,158 @@
+// Package asyncdb lets services built around contexts embed novasql without
+// blocking on its disk I/O.
+//
+// A DB hands every call to a worker goroutine that owns the database (a
+// novasql.Database is not safe for concurrent use) and waits for the result
+// or for the call's context, whichever comes first. A call whose context ends
+// before the worker picks it up is dropped; one already running finishes, or
+// for a SQL statement is cancelled, and its result is discarded.
+//
+// Go only links the packages a program imports, so importing asyncdb is the
+// opt-in: the rest of novasql does not depend on it.
+package asyncdb
+
+import (
+	"context"
+	"errors"
+	"sync"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/sql/executor"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+var ErrClosed = errors.New("asyncdb: database is closed")
+
+type DB struct {
+	db   *novasql.Database
+	ex   *executor.Executor
+	jobs chan func()
+	done chan struct{}
+
+	closeOnce sync.Once
+	closeErr  error
+}
+
+// Open opens the database in workDir with opts (nil = novasql.Options())
+// off the calling goroutine.
+func Open(ctx context.Context, workDir string, opts *novasql.OpenOptions) (*DB, error) {
+	if opts == nil {
+		opts = novasql.Options()
+	}
+	type opened struct {
+		db  *novasql.Database
+		err error
+	}
+	ch := make(chan opened, 1)
+	go func() {
+		db, err := opts.Open(workDir)
+		ch <- opened{db, err}
+	}()
+
+	select {
+	case o := <-ch:
+		if o.err != nil {
+			return nil, o.err
+		}
+		return New(o.db), nil
+	case <-ctx.Done():
+		go func() {
+			if o := <-ch; o.err == nil {
+				_ = o.db.Close()
+			}
+		}()
+		return nil, ctx.Err()
+	}
+}
+
+// New serves db, which the DB owns from now on: use it only through the DB.
+func New(db *novasql.Database) *DB {
+	d := &DB{
+		db:   db,
+		ex:   executor.NewExecutor(db),
+		jobs: make(chan func()),
+		done: make(chan struct{}),
+	}
+	go d.work()
+	return d
+}
+
+func (d *DB) work() {
+	for {
+		select {
+		case job := <-d.jobs:
+			job()
+		case <-d.done:
+			return
+		}
+	}
+}
+
+// Execute runs one SQL statement, cancelled once ctx is done; see
+// executor.Executor.ExecContext.
+func (d *DB) Execute(ctx context.Context, sql string) (*executor.Result, error) {
+	return run(ctx, d, func() (*executor.Result, error) { return d.ex.ExecContext(ctx, sql) })
+}
+
+// GetPage reads a page of fs straight from storage; pages still dirty in the
+// buffer pool are not seen until they are flushed.
+func (d *DB) GetPage(ctx context.Context, fs storage.FileSet, pageID uint32) (*storage.Page, error) {
+	return run(ctx, d, func() (*storage.Page, error) { return d.db.SM.LoadPage(fs, pageID) })
+}
+
+// WritePage writes a page of fs straight to storage, sealing its checksum.
+// It bypasses the buffer pool: do not use it on files the database has open.
+func (d *DB) WritePage(ctx context.Context, fs storage.FileSet, pageID uint32, p storage.Page) error {
+	_, err := run(ctx, d, func() (struct{}, error) { return struct{}{}, d.db.SM.SavePage(fs, pageID, p) })
+	return err
+}
+
+// Checkpoint runs novasql.Database.Checkpoint.
+func (d *DB) Checkpoint(ctx context.Context) error {
+	_, err := run(ctx, d, func() (struct{}, error) { return struct{}{}, d.db.Checkpoint() })
+	return err
+}
+
+// Close waits for the running call, closes the database and stops the
+// worker. Later calls fail with ErrClosed.
+func (d *DB) Close() error {
+	d.closeOnce.Do(func() {
+		errc := make(chan error, 1)
+		d.jobs <- func() { errc <- d.db.Close() }
+		d.closeErr = <-errc
+		close(d.done)
+	})
+	return d.closeErr
+}
+
+// run hands fn to the worker of d and waits for its result or for ctx.
+func run[T any](ctx context.Context, d *DB, fn func() (T, error)) (T, error) {
+	type result struct {
+		v   T
+		err error
+	}
+	var zero T
+	res := make(chan result, 1)
+	job := func() {
+		if err := ctx.Err(); err != nil {
+			res <- result{zero, err}
+			return
+		}
+		v, err := fn()
+		res <- result{v, err}
+	}
+
+	select {
+	case d.jobs <- job:
+	case <-ctx.Done():
+		return zero, ctx.Err()
+	case <-d.done:
+		return zero, ErrClosed
+	}
+	select {
+	case r := <-res:
+		return r.v, r.err
+	case <-ctx.Done():
+		return zero, ctx.Err()
+	}
+}
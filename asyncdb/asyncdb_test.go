@@ -0,0 +1,71 @@
+package asyncdb
+
+import (
+	"context"
+	"fmt"
+	"sync"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+func TestDB_ConcurrentExecute(t *testing.T) {
+	ctx := context.Background()
+	d, err := Open(ctx, t.TempDir(), nil)
+	require.NoError(t, err)
+
+	_, err = d.Execute(ctx, "CREATE TABLE t (id INT);")
+	require.NoError(t, err)
+
+	var wg sync.WaitGroup
+	errs := make(chan error, 20)
+	for i := range 20 {
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			_, err := d.Execute(ctx, fmt.Sprintf("INSERT INTO t VALUES (%d);", i))
+			errs <- err
+		}()
+	}
+	wg.Wait()
+	close(errs)
+	for err := range errs {
+		require.NoError(t, err)
+	}
+
+	res, err := d.Execute(ctx, "SELECT COUNT(*) FROM t;")
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{int64(20)}}, res.Rows)
+
+	canceled, cancel := context.WithCancel(ctx)
+	cancel()
+	_, err = d.Execute(canceled, "SELECT * FROM t;")
+	require.ErrorIs(t, err, context.Canceled)
+
+	require.NoError(t, d.Close())
+	require.NoError(t, d.Close())
+	_, err = d.Execute(ctx, "SELECT * FROM t;")
+	require.ErrorIs(t, err, ErrClosed)
+}
+
+func TestDB_GetWritePage(t *testing.T) {
+	ctx := context.Background()
+	d, err := Open(ctx, t.TempDir(), nil)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = d.Close() })
+
+	fs := storage.LocalFileSet{Dir: t.TempDir(), Base: "raw"}
+	p, err := storage.NewPage(make([]byte, storage.PageSize), 2)
+	require.NoError(t, err)
+	_, err = p.InsertTuple([]byte("hello"))
+	require.NoError(t, err)
+	require.NoError(t, d.WritePage(ctx, fs, 2, *p))
+
+	got, err := d.GetPage(ctx, fs, 2)
+	require.NoError(t, err)
+	tup, err := got.ReadTuple(0)
+	require.NoError(t, err)
+	require.Equal(t, []byte("hello"), tup)
+}
@@ -0,0 +1,190 @@
+package main
+
+import (
+	"errors"
+	"flag"
+	"fmt"
+	"math"
+	"math/rand"
+	"os"
+	"slices"
+	"sync"
+	"text/tabwriter"
+	"time"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/sql/executor"
+)
+
+// runBench loads a table into database dbName (default "default"), has
+// -threads sessions, each with its own handle as server connections have,
+// run -ops point reads and updates of it in the -read mix, and prints the
+// throughput and latency percentiles. The table is dropped afterwards
+// unless -keep is given.
+func runBench(cfg *internal.NovaSqlConfig, args []string) error {
+	fset := flag.NewFlagSet("bench", flag.ExitOnError)
+	rows := fset.Int("rows", 10000, "rows loaded before the workload")
+	ops := fset.Int("ops", 100000, "statements run by all threads together")
+	threads := fset.Int("threads", 4, "concurrent sessions")
+	readMix := fset.Float64("read", 0.9, "fraction of the statements that are reads")
+	table := fset.String("table", "bench", "table created for the run; it must not exist")
+	keep := fset.Bool("keep", false, "keep the table afterwards")
+	dbName := "default"
+	if len(args) > 0 && args[0] != "" && args[0][0] != '-' {
+		dbName, args = args[0], args[1:]
+	}
+	if err := fset.Parse(args); err != nil {
+		return err
+	}
+	if *rows < 1 || *ops < 1 || *threads < 1 || *readMix < 0 || *readMix > 1 {
+		return errors.New("bench: -rows, -ops and -threads must be positive and -read between 0 and 1")
+	}
+
+	db, err := openDatabase(cfg, dbName)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = db.Close() }()
+	start := time.Now()
+	if err := loadBenchTable(db, *table, *rows); err != nil {
+		return err
+	}
+	if !*keep {
+		defer func() { _ = db.DropTablePurge(*table) }()
+	}
+	fmt.Printf("loaded %d rows into %s in %s\n", *rows, *table, time.Since(start).Round(time.Millisecond))
+
+	w := benchWorkload{table: *table, rows: *rows, readMix: *readMix}
+	results := make([]benchResult, *threads)
+	errs := make([]error, *threads)
+	var wg sync.WaitGroup
+	start = time.Now()
+	for i := range *threads {
+		n := *ops / *threads
+		if i < *ops%*threads {
+			n++
+		}
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			results[i], errs[i] = w.run(cfg, dbName, n, int64(i)+1)
+		}()
+	}
+	wg.Wait()
+	elapsed := time.Since(start)
+	if err := errors.Join(errs...); err != nil {
+		return err
+	}
+
+	var total benchResult
+	for _, r := range results {
+		total.reads = append(total.reads, r.reads...)
+		total.writes = append(total.writes, r.writes...)
+	}
+	fmt.Printf("%d ops in %s with %d threads: %.0f ops/s\n",
+		*ops, elapsed.Round(time.Millisecond), *threads, float64(*ops)/elapsed.Seconds())
+	tw := tabwriter.NewWriter(os.Stdout, 0, 4, 2, ' ', 0)
+	fmt.Fprintln(tw, "OP\tCOUNT\tP50\tP95\tP99\tMAX")
+	for _, l := range []struct {
+		op  string
+		lat []time.Duration
+	}{
+		{"read", total.reads},
+		{"write", total.writes},
+		{"all", slices.Concat(total.reads, total.writes)},
+	} {
+		if len(l.lat) == 0 {
+			continue
+		}
+		slices.Sort(l.lat)
+		fmt.Fprintf(tw, "%s\t%d\t%s\t%s\t%s\t%s\n", l.op, len(l.lat),
+			percentile(l.lat, 0.50), percentile(l.lat, 0.95), percentile(l.lat, 0.99), l.lat[len(l.lat)-1])
+	}
+	return tw.Flush()
+}
+
+// loadBenchTable creates table with an INT primary key and a TEXT value and
+// bulk loads n rows, ids 0 to n-1, into it.
+func loadBenchTable(db *novasql.Database, table string, n int) error {
+	ex := executor.NewExecutor(db)
+	if _, err := ex.ExecSQL(fmt.Sprintf("CREATE TABLE %s (id INT PRIMARY KEY, v TEXT NOT NULL);", table)); err != nil {
+		return err
+	}
+	const batch = 10000
+	rows := make([][]any, 0, min(n, batch))
+	for i := range n {
+		rows = append(rows, []any{int64(i), benchValue(i)})
+		if len(rows) == cap(rows) || i == n-1 {
+			if _, err := db.InsertRows(table, rows); err != nil {
+				return err
+			}
+			rows = rows[:0]
+		}
+	}
+	return nil
+}
+
+func benchValue(i int) string { return fmt.Sprintf("value-%08d", i) }
+
+// benchWorkload is the statement mix of a run: point reads by primary key
+// and updates of the value, of rows chosen uniformly.
+type benchWorkload struct {
+	table   string
+	rows    int
+	readMix float64
+}
+
+// benchResult holds the latency of every statement a session ran.
+type benchResult struct {
+	reads, writes []time.Duration
+}
+
+// run opens a session on dbName and runs n statements of w, choosing them
+// with a generator seeded with seed.
+func (w benchWorkload) run(cfg *internal.NovaSqlConfig, dbName string, n int, seed int64) (benchResult, error) {
+	var res benchResult
+	db, err := openDatabase(cfg, dbName)
+	if err != nil {
+		return res, err
+	}
+	defer func() { _ = db.Close() }()
+	ex := executor.NewExecutor(db)
+	read, err := ex.Prepare(fmt.Sprintf("SELECT v FROM %s WHERE id = ?;", w.table))
+	if err != nil {
+		return res, err
+	}
+	write, err := ex.Prepare(fmt.Sprintf("UPDATE %s SET v = ? WHERE id = ?;", w.table))
+	if err != nil {
+		return res, err
+	}
+
+	rng := rand.New(rand.NewSource(seed))
+	for range n {
+		id := int64(rng.Intn(w.rows))
+		stmt, args := read, []any{id}
+		if rng.Float64() >= w.readMix {
+			stmt, args = write, []any{benchValue(rng.Int()), id}
+		}
+		start := time.Now()
+		if err := stmt.Bind(args...); err != nil {
+			return res, err
+		}
+		if _, err := stmt.Execute(); err != nil {
+			return res, err
+		}
+		if stmt == read {
+			res.reads = append(res.reads, time.Since(start))
+		} else {
+			res.writes = append(res.writes, time.Since(start))
+		}
+	}
+	return res, nil
+}
+
+// percentile is the latency at or below which fraction p of the sorted
+// latencies fall.
+func percentile(sorted []time.Duration, p float64) time.Duration {
+	i := int(math.Ceil(p*float64(len(sorted)))) - 1
+	return sorted[max(i, 0)]
+}
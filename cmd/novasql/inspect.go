@@ -0,0 +1,164 @@
+package main
+
+import (
+	"encoding/hex"
+	"errors"
+	"flag"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"strings"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+// runInspect prints what a file of a database directory holds, read
+// straight from disk: the decoded header for <db>/header; for a table or
+// index file (the path without the segment suffix, e.g.
+// <db>/tables/users) the node structure and free list of its B-tree when
+// it has a meta file, and the header, trailer and slots of each page, or of
+// page -page, with the tuples hex-dumped under -hex. Encrypted pages cannot
+// be decoded and are dumped as stored.
+func runInspect(args []string) error {
+	if len(args) < 1 || args[0] == "" || args[0][0] == '-' {
+		usage()
+	}
+	file, args := args[0], args[1:]
+	fset := flag.NewFlagSet("inspect", flag.ExitOnError)
+	page := fset.Int("page", -1, "only this page")
+	dump := fset.Bool("hex", false, "hex-dump the tuples")
+	if err := fset.Parse(args); err != nil {
+		return err
+	}
+
+	w := os.Stdout
+	if filepath.Base(file) == "header" {
+		h, err := novasql.InspectHeader(filepath.Dir(file))
+		if err != nil {
+			return err
+		}
+		fmt.Fprintf(w, "%s: format %d, page size %d, compression %s, encrypted %t\n",
+			file, h.FormatVersion, h.PageSize, h.Compression, h.Encrypted)
+		return nil
+	}
+
+	fs := storage.LocalFileSet{Dir: filepath.Dir(file), Base: filepath.Base(file)}
+	sm := storage.NewStorageManager()
+	sm.SetReadOnly(true)
+	n, err := sm.CountPages(fs)
+	if err != nil {
+		return err
+	}
+	if n == 0 {
+		return fmt.Errorf("inspect: %s has no pages", file)
+	}
+	fmt.Fprintf(w, "%s: %d pages\n", file, n)
+	read := func(pageID uint32, dst []byte) error { return sm.ReadPage(fs, int32(pageID), dst) }
+
+	if _, err := os.Stat(btree.MetaPath(fs)); err == nil {
+		printKVTree(w, fs, read)
+	}
+
+	first, last := uint32(0), n-1
+	if *page >= 0 {
+		if *page >= int(n) {
+			return fmt.Errorf("inspect: %s has no page %d", file, *page)
+		}
+		first, last = uint32(*page), uint32(*page)
+	}
+	buf := make([]byte, storage.PageSize)
+	for id := first; id <= last; id++ {
+		if err := read(id, buf); err != nil {
+			var ce *storage.CorruptedPageError
+			if !errors.As(err, &ce) {
+				return err
+			}
+			fmt.Fprintf(w, "page %d: %s; as stored:\n", id, ce.Reason)
+			if err := sm.ReadRawPage(fs, int32(id), buf); err != nil {
+				return err
+			}
+			fmt.Fprint(w, indent(hex.Dump(buf), "  "))
+			continue
+		}
+		printPage(w, id, storage.InspectPage(buf), *dump)
+	}
+	return nil
+}
+
+// printKVTree prints the meta file and the nodes, level by level, of the
+// B-tree in fs. A walk that fails is reported and ends there.
+func printKVTree(w io.Writer, fs storage.LocalFileSet, read func(uint32, []byte) error) {
+	info, err := btree.InspectKVTree(fs, read)
+	if info != nil {
+		fmt.Fprintf(w, "btree: root %d, height %d, next page %d, free list %v\n",
+			info.Root, info.Height, info.NextPageID, info.FreePages)
+		for _, node := range info.Nodes {
+			kind := "leaf"
+			if node.Level > 0 {
+				kind = "internal"
+			}
+			fmt.Fprintf(w, "  level %d %s page %d: %d keys", node.Level, kind, node.PageID, len(node.Keys))
+			if len(node.Keys) > 0 {
+				fmt.Fprintf(w, ", %s .. %s", shortKey(node.Keys[0]), shortKey(node.Keys[len(node.Keys)-1]))
+			}
+			if node.Children != nil {
+				fmt.Fprintf(w, ", children %v", node.Children)
+			}
+			fmt.Fprintln(w)
+		}
+	}
+	if err != nil {
+		fmt.Fprintf(w, "btree: walk stopped: %v\n", err)
+	}
+}
+
+func printPage(w io.Writer, id uint32, info storage.PageInfo, dump bool) {
+	if info.Uninitialized {
+		fmt.Fprintf(w, "page %d: uninitialized\n", id)
+		return
+	}
+	fmt.Fprintf(w, "page %d: lower %d, upper %d, special %d, free %d, flags %#x, checksum %#08x, version %d, lsn %d\n",
+		id, info.Lower, info.Upper, info.Special, info.FreeSpace, info.Flags, info.Checksum, info.Version, info.LSN)
+	if info.PageID != id {
+		fmt.Fprintf(w, "  header says page %d\n", info.PageID)
+	}
+	if info.Problem != "" {
+		fmt.Fprintf(w, "  problem: %s\n", info.Problem)
+	}
+	for i, s := range info.Slots {
+		switch {
+		case s.Flags&storage.SlotFlagMoved != 0:
+			fmt.Fprintf(w, "  slot %d: moved to slot %d\n", i, s.Offset)
+		case s.Flags&storage.SlotFlagDeleted != 0:
+			fmt.Fprintf(w, "  slot %d: deleted\n", i)
+		case s.Tuple == nil:
+			fmt.Fprintf(w, "  slot %d: offset %d, length %d: out of bounds\n", i, s.Offset, s.Length)
+		default:
+			fmt.Fprintf(w, "  slot %d: offset %d, length %d\n", i, s.Offset, s.Length)
+			if dump {
+				fmt.Fprint(w, indent(hex.Dump(s.Tuple), "    "))
+			}
+		}
+	}
+}
+
+// shortKey quotes a B-tree key, cut to its first 24 bytes.
+func shortKey(k []byte) string {
+	if len(k) > 24 {
+		return fmt.Sprintf("%q...", k[:24])
+	}
+	return fmt.Sprintf("%q", k)
+}
+
+func indent(s, prefix string) string {
+	lines := strings.SplitAfter(s, "\n")
+	for i, l := range lines {
+		if l != "" {
+			lines[i] = prefix + l
+		}
+	}
+	return strings.Join(lines, "")
+}
@@ -0,0 +1,156 @@
+// Command novasql is the offline admin tool and local SQL shell. It opens
+// the data directory directly, so the server should not be running against
+// the same workdir.
+//
+//	novasql [-config novasql.yaml] shell [db]
+//	novasql [-config novasql.yaml] trash list <db>
+//	novasql [-config novasql.yaml] trash restore <db> <table|id> [-as name]
+//	novasql [-config novasql.yaml] trash purge <db> [-older-than 24h]
+//	novasql [-config novasql.yaml] bench [db] [-rows n] [-ops n] [-threads n] [-read 0.9]
+//	novasql inspect <file> [-page n] [-hex]
+package main
+
+import (
+	"flag"
+	"fmt"
+	"log"
+	"os"
+	"slices"
+	"text/tabwriter"
+	"time"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal"
+)
+
+func usage() {
+	fmt.Fprintln(os.Stderr, `usage: novasql [-config path] <command> [args]
+
+commands:
+  shell [db]                              interactive SQL prompt (default db: default)
+  trash list <db>                         list dropped tables
+  trash restore <db> <table|id> [-as n]   restore a dropped table (optionally as n)
+  trash purge <db> [-older-than d]        permanently delete dropped tables
+  bench [db] [-rows n] [-ops n] [-threads n] [-read f] [-table t] [-keep]
+                                          load a table and report ops/s and latency percentiles
+                                          of a point read/update mix
+  inspect <file> [-page n] [-hex]         decode a header, table or index file: pages, slots,
+                                          B-tree nodes and free list`)
+	os.Exit(2)
+}
+
+func main() {
+	var cfgPath string
+	flag.StringVar(&cfgPath, "config", "novasql.yaml", "Path to novasql yaml config")
+	flag.Usage = usage
+	flag.Parse()
+	if flag.NArg() < 1 {
+		usage()
+	}
+
+	cfg, err := internal.LoadConfig(cfgPath)
+	if err != nil {
+		log.Fatalf("load config: %v", err)
+	}
+
+	switch flag.Arg(0) {
+	case "shell":
+		err = runShell(cfg, flag.Args()[1:])
+	case "trash":
+		err = runTrash(cfg, flag.Args()[1:])
+	case "bench":
+		err = runBench(cfg, flag.Args()[1:])
+	case "inspect":
+		err = runInspect(flag.Args()[1:])
+	default:
+		usage()
+	}
+	if err != nil {
+		log.Fatal(err)
+	}
+}
+
+// openDatabase opens the configured workdir and selects database name.
+func openDatabase(cfg *internal.NovaSqlConfig, name string) (*novasql.Database, error) {
+	workdir := cfg.Storage.Workdir
+	if workdir == "" {
+		workdir = internal.DefaultWorkdir
+	}
+	db, err := novasql.Options().Config(cfg).Open(workdir)
+	if err != nil {
+		return nil, err
+	}
+	names, err := db.ListDatabase()
+	if err != nil {
+		_ = db.Close()
+		return nil, err
+	}
+	if !slices.Contains(names, name) {
+		_ = db.Close()
+		return nil, fmt.Errorf("%w: %s", novasql.ErrDatabaseNotFound, name)
+	}
+	if _, err := db.SelectDatabase(name); err != nil {
+		_ = db.Close()
+		return nil, err
+	}
+	return db, nil
+}
+
+func runTrash(cfg *internal.NovaSqlConfig, args []string) error {
+	if len(args) < 2 {
+		usage()
+	}
+	sub, dbName := args[0], args[1]
+
+	fset := flag.NewFlagSet("trash "+sub, flag.ExitOnError)
+	as := fset.String("as", "", "restore under this table name")
+	olderThan := fset.Duration("older-than", 0, "only purge tables dropped at least this long ago")
+
+	var ref string
+	rest := args[2:]
+	if sub == "restore" {
+		if len(rest) < 1 {
+			usage()
+		}
+		ref, rest = rest[0], rest[1:]
+	}
+	if err := fset.Parse(rest); err != nil {
+		return err
+	}
+
+	db, err := openDatabase(cfg, dbName)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = db.Close() }()
+
+	switch sub {
+	case "list":
+		entries, err := db.ListTrash()
+		if err != nil {
+			return err
+		}
+		tw := tabwriter.NewWriter(os.Stdout, 0, 4, 2, ' ', 0)
+		fmt.Fprintln(tw, "ID\tTABLE\tDROPPED AT")
+		for _, e := range entries {
+			fmt.Fprintf(tw, "%s\t%s\t%s\n", e.ID, e.Table.Name, e.DroppedAt.Format(time.RFC3339))
+		}
+		return tw.Flush()
+	case "restore":
+		if err := db.RestoreTableAs(ref, *as); err != nil {
+			return err
+		}
+		fmt.Println("restored", ref)
+		return nil
+	case "purge":
+		n, err := db.PurgeTrash(*olderThan)
+		if err != nil {
+			return err
+		}
+		fmt.Printf("purged %d table(s)\n", n)
+		return nil
+	default:
+		usage()
+		return nil
+	}
+}
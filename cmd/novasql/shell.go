@@ -0,0 +1,78 @@
+package main
+
+import (
+	"errors"
+	"flag"
+	"fmt"
+	"io"
+	"os"
+
+	"github.com/chzyer/readline"
+
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/shell"
+)
+
+// runShell opens database dbName (default "default") directly and reads
+// statements from an interactive prompt.
+func runShell(cfg *internal.NovaSqlConfig, args []string) error {
+	fset := flag.NewFlagSet("shell", flag.ExitOnError)
+	histPath := fset.String("history", shell.DefaultHistoryPath(), "history file path")
+	histMax := fset.Int("history-max", 2000, "max history lines loaded into memory")
+	dbName := "default"
+	if len(args) > 0 && args[0] != "" && args[0][0] != '-' {
+		dbName, args = args[0], args[1:]
+	}
+	if err := fset.Parse(args); err != nil {
+		return err
+	}
+
+	db, err := openDatabase(cfg, dbName)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = db.Close() }()
+
+	h := shell.NewHistory(*histPath)
+	_ = h.Load(*histMax)
+
+	rl, err := readline.NewEx(&readline.Config{
+		Prompt:          shell.Prompt,
+		InterruptPrompt: "^C",
+		EOFPrompt:       "exit",
+	})
+	if err != nil {
+		return fmt.Errorf("readline: %w", err)
+	}
+	defer func() { _ = rl.Close() }()
+	for _, line := range h.Lines() {
+		_ = rl.SaveHistory(line)
+	}
+
+	sess := shell.NewSession(db, os.Stdout)
+	sess.Executed = func(stmt string) {
+		_ = h.Append(stmt)
+		_ = rl.SaveHistory(shell.CompactOneLine(stmt))
+	}
+
+	fmt.Printf("opened database %q (type .help for help)\n", dbName)
+	for {
+		rl.SetPrompt(sess.Prompt())
+		line, err := rl.Readline()
+		if errors.Is(err, readline.ErrInterrupt) {
+			// Ctrl+C clears the current statement
+			sess.Reset()
+			continue
+		}
+		if errors.Is(err, io.EOF) {
+			fmt.Println()
+			return nil
+		}
+		if err != nil {
+			return err
+		}
+		if sess.Feed(line) {
+			return nil
+		}
+	}
+}
@@ -1,14 +1,27 @@
 package main
 
 import (
+	"context"
 	"flag"
 	"fmt"
 	"log"
+	"log/slog"
+	"net"
+	"net/http"
 	"os"
+	"os/signal"
+	"strconv"
+	"sync/atomic"
+	"syscall"
+	"time"
 
+	"github.com/tuannm99/novasql"
 	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/metrics"
+	"github.com/tuannm99/novasql/internal/sql/executor"
 	"github.com/tuannm99/novasql/internal/storage"
 	"github.com/tuannm99/novasql/server/novasqlwire"
+	"github.com/tuannm99/novasql/server/pgwire"
 )
 
 func main() {
@@ -20,33 +33,193 @@ func main() {
 	if err != nil {
 		log.Fatalf("load config: %v", err)
 	}
+	var level slog.LevelVar
+	lv, err := cfg.LogLevel()
+	if err != nil {
+		log.Fatalf("config: %v", err)
+	}
+	level.Set(lv)
+	logger, err := cfg.NewLevelLogger(os.Stderr, &level)
+	if err != nil {
+		log.Fatalf("config: %v", err)
+	}
+	slog.SetDefault(logger)
+
+	var current atomic.Pointer[internal.NovaSqlConfig]
+	current.Store(cfg)
+	reloadOnHangup(cfgPath, &current, &level)
 
 	addr := os.Getenv("NOVASQL_ADDR")
 	if addr == "" {
-		// Use config port by default
-		port := cfg.Server.Port
-		if port == 0 {
-			port = 6543
-		}
-		addr = fmt.Sprintf("127.0.0.1:%d", port)
+		addr = fmt.Sprintf("127.0.0.1:%d", cfg.Server.Port)
 	}
 
 	workdir := cfg.Storage.Workdir
 	if workdir == "" {
-		workdir = "./data"
+		workdir = internal.DefaultWorkdir // memory mode
 	}
 
 	if err := os.MkdirAll(workdir, storage.FileMode0755); err != nil {
 		log.Fatalf("create data dir: %v", err)
 	}
 
+	reg := metrics.New()
 	sc := novasqlwire.ServerConfig{
 		Addr:    addr,
 		Workdir: workdir,
 		CfgPath: cfgPath,
+		Config:  cfg,
+		Metrics: reg,
+		Current: current.Load,
+	}
+	if cfg.Server.MetricsPort != 0 {
+		serveMetrics(fmt.Sprintf("127.0.0.1:%d", cfg.Server.MetricsPort), reg)
+	}
+	// The server's own handle, for its background work.
+	var db *novasql.Database
+	if cfg.Server.ReplicationPort != 0 || cfg.Storage.TTLSweepInterval > 0 {
+		if db, err = novasql.Options().Config(cfg).Upgrade(true).Metrics(reg).Open(workdir); err != nil {
+			log.Fatalf("open %s: %v", workdir, err)
+		}
+	}
+	if cfg.Server.ReplicationPort != 0 {
+		host, _, err := net.SplitHostPort(addr)
+		if err != nil {
+			log.Fatalf("NOVASQL_ADDR: %v", err)
+		}
+		serveReplication(net.JoinHostPort(host, strconv.Itoa(cfg.Server.ReplicationPort)), db)
+	}
+	if cfg.Storage.TTLSweepInterval > 0 {
+		sweepExpired(db)
+	}
+
+	if cfg.Server.PgPort == 0 {
+		if err := novasqlwire.Run(sc); err != nil {
+			log.Fatalf("server error: %v", err)
+		}
+		return
 	}
 
-	if err := novasqlwire.Run(sc); err != nil {
+	// Both listeners share one signal context so SIGINT/SIGTERM drains them together.
+	ctx, stop := signal.NotifyContext(context.Background(), syscall.SIGINT, syscall.SIGTERM)
+	defer stop()
+
+	ln, err := net.Listen("tcp", addr)
+	if err != nil {
+		log.Fatalf("listen: %v", err)
+	}
+	pgAddr := fmt.Sprintf("127.0.0.1:%d", cfg.Server.PgPort)
+	pgLn, err := net.Listen("tcp", pgAddr)
+	if err != nil {
+		log.Fatalf("listen: %v", err)
+	}
+	log.Printf("novasql tcp server listening on %s, postgres protocol on %s (workdir=%s)", addr, pgAddr, workdir)
+
+	pgDone := make(chan error, 1)
+	go func() {
+		pgDone <- pgwire.Serve(ctx, pgLn, pgwire.Config{
+			Workdir: workdir,
+			Config:  cfg,
+			Metrics: reg,
+			Current: current.Load,
+		})
+	}()
+	err = novasqlwire.Serve(ctx, ln, sc)
+	stop()
+	if pgErr := <-pgDone; err == nil {
+		err = pgErr
+	}
+	if err != nil {
 		log.Fatalf("server error: %v", err)
 	}
 }
+
+// reloadOnHangup rereads the config file at path on every SIGHUP, in the
+// background: the log level changes at once, and sessions apply the
+// changeable settings before their next statement. A file that fails to
+// load keeps the config as it was.
+func reloadOnHangup(path string, current *atomic.Pointer[internal.NovaSqlConfig], level *slog.LevelVar) {
+	hup := make(chan os.Signal, 1)
+	signal.Notify(hup, syscall.SIGHUP)
+	go func() {
+		for range hup {
+			cfg, err := internal.LoadConfig(path)
+			var lv slog.Level
+			if err == nil {
+				lv, err = cfg.LogLevel()
+			}
+			if err != nil {
+				slog.Error("config reload failed", "path", path, "err", err)
+				continue
+			}
+			level.Set(lv)
+			current.Store(cfg)
+			slog.Info("config reloaded", "path", path)
+		}
+	}()
+}
+
+// serveMetrics serves the metrics of every session at http://addr/metrics in
+// the background.
+func serveMetrics(addr string, reg *metrics.Registry) {
+	ln, err := net.Listen("tcp", addr)
+	if err != nil {
+		log.Fatalf("listen: %v", err)
+	}
+	mux := http.NewServeMux()
+	mux.HandleFunc("/metrics", func(w http.ResponseWriter, _ *http.Request) {
+		w.Header().Set("Content-Type", "text/plain; version=0.0.4")
+		_ = reg.Snapshot().WritePrometheus(w)
+	})
+	log.Printf("metrics on http://%s/metrics", addr)
+	go func() {
+		srv := &http.Server{Handler: mux, ReadHeaderTimeout: 5 * time.Second}
+		if err := srv.Serve(ln); err != nil {
+			log.Printf("metrics server: %v", err)
+		}
+	}()
+}
+
+// serveReplication streams the default database of db to the replicas that
+// connect to addr, in the background.
+func serveReplication(addr string, db *novasql.Database) {
+	ln, err := net.Listen("tcp", addr)
+	if err != nil {
+		log.Fatalf("listen: %v", err)
+	}
+	log.Printf("replication on %s", addr)
+	go func() {
+		if err := db.ServeReplication(ln); err != nil {
+			log.Printf("replication: %v", err)
+		}
+	}()
+}
+
+// sweepExpired deletes the expired rows of every database of db every
+// storage.ttl_sweep_interval, in the background.
+func sweepExpired(db *novasql.Database) {
+	ex := executor.NewExecutor(db)
+	tick := time.NewTicker(db.TTLSweepInterval())
+	go func() {
+		for range tick.C {
+			names, err := db.ListDatabase()
+			if err != nil {
+				slog.Error("ttl sweep failed", "err", err)
+				continue
+			}
+			for _, name := range names {
+				if _, err := db.SelectDatabase(name); err != nil {
+					slog.Error("ttl sweep failed", "database", name, "err", err)
+					continue
+				}
+				n, err := ex.ReapExpired(time.Now())
+				if err != nil {
+					slog.Error("ttl sweep failed", "database", name, "err", err)
+				}
+				if n > 0 {
+					slog.Info("ttl sweep: deleted expired rows", "database", name, "rows", n)
+				}
+			}
+		}
+	}()
+}
@@ -7,14 +7,18 @@ import (
 	"log/slog"
 	"os"
 	"path/filepath"
+	"runtime"
 	"strings"
 	"sync"
 	"time"
 
+	"github.com/tuannm99/novasql/internal"
 	"github.com/tuannm99/novasql/internal/btree"
 	"github.com/tuannm99/novasql/internal/bufferpool"
 	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/mvcc"
 	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/expr"
 	"github.com/tuannm99/novasql/internal/storage"
 	"github.com/tuannm99/novasql/internal/wal"
 )
@@ -25,6 +29,10 @@ var (
 	ErrBadIdent       = errors.New("novasql: invalid identifier")
 )
 
+// cacheManifestFile is written into the database directory on clean shutdown
+// and lists the pages that were resident in the buffer pool.
+const cacheManifestFile = "bufferpool.manifest.json"
+
 // DatabaseOperation defines the high-level operations that a Database supports.
 type DatabaseOperation interface {
 	ListDatabase() ([]string, error)
@@ -48,6 +56,18 @@ type TableMeta struct {
 	PageCount uint32        `json:"page_count"`
 	Indexes   []IndexMeta   `json:"indexes,omitempty"`
 
+	// ForeignKeys reference PRIMARY KEY or UNIQUE columns of tables.
+	ForeignKeys []ForeignKey `json:"foreign_keys,omitempty"`
+
+	// AutoIncrement is the counter of the AUTOINCREMENT column, if any.
+	AutoIncrement *AutoIncrement `json:"auto_increment,omitempty"`
+
+	// TTLColumn is the TIMESTAMP column past which rows expire ("" = none).
+	TTLColumn string `json:"ttl_column,omitempty"`
+
+	// Stats is replaced as a whole by Analyze, never edited in place.
+	Stats *TableStats `json:"stats,omitempty"`
+
 	CreatedAt time.Time `json:"created_at"`
 	UpdatedAt time.Time `json:"updated_at"`
 }
@@ -65,6 +85,9 @@ type Database struct {
 	WAL *wal.Manager
 	SM  *storage.StorageManager
 
+	// xact is the transaction status log of DataDir (row-level MVCC).
+	xact *mvcc.Manager
+
 	// Global shared buffer pool (like PostgreSQL shared_buffers).
 	bp *bufferpool.GlobalPool
 
@@ -72,36 +95,213 @@ type Database struct {
 	muViews sync.Mutex
 	views   map[string]bufferpool.Manager
 
+	// opts holds the options the handle was opened with.
+	opts OpenOptions
+
+	// cat caches the selected database's table metadata.
+	cat catalog
+
+	// stopWarmup cancels a running cache warm-up (nil when none is running).
+	stopWarmup func()
+
+	// stopWriter stops the background writers of bp (nil when none run).
+	stopWriter func()
+
+	// memDir is the metadata directory of an in-memory database, removed on
+	// Close ("" otherwise).
+	memDir string
+
+	// layout is where the files of DataDir live (see Layout); tempDir is
+	// this handle's directory under its Temp, "" until TempDir is called.
+	layout  Layout
+	tempDir string
+
+	// temp holds the temporary tables of the handle (see CreateTempTable).
+	temp tempTables
+
+	// funcs holds the SQL functions added to the handle (see CreateFunction).
+	funcs expr.Registry
+
+	// backup is the running online backup, if any.
+	backup *Backup
+
+	// repl is where replication stands (see ReplicationStatus); follower
+	// keeps a replica up to date, nil on other handles.
+	repl     replState
+	follower *follower
+
+	// feed is the change feed of DataDir; changes holds the subscriptions
+	// of the handle and the changes of its running transactions.
+	feed    *changeFeed
+	changes changeLog
+
+	// muAutoInc serializes updates of AUTOINCREMENT counters.
+	muAutoInc sync.Mutex
+
+	// lock is the advisory lock on WorkDir (see storage.LockDir).
+	lock *storage.DirLock
+
+	// unclosed and cleanup shut the handle down if it is garbage collected
+	// while open (see watchUnclosed).
+	unclosed *shutdown
+	cleanup  runtime.Cleanup
+
 	closed bool
 }
 
 // NewDatabase creates a new database handle without touching the filesystem.
 // workDir is the root directory that contains databases.
 func NewDatabase(workDir string) *Database {
-	sm := storage.NewStorageManager()
-
-	root := filepath.Clean(workDir)
-	cur := filepath.Join(root, "default")
+	return NewDatabaseWithConfig(workDir, nil)
+}
 
-	db := &Database{
-		WorkDir: root,
-		DataDir: cur,
-		SM:      sm,
-		views:   make(map[string]bufferpool.Manager),
+// NewDatabaseWithConfig is NewDatabase with runtime options taken from cfg.
+// A nil cfg behaves exactly like NewDatabase.
+//
+// Both constructors are thin wrappers over Options().Open and keep their
+// historical lenient behavior: legacy metadata is upgraded in place and an open
+// failure is logged and yields a closed handle (every call returns
+// ErrDatabaseClosed). Use Options for error reporting and finer control.
+func NewDatabaseWithConfig(workDir string, cfg *internal.NovaSqlConfig) *Database {
+	db, err := Options().Config(cfg).Upgrade(true).Open(workDir)
+	if err != nil {
+		slog.Error("open database failed", "workdir", workDir, "err", err)
+		root := filepath.Clean(workDir)
+		return &Database{
+			WorkDir: root,
+			DataDir: filepath.Join(root, "default"),
+			SM:      storage.NewStorageManager(),
+			views:   make(map[string]bufferpool.Manager),
+			closed:  true,
+		}
 	}
-	_ = os.MkdirAll(filepath.Join(cur, "tables"), 0o755)
+	return db
+}
 
-	// WAL per database directory
-	w, _ := wal.Open(filepath.Join(cur, "wal"))
+// openWAL opens the WAL of the current DataDir and replays it. After a
+// successful replay the recovered pages are synced and the log truncated.
+// In-memory databases have no WAL: nothing of them survives to recover.
+func (db *Database) openWAL() {
+	if db.SM.InMemory() {
+		return
+	}
+	open := func(name string, flag int, perm os.FileMode) (wal.File, error) {
+		return db.openLogFile(name, flag, perm)
+	}
+	w, _ := wal.OpenWith(db.layout.WAL, open)
 	db.WAL = w
-	if db.WAL != nil {
-		if err := db.WAL.Recover(storage.NewWALWriter(db.SM)); err != nil {
-			slog.Warn("wal recover failed", "err", err)
+	if db.WAL == nil {
+		return
+	}
+	db.WAL.SetSyncMode(db.syncMode())
+	db.WAL.SetMetrics(db.opts.metrics)
+	if c := db.SM.PageCipher(); c != nil {
+		db.WAL.SetPageCipher(c)
+	}
+	if dir := db.walArchiveDir(); dir != "" {
+		db.WAL.SetArchiveDir(dir)
+	}
+	if err := db.WAL.Recover(storage.NewWALWriter(db.SM)); err != nil {
+		slog.Warn("wal recover failed", "err", err)
+		return
+	}
+	if err := db.syncDataFiles(); err != nil {
+		slog.Warn("wal recover: sync data files failed", "err", err)
+		return
+	}
+	if err := db.WAL.Checkpoint(); err != nil {
+		slog.Warn("wal checkpoint after recovery failed", "err", err)
+	}
+}
+
+// syncDataFiles fsyncs all files of the current database, after msyncing
+// them in mmap mode.
+func (db *Database) syncDataFiles() error {
+	return syncDataDir(db.SM, db.DataDir)()
+}
+
+// syncDataDir is syncDataFiles for the files of sm in dir, as a function
+// that holds no reference to the handle (see shutdown).
+func syncDataDir(sm *storage.StorageManager, dir string) func() error {
+	return func() error {
+		if err := sm.Sync(); err != nil {
+			return err
 		}
+		return storage.SyncDir(dir)
 	}
+}
 
-	db.bp = bufferpool.NewGlobalPool(sm, bufferpool.DefaultCapacity, db.WAL)
-	return db
+// storageMode is storage.mode of cfg; unset is classic.
+func storageMode(cfg *internal.NovaSqlConfig) (storage.StorageMode, error) {
+	if cfg == nil || cfg.Storage.Mode == "" {
+		return storage.Classic, nil
+	}
+	return storage.GetStorageMode(cfg.Storage.Mode)
+}
+
+// Checkpoint writes every dirty page of the current database to its data
+// files, fsyncs them and truncates the WAL. It also happens automatically once
+// the WAL reaches storage.checkpoint_wal_bytes.
+func (db *Database) Checkpoint() error {
+	_, err := db.checkpoint()
+	return err
+}
+
+// checkpoint is Checkpoint returning the LSN it was taken at.
+func (db *Database) checkpoint() (uint64, error) {
+	if err := db.ensureWritable(); err != nil {
+		return 0, err
+	}
+	if db.bp == nil {
+		return 0, nil
+	}
+	return db.bp.CheckpointLSN(db.syncDataFiles)
+}
+
+// openXact loads the transaction status log of the current DataDir.
+func (db *Database) openXact() error {
+	open := func(name string, flag int, perm os.FileMode) (mvcc.File, error) {
+		return db.openLogFile(name, flag, perm)
+	}
+	x, err := mvcc.OpenWith(filepath.Join(db.DataDir, "xact"), db.opts.readOnly, open)
+	if err != nil {
+		return err
+	}
+	if db.opts.cfg != nil {
+		x.SetLockTimeout(db.opts.cfg.Transaction.LockWaitTimeout)
+	}
+	x.SetMetrics(db.opts.metrics)
+	x.SetFinishHook(db.finishChanges)
+	if db.WAL != nil {
+		x.SetLog(db.WAL)
+	}
+	db.xact = x
+	return nil
+}
+
+// openLogFile opens the WAL or the transaction status log through the
+// engine of the handle if it handles them, as an OS file otherwise.
+func (db *Database) openLogFile(name string, flag int, perm os.FileMode) (storage.File, error) {
+	if o, ok := db.opts.engine.(storage.FileOpener); ok {
+		return o.OpenFile(name, flag, perm)
+	}
+	f, err := os.OpenFile(name, flag, perm)
+	if err != nil {
+		return nil, err
+	}
+	return f, nil
+}
+
+// closeWAL closes the WAL and the transaction status log of DataDir.
+func (db *Database) closeWAL() {
+	if db.WAL != nil {
+		_ = db.WAL.Close()
+		db.WAL = nil
+	}
+	if db.xact != nil {
+		_ = db.xact.Close()
+		db.xact = nil
+	}
 }
 
 func (db *Database) ensureOpen() error {
@@ -140,6 +340,9 @@ func (db *Database) dropView(fs storage.FileSet) {
 
 // flushAndDropFileSet invalidates all cached pages of this FileSet in global pool.
 // IMPORTANT: call this before deleting/renaming segment files.
+//
+// It also checkpoints the WAL, so no logged page image still refers to the
+// files: replaying one after they were deleted or renamed would resurrect them.
 func (db *Database) flushAndDropFileSet(fs storage.FileSet) error {
 	if db.bp == nil {
 		return nil
@@ -153,7 +356,10 @@ func (db *Database) flushAndDropFileSet(fs storage.FileSet) error {
 		return err
 	}
 	db.dropView(fs)
-	return nil
+	if err := db.bp.Checkpoint(db.syncDataFiles); err != nil {
+		return err
+	}
+	return db.SM.Release(fs)
 }
 
 func (db *Database) rootDir() string {
@@ -166,12 +372,158 @@ func (db *Database) dbDir(name string) string {
 }
 
 func (db *Database) resetBufferPool() {
+	db.stopCacheWarmup()
+	db.stopBackgroundWriter()
+
 	// Recreate shared buffer pool and drop all cached views.
-	db.bp = bufferpool.NewGlobalPool(db.SM, bufferpool.DefaultCapacity, db.WAL)
+	db.bp = bufferpool.NewGlobalPool(db.SM, db.bufferPoolPages(), db.WAL)
+	db.bp.SetAutoCheckpoint(db.checkpointWALBytes(), syncDataDir(db.SM, db.DataDir))
+	db.bp.SetMetrics(db.opts.metrics)
 
 	db.muViews.Lock()
 	db.views = make(map[string]bufferpool.Manager)
 	db.muViews.Unlock()
+
+	db.startCacheWarmup()
+	db.startBackgroundWriter()
+	db.noteShutdown()
+}
+
+// bufferPoolPages returns storage.buffer_pool_pages, or the default capacity.
+func (db *Database) bufferPoolPages() int {
+	if db.opts.cfg == nil || db.opts.cfg.Storage.BufferPoolPages <= 0 {
+		return bufferpool.DefaultCapacity
+	}
+	return db.opts.cfg.Storage.BufferPoolPages
+}
+
+// defaultCheckpointWALBytes is the WAL size that triggers an automatic
+// checkpoint when storage.checkpoint_wal_bytes is unset.
+const defaultCheckpointWALBytes = internal.DefaultCheckpointWALBytes
+
+// checkpointWALBytes returns storage.checkpoint_wal_bytes, or the default.
+func (db *Database) checkpointWALBytes() int64 {
+	if db.opts.cfg == nil || db.opts.cfg.Storage.CheckpointWALBytes == 0 {
+		return defaultCheckpointWALBytes
+	}
+	return db.opts.cfg.Storage.CheckpointWALBytes
+}
+
+// defaultSortMemBytes is what one sort may hold in memory when
+// storage.sort_mem_bytes is unset.
+const defaultSortMemBytes = internal.DefaultSortMemBytes
+
+// SortMemBytes is the memory budget of one ORDER BY: past it, the executor
+// writes sorted runs to TempDir and merges them. It is
+// storage.sort_mem_bytes, or 64 MiB; negative means sorts never spill.
+func (db *Database) SortMemBytes() int64 {
+	if db.opts.cfg == nil || db.opts.cfg.Storage.SortMemBytes == 0 {
+		return defaultSortMemBytes
+	}
+	return db.opts.cfg.Storage.SortMemBytes
+}
+
+// walArchiveDir is the archive of the current database under
+// storage.wal_archive_dir, or "" without archiving.
+func (db *Database) walArchiveDir() string {
+	if db.opts.cfg == nil || db.opts.cfg.Storage.WALArchiveDir == "" {
+		return ""
+	}
+	return filepath.Join(db.opts.cfg.Storage.WALArchiveDir, filepath.Base(db.DataDir))
+}
+
+// syncMode returns storage.sync_mode (validated by OpenOptions), or SyncFull.
+func (db *Database) syncMode() wal.SyncMode {
+	if db.opts.cfg == nil {
+		return wal.SyncFull
+	}
+	mode, _ := wal.ParseSyncMode(db.opts.cfg.Storage.SyncMode)
+	return mode
+}
+
+func (db *Database) cacheManifestPath() string {
+	return filepath.Join(db.DataDir, cacheManifestFile)
+}
+
+// saveCacheManifest records the resident pages of the current database so the
+// next open can warm the cache. Best-effort: a missing manifest only means a
+// cold start.
+func (db *Database) saveCacheManifest() {
+	if db.bp == nil || db.opts.readOnly {
+		return
+	}
+	if err := db.bp.SaveManifest(db.cacheManifestPath()); err != nil {
+		slog.Warn("save cache manifest failed", "err", err)
+	}
+}
+
+// startCacheWarmup kicks off a background prefetch of the saved manifest when
+// cache.warmup is enabled.
+func (db *Database) startCacheWarmup() {
+	if db.opts.cfg == nil || !db.opts.cfg.Cache.Warmup || db.bp == nil {
+		return
+	}
+	entries, err := bufferpool.LoadManifest(db.cacheManifestPath())
+	if err != nil {
+		slog.Debug("cache warmup skipped: unreadable manifest", "err", err)
+		return
+	}
+	if len(entries) == 0 {
+		return
+	}
+	db.stopWarmup = db.bp.StartWarmup(entries, db.opts.cfg.Cache.WarmupRate)
+}
+
+func (db *Database) stopCacheWarmup() {
+	if db.stopWarmup != nil {
+		db.stopWarmup()
+		db.stopWarmup = nil
+	}
+}
+
+// startBackgroundWriter starts the storage.background_writers writers of
+// the pool, which also checkpoint every storage.checkpoint_interval.
+func (db *Database) startBackgroundWriter() {
+	if db.opts.cfg == nil || db.opts.readOnly || db.bp == nil {
+		return
+	}
+	db.stopWriter = db.bp.StartWriter(db.backgroundWriters(), db.checkpointInterval())
+}
+
+// stopBackgroundWriter waits for the background writers to finish their
+// round and exit; call it before the pool or its WAL goes away.
+func (db *Database) stopBackgroundWriter() {
+	if db.stopWriter != nil {
+		db.stopWriter()
+		db.stopWriter = nil
+	}
+}
+
+// backgroundWriters returns storage.background_writers; without a config
+// there are none.
+func (db *Database) backgroundWriters() int {
+	if db.opts.cfg == nil {
+		return 0
+	}
+	return db.opts.cfg.Storage.BackgroundWriters
+}
+
+// checkpointInterval returns storage.checkpoint_interval, 0 without a
+// config.
+func (db *Database) checkpointInterval() time.Duration {
+	if db.opts.cfg == nil {
+		return 0
+	}
+	return db.opts.cfg.Storage.CheckpointInterval
+}
+
+// BufferPoolStats returns the shared buffer pool counters, including cache
+// warm-up progress.
+func (db *Database) BufferPoolStats() bufferpool.Stats {
+	if db == nil || db.bp == nil {
+		return bufferpool.Stats{}
+	}
+	return db.bp.Stats()
 }
 
 // tableDir returns the directory where table data and meta files live.
@@ -205,17 +557,35 @@ func (db *Database) writeTableMeta(meta *TableMeta) error {
 	if err := os.MkdirAll(db.tableDir(), 0o755); err != nil {
 		return err
 	}
-	meta.UpdatedAt = time.Now()
+	meta.UpdatedAt = db.now()
 	data, err := json.MarshalIndent(meta, "", "  ")
 	if err != nil {
 		return err
 	}
 	path := db.tableMetaPath(meta.Name)
-	return writeFileAtomic(path, data, 0o644)
+	if err := writeFileAtomic(path, data, 0o644); err != nil {
+		return err
+	}
+	db.rememberTableMeta(meta)
+	return nil
 }
 
-// readTableMeta loads table metadata from JSON file.
+// readTableMeta returns table metadata from the catalog, falling back to the
+// JSON file for tables the catalog has not seen.
 func (db *Database) readTableMeta(name string) (*TableMeta, error) {
+	if meta, ok := db.cachedTableMeta(name); ok {
+		return meta, nil
+	}
+	meta, err := db.readTableMetaFile(name)
+	if err != nil {
+		return nil, err
+	}
+	db.rememberTableMeta(meta)
+	return meta, nil
+}
+
+// readTableMetaFile loads table metadata from JSON file.
+func (db *Database) readTableMetaFile(name string) (*TableMeta, error) {
 	path := db.tableMetaPath(name)
 
 	data, err := os.ReadFile(path)
@@ -227,6 +597,7 @@ func (db *Database) readTableMeta(name string) (*TableMeta, error) {
 	if err := json.Unmarshal(data, &meta); err != nil {
 		return nil, err
 	}
+	fixStatsTypes(meta.Stats, meta.Schema)
 	return &meta, nil
 }
 
@@ -309,7 +680,7 @@ func (db *Database) BufferView(fs storage.FileSet) bufferpool.Manager {
 }
 
 func (db *Database) CreateDatabase(name string) error {
-	if err := db.ensureOpen(); err != nil {
+	if err := db.ensureWritable(); err != nil {
 		return err
 	}
 	if err := validateIdent(name); err != nil {
@@ -327,7 +698,7 @@ func (db *Database) CreateDatabase(name string) error {
 
 // DropDatabase implements DatabaseOperation.
 func (db *Database) DropDatabase(name string) ([]string, error) {
-	if err := db.ensureOpen(); err != nil {
+	if err := db.ensureWritable(); err != nil {
 		return nil, err
 	}
 	if err := validateIdent(name); err != nil {
@@ -345,13 +716,22 @@ func (db *Database) DropDatabase(name string) ([]string, error) {
 	}
 
 	if target == cur {
-		if db.WAL != nil {
-			_ = db.WAL.Close()
-			db.WAL = nil
+		db.stopCacheWarmup()
+		db.stopBackgroundWriter()
+		db.closeWAL()
+		if err := db.releaseTempDir(); err != nil {
+			return nil, err
 		}
 	}
 
-	// Remove the database directory.
+	// Remove the database directory, and its WAL and temporary files kept
+	// elsewhere.
+	if err := removeLayout(target); err != nil {
+		return nil, err
+	}
+	if err := db.SM.RemoveDir(target); err != nil {
+		return nil, err
+	}
 	if err := os.RemoveAll(target); err != nil {
 		return nil, err
 	}
@@ -361,15 +741,21 @@ func (db *Database) DropDatabase(name string) ([]string, error) {
 	if target == cur {
 		db.DataDir = db.dbDir("default")
 		_ = os.MkdirAll(filepath.Join(db.DataDir, "tables"), 0o755)
+		if err := db.openHeader(); err != nil {
+			return nil, err
+		}
+		if err := db.openLayout(); err != nil {
+			return nil, err
+		}
 
-		w, _ := wal.Open(filepath.Join(db.DataDir, "wal"))
-		db.WAL = w
-		if db.WAL != nil {
-			if err := db.WAL.Recover(storage.NewWALWriter(db.SM)); err != nil {
-				slog.Warn("wal recover failed", "err", err)
-			}
+		db.openWAL()
+		if err := db.openXact(); err != nil {
+			return nil, err
 		}
 		db.resetBufferPool()
+		if err := db.loadCatalog(); err != nil {
+			return nil, err
+		}
 	}
 	return db.ListDatabase()
 }
@@ -410,39 +796,47 @@ func (db *Database) SelectDatabase(name string) ([]string, error) {
 		return nil, err
 	}
 
+	if db.follower != nil {
+		return nil, fmt.Errorf("%w: a replica follows the database it was opened with", ErrConflictingOptions)
+	}
+
 	target := db.dbDir(name)
 
 	// Ensure target exists (or at least create it).
-	if err := os.MkdirAll(filepath.Join(target, "tables"), 0o755); err != nil {
+	if db.opts.readOnly {
+		if !dirExists(filepath.Join(target, "tables")) {
+			return nil, fmt.Errorf("%w: %s", ErrDatabaseNotFound, name)
+		}
+	} else if err := os.MkdirAll(filepath.Join(target, "tables"), 0o755); err != nil {
 		return nil, err
 	}
 
-	// Flush current shared buffers before switching.
+	// Checkpoint the current database before switching. The writers stop
+	// meanwhile, and go on with it when the switch fails.
+	db.stopBackgroundWriter()
 	if db.bp != nil {
-		if err := db.bp.FlushAll(); err != nil {
+		if err := db.bp.Checkpoint(db.syncDataFiles); err != nil {
+			db.startBackgroundWriter()
 			return nil, err
 		}
 	}
+	db.saveCacheManifest()
+	if err := db.releaseTempDir(); err != nil {
+		db.startBackgroundWriter()
+		return nil, err
+	}
 
 	// Switch DataDir + reset caches/pool.
 	db.DataDir = target
 
-	// Close old WAL (best-effort)
-	if db.WAL != nil {
-		_ = db.WAL.Close()
-		db.WAL = nil
-	}
-
-	// Open WAL for new DB and recover
-	w, _ := wal.Open(filepath.Join(db.DataDir, "wal"))
-	db.WAL = w
-	if db.WAL != nil {
-		if err := db.WAL.Recover(storage.NewWALWriter(db.SM)); err != nil {
-			slog.Warn("wal recover failed", "err", err)
-		}
-	}
-
+	// Close old WAL (best-effort), then open + recover the new one and apply
+	// the open policy (upgrade/check) to the new database.
+	db.closeWAL()
+	err := db.openDataDir()
 	db.resetBufferPool()
+	if err != nil {
+		return nil, err
+	}
 
 	// Return list of tables in the selected DB.
 	metas, err := db.ListTables()
@@ -461,17 +855,20 @@ func (db *Database) SelectDatabase(name string) ([]string, error) {
 
 // CreateTable creates a new heap table and its associated overflow storage.
 func (db *Database) CreateTable(name string, schema record.Schema) (*heap.Table, error) {
-	if err := db.ensureOpen(); err != nil {
+	if err := db.ensureWritable(); err != nil {
 		return nil, err
 	}
 	if err := validateIdent(name); err != nil {
 		return nil, err
 	}
+	if db.isTempTable(name) || db.isView(name) {
+		return nil, fmt.Errorf("%w: %s", ErrTableExists, name)
+	}
 
 	fs := db.tableFileSet(name)
 	bp := db.viewFor(fs)
 
-	now := time.Now()
+	now := db.now()
 	meta := &TableMeta{
 		Name:      name,
 		Schema:    schema,
@@ -486,11 +883,14 @@ func (db *Database) CreateTable(name string, schema record.Schema) (*heap.Table,
 
 	overflowFS := db.overflowFileSet(name)
 	ovf := storage.NewOverflowManagerWithWAL(overflowFS, db.WAL)
+	ovf.SetStorage(db.SM)
 
 	tbl := heap.NewTable(name, schema, db.SM, fs, bp, ovf, 0)
 	tbl.SetPageCountHook(func(pc uint32) error {
 		return db.syncTableMetaPageCountByName(name, pc)
 	})
+	tbl.SetMVCC(db.xact)
+	tbl.SetChangeHook(db.changeHook())
 	return tbl, nil
 }
 
@@ -502,11 +902,17 @@ func (db *Database) OpenTable(name string) (*heap.Table, error) {
 	if err := validateIdent(name); err != nil {
 		return nil, err
 	}
+	if db.isTempTable(name) {
+		return db.openTempTable(name, db.tempDir)
+	}
 
 	fs := db.tableFileSet(name)
 	bp := db.viewFor(fs)
 
 	meta, err := db.readTableMeta(name)
+	if errors.Is(err, os.ErrNotExist) {
+		return nil, tableNotFound(name)
+	}
 	if err != nil {
 		return nil, err
 	}
@@ -518,34 +924,46 @@ func (db *Database) OpenTable(name string) (*heap.Table, error) {
 
 	// Refresh meta snapshot (keep Indexes intact).
 	meta.PageCount = pageCount
-	meta.UpdatedAt = time.Now()
+	meta.UpdatedAt = db.now()
 
 	// Best-effort update.
-	if err := db.writeTableMeta(meta); err != nil {
-		slog.Info("open table: error writing table meta", "err", err, "table", name)
+	if !db.opts.readOnly {
+		if err := db.writeTableMeta(meta); err != nil {
+			slog.Info("open table: error writing table meta", "err", err, "table", name)
+		}
 	}
 
 	overflowFS := db.overflowFileSet(name)
 	ovf := storage.NewOverflowManagerWithWAL(overflowFS, db.WAL)
+	ovf.SetStorage(db.SM)
 
 	tbl := heap.NewTable(name, meta.Schema, db.SM, fs, bp, ovf, pageCount)
 	tbl.SetPageCountHook(func(pc uint32) error {
 		return db.syncTableMetaPageCountByName(name, pc)
 	})
+	tbl.SetReadOnly(db.opts.readOnly)
+	tbl.SetMVCC(db.xact)
+	tbl.SetChangeHook(db.changeHook())
 	return tbl, nil
 }
 
-// DropTable removes all on-disk data for a table: indexes, heap pages, overflow pages,
-// and the metadata JSON file.
+// DropTablePurge removes all on-disk data for a table: indexes, heap pages, overflow pages,
+// and the metadata JSON file. Unlike DropTable it bypasses the trash.
 //
 // IMPORTANT: flush/drop from global pool BEFORE deleting files.
-func (db *Database) DropTable(name string) error {
-	if err := db.ensureOpen(); err != nil {
+func (db *Database) DropTablePurge(name string) error {
+	if err := db.ensureWritable(); err != nil {
 		return err
 	}
 	if err := validateIdent(name); err != nil {
 		return err
 	}
+	if db.isTempTable(name) {
+		return db.dropTempTable(name)
+	}
+	if err := db.checkNotReferenced(name, ""); err != nil {
+		return err
+	}
 	if err := os.MkdirAll(db.tableDir(), 0o755); err != nil {
 		return err
 	}
@@ -568,7 +986,7 @@ func (db *Database) DropTable(name string) error {
 
 	if meta != nil {
 		for _, im := range meta.Indexes {
-			if im.Kind != IndexKindBTree {
+			if !im.hasFiles() {
 				continue
 			}
 			base := im.FileBase
@@ -585,7 +1003,7 @@ func (db *Database) DropTable(name string) error {
 	// 1) Drop indexes files first (best practice: avoid leaving garbage).
 	if meta != nil {
 		for _, im := range meta.Indexes {
-			if im.Kind != IndexKindBTree {
+			if !im.hasFiles() {
 				continue
 			}
 			base := im.FileBase
@@ -593,17 +1011,17 @@ func (db *Database) DropTable(name string) error {
 				base = db.fmtIndexBase(name, im.Name)
 			}
 			fs := storage.LocalFileSet{Dir: db.tableDir(), Base: base}
-			if err := btree.DropIndex(fs); err != nil {
+			if err := btree.DropIndex(db.SM, fs); err != nil {
 				return err
 			}
 		}
 	}
 
 	// 2) Remove heap/ovf segments
-	if err := storage.RemoveAllSegments(heapFS); err != nil {
+	if err := db.SM.RemoveSegments(heapFS); err != nil {
 		return err
 	}
-	if err := storage.RemoveAllSegments(ovfFS); err != nil {
+	if err := db.SM.RemoveSegments(ovfFS); err != nil {
 		return err
 	}
 
@@ -612,50 +1030,24 @@ func (db *Database) DropTable(name string) error {
 	if err := os.Remove(metaPath); err != nil && !errors.Is(err, os.ErrNotExist) {
 		return err
 	}
+	db.forgetTableMeta(name)
 
 	return nil
 }
 
-// ListTables scans the table directory for *.meta.json files and returns their metadata.
+// ListTables returns the metadata of every table in the catalog, by name.
 func (db *Database) ListTables() ([]*TableMeta, error) {
 	if err := db.ensureOpen(); err != nil {
 		return nil, err
 	}
-	if err := os.MkdirAll(db.tableDir(), 0o755); err != nil {
-		return nil, err
-	}
-
-	entries, err := os.ReadDir(db.tableDir())
-	if err != nil {
-		return nil, err
-	}
-
-	var metas []*TableMeta
-	for _, entry := range entries {
-		if entry.IsDir() {
-			continue
-		}
-		name := entry.Name()
-		if !strings.HasSuffix(name, ".meta.json") {
-			continue
-		}
-
-		tableName := strings.TrimSuffix(name, ".meta.json")
-		meta, err := db.readTableMeta(tableName)
-		if err != nil {
-			slog.Warn("list tables: failed to read table meta", "file", name, "err", err)
-			continue
-		}
-		metas = append(metas, meta)
-	}
-	return metas, nil
+	return db.catalogTables(), nil
 }
 
 // RenameTable renames heap + ovf + meta + index segments and updates registry.
 //
 // IMPORTANT: flush/drop old cached pages BEFORE renaming files.
 func (db *Database) RenameTable(oldName, newName string) error {
-	if err := db.ensureOpen(); err != nil {
+	if err := db.ensureWritable(); err != nil {
 		return err
 	}
 	if err := validateIdent(oldName); err != nil {
@@ -670,6 +1062,9 @@ func (db *Database) RenameTable(oldName, newName string) error {
 
 	// Prevent accidental overwrite
 	newMetaPath := db.tableMetaPath(newName)
+	if db.isView(newName) {
+		return fmt.Errorf("%w: %s", ErrTableExists, newName)
+	}
 	if _, err := os.Stat(newMetaPath); err == nil {
 		return fmt.Errorf("novasql: table already exists: %s", newName)
 	} else if !errors.Is(err, os.ErrNotExist) {
@@ -694,7 +1089,7 @@ func (db *Database) RenameTable(oldName, newName string) error {
 	}
 
 	for _, im := range meta.Indexes {
-		if im.Kind != IndexKindBTree {
+		if !im.hasFiles() {
 			continue
 		}
 		oldBase := im.FileBase
@@ -707,7 +1102,7 @@ func (db *Database) RenameTable(oldName, newName string) error {
 	}
 
 	// 1) Rename heap segments
-	if err := storage.RenameAllSegments(
+	if err := db.SM.RenameSegments(
 		storage.LocalFileSet{Dir: db.tableDir(), Base: oldName},
 		storage.LocalFileSet{Dir: db.tableDir(), Base: newName},
 	); err != nil {
@@ -715,7 +1110,7 @@ func (db *Database) RenameTable(oldName, newName string) error {
 	}
 
 	// 2) Rename overflow segments
-	if err := storage.RenameAllSegments(
+	if err := db.SM.RenameSegments(
 		storage.LocalFileSet{Dir: db.tableDir(), Base: oldName + "_ovf"},
 		storage.LocalFileSet{Dir: db.tableDir(), Base: newName + "_ovf"},
 	); err != nil {
@@ -723,10 +1118,10 @@ func (db *Database) RenameTable(oldName, newName string) error {
 	}
 
 	// 3) Rename index segments + update registry FileBase
-	now := time.Now()
+	now := db.now()
 	for i := range meta.Indexes {
 		im := &meta.Indexes[i]
-		if im.Kind != IndexKindBTree {
+		if !im.hasFiles() {
 			continue
 		}
 
@@ -736,7 +1131,8 @@ func (db *Database) RenameTable(oldName, newName string) error {
 		}
 		newBase := db.fmtIndexBase(newName, im.Name)
 
-		if err := storage.RenameAllSegments(
+		if err := btree.RenameIndex(
+			db.SM,
 			storage.LocalFileSet{Dir: db.tableDir(), Base: oldBase},
 			storage.LocalFileSet{Dir: db.tableDir(), Base: newBase},
 		); err != nil {
@@ -756,10 +1152,16 @@ func (db *Database) RenameTable(oldName, newName string) error {
 	if err := os.Rename(oldMetaPath, newMetaPath); err != nil {
 		return err
 	}
+	db.forgetTableMeta(oldName)
 
 	// 5) Rewrite meta content with new table name
 	meta.Name = newName
 	meta.UpdatedAt = now
+	for i := range meta.ForeignKeys {
+		if meta.ForeignKeys[i].RefTable == oldName {
+			meta.ForeignKeys[i].RefTable = newName
+		}
+	}
 
 	// Drop views for old/new heap+ovf names
 	db.dropView(storage.LocalFileSet{Dir: db.tableDir(), Base: oldName})
@@ -767,7 +1169,10 @@ func (db *Database) RenameTable(oldName, newName string) error {
 	db.dropView(storage.LocalFileSet{Dir: db.tableDir(), Base: oldName + "_ovf"})
 	db.dropView(storage.LocalFileSet{Dir: db.tableDir(), Base: newName + "_ovf"})
 
-	return db.writeTableMeta(meta)
+	if err := db.writeTableMeta(meta); err != nil {
+		return err
+	}
+	return db.renameReferences(oldName, newName)
 }
 
 func (db *Database) syncTableMetaPageCountByName(name string, pageCount uint32) error {
@@ -798,13 +1203,26 @@ func (db *Database) Close() error {
 	if db.closed {
 		return nil
 	}
+	db.cleanup.Stop()
 
-	// Flush global pool (shared_buffers).
+	var errs []error
+	if db.follower != nil {
+		errs = append(errs, db.follower.close())
+	}
+	db.closeSubscriptions()
+	db.stopCacheWarmup()
+	db.stopBackgroundWriter()
+	if db.backup != nil {
+		db.backup.stop()
+	}
+
+	// Flush global pool (shared_buffers) and truncate the WAL: a clean
+	// shutdown leaves nothing to replay. The handle is released even if
+	// that fails; the WAL, synced on close, still has every commit.
 	if db.bp != nil {
-		if err := db.bp.FlushAll(); err != nil {
-			return err
-		}
+		errs = append(errs, db.bp.Checkpoint(db.syncDataFiles))
 	}
+	db.saveCacheManifest()
 
 	// Clear cached views.
 	db.muViews.Lock()
@@ -814,17 +1232,16 @@ func (db *Database) Close() error {
 	db.muViews.Unlock()
 
 	db.closed = true
-
-	if db.WAL != nil {
-		_ = db.WAL.Close()
-		db.WAL = nil
+	db.closeWAL()
+	errs = append(errs, db.releaseTempDir(), db.SM.Close(), db.lock.Unlock())
+	if db.memDir != "" {
+		errs = append(errs, os.RemoveAll(db.memDir))
 	}
-
-	return nil
+	return errors.Join(errs...)
 }
 
 func (db *Database) UpdateTableSchema(name string, newSchema record.Schema) error {
-	if err := db.ensureOpen(); err != nil {
+	if err := db.ensureWritable(); err != nil {
 		return err
 	}
 	meta, err := db.readTableMeta(name)
@@ -833,12 +1250,12 @@ func (db *Database) UpdateTableSchema(name string, newSchema record.Schema) erro
 	}
 
 	meta.Schema = newSchema
-	meta.UpdatedAt = time.Now()
+	meta.UpdatedAt = db.now()
 	return db.writeTableMeta(meta)
 }
 
 func (db *Database) SyncTableMetaPageCount(tbl *heap.Table) error {
-	if err := db.ensureOpen(); err != nil {
+	if err := db.ensureWritable(); err != nil {
 		return err
 	}
 	meta, err := db.readTableMeta(tbl.Name)
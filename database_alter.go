@@ -0,0 +1,148 @@
+package novasql
+
+import (
+	"errors"
+	"fmt"
+	"maps"
+	"os"
+	"slices"
+
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+var (
+	ErrColumnExists   = errors.New("novasql: column already exists")
+	ErrColumnNotFound = errors.New("novasql: column not found")
+	ErrColumnDefault  = errors.New("novasql: NOT NULL column needs a non-NULL DEFAULT")
+	ErrColumnInUse    = errors.New("novasql: column is used by a constraint")
+	ErrLastColumn     = errors.New("novasql: cannot drop the only column of a table")
+	ErrAlterBusy      = errors.New("novasql: ALTER TABLE cannot rewrite rows while transactions are open")
+)
+
+// alterSuffix marks the file sets a table is rewritten into by AddColumn and
+// DropColumn.
+const alterSuffix = ".alter"
+
+// AddColumn appends col to the schema of table. Existing rows get def, which
+// may only be nil for a nullable column. The rows are rewritten under the
+// new schema, so like Vacuum it refuses to run while a transaction is open,
+// and tables opened before are stale.
+func (db *Database) AddColumn(table string, col record.Column, def any) error {
+	meta, err := db.alterableTable(table)
+	if err != nil {
+		return err
+	}
+	if err := validateIdent(col.Name); err != nil {
+		return err
+	}
+	if db.hasColumn(meta, col.Name) {
+		return fmt.Errorf("%w: %s.%s", ErrColumnExists, table, col.Name)
+	}
+	if def == nil && !col.Nullable {
+		return fmt.Errorf("%w: %s.%s", ErrColumnDefault, table, col.Name)
+	}
+
+	schema := record.Schema{Cols: append(slices.Clone(meta.Schema.Cols), col)}
+	return db.alterRows(meta, schema, func(row []any) ([]any, error) { return append(row, def), nil })
+}
+
+// DropColumn removes column from table and rewrites its rows without it.
+// Plain indexes on the column, or on an expression of it, are dropped with
+// it; a column behind a PRIMARY KEY, UNIQUE or FOREIGN KEY constraint cannot
+// be dropped.
+func (db *Database) DropColumn(table, column string) error {
+	meta, err := db.alterableTable(table)
+	if err != nil {
+		return err
+	}
+	pos := slices.IndexFunc(meta.Schema.Cols, func(c record.Column) bool { return c.Name == column })
+	if pos < 0 {
+		return fmt.Errorf("%w: %s.%s", ErrColumnNotFound, table, column)
+	}
+	if len(meta.Schema.Cols) == 1 {
+		return ErrLastColumn
+	}
+	for _, im := range meta.Indexes {
+		if im.KeyColumn == column && im.Constraint != "" {
+			return fmt.Errorf("%w: %s.%s (%s)", ErrColumnInUse, table, column, im.Name)
+		}
+	}
+	for _, fk := range meta.ForeignKeys {
+		if fk.Column == column {
+			return fmt.Errorf("%w: %s.%s (%s)", ErrColumnInUse, table, column, fk.Name)
+		}
+	}
+	if meta.TTLColumn == column {
+		return fmt.Errorf("%w: %s.%s (ttl_column)", ErrColumnInUse, table, column)
+	}
+
+	for _, im := range meta.Indexes {
+		if !im.usesColumn(meta.Schema, column) {
+			continue
+		}
+		if err := db.DropIndex(table, im.Name); err != nil {
+			return err
+		}
+	}
+	if meta, err = db.readTableMeta(table); err != nil {
+		return err
+	}
+	if st := meta.Stats; st != nil {
+		cols := maps.Clone(st.Columns)
+		delete(cols, column)
+		meta.Stats = &TableStats{Rows: st.Rows, Columns: cols, AnalyzedAt: st.AnalyzedAt}
+	}
+
+	schema := record.Schema{Cols: slices.Delete(slices.Clone(meta.Schema.Cols), pos, pos+1)}
+	return db.alterRows(meta, schema, func(row []any) ([]any, error) {
+		return slices.Delete(row, pos, pos+1), nil
+	})
+}
+
+// alterableTable returns the metadata of table if its rows may be rewritten.
+func (db *Database) alterableTable(table string) (*TableMeta, error) {
+	if err := db.ensureWritable(); err != nil {
+		return nil, err
+	}
+	if err := validateIdent(table); err != nil {
+		return nil, err
+	}
+	if db.xact != nil && db.xact.Running() > 0 {
+		return nil, ErrAlterBusy
+	}
+	meta, err := db.readTableMeta(table)
+	if errors.Is(err, os.ErrNotExist) {
+		return nil, tableNotFound(table)
+	}
+	return meta, err
+}
+
+// alterRows rewrites every row version of the table of meta under schema,
+// passing it through f, then stores meta with schema and rebuilds its
+// indexes: the rows got new TIDs.
+func (db *Database) alterRows(meta *TableMeta, schema record.Schema, f func(row []any) ([]any, error)) error {
+	if err := db.recoverRewrite(meta.Name, alterSuffix); err != nil {
+		return err
+	}
+	tbl, err := db.OpenTable(meta.Name)
+	if err != nil {
+		return err
+	}
+	if err := db.rewriteTable(meta.Name, schema, alterSuffix, func(dst *heap.Table) error {
+		_, err := tbl.RewriteRowsInto(dst, f)
+		return err
+	}); err != nil {
+		return err
+	}
+
+	meta.Schema = schema
+	if err := db.writeTableMeta(meta); err != nil {
+		return err
+	}
+	if err := db.rebuildIndexes(meta); err != nil {
+		return err
+	}
+	_, err = db.OpenTable(meta.Name) // records the new page count
+	return err
+}
@@ -0,0 +1,89 @@
+package novasql
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func TestAlter_AddDropColumn(t *testing.T) {
+	dir := t.TempDir()
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i, name := range []string{"ann", "bob", "cat"} {
+		_, err := tbl.Insert([]any{int64(i + 1), name})
+		require.NoError(t, err)
+	}
+	require.NoError(t, db.AddPrimaryKey("users", "id"))
+	require.NoError(t, db.CreateIndex("users", "users_name_idx", "name"))
+	require.NoError(t, db.Analyze("users"))
+
+	age := record.Column{Name: "age", Type: record.ColInt64}
+	require.ErrorIs(t, db.AddColumn("users", age, nil), ErrColumnDefault)
+	require.ErrorIs(t, db.AddColumn("users", record.Column{Name: "name", Type: record.ColText}, "x"), ErrColumnExists)
+	require.ErrorIs(t, db.AddColumn("missing", age, int64(18)), ErrTableNotFound)
+
+	tx, err := db.BeginSnapshot()
+	require.NoError(t, err)
+	require.ErrorIs(t, db.AddColumn("users", age, int64(18)), ErrAlterBusy)
+	require.NoError(t, tx.Abort())
+
+	require.NoError(t, db.AddColumn("users", age, int64(18)))
+	require.NoError(t, db.AddColumn("users", record.Column{Name: "nick", Type: record.ColText, Nullable: true}, nil))
+	rows := func() [][]any {
+		t.Helper()
+		tbl, err := db.OpenTable("users")
+		require.NoError(t, err)
+		var out [][]any
+		require.NoError(t, tbl.Scan(func(_ heap.TID, row []any) error {
+			out = append(out, row)
+			return nil
+		}))
+		return out
+	}
+	require.Equal(t, [][]any{
+		{int64(1), "ann", int64(18), nil},
+		{int64(2), "bob", int64(18), nil},
+		{int64(3), "cat", int64(18), nil},
+	}, rows())
+
+	// Constraint columns stay; a plain index goes with its column.
+	require.ErrorIs(t, db.DropColumn("users", "id"), ErrColumnInUse)
+	require.ErrorIs(t, db.DropColumn("users", "missing"), ErrColumnNotFound)
+	require.NoError(t, db.DropColumn("users", "name"))
+	indexes, err := db.ListIndexes("users")
+	require.NoError(t, err)
+	require.Len(t, indexes, 1)
+	require.Equal(t, PrimaryKeyName("users"), indexes[0].Name)
+	st, err := db.TableStats("users")
+	require.NoError(t, err)
+	require.NotContains(t, st.Columns, "name")
+
+	// The schema change is kept.
+	require.NoError(t, db.Close())
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	schema, err := db.TableSchema("users")
+	require.NoError(t, err)
+	require.Equal(t, []record.Column{
+		{Name: "id", Type: record.ColInt64},
+		{Name: "age", Type: record.ColInt64},
+		{Name: "nick", Type: record.ColText, Nullable: true},
+	}, schema.Cols)
+	require.Equal(t, [][]any{
+		{int64(1), int64(18), nil},
+		{int64(2), int64(18), nil},
+		{int64(3), int64(18), nil},
+	}, rows())
+	require.Error(t, db.CreateUniqueIndex("users", "users_age_key", "age"))
+
+	require.NoError(t, db.DropColumn("users", "age"))
+	require.NoError(t, db.DropColumn("users", "nick"))
+	require.ErrorIs(t, db.DropColumn("users", "id"), ErrLastColumn)
+}
@@ -0,0 +1,200 @@
+package novasql
+
+import (
+	"errors"
+	"fmt"
+	"math"
+	"os"
+	"slices"
+	"strings"
+	"time"
+
+	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+// statsBuckets is the number of buckets of a column histogram.
+const statsBuckets = 32
+
+// TableStats are what Analyze collected about the rows of a table. They are
+// a snapshot: rows written since are not reflected until the next Analyze.
+type TableStats struct {
+	Rows       int64                  `json:"rows"`
+	Columns    map[string]ColumnStats `json:"columns"`
+	AnalyzedAt time.Time              `json:"analyzed_at"`
+}
+
+// ColumnStats describe the values of one column.
+type ColumnStats struct {
+	Nulls    int64 `json:"nulls"`
+	Distinct int64 `json:"distinct"` // distinct non-NULL values
+
+	// Bounds is an equi-depth histogram of the non-NULL values: the buckets
+	// between consecutive bounds hold about as many values each, Bounds[0]
+	// is the smallest value and the last bound the largest. Non-finite
+	// floats are left out.
+	Bounds []any `json:"bounds,omitempty"`
+}
+
+// Analyze collects the statistics of the named tables, or of every table of
+// the selected database when none is named, and stores them in the table
+// metadata, where the planner finds them. It reads every visible row.
+func (db *Database) Analyze(names ...string) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	if len(names) == 0 {
+		for _, meta := range db.catalogTables() {
+			names = append(names, meta.Name)
+		}
+	}
+	for _, name := range names {
+		if err := db.analyzeTable(name); err != nil {
+			return fmt.Errorf("analyze %s: %w", name, err)
+		}
+	}
+	return nil
+}
+
+// TableStats returns the statistics of table name, or nil if it was never
+// analyzed.
+func (db *Database) TableStats(name string) (*TableStats, error) {
+	if err := db.ensureOpen(); err != nil {
+		return nil, err
+	}
+	if err := validateIdent(name); err != nil {
+		return nil, err
+	}
+	if db.isTempTable(name) {
+		return nil, nil // never analyzed
+	}
+	meta, err := db.readTableMeta(name)
+	if errors.Is(err, os.ErrNotExist) {
+		return nil, tableNotFound(name)
+	}
+	if err != nil {
+		return nil, err
+	}
+	return meta.Stats, nil
+}
+
+func (db *Database) analyzeTable(name string) error {
+	if _, err := db.TableSchema(name); err != nil {
+		return err
+	}
+	if db.isTempTable(name) {
+		return nil // statistics are not kept for temporary tables
+	}
+	tbl, err := db.OpenTable(name)
+	if err != nil {
+		return err
+	}
+
+	st := &TableStats{Columns: make(map[string]ColumnStats, len(tbl.Schema.Cols))}
+	values := make([][]any, len(tbl.Schema.Cols))
+	it := tbl.Iter()
+	for {
+		_, row, ok, err := it.Next()
+		if err != nil {
+			return err
+		}
+		if !ok {
+			break
+		}
+		st.Rows++
+		for i, v := range row {
+			values[i] = append(values[i], v)
+		}
+	}
+	for i, c := range tbl.Schema.Cols {
+		cs, err := columnStats(values[i])
+		if err != nil {
+			return fmt.Errorf("column %s: %w", c.Name, err)
+		}
+		st.Columns[c.Name] = cs
+	}
+
+	meta, err := db.readTableMeta(name)
+	if err != nil {
+		return err
+	}
+	st.AnalyzedAt = db.now()
+	meta.Stats = st
+	return db.writeTableMeta(meta)
+}
+
+// columnStats counts the NULLs and distinct values of vals and builds their
+// histogram, in index key order.
+func columnStats(vals []any) (ColumnStats, error) {
+	type keyed struct {
+		key string
+		v   any
+	}
+	var cs ColumnStats
+	var sorted []keyed
+	for _, v := range vals {
+		if v == nil {
+			cs.Nulls++
+			continue
+		}
+		key, err := btree.EncodeIndexValue(v)
+		if err != nil {
+			return cs, err
+		}
+		sorted = append(sorted, keyed{key: string(key), v: v})
+	}
+	slices.SortFunc(sorted, func(a, b keyed) int { return strings.Compare(a.key, b.key) })
+
+	var bounded []any
+	for i, k := range sorted {
+		if i == 0 || k.key != sorted[i-1].key {
+			cs.Distinct++
+		}
+		if f, ok := k.v.(float64); !ok || (!math.IsInf(f, 0) && !math.IsNaN(f)) {
+			bounded = append(bounded, k.v)
+		}
+	}
+	if len(bounded) == 0 {
+		return cs, nil
+	}
+	n := min(statsBuckets, len(bounded)-1)
+	if n == 0 {
+		cs.Bounds = bounded
+		return cs, nil
+	}
+	for i := 0; i <= n; i++ {
+		cs.Bounds = append(cs.Bounds, bounded[i*(len(bounded)-1)/n])
+	}
+	return cs, nil
+}
+
+// fixStatsTypes turns the histogram bounds of INT columns back into int64
+// after a JSON round trip, which reads every number as a float64, and those
+// of dates, times and decimals, written as text, back into their values.
+func fixStatsTypes(st *TableStats, schema record.Schema) {
+	if st == nil {
+		return
+	}
+	for _, c := range schema.Cols {
+		cs, ok := st.Columns[c.Name]
+		if !ok {
+			continue
+		}
+		for i, b := range cs.Bounds {
+			switch c.Type {
+			case record.ColInt64:
+				if f, ok := b.(float64); ok {
+					cs.Bounds[i] = int64(f)
+				}
+			case record.ColDate, record.ColTime, record.ColTimestamp:
+				if v, err := record.TemporalValue(c.Type, b); err == nil {
+					cs.Bounds[i] = v
+				}
+			case record.ColDecimal:
+				if v, err := record.DecimalValue(c, b); err == nil {
+					cs.Bounds[i] = v
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,65 @@
+package novasql
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func TestAnalyze_CollectsStats(t *testing.T) {
+	dir := t.TempDir()
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+
+	schema := record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64},
+		{Name: "tag", Type: record.ColText, Nullable: true},
+	}}
+	tbl, err := db.CreateTable("items", schema)
+	require.NoError(t, err)
+	for i := range 1000 {
+		var tag any = []string{"a", "b", "c", "d"}[i%4]
+		if i%10 == 0 {
+			tag = nil
+		}
+		_, err := tbl.Insert([]any{int64(i), tag})
+		require.NoError(t, err)
+	}
+
+	st, err := db.TableStats("items")
+	require.NoError(t, err)
+	require.Nil(t, st)
+	_, err = db.TableStats("missing")
+	require.ErrorIs(t, err, ErrTableNotFound)
+	require.ErrorIs(t, db.Analyze("missing"), ErrTableNotFound)
+
+	require.NoError(t, db.Analyze())
+	check := func(st *TableStats) {
+		t.Helper()
+		require.Equal(t, int64(1000), st.Rows)
+		id := st.Columns["id"]
+		require.Equal(t, int64(0), id.Nulls)
+		require.Equal(t, int64(1000), id.Distinct)
+		require.Len(t, id.Bounds, statsBuckets+1)
+		require.Equal(t, int64(0), id.Bounds[0])
+		require.Equal(t, int64(999), id.Bounds[statsBuckets])
+		tag := st.Columns["tag"]
+		require.Equal(t, int64(100), tag.Nulls)
+		require.Equal(t, int64(4), tag.Distinct)
+		require.Equal(t, "a", tag.Bounds[0])
+		require.Equal(t, "d", tag.Bounds[len(tag.Bounds)-1])
+	}
+	st, err = db.TableStats("items")
+	require.NoError(t, err)
+	check(st)
+
+	// Statistics are kept in the table metadata.
+	require.NoError(t, db.Close())
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	st, err = db.TableStats("items")
+	require.NoError(t, err)
+	check(st)
+}
@@ -0,0 +1,119 @@
+package novasql
+
+import (
+	"errors"
+	"math"
+	"slices"
+
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+var (
+	ErrAutoIncrementColumn    = errors.New("novasql: AUTOINCREMENT column must be the INT primary key")
+	ErrAutoIncrementExists    = errors.New("novasql: table already has an AUTOINCREMENT column")
+	ErrAutoIncrementNone      = errors.New("novasql: table has no AUTOINCREMENT column")
+	ErrAutoIncrementExhausted = errors.New("novasql: AUTOINCREMENT counter exhausted")
+)
+
+// AutoIncrement is the counter of an AUTOINCREMENT column, kept in the table
+// metadata so it survives restarts. Values are never handed out twice, even
+// after the rows that had them are deleted or their inserts rolled back.
+type AutoIncrement struct {
+	Column string `json:"column"`
+	Last   int64  `json:"last"` // largest value handed out or inserted
+}
+
+// SetAutoIncrement makes column, the INT primary key of table, an
+// AUTOINCREMENT column; its counter starts at the largest value in the table.
+func (db *Database) SetAutoIncrement(table, column string) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	if err := validateIdent(table); err != nil {
+		return ErrIndexBadTable
+	}
+	db.muAutoInc.Lock()
+	defer db.muAutoInc.Unlock()
+
+	meta, err := db.readTableMeta(table)
+	if err != nil {
+		return err
+	}
+	if meta.AutoIncrement != nil {
+		return ErrAutoIncrementExists
+	}
+	pos := slices.IndexFunc(meta.Schema.Cols, func(c record.Column) bool { return c.Name == column })
+	if pos < 0 || meta.Schema.Cols[pos].Type != record.ColInt64 || !slices.ContainsFunc(meta.Indexes,
+		func(im IndexMeta) bool { return im.Constraint == ConstraintPrimaryKey && im.KeyColumn == column }) {
+		return ErrAutoIncrementColumn
+	}
+
+	ai := &AutoIncrement{Column: column}
+	tbl, err := db.OpenTable(table)
+	if err != nil {
+		return err
+	}
+	if err := forEachRow(tbl, func(row []any) error {
+		if v, ok := row[pos].(int64); ok {
+			ai.Last = max(ai.Last, v)
+		}
+		return nil
+	}); err != nil {
+		return err
+	}
+	meta.AutoIncrement = ai
+	return db.writeTableMeta(meta)
+}
+
+// NextAutoIncrement hands out the next value of the AUTOINCREMENT column of
+// table.
+func (db *Database) NextAutoIncrement(table string) (int64, error) {
+	var next int64
+	err := db.updateAutoIncrement(table, func(ai *AutoIncrement) (bool, error) {
+		if ai.Last == math.MaxInt64 {
+			return false, ErrAutoIncrementExhausted
+		}
+		ai.Last++
+		next = ai.Last
+		return true, nil
+	})
+	return next, err
+}
+
+// ObserveAutoIncrement records that v was inserted into the AUTOINCREMENT
+// column of table explicitly, so the counter never hands it out.
+func (db *Database) ObserveAutoIncrement(table string, v int64) error {
+	return db.updateAutoIncrement(table, func(ai *AutoIncrement) (bool, error) {
+		if v <= ai.Last {
+			return false, nil
+		}
+		ai.Last = v
+		return true, nil
+	})
+}
+
+// updateAutoIncrement applies f to the counter of table and stores it when f
+// reports a change.
+func (db *Database) updateAutoIncrement(table string, f func(ai *AutoIncrement) (bool, error)) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	if err := validateIdent(table); err != nil {
+		return ErrIndexBadTable
+	}
+	db.muAutoInc.Lock()
+	defer db.muAutoInc.Unlock()
+
+	meta, err := db.readTableMeta(table)
+	if err != nil {
+		return err
+	}
+	if meta.AutoIncrement == nil {
+		return ErrAutoIncrementNone
+	}
+	changed, err := f(meta.AutoIncrement)
+	if err != nil || !changed {
+		return err
+	}
+	return db.writeTableMeta(meta)
+}
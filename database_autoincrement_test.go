@@ -0,0 +1,41 @@
+package novasql
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestAutoIncrement_Counter(t *testing.T) {
+	dir := t.TempDir()
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+
+	users, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	_, err = users.Insert([]any{int64(41), "ann"})
+	require.NoError(t, err)
+
+	require.ErrorIs(t, db.SetAutoIncrement("users", "id"), ErrAutoIncrementColumn)
+	_, err = db.NextAutoIncrement("users")
+	require.ErrorIs(t, err, ErrAutoIncrementNone)
+	require.NoError(t, db.AddPrimaryKey("users", "id"))
+	require.ErrorIs(t, db.SetAutoIncrement("users", "name"), ErrAutoIncrementColumn)
+	require.NoError(t, db.SetAutoIncrement("users", "id"))
+	require.ErrorIs(t, db.SetAutoIncrement("users", "id"), ErrAutoIncrementExists)
+
+	// The counter starts past the existing rows and skips observed values.
+	next, err := db.NextAutoIncrement("users")
+	require.NoError(t, err)
+	require.Equal(t, int64(42), next)
+	require.NoError(t, db.ObserveAutoIncrement("users", 50))
+	require.NoError(t, db.ObserveAutoIncrement("users", 7))
+
+	require.NoError(t, db.Close())
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	next, err = db.NextAutoIncrement("users")
+	require.NoError(t, err)
+	require.Equal(t, int64(51), next)
+}
@@ -0,0 +1,331 @@
+package novasql
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sync"
+	"time"
+
+	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+var (
+	ErrBackupRunning = errors.New("novasql: a backup is already running")
+	ErrBackupTarget  = errors.New("novasql: backup target already holds a database")
+	ErrBackupAborted = errors.New("novasql: backup aborted")
+)
+
+// Backup is an online copy of the selected database, made while the handle
+// keeps serving reads and writes. Step copies the pages of the data files a
+// few at a time, straight from disk; the storage manager reports every page
+// written meanwhile, and pages already copied are queued to be copied
+// again. Finish flushes the buffer pool, copies whatever is still queued
+// and then the metadata, so the copy is consistent as of Finish.
+//
+// Pages are copied as stored: the copy of an encrypted database needs the
+// same key. Dropped tables waiting in the trash are not copied, nor is the
+// WAL: a read-only handle copies what is on disk. The copy of a read-write
+// handle records the LSN it is consistent with in backup_label, so Restore
+// can roll it forward with archived WAL.
+type Backup struct {
+	db      *Database
+	srcDir  string // DataDir being copied
+	dest    string // data directory of the copy
+	destSM  *storage.StorageManager
+	mu      sync.Mutex
+	files   map[string]*backupFile // by LocalFileSet.Base
+	order   []string
+	dirty   map[backupPage]struct{}
+	stopped bool
+}
+
+// backupFile is the copy progress of one file set. A fresh file is copied
+// from scratch on the next Step: it is new, or was removed or renamed.
+type backupFile struct {
+	next, count uint32
+	fresh       bool
+}
+
+// backupLabelFile is written next to the header of a finished copy.
+const backupLabelFile = "backup_label"
+
+type backupLabel struct {
+	DataDir string    `json:"data_dir"`
+	LSN     uint64    `json:"lsn"`
+	Time    time.Time `json:"time"`
+}
+
+type backupPage struct {
+	base   string
+	pageID uint32
+}
+
+// StartBackup starts copying the selected database to workDir dest: once
+// finished, Options().Open(dest) opens the copy as its default database.
+// Only one backup runs at a time.
+func (db *Database) StartBackup(dest string) (*Backup, error) {
+	if err := db.ensureOpen(); err != nil {
+		return nil, err
+	}
+	if db.backup != nil {
+		return nil, ErrBackupRunning
+	}
+	target := filepath.Join(filepath.Clean(dest), "default")
+	if entries, err := os.ReadDir(target); err == nil && len(entries) > 0 {
+		return nil, fmt.Errorf("%w: %s", ErrBackupTarget, target)
+	}
+	if err := os.MkdirAll(filepath.Join(target, "tables"), 0o755); err != nil {
+		return nil, err
+	}
+
+	b := &Backup{
+		db:     db,
+		srcDir: db.DataDir,
+		dest:   target,
+		destSM: storage.NewStorageManager(),
+		files:  make(map[string]*backupFile),
+		dirty:  make(map[backupPage]struct{}),
+	}
+	for _, meta := range db.catalogTables() {
+		for _, fs := range b.fileSets(meta) {
+			b.track(fs.Base)
+		}
+	}
+	db.backup = b
+	db.SM.SetWriteObserver(b.observe)
+	return b, nil
+}
+
+// BackupTo copies the selected database to workDir dest in one go.
+func (db *Database) BackupTo(dest string) error {
+	b, err := db.StartBackup(dest)
+	if err != nil {
+		return err
+	}
+	return b.Finish()
+}
+
+// Step copies up to n pages (n <= 0: all) and reports whether nothing is
+// left to copy for now. Later writes can queue more.
+func (b *Backup) Step(n int) (bool, error) {
+	if err := b.check(); err != nil {
+		return false, err
+	}
+	b.mu.Lock()
+	defer b.mu.Unlock()
+	return b.stepLocked(n)
+}
+
+// Finish completes the copy and stops the backup, successful or not.
+func (b *Backup) Finish() error {
+	if err := b.check(); err != nil {
+		return err
+	}
+	defer b.stop()
+	var lsn uint64
+	if !b.db.opts.readOnly {
+		var err error
+		if lsn, err = b.db.checkpoint(); err != nil {
+			return err
+		}
+	}
+
+	metas := b.db.catalogTables()
+	live := make(map[string]bool)
+	b.mu.Lock()
+	for _, meta := range metas {
+		for _, fs := range b.fileSets(meta) {
+			live[fs.Base] = true
+			if b.files[fs.Base] == nil {
+				b.track(fs.Base)
+			}
+		}
+	}
+	_, err := b.stepLocked(0)
+	b.mu.Unlock()
+	if err != nil {
+		return err
+	}
+	for base := range b.files {
+		if !live[base] {
+			if err := b.destSM.RemoveSegments(b.destFileSet(base)); err != nil {
+				return err
+			}
+		}
+	}
+
+	// Metadata: table and index metas, the header and the transaction log,
+	// which marks transactions still running as aborted in the copy.
+	files := []string{headerFile, "xact"}
+	for _, meta := range metas {
+		files = append(files, filepath.Join("tables", filepath.Base(b.db.tableMetaPath(meta.Name))))
+		for _, fs := range b.db.pagedFileSets(meta) {
+			files = append(files, filepath.Join("tables", filepath.Base(btree.MetaPath(fs))))
+		}
+	}
+	for _, name := range files {
+		data, err := os.ReadFile(filepath.Join(b.srcDir, name))
+		if errors.Is(err, os.ErrNotExist) {
+			continue
+		}
+		if err != nil {
+			return err
+		}
+		if err := writeFileAtomic(filepath.Join(b.dest, name), data, 0o644); err != nil {
+			return err
+		}
+	}
+	if err := b.writeLabel(lsn); err != nil {
+		return err
+	}
+	return storage.SyncDir(b.dest)
+}
+
+// writeLabel records where in the WAL the copy stands, for Restore: lsn, the
+// checkpoint Finish took. The copy holds every record up to it; pages logged
+// after it may or may not be in the copy, so Restore replays from there.
+func (b *Backup) writeLabel(lsn uint64) error {
+	if b.db.WAL == nil {
+		return nil
+	}
+	data, err := json.MarshalIndent(backupLabel{
+		DataDir: b.srcDir,
+		LSN:     lsn,
+		Time:    b.db.now(),
+	}, "", "  ")
+	if err != nil {
+		return err
+	}
+	return writeFileAtomic(filepath.Join(b.dest, backupLabelFile), data, 0o644)
+}
+
+// Abort stops the backup and removes the partial copy.
+func (b *Backup) Abort() error {
+	b.stop()
+	return os.RemoveAll(b.dest)
+}
+
+func (b *Backup) check() error {
+	b.mu.Lock()
+	stopped := b.stopped
+	b.mu.Unlock()
+	switch {
+	case stopped:
+		return fmt.Errorf("%w: already finished", ErrBackupAborted)
+	case b.db.closed:
+		return fmt.Errorf("%w: %w", ErrBackupAborted, ErrDatabaseClosed)
+	case b.db.DataDir != b.srcDir:
+		return fmt.Errorf("%w: another database was selected", ErrBackupAborted)
+	}
+	return nil
+}
+
+func (b *Backup) stop() {
+	b.mu.Lock()
+	defer b.mu.Unlock()
+	if b.stopped {
+		return
+	}
+	b.stopped = true
+	if b.db.backup == b {
+		b.db.backup = nil
+		b.db.SM.SetWriteObserver(nil)
+	}
+}
+
+// fileSets are the file sets of a table that hold pages: heap, indexes and
+// overflow.
+func (b *Backup) fileSets(meta *TableMeta) []storage.LocalFileSet {
+	return append(b.db.pagedFileSets(meta), b.db.overflowFileSet(meta.Name))
+}
+
+func (b *Backup) track(base string) {
+	if b.files[base] == nil {
+		b.order = append(b.order, base)
+	}
+	b.files[base] = &backupFile{fresh: true}
+	for p := range b.dirty {
+		if p.base == base {
+			delete(b.dirty, p)
+		}
+	}
+}
+
+// observe is the storage.WriteObserver of a running backup.
+func (b *Backup) observe(lfs storage.LocalFileSet, pageID int32) {
+	if lfs.Dir != filepath.Join(b.srcDir, "tables") {
+		return
+	}
+	b.mu.Lock()
+	defer b.mu.Unlock()
+	f := b.files[lfs.Base]
+	switch {
+	case pageID < 0 || f == nil:
+		b.track(lfs.Base)
+	case f.fresh || uint32(pageID) >= f.next && uint32(pageID) < f.count:
+		// Not copied yet: the copy will see this write.
+	default:
+		b.dirty[backupPage{base: lfs.Base, pageID: uint32(pageID)}] = struct{}{}
+	}
+}
+
+func (b *Backup) stepLocked(n int) (bool, error) {
+	buf := make([]byte, storage.PageSize)
+	copied := 0
+	budget := func() bool {
+		copied++
+		return n <= 0 || copied <= n
+	}
+	for _, base := range b.order {
+		f := b.files[base]
+		src, dst := b.srcFileSet(base), b.destFileSet(base)
+		if f.fresh {
+			count, err := b.db.SM.CountPages(src)
+			if err != nil {
+				return false, err
+			}
+			if err := b.destSM.RemoveSegments(dst); err != nil {
+				return false, err
+			}
+			*f = backupFile{count: count}
+		}
+		for f.next < f.count {
+			if !budget() {
+				return false, nil
+			}
+			if err := b.copyPage(src, dst, f.next, buf); err != nil {
+				return false, err
+			}
+			f.next++
+		}
+	}
+	for p := range b.dirty {
+		if !budget() {
+			return false, nil
+		}
+		if err := b.copyPage(b.srcFileSet(p.base), b.destFileSet(p.base), p.pageID, buf); err != nil {
+			return false, err
+		}
+		delete(b.dirty, p)
+	}
+	return true, nil
+}
+
+func (b *Backup) copyPage(src, dst storage.LocalFileSet, pageID uint32, buf []byte) error {
+	if err := b.db.SM.ReadRawPage(src, int32(pageID), buf); err != nil {
+		return err
+	}
+	return b.destSM.WritePage(dst, int32(pageID), buf)
+}
+
+func (b *Backup) srcFileSet(base string) storage.LocalFileSet {
+	return storage.LocalFileSet{Dir: filepath.Join(b.srcDir, "tables"), Base: base}
+}
+
+func (b *Backup) destFileSet(base string) storage.LocalFileSet {
+	return storage.LocalFileSet{Dir: filepath.Join(b.dest, "tables"), Base: base}
+}
@@ -0,0 +1,82 @@
+package novasql
+
+import (
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestBackup_WhileWriting(t *testing.T) {
+	dir := t.TempDir()
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	users, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 500 {
+		_, err := users.Insert([]any{int64(i), "user"})
+		require.NoError(t, err)
+	}
+	_, err = db.CreateTable("gone", testSchema())
+	require.NoError(t, err)
+	require.NoError(t, db.Checkpoint())
+
+	dest := filepath.Join(t.TempDir(), "copy")
+	b, err := db.StartBackup(dest)
+	require.NoError(t, err)
+	_, err = db.StartBackup(t.TempDir())
+	require.ErrorIs(t, err, ErrBackupRunning)
+
+	done, err := b.Step(2)
+	require.NoError(t, err)
+	require.False(t, done)
+
+	// Writes during the backup: rows on pages already copied and on new
+	// pages, a new table, a renamed and a dropped one.
+	for i := range 500 {
+		_, err := users.Insert([]any{int64(500 + i), "later"})
+		require.NoError(t, err)
+	}
+	require.NoError(t, db.Checkpoint())
+	orders, err := db.CreateTable("orders", testSchema())
+	require.NoError(t, err)
+	_, err = orders.Insert([]any{int64(1), "order"})
+	require.NoError(t, err)
+	done, err = b.Step(0)
+	require.NoError(t, err)
+	require.True(t, done)
+
+	require.NoError(t, db.RenameTable("orders", "purchases"))
+	require.NoError(t, db.DropTable("gone"))
+	_, err = users.Insert([]any{int64(1000), "last"})
+	require.NoError(t, err)
+	require.NoError(t, b.Finish())
+	_, err = b.Step(1)
+	require.ErrorIs(t, err, ErrBackupAborted)
+
+	// Later writes do not reach the copy.
+	_, err = users.Insert([]any{int64(1001), "after"})
+	require.NoError(t, err)
+
+	cp, err := Options().Create(CreateNever).Open(dest)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = cp.Close() })
+	tbl, err := cp.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 1001, countRows(t, tbl))
+	tbl, err = cp.OpenTable("purchases")
+	require.NoError(t, err)
+	require.Equal(t, 1, countRows(t, tbl))
+	_, err = cp.OpenTable("gone")
+	require.Error(t, err)
+	_, err = cp.OpenTable("orders")
+	require.Error(t, err)
+	bad, err := cp.VerifyIntegrity()
+	require.NoError(t, err)
+	require.Empty(t, bad)
+
+	_, err = db.StartBackup(dest)
+	require.ErrorIs(t, err, ErrBackupTarget)
+}
@@ -0,0 +1,209 @@
+package novasql
+
+import (
+	"bytes"
+	"errors"
+	"fmt"
+	"slices"
+
+	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/fulltext"
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/mvcc"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/expr"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+var ErrBulkValue = errors.New("novasql: invalid bulk insert value")
+
+// InsertRows inserts rows, a value per column each, into table as a bulk
+// load and returns how many it inserted. Values convert as those of an
+// INSERT do, and the rows are checked as ImportCSV checks its own: if one
+// breaks a NOT NULL, PRIMARY KEY, UNIQUE or FOREIGN KEY constraint, none of
+// them is inserted. NULL in an AUTOINCREMENT column gets the next value of
+// its counter.
+//
+// The rows go in as one transaction that fills the pages it writes before
+// logging them all as one WAL group, so a load costs a page image per page
+// rather than per row. The index entries of the rows are added in key
+// order, one pass per index, before the transaction commits; if that fails
+// it aborts.
+func (db *Database) InsertRows(table string, rows [][]any) (int64, error) {
+	if err := db.ensureWritable(); err != nil {
+		return 0, err
+	}
+	meta, err := db.csvTable(table)
+	if err != nil {
+		return 0, err
+	}
+	vals := make([][]any, len(rows))
+	for i, row := range rows {
+		if vals[i], err = bulkRow(meta.Schema, row); err != nil {
+			return 0, fmt.Errorf("%w: row %d: %v", ErrBulkValue, i+1, err)
+		}
+	}
+	imp, err := db.newCSVImport(meta)
+	if err != nil {
+		return 0, err
+	}
+	if err := imp.check(vals); err != nil {
+		return 0, err
+	}
+	tbl, err := db.OpenTable(table)
+	if err != nil {
+		return 0, err
+	}
+
+	tx, err := db.BeginSnapshot()
+	if err != nil {
+		return 0, err
+	}
+	tids, err := tbl.InsertBatchTx(tx, vals)
+	if err == nil {
+		err = db.indexRows(meta, tbl, tx, vals, tids)
+	}
+	if err != nil {
+		_ = tx.Abort()
+		return 0, err
+	}
+	if err := tx.Commit(); err != nil {
+		return 0, err
+	}
+	return int64(len(vals)), nil
+}
+
+// bulkRow is row converted to the column types of schema.
+func bulkRow(schema record.Schema, row []any) ([]any, error) {
+	if len(row) != len(schema.Cols) {
+		return nil, fmt.Errorf("%d values for %d columns", len(row), len(schema.Cols))
+	}
+	out := make([]any, len(row))
+	for i, v := range row {
+		if v == nil {
+			continue
+		}
+		c := schema.Cols[i]
+		var err error
+		if out[i], err = bulkValue(c, v); err != nil {
+			return nil, fmt.Errorf("column %s: %w", c.Name, err)
+		}
+	}
+	return out, nil
+}
+
+// bulkValue is v, not nil, as a value of column c: Go integers are INT and
+// FLOAT values, JSON text is normalized, and dates, times and decimals are
+// read as INSERT reads them. Other values are left for the row encoding to
+// check.
+func bulkValue(c record.Column, v any) (any, error) {
+	switch c.Type {
+	case record.ColInt64:
+		switch x := v.(type) {
+		case int:
+			return int64(x), nil
+		case int32:
+			return int64(x), nil
+		}
+	case record.ColFloat64:
+		switch x := v.(type) {
+		case int:
+			return float64(x), nil
+		case int64:
+			return float64(x), nil
+		}
+	case record.ColJSON:
+		if s, ok := v.(string); ok {
+			return expr.NormalizeJSON(s)
+		}
+	case record.ColDate, record.ColTime, record.ColTimestamp:
+		return record.TemporalValue(c.Type, v)
+	case record.ColDecimal:
+		return record.DecimalValue(c, v)
+	}
+	return v, nil
+}
+
+// indexRows adds the entries of rows, inserted into tbl by tx at tids, to
+// the indexes of meta. A legacy B-tree index, built from keys in order, is
+// rebuilt instead from the rows tx sees.
+func (db *Database) indexRows(meta *TableMeta, tbl *heap.Table, tx *mvcc.Tx, rows [][]any, tids []heap.TID) error {
+	for _, im := range meta.Indexes {
+		if !im.hasFiles() {
+			continue
+		}
+		base := im.FileBase
+		if base == "" {
+			base = db.fmtIndexBase(meta.Name, im.Name)
+		}
+		fs := storage.LocalFileSet{Dir: db.TableDir(), Base: base}
+		if im.Kind == IndexKindBTree {
+			if err := db.refillBTreeIndex(meta, im, fs, func(fn func(heap.TID, []any) error) error {
+				return tbl.ScanTx(tx, fn)
+			}); err != nil {
+				return err
+			}
+			continue
+		}
+
+		key, err := im.KeyFunc(meta.Schema)
+		if err != nil {
+			return fmt.Errorf("%s: %w", meta.Name, err)
+		}
+		var entries []fulltext.Entry
+		for i, row := range rows {
+			v, err := key(row)
+			if err != nil {
+				return err
+			}
+			if v == nil {
+				continue
+			}
+			if im.Kind == IndexKindFullText {
+				text, _ := v.(string)
+				terms, err := fulltext.Entries(text, tids[i])
+				if err != nil {
+					return err
+				}
+				entries = append(entries, terms...)
+				continue
+			}
+			entry, err := btree.IndexEntryKey(v, tids[i])
+			if err != nil {
+				return err
+			}
+			entries = append(entries, fulltext.Entry{Key: entry})
+		}
+		slices.SortFunc(entries, func(a, b fulltext.Entry) int { return bytes.Compare(a.Key, b.Key) })
+
+		tree, err := btree.OpenKVTree(db.SM, fs, db.viewFor(fs))
+		if err != nil {
+			return err
+		}
+		for _, entry := range entries {
+			if err = tree.Insert(entry.Key, entry.Value); err != nil {
+				break
+			}
+		}
+		if cerr := tree.Close(); err == nil {
+			err = cerr
+		}
+		if err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// refillBTreeIndex empties the legacy B-tree index im at fs and fills it
+// from the rows scan visits.
+func (db *Database) refillBTreeIndex(meta *TableMeta, im IndexMeta, fs storage.LocalFileSet, scan heapScan) error {
+	if err := db.flushAndDropFileSet(fs); err != nil {
+		return err
+	}
+	if err := btree.DropIndex(db.SM, fs); err != nil {
+		return err
+	}
+	pos := slices.IndexFunc(meta.Schema.Cols, func(c record.Column) bool { return c.Name == im.KeyColumn })
+	return db.fillBTreeIndex(scan, pos, fs)
+}
@@ -0,0 +1,143 @@
+package novasql
+
+import (
+	"fmt"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func TestInsertRows(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	_, err = db.CreateTable("items", record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64},
+		{Name: "name", Type: record.ColText},
+		{Name: "price", Type: record.ColFloat64, Nullable: true},
+	}})
+	require.NoError(t, err)
+	require.NoError(t, db.AddPrimaryKey("items", "id"))
+	require.NoError(t, db.SetAutoIncrement("items", "id"))
+	require.NoError(t, db.CreateIndex("items", "items_name", "name"))
+
+	const n = 5000
+	rows := make([][]any, n)
+	for i := range rows {
+		rows[i] = []any{i + 1, fmt.Sprintf("item-%d", n-i), i}
+	}
+	rows[n-1][0] = nil
+	got, err := db.InsertRows("items", rows)
+	require.NoError(t, err)
+	require.Equal(t, int64(n), got)
+
+	tbl, err := db.OpenTable("items")
+	require.NoError(t, err)
+	require.Equal(t, n, countRows(t, tbl))
+	id, err := db.NextAutoIncrement("items")
+	require.NoError(t, err)
+	require.Equal(t, int64(n+1), id)
+
+	tree, err := db.OpenKVTreeIndex("items", "items_name")
+	require.NoError(t, err)
+	entries := 0
+	for it := tree.Scan(nil, nil); it.Next(); {
+		entries++
+	}
+	require.NoError(t, tree.Close())
+	require.Equal(t, n, entries)
+
+	// A bad row anywhere rejects the whole batch.
+	for _, bad := range []struct {
+		row  []any
+		want error
+	}{
+		{[]any{int64(n + 2), "x"}, ErrBulkValue},
+		{[]any{int64(n + 2), "x", "not-a-price"}, record.ErrSchemaMismatchNotFloat64},
+		{[]any{int64(1), "dup", nil}, ErrConstraintViolation},
+		{[]any{int64(n + 2), nil, nil}, ErrConstraintViolation},
+	} {
+		_, err := db.InsertRows("items", [][]any{{int64(n + 3), "ok", 1.5}, bad.row})
+		require.ErrorIs(t, err, bad.want, "%v", bad.row)
+	}
+	require.Equal(t, n, countRows(t, tbl))
+
+	_, err = db.InsertRows("missing", rows)
+	require.ErrorIs(t, err, ErrTableNotFound)
+}
+
+func TestInsertRows_OneWALGroup(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	schema := record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64},
+		{Name: "name", Type: record.ColText},
+	}}
+	for _, name := range []string{"items", "keyed"} {
+		_, err = db.CreateTable(name, schema)
+		require.NoError(t, err)
+	}
+	tree, err := db.CreateBTreeIndex("keyed", "keyed_id", "id")
+	require.NoError(t, err)
+	require.NoError(t, tree.Close())
+
+	const n = 4000
+	rows := make([][]any, n)
+	for i := range rows {
+		rows[i] = []any{i, fmt.Sprintf("item-%d-padding-padding-padding", i)}
+	}
+	synced := db.WAL.SyncCount()
+	_, err = db.InsertRows("items", rows)
+	require.NoError(t, err)
+	tbl, err := db.OpenTable("items")
+	require.NoError(t, err)
+	require.Greater(t, tbl.PageCount, uint32(10))
+	require.LessOrEqual(t, db.WAL.SyncCount()-synced, uint64(2), "the heap pages are one WAL group")
+
+	// The legacy index is rebuilt in the transaction, with its rows.
+	_, err = db.InsertRows("keyed", rows)
+	require.NoError(t, err)
+	keyed, err := db.OpenTable("keyed")
+	require.NoError(t, err)
+	idx, err := db.OpenBTreeIndex("keyed", "keyed_id")
+	require.NoError(t, err)
+	tids, err := idx.SearchEqual(n - 1)
+	require.NoError(t, err)
+	require.Len(t, tids, 1)
+	got, err := keyed.Get(tids[0])
+	require.NoError(t, err)
+	require.Equal(t, int64(n-1), got[0])
+}
+
+func BenchmarkInsertRows(b *testing.B) {
+	const batch = 10000
+	rows := make([][]any, batch)
+	for i := range rows {
+		rows[i] = []any{int64(i), fmt.Sprintf("item-%d", i), float64(i) / 4}
+	}
+
+	for range b.N {
+		b.StopTimer()
+		db, err := Options().Open(b.TempDir())
+		require.NoError(b, err)
+		_, err = db.CreateTable("items", record.Schema{Cols: []record.Column{
+			{Name: "id", Type: record.ColInt64},
+			{Name: "name", Type: record.ColText},
+			{Name: "price", Type: record.ColFloat64},
+		}})
+		require.NoError(b, err)
+		b.StartTimer()
+
+		_, err = db.InsertRows("items", rows)
+		require.NoError(b, err)
+
+		b.StopTimer()
+		require.NoError(b, db.Close())
+		b.StartTimer()
+	}
+	b.ReportMetric(float64(b.N*batch)/b.Elapsed().Seconds(), "rows/s")
+}
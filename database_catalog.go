@@ -0,0 +1,181 @@
+package novasql
+
+import (
+	"errors"
+	"log/slog"
+	"os"
+	"slices"
+	"strings"
+	"sync"
+
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+var (
+	ErrTableNotFound = errors.New("novasql: table not found")
+	ErrTableExists   = errors.New("novasql: table already exists")
+)
+
+// catalog is the in-memory copy of the selected database's table metadata.
+// It is loaded when a database is opened or selected and kept current by
+// writeTableMeta/forgetTableMeta; the per-table JSON files stay the persisted
+// form, so the trash, rename and the admin tool keep working on them.
+type catalog struct {
+	mu     sync.RWMutex
+	tables map[string]*TableMeta // by table name; values are never handed out
+	views  map[string]*ViewMeta  // by view name, likewise
+
+	// version counts changes of what statements are planned against.
+	version uint64
+}
+
+// SchemaVersion changes whenever a table of the selected database is created,
+// dropped or altered, its indexes and constraints included, when a view is
+// created or dropped, and when another database is selected. Page counts, AUTOINCREMENT counters and statistics do
+// not change it. Caches of planned statements compare it to know when they
+// are stale.
+func (db *Database) SchemaVersion() uint64 {
+	db.cat.mu.RLock()
+	defer db.cat.mu.RUnlock()
+	return db.cat.version
+}
+
+// sameShape reports whether a and b plan alike: same columns, indexes,
+// foreign keys and AUTOINCREMENT column.
+func sameShape(a, b *TableMeta) bool {
+	sameIndex := func(x, y IndexMeta) bool {
+		return x.Name == y.Name && x.Kind == y.Kind && x.KeyColumn == y.KeyColumn &&
+			x.FileBase == y.FileBase && x.Constraint == y.Constraint && x.Expression == y.Expression
+	}
+	autoInc := func(m *TableMeta) string {
+		if m.AutoIncrement == nil {
+			return ""
+		}
+		return m.AutoIncrement.Column
+	}
+	return slices.Equal(a.Schema.Cols, b.Schema.Cols) && slices.EqualFunc(a.Indexes, b.Indexes, sameIndex) &&
+		slices.Equal(a.ForeignKeys, b.ForeignKeys) && autoInc(a) == autoInc(b)
+}
+
+// cloneTableMeta deep-copies the slices so callers can edit the result freely.
+func cloneTableMeta(m *TableMeta) *TableMeta {
+	cp := *m
+	cp.Schema.Cols = slices.Clone(m.Schema.Cols)
+	cp.Indexes = slices.Clone(m.Indexes)
+	cp.ForeignKeys = slices.Clone(m.ForeignKeys)
+	if m.AutoIncrement != nil {
+		ai := *m.AutoIncrement
+		cp.AutoIncrement = &ai
+	}
+	return &cp
+}
+
+// loadCatalog reads every table meta of the current database into memory.
+// Unreadable metas are skipped; the open check reports them.
+func (db *Database) loadCatalog() error {
+	names, err := db.tableMetaNames()
+	if err != nil {
+		return err
+	}
+	tables := make(map[string]*TableMeta, len(names))
+	for _, name := range names {
+		meta, err := db.readTableMetaFile(name)
+		if err != nil {
+			slog.Warn("catalog: failed to read table meta", "table", name, "err", err)
+			continue
+		}
+		tables[name] = meta
+	}
+	views, err := db.loadViews()
+	if err != nil {
+		return err
+	}
+
+	db.cat.mu.Lock()
+	db.cat.tables = tables
+	db.cat.views = views
+	db.cat.version++
+	db.cat.mu.Unlock()
+	return nil
+}
+
+// bumpSchemaVersion records a change the catalog does not hold, such as a
+// temporary table coming or going.
+func (db *Database) bumpSchemaVersion() {
+	db.cat.mu.Lock()
+	db.cat.version++
+	db.cat.mu.Unlock()
+}
+
+func (db *Database) resetCatalog() {
+	db.cat.mu.Lock()
+	defer db.cat.mu.Unlock()
+	db.cat.tables = nil
+	db.cat.views = nil
+	db.cat.version++
+}
+
+func (db *Database) cachedTableMeta(name string) (*TableMeta, bool) {
+	db.cat.mu.RLock()
+	defer db.cat.mu.RUnlock()
+	m, ok := db.cat.tables[name]
+	if !ok {
+		return nil, false
+	}
+	return cloneTableMeta(m), true
+}
+
+func (db *Database) rememberTableMeta(meta *TableMeta) {
+	db.cat.mu.Lock()
+	defer db.cat.mu.Unlock()
+	if db.cat.tables == nil {
+		db.cat.tables = make(map[string]*TableMeta)
+	}
+	if old, ok := db.cat.tables[meta.Name]; !ok || !sameShape(old, meta) {
+		db.cat.version++
+	}
+	db.cat.tables[meta.Name] = cloneTableMeta(meta)
+}
+
+// forgetTableMeta drops name from the catalog after its meta file went away.
+func (db *Database) forgetTableMeta(name string) {
+	db.cat.mu.Lock()
+	defer db.cat.mu.Unlock()
+	if _, ok := db.cat.tables[name]; ok {
+		delete(db.cat.tables, name)
+		db.cat.version++
+	}
+}
+
+// catalogTables returns copies of all cataloged tables, sorted by name.
+func (db *Database) catalogTables() []*TableMeta {
+	db.cat.mu.RLock()
+	defer db.cat.mu.RUnlock()
+	out := make([]*TableMeta, 0, len(db.cat.tables))
+	for _, m := range db.cat.tables {
+		out = append(out, cloneTableMeta(m))
+	}
+	slices.SortFunc(out, func(a, b *TableMeta) int { return strings.Compare(a.Name, b.Name) })
+	return out
+}
+
+// TableSchema returns the column definitions of table name.
+func (db *Database) TableSchema(name string) (record.Schema, error) {
+	if err := db.ensureOpen(); err != nil {
+		return record.Schema{}, err
+	}
+	if err := validateIdent(name); err != nil {
+		return record.Schema{}, err
+	}
+	if meta, ok := db.temp.tables[name]; ok {
+		return meta.Schema, nil
+	}
+	meta, err := db.readTableMeta(name)
+	if errors.Is(err, os.ErrNotExist) {
+		return record.Schema{}, tableNotFound(name)
+	}
+	if err != nil {
+		return record.Schema{}, err
+	}
+	return meta.Schema, nil
+}
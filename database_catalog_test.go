@@ -0,0 +1,117 @@
+package novasql
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func tableNames(t *testing.T, db *Database) []string {
+	t.Helper()
+
+	metas, err := db.ListTables()
+	require.NoError(t, err)
+	names := make([]string, 0, len(metas))
+	for _, m := range metas {
+		names = append(names, m.Name)
+	}
+	return names
+}
+
+func TestCatalog_ListAndSchema(t *testing.T) {
+	dir := t.TempDir()
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+
+	for _, name := range []string{"orders", "accounts", "users"} {
+		_, err := db.CreateTable(name, testSchema())
+		require.NoError(t, err)
+	}
+	require.Equal(t, []string{"accounts", "orders", "users"}, tableNames(t, db))
+
+	schema, err := db.TableSchema("orders")
+	require.NoError(t, err)
+	require.Equal(t, testSchema(), schema)
+	_, err = db.TableSchema("missing")
+	require.ErrorIs(t, err, ErrTableNotFound)
+
+	// Callers get copies: editing one does not touch the catalog.
+	schema.Cols[0].Name = "changed"
+	again, err := db.TableSchema("orders")
+	require.NoError(t, err)
+	require.Equal(t, "id", again.Cols[0].Name)
+
+	require.NoError(t, db.RenameTable("orders", "purchases"))
+	require.NoError(t, db.DropTablePurge("accounts"))
+	require.Equal(t, []string{"purchases", "users"}, tableNames(t, db))
+	_, err = db.TableSchema("orders")
+	require.ErrorIs(t, err, ErrTableNotFound)
+	require.NoError(t, db.Close())
+
+	// Reloaded from disk on open.
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	require.Equal(t, []string{"purchases", "users"}, tableNames(t, db))
+}
+
+func TestCatalog_FollowsSelectedDatabase(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	_, err = db.CreateTable("items", testSchema())
+	require.NoError(t, err)
+
+	require.NoError(t, db.CreateDatabase("other"))
+	_, err = db.SelectDatabase("other")
+	require.NoError(t, err)
+	require.Empty(t, tableNames(t, db))
+
+	flags := record.Schema{Cols: []record.Column{{Name: "on", Type: record.ColBool}}}
+	_, err = db.CreateTable("items", flags)
+	require.NoError(t, err)
+	schema, err := db.TableSchema("items")
+	require.NoError(t, err)
+	require.Equal(t, flags, schema)
+
+	_, err = db.SelectDatabase("default")
+	require.NoError(t, err)
+	schema, err = db.TableSchema("items")
+	require.NoError(t, err)
+	require.Equal(t, testSchema(), schema)
+}
+
+func TestCatalog_SchemaVersion(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	v := db.SchemaVersion()
+	changed := func() bool {
+		t.Helper()
+		old := v
+		v = db.SchemaVersion()
+		return v != old
+	}
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	require.True(t, changed())
+
+	// Writing rows and reopening the table only moves page counts.
+	for i := range 200 {
+		_, err := tbl.Insert([]any{int64(i), "name"})
+		require.NoError(t, err)
+	}
+	_, err = db.OpenTable("users")
+	require.NoError(t, err)
+	require.NoError(t, db.Analyze("users"))
+	require.False(t, changed())
+
+	require.NoError(t, db.CreateIndex("users", "users_id", "id"))
+	require.True(t, changed())
+	require.NoError(t, db.DropTable("users"))
+	require.True(t, changed())
+}
@@ -0,0 +1,251 @@
+package novasql
+
+import (
+	"errors"
+	"path/filepath"
+	"slices"
+	"sync"
+	"sync/atomic"
+	"time"
+
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/mvcc"
+)
+
+// Change feed
+//
+// Subscribe streams the rows written to tables of the selected database as
+// they commit: rows written without a transaction at once, those of a
+// snapshot transaction (BeginSnapshot) together when it commits, and none
+// of those it aborts. Every handle of the process that writes the database
+// feeds the same subscriptions, so do the sessions of a server. Rows moved
+// rather than written (VACUUM, ALTER TABLE rewrites, restores) and rows a
+// replica applies from its primary are not changes.
+
+var ErrSubscriptionLagged = errors.New("novasql: subscription fell behind the change feed")
+
+// subscriptionBuffer is how many events a subscription holds before it is
+// dropped as lagging.
+const subscriptionBuffer = 4096
+
+// ChangeKind is what a change did to a row.
+type ChangeKind uint8
+
+const (
+	ChangeInsert ChangeKind = ChangeKind(heap.ChangeInsert)
+	ChangeUpdate ChangeKind = ChangeKind(heap.ChangeUpdate)
+	ChangeDelete ChangeKind = ChangeKind(heap.ChangeDelete)
+)
+
+func (k ChangeKind) String() string {
+	switch k {
+	case ChangeInsert:
+		return "insert"
+	case ChangeUpdate:
+		return "update"
+	case ChangeDelete:
+		return "delete"
+	}
+	return "unknown"
+}
+
+// ChangeEvent is one row a committed write changed.
+type ChangeEvent struct {
+	Table string
+	Kind  ChangeKind
+	Old   []any    // the row before: updates and deletes
+	New   []any    // the row after: inserts and updates
+	TID   heap.TID // of the new row version, or of the deleted one
+	XID   uint64   // snapshot transaction of the write, 0 without one
+	At    time.Time
+}
+
+// Subscription receives the events of Subscribe on C, in commit order,
+// until Close. A subscriber that lets subscriptionBuffer events pile up is
+// dropped: C is closed and Err reports ErrSubscriptionLagged.
+type Subscription struct {
+	C <-chan ChangeEvent
+
+	ch     chan ChangeEvent
+	feed   *changeFeed
+	tables map[string]bool // nil = every table
+	err    error
+	done   bool
+}
+
+// Subscribe streams the changes committed to tables from now on, to every
+// table when none are given. The tables need not exist yet. Closing the
+// handle closes its subscriptions.
+func (db *Database) Subscribe(tables ...string) (*Subscription, error) {
+	if err := db.ensureOpen(); err != nil {
+		return nil, err
+	}
+	var names map[string]bool
+	if len(tables) > 0 {
+		names = make(map[string]bool, len(tables))
+		for _, name := range tables {
+			if err := validateIdent(name); err != nil {
+				return nil, err
+			}
+			names[name] = true
+		}
+	}
+	ch := make(chan ChangeEvent, subscriptionBuffer)
+	s := &Subscription{C: ch, ch: ch, feed: db.feed, tables: names}
+	db.feed.add(s)
+	db.changes.mu.Lock()
+	db.changes.subs = append(db.changes.subs, s)
+	db.changes.mu.Unlock()
+	return s, nil
+}
+
+// Close stops the subscription and closes C. Closing again does nothing.
+func (s *Subscription) Close() error {
+	s.feed.remove(s, nil)
+	return nil
+}
+
+// Err reports why C was closed: ErrSubscriptionLagged, or nil after Close.
+func (s *Subscription) Err() error {
+	s.feed.mu.Lock()
+	defer s.feed.mu.Unlock()
+	return s.err
+}
+
+// changeFeed is the subscriptions to one database directory.
+type changeFeed struct {
+	mu   sync.Mutex
+	subs []*Subscription
+	n    atomic.Int32 // len(subs), read by writers without the lock
+}
+
+// changeFeeds holds the feed of every database directory of the process.
+var changeFeeds = struct {
+	sync.Mutex
+	m map[string]*changeFeed
+}{m: make(map[string]*changeFeed)}
+
+// changeFeedOf returns the feed of the database in dir.
+func changeFeedOf(dir string) *changeFeed {
+	if abs, err := filepath.Abs(dir); err == nil {
+		dir = abs
+	}
+	changeFeeds.Lock()
+	defer changeFeeds.Unlock()
+	f := changeFeeds.m[dir]
+	if f == nil {
+		f = &changeFeed{}
+		changeFeeds.m[dir] = f
+	}
+	return f
+}
+
+func (f *changeFeed) add(s *Subscription) {
+	f.mu.Lock()
+	defer f.mu.Unlock()
+	f.subs = append(f.subs, s)
+	f.n.Store(int32(len(f.subs)))
+}
+
+func (f *changeFeed) remove(s *Subscription, err error) {
+	f.mu.Lock()
+	defer f.mu.Unlock()
+	f.removeLocked(s, err)
+}
+
+func (f *changeFeed) removeLocked(s *Subscription, err error) {
+	if s.done {
+		return
+	}
+	s.done, s.err = true, err
+	close(s.ch)
+	f.subs = slices.DeleteFunc(f.subs, func(o *Subscription) bool { return o == s })
+	f.n.Store(int32(len(f.subs)))
+}
+
+// publish hands the events of one commit to the subscriptions.
+func (f *changeFeed) publish(events []ChangeEvent) {
+	f.mu.Lock()
+	defer f.mu.Unlock()
+	for _, s := range slices.Clone(f.subs) {
+	send:
+		for _, ev := range events {
+			if s.tables != nil && !s.tables[ev.Table] {
+				continue
+			}
+			select {
+			case s.ch <- ev:
+			default:
+				f.removeLocked(s, ErrSubscriptionLagged)
+				break send
+			}
+		}
+	}
+}
+
+// changeLog is the change feed side of a handle: the subscriptions it made
+// and the changes of its snapshot transactions, until they end.
+type changeLog struct {
+	mu      sync.Mutex
+	subs    []*Subscription
+	pending map[mvcc.XID][]ChangeEvent
+}
+
+// changeHook is the heap.ChangeHook of the tables of db.
+type changeHook struct {
+	db   *Database
+	feed *changeFeed
+}
+
+func (db *Database) changeHook() heap.ChangeHook {
+	return &changeHook{db: db, feed: db.feed}
+}
+
+func (h *changeHook) Watching() bool {
+	return h.feed.n.Load() > 0
+}
+
+func (h *changeHook) RowChanged(table string, c heap.Change) {
+	ev := ChangeEvent{Table: table, Kind: ChangeKind(c.Kind), Old: c.Old, New: c.New, TID: c.TID, XID: uint64(c.XID)}
+	if c.XID == 0 {
+		ev.At = h.db.now()
+		h.feed.publish([]ChangeEvent{ev})
+		return
+	}
+	cl := &h.db.changes
+	cl.mu.Lock()
+	defer cl.mu.Unlock()
+	if cl.pending == nil {
+		cl.pending = make(map[mvcc.XID][]ChangeEvent)
+	}
+	cl.pending[c.XID] = append(cl.pending[c.XID], ev)
+}
+
+// finishChanges publishes the changes of xid when it committed, and forgets
+// them either way (see mvcc.Manager.SetFinishHook).
+func (db *Database) finishChanges(xid mvcc.XID, committed bool) {
+	cl := &db.changes
+	cl.mu.Lock()
+	events := cl.pending[xid]
+	delete(cl.pending, xid)
+	cl.mu.Unlock()
+	if !committed || len(events) == 0 {
+		return
+	}
+	at := db.now()
+	for i := range events {
+		events[i].At = at
+	}
+	db.feed.publish(events)
+}
+
+// closeSubscriptions closes the subscriptions made through db.
+func (db *Database) closeSubscriptions() {
+	db.changes.mu.Lock()
+	subs := db.changes.subs
+	db.changes.subs = nil
+	db.changes.mu.Unlock()
+	for _, s := range subs {
+		_ = s.Close()
+	}
+}
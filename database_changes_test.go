@@ -0,0 +1,109 @@
+package novasql
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+// nextChange returns the next event of s, failing when none is ready.
+func nextChange(t *testing.T, s *Subscription) ChangeEvent {
+	t.Helper()
+
+	select {
+	case ev, ok := <-s.C:
+		require.True(t, ok, "subscription closed: %v", s.Err())
+		return ev
+	default:
+		require.FailNow(t, "no change event")
+		return ChangeEvent{}
+	}
+}
+
+func TestSubscribe(t *testing.T) {
+	dir := t.TempDir()
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	users, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	orders, err := db.CreateTable("orders", testSchema())
+	require.NoError(t, err)
+
+	sub, err := db.Subscribe("users")
+	require.NoError(t, err)
+	all, err := db.Subscribe()
+	require.NoError(t, err)
+
+	id, err := users.Insert([]any{int64(1), "ann"})
+	require.NoError(t, err)
+	require.NoError(t, users.Update(id, []any{int64(1), "anna"}))
+	_, err = orders.Insert([]any{int64(7), "book"})
+	require.NoError(t, err)
+
+	ev := nextChange(t, sub)
+	require.Equal(t, "users", ev.Table)
+	require.Equal(t, ChangeInsert, ev.Kind)
+	require.Equal(t, []any{int64(1), "ann"}, ev.New)
+	require.False(t, ev.At.IsZero())
+	ev = nextChange(t, sub)
+	require.Equal(t, ChangeUpdate, ev.Kind)
+	require.Equal(t, []any{int64(1), "ann"}, ev.Old)
+	require.Equal(t, []any{int64(1), "anna"}, ev.New)
+	require.Empty(t, sub.C, "orders is not subscribed to")
+	require.Len(t, all.C, 3)
+	for range 3 {
+		nextChange(t, all)
+	}
+
+	// Transactions deliver their changes when they commit, none if they abort.
+	tx, err := db.BeginSnapshot()
+	require.NoError(t, err)
+	require.NoError(t, users.DeleteTx(tx, id))
+	require.Empty(t, sub.C)
+	require.NoError(t, tx.Commit())
+	ev = nextChange(t, sub)
+	require.Equal(t, ChangeDelete, ev.Kind)
+	require.Equal(t, []any{int64(1), "anna"}, ev.Old)
+	require.Equal(t, uint64(tx.XID()), ev.XID)
+
+	tx, err = db.BeginSnapshot()
+	require.NoError(t, err)
+	_, err = users.InsertTx(tx, []any{int64(2), "bob"})
+	require.NoError(t, err)
+	require.NoError(t, tx.Abort())
+	require.Empty(t, sub.C)
+
+	// Other handles of the database feed the same subscriptions.
+	require.NoError(t, db.Checkpoint())
+	other, err := Options().Open(dir)
+	require.NoError(t, err)
+	ou, err := other.OpenTable("users")
+	require.NoError(t, err)
+	_, err = ou.Insert([]any{int64(3), "cy"})
+	require.NoError(t, err)
+	require.NoError(t, other.Close())
+	require.Equal(t, []any{int64(3), "cy"}, nextChange(t, sub).New)
+
+	// A subscriber that falls behind is dropped.
+	rows := make([][]any, subscriptionBuffer+1)
+	for i := range rows {
+		rows[i] = []any{int64(100 + i), "bulk"}
+	}
+	_, err = users.InsertBatch(rows)
+	require.NoError(t, err)
+	n := 0
+	for range sub.C {
+		n++
+	}
+	require.Equal(t, subscriptionBuffer, n)
+	require.ErrorIs(t, sub.Err(), ErrSubscriptionLagged)
+
+	// Closing the handle closes its subscriptions.
+	late, err := db.Subscribe("orders")
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+	_, ok := <-late.C
+	require.False(t, ok)
+	require.NoError(t, late.Err())
+}
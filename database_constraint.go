@@ -0,0 +1,127 @@
+package novasql
+
+import (
+	"bytes"
+	"errors"
+	"fmt"
+	"slices"
+
+	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+var (
+	ErrConstraintViolation = errors.New("novasql: constraint violation")
+	ErrPrimaryKeyExists    = errors.New("novasql: table already has a primary key")
+	ErrPrimaryKeyNullable  = errors.New("novasql: primary key column must be NOT NULL")
+)
+
+// ConstraintKind is the kind of a table constraint.
+type ConstraintKind string
+
+const (
+	ConstraintPrimaryKey ConstraintKind = "PRIMARY KEY"
+	ConstraintUnique     ConstraintKind = "UNIQUE"
+	ConstraintNotNull    ConstraintKind = "NOT NULL"
+	ConstraintForeignKey ConstraintKind = "FOREIGN KEY"
+)
+
+// ConstraintError reports a row change that would break a constraint of
+// Table. Constraint is the name of the index behind a PRIMARY KEY or UNIQUE
+// constraint, of the ForeignKey, or "<table>_<column>_not_null" for NOT
+// NULL. It matches ErrConstraintViolation.
+type ConstraintError struct {
+	Kind       ConstraintKind
+	Constraint string
+	Table      string
+	Column     string
+}
+
+func (e *ConstraintError) Error() string {
+	switch e.Kind {
+	case ConstraintNotNull:
+		return fmt.Sprintf("novasql: NULL in %s.%s violates NOT NULL constraint %q", e.Table, e.Column, e.Constraint)
+	case ConstraintForeignKey:
+		return fmt.Sprintf("novasql: change violates FOREIGN KEY constraint %q on %s.%s",
+			e.Constraint, e.Table, e.Column)
+	}
+	return fmt.Sprintf("novasql: duplicate value in %s.%s violates %s constraint %q",
+		e.Table, e.Column, e.Kind, e.Constraint)
+}
+
+func (e *ConstraintError) Unwrap() error { return ErrConstraintViolation }
+
+// NotNullError is the ConstraintError for a NULL in column of table.
+func NotNullError(table, column string) *ConstraintError {
+	return &ConstraintError{
+		Kind:       ConstraintNotNull,
+		Constraint: table + "_" + column + "_not_null",
+		Table:      table,
+		Column:     column,
+	}
+}
+
+// PrimaryKeyName is the name of the index behind the PRIMARY KEY of table.
+func PrimaryKeyName(table string) string { return table + "_pkey" }
+
+// CreateUniqueIndex is CreateIndex for an index that also enforces a UNIQUE
+// constraint named indexName: it fails with a ConstraintError when rows
+// already share a value, and the SQL layer rejects rows that would.
+func (db *Database) CreateUniqueIndex(table, indexName, column string) error {
+	return db.createKVIndex(table, indexName, column, ConstraintUnique)
+}
+
+// AddPrimaryKey makes column, which must be NOT NULL, the primary key of
+// table: a unique index named PrimaryKeyName(table).
+func (db *Database) AddPrimaryKey(table, column string) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	if err := validateIdent(table); err != nil {
+		return ErrIndexBadTable
+	}
+	meta, err := db.readTableMeta(table)
+	if err != nil {
+		return err
+	}
+	if slices.ContainsFunc(meta.Indexes, func(im IndexMeta) bool { return im.Constraint == ConstraintPrimaryKey }) {
+		return ErrPrimaryKeyExists
+	}
+	pos := slices.IndexFunc(meta.Schema.Cols, func(c record.Column) bool { return c.Name == column })
+	if pos < 0 {
+		return ErrIndexBadColumn
+	}
+	if meta.Schema.Cols[pos].Nullable {
+		return ErrPrimaryKeyNullable
+	}
+	return db.createKVIndex(table, PrimaryKeyName(table), column, ConstraintPrimaryKey)
+}
+
+// checkUniqueIndex fails with ErrConstraintViolation when two entries of the
+// KVTree index at fs have the same value.
+func (db *Database) checkUniqueIndex(fs storage.LocalFileSet) error {
+	tree, err := btree.OpenKVTree(db.SM, fs, db.viewFor(fs))
+	if err != nil {
+		return err
+	}
+	err = func() error {
+		var prev []byte
+		it := tree.Scan(nil, nil)
+		for it.Next() {
+			v, err := btree.IndexEntryValue(it.Key())
+			if err != nil {
+				return err
+			}
+			if prev != nil && bytes.Equal(prev, v) {
+				return ErrConstraintViolation
+			}
+			prev = append(prev[:0], v...)
+		}
+		return it.Err()
+	}()
+	if cerr := tree.Close(); err == nil {
+		err = cerr
+	}
+	return err
+}
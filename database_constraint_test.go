@@ -0,0 +1,52 @@
+package novasql
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func TestConstraints_UniqueIndexAndPrimaryKey(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	users, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i, name := range []string{"ann", "bob", "ann"} {
+		_, err := users.Insert([]any{int64(i), name})
+		require.NoError(t, err)
+	}
+
+	// Existing duplicates keep a unique index from being created.
+	err = db.CreateUniqueIndex("users", "users_name_key", "name")
+	require.ErrorIs(t, err, ErrConstraintViolation)
+	var ce *ConstraintError
+	require.ErrorAs(t, err, &ce)
+	require.Equal(t, ConstraintError{
+		Kind:       ConstraintUnique,
+		Constraint: "users_name_key",
+		Table:      "users",
+		Column:     "name",
+	}, *ce)
+	_, err = db.OpenKVTreeIndex("users", "users_name_key")
+	require.Error(t, err)
+
+	require.NoError(t, db.AddPrimaryKey("users", "id"))
+	require.ErrorIs(t, db.AddPrimaryKey("users", "name"), ErrPrimaryKeyExists)
+	meta, err := db.readTableMeta("users")
+	require.NoError(t, err)
+	require.Len(t, meta.Indexes, 1)
+	require.Equal(t, PrimaryKeyName("users"), meta.Indexes[0].Name)
+	require.Equal(t, ConstraintPrimaryKey, meta.Indexes[0].Constraint)
+
+	schema := record.Schema{Cols: []record.Column{{Name: "tag", Type: record.ColText, Nullable: true}}}
+	_, err = db.CreateTable("tags", schema)
+	require.NoError(t, err)
+	require.ErrorIs(t, db.AddPrimaryKey("tags", "tag"), ErrPrimaryKeyNullable)
+	require.ErrorIs(t, db.AddPrimaryKey("tags", "missing"), ErrIndexBadColumn)
+
+	require.ErrorIs(t, NotNullError("users", "name"), ErrConstraintViolation)
+	require.Equal(t, "users_name_not_null", NotNullError("users", "name").Constraint)
+}
@@ -0,0 +1,389 @@
+package novasql
+
+import (
+	"encoding/csv"
+	"encoding/hex"
+	"errors"
+	"fmt"
+	"io"
+	"math"
+	"os"
+	"slices"
+	"strconv"
+	"strings"
+
+	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/expr"
+)
+
+var ErrCSVValue = errors.New("novasql: invalid CSV value")
+
+// csvBatchRows is how many rows ImportCSV reads, checks and inserts at once.
+const csvBatchRows = 1024
+
+// CSVOptions describe the CSV files of ImportCSV and ExportCSV: one line per
+// row, the fields in column order.
+type CSVOptions struct {
+	Header    bool   // the first line holds the column names
+	Delimiter rune   // ',' when zero
+	Null      string // the field of a NULL; "" also reads an empty TEXT as NULL
+}
+
+// ImportCSV inserts the rows of r into table and returns how many it read.
+// The rows are inserted in a single transaction, in batches: if a value does
+// not parse or a row breaks a NOT NULL, PRIMARY KEY, UNIQUE or FOREIGN KEY
+// constraint, none of them is. NULL in an AUTOINCREMENT column gets the next
+// value of its counter. The indexes of the table are rebuilt once the rows
+// are in.
+func (db *Database) ImportCSV(table string, r io.Reader, opts CSVOptions) (int64, error) {
+	if err := db.ensureWritable(); err != nil {
+		return 0, err
+	}
+	meta, err := db.csvTable(table)
+	if err != nil {
+		return 0, err
+	}
+	imp, err := db.newCSVImport(meta)
+	if err != nil {
+		return 0, err
+	}
+	tbl, err := db.OpenTable(table)
+	if err != nil {
+		return 0, err
+	}
+
+	cr := csv.NewReader(r)
+	cr.Comma = opts.delimiter()
+	cr.FieldsPerRecord = len(meta.Schema.Cols)
+	if opts.Header {
+		if _, err := cr.Read(); err != nil && !errors.Is(err, io.EOF) {
+			return 0, err
+		}
+	}
+
+	tx, err := db.BeginSnapshot()
+	if err != nil {
+		return 0, err
+	}
+	var n int64
+	err = func() error {
+		for {
+			rows, err := imp.readBatch(cr, opts)
+			if err != nil {
+				return err
+			}
+			if err := imp.check(rows); err != nil {
+				return err
+			}
+			if _, err := tbl.InsertBatchTx(tx, rows); err != nil {
+				return err
+			}
+			n += int64(len(rows))
+			if len(rows) < csvBatchRows {
+				return nil
+			}
+		}
+	}()
+	if err != nil {
+		_ = tx.Abort()
+		return 0, err
+	}
+	if err := tx.Commit(); err != nil {
+		return 0, err
+	}
+	return n, db.rebuildIndexes(meta)
+}
+
+// ExportCSV writes the visible rows of table to w and returns how many it
+// wrote.
+func (db *Database) ExportCSV(table string, w io.Writer, opts CSVOptions) (int64, error) {
+	if err := db.ensureOpen(); err != nil {
+		return 0, err
+	}
+	meta, err := db.csvTable(table)
+	if err != nil {
+		return 0, err
+	}
+	tbl, err := db.OpenTable(table)
+	if err != nil {
+		return 0, err
+	}
+
+	cw := csv.NewWriter(w)
+	cw.Comma = opts.delimiter()
+	fields := make([]string, len(meta.Schema.Cols))
+	if opts.Header {
+		for i, c := range meta.Schema.Cols {
+			fields[i] = c.Name
+		}
+		if err := cw.Write(fields); err != nil {
+			return 0, err
+		}
+	}
+	var n int64
+	if err := forEachRow(tbl, func(row []any) error {
+		for i, v := range row {
+			fields[i] = formatCSVValue(v, opts)
+		}
+		n++
+		return cw.Write(fields)
+	}); err != nil {
+		return n, err
+	}
+	cw.Flush()
+	return n, cw.Error()
+}
+
+func (o CSVOptions) delimiter() rune {
+	if o.Delimiter == 0 {
+		return ','
+	}
+	return o.Delimiter
+}
+
+func (db *Database) csvTable(table string) (*TableMeta, error) {
+	if err := validateIdent(table); err != nil {
+		return nil, err
+	}
+	meta, err := db.readTableMeta(table)
+	if errors.Is(err, os.ErrNotExist) {
+		return nil, tableNotFound(table)
+	}
+	return meta, err
+}
+
+// csvImport checks the rows of an import against the constraints of the
+// table, the rows imported before them included.
+type csvImport struct {
+	db      *Database
+	meta    *TableMeta
+	autoInc int // position of the AUTOINCREMENT column, or -1
+
+	unique  []csvKeys // values of the PRIMARY KEY and UNIQUE columns
+	foreign []csvKeys // values of the columns the foreign keys reference
+}
+
+// csvKeys are the index encodings of the values of a column.
+type csvKeys struct {
+	pos  int // checked column of the imported rows
+	keys map[string]bool
+	err  func() *ConstraintError
+
+	// self is, for a foreign key to its own table, the referenced column,
+	// whose imported values count too (-1 otherwise).
+	self int
+}
+
+func (db *Database) newCSVImport(meta *TableMeta) (*csvImport, error) {
+	imp := &csvImport{db: db, meta: meta, autoInc: -1}
+	cols := meta.Schema.Cols
+	colPos := func(name string) int {
+		return slices.IndexFunc(cols, func(c record.Column) bool { return c.Name == name })
+	}
+	if ai := meta.AutoIncrement; ai != nil {
+		imp.autoInc = colPos(ai.Column)
+	}
+	for _, im := range meta.Indexes {
+		if im.Constraint == "" {
+			continue
+		}
+		ck := csvKeys{pos: colPos(im.KeyColumn), keys: make(map[string]bool), self: -1, err: func() *ConstraintError {
+			return &ConstraintError{Kind: im.Constraint, Constraint: im.Name, Table: meta.Name, Column: im.KeyColumn}
+		}}
+		if err := db.addKeys(meta.Name, ck.pos, ck.keys); err != nil {
+			return nil, err
+		}
+		imp.unique = append(imp.unique, ck)
+	}
+	for _, fk := range meta.ForeignKeys {
+		ck := csvKeys{pos: colPos(fk.Column), keys: make(map[string]bool), self: -1, err: func() *ConstraintError {
+			return &ConstraintError{Kind: ConstraintForeignKey, Constraint: fk.Name, Table: meta.Name, Column: fk.Column}
+		}}
+		refPos := colPos(fk.RefColumn)
+		if fk.RefTable != meta.Name {
+			ref, err := db.readTableMeta(fk.RefTable)
+			if err != nil {
+				return nil, err
+			}
+			refPos = slices.IndexFunc(ref.Schema.Cols, func(c record.Column) bool { return c.Name == fk.RefColumn })
+		} else {
+			ck.self = refPos
+		}
+		if err := db.addKeys(fk.RefTable, refPos, ck.keys); err != nil {
+			return nil, err
+		}
+		imp.foreign = append(imp.foreign, ck)
+	}
+	return imp, nil
+}
+
+func (db *Database) addKeys(table string, pos int, keys map[string]bool) error {
+	return db.eachValue(table, pos, func(key []byte) error {
+		keys[string(key)] = true
+		return nil
+	})
+}
+
+// readBatch reads up to csvBatchRows rows.
+func (imp *csvImport) readBatch(cr *csv.Reader, opts CSVOptions) ([][]any, error) {
+	var rows [][]any
+	for len(rows) < csvBatchRows {
+		fields, err := cr.Read()
+		if errors.Is(err, io.EOF) {
+			break
+		}
+		if err != nil {
+			return nil, err
+		}
+		row := make([]any, len(fields))
+		for i, f := range fields {
+			if row[i], err = parseCSVValue(imp.meta.Schema.Cols[i], f, opts); err != nil {
+				line, _ := cr.FieldPos(i)
+				return nil, fmt.Errorf("%w: line %d, column %s: %v", ErrCSVValue, line, imp.meta.Schema.Cols[i].Name, err)
+			}
+		}
+		rows = append(rows, row)
+	}
+	return rows, nil
+}
+
+// check gives the NULLs of the AUTOINCREMENT column their values and fails
+// with a ConstraintError when a row breaks a constraint.
+func (imp *csvImport) check(rows [][]any) error {
+	if err := imp.autoIncrement(rows); err != nil {
+		return err
+	}
+	for _, row := range rows {
+		for i, c := range imp.meta.Schema.Cols {
+			if row[i] == nil && !c.Nullable {
+				return NotNullError(imp.meta.Name, c.Name)
+			}
+		}
+		for _, ck := range imp.unique {
+			key, ok, err := csvKey(row, ck.pos)
+			if err != nil {
+				return err
+			}
+			if ok && ck.keys[key] {
+				return ck.err()
+			}
+			if ok {
+				ck.keys[key] = true
+			}
+		}
+		for _, ck := range imp.foreign {
+			if ck.self >= 0 {
+				key, ok, err := csvKey(row, ck.self)
+				if err != nil {
+					return err
+				}
+				if ok {
+					ck.keys[key] = true
+				}
+			}
+			key, ok, err := csvKey(row, ck.pos)
+			if err != nil {
+				return err
+			}
+			if ok && !ck.keys[key] {
+				return ck.err()
+			}
+		}
+	}
+	return nil
+}
+
+// autoIncrement reserves counter values for the NULLs of the AUTOINCREMENT
+// column in rows, past the values the rows give it.
+func (imp *csvImport) autoIncrement(rows [][]any) error {
+	if imp.autoInc < 0 {
+		return nil
+	}
+	var nulls, largest int64
+	for _, row := range rows {
+		switch v := row[imp.autoInc].(type) {
+		case nil:
+			nulls++
+		case int64:
+			largest = max(largest, v)
+		}
+	}
+	var next int64
+	err := imp.db.updateAutoIncrement(imp.meta.Name, func(ai *AutoIncrement) (bool, error) {
+		last := max(ai.Last, largest)
+		if nulls > math.MaxInt64-last {
+			return false, ErrAutoIncrementExhausted
+		}
+		changed := last+nulls != ai.Last
+		next, ai.Last = last+1, last+nulls
+		return changed, nil
+	})
+	if err != nil {
+		return err
+	}
+	for _, row := range rows {
+		if row[imp.autoInc] == nil {
+			row[imp.autoInc] = next
+			next++
+		}
+	}
+	return nil
+}
+
+// csvKey is the index encoding of row[pos], with ok false for NULL.
+func csvKey(row []any, pos int) (string, bool, error) {
+	if row[pos] == nil {
+		return "", false, nil
+	}
+	key, err := btree.EncodeIndexValue(row[pos])
+	return string(key), true, err
+}
+
+func parseCSVValue(col record.Column, field string, opts CSVOptions) (any, error) {
+	if field == opts.Null {
+		return nil, nil
+	}
+	switch col.Type {
+	case record.ColInt32:
+		v, err := strconv.ParseInt(field, 10, 32)
+		return int32(v), err
+	case record.ColInt64:
+		return strconv.ParseInt(field, 10, 64)
+	case record.ColFloat64:
+		return strconv.ParseFloat(field, 64)
+	case record.ColBool:
+		return strconv.ParseBool(field)
+	case record.ColText:
+		return field, nil
+	case record.ColJSON:
+		return expr.NormalizeJSON(field)
+	case record.ColDate, record.ColTime, record.ColTimestamp:
+		return record.TemporalValue(col.Type, field)
+	case record.ColDecimal:
+		return record.DecimalValue(col, field)
+	case record.ColBytes:
+		return hex.DecodeString(strings.TrimPrefix(field, `\x`))
+	}
+	return nil, record.ErrUnsupportedType
+}
+
+func formatCSVValue(v any, opts CSVOptions) string {
+	switch x := v.(type) {
+	case nil:
+		return opts.Null
+	case int32:
+		return strconv.FormatInt(int64(x), 10)
+	case int64:
+		return strconv.FormatInt(x, 10)
+	case float64:
+		return strconv.FormatFloat(x, 'g', -1, 64)
+	case bool:
+		return strconv.FormatBool(x)
+	case string:
+		return x
+	case []byte:
+		return `\x` + hex.EncodeToString(x)
+	}
+	return fmt.Sprint(v)
+}
@@ -0,0 +1,105 @@
+package novasql
+
+import (
+	"bytes"
+	"fmt"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func TestCSV_ImportExport(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	_, err = db.CreateTable("items", record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64},
+		{Name: "name", Type: record.ColText},
+		{Name: "price", Type: record.ColFloat64, Nullable: true},
+		{Name: "sold", Type: record.ColBool, Nullable: true},
+	}})
+	require.NoError(t, err)
+	require.NoError(t, db.AddPrimaryKey("items", "id"))
+	require.NoError(t, db.SetAutoIncrement("items", "id"))
+	rows := func() int {
+		t.Helper()
+		tbl, err := db.OpenTable("items")
+		require.NoError(t, err)
+		return countRows(t, tbl)
+	}
+
+	opts := CSVOptions{Header: true, Delimiter: ';', Null: `\N`}
+	in := "id;name;price;sold\n" +
+		"5;\"semi;colon\";1.5;true\n" +
+		"\\N;plain;\\N;false\n" +
+		"\\N;\"multi\nline\";-2;\\N\n"
+	n, err := db.ImportCSV("items", strings.NewReader(in), opts)
+	require.NoError(t, err)
+	require.Equal(t, int64(3), n)
+
+	// NULL ids got counter values past the explicit one.
+	var out bytes.Buffer
+	n, err = db.ExportCSV("items", &out, opts)
+	require.NoError(t, err)
+	require.Equal(t, int64(3), n)
+	require.Equal(t, "id;name;price;sold\n"+
+		"5;\"semi;colon\";1.5;true\n"+
+		"6;plain;\\N;false\n"+
+		"7;\"multi\nline\";-2;\\N\n", out.String())
+	id, err := db.NextAutoIncrement("items")
+	require.NoError(t, err)
+	require.Equal(t, int64(8), id)
+
+	// A bad row anywhere rejects the whole file.
+	for _, bad := range []struct {
+		csv  string
+		want error
+	}{
+		{"9;a;;\n10;b;x;\n", ErrCSVValue},
+		{"9;a;;\n5;dup;;\n", ErrConstraintViolation},
+		{"9;a;;\n9;dup;;\n", ErrConstraintViolation},
+		{"9;;;\n", ErrConstraintViolation},
+	} {
+		_, err := db.ImportCSV("items", strings.NewReader(bad.csv), CSVOptions{Delimiter: ';'})
+		require.ErrorIs(t, err, bad.want, bad.csv)
+	}
+	require.Equal(t, 3, rows())
+
+	// Several batches.
+	var big strings.Builder
+	for i := range 3 * csvBatchRows {
+		fmt.Fprintf(&big, "%d,item-%d,%d.5,\n", 100+i, i, i)
+	}
+	n, err = db.ImportCSV("items", strings.NewReader(big.String()), CSVOptions{})
+	require.NoError(t, err)
+	require.Equal(t, int64(3*csvBatchRows), n)
+	require.Equal(t, 3+3*csvBatchRows, rows())
+}
+
+func TestCSV_ImportForeignKey(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	_, err = db.CreateTable("nodes", record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64},
+		{Name: "parent", Type: record.ColInt64, Nullable: true},
+	}})
+	require.NoError(t, err)
+	require.NoError(t, db.AddPrimaryKey("nodes", "id"))
+	require.NoError(t, db.AddForeignKey("nodes", ForeignKey{Column: "parent", RefTable: "nodes"}))
+
+	// Rows may reference rows imported before them.
+	n, err := db.ImportCSV("nodes", strings.NewReader("1,\n2,1\n3,2\n"), CSVOptions{})
+	require.NoError(t, err)
+	require.Equal(t, int64(3), n)
+	_, err = db.ImportCSV("nodes", strings.NewReader("4,5\n5,\n"), CSVOptions{})
+	require.ErrorIs(t, err, ErrConstraintViolation)
+	require.Contains(t, err.Error(), "nodes_parent_fkey")
+
+	_, err = db.ImportCSV("missing", strings.NewReader(""), CSVOptions{})
+	require.ErrorIs(t, err, ErrTableNotFound)
+}
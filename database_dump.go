@@ -0,0 +1,166 @@
+package novasql
+
+import (
+	"bufio"
+	"cmp"
+	"fmt"
+	"io"
+	"math"
+	"strconv"
+	"strings"
+
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+// Dump writes SQL statements that rebuild the database: a CREATE TABLE and
+// the INSERTs of its visible rows for every table, then the indexes and the
+// foreign keys, which come last so that rows load in any order, and the
+// views in the order they were created. Restore a dump with
+// executor.Executor.Restore.
+//
+// The dump keeps the names of indexes and constraints, but not the
+// AUTOINCREMENT counters (they restart at the largest value in the column)
+// and every index comes back as a KVTree one. Tables with INT32 or BYTES
+// columns, which SQL cannot declare, cannot be dumped.
+func (db *Database) Dump(w io.Writer) error {
+	if err := db.ensureOpen(); err != nil {
+		return err
+	}
+	bw := bufio.NewWriter(w)
+	tables := db.catalogTables()
+	for _, meta := range tables {
+		if err := db.dumpTable(bw, meta); err != nil {
+			return err
+		}
+	}
+	for _, meta := range tables {
+		for _, im := range meta.Indexes {
+			switch {
+			case im.Constraint == ConstraintPrimaryKey:
+			case im.Constraint == ConstraintUnique:
+				_, _ = fmt.Fprintf(bw, "CREATE UNIQUE INDEX %s ON %s (%s);\n", im.Name, meta.Name, im.KeyColumn)
+			case im.Kind == IndexKindFullText:
+				_, _ = fmt.Fprintf(bw, "CREATE INDEX %s ON %s USING fulltext (%s);\n", im.Name, meta.Name, im.KeyColumn)
+			default:
+				_, _ = fmt.Fprintf(bw, "CREATE INDEX %s ON %s (%s);\n", im.Name, meta.Name, cmp.Or(im.Expression, im.KeyColumn))
+			}
+		}
+	}
+	for _, meta := range tables {
+		for _, fk := range meta.ForeignKeys {
+			_, _ = fmt.Fprintf(bw, "ALTER TABLE %s ADD CONSTRAINT %s FOREIGN KEY (%s) REFERENCES %s (%s) ON DELETE %s;\n",
+				meta.Name, fk.Name, fk.Column, fk.RefTable, fk.RefColumn, fk.OnDelete)
+		}
+	}
+	views, err := db.ListViews()
+	if err != nil {
+		return err
+	}
+	for _, v := range views {
+		_, _ = fmt.Fprintf(bw, "CREATE VIEW %s AS %s;
+", v.Name, v.Query)
+	}
+	return bw.Flush()
+}
+
+func (db *Database) dumpTable(w *bufio.Writer, meta *TableMeta) error {
+	var pk string
+	for _, im := range meta.Indexes {
+		if im.Constraint == ConstraintPrimaryKey {
+			pk = im.KeyColumn
+		}
+	}
+	defs := make([]string, len(meta.Schema.Cols))
+	for i, c := range meta.Schema.Cols {
+		typ, err := dumpType(c)
+		if err != nil {
+			return fmt.Errorf("%w: %s.%s", err, meta.Name, c.Name)
+		}
+		defs[i] = c.Name + " " + typ
+		if c.Collation != "" {
+			defs[i] += " COLLATE " + c.Collation
+		}
+		if !c.Nullable {
+			defs[i] += " NOT NULL"
+		}
+		if c.Name == pk {
+			defs[i] += " PRIMARY KEY"
+		}
+		if ai := meta.AutoIncrement; ai != nil && ai.Column == c.Name {
+			defs[i] += " AUTOINCREMENT"
+		}
+	}
+	var with string
+	if meta.TTLColumn != "" {
+		with = " WITH (ttl_column = " + meta.TTLColumn + ")"
+	}
+	_, _ = fmt.Fprintf(w, "CREATE TABLE %s (%s)%s;\n", meta.Name, strings.Join(defs, ", "), with)
+
+	tbl, err := db.OpenTable(meta.Name)
+	if err != nil {
+		return err
+	}
+	vals := make([]string, len(meta.Schema.Cols))
+	return forEachRow(tbl, func(row []any) error {
+		for i, v := range row {
+			if vals[i], err = sqlLiteral(v); err != nil {
+				return fmt.Errorf("%w: %s.%s", err, meta.Name, meta.Schema.Cols[i].Name)
+			}
+		}
+		_, err = fmt.Fprintf(w, "INSERT INTO %s VALUES (%s);\n", meta.Name, strings.Join(vals, ", "))
+		return err
+	})
+}
+
+func dumpType(c record.Column) (string, error) {
+	switch c.Type {
+	case record.ColInt64:
+		return "INT", nil
+	case record.ColFloat64:
+		return "FLOAT", nil
+	case record.ColText:
+		return "TEXT", nil
+	case record.ColBool:
+		return "BOOL", nil
+	case record.ColJSON:
+		return "JSON", nil
+	case record.ColDate:
+		return "DATE", nil
+	case record.ColTime:
+		return "TIME", nil
+	case record.ColTimestamp:
+		return "TIMESTAMP", nil
+	case record.ColDecimal:
+		return fmt.Sprintf("DECIMAL(%d, %d)", c.Precision, c.Scale), nil
+	}
+	return "", record.ErrUnsupportedType
+}
+
+// sqlLiteral is v as the SQL parser reads it back.
+func sqlLiteral(v any) (string, error) {
+	switch x := v.(type) {
+	case nil:
+		return "NULL", nil
+	case int64:
+		return strconv.FormatInt(x, 10), nil
+	case float64:
+		if math.IsNaN(x) || math.IsInf(x, 0) {
+			return "", fmt.Errorf("%w: %v", record.ErrUnsupportedType, x)
+		}
+		s := strconv.FormatFloat(x, 'f', -1, 64)
+		if !strings.Contains(s, ".") {
+			s += ".0"
+		}
+		return s, nil
+	case bool:
+		if x {
+			return "TRUE", nil
+		}
+		return "FALSE", nil
+	case string:
+		return "'" + strings.ReplaceAll(x, "'", "''") + "'", nil
+	case record.Date, record.Time, record.Timestamp, record.Decimal:
+		return "'" + fmt.Sprint(x) + "'", nil // read back by the column type
+	}
+	return "", record.ErrUnsupportedType
+}
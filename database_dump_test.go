@@ -0,0 +1,60 @@
+package novasql
+
+import (
+	"bytes"
+	"io"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func TestDump(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	users, err := db.CreateTable("users", record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64},
+		{Name: "name", Type: record.ColText},
+		{Name: "score", Type: record.ColFloat64, Nullable: true},
+	}})
+	require.NoError(t, err)
+	for _, row := range [][]any{{int64(1), "ann", 1.5}, {int64(2), "it's", nil}, {int64(3), "cat", float64(-3)}} {
+		_, err := users.Insert(row)
+		require.NoError(t, err)
+	}
+	require.NoError(t, db.AddPrimaryKey("users", "id"))
+	require.NoError(t, db.SetAutoIncrement("users", "id"))
+	require.NoError(t, db.CreateUniqueIndex("users", "users_name_key", "name"))
+
+	posts, err := db.CreateTable("posts", record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64},
+		{Name: "author", Type: record.ColInt64, Nullable: true},
+		{Name: "draft", Type: record.ColBool, Nullable: true},
+	}})
+	require.NoError(t, err)
+	_, err = posts.Insert([]any{int64(10), int64(2), true})
+	require.NoError(t, err)
+	require.NoError(t, db.AddPrimaryKey("posts", "id"))
+	require.NoError(t, db.CreateIndex("posts", "posts_author_idx", "author"))
+	fk := ForeignKey{Column: "author", RefTable: "users", OnDelete: ForeignKeyCascade}
+	require.NoError(t, db.AddForeignKey("posts", fk))
+
+	var out bytes.Buffer
+	require.NoError(t, db.Dump(&out))
+	require.Equal(t, "CREATE TABLE posts (id INT NOT NULL PRIMARY KEY, author INT, draft BOOL);\n"+
+		"INSERT INTO posts VALUES (10, 2, TRUE);\n"+
+		"CREATE TABLE users (id INT NOT NULL PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, score FLOAT);\n"+
+		"INSERT INTO users VALUES (1, 'ann', 1.5);\n"+
+		"INSERT INTO users VALUES (2, 'it''s', NULL);\n"+
+		"INSERT INTO users VALUES (3, 'cat', -3.0);\n"+
+		"CREATE INDEX posts_author_idx ON posts (author);\n"+
+		"CREATE UNIQUE INDEX users_name_key ON users (name);\n"+
+		"ALTER TABLE posts ADD CONSTRAINT posts_author_fkey FOREIGN KEY (author) REFERENCES users (id) ON DELETE CASCADE;\n",
+		out.String())
+
+	_, err = db.CreateTable("blobs", record.Schema{Cols: []record.Column{{Name: "b", Type: record.ColBytes}}})
+	require.NoError(t, err)
+	require.ErrorIs(t, db.Dump(io.Discard), record.ErrUnsupportedType)
+}
@@ -0,0 +1,16 @@
+package novasql
+
+import "github.com/tuannm99/novasql/internal/storage"
+
+// StorageEngine stores the pages of tables and indexes for a database opened
+// with OpenOptions.Engine or NewDatabaseWithEngine: ReadPage, WritePage,
+// Allocate, Len and Sync, plus Remove, Rename and RemoveDir for dropped and
+// renamed relations. Files are named by path, e.g.
+// "/data/default/tables/users"; see storage.Engine for the contract.
+type StorageEngine = storage.Engine
+
+// NewDatabaseWithEngine opens the database rooted at workDir with its pages
+// in engine, like Options().Engine(engine).Open(workDir).
+func NewDatabaseWithEngine(workDir string, engine StorageEngine) (*Database, error) {
+	return Options().Engine(engine).Open(workDir)
+}
@@ -0,0 +1,117 @@
+package novasql
+
+import (
+	"maps"
+	"path/filepath"
+	"strings"
+	"sync"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+// pageMap is a StorageEngine keeping pages in a map.
+type pageMap struct {
+	mu    sync.Mutex
+	files map[string][][]byte
+}
+
+func (m *pageMap) ReadPage(file string, pageID uint32, dst []byte) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	clear(dst)
+	if pages := m.files[file]; pageID < uint32(len(pages)) {
+		copy(dst, pages[pageID])
+	}
+	return nil
+}
+
+func (m *pageMap) WritePage(file string, pageID uint32, src []byte) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	m.files[file][pageID] = append([]byte(nil), src...)
+	return nil
+}
+
+func (m *pageMap) Allocate(file string, n uint32) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	for uint32(len(m.files[file])) < n {
+		m.files[file] = append(m.files[file], make([]byte, storage.PageSize))
+	}
+	return nil
+}
+
+func (m *pageMap) Len(file string) (uint32, error) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	return uint32(len(m.files[file])), nil
+}
+
+func (m *pageMap) Sync() error { return nil }
+
+func (m *pageMap) Remove(file string) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	delete(m.files, file)
+	return nil
+}
+
+func (m *pageMap) Rename(oldFile, newFile string) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	if pages, ok := m.files[oldFile]; ok {
+		m.files[newFile] = pages
+		delete(m.files, oldFile)
+	}
+	return nil
+}
+
+func (m *pageMap) RemoveDir(dir string) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	maps.DeleteFunc(m.files, func(file string, _ [][]byte) bool {
+		return strings.HasPrefix(file, dir+string(filepath.Separator))
+	})
+	return nil
+}
+
+func TestDatabase_StorageEngine(t *testing.T) {
+	dir := t.TempDir()
+	engine := &pageMap{files: make(map[string][][]byte)}
+	db, err := NewDatabaseWithEngine(dir, engine)
+	require.NoError(t, err)
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 10 {
+		_, err := tbl.Insert([]any{int64(i), "user"})
+		require.NoError(t, err)
+	}
+	require.NoError(t, db.Close())
+
+	// The pages went to the engine, the metadata to the directory.
+	require.Contains(t, engine.files, filepath.Join(db.TableDir(), "users"))
+	require.NoFileExists(t, filepath.Join(db.TableDir(), "users"))
+	require.FileExists(t, filepath.Join(db.TableDir(), "users.meta.json"))
+
+	db, err = Options().Engine(engine).Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	tbl, err = db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 10, countRows(t, tbl))
+	require.NoError(t, db.DropTable("users"))
+	_, err = db.PurgeTrash(0)
+	require.NoError(t, err)
+	require.Empty(t, engine.files)
+
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.Mode = "memory"
+	_, err = Options().Config(cfg).Engine(engine).Open(t.TempDir())
+	require.ErrorIs(t, err, ErrConflictingOptions)
+	_, err = Options().Engine(engine).Open(MemoryPath)
+	require.ErrorIs(t, err, ErrConflictingOptions)
+}
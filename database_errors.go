@@ -0,0 +1,167 @@
+package novasql
+
+import (
+	"errors"
+	"fmt"
+	"io"
+	"io/fs"
+	"strings"
+
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/mvcc"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+// ErrorCode is the stable, machine-readable kind of an error, for callers
+// that branch on what went wrong rather than on messages. The set of codes
+// grows over releases: treat a code you do not know like CodeInternal.
+type ErrorCode string
+
+const (
+	CodeInternal      ErrorCode = "internal"
+	CodeIO            ErrorCode = "io"
+	CodeCorrupt       ErrorCode = "corrupt"
+	CodeIncompatible  ErrorCode = "incompatible" // built by another format, page size or key
+	CodeSyntax        ErrorCode = "syntax"
+	CodeTableNotFound ErrorCode = "table_not_found"
+	CodeNotFound      ErrorCode = "not_found" // databases, views, indexes, columns, trash entries
+	CodeExists        ErrorCode = "already_exists"
+	CodeInvalid       ErrorCode = "invalid_argument"
+	CodeConstraint    ErrorCode = "constraint_violation"
+	CodeConflict      ErrorCode = "conflict" // write conflicts, deadlocks, lock timeouts
+	CodeBusy          ErrorCode = "busy"     // refused while transactions or a backup run
+	CodeReadOnly      ErrorCode = "read_only"
+	CodeClosed        ErrorCode = "closed"
+	CodeCancelled     ErrorCode = "cancelled" // by a cancel request or statement_timeout
+)
+
+// ErrQueryCancelled fails a statement stopped before it finished, by a
+// cancel request or because it ran past statement_timeout.
+var ErrQueryCancelled = errors.New("novasql: query cancelled")
+
+// Error is an error with its code and where it happened: the table, the page
+// and, for a SQL statement, the byte offset in it. Err is the cause, so the
+// sentinel errors still match with errors.Is.
+type Error struct {
+	Code   ErrorCode
+	Table  string // "" when not about a table
+	PageID int64  // -1 when not about a page
+	Pos    int    // byte offset in the statement, -1 when not about one
+	Err    error
+}
+
+// NewError is the Error of err with code and no context.
+func NewError(code ErrorCode, err error) *Error {
+	return &Error{Code: code, PageID: -1, Pos: -1, Err: err}
+}
+
+func (e *Error) Error() string {
+	var where []string
+	if e.Table != "" {
+		where = append(where, "table "+e.Table)
+	}
+	if e.PageID >= 0 {
+		where = append(where, fmt.Sprintf("page %d", e.PageID))
+	}
+	if e.Pos >= 0 {
+		where = append(where, fmt.Sprintf("at byte %d", e.Pos))
+	}
+	if len(where) == 0 {
+		return e.Err.Error()
+	}
+	return fmt.Sprintf("%v (%s)", e.Err, strings.Join(where, ", "))
+}
+
+func (e *Error) Unwrap() error { return e.Err }
+
+// AsError returns err as an *Error, nil for nil. An *Error in the chain is
+// returned as is; any other error gets the code of CodeOf and what the
+// ConstraintError or CorruptedPageError in its chain tell about where.
+func AsError(err error) *Error {
+	if err == nil {
+		return nil
+	}
+	var e *Error
+	if errors.As(err, &e) {
+		return e
+	}
+	e = NewError(CodeOf(err), err)
+	var ce *ConstraintError
+	if errors.As(err, &ce) {
+		e.Table = ce.Table
+	}
+	var pe *CorruptedPageError
+	if errors.As(err, &pe) {
+		e.PageID = int64(pe.PageID)
+	}
+	return e
+}
+
+// CodeOf is the ErrorCode of err: the code of the first *Error in its chain,
+// else the code of the sentinel error it matches, else CodeInternal.
+func CodeOf(err error) ErrorCode {
+	var e *Error
+	if errors.As(err, &e) {
+		return e.Code
+	}
+	for _, c := range errorCodes {
+		for _, sentinel := range c.errs {
+			if errors.Is(err, sentinel) {
+				return c.code
+			}
+		}
+	}
+	var vc *VersionConflictError
+	if errors.As(err, &vc) {
+		return CodeConflict
+	}
+	var pe *fs.PathError
+	if errors.As(err, &pe) {
+		return CodeIO
+	}
+	return CodeInternal
+}
+
+var errorCodes = []struct {
+	code ErrorCode
+	errs []error
+}{
+	{CodeTableNotFound, []error{ErrTableNotFound}},
+	{CodeNotFound, []error{
+		ErrDatabaseNotFound, ErrIndexNotFound, ErrIndexBadColumn, ErrColumnNotFound, ErrTrashNotFound,
+		ErrAutoIncrementNone, ErrViewNotFound, ErrNoSavepoint, ErrUnknownSetting,
+	}},
+	{CodeExists, []error{
+		ErrDatabaseExists, ErrIndexExists, ErrColumnExists, ErrPrimaryKeyExists, ErrForeignKeyExists,
+		ErrAutoIncrementExists, ErrBackupTarget, ErrTableExists, ErrNotReplica,
+	}},
+	{CodeConstraint, []error{ErrConstraintViolation, ErrReferenced, ErrColumnInUse}},
+	{CodeInvalid, []error{
+		ErrBadIdent, ErrInvalidPageID, ErrIndexBadKind, ErrIndexBadName, ErrIndexBadTable, ErrIndexBadKeyCol,
+		ErrPrimaryKeyNullable, ErrForeignKeyTarget, ErrForeignKeyType, ErrForeignKeySetNull, ErrForeignKeyAction,
+		ErrAutoIncrementColumn, ErrAutoIncrementExhausted, ErrColumnDefault, ErrLastColumn, ErrCSVValue,
+		ErrBulkValue, ErrConflictingOptions, ErrBackupLabel, ErrTxDone, mvcc.ErrTxDone, record.ErrUnsupportedType,
+		ErrSettingFixed, ErrBadSetting, ErrTTLColumn, ErrBadCollation,
+	}},
+	{CodeConflict, []error{mvcc.ErrWriteConflict, mvcc.ErrDeadlock, mvcc.ErrLockTimeout}},
+	{CodeBusy, []error{ErrVacuumBusy, ErrAlterBusy, ErrBackupRunning, storage.ErrLocked}},
+	{CodeReadOnly, []error{ErrReadOnly, heap.ErrTableReadOnly, mvcc.ErrReadOnly, storage.ErrReadOnly}},
+	{CodeClosed, []error{ErrDatabaseClosed, heap.ErrTableClosed}},
+	{CodeCancelled, []error{ErrQueryCancelled}},
+	{CodeIncompatible, []error{
+		ErrFormatTooNew, ErrPageSizeMismatch, ErrLayoutMismatch, ErrEncryption, ErrUpgradeRequired, storage.ErrDirectIO,
+		ErrReplicaBehind,
+	}},
+	{CodeCorrupt, []error{
+		ErrBadHeader, ErrOpenCheckFailed, storage.ErrPageCorrupted, storage.ErrCorruption,
+		storage.ErrOverflowCorruption, storage.ErrDecrypt, wal.ErrBadCRC,
+	}},
+	{CodeIO, []error{storage.ErrStorageIO, io.ErrUnexpectedEOF}},
+}
+
+// tableNotFound is the error for a table that does not exist.
+func tableNotFound(table string) error {
+	return &Error{Code: CodeTableNotFound, Table: table, PageID: -1, Pos: -1, Err: ErrTableNotFound}
+}
@@ -0,0 +1,55 @@
+package novasql
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func TestErrorCodes(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	_, err = db.OpenTable("missing")
+	require.ErrorIs(t, err, ErrTableNotFound)
+	require.Equal(t, "novasql: table not found (table missing)", err.Error())
+	e := AsError(err)
+	require.Equal(t, CodeTableNotFound, e.Code)
+	require.Equal(t, "missing", e.Table)
+	_, err = db.TableSchema("missing")
+	require.Equal(t, CodeTableNotFound, CodeOf(err))
+
+	// Sentinels map to their codes, wrapped or not.
+	_, err = db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	require.Equal(t, CodeExists, CodeOf(db.AddColumn("users", record.Column{Name: "name", Type: record.ColText}, "x")))
+	require.Equal(t, CodeInvalid, CodeOf(db.AddColumn("users", record.Column{Name: "age", Type: record.ColInt64}, nil)))
+	require.Equal(t, CodeNotFound, CodeOf(fmt.Errorf("drop: %w", db.DropIndex("users", "nope"))))
+	_, err = os.Open(filepath.Join(t.TempDir(), "nope"))
+	require.Equal(t, CodeIO, CodeOf(err))
+	require.Equal(t, CodeInternal, CodeOf(errors.New("boom")))
+
+	// Typed errors in the chain give their context.
+	e = AsError(fmt.Errorf("insert: %w", NotNullError("users", "name")))
+	require.Equal(t, CodeConstraint, e.Code)
+	require.Equal(t, "users", e.Table)
+	require.Equal(t, int64(-1), e.PageID)
+	e = AsError(&CorruptedPageError{Base: "users", PageID: 7, Reason: "bad checksum"})
+	require.Equal(t, CodeCorrupt, e.Code)
+	require.Equal(t, int64(7), e.PageID)
+
+	err = fmt.Errorf("exec: %w", &Error{Code: CodeSyntax, PageID: -1, Pos: 17, Err: errors.New("bad token")})
+	require.Equal(t, CodeSyntax, CodeOf(err))
+	require.Equal(t, "exec: bad token (at byte 17)", err.Error())
+	require.Nil(t, AsError(nil))
+
+	require.NoError(t, db.Close())
+	_, err = db.OpenTable("users")
+	require.Equal(t, CodeClosed, CodeOf(err))
+}
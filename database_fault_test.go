@@ -0,0 +1,259 @@
+package novasql
+
+import (
+	"fmt"
+	"math/rand"
+	"slices"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/mvcc"
+	"github.com/tuannm99/novasql/internal/storage/storagetest"
+)
+
+// The workloads crashWorkload runs.
+const (
+	autocommitOps = iota // inserts, updates and deletes, each on its own
+	snapshotOps          // the same in BeginSnapshot transactions of a few changes each
+	bulkOps              // InsertRows batches
+	numWorkloads
+)
+
+// crashWorkload runs random changes of workload against a database whose
+// pages, WAL and transaction log are on a FaultEngine armed to fail the
+// at-th I/O (at 0: none) with fault. Once the engine has crashed, or after
+// the last change, the handle is abandoned, the engine restarted with what
+// it had synced and the database reopened. Recovery must keep every change
+// that returned nil, may keep or lose one that failed, and must leave no
+// corrupted page.
+func crashWorkload(t *testing.T, seed int64, workload, at int, fault storagetest.Fault) {
+	t.Helper()
+
+	dir := t.TempDir()
+	engine := storagetest.NewFaultEngine()
+	db, err := Options().Engine(engine).Open(dir)
+	require.NoError(t, err)
+	tbl, err := db.CreateTable("kv", testSchema())
+	require.NoError(t, err)
+	require.NoError(t, db.Checkpoint())
+
+	type row struct {
+		tid  heap.TID
+		name string
+	}
+	known := make(map[int64]row)      // rows as the changes that returned nil left them
+	maybe := make(map[int64][]string) // names a failed change may have left, "" for no row
+	var ids []int64                   // the keys of known, in a stable order
+
+	// A change leaves row id named name, at tid, or deletes it for name "";
+	// old is its name before, "" for none.
+	type change struct {
+		id        int64
+		old, name string
+		tid       heap.TID
+	}
+	forget := func(id int64) {
+		delete(known, id)
+		ids = slices.DeleteFunc(ids, func(k int64) bool { return k == id })
+	}
+	done := func(c change) {
+		if c.name == "" {
+			forget(c.id)
+			return
+		}
+		if _, ok := known[c.id]; !ok {
+			ids = append(ids, c.id)
+		}
+		known[c.id] = row{tid: c.tid, name: c.name}
+	}
+	failed := func(c change) {
+		maybe[c.id] = []string{c.old, c.name}
+		forget(c.id)
+	}
+
+	rng := rand.New(rand.NewSource(seed))
+	next := int64(0)
+	newRow := func() change {
+		next++
+		return change{id: next, name: fmt.Sprintf("v%d", next)}
+	}
+	// random makes a random change, in tx unless nil, to a row not in busy.
+	random := func(tx *mvcc.Tx, busy map[int64]bool) (change, error) {
+		c := newRow()
+		if op := rng.Intn(4); op >= 2 && len(ids) > 0 {
+			if id := ids[rng.Intn(len(ids))]; !busy[id] {
+				r := known[id]
+				c.id, c.old, c.tid = id, r.name, r.tid
+				var err error
+				switch {
+				case op == 3:
+					c.name = ""
+					if tx == nil {
+						err = tbl.Delete(r.tid)
+					} else {
+						err = tbl.DeleteTx(tx, r.tid)
+					}
+				case tx == nil:
+					err = tbl.Update(r.tid, []any{id, c.name})
+				default:
+					c.tid, err = tbl.UpdateTx(tx, r.tid, []any{id, c.name})
+				}
+				return c, err
+			}
+		}
+		var err error
+		if tx == nil {
+			c.tid, err = tbl.Insert([]any{c.id, c.name})
+		} else {
+			c.tid, err = tbl.InsertTx(tx, []any{c.id, c.name})
+		}
+		return c, err
+	}
+
+	engine.InjectAt(at, fault)
+	for n := 0; n < 200 && !engine.Crashed(); n++ {
+		switch workload {
+		case snapshotOps:
+			// The changes of a transaction are kept or lost together.
+			tx, err := db.BeginSnapshot()
+			if err != nil {
+				continue
+			}
+			var changes []change
+			busy := make(map[int64]bool)
+			for range 1 + rng.Intn(4) {
+				var c change
+				c, err = random(tx, busy)
+				changes = append(changes, c)
+				busy[c.id] = true
+				if err != nil {
+					_ = tx.Abort()
+					break
+				}
+			}
+			if err == nil {
+				err = tx.Commit()
+			}
+			for _, c := range changes {
+				if err != nil {
+					failed(c)
+				} else {
+					done(c)
+				}
+			}
+		case bulkOps:
+			changes := make([]change, 1+rng.Intn(20))
+			rows := make([][]any, len(changes))
+			for i := range changes {
+				changes[i] = newRow()
+				rows[i] = []any{changes[i].id, changes[i].name}
+			}
+			_, err := db.InsertRows("kv", rows)
+			for _, c := range changes {
+				if err != nil {
+					failed(c)
+				} else {
+					done(c)
+				}
+			}
+		default:
+			if c, err := random(nil, nil); err != nil {
+				failed(c)
+			} else {
+				done(c)
+			}
+		}
+	}
+
+	// Crash: abandon the handle and lose what the engine had not synced.
+	engine.Crash()
+	db.closeWAL()
+	db.closed = true
+	engine.Restart()
+
+	db, err = Options().Engine(engine).Open(dir)
+	require.NoError(t, err, "reopen after %s at I/O %d", fault, at)
+	t.Cleanup(func() { _ = db.Close() })
+	tbl, err = db.OpenTable("kv")
+	require.NoError(t, err)
+
+	seen := make(map[int64]bool)
+	require.NoError(t, tbl.Scan(func(_ heap.TID, r []any) error {
+		id, name := r[0].(int64), r[1].(string)
+		require.False(t, seen[id], "row %d twice", id)
+		seen[id] = true
+		if k, ok := known[id]; ok {
+			require.Equal(t, k.name, name, "row %d", id)
+		} else {
+			require.Contains(t, maybe[id], name, "row %d", id)
+		}
+		return nil
+	}))
+	for id := range known {
+		require.True(t, seen[id], "committed row %d lost after %s at I/O %d", id, fault, at)
+	}
+	for id, names := range maybe {
+		if !slices.Contains(names, "") {
+			require.True(t, seen[id], "row %d lost after %s at I/O %d", id, fault, at)
+		}
+	}
+	bad, err := db.VerifyIntegrity()
+	require.NoError(t, err)
+	require.Empty(t, bad, "after %s at I/O %d", fault, at)
+}
+
+// FuzzCrashRecovery runs crashWorkload; go test runs the seeds, go test
+// -fuzz=FuzzCrashRecovery searches for workloads and crash points that
+// break recovery.
+func FuzzCrashRecovery(f *testing.F) {
+	f.Add(int64(1), uint8(autocommitOps), uint16(0), uint8(storagetest.FailIO))
+	for i, fault := range []storagetest.Fault{storagetest.FailIO, storagetest.ShortWrite, storagetest.TornWrite} {
+		for w := range uint8(numWorkloads) {
+			for _, at := range []uint16{1, 40, 150, 301} {
+				f.Add(int64(i), w, at+uint16(i), uint8(fault))
+			}
+		}
+	}
+	f.Fuzz(func(t *testing.T, seed int64, workload uint8, at uint16, fault uint8) {
+		crashWorkload(t, seed, int(workload%numWorkloads), int(at), storagetest.Fault(fault%3))
+	})
+}
+
+func TestCrash_CommittedSnapshotRowsSurvive(t *testing.T) {
+	dir := t.TempDir()
+	engine := storagetest.NewFaultEngine()
+	db, err := Options().Engine(engine).Open(dir)
+	require.NoError(t, err)
+	tbl, err := db.CreateTable("kv", testSchema())
+	require.NoError(t, err)
+	require.NoError(t, db.Checkpoint())
+
+	tx, err := db.BeginSnapshot()
+	require.NoError(t, err)
+	for i := range 20 {
+		_, err := tbl.InsertTx(tx, []any{int64(i), fmt.Sprintf("v%d", i)})
+		require.NoError(t, err)
+	}
+	require.NoError(t, tx.Commit())
+
+	// The data pages were never synced; the commit must have made the log
+	// durable before recording itself.
+	engine.Crash()
+	db.closeWAL()
+	db.closed = true
+	engine.Restart()
+
+	db, err = Options().Engine(engine).Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	tbl, err = db.OpenTable("kv")
+	require.NoError(t, err)
+	n := 0
+	require.NoError(t, tbl.Scan(func(heap.TID, []any) error {
+		n++
+		return nil
+	}))
+	require.Equal(t, 20, n)
+}
@@ -0,0 +1,208 @@
+package novasql
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"slices"
+
+	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+var (
+	ErrForeignKeyExists  = errors.New("novasql: foreign key already exists")
+	ErrForeignKeyTarget  = errors.New("novasql: foreign key must reference a PRIMARY KEY or UNIQUE column")
+	ErrForeignKeyType    = errors.New("novasql: foreign key column type differs from the referenced column")
+	ErrForeignKeySetNull = errors.New("novasql: ON DELETE SET NULL needs a nullable column")
+	ErrForeignKeyAction  = errors.New("novasql: unsupported ON DELETE action")
+	ErrReferenced        = errors.New("novasql: referenced by a foreign key")
+)
+
+// ForeignKeyAction is what deleting a referenced row does to the rows that
+// reference it.
+type ForeignKeyAction string
+
+const (
+	// ForeignKeyRestrict rejects the delete (the default).
+	ForeignKeyRestrict ForeignKeyAction = "RESTRICT"
+	// ForeignKeyCascade deletes the referencing rows too.
+	ForeignKeyCascade ForeignKeyAction = "CASCADE"
+	// ForeignKeySetNull sets the referencing column to NULL.
+	ForeignKeySetNull ForeignKeyAction = "SET NULL"
+)
+
+// ForeignKey makes every non-NULL value of Column equal to the RefColumn of
+// a row of RefTable. RefColumn is a PRIMARY KEY or UNIQUE column, so that
+// row is the only one.
+type ForeignKey struct {
+	Name      string           `json:"name"`
+	Column    string           `json:"column"`
+	RefTable  string           `json:"ref_table"`
+	RefColumn string           `json:"ref_column"`
+	OnDelete  ForeignKeyAction `json:"on_delete"`
+}
+
+// ForeignKeyName is the default name of the foreign key on column of table.
+func ForeignKeyName(table, column string) string { return table + "_" + column + "_fkey" }
+
+// AddForeignKey adds fk to table once its existing rows satisfy it. An empty
+// Name defaults to ForeignKeyName, an empty RefColumn to the primary key of
+// RefTable and an empty OnDelete to ForeignKeyRestrict. Like the other
+// constraints, the SQL layer enforces it on INSERT, UPDATE and DELETE.
+func (db *Database) AddForeignKey(table string, fk ForeignKey) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	if err := validateIdent(table); err != nil {
+		return ErrIndexBadTable
+	}
+	if err := validateIdent(fk.RefTable); err != nil {
+		return ErrIndexBadTable
+	}
+	if fk.Name == "" {
+		fk.Name = ForeignKeyName(table, fk.Column)
+	}
+	if err := validateIdent(fk.Name); err != nil {
+		return ErrIndexBadName
+	}
+	switch fk.OnDelete {
+	case "":
+		fk.OnDelete = ForeignKeyRestrict
+	case ForeignKeyRestrict, ForeignKeyCascade, ForeignKeySetNull:
+	default:
+		return fmt.Errorf("%w: %s", ErrForeignKeyAction, fk.OnDelete)
+	}
+
+	meta, err := db.readTableMeta(table)
+	if err != nil {
+		return err
+	}
+	ref, err := db.readTableMeta(fk.RefTable)
+	if errors.Is(err, os.ErrNotExist) {
+		return tableNotFound(fk.RefTable)
+	}
+	if err != nil {
+		return err
+	}
+	if slices.ContainsFunc(meta.ForeignKeys, func(f ForeignKey) bool { return f.Name == fk.Name }) {
+		return ErrForeignKeyExists
+	}
+	if fk.RefColumn == "" {
+		if i := slices.IndexFunc(ref.Indexes, func(im IndexMeta) bool {
+			return im.Constraint == ConstraintPrimaryKey
+		}); i >= 0 {
+			fk.RefColumn = ref.Indexes[i].KeyColumn
+		}
+	}
+	pos := slices.IndexFunc(meta.Schema.Cols, func(c record.Column) bool { return c.Name == fk.Column })
+	refPos := slices.IndexFunc(ref.Schema.Cols, func(c record.Column) bool { return c.Name == fk.RefColumn })
+	if pos < 0 || (refPos < 0 && fk.RefColumn != "") {
+		return ErrIndexBadColumn
+	}
+	if !slices.ContainsFunc(ref.Indexes, func(im IndexMeta) bool {
+		return im.Constraint != "" && im.KeyColumn == fk.RefColumn
+	}) {
+		return ErrForeignKeyTarget
+	}
+	if meta.Schema.Cols[pos].Type != ref.Schema.Cols[refPos].Type {
+		return ErrForeignKeyType
+	}
+	if fk.OnDelete == ForeignKeySetNull && !meta.Schema.Cols[pos].Nullable {
+		return ErrForeignKeySetNull
+	}
+
+	if err := db.checkForeignKey(table, pos, fk.RefTable, refPos); err != nil {
+		if errors.Is(err, ErrConstraintViolation) {
+			err = &ConstraintError{Kind: ConstraintForeignKey, Constraint: fk.Name, Table: table, Column: fk.Column}
+		}
+		return err
+	}
+	meta.ForeignKeys = append(meta.ForeignKeys, fk)
+	return db.writeTableMeta(meta)
+}
+
+// checkForeignKey fails with ErrConstraintViolation when a non-NULL value in
+// column pos of table has no equal in column refPos of refTable.
+func (db *Database) checkForeignKey(table string, pos int, refTable string, refPos int) error {
+	keys := make(map[string]bool)
+	if err := db.eachValue(refTable, refPos, func(key []byte) error {
+		keys[string(key)] = true
+		return nil
+	}); err != nil {
+		return err
+	}
+	return db.eachValue(table, pos, func(key []byte) error {
+		if !keys[string(key)] {
+			return ErrConstraintViolation
+		}
+		return nil
+	})
+}
+
+// eachValue calls f with the index encoding of each non-NULL value in column
+// pos of the visible rows of table.
+func (db *Database) eachValue(table string, pos int, f func(key []byte) error) error {
+	tbl, err := db.OpenTable(table)
+	if err != nil {
+		return err
+	}
+	return forEachRow(tbl, func(row []any) error {
+		if row[pos] == nil {
+			return nil
+		}
+		key, err := btree.EncodeIndexValue(row[pos])
+		if err != nil {
+			return err
+		}
+		return f(key)
+	})
+}
+
+func forEachRow(tbl *heap.Table, f func(row []any) error) error {
+	it := tbl.Iter()
+	for {
+		_, row, ok, err := it.Next()
+		if err != nil || !ok {
+			return err
+		}
+		if err := f(row); err != nil {
+			return err
+		}
+	}
+}
+
+// checkNotReferenced fails with ErrReferenced when another table has a
+// foreign key to table, or, if column is set, to that column of it.
+func (db *Database) checkNotReferenced(table, column string) error {
+	for _, m := range db.catalogTables() {
+		for _, fk := range m.ForeignKeys {
+			if fk.RefTable != table || (m.Name == table && column == "") || (column != "" && fk.RefColumn != column) {
+				continue
+			}
+			return fmt.Errorf("%w: %s (%s on %s)", ErrReferenced, table, fk.Name, m.Name)
+		}
+	}
+	return nil
+}
+
+// renameReferences points the foreign keys to table oldName at newName.
+func (db *Database) renameReferences(oldName, newName string) error {
+	for _, m := range db.catalogTables() {
+		changed := false
+		for i := range m.ForeignKeys {
+			if m.ForeignKeys[i].RefTable == oldName {
+				m.ForeignKeys[i].RefTable = newName
+				changed = true
+			}
+		}
+		if !changed {
+			continue
+		}
+		if err := db.writeTableMeta(m); err != nil {
+			return err
+		}
+	}
+	return nil
+}
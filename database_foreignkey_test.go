@@ -0,0 +1,69 @@
+package novasql
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func TestForeignKey_AddDropRename(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	teams, err := db.CreateTable("teams", testSchema())
+	require.NoError(t, err)
+	_, err = teams.Insert([]any{int64(1), "core"})
+	require.NoError(t, err)
+	users, err := db.CreateTable("users", record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64},
+		{Name: "team", Type: record.ColInt64, Nullable: true},
+		{Name: "name", Type: record.ColText, Nullable: true},
+	}})
+	require.NoError(t, err)
+	_, err = users.Insert([]any{int64(1), int64(1), "ann"})
+	require.NoError(t, err)
+	_, err = users.Insert([]any{int64(2), nil, "bob"})
+	require.NoError(t, err)
+
+	fk := ForeignKey{Column: "team", RefTable: "teams"}
+	require.ErrorIs(t, db.AddForeignKey("users", fk), ErrForeignKeyTarget)
+	require.NoError(t, db.AddPrimaryKey("teams", "id"))
+	require.ErrorIs(t, db.AddForeignKey("users", ForeignKey{Column: "name", RefTable: "teams"}), ErrForeignKeyType)
+	require.ErrorIs(t, db.AddForeignKey("users", ForeignKey{Column: "team", RefTable: "missing"}), ErrTableNotFound)
+	require.ErrorIs(t, db.AddForeignKey("users", ForeignKey{Column: "team", RefTable: "teams", OnDelete: "NOTHING"}),
+		ErrForeignKeyAction)
+
+	// Existing rows must satisfy the key.
+	cat, err := users.Insert([]any{int64(3), int64(7), "cat"})
+	require.NoError(t, err)
+	err = db.AddForeignKey("users", fk)
+	require.ErrorIs(t, err, ErrConstraintViolation)
+	require.Contains(t, err.Error(), "users_team_fkey")
+	require.NoError(t, users.Delete(cat))
+	require.NoError(t, db.AddForeignKey("users", fk))
+	require.ErrorIs(t, db.AddForeignKey("users", fk), ErrForeignKeyExists)
+
+	meta, err := db.readTableMeta("users")
+	require.NoError(t, err)
+	require.Equal(t, []ForeignKey{{
+		Name:      "users_team_fkey",
+		Column:    "team",
+		RefTable:  "teams",
+		RefColumn: "id",
+		OnDelete:  ForeignKeyRestrict,
+	}}, meta.ForeignKeys)
+
+	// The referenced table and its key stay while referenced, and follow a
+	// rename.
+	require.ErrorIs(t, db.DropTable("teams"), ErrReferenced)
+	require.ErrorIs(t, db.DropTablePurge("teams"), ErrReferenced)
+	require.ErrorIs(t, db.DropIndex("teams", PrimaryKeyName("teams")), ErrReferenced)
+	require.NoError(t, db.RenameTable("teams", "groups"))
+	meta, err = db.readTableMeta("users")
+	require.NoError(t, err)
+	require.Equal(t, "groups", meta.ForeignKeys[0].RefTable)
+	require.NoError(t, db.DropTable("users"))
+	require.NoError(t, db.DropTable("groups"))
+}
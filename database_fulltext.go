@@ -0,0 +1,108 @@
+package novasql
+
+import (
+	"fmt"
+	"os"
+	"slices"
+
+	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/fulltext"
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+// CreateFullTextIndex registers a full-text index over the TEXT column and
+// fills it from the rows already in the table: a KVTree with an entry per
+// term of each row (see package fulltext). The SQL layer keeps it in step
+// with INSERT, UPDATE and DELETE, and reads it for "column MATCH 'terms'",
+// best match first.
+func (db *Database) CreateFullTextIndex(table, indexName, column string) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	if err := validateIdent(table); err != nil {
+		return ErrIndexBadTable
+	}
+	if err := validateIdent(indexName); err != nil {
+		return ErrIndexBadName
+	}
+	if err := validateIdent(column); err != nil {
+		return ErrIndexBadKeyCol
+	}
+
+	tmeta, err := db.readTableMeta(table)
+	if err != nil {
+		return err
+	}
+	pos := slices.IndexFunc(tmeta.Schema.Cols, func(c record.Column) bool { return c.Name == column })
+	if pos < 0 {
+		return ErrIndexBadColumn
+	}
+	if tmeta.Schema.Cols[pos].Type != record.ColText {
+		return fmt.Errorf("%w: %s is not TEXT", ErrIndexBadKeyCol, column)
+	}
+	if _, im := db.findIndexMeta(tmeta, indexName); im != nil {
+		return ErrIndexExists
+	}
+	tbl, err := db.OpenTable(table)
+	if err != nil {
+		return err
+	}
+
+	_ = os.MkdirAll(db.TableDir(), 0o755)
+	fs := db.indexFileSet(table, indexName)
+	if err := db.fillFullTextIndex(tbl, pos, fs); err != nil {
+		return err
+	}
+	now := db.now()
+	tmeta.Indexes = append(tmeta.Indexes, IndexMeta{
+		Name:      indexName,
+		Kind:      IndexKindFullText,
+		KeyColumn: column,
+		FileBase:  fs.Base,
+		CreatedAt: now,
+		UpdatedAt: now,
+	})
+	return db.writeTableMeta(tmeta)
+}
+
+// fillFullTextIndex builds the full-text index at fs from the texts in
+// column pos of the rows of tbl. On failure the index files are removed.
+func (db *Database) fillFullTextIndex(tbl *heap.Table, pos int, fs storage.LocalFileSet) error {
+	tree, err := btree.OpenKVTree(db.SM, fs, db.viewFor(fs))
+	if err != nil {
+		return err
+	}
+	err = tbl.Scan(func(id heap.TID, row []any) error {
+		return insertFullText(tree, row[pos], id)
+	})
+	if cerr := tree.Close(); err == nil {
+		err = cerr
+	}
+	if err != nil {
+		_ = db.flushAndDropFileSet(fs)
+		_ = btree.DropIndex(db.SM, fs)
+		return err
+	}
+	return nil
+}
+
+// insertFullText adds the entries of v, the text of the row at tid or NULL,
+// to the full-text index tree.
+func insertFullText(tree *btree.KVTree, v any, tid heap.TID) error {
+	text, ok := v.(string)
+	if !ok {
+		return nil
+	}
+	entries, err := fulltext.Entries(text, tid)
+	if err != nil {
+		return err
+	}
+	for _, e := range entries {
+		if err := tree.Insert(e.Key, e.Value); err != nil {
+			return err
+		}
+	}
+	return nil
+}
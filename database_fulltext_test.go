@@ -0,0 +1,55 @@
+package novasql
+
+import (
+	"bytes"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/fulltext"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func TestCreateFullTextIndex(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	docs, err := db.CreateTable("docs", record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64},
+		{Name: "body", Type: record.ColText, Nullable: true},
+	}})
+	require.NoError(t, err)
+	_, err = docs.Insert([]any{int64(1), "Disk error on node 1"})
+	require.NoError(t, err)
+
+	require.ErrorIs(t, db.CreateFullTextIndex("docs", "docs_id", "id"), ErrIndexBadKeyCol)
+	require.ErrorIs(t, db.CreateFullTextIndex("docs", "docs_x", "nope"), ErrIndexBadColumn)
+	require.NoError(t, db.CreateFullTextIndex("docs", "docs_body", "body"))
+	require.ErrorIs(t, db.CreateFullTextIndex("docs", "docs_body", "body"), ErrIndexExists)
+
+	// A bulk load indexes its rows too.
+	_, err = db.InsertRows("docs", [][]any{{int64(2), "disk full"}, {int64(3), nil}})
+	require.NoError(t, err)
+	search := func(query string) int {
+		t.Helper()
+		fs := db.indexFileSet("docs", "docs_body")
+		tree, err := btree.OpenKVTree(db.SM, fs, db.viewFor(fs))
+		require.NoError(t, err)
+		defer func() { _ = tree.Close() }()
+		hits, err := fulltext.Search(tree, query)
+		require.NoError(t, err)
+		return len(hits)
+	}
+	require.Equal(t, 2, search("DISK"))
+	require.Equal(t, 1, search("disk error"))
+
+	var out bytes.Buffer
+	require.NoError(t, db.Dump(&out))
+	require.Contains(t, out.String(), "CREATE INDEX docs_body ON docs USING fulltext (body);\n")
+
+	require.NoError(t, db.DropIndex("docs", "docs_body"))
+	idxs, err := db.ListIndexes("docs")
+	require.NoError(t, err)
+	require.Empty(t, idxs)
+}
@@ -0,0 +1,134 @@
+package novasql
+
+import (
+	"errors"
+	"fmt"
+
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/expr"
+)
+
+var (
+	ErrBadFunction  = errors.New("novasql: invalid function")
+	ErrBadCollation = errors.New("novasql: invalid collation")
+)
+
+// Function is a scalar SQL function added with CreateFunction. Calls are
+// type-checked against Args and Result when a statement is planned.
+type Function struct {
+	Args     []record.ColumnType // one per argument; a FLOAT one takes INTs and DECIMALs too
+	Variadic bool                // the last of Args may repeat, or be left out
+	Result   record.ColumnType
+
+	// Nulls: Call gets NULL (nil) arguments too. Otherwise a call with one is
+	// NULL without Call being made.
+	Nulls bool
+
+	// Call computes the result from the arguments: int64, float64, string,
+	// bool or record.Decimal, Date, Time or Timestamp values as Args says
+	// (int, float32 and time.Time results are converted, and numbers for a
+	// DECIMAL), or nil for NULL. Its error fails the statement.
+	Call func(args []any) (any, error)
+}
+
+// Aggregator computes an aggregate function for one group of rows: Step gets
+// every non-NULL value of the column, then Result gives the value of the
+// group (nil for NULL), as for Function.Call.
+type Aggregator interface {
+	Step(v any) error
+	Result() (any, error)
+}
+
+// AggregateFunction is an aggregate SQL function of one column added with
+// CreateAggregate, used like SUM(col) in the SELECT list of a query.
+type AggregateFunction struct {
+	Arg    record.ColumnType // of the column; a FLOAT one takes INTs too
+	Result record.ColumnType
+
+	// New returns the Aggregator of a new group.
+	New func() Aggregator
+}
+
+// CreateFunction makes fn callable from SQL as name (in any case) on this
+// handle, replacing a function of that name it added before. Built-in
+// functions cannot be replaced. Indexes cannot use added functions, since
+// they must be computable by any handle.
+func (db *Database) CreateFunction(name string, fn Function) error {
+	if fn.Call == nil || fn.Variadic && len(fn.Args) == 0 {
+		return fmt.Errorf("%w: %s: needs Call and, if variadic, Args", ErrBadFunction, name)
+	}
+	if err := validateIdent(name); err != nil {
+		return err
+	}
+	args := make([]record.ColumnType, len(fn.Args))
+	for i, t := range fn.Args {
+		args[i] = functionType(t)
+	}
+	f := &expr.Func{
+		Name:     name,
+		Args:     args,
+		Variadic: fn.Variadic,
+		Result:   functionType(fn.Result),
+		Nulls:    fn.Nulls,
+		Call:     fn.Call,
+	}
+	if err := db.funcs.Register(f); err != nil {
+		return fmt.Errorf("%w: %w", ErrBadFunction, err)
+	}
+	db.bumpSchemaVersion()
+	return nil
+}
+
+// CreateAggregate makes fn callable from SQL as the aggregate name (in any
+// case), like CreateFunction.
+func (db *Database) CreateAggregate(name string, fn AggregateFunction) error {
+	if fn.New == nil {
+		return fmt.Errorf("%w: %s: needs New", ErrBadFunction, name)
+	}
+	if err := validateIdent(name); err != nil {
+		return err
+	}
+	a := &expr.Aggregate{
+		Name:   name,
+		Arg:    functionType(fn.Arg),
+		Result: functionType(fn.Result),
+		New:    func() expr.Accumulator { return fn.New() },
+	}
+	if err := db.funcs.RegisterAggregate(a); err != nil {
+		return fmt.Errorf("%w: %w", ErrBadFunction, err)
+	}
+	db.bumpSchemaVersion()
+	return nil
+}
+
+// CreateCollation makes the collation name (in any case) usable on this
+// handle as "<text> COLLATE name" and "ORDER BY <column> COLLATE name",
+// replacing a collation of that name it added before: compare orders two
+// strings as strings.Compare does. The built-in BINARY and NOCASE cannot be
+// replaced, and are the only collations a column can have, since the
+// indexes of a table must be ordered alike by every handle.
+func (db *Database) CreateCollation(name string, compare func(a, b string) int) error {
+	if compare == nil {
+		return fmt.Errorf("%w: %s: needs a comparison", ErrBadCollation, name)
+	}
+	if err := validateIdent(name); err != nil {
+		return err
+	}
+	if err := db.funcs.RegisterCollation(&expr.Collation{Name: name, Compare: compare}); err != nil {
+		return fmt.Errorf("%w: %w", ErrBadCollation, err)
+	}
+	db.bumpSchemaVersion()
+	return nil
+}
+
+// Functions holds the functions and collations added to this handle, for
+// the planner.
+func (db *Database) Functions() *expr.Registry { return &db.funcs }
+
+// functionType is t as SQL values have it: INT columns are read as int64.
+func functionType(t record.ColumnType) record.ColumnType {
+	if t == record.ColInt32 {
+		return record.ColInt64
+	}
+	return t
+}
@@ -0,0 +1,290 @@
+package novasql
+
+import (
+	"bytes"
+	"errors"
+	"fmt"
+	"hash/crc32"
+	"os"
+	"path/filepath"
+
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/pkg/bx"
+)
+
+var (
+	ErrBadHeader        = errors.New("novasql: bad database header")
+	ErrPageSizeMismatch = errors.New("novasql: page size mismatch")
+	ErrFormatTooNew     = errors.New("novasql: database format is newer than this build")
+	ErrEncryption       = errors.New("novasql: encryption setting does not match the database")
+)
+
+const (
+	headerFile = "header"
+
+	headerMagic uint32 = 0x41564F4E // "NOVA"
+
+	// headerFormatVersion is written by databases without compressed pages;
+	// headerFormatCompressed once pages may be compressed and
+	// headerFormatEncrypted by encrypted databases, so that builds that
+	// cannot read their pages refuse the directory.
+	headerFormatVersion    uint16 = 1
+	headerFormatCompressed uint16 = 2
+	headerFormatEncrypted  uint16 = 3
+
+	// Header page layout (the rest of the page is zero):
+	//   [0..3]   magic
+	//   [4..5]   format version
+	//   [6]      page compression codec (storage.Compression)
+	//   [7]      flags (headerFlagEncrypted)
+	//   [8..11]  page size the files were written with
+	//   [12..15] crc32 (IEEE) of [0..11]
+	//   [16..59] storage.KeyCheck of the encryption key, when encrypted
+	headerFieldsSize = 12
+	headerSize       = headerFieldsSize + 4
+
+	headerFlagEncrypted = 1 << 0
+)
+
+// dbHeader is the fixed page at the start of every database directory that
+// says how its files were written. Table and index files keep starting at
+// page 0, so the header lives in its own file instead of inside them.
+type dbHeader struct {
+	FormatVersion uint16
+	PageSize      uint32
+	Compression   storage.Compression
+	KeyCheck      []byte // nil unless encrypted
+}
+
+// HeaderInfo is what the header of a database directory says about how its
+// files were written.
+type HeaderInfo struct {
+	FormatVersion uint16
+	PageSize      uint32
+	Compression   storage.Compression
+	Encrypted     bool
+}
+
+// InspectHeader decodes the header of the database directory dataDir (e.g.
+// <workdir>/default) without opening the database.
+func InspectHeader(dataDir string) (HeaderInfo, error) {
+	data, err := os.ReadFile(filepath.Join(dataDir, headerFile))
+	if err != nil {
+		return HeaderInfo{}, err
+	}
+	h, err := decodeHeader(data)
+	if err != nil {
+		return HeaderInfo{}, err
+	}
+	return HeaderInfo{
+		FormatVersion: h.FormatVersion,
+		PageSize:      h.PageSize,
+		Compression:   h.Compression,
+		Encrypted:     h.KeyCheck != nil,
+	}, nil
+}
+
+func (db *Database) headerPath() string {
+	return filepath.Join(db.DataDir, headerFile)
+}
+
+// configuredPageSize is storage.page_size from the config, or the built-in
+// page size when unset.
+func (db *Database) configuredPageSize() uint32 {
+	if db.opts.cfg == nil || db.opts.cfg.Storage.PageSize <= 0 {
+		return storage.PageSize
+	}
+	return uint32(db.opts.cfg.Storage.PageSize)
+}
+
+func encodeHeader(h dbHeader) []byte {
+	buf := make([]byte, storage.PageSize)
+	bx.PutU32At(buf, 0, headerMagic)
+	bx.PutU16At(buf, 4, h.FormatVersion)
+	buf[6] = byte(h.Compression)
+	if h.KeyCheck != nil {
+		buf[7] |= headerFlagEncrypted
+		copy(buf[headerSize:], h.KeyCheck)
+	}
+	bx.PutU32At(buf, 8, h.PageSize)
+	bx.PutU32At(buf, headerFieldsSize, crc32.ChecksumIEEE(buf[:headerFieldsSize]))
+	return buf
+}
+
+// decodeHeader only looks at the leading fields, so a header written with a
+// different page size still decodes and can be reported as a mismatch.
+func decodeHeader(b []byte) (dbHeader, error) {
+	if len(b) < headerSize {
+		return dbHeader{}, fmt.Errorf("%w: truncated (%d bytes)", ErrBadHeader, len(b))
+	}
+	if bx.U32At(b, 0) != headerMagic {
+		return dbHeader{}, fmt.Errorf("%w: bad magic", ErrBadHeader)
+	}
+	if bx.U32At(b, headerFieldsSize) != crc32.ChecksumIEEE(b[:headerFieldsSize]) {
+		return dbHeader{}, fmt.Errorf("%w: checksum mismatch", ErrBadHeader)
+	}
+	h := dbHeader{
+		FormatVersion: bx.U16At(b, 4),
+		PageSize:      bx.U32At(b, 8),
+		Compression:   storage.Compression(b[6]),
+	}
+	if b[7]&headerFlagEncrypted != 0 {
+		if len(b) < headerSize+storage.KeyCheckSize {
+			return dbHeader{}, fmt.Errorf("%w: truncated key check", ErrBadHeader)
+		}
+		h.KeyCheck = bytes.Clone(b[headerSize : headerSize+storage.KeyCheckSize])
+	}
+	return h, nil
+}
+
+// openHeader validates the header of db.DataDir before anything else reads
+// its pages. Directories without one (new, or created before headers
+// existed) get one written unless the handle is read-only.
+//
+// It also picks the page codec: storage.compression when set (recorded in
+// the header for the next open), the header's codec otherwise; and sets the
+// encryption key, which must match the header's key check. A new directory
+// is encrypted when a key is configured.
+func (db *Database) openHeader() error {
+	if want := db.configuredPageSize(); want != storage.PageSize {
+		return fmt.Errorf("%w: storage.page_size is %d but this build uses %d",
+			ErrPageSizeMismatch, want, storage.PageSize)
+	}
+	codec, set, err := db.configuredCompression()
+	if err != nil {
+		return err
+	}
+	key, err := db.configuredEncryptionKey()
+	if err != nil {
+		return err
+	}
+
+	data, err := os.ReadFile(db.headerPath())
+	if errors.Is(err, os.ErrNotExist) {
+		h := dbHeader{
+			FormatVersion: headerFormatVersion,
+			PageSize:      storage.PageSize,
+			Compression:   codec,
+		}
+		if key != nil {
+			if err := db.ensureNoTables(); err != nil {
+				return err
+			}
+			if h.KeyCheck, err = storage.KeyCheck(key); err != nil {
+				return err
+			}
+		}
+		if err := db.SM.SetEncryptionKey(key); err != nil {
+			return err
+		}
+		db.SM.SetCompression(codec)
+		if db.opts.readOnly {
+			return nil
+		}
+		return db.writeHeader(h)
+	}
+	if err != nil {
+		return err
+	}
+
+	h, err := decodeHeader(data)
+	if err != nil {
+		return fmt.Errorf("%s: %w", db.headerPath(), err)
+	}
+	if h.FormatVersion > headerFormatEncrypted {
+		return fmt.Errorf("%w: format %d, supported up to %d", ErrFormatTooNew, h.FormatVersion, headerFormatEncrypted)
+	}
+	if h.PageSize != storage.PageSize {
+		return fmt.Errorf("%w: %s was written with %d-byte pages, this build uses %d",
+			ErrPageSizeMismatch, db.DataDir, h.PageSize, storage.PageSize)
+	}
+	if h.Compression > storage.CompressionLZ4 {
+		return fmt.Errorf("%w: %s: codec %d", storage.ErrCompressionUnsupported, db.headerPath(), h.Compression)
+	}
+	if err := db.checkEncryptionKey(h, key); err != nil {
+		return err
+	}
+	if err := db.SM.SetEncryptionKey(key); err != nil {
+		return err
+	}
+	if !set {
+		codec = h.Compression
+	}
+	db.SM.SetCompression(codec)
+	if codec == h.Compression || db.opts.readOnly {
+		return nil
+	}
+	h.Compression = codec
+	return db.writeHeader(h)
+}
+
+// writeHeader writes h, marking the format as compressed whenever pages may
+// be (turning compression off again leaves the compressed pages in place)
+// and as encrypted when they are.
+func (db *Database) writeHeader(h dbHeader) error {
+	if h.Compression != storage.CompressionNone {
+		h.FormatVersion = max(h.FormatVersion, headerFormatCompressed)
+	}
+	if h.KeyCheck != nil {
+		h.FormatVersion = max(h.FormatVersion, headerFormatEncrypted)
+	}
+	return writeFileAtomic(db.headerPath(), encodeHeader(h), 0o644)
+}
+
+// configuredCompression is storage.compression from the config and whether
+// it is set at all.
+func (db *Database) configuredCompression() (storage.Compression, bool, error) {
+	if db.opts.cfg == nil || db.opts.cfg.Storage.Compression == "" {
+		return storage.CompressionNone, false, nil
+	}
+	c, err := storage.ParseCompression(db.opts.cfg.Storage.Compression)
+	return c, true, err
+}
+
+// configuredEncryptionKey is the key of storage.encryption_key or
+// storage.encryption_key_file, nil when neither is set.
+func (db *Database) configuredEncryptionKey() ([]byte, error) {
+	if db.opts.cfg == nil {
+		return nil, nil
+	}
+	st := db.opts.cfg.Storage
+	switch {
+	case st.EncryptionKey != "":
+		return storage.ParseEncryptionKey(st.EncryptionKey)
+	case st.EncryptionKeyFile != "":
+		key, err := storage.ReadEncryptionKeyFile(st.EncryptionKeyFile)
+		if err != nil {
+			return nil, fmt.Errorf("storage.encryption_key_file: %w", err)
+		}
+		return key, nil
+	}
+	return nil, nil
+}
+
+// checkEncryptionKey checks key (nil = none) against the header h.
+func (db *Database) checkEncryptionKey(h dbHeader, key []byte) error {
+	switch {
+	case h.KeyCheck != nil && key == nil:
+		return fmt.Errorf("%w: %s is encrypted; set storage.encryption_key", ErrEncryption, db.DataDir)
+	case h.KeyCheck == nil && key != nil:
+		return fmt.Errorf("%w: %s is not encrypted; only a new database can be", ErrEncryption, db.DataDir)
+	case key != nil:
+		if err := storage.VerifyKeyCheck(key, h.KeyCheck); err != nil {
+			return fmt.Errorf("%s: %w", db.DataDir, err)
+		}
+	}
+	return nil
+}
+
+// ensureNoTables refuses to encrypt a directory that already has plaintext
+// tables (one created before headers existed).
+func (db *Database) ensureNoTables() error {
+	names, err := db.tableMetaNames()
+	if err != nil {
+		return err
+	}
+	if len(names) > 0 {
+		return fmt.Errorf("%w: %s already has tables; only a new database can be encrypted", ErrEncryption, db.DataDir)
+	}
+	return nil
+}
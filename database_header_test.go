@@ -0,0 +1,175 @@
+package novasql
+
+import (
+	"io/fs"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/pkg/bx"
+)
+
+func TestHeader_WrittenAndValidated(t *testing.T) {
+	dir := t.TempDir()
+	seedDatabase(t, dir)
+	path := filepath.Join(dir, "default", headerFile)
+
+	data, err := os.ReadFile(path)
+	require.NoError(t, err)
+	require.Len(t, data, storage.PageSize)
+	h, err := decodeHeader(data)
+	require.NoError(t, err)
+	require.Equal(t, dbHeader{FormatVersion: headerFormatVersion, PageSize: storage.PageSize}, h)
+
+	// Written with other pages: refuse instead of misreading offsets.
+	require.NoError(t, os.WriteFile(path, encodeHeader(dbHeader{FormatVersion: 1, PageSize: 4096}), 0o644))
+	_, err = Options().Open(dir)
+	require.ErrorIs(t, err, ErrPageSizeMismatch)
+
+	require.NoError(t, os.WriteFile(path, encodeHeader(dbHeader{FormatVersion: 9, PageSize: storage.PageSize}), 0o644))
+	_, err = Options().Open(dir)
+	require.ErrorIs(t, err, ErrFormatTooNew)
+
+	corrupt := encodeHeader(dbHeader{FormatVersion: 1, PageSize: storage.PageSize})
+	bx.PutU32At(corrupt, 8, 8193)
+	require.NoError(t, os.WriteFile(path, corrupt, 0o644))
+	_, err = Options().ReadOnly(true).Open(dir)
+	require.ErrorIs(t, err, ErrBadHeader)
+
+	// Databases from before headers existed get one on their next open.
+	require.NoError(t, os.Remove(path))
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	tbl, err := db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 3, countRows(t, tbl))
+	require.NoError(t, db.Close())
+	require.FileExists(t, path)
+}
+
+func TestHeader_ConfiguredPageSize(t *testing.T) {
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.PageSize = 4096
+	_, err := Options().Config(cfg).Open(t.TempDir())
+	require.ErrorIs(t, err, ErrPageSizeMismatch)
+
+	cfg.Storage.PageSize = storage.PageSize
+	db, err := Options().Config(cfg).Open(t.TempDir())
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+}
+
+func TestHeader_Compression(t *testing.T) {
+	dir := t.TempDir()
+	path := filepath.Join(dir, "default", headerFile)
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.Compression = "lz4"
+
+	db, err := Options().Config(cfg).Open(dir)
+	require.NoError(t, err)
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 300 {
+		_, err := tbl.Insert([]any{int64(i), "a user name that repeats a lot"})
+		require.NoError(t, err)
+	}
+	require.NoError(t, db.Close())
+
+	data, err := os.ReadFile(path)
+	require.NoError(t, err)
+	h, err := decodeHeader(data)
+	require.NoError(t, err)
+	require.Equal(t, storage.CompressionLZ4, h.Compression)
+	require.Equal(t, headerFormatCompressed, h.FormatVersion)
+
+	// Without storage.compression the header's codec stays in use.
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	tbl, err = db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 300, countRows(t, tbl))
+	bad, err := db.VerifyIntegrity()
+	require.NoError(t, err)
+	require.Empty(t, bad)
+	require.NoError(t, db.Close())
+
+	// Turning it off is recorded; the format stays compressed.
+	cfg.Storage.Compression = "none"
+	db, err = Options().Config(cfg).Open(dir)
+	require.NoError(t, err)
+	tbl, err = db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 300, countRows(t, tbl))
+	require.NoError(t, db.Close())
+	data, err = os.ReadFile(path)
+	require.NoError(t, err)
+	h, err = decodeHeader(data)
+	require.NoError(t, err)
+	require.Equal(t, dbHeader{FormatVersion: headerFormatCompressed, PageSize: storage.PageSize}, h)
+
+	cfg.Storage.Compression = "zstd"
+	_, err = Options().Config(cfg).Open(dir)
+	require.ErrorIs(t, err, storage.ErrCompressionUnsupported)
+}
+
+func TestHeader_Encryption(t *testing.T) {
+	dir := t.TempDir()
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.EncryptionKey = strings.Repeat("ab", 32)
+
+	db, err := Options().Config(cfg).Open(dir)
+	require.NoError(t, err)
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 300 {
+		_, err := tbl.Insert([]any{int64(i), "confidential name"})
+		require.NoError(t, err)
+	}
+	require.NoError(t, db.Checkpoint())
+	require.NoError(t, db.Close())
+
+	// Neither the table files nor the WAL hold the rows in the clear.
+	err = filepath.WalkDir(filepath.Join(dir, "default"), func(path string, d fs.DirEntry, err error) error {
+		if err != nil || d.IsDir() || strings.HasSuffix(path, ".json") {
+			return err
+		}
+		data, err := os.ReadFile(path)
+		require.NoError(t, err)
+		require.NotContains(t, string(data), "confidential", path)
+		return nil
+	})
+	require.NoError(t, err)
+
+	data, err := os.ReadFile(filepath.Join(dir, "default", headerFile))
+	require.NoError(t, err)
+	h, err := decodeHeader(data)
+	require.NoError(t, err)
+	require.Equal(t, headerFormatEncrypted, h.FormatVersion)
+	require.Len(t, h.KeyCheck, storage.KeyCheckSize)
+
+	db, err = Options().Config(cfg).Open(dir)
+	require.NoError(t, err)
+	tbl, err = db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 300, countRows(t, tbl))
+	require.NoError(t, db.Close())
+
+	_, err = Options().Open(dir)
+	require.ErrorIs(t, err, ErrEncryption)
+	cfg.Storage.EncryptionKey = strings.Repeat("cd", 32)
+	_, err = Options().Config(cfg).Open(dir)
+	require.ErrorIs(t, err, storage.ErrEncryptionKey)
+
+	// An existing plaintext database cannot be encrypted in place.
+	plain := t.TempDir()
+	db, err = Options().Open(plain)
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+	_, err = Options().Config(cfg).Open(plain)
+	require.ErrorIs(t, err, ErrEncryption)
+}
@@ -1,18 +1,31 @@
 package novasql
 
 import (
+	"cmp"
 	"errors"
+	"fmt"
 	"os"
+	"slices"
 	"time"
 
 	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/expr"
+	"github.com/tuannm99/novasql/internal/sql/parser"
 	"github.com/tuannm99/novasql/internal/storage"
 )
 
 type IndexKind string
 
 const (
-	IndexKindBTree IndexKind = "btree"
+	IndexKindBTree    IndexKind = "btree"
+	// IndexKindKVTree is an ordered index over any column type, stored in a
+	// btree.KVTree (see CreateIndex).
+	IndexKindKVTree   IndexKind = "kvtree"
+	// IndexKindFullText is an inverted index over the terms of a TEXT column,
+	// stored in a btree.KVTree (see CreateFullTextIndex).
+	IndexKindFullText IndexKind = "fulltext"
 )
 
 var (
@@ -23,6 +36,7 @@ var (
 	ErrIndexBadName   = errors.New("novasql: invalid index name")
 	ErrIndexBadTable  = errors.New("novasql: invalid table name")
 	ErrIndexBadKeyCol = errors.New("novasql: invalid key column")
+	ErrIndexBadExpr   = errors.New("novasql: invalid index expression")
 )
 
 // IndexMeta is stored inside TableMeta (table.meta.json).
@@ -33,6 +47,113 @@ type IndexMeta struct {
 	FileBase  string    `json:"file_base"` // LocalFileSet.Base (segments live in db.tableDir())
 	CreatedAt time.Time `json:"created_at"`
 	UpdatedAt time.Time `json:"updated_at"`
+
+	// Constraint is set on the KVTree index of a PRIMARY KEY or UNIQUE
+	// constraint: no two rows may have the same non-NULL value.
+	Constraint ConstraintKind `json:"constraint,omitempty"`
+
+	// Expression is set, and KeyColumn empty, on the KVTree index of an
+	// expression over the columns of the table (see CreateExpressionIndex).
+	Expression string `json:"expression,omitempty"`
+}
+
+// KeyFunc returns the key of the index for a row of schema: the value of its
+// column, or of its expression, text as the collation of either orders it
+// (see Collation).
+func (im IndexMeta) KeyFunc(schema record.Schema) (func(row []any) (any, error), error) {
+	if im.Expression == "" {
+		pos := slices.IndexFunc(schema.Cols, func(c record.Column) bool { return c.Name == im.KeyColumn })
+		if pos < 0 {
+			return nil, fmt.Errorf("%w: %s", ErrIndexBadColumn, im.KeyColumn)
+		}
+		coll, err := columnCollation(schema.Cols[pos])
+		if err != nil {
+			return nil, err
+		}
+		return func(row []any) (any, error) { return coll.IndexKey(row[pos]), nil }, nil
+	}
+	e, err := bindIndexExpr(schema, im.Expression)
+	if err != nil {
+		return nil, err
+	}
+	coll := e.Collation()
+	return func(row []any) (any, error) {
+		v, err := e.Eval(row)
+		return coll.IndexKey(v), err
+	}, nil
+}
+
+// Collation returns the collation the index orders text by, nil for
+// bytewise: that of its column or its expression. A text v is found in the
+// index as Collation(schema).IndexKey(v).
+func (im IndexMeta) Collation(schema record.Schema) (*expr.Collation, error) {
+	if im.Expression == "" {
+		pos := slices.IndexFunc(schema.Cols, func(c record.Column) bool { return c.Name == im.KeyColumn })
+		if pos < 0 {
+			return nil, fmt.Errorf("%w: %s", ErrIndexBadColumn, im.KeyColumn)
+		}
+		return columnCollation(schema.Cols[pos])
+	}
+	e, err := bindIndexExpr(schema, im.Expression)
+	if err != nil {
+		return nil, err
+	}
+	return e.Collation(), nil
+}
+
+// columnCollation is the collation of col, nil for BINARY. Only built-in
+// collations can be those of columns.
+func columnCollation(col record.Column) (*expr.Collation, error) {
+	if col.Collation == "" {
+		return nil, nil
+	}
+	coll, ok := expr.LookupCollation(col.Collation)
+	if !ok {
+		return nil, fmt.Errorf("%w: %s of column %s", ErrBadCollation, col.Collation, col.Name)
+	}
+	return coll, nil
+}
+
+// usesColumn reports whether the key of the index is computed from column.
+func (im IndexMeta) usesColumn(schema record.Schema, column string) bool {
+	if im.Expression == "" {
+		return im.KeyColumn == column
+	}
+	e, err := bindIndexExpr(schema, im.Expression)
+	if err != nil {
+		return false
+	}
+	pos := slices.IndexFunc(schema.Cols, func(c record.Column) bool { return c.Name == column })
+	return slices.Contains(e.Columns(), pos)
+}
+
+// bindIndexExpr binds the expression src of an index to the columns of
+// schema. Only built-in functions may be called: functions added with
+// CreateFunction belong to one handle.
+func bindIndexExpr(schema record.Schema, src string) (*expr.Expr, error) {
+	pe, err := parser.ParseExpr(src)
+	if err != nil {
+		return nil, fmt.Errorf("%w: %s: %w", ErrIndexBadExpr, src, err)
+	}
+	e, err := expr.Bind(pe, schema, func(name string) (int, error) {
+		if pos := slices.IndexFunc(schema.Cols, func(c record.Column) bool { return c.Name == name }); pos >= 0 {
+			return pos, nil
+		}
+		return -1, fmt.Errorf("%w: %s", ErrIndexBadColumn, name)
+	}, nil)
+	if err != nil {
+		return nil, err
+	}
+	if e.Volatile() {
+		return nil, fmt.Errorf("%w: %s changes from call to call", ErrIndexBadExpr, src)
+	}
+	return e, nil
+}
+
+// hasFiles reports whether the index is stored in its own paged files next to
+// the table (which then have to follow the table on drop, rename and move).
+func (im IndexMeta) hasFiles() bool {
+	return im.Kind == IndexKindBTree || im.Kind == IndexKindKVTree || im.Kind == IndexKindFullText
 }
 
 func (db *Database) ListIndexes(table string) ([]IndexMeta, error) {
@@ -77,7 +198,7 @@ func (db *Database) indexFileSet(table, index string) storage.LocalFileSet {
 // CreateBTreeIndex registers an index and creates a new BTree handle.
 // NOTE: This does not backfill existing rows yet (phase2 minimal).
 func (db *Database) CreateBTreeIndex(table, indexName, keyColumn string) (*btree.Tree, error) {
-	if err := db.ensureOpen(); err != nil {
+	if err := db.ensureWritable(); err != nil {
 		return nil, err
 	}
 	if err := validateIdent(table); err != nil {
@@ -107,7 +228,7 @@ func (db *Database) CreateBTreeIndex(table, indexName, keyColumn string) (*btree
 
 	tree := btree.NewTree(db.SM, fs, bp)
 
-	now := time.Now()
+	now := db.now()
 	tmeta.Indexes = append(tmeta.Indexes, IndexMeta{
 		Name:      indexName,
 		Kind:      IndexKindBTree,
@@ -123,6 +244,239 @@ func (db *Database) CreateBTreeIndex(table, indexName, keyColumn string) (*btree
 	return tree, nil
 }
 
+// CreateIndex registers a KVTree index over column and fills it from the rows
+// already in the table. Unlike CreateBTreeIndex it takes keys of any column
+// type in any order, so the SQL layer keeps it in step with INSERT, UPDATE
+// and DELETE (see btree.IndexEntryKey for the entry layout).
+func (db *Database) CreateIndex(table, indexName, column string) error {
+	return db.createKVIndex(table, indexName, column, "")
+}
+
+// createKVIndex is CreateIndex for an index that may back a constraint.
+func (db *Database) createKVIndex(table, indexName, column string, constraint ConstraintKind) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	if err := validateIdent(table); err != nil {
+		return ErrIndexBadTable
+	}
+	if err := validateIdent(indexName); err != nil {
+		return ErrIndexBadName
+	}
+	if err := validateIdent(column); err != nil {
+		return ErrIndexBadKeyCol
+	}
+
+	tmeta, err := db.readTableMeta(table)
+	if err != nil {
+		return err
+	}
+	pos := slices.IndexFunc(tmeta.Schema.Cols, func(c record.Column) bool { return c.Name == column })
+	if pos < 0 {
+		return ErrIndexBadColumn
+	}
+	if _, im := db.findIndexMeta(tmeta, indexName); im != nil {
+		return ErrIndexExists
+	}
+	key, err := IndexMeta{KeyColumn: column}.KeyFunc(tmeta.Schema)
+	if err != nil {
+		return err
+	}
+	tbl, err := db.OpenTable(table)
+	if err != nil {
+		return err
+	}
+
+	_ = os.MkdirAll(db.TableDir(), 0o755)
+	fs := db.indexFileSet(table, indexName)
+	if err := db.fillIndex(tbl, key, fs); err != nil {
+		return err
+	}
+	if constraint != "" {
+		if err := db.checkUniqueIndex(fs); err != nil {
+			_ = db.flushAndDropFileSet(fs)
+			_ = btree.DropIndex(db.SM, fs)
+			if errors.Is(err, ErrConstraintViolation) {
+				err = &ConstraintError{Kind: constraint, Constraint: indexName, Table: table, Column: column}
+			}
+			return err
+		}
+	}
+
+	now := db.now()
+	tmeta.Indexes = append(tmeta.Indexes, IndexMeta{
+		Name:       indexName,
+		Kind:       IndexKindKVTree,
+		KeyColumn:  column,
+		FileBase:   fs.Base,
+		CreatedAt:  now,
+		UpdatedAt:  now,
+		Constraint: constraint,
+	})
+	return db.writeTableMeta(tmeta)
+}
+
+// CreateExpressionIndex registers a KVTree index over the value expression,
+// over the columns of table, has for each row ("LOWER(name)") and fills it.
+// The SQL layer keeps it in step with the rows like a CreateIndex one, and
+// reads it for conditions comparing the same expression with a value.
+func (db *Database) CreateExpressionIndex(table, indexName, expression string) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	if err := validateIdent(table); err != nil {
+		return ErrIndexBadTable
+	}
+	if err := validateIdent(indexName); err != nil {
+		return ErrIndexBadName
+	}
+
+	tmeta, err := db.readTableMeta(table)
+	if err != nil {
+		return err
+	}
+	e, err := bindIndexExpr(tmeta.Schema, expression)
+	if err != nil {
+		return err
+	}
+	if _, im := db.findIndexMeta(tmeta, indexName); im != nil {
+		return ErrIndexExists
+	}
+	tbl, err := db.OpenTable(table)
+	if err != nil {
+		return err
+	}
+
+	_ = os.MkdirAll(db.TableDir(), 0o755)
+	fs := db.indexFileSet(table, indexName)
+	key, err := IndexMeta{Expression: e.String()}.KeyFunc(tmeta.Schema)
+	if err != nil {
+		return err
+	}
+	if err := db.fillIndex(tbl, key, fs); err != nil {
+		return err
+	}
+	now := db.now()
+	tmeta.Indexes = append(tmeta.Indexes, IndexMeta{
+		Name:       indexName,
+		Kind:       IndexKindKVTree,
+		FileBase:   fs.Base,
+		CreatedAt:  now,
+		UpdatedAt:  now,
+		Expression: e.String(),
+	})
+	return db.writeTableMeta(tmeta)
+}
+
+// fillIndex builds the KVTree index at fs from the rows of tbl, keyed by
+// what key returns for them. On failure the index files are removed.
+func (db *Database) fillIndex(tbl *heap.Table, key func(row []any) (any, error), fs storage.LocalFileSet) error {
+	tree, err := btree.OpenKVTree(db.SM, fs, db.viewFor(fs))
+	if err != nil {
+		return err
+	}
+	err = tbl.Scan(func(id heap.TID, row []any) error {
+		v, err := key(row)
+		if err != nil || v == nil {
+			return err
+		}
+		entry, err := btree.IndexEntryKey(v, id)
+		if err != nil {
+			return err
+		}
+		return tree.Insert(entry, nil)
+	})
+	if cerr := tree.Close(); err == nil {
+		err = cerr
+	}
+	if err != nil {
+		_ = db.flushAndDropFileSet(fs)
+		_ = btree.DropIndex(db.SM, fs)
+		return err
+	}
+	return nil
+}
+
+// fillBTreeIndex builds the B-tree index at fs from the int64 keys of
+// column pos of the rows scan visits, inserted in key order as the tree
+// requires.
+func (db *Database) fillBTreeIndex(scan heapScan, pos int, fs storage.LocalFileSet) error {
+	type entry struct {
+		key int64
+		tid heap.TID
+	}
+	var entries []entry
+	err := scan(func(id heap.TID, row []any) error {
+		if k, ok := row[pos].(int64); ok {
+			entries = append(entries, entry{key: k, tid: id})
+		}
+		return nil
+	})
+	if err != nil {
+		return err
+	}
+	slices.SortStableFunc(entries, func(a, b entry) int { return cmp.Compare(a.key, b.key) })
+
+	tree := btree.NewTree(db.SM, fs, db.viewFor(fs))
+	for _, e := range entries {
+		if err = tree.Insert(e.key, e.tid); err != nil {
+			break
+		}
+	}
+	if cerr := tree.Close(); err == nil {
+		err = cerr
+	}
+	return err
+}
+
+// heapScan is Table.Scan or a Table.ScanTx of some transaction.
+type heapScan func(fn func(id heap.TID, row []any) error) error
+
+// rebuildIndexes recreates every index of meta from the rows of the table,
+// once its TIDs or its index pages can no longer be trusted.
+func (db *Database) rebuildIndexes(meta *TableMeta) error {
+	if !slices.ContainsFunc(meta.Indexes, IndexMeta.hasFiles) {
+		return nil
+	}
+	tbl, err := db.OpenTable(meta.Name)
+	if err != nil {
+		return err
+	}
+	for _, im := range meta.Indexes {
+		if !im.hasFiles() {
+			continue
+		}
+		key, err := im.KeyFunc(meta.Schema)
+		if err != nil {
+			return fmt.Errorf("%s: %w", meta.Name, err)
+		}
+		pos := slices.IndexFunc(meta.Schema.Cols, func(c record.Column) bool { return c.Name == im.KeyColumn })
+		base := im.FileBase
+		if base == "" {
+			base = db.fmtIndexBase(meta.Name, im.Name)
+		}
+		fs := storage.LocalFileSet{Dir: db.TableDir(), Base: base}
+		if err := db.flushAndDropFileSet(fs); err != nil {
+			return err
+		}
+		if err := btree.DropIndex(db.SM, fs); err != nil {
+			return err
+		}
+		switch im.Kind {
+		case IndexKindBTree:
+			err = db.fillBTreeIndex(tbl.Scan, pos, fs)
+		case IndexKindFullText:
+			err = db.fillFullTextIndex(tbl, pos, fs)
+		default:
+			err = db.fillIndex(tbl, key, fs)
+		}
+		if err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
 func (db *Database) OpenBTreeIndex(table, indexName string) (*btree.Tree, error) {
 	if err := db.ensureOpen(); err != nil {
 		return nil, err
@@ -159,9 +513,37 @@ func (db *Database) OpenBTreeIndex(table, indexName string) (*btree.Tree, error)
 	return btree.OpenTree(db.SM, fs, bp)
 }
 
+// OpenKVTreeIndex opens an index created by CreateIndex.
+func (db *Database) OpenKVTreeIndex(table, indexName string) (*btree.KVTree, error) {
+	if err := db.ensureOpen(); err != nil {
+		return nil, err
+	}
+	if err := validateIdent(table); err != nil {
+		return nil, ErrIndexBadTable
+	}
+	if err := validateIdent(indexName); err != nil {
+		return nil, ErrIndexBadName
+	}
+
+	tmeta, err := db.readTableMeta(table)
+	if err != nil {
+		return nil, err
+	}
+	_, im := db.findIndexMeta(tmeta, indexName)
+	if im == nil {
+		return nil, ErrIndexNotFound
+	}
+	if im.Kind != IndexKindKVTree {
+		return nil, ErrIndexBadKind
+	}
+
+	fs := storage.LocalFileSet{Dir: db.TableDir(), Base: im.FileBase}
+	return btree.OpenKVTree(db.SM, fs, db.viewFor(fs))
+}
+
 // IMPORTANT: flush/drop from global pool BEFORE deleting files.
 func (db *Database) DropIndex(table, indexName string) error {
-	if err := db.ensureOpen(); err != nil {
+	if err := db.ensureWritable(); err != nil {
 		return err
 	}
 	if err := validateIdent(table); err != nil {
@@ -180,9 +562,14 @@ func (db *Database) DropIndex(table, indexName string) error {
 	if im == nil {
 		return ErrIndexNotFound
 	}
-	if im.Kind != IndexKindBTree {
+	if !im.hasFiles() {
 		return ErrIndexBadKind
 	}
+	if im.Constraint != "" {
+		if err := db.checkNotReferenced(table, im.KeyColumn); err != nil {
+			return err
+		}
+	}
 
 	base := im.FileBase
 	if base == "" {
@@ -196,7 +583,7 @@ func (db *Database) DropIndex(table, indexName string) error {
 	}
 
 	// Drop index files.
-	if err := btree.DropIndex(fs); err != nil {
+	if err := btree.DropIndex(db.SM, fs); err != nil {
 		return err
 	}
 
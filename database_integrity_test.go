@@ -0,0 +1,54 @@
+package novasql
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+func TestVerifyIntegrity(t *testing.T) {
+	dir := t.TempDir()
+	seedDatabase(t, dir)
+
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	_, err = db.CreateBTreeIndex("users", "users_id", "id")
+	require.NoError(t, err)
+	bad, err := db.VerifyIntegrity()
+	require.NoError(t, err)
+	require.Empty(t, bad)
+	require.NoError(t, db.Close())
+
+	// Flip one tuple byte of heap page 0 without fixing its checksum.
+	heapPath := filepath.Join(dir, "default", "tables", "users")
+	data, err := os.ReadFile(heapPath)
+	require.NoError(t, err)
+	data[storage.PageSize-30] ^= 0xff
+	require.NoError(t, os.WriteFile(heapPath, data, 0o644))
+
+	// Read-only: WAL replay would otherwise rewrite the page.
+	db, err = Options().ReadOnly(true).Open(dir)
+	require.NoError(t, err)
+
+	bad, err = db.VerifyIntegrity()
+	require.NoError(t, err)
+	require.Len(t, bad, 1)
+	require.Equal(t, "users", bad[0].Base)
+	require.Equal(t, uint32(0), bad[0].PageID)
+
+	tbl, err := db.OpenTable("users")
+	require.NoError(t, err)
+	err = tbl.Scan(func(heap.TID, []any) error { return nil })
+	var corrupted *CorruptedPageError
+	require.ErrorAs(t, err, &corrupted)
+	require.ErrorIs(t, err, storage.ErrPageCorrupted)
+	require.NoError(t, db.Close())
+
+	_, err = Options().ReadOnly(true).OpenCheck(CheckFull).Open(dir)
+	require.ErrorIs(t, err, ErrOpenCheckFailed)
+}
@@ -0,0 +1,188 @@
+package novasql
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+)
+
+var ErrLayoutMismatch = errors.New("novasql: storage layout does not match the database manifest")
+
+const (
+	// layoutFile is the manifest of a database directory: where its data
+	// files, WAL and temporary files live.
+	layoutFile    = "manifest.json"
+	layoutVersion = 1
+)
+
+// Layout is where the files of a database live. Data holds the table and
+// index files and their metadata (always the tables subdirectory); WAL the
+// write-ahead log (storage.wal_dir); Temp the spill files of large sorts and
+// temporary tables (storage.temp_dir). The database directory itself keeps
+// the header, the transaction log and the manifest recording the layout.
+type Layout struct {
+	Data string `json:"data"`
+	WAL  string `json:"wal"`
+	Temp string `json:"temp"`
+}
+
+type layoutManifest struct {
+	Version int `json:"version"`
+	Layout
+}
+
+// Layout is the layout of the selected database, with absolute paths.
+func (db *Database) Layout() Layout {
+	return db.layout
+}
+
+// configuredLayout is the layout storage.wal_dir and storage.temp_dir give
+// the database in dir: a subdirectory per database under each, or inside
+// dir when unset.
+func (db *Database) configuredLayout(dir string) Layout {
+	l := Layout{
+		Data: filepath.Join(dir, "tables"),
+		WAL:  filepath.Join(dir, "wal"),
+		Temp: filepath.Join(dir, "tmp"),
+	}
+	if cfg := db.opts.cfg; cfg != nil {
+		if cfg.Storage.WALDir != "" {
+			l.WAL = filepath.Join(cfg.Storage.WALDir, filepath.Base(dir))
+		}
+		if cfg.Storage.TempDir != "" {
+			l.Temp = filepath.Join(cfg.Storage.TempDir, filepath.Base(dir))
+		}
+	}
+	return l
+}
+
+// layoutOf reads the manifest of the database in dir; ok is false without
+// one.
+func layoutOf(dir string) (l Layout, ok bool, err error) {
+	data, err := os.ReadFile(filepath.Join(dir, layoutFile))
+	if errors.Is(err, os.ErrNotExist) {
+		return Layout{}, false, nil
+	}
+	if err != nil {
+		return Layout{}, false, err
+	}
+	var m layoutManifest
+	if err := json.Unmarshal(data, &m); err != nil {
+		return Layout{}, false, fmt.Errorf("%s: %w", layoutFile, err)
+	}
+	if m.Version > layoutVersion {
+		return Layout{}, false, fmt.Errorf("%w: %s version %d, supported up to %d",
+			ErrFormatTooNew, layoutFile, m.Version, layoutVersion)
+	}
+	abs := func(p string) string {
+		if filepath.IsAbs(p) {
+			return p
+		}
+		return filepath.Join(dir, p)
+	}
+	return Layout{Data: abs(m.Data), WAL: abs(m.WAL), Temp: abs(m.Temp)}, true, nil
+}
+
+// openLayout loads the manifest of db.DataDir and records the configured
+// layout in it (read-write handles only). The WAL stays where the manifest
+// says, so a database keeps finding it when storage.wal_dir is removed from
+// the config; setting storage.wal_dir to another place fails with
+// ErrLayoutMismatch, since the log would have to be moved first. Databases
+// created before manifests have their WAL inside their directory. Temporary
+// files do not outlive a handle and simply follow storage.temp_dir.
+func (db *Database) openLayout() error {
+	want := db.configuredLayout(db.DataDir)
+	have, ok, err := layoutOf(db.DataDir)
+	if err != nil {
+		return err
+	}
+	recorded := have
+	if !ok && dirExists(filepath.Join(db.DataDir, "wal")) {
+		have, ok = Layout{Data: want.Data, WAL: filepath.Join(db.DataDir, "wal"), Temp: want.Temp}, true
+	}
+	if ok {
+		if walSet := db.opts.cfg != nil && db.opts.cfg.Storage.WALDir != ""; walSet &&
+			filepath.Clean(want.WAL) != filepath.Clean(have.WAL) {
+			return fmt.Errorf("%w: %s has its WAL in %s, not %s", ErrLayoutMismatch, db.DataDir, have.WAL, want.WAL)
+		}
+		want.WAL = have.WAL
+	}
+	db.layout = want
+	if db.opts.readOnly || recorded == want {
+		return nil
+	}
+	return db.writeLayout()
+}
+
+// writeLayout records db.layout in the manifest, with paths inside the
+// database directory relative to it.
+func (db *Database) writeLayout() error {
+	rel := func(p string) string {
+		if r, err := filepath.Rel(db.DataDir, p); err == nil && filepath.IsLocal(r) {
+			return r
+		}
+		return p
+	}
+	data, err := json.MarshalIndent(layoutManifest{
+		Version: layoutVersion,
+		Layout:  Layout{Data: rel(db.layout.Data), WAL: rel(db.layout.WAL), Temp: rel(db.layout.Temp)},
+	}, "", "  ")
+	if err != nil {
+		return err
+	}
+	return writeFileAtomic(filepath.Join(db.DataDir, layoutFile), data, 0o644)
+}
+
+// TempDir returns the directory of this handle's temporary files, created
+// on first use under the Temp directory of the layout and removed, with its
+// contents, when the handle closes or selects another database.
+func (db *Database) TempDir() (string, error) {
+	if err := db.ensureOpen(); err != nil {
+		return "", err
+	}
+	if db.tempDir != "" {
+		return db.tempDir, nil
+	}
+	if err := os.MkdirAll(db.layout.Temp, 0o755); err != nil {
+		return "", err
+	}
+	dir, err := os.MkdirTemp(db.layout.Temp, "handle-")
+	if err != nil {
+		return "", err
+	}
+	db.tempDir = dir
+	return dir, nil
+}
+
+// releaseTempDir removes the temporary files and tables of the handle.
+func (db *Database) releaseTempDir() error {
+	if db.tempDir == "" {
+		return nil
+	}
+	if err := db.discardTempTables(); err != nil {
+		return err
+	}
+	err := os.RemoveAll(db.tempDir)
+	db.tempDir = ""
+	return err
+}
+
+// removeLayout removes the WAL and temporary directories of the database in
+// dir that live outside it, for DropDatabase.
+func removeLayout(dir string) error {
+	l, ok, err := layoutOf(dir)
+	if err != nil || !ok {
+		return err
+	}
+	for _, p := range []string{l.WAL, l.Temp} {
+		if r, err := filepath.Rel(dir, p); err == nil && filepath.IsLocal(r) {
+			continue
+		}
+		if err := os.RemoveAll(p); err != nil {
+			return err
+		}
+	}
+	return nil
+}
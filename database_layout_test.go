@@ -0,0 +1,69 @@
+package novasql
+
+import (
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal"
+)
+
+func TestDatabase_Layout(t *testing.T) {
+	dir := t.TempDir()
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	def := filepath.Join(dir, "default")
+	require.Equal(t, Layout{
+		Data: filepath.Join(def, "tables"),
+		WAL:  filepath.Join(def, "wal"),
+		Temp: filepath.Join(def, "tmp"),
+	}, db.Layout())
+	require.FileExists(t, filepath.Join(def, layoutFile))
+
+	// Temporary files live as long as the handle.
+	tmp, err := db.TempDir()
+	require.NoError(t, err)
+	require.DirExists(t, tmp)
+	again, err := db.TempDir()
+	require.NoError(t, err)
+	require.Equal(t, tmp, again)
+	require.NoError(t, db.Close())
+	require.NoDirExists(t, tmp)
+
+	// storage.wal_dir moves the WAL of new databases; the manifest keeps it
+	// there when the setting goes away.
+	walRoot, tmpRoot := t.TempDir(), t.TempDir()
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.Workdir = t.TempDir()
+	cfg.Storage.WALDir = walRoot
+	cfg.Storage.TempDir = tmpRoot
+	db, err = Options().Config(cfg).Open("")
+	require.NoError(t, err)
+	require.Equal(t, filepath.Join(cfg.Storage.Workdir, "default"), db.DataDir)
+	require.Equal(t, filepath.Join(walRoot, "default"), db.Layout().WAL)
+	require.Equal(t, filepath.Join(tmpRoot, "default"), db.Layout().Temp)
+	_, err = db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	require.FileExists(t, filepath.Join(walRoot, "default", "wal.log"))
+	require.NoDirExists(t, filepath.Join(db.DataDir, "wal"))
+	require.NoError(t, db.CreateDatabase("shop"))
+	_, err = db.SelectDatabase("shop")
+	require.NoError(t, err)
+	require.DirExists(t, filepath.Join(walRoot, "shop"))
+	_, err = db.DropDatabase("shop")
+	require.NoError(t, err)
+	require.NoDirExists(t, filepath.Join(walRoot, "shop"))
+	require.NoError(t, db.Close())
+
+	db, err = Options().Open(cfg.Storage.Workdir)
+	require.NoError(t, err)
+	require.Equal(t, filepath.Join(walRoot, "default"), db.Layout().WAL)
+	require.Equal(t, filepath.Join(db.DataDir, "tmp"), db.Layout().Temp)
+	require.NoError(t, db.Close())
+
+	cfg.Storage.WALDir = t.TempDir()
+	_, err = Options().Config(cfg).Open("")
+	require.ErrorIs(t, err, ErrLayoutMismatch)
+	require.Equal(t, CodeIncompatible, CodeOf(err))
+}
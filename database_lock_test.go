@@ -0,0 +1,42 @@
+//go:build linux || darwin
+
+package novasql
+
+import (
+	"os"
+	"path/filepath"
+	"syscall"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+func TestOptions_LockTimeout(t *testing.T) {
+	dir := t.TempDir()
+	seedDatabase(t, dir)
+
+	// other stands for another process holding the lock.
+	other, err := os.Open(filepath.Join(dir, storage.LockFileName))
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = other.Close() })
+	require.NoError(t, syscall.Flock(int(other.Fd()), syscall.LOCK_EX))
+
+	_, err = Options().Open(dir)
+	require.ErrorIs(t, err, storage.ErrLocked)
+	start := time.Now()
+	_, err = Options().LockTimeout(50 * time.Millisecond).Open(dir)
+	require.ErrorIs(t, err, storage.ErrLocked)
+	require.GreaterOrEqual(t, time.Since(start), 50*time.Millisecond)
+
+	// Open waits for the lock to be released.
+	go func() {
+		time.Sleep(50 * time.Millisecond)
+		_ = syscall.Flock(int(other.Fd()), syscall.LOCK_UN)
+	}()
+	db, err := Options().LockTimeout(10 * time.Second).Open(dir)
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+}
@@ -0,0 +1,21 @@
+package novasql
+
+import "github.com/tuannm99/novasql/internal/metrics"
+
+// Metrics returns what the handle has counted since it was opened: buffer
+// pool hits and misses, pages read and written, WAL bytes and fsyncs,
+// transaction commits and aborts, and statement latencies (recorded by the
+// SQL executor). Handles opened with a shared registry (OpenOptions.Metrics)
+// all report its totals.
+func (db *Database) Metrics() metrics.Snapshot {
+	return db.MetricsRegistry().Snapshot()
+}
+
+// MetricsRegistry is the registry the handle counts into, nil for a nil
+// handle; its updates are then ignored.
+func (db *Database) MetricsRegistry() *metrics.Registry {
+	if db == nil {
+		return nil
+	}
+	return db.opts.metrics
+}
@@ -0,0 +1,69 @@
+package novasql
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/metrics"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+func TestDatabase_Metrics(t *testing.T) {
+	reg := metrics.New()
+	db, err := Options().Metrics(reg).Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	require.Same(t, reg, db.MetricsRegistry())
+
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 20 {
+		_, err := tbl.Insert([]any{int64(i), "user"})
+		require.NoError(t, err)
+	}
+	require.Equal(t, 20, countRows(t, tbl))
+	m := db.Metrics()
+	require.Positive(t, m.Get(metrics.PoolHits))
+	require.Positive(t, m.Get(metrics.PoolMisses))
+	require.Positive(t, m.Get(metrics.WALBytes))
+	require.Positive(t, m.HitRatio())
+
+	// Checkpoints write the dirty pages and fsync the log.
+	require.NoError(t, db.Checkpoint())
+	after := db.Metrics()
+	require.Greater(t, after.Get(metrics.PagesWritten), m.Get(metrics.PagesWritten))
+	require.Greater(t, after.Get(metrics.WALSyncs), m.Get(metrics.WALSyncs))
+
+	// Page transactions count as they finish.
+	fs := storage.LocalFileSet{Dir: db.TableDir(), Base: "raw"}
+	tx, err := db.Begin()
+	require.NoError(t, err)
+	require.NoError(t, tx.WritePage(fs, 0, txPut(t, tx, fs, 0, "a")))
+	require.NoError(t, tx.Commit())
+	tx, err = db.Begin()
+	require.NoError(t, err)
+	require.NoError(t, tx.Rollback())
+	snap, err := db.BeginSnapshot()
+	require.NoError(t, err)
+	require.NoError(t, snap.Commit())
+	m = db.Metrics()
+	require.Equal(t, uint64(2), m.Get(metrics.TxCommits))
+	require.Equal(t, uint64(1), m.Get(metrics.TxAborts))
+
+	// A second handle on the registry adds to the same totals.
+	other, err := Options().Metrics(reg).Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = other.Close() })
+	tx, err = other.Begin()
+	require.NoError(t, err)
+	require.NoError(t, tx.Commit())
+	require.Equal(t, uint64(3), db.Metrics().Get(metrics.TxCommits))
+
+	// Handles get a registry of their own by default.
+	solo, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = solo.Close() })
+	require.NotNil(t, solo.MetricsRegistry())
+	require.NotSame(t, reg, solo.MetricsRegistry())
+}
@@ -0,0 +1,503 @@
+package novasql
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+	"time"
+
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/bufferpool"
+	"github.com/tuannm99/novasql/internal/metrics"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+var (
+	ErrReadOnly           = errors.New("novasql: database is read-only")
+	ErrConflictingOptions = errors.New("novasql: conflicting open options")
+	ErrDatabaseNotFound   = errors.New("novasql: database does not exist")
+	ErrDatabaseExists     = errors.New("novasql: database already exists")
+	ErrUpgradeRequired    = errors.New("novasql: database metadata needs upgrade")
+	ErrOpenCheckFailed    = errors.New("novasql: open integrity check failed")
+)
+
+// CreateMode controls what Open does when the database directory is missing
+// or already present.
+type CreateMode int
+
+const (
+	// CreateIfMissing opens an existing database or creates a new one (default).
+	CreateIfMissing CreateMode = iota
+	// CreateNever only opens an existing database.
+	CreateNever
+	// CreateAlways only creates a new database and fails if one exists.
+	CreateAlways
+)
+
+// CheckLevel is the amount of integrity checking done at open time.
+type CheckLevel int
+
+const (
+	// CheckNone skips verification (default).
+	CheckNone CheckLevel = iota
+	// CheckQuick verifies table metadata and segment file shapes.
+	CheckQuick
+	// CheckFull additionally reads and validates every heap and index page.
+	CheckFull
+)
+
+// OpenOptions configures how a Database is opened. Create one with Options,
+// chain the setters and finish with Open:
+//
+//	db, err := novasql.Options().ReadOnly(true).Create(novasql.CreateNever).Open(dir)
+type OpenOptions struct {
+	cfg         *internal.NovaSqlConfig
+	readOnly    bool
+	create      CreateMode
+	lockTimeout time.Duration
+	check       CheckLevel
+	clock       func() time.Time
+	upgrade     bool
+	metrics     *metrics.Registry
+	engine      StorageEngine
+	replica     string
+}
+
+// Options returns OpenOptions with defaults: read-write, CreateIfMissing,
+// CheckNone, wall clock, no metadata upgrade.
+func Options() *OpenOptions {
+	return &OpenOptions{}
+}
+
+// OpenReadOnly opens the existing databases under workDir read-only; it is
+// Options().ReadOnly(true).Create(CreateNever).Open(workDir).
+func OpenReadOnly(workDir string) (*Database, error) {
+	return Options().ReadOnly(true).Create(CreateNever).Open(workDir)
+}
+
+// Config sets runtime configuration (cache warm-up, ...). nil means defaults.
+func (o *OpenOptions) Config(cfg *internal.NovaSqlConfig) *OpenOptions {
+	o.cfg = cfg
+	return o
+}
+
+// ReadOnly opens the database without writing anything to disk but the lock
+// file of the directory: no directory creation, no WAL replay, data files
+// opened without write permission, and all mutating APIs return ErrReadOnly.
+func (o *OpenOptions) ReadOnly(v bool) *OpenOptions {
+	o.readOnly = v
+	return o
+}
+
+// Create sets the create-vs-open policy.
+func (o *OpenOptions) Create(mode CreateMode) *OpenOptions {
+	o.create = mode
+	return o
+}
+
+// LockTimeout bounds how long Open waits for the lock of the directory while
+// another process holds it (0 = fail fast with storage.ErrLocked).
+func (o *OpenOptions) LockTimeout(d time.Duration) *OpenOptions {
+	o.lockTimeout = d
+	return o
+}
+
+// OpenCheck sets the integrity check level run at open time.
+func (o *OpenOptions) OpenCheck(level CheckLevel) *OpenOptions {
+	o.check = level
+	return o
+}
+
+// Clock overrides the time source used for metadata timestamps.
+func (o *OpenOptions) Clock(now func() time.Time) *OpenOptions {
+	o.clock = now
+	return o
+}
+
+// Upgrade allows Open to rewrite metadata written by older versions. Without
+// it such databases fail to open with ErrUpgradeRequired.
+func (o *OpenOptions) Upgrade(v bool) *OpenOptions {
+	o.upgrade = v
+	return o
+}
+
+// Metrics makes the handle count into r, which may be shared by several
+// handles (a server shares one across its sessions). nil, the default, gives
+// the handle a registry of its own.
+func (o *OpenOptions) Metrics(r *metrics.Registry) *OpenOptions {
+	o.metrics = r
+	return o
+}
+
+// Engine keeps the pages of tables and indexes in e instead of segment
+// files; metadata, the WAL and the transaction log stay in the database
+// directory, the logs opened through e if it is a storage.FileOpener. It
+// needs storage.mode classic (or unset) and no direct I/O.
+func (o *OpenOptions) Engine(e StorageEngine) *OpenOptions {
+	o.engine = e
+	return o
+}
+
+// lockRetryInterval is how often Open tries the directory lock again while
+// it waits for it.
+const lockRetryInterval = 10 * time.Millisecond
+
+// lockDir takes the lock of root with storage.LockDir, trying again until
+// LockTimeout has passed while another process holds it.
+func (o *OpenOptions) lockDir(root string) (*storage.DirLock, error) {
+	deadline := time.Now().Add(o.lockTimeout)
+	for {
+		l, err := storage.LockDir(root, o.readOnly)
+		left := time.Until(deadline)
+		if !errors.Is(err, storage.ErrLocked) || left <= 0 {
+			return l, err
+		}
+		time.Sleep(min(lockRetryInterval, left))
+	}
+}
+
+func (o *OpenOptions) validate() error {
+	if o.readOnly && o.create == CreateAlways {
+		return fmt.Errorf("%w: ReadOnly(true) cannot be combined with Create(CreateAlways)", ErrConflictingOptions)
+	}
+	if o.lockTimeout < 0 {
+		return fmt.Errorf("%w: LockTimeout must not be negative", ErrConflictingOptions)
+	}
+	if o.cfg != nil {
+		if _, err := wal.ParseSyncMode(o.cfg.Storage.SyncMode); err != nil {
+			return fmt.Errorf("storage.sync_mode: %w", err)
+		}
+		mode, err := storageMode(o.cfg)
+		if err != nil {
+			return fmt.Errorf("storage.mode: %w", err)
+		}
+		if o.cfg.Storage.DirectIO && mode != storage.Classic {
+			return fmt.Errorf("%w: storage.direct_io needs storage.mode classic, not %s", ErrConflictingOptions, mode)
+		}
+		if o.engine != nil && mode != storage.Classic {
+			return fmt.Errorf("%w: Engine cannot be combined with storage.mode %s", ErrConflictingOptions, mode)
+		}
+		if o.engine != nil && o.cfg.Storage.DirectIO {
+			return fmt.Errorf("%w: Engine cannot be combined with storage.direct_io", ErrConflictingOptions)
+		}
+		if c := o.cfg.Storage.Compression; c != "" {
+			if _, err := storage.ParseCompression(c); err != nil {
+				return fmt.Errorf("storage.compression: %w", err)
+			}
+		}
+		if k := o.cfg.Storage.EncryptionKey; k != "" {
+			if o.cfg.Storage.EncryptionKeyFile != "" {
+				return fmt.Errorf("%w: storage.encryption_key and storage.encryption_key_file are both set",
+					ErrConflictingOptions)
+			}
+			if _, err := storage.ParseEncryptionKey(k); err != nil {
+				return fmt.Errorf("storage.encryption_key: %w", err)
+			}
+		}
+	}
+	return nil
+}
+
+// MemoryPath opens an in-memory database, like storage.mode: memory.
+const MemoryPath = ":memory:"
+
+// Open opens (or creates) the database rooted at workDir and selects its
+// "default" database. An empty workDir means storage.workdir.
+//
+// With storage.mode memory, or workDir MemoryPath, the database is new and
+// lives in memory until Close: pages are never written to disk, there is no
+// WAL, and workDir is not used. The catalog's small metadata files go to a
+// private temporary directory that Close removes.
+func (o *OpenOptions) Open(workDir string) (*Database, error) {
+	if err := o.validate(); err != nil {
+		return nil, err
+	}
+	if o.replica != "" {
+		return o.openReplica(workDir)
+	}
+	mode, err := storageMode(o.cfg)
+	if err != nil {
+		return nil, err
+	}
+	if workDir == "" && o.cfg != nil {
+		workDir = o.cfg.Storage.Workdir
+	}
+	if workDir == MemoryPath {
+		if o.engine != nil {
+			return nil, fmt.Errorf("%w: Engine cannot be combined with %s", ErrConflictingOptions, MemoryPath)
+		}
+		mode = storage.Memory
+	}
+
+	root := filepath.Clean(workDir)
+	var memDir string
+	if mode == storage.Memory {
+		if memDir, err = os.MkdirTemp("", "novasql-memory-"); err != nil {
+			return nil, err
+		}
+		root = memDir
+	}
+	db, err := o.open(root, mode)
+	if err != nil {
+		if memDir != "" {
+			_ = os.RemoveAll(memDir)
+		}
+		return nil, err
+	}
+	db.memDir = memDir
+	return db, nil
+}
+
+func (o *OpenOptions) open(root string, mode storage.StorageMode) (*Database, error) {
+	cur := filepath.Join(root, "default")
+
+	exists := dirExists(filepath.Join(cur, "tables"))
+	switch o.create {
+	case CreateNever:
+		if !exists {
+			return nil, fmt.Errorf("%w: %s", ErrDatabaseNotFound, cur)
+		}
+	case CreateAlways:
+		if exists {
+			return nil, fmt.Errorf("%w: %s", ErrDatabaseExists, cur)
+		}
+	case CreateIfMissing:
+		if !exists && o.readOnly {
+			return nil, fmt.Errorf("%w: %s (read-only handles cannot create it)", ErrDatabaseNotFound, cur)
+		}
+	default:
+		return nil, fmt.Errorf("%w: unknown create mode %d", ErrConflictingOptions, o.create)
+	}
+
+	sm, err := storage.NewStorageManagerFor(mode)
+	if err != nil {
+		return nil, err
+	}
+	if o.engine != nil {
+		sm = storage.NewStorageManagerWithEngine(o.engine)
+	}
+	sm.SetReadOnly(o.readOnly)
+	db := &Database{
+		WorkDir: root,
+		DataDir: cur,
+		SM:      sm,
+		views:   make(map[string]bufferpool.Manager),
+		opts:    *o,
+	}
+	if db.opts.metrics == nil {
+		db.opts.metrics = metrics.New()
+	}
+	if !o.readOnly {
+		if err := os.MkdirAll(filepath.Join(cur, "tables"), 0o755); err != nil {
+			return nil, err
+		}
+	}
+	// Processes lock root, whose databases they share: one writer, or any
+	// number of readers.
+	if db.lock, err = o.lockDir(root); err != nil {
+		return nil, fmt.Errorf("%s: %w", root, err)
+	}
+	if o.cfg != nil && o.cfg.Storage.DirectIO && mode == storage.Classic {
+		probe := filepath.Join(cur, "tables")
+		if o.readOnly {
+			probe = ""
+		}
+		if err := sm.SetDirectIO(probe); err != nil {
+			_ = db.lock.Unlock()
+			return nil, fmt.Errorf("storage.direct_io: %w", err)
+		}
+	}
+	if err := db.openDataDir(); err != nil {
+		db.closeWAL()
+		_ = sm.Close()
+		_ = db.lock.Unlock()
+		return nil, err
+	}
+	db.resetBufferPool()
+	db.watchUnclosed()
+	return db, nil
+}
+
+// openDataDir validates the header of db.DataDir, opens and replays its WAL
+// (read-write handles only) and loads its transaction status log, then
+// applies the upgrade policy, the open check and trash retention.
+func (db *Database) openDataDir() error {
+	// The catalog belongs to the previous DataDir until reloaded below.
+	db.resetCatalog()
+	db.feed = changeFeedOf(db.DataDir)
+	if err := db.openHeader(); err != nil {
+		return err
+	}
+	if err := db.openLayout(); err != nil {
+		return err
+	}
+	if !db.opts.readOnly {
+		db.openWAL()
+	}
+	if err := db.openXact(); err != nil {
+		return err
+	}
+	if err := db.upgradeMetas(); err != nil {
+		return err
+	}
+	if err := db.openCheck(); err != nil {
+		return err
+	}
+	if err := db.loadCatalog(); err != nil {
+		return err
+	}
+	db.purgeExpiredTrash()
+	return nil
+}
+
+func (db *Database) now() time.Time {
+	if db.opts.clock != nil {
+		return db.opts.clock()
+	}
+	return time.Now()
+}
+
+// ensureWritable is ensureOpen for APIs that change anything on disk.
+func (db *Database) ensureWritable() error {
+	if err := db.ensureOpen(); err != nil {
+		return err
+	}
+	if db.opts.readOnly {
+		return ErrReadOnly
+	}
+	return nil
+}
+
+// ReadOnly reports whether the handle was opened read-only.
+func (db *Database) ReadOnly() bool {
+	return db != nil && db.opts.readOnly
+}
+
+// tableMetaNames lists table names that have a meta file in the current database.
+func (db *Database) tableMetaNames() ([]string, error) {
+	entries, err := os.ReadDir(db.tableDir())
+	if err != nil {
+		if errors.Is(err, os.ErrNotExist) {
+			return nil, nil
+		}
+		return nil, err
+	}
+	var names []string
+	for _, e := range entries {
+		if e.IsDir() || !strings.HasSuffix(e.Name(), ".meta.json") {
+			continue
+		}
+		names = append(names, strings.TrimSuffix(e.Name(), ".meta.json"))
+	}
+	return names, nil
+}
+
+// upgradeLegacyMeta fills fields that older versions left empty.
+// It reports whether meta changed.
+func (db *Database) upgradeLegacyMeta(meta *TableMeta) bool {
+	changed := false
+	if meta.CreatedAt.IsZero() {
+		meta.CreatedAt = db.now()
+		changed = true
+	}
+	for i := range meta.Indexes {
+		im := &meta.Indexes[i]
+		if im.Kind == "" {
+			im.Kind = IndexKindBTree
+			changed = true
+		}
+		if im.FileBase == "" {
+			im.FileBase = db.fmtIndexBase(meta.Name, im.Name)
+			changed = true
+		}
+		if im.CreatedAt.IsZero() {
+			im.CreatedAt = db.now()
+			changed = true
+		}
+	}
+	return changed
+}
+
+func (db *Database) upgradeMetas() error {
+	names, err := db.tableMetaNames()
+	if err != nil {
+		return err
+	}
+	for _, name := range names {
+		meta, err := db.readTableMeta(name)
+		if err != nil {
+			// Unreadable metas are the open check's business.
+			continue
+		}
+		if !db.upgradeLegacyMeta(meta) {
+			continue
+		}
+		switch {
+		case !db.opts.upgrade:
+			return fmt.Errorf("%w: table %q (open with Upgrade(true))", ErrUpgradeRequired, name)
+		case db.opts.readOnly:
+			return fmt.Errorf("%w: table %q: ReadOnly(true) prevents Upgrade(true) from rewriting it",
+				ErrUpgradeRequired, name)
+		}
+		if err := db.writeTableMeta(meta); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+func (db *Database) openCheck() error {
+	if db.opts.check == CheckNone {
+		return nil
+	}
+	names, err := db.tableMetaNames()
+	if err != nil {
+		return err
+	}
+	for _, name := range names {
+		if err := db.checkTable(name); err != nil {
+			return fmt.Errorf("%w: table %q: %w", ErrOpenCheckFailed, name, err)
+		}
+	}
+	return nil
+}
+
+func (db *Database) checkTable(name string) error {
+	meta, err := db.readTableMeta(name)
+	if err != nil {
+		return err
+	}
+	if meta.Name != name {
+		return fmt.Errorf("meta name %q does not match file name", meta.Name)
+	}
+
+	// Overflow pages use their own layout; only their file shape is checked.
+	if err := db.SM.VerifySegments(db.overflowFileSet(name)); err != nil {
+		return err
+	}
+
+	for _, fs := range db.pagedFileSets(meta) {
+		if err := db.SM.VerifySegments(fs); err != nil {
+			return err
+		}
+		if db.opts.check < CheckFull {
+			continue
+		}
+		bad, err := db.verifyPages(fs)
+		if err != nil {
+			return err
+		}
+		if len(bad) > 0 {
+			return bad[0]
+		}
+	}
+	return nil
+}
+
+func dirExists(path string) bool {
+	st, err := os.Stat(path)
+	return err == nil && st.IsDir()
+}
@@ -0,0 +1,297 @@
+package novasql
+
+import (
+	"encoding/json"
+	"errors"
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+// seedDatabase creates <dir>/default with one table holding a few rows.
+func seedDatabase(t *testing.T, dir string) {
+	t.Helper()
+
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 3 {
+		_, err := tbl.Insert([]any{int64(i), "u"})
+		require.NoError(t, err)
+	}
+	require.NoError(t, db.Close())
+}
+
+func TestOptions_Defaults(t *testing.T) {
+	dir := t.TempDir()
+
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	require.False(t, db.ReadOnly())
+	require.DirExists(t, filepath.Join(dir, "default", "tables"))
+	require.NoError(t, db.Close())
+
+	// Reopen of an existing database is fine too.
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+}
+
+func TestOptions_CreateNever(t *testing.T) {
+	dir := t.TempDir()
+
+	_, err := Options().Create(CreateNever).Open(dir)
+	require.ErrorIs(t, err, ErrDatabaseNotFound)
+	require.NoDirExists(t, filepath.Join(dir, "default"))
+
+	seedDatabase(t, dir)
+	db, err := Options().Create(CreateNever).Open(dir)
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+}
+
+func TestOptions_CreateAlways(t *testing.T) {
+	dir := t.TempDir()
+
+	db, err := Options().Create(CreateAlways).Open(dir)
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+
+	_, err = Options().Create(CreateAlways).Open(dir)
+	require.ErrorIs(t, err, ErrDatabaseExists)
+}
+
+func TestOptions_Conflicts(t *testing.T) {
+	dir := t.TempDir()
+
+	_, err := Options().ReadOnly(true).Create(CreateAlways).Open(dir)
+	require.ErrorIs(t, err, ErrConflictingOptions)
+	require.ErrorContains(t, err, "ReadOnly")
+	require.ErrorContains(t, err, "Create(CreateAlways)")
+	require.NoDirExists(t, filepath.Join(dir, "default"))
+
+	_, err = Options().LockTimeout(-time.Second).Open(dir)
+	require.ErrorIs(t, err, ErrConflictingOptions)
+
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.SyncMode = "sometimes"
+	_, err = Options().Config(cfg).Open(dir)
+	require.ErrorIs(t, err, wal.ErrSyncMode)
+
+	cfg = &internal.NovaSqlConfig{}
+	cfg.Storage.Mode = "tape"
+	_, err = Options().Config(cfg).Open(dir)
+	require.ErrorContains(t, err, "storage.mode")
+}
+
+func TestOptions_ReadOnly(t *testing.T) {
+	dir := t.TempDir()
+
+	// Read-only handles never create a database.
+	_, err := Options().ReadOnly(true).Open(dir)
+	require.ErrorIs(t, err, ErrDatabaseNotFound)
+	require.NoDirExists(t, filepath.Join(dir, "default"))
+
+	seedDatabase(t, dir)
+	metaPath := filepath.Join(dir, "default", "tables", "users.meta.json")
+	before, err := os.ReadFile(metaPath)
+	require.NoError(t, err)
+
+	db, err := Options().ReadOnly(true).Open(dir)
+	require.NoError(t, err)
+	require.True(t, db.ReadOnly())
+
+	tbl, err := db.OpenTable("users")
+	require.NoError(t, err)
+	rows := 0
+	require.NoError(t, tbl.Scan(func(heap.TID, []any) error {
+		rows++
+		return nil
+	}))
+	require.Equal(t, 3, rows)
+
+	_, err = tbl.Insert([]any{int64(9), "x"})
+	require.ErrorIs(t, err, heap.ErrTableReadOnly)
+	_, err = db.CreateTable("other", testSchema())
+	require.ErrorIs(t, err, ErrReadOnly)
+	require.ErrorIs(t, db.DropTable("users"), ErrReadOnly)
+	require.ErrorIs(t, db.RenameTable("users", "people"), ErrReadOnly)
+	require.ErrorIs(t, db.CreateDatabase("x"), ErrReadOnly)
+	_, err = db.CreateBTreeIndex("users", "users_id", "id")
+	require.ErrorIs(t, err, ErrReadOnly)
+
+	_, err = db.SelectDatabase("missing")
+	require.ErrorIs(t, err, ErrDatabaseNotFound)
+
+	require.NoError(t, db.Close())
+
+	after, err := os.ReadFile(metaPath)
+	require.NoError(t, err)
+	require.Equal(t, before, after)
+}
+
+func TestOpenReadOnly(t *testing.T) {
+	dir := t.TempDir()
+	seedDatabase(t, dir)
+	require.FileExists(t, filepath.Join(dir, storage.LockFileName))
+
+	// The files are only ever opened for reading.
+	if os.Geteuid() != 0 {
+		require.NoError(t, filepath.WalkDir(filepath.Join(dir, "default"), func(path string, d os.DirEntry, err error) error {
+			if err != nil || d.IsDir() {
+				return err
+			}
+			return os.Chmod(path, 0o444)
+		}))
+	}
+
+	db, err := OpenReadOnly(dir)
+	require.NoError(t, err)
+	tbl, err := db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 3, countRows(t, tbl))
+	_, err = tbl.Insert([]any{int64(9), "x"})
+	require.Equal(t, CodeReadOnly, CodeOf(err))
+
+	// Readers share the lock of the directory.
+	other, err := OpenReadOnly(dir)
+	require.NoError(t, err)
+	require.NoError(t, other.Close())
+	require.NoError(t, db.Close())
+
+	_, err = OpenReadOnly(t.TempDir())
+	require.ErrorIs(t, err, ErrDatabaseNotFound)
+}
+
+func TestOptions_Clock(t *testing.T) {
+	fixed := time.Date(2020, 1, 2, 3, 4, 5, 0, time.UTC)
+
+	db, err := Options().Clock(func() time.Time { return fixed }).Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	_, err = db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+
+	meta, err := db.readTableMeta("users")
+	require.NoError(t, err)
+	require.True(t, fixed.Equal(meta.CreatedAt))
+	require.True(t, fixed.Equal(meta.UpdatedAt))
+}
+
+func TestOptions_Upgrade(t *testing.T) {
+	dir := t.TempDir()
+	seedDatabase(t, dir)
+
+	// Simulate metadata written by an older version: index without FileBase.
+	metaPath := filepath.Join(dir, "default", "tables", "users.meta.json")
+	var meta TableMeta
+	data, err := os.ReadFile(metaPath)
+	require.NoError(t, err)
+	require.NoError(t, json.Unmarshal(data, &meta))
+	meta.Indexes = []IndexMeta{{Name: "users_id", Kind: IndexKindBTree, KeyColumn: "id"}}
+	data, err = json.Marshal(&meta)
+	require.NoError(t, err)
+	require.NoError(t, os.WriteFile(metaPath, data, 0o644))
+
+	_, err = Options().Open(dir)
+	require.ErrorIs(t, err, ErrUpgradeRequired)
+	require.ErrorContains(t, err, "Upgrade(true)")
+
+	_, err = Options().ReadOnly(true).Upgrade(true).Open(dir)
+	require.ErrorIs(t, err, ErrUpgradeRequired)
+	require.ErrorContains(t, err, "ReadOnly(true)")
+
+	db, err := Options().Upgrade(true).Open(dir)
+	require.NoError(t, err)
+	idx, err := db.ListIndexes("users")
+	require.NoError(t, err)
+	require.Len(t, idx, 1)
+	require.Equal(t, "users__idx__users_id", idx[0].FileBase)
+	require.False(t, idx[0].CreatedAt.IsZero())
+	require.NoError(t, db.Close())
+
+	// Once upgraded, the default policy opens it.
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+}
+
+func TestOptions_OpenCheck(t *testing.T) {
+	dir := t.TempDir()
+	seedDatabase(t, dir)
+	heapPath := filepath.Join(dir, "default", "tables", "users")
+
+	for _, level := range []CheckLevel{CheckNone, CheckQuick, CheckFull} {
+		db, err := Options().OpenCheck(level).Open(dir)
+		require.NoError(t, err, "level %d", level)
+		require.NoError(t, db.Close())
+	}
+
+	// Corrupt page 0's header: lower > upper. Read-only skips WAL replay, which
+	// would otherwise repair the page before the check sees it.
+	f, err := os.OpenFile(heapPath, os.O_RDWR, 0o644)
+	require.NoError(t, err)
+	_, err = f.WriteAt([]byte{0xff, 0xff}, 6)
+	require.NoError(t, err)
+	require.NoError(t, f.Close())
+
+	db, err := Options().ReadOnly(true).OpenCheck(CheckQuick).Open(dir)
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+
+	_, err = Options().ReadOnly(true).OpenCheck(CheckFull).Open(dir)
+	require.ErrorIs(t, err, ErrOpenCheckFailed)
+	require.ErrorContains(t, err, "users")
+
+	// A torn segment (not a whole number of pages) fails the quick check.
+	f, err = os.OpenFile(heapPath, os.O_WRONLY|os.O_APPEND, 0o644)
+	require.NoError(t, err)
+	_, err = f.Write([]byte("torn"))
+	require.NoError(t, err)
+	require.NoError(t, f.Close())
+
+	_, err = Options().OpenCheck(CheckQuick).Open(dir)
+	require.ErrorIs(t, err, ErrOpenCheckFailed)
+}
+
+func TestOptions_DirectIO(t *testing.T) {
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.DirectIO = true
+	cfg.Storage.Mode = "mmap"
+	_, err := Options().Config(cfg).Open(t.TempDir())
+	require.ErrorIs(t, err, ErrConflictingOptions)
+
+	cfg.Storage.Mode = ""
+	dir := t.TempDir()
+	db, err := Options().Config(cfg).Open(dir)
+	if errors.Is(err, storage.ErrDirectIO) {
+		require.Equal(t, CodeIncompatible, CodeOf(err))
+		t.Skip(err)
+	}
+	require.NoError(t, err)
+	require.True(t, db.SM.DirectIO())
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 500 {
+		_, err := tbl.Insert([]any{int64(i), "u"})
+		require.NoError(t, err)
+	}
+	require.NoError(t, db.Close())
+
+	db, err = Options().Config(cfg).ReadOnly(true).Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	tbl, err = db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 500, countRows(t, tbl))
+}
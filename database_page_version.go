@@ -0,0 +1,41 @@
+package novasql
+
+import (
+	"github.com/tuannm99/novasql/internal/bufferpool"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+// VersionConflictError is returned by WritePageIfVersion when another write
+// changed the page after it was read.
+type VersionConflictError = bufferpool.VersionConflictError
+
+// ErrPageUnversioned is returned for pages written before page versions existed.
+var ErrPageUnversioned = bufferpool.ErrPageUnversioned
+
+// GetPageVersioned returns a copy of page pageID of fs and its version.
+// Every write that goes through the buffer pool bumps the version; it is kept
+// in the page trailer, so it survives reopen and WAL replay.
+//
+// fs is a relation file set, e.g. LocalFileSet{Dir: db.TableDir(), Base: table}.
+// Overflow file sets are not supported: they bypass the buffer pool.
+func (db *Database) GetPageVersioned(fs storage.FileSet, pageID uint32) ([]byte, uint64, error) {
+	if err := db.ensureOpen(); err != nil {
+		return nil, 0, err
+	}
+	return db.bp.ReadVersioned(fs, pageID)
+}
+
+// WritePageIfVersion replaces the page with data only if its version is still
+// expected, and returns the new version. On a mismatch it returns
+// *VersionConflictError and leaves the page untouched.
+func (db *Database) WritePageIfVersion(
+	fs storage.FileSet,
+	pageID uint32,
+	data []byte,
+	expected uint64,
+) (uint64, error) {
+	if err := db.ensureWritable(); err != nil {
+		return 0, err
+	}
+	return db.bp.WriteIfVersion(fs, pageID, data, expected)
+}
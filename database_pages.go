@@ -0,0 +1,38 @@
+package novasql
+
+import "github.com/tuannm99/novasql/internal/storage"
+
+// ReadPages returns copies of the n pages of fs from first. Pages in the
+// buffer pool are copied from it; the runs of others are read with one read
+// per segment and are not cached, so a scan or an export through ReadPages
+// leaves the working set alone.
+//
+// fs is a relation file set, as for GetPageVersioned.
+func (db *Database) ReadPages(fs storage.FileSet, first uint32, n int) ([][]byte, error) {
+	if err := db.ensureOpen(); err != nil {
+		return nil, err
+	}
+	return db.bp.ReadPages(fs, first, n)
+}
+
+// WritePages replaces the len(pages) pages of fs from first, each bumping
+// its version, with one WAL group for all of them. Pages not in the buffer
+// pool go to disk in runs, one write per segment, instead of through it.
+func (db *Database) WritePages(fs storage.FileSet, first uint32, pages [][]byte) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	return db.bp.WritePages(fs, first, pages)
+}
+
+// DebugPage describes page pageID of fs as ReadPages returns it: header,
+// trailer, checksum state and slots with their tuples (see
+// storage.InspectPage), for diagnosing a damaged page. `novasql inspect`
+// prints the same for the files of a closed database.
+func (db *Database) DebugPage(fs storage.FileSet, pageID uint32) (storage.PageInfo, error) {
+	pages, err := db.ReadPages(fs, pageID, 1)
+	if err != nil {
+		return storage.PageInfo{}, err
+	}
+	return storage.InspectPage(pages[0]), nil
+}
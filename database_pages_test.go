@@ -0,0 +1,82 @@
+package novasql
+
+import (
+	"os"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+func TestReadWritePages(t *testing.T) {
+	dir := t.TempDir()
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	fs := storage.LocalFileSet{Dir: db.TableDir(), Base: "raw"}
+
+	// Page 2 is resident and gets its write in the pool, the others on disk.
+	_, v, err := db.GetPageVersioned(fs, 2)
+	require.NoError(t, err)
+	require.Equal(t, uint64(0), v)
+	before := db.BufferPoolStats().Resident
+
+	pages := make([][]byte, 5)
+	for i := range pages {
+		p, err := storage.NewPage(make([]byte, storage.PageSize), uint32(i))
+		require.NoError(t, err)
+		_, err = p.InsertTuple([]byte{'a' + byte(i)})
+		require.NoError(t, err)
+		pages[i] = p.Buf
+	}
+	require.NoError(t, db.WritePages(fs, 0, pages))
+	require.Equal(t, before, db.BufferPoolStats().Resident)
+	require.Equal(t, []string{"c"}, pageTuples(t, db, fs, 2))
+
+	got, err := db.ReadPages(fs, 0, 6)
+	require.NoError(t, err)
+	require.Len(t, got, 6)
+	for i, buf := range got[:5] {
+		p := &storage.Page{Buf: buf}
+		require.Equal(t, uint64(1), p.Version())
+		raw, err := p.ReadTuple(0)
+		require.NoError(t, err)
+		require.Equal(t, []byte{'a' + byte(i)}, raw)
+	}
+	require.Equal(t, 0, (&storage.Page{Buf: got[5]}).NumSlots())
+	require.Equal(t, before, db.BufferPoolStats().Resident)
+
+	// The pool page reaches disk with the WAL; the rest is there already.
+	require.NoError(t, db.Close())
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	for i := range 5 {
+		require.Equal(t, []string{string(rune('a' + i))}, pageTuples(t, db, fs, uint32(i)))
+	}
+
+	require.ErrorIs(t, db.WritePages(fs, 0, [][]byte{make([]byte, 10)}), storage.ErrWrongSize)
+}
+
+func TestDebugPage(t *testing.T) {
+	dir := t.TempDir()
+	seedDatabase(t, dir)
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	info, err := db.DebugPage(storage.LocalFileSet{Dir: db.TableDir(), Base: "users"}, 0)
+	require.NoError(t, err)
+	require.Empty(t, info.Problem)
+	require.True(t, info.ChecksumOK)
+	require.Len(t, info.Slots, 3)
+	for _, s := range info.Slots {
+		require.NotEmpty(t, s.Tuple)
+	}
+
+	h, err := InspectHeader(db.DataDir)
+	require.NoError(t, err)
+	require.Equal(t, HeaderInfo{FormatVersion: headerFormatVersion, PageSize: storage.PageSize}, h)
+	_, err = InspectHeader(t.TempDir())
+	require.ErrorIs(t, err, os.ErrNotExist)
+}
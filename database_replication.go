@@ -0,0 +1,938 @@
+package novasql
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"log/slog"
+	"net"
+	"os"
+	"path/filepath"
+	"slices"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/tuannm99/novasql/internal/bufferpool"
+	"github.com/tuannm99/novasql/internal/replication"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+// Replication
+//
+// A primary streams its database to read-only replicas (see
+// ServeReplication): the WAL records as they are logged, which carry the
+// pages of tables and indexes, and the metadata files the WAL does not
+// carry, whole, whenever they change. A new replica first gets a snapshot,
+// a copy of every file. Files the WAL does not describe rewrites of, those
+// VACUUM and ALTER TABLE swap in or a checkpoint truncated records of
+// before they were streamed, are copied again.
+//
+// A replica (see OpenOptions.Replica) applies them to its own directory and
+// serves reads from it, a little behind the primary.
+
+var (
+	ErrNotReplica    = errors.New("novasql: database is not a replica")
+	ErrReplicaBehind = errors.New("novasql: replica is too far behind its primary")
+)
+
+const (
+	// replicaStateFile records the primary of a replica and the LSN it
+	// applied up to.
+	replicaStateFile = "replica.json"
+	// replCopySuffix marks a file of a replica still being copied.
+	replCopySuffix = ".repl-copy"
+
+	replPoll       = 50 * time.Millisecond // how often a primary looks for changes
+	replHeartbeat  = time.Second           // batch sent at least this often, empty if need be
+	replChunk      = 1 << 20               // bytes of a file per File
+	replBatchBytes = 8 << 20               // bytes of files per batch
+	replRetry      = time.Second           // wait before a replica reconnects
+	replSaveEvery  = time.Second           // how often a replica persists its LSN
+)
+
+// ReplicationStatus is where the replication of a handle stands.
+type ReplicationStatus struct {
+	Role string // "primary" once serving replicas, "replica", "" for neither
+	LSN  uint64 // primary: last LSN streamed; replica: last LSN applied
+
+	Replicas []ReplicaStatus // primary: connected replicas
+
+	Primary    string        // replica: address of the primary
+	PrimaryLSN uint64        // replica: last LSN the primary reported
+	Lag        uint64        // replica: LSNs behind the primary
+	Delay      time.Duration // replica: age of the oldest change not applied yet
+	Err        error         // replica: why the stream is down, nil while it runs
+}
+
+// ReplicaStatus is a replica connected to a primary.
+type ReplicaStatus struct {
+	Addr  string
+	LSN   uint64 // acknowledged
+	Lag   uint64 // LSNs streamed and not acknowledged yet
+	Since time.Time
+}
+
+// replState is the ReplicationStatus of a handle as it changes.
+type replState struct {
+	mu         sync.Mutex
+	role       string
+	lsn        uint64
+	replicas   []*replicaConn
+	primary    string
+	primaryLSN uint64
+	delay      time.Duration
+	err        error
+}
+
+// replicaConn is a replica connected to a primary.
+type replicaConn struct {
+	conn  net.Conn
+	since time.Time
+	acked uint64
+}
+
+// ReplicationStatus reports the replicas of a primary, or how far a replica
+// is behind its primary.
+func (db *Database) ReplicationStatus() ReplicationStatus {
+	st := &db.repl
+	st.mu.Lock()
+	defer st.mu.Unlock()
+	out := ReplicationStatus{
+		Role:       st.role,
+		LSN:        st.lsn,
+		Primary:    st.primary,
+		PrimaryLSN: st.primaryLSN,
+		Delay:      st.delay,
+		Err:        st.err,
+	}
+	if st.primaryLSN > st.lsn {
+		out.Lag = st.primaryLSN - st.lsn
+	}
+	for _, rc := range st.replicas {
+		rs := ReplicaStatus{Addr: rc.conn.RemoteAddr().String(), LSN: rc.acked, Since: rc.since}
+		if st.lsn > rc.acked {
+			rs.Lag = st.lsn - rc.acked
+		}
+		out.Replicas = append(out.Replicas, rs)
+	}
+	return out
+}
+
+func (st *replState) update(fn func(st *replState)) {
+	st.mu.Lock()
+	fn(st)
+	st.mu.Unlock()
+}
+
+// ServeReplication streams the selected database to the replicas that
+// connect to ln, until ln is closed; it then disconnects them and returns
+// nil. It reads the files of the database selected when it is called, so
+// the handle may go on with other work meanwhile, in other databases too.
+// Replicas of a database that is not archived (storage.wal_archive_dir)
+// recopy the files a checkpoint truncated records of before they were
+// streamed; those that were disconnected meanwhile fail with
+// ErrReplicaBehind and must be recreated.
+func (db *Database) ServeReplication(ln net.Listener) error {
+	if err := db.ensureOpen(); err != nil {
+		return err
+	}
+	if db.SM.InMemory() || db.SM.Engine() != nil {
+		return fmt.Errorf("%w: replication needs a database in segment files", ErrConflictingOptions)
+	}
+	if db.follower != nil {
+		return fmt.Errorf("%w: a replica cannot serve replicas", ErrConflictingOptions)
+	}
+	db.repl.update(func(st *replState) { st.role = "primary" })
+	src := replSource{dataDir: db.DataDir, walDir: db.layout.WAL, archiveDir: db.walArchiveDir()}
+
+	var (
+		mu    sync.Mutex
+		conns = make(map[net.Conn]struct{})
+		wg    sync.WaitGroup
+	)
+	defer func() {
+		mu.Lock()
+		for conn := range conns {
+			_ = conn.Close()
+		}
+		mu.Unlock()
+		wg.Wait()
+	}()
+	for {
+		conn, err := ln.Accept()
+		if errors.Is(err, net.ErrClosed) {
+			return nil
+		}
+		if err != nil {
+			return err
+		}
+		mu.Lock()
+		conns[conn] = struct{}{}
+		mu.Unlock()
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			err := db.repl.serveReplica(conn, src)
+			mu.Lock()
+			delete(conns, conn)
+			mu.Unlock()
+			if err != nil && !errors.Is(err, net.ErrClosed) && !errors.Is(err, io.EOF) {
+				slog.Warn("replication: replica stream ended", "replica", conn.RemoteAddr(), "err", err)
+			}
+		}()
+	}
+}
+
+// serveReplica streams src to the replica on conn until it goes away.
+func (st *replState) serveReplica(conn net.Conn, src replSource) error {
+	defer func() { _ = conn.Close() }()
+	msg, err := replication.ReadMessage(conn)
+	if err != nil {
+		return err
+	}
+	if msg.Hello == nil {
+		return errors.New("replication: stream does not start with hello")
+	}
+	s, err := newReplSender(src, msg.Hello)
+	if err != nil {
+		_ = replication.WriteMessage(conn, &replication.Message{Error: err.Error()})
+		return err
+	}
+	welcome := &replication.Welcome{Snapshot: !msg.Hello.Resume}
+	if err := replication.WriteMessage(conn, &replication.Message{Welcome: welcome}); err != nil {
+		return err
+	}
+
+	rc := &replicaConn{conn: conn, since: time.Now(), acked: msg.Hello.LSN}
+	st.update(func(st *replState) { st.replicas = append(st.replicas, rc) })
+	defer st.update(func(st *replState) {
+		st.replicas = slices.DeleteFunc(st.replicas, func(c *replicaConn) bool { return c == rc })
+	})
+	go st.readAcks(rc)
+
+	tick := time.NewTicker(replPoll)
+	defer tick.Stop()
+	var sent time.Time
+	for range tick.C {
+		b, err := s.next()
+		if err != nil {
+			_ = replication.WriteMessage(conn, &replication.Message{
+				Error:  err.Error(),
+				Behind: errors.Is(err, ErrReplicaBehind),
+			})
+			return err
+		}
+		if replEmpty(b) && time.Since(sent) < replHeartbeat {
+			continue
+		}
+		if err := replication.WriteMessage(conn, &replication.Message{Batch: b}); err != nil {
+			return err
+		}
+		sent = time.Now()
+		st.update(func(st *replState) { st.lsn = max(st.lsn, b.LSN) })
+	}
+	return nil
+}
+
+// readAcks records the acknowledgements of rc, and closes its connection
+// once it cannot read them, which ends the stream.
+func (st *replState) readAcks(rc *replicaConn) {
+	defer func() { _ = rc.conn.Close() }()
+	for {
+		msg, err := replication.ReadMessage(rc.conn)
+		if err != nil {
+			return
+		}
+		if msg.Ack != nil {
+			st.update(func(*replState) { rc.acked = msg.Ack.LSN })
+		}
+	}
+}
+
+func replEmpty(b *replication.Batch) bool {
+	return !b.Partial && len(b.Files) == 0 && len(b.Records) == 0 && len(b.Meta) == 0
+}
+
+// replSource is the database a primary streams.
+type replSource struct {
+	dataDir, walDir, archiveDir string
+}
+
+// files lists the files of the database that are streamed, by path
+// relative to dataDir: the header, the transaction log and the top-level
+// files of tables (not the trash, nor files being written).
+func (src replSource) files() (map[string]os.FileInfo, error) {
+	out := make(map[string]os.FileInfo)
+	for _, name := range []string{headerFile, "xact"} {
+		info, err := os.Stat(filepath.Join(src.dataDir, name))
+		switch {
+		case err == nil:
+			out[name] = info
+		case !errors.Is(err, os.ErrNotExist):
+			return nil, err
+		}
+	}
+	entries, err := os.ReadDir(filepath.Join(src.dataDir, "tables"))
+	if err != nil && !errors.Is(err, os.ErrNotExist) {
+		return nil, err
+	}
+	for _, e := range entries {
+		name := e.Name()
+		if !e.Type().IsRegular() || strings.Contains(name, ".tmp-") || strings.HasSuffix(name, replCopySuffix) {
+			continue
+		}
+		info, err := e.Info()
+		if errors.Is(err, os.ErrNotExist) {
+			continue
+		}
+		if err != nil {
+			return nil, err
+		}
+		out[filepath.Join("tables", name)] = info
+	}
+	return out, nil
+}
+
+// replMeta reports whether path is a metadata file, streamed whole after
+// the records rather than copied.
+func replMeta(path string) bool {
+	return path == headerFile || path == "xact" || strings.HasSuffix(path, ".json")
+}
+
+// replSender is the stream of one database to one replica.
+type replSender struct {
+	src    replSource
+	tail   *wal.Tailer
+	live   bool                   // the log was read once without a gap
+	seen   map[string]os.FileInfo // files as last streamed
+	gone   []string               // files a resuming replica has and the primary does not
+	copies []*replCopy            // files being copied, in order
+	start  uint64                 // tail.Start() when the copies began
+	resend bool                   // resend the log once the copies are done
+}
+
+// replCopy is a file being copied, up to off so far.
+type replCopy struct {
+	path string
+	off  int64
+}
+
+func newReplSender(src replSource, hello *replication.Hello) (*replSender, error) {
+	after := uint64(0)
+	if hello.Resume {
+		after = hello.LSN
+	}
+	tail, err := wal.NewTailer(src.walDir, src.archiveDir, after)
+	if err != nil {
+		return nil, err
+	}
+	s := &replSender{src: src, tail: tail, seen: make(map[string]os.FileInfo)}
+	files, err := src.files()
+	if err != nil {
+		return nil, err
+	}
+	if !hello.Resume {
+		// The snapshot: every file, the pages logged meanwhile resent after.
+		for _, path := range sortedKeys(files) {
+			s.queueCopy(path)
+			s.seen[path] = files[path]
+		}
+		return s, nil
+	}
+	// Data files the replica has whole are up to date but for the log;
+	// the others, and every metadata file, are sent again.
+	have := make(map[string]int64, len(hello.Files))
+	for _, f := range hello.Files {
+		have[f.Path] = f.Size
+	}
+	for path, info := range files {
+		if size, ok := have[path]; ok && !replMeta(path) && size == info.Size() {
+			s.seen[path] = info
+		}
+	}
+	for _, f := range hello.Files {
+		if _, ok := files[f.Path]; !ok {
+			s.gone = append(s.gone, f.Path)
+		}
+	}
+	return s, nil
+}
+
+// next returns what changed since the previous batch.
+func (s *replSender) next() (*replication.Batch, error) {
+	records, err := s.tail.Read()
+	heal := false
+	switch {
+	case errors.Is(err, wal.ErrLogGap) && !s.live:
+		return nil, ErrReplicaBehind
+	case errors.Is(err, wal.ErrLogGap):
+		// The pages of the records lost reached the data files before the
+		// checkpoint: copy the files written since the previous batch.
+		heal = true
+	case err != nil:
+		return nil, err
+	}
+	s.live = true
+	if len(s.copies) > 0 && s.tail.Start() != s.start {
+		// A checkpoint truncated records written to the parts already
+		// copied: copy them again.
+		for _, c := range s.copies {
+			c.off = 0
+		}
+		s.start = s.tail.Start()
+	}
+
+	b := &replication.Batch{}
+	files, err := s.src.files()
+	if err != nil {
+		return nil, err
+	}
+	for path := range s.seen {
+		if _, ok := files[path]; !ok {
+			s.gone = append(s.gone, path)
+			delete(s.seen, path)
+			s.copies = slices.DeleteFunc(s.copies, func(c *replCopy) bool { return c.path == path })
+		}
+	}
+	for _, path := range s.gone {
+		if replMeta(path) {
+			b.Meta = append(b.Meta, replication.File{Path: path, Remove: true})
+		} else {
+			b.Files = append(b.Files, replication.File{Path: path, Remove: true})
+		}
+	}
+	s.gone = nil
+	for _, path := range sortedKeys(files) {
+		info, prev := files[path], s.seen[path]
+		changed := prev == nil || prev.Size() != info.Size() || !prev.ModTime().Equal(info.ModTime())
+		switch {
+		case replMeta(path):
+			if !changed && os.SameFile(prev, info) {
+				continue
+			}
+			data, err := os.ReadFile(filepath.Join(s.src.dataDir, path))
+			if errors.Is(err, os.ErrNotExist) {
+				continue
+			}
+			if err != nil {
+				return nil, err
+			}
+			b.Meta = append(b.Meta, replication.File{Path: path, Data: data, Create: true, Done: true})
+		case prev == nil || !os.SameFile(prev, info) || heal && changed:
+			s.queueCopy(path)
+		}
+		s.seen[path] = info
+	}
+	if err := s.copyChunks(b); err != nil {
+		return nil, err
+	}
+
+	b.Records = records
+	if s.resend && len(s.copies) == 0 {
+		// Pages logged while the files were copied may be missing from
+		// the copies.
+		again, err := s.tail.Reread()
+		if err != nil {
+			return nil, err
+		}
+		b.Records = append(b.Records, again...)
+		s.resend = false
+	}
+	b.Partial = len(s.copies) > 0
+	b.LSN, b.At = s.tail.LSN(), s.tail.Time()
+	return b, nil
+}
+
+// queueCopy copies path (again) from the start.
+func (s *replSender) queueCopy(path string) {
+	if len(s.copies) == 0 {
+		s.start = s.tail.Start()
+	}
+	s.resend = true
+	for _, c := range s.copies {
+		if c.path == path {
+			c.off = 0
+			return
+		}
+	}
+	s.copies = append(s.copies, &replCopy{path: path})
+}
+
+// copyChunks adds the next pieces of the files being copied to b.
+func (s *replSender) copyChunks(b *replication.Batch) error {
+	for budget := replBatchBytes; len(s.copies) > 0 && budget > 0; {
+		c := s.copies[0]
+		data, eof, err := readChunk(filepath.Join(s.src.dataDir, c.path), c.off, min(replChunk, budget))
+		if errors.Is(err, os.ErrNotExist) {
+			// Removed meanwhile: the next scan sends the removal.
+			s.copies = s.copies[1:]
+			continue
+		}
+		if err != nil {
+			return err
+		}
+		b.Files = append(b.Files, replication.File{
+			Path:   c.path,
+			Offset: c.off,
+			Data:   data,
+			Create: c.off == 0,
+			Done:   eof,
+		})
+		c.off += int64(len(data))
+		budget -= len(data)
+		if eof {
+			s.copies = s.copies[1:]
+		}
+	}
+	return nil
+}
+
+// readChunk reads up to n bytes of path at off; eof reports it reached the
+// end of the file.
+func readChunk(path string, off int64, n int) (data []byte, eof bool, err error) {
+	f, err := os.Open(path)
+	if err != nil {
+		return nil, false, err
+	}
+	defer func() { _ = f.Close() }()
+	buf := make([]byte, n)
+	got, err := f.ReadAt(buf, off)
+	if errors.Is(err, io.EOF) {
+		return buf[:got], true, nil
+	}
+	return buf[:got], false, err
+}
+
+func sortedKeys[V any](m map[string]V) []string {
+	keys := make([]string, 0, len(m))
+	for k := range m {
+		keys = append(keys, k)
+	}
+	slices.Sort(keys)
+	return keys
+}
+
+// Replica opens a replica of the database the primary at addr serves with
+// ServeReplication: read-only, kept up to date in the background until
+// Close. The first Open copies the database from the primary into an empty
+// workDir; later ones resume from where the previous handle stopped, and
+// may serve reads before the primary is reachable again. A workDir that
+// holds a database which is not a replica fails with ErrNotReplica; a
+// replica the primary can no longer catch up stops following it, and
+// ReplicationStatus reports ErrReplicaBehind.
+func (o *OpenOptions) Replica(addr string) *OpenOptions {
+	o.replica = addr
+	return o
+}
+
+// replicaState is the content of replicaStateFile.
+type replicaState struct {
+	Primary string `json:"primary"`
+	LSN     uint64 `json:"lsn"`
+}
+
+// openReplica is Open for Replica: it copies the database from the primary
+// when there is none yet, then opens it read-only and follows the primary.
+func (o *OpenOptions) openReplica(workDir string) (*Database, error) {
+	mode, _ := storageMode(o.cfg)
+	if mode == storage.Memory || workDir == MemoryPath || o.engine != nil {
+		return nil, fmt.Errorf("%w: Replica needs a database in segment files", ErrConflictingOptions)
+	}
+	if workDir == "" && o.cfg != nil {
+		workDir = o.cfg.Storage.Workdir
+	}
+	dir := filepath.Join(filepath.Clean(workDir), "default")
+	f := &follower{addr: o.replica, dir: dir, stop: make(chan struct{}), done: make(chan struct{})}
+	data, err := os.ReadFile(filepath.Join(dir, replicaStateFile))
+	resume := err == nil
+	switch {
+	case resume:
+		if err := json.Unmarshal(data, &f.state); err != nil {
+			return nil, fmt.Errorf("%s: %w", replicaStateFile, err)
+		}
+	case !errors.Is(err, os.ErrNotExist):
+		return nil, err
+	case dirExists(filepath.Join(dir, "tables")):
+		return nil, fmt.Errorf("%w: %s", ErrNotReplica, dir)
+	}
+	f.state.Primary = o.replica
+
+	// A replica that resumes opens even while its primary is unreachable.
+	var first *replication.Batch
+	conn, dialErr := f.dial(resume)
+	if !resume {
+		if dialErr != nil {
+			return nil, dialErr
+		}
+		if first, err = f.catchUp(conn); err != nil {
+			_ = conn.Close()
+			_ = os.RemoveAll(dir)
+			return nil, err
+		}
+	}
+
+	ro := *o
+	ro.replica, ro.readOnly, ro.create = "", true, CreateNever
+	db, err := ro.Open(workDir)
+	if err != nil {
+		if conn != nil {
+			_ = conn.Close()
+		}
+		return nil, err
+	}
+	f.db, f.sm = db, db.SM.Writable()
+	db.follower = f
+	db.repl.update(func(st *replState) {
+		st.role, st.primary, st.lsn, st.err = "replica", f.addr, f.state.LSN, dialErr
+	})
+	if first != nil {
+		// Its files and metadata went in with the snapshot.
+		err = f.apply(&replication.Batch{Records: first.Records, LSN: first.LSN, At: first.At}, conn)
+		if err == nil {
+			err = f.save()
+		}
+		if err != nil {
+			_ = conn.Close()
+			_ = db.Close()
+			return nil, err
+		}
+	}
+	go f.run(conn)
+	return db, nil
+}
+
+// follower keeps a replica up to date with its primary.
+type follower struct {
+	addr  string
+	dir   string // DataDir of the replica
+	db    *Database
+	sm    *storage.StorageManager // writes the pages of the replica
+	state replicaState
+	saved time.Time
+
+	// stale are cached pages the primary changed that were pinned when
+	// they were written: dropped from the buffer pool once unpinned.
+	stale []stalePage
+
+	mu   sync.Mutex
+	conn net.Conn
+	stop chan struct{}
+	done chan struct{}
+}
+
+// stalePage is a page of fs, or every page with pageID -1.
+type stalePage struct {
+	fs     storage.LocalFileSet
+	pageID int64
+}
+
+// dial connects to the primary and opens the stream: a snapshot, or with
+// resume the changes since the LSN f applied up to.
+func (f *follower) dial(resume bool) (net.Conn, error) {
+	conn, err := net.DialTimeout("tcp", f.addr, 10*time.Second)
+	if err != nil {
+		return nil, err
+	}
+	hello := &replication.Hello{Resume: resume, LSN: f.state.LSN}
+	if resume {
+		files, err := replSource{dataDir: f.dir}.files()
+		if err != nil {
+			_ = conn.Close()
+			return nil, err
+		}
+		for _, path := range sortedKeys(files) {
+			hello.Files = append(hello.Files, replication.FileSize{Path: path, Size: files[path].Size()})
+		}
+	}
+	var msg *replication.Message
+	err = replication.WriteMessage(conn, &replication.Message{Hello: hello})
+	if err == nil {
+		msg, err = readReplMessage(conn)
+	}
+	if err == nil && (msg.Welcome == nil || msg.Welcome.Snapshot == resume) {
+		err = errors.New("replication: unexpected answer to hello")
+	}
+	f.mu.Lock()
+	defer f.mu.Unlock()
+	select {
+	case <-f.stop:
+		err = net.ErrClosed
+	default:
+	}
+	if err != nil {
+		_ = conn.Close()
+		return nil, err
+	}
+	f.conn = conn
+	return conn, nil
+}
+
+// readReplMessage reads the next message of the primary, its Error as an
+// error.
+func readReplMessage(conn net.Conn) (*replication.Message, error) {
+	msg, err := replication.ReadMessage(conn)
+	switch {
+	case err != nil:
+		return nil, err
+	case msg.Behind:
+		return nil, fmt.Errorf("%w: %s", ErrReplicaBehind, msg.Error)
+	case msg.Error != "":
+		return nil, fmt.Errorf("replication: primary: %s", msg.Error)
+	}
+	return msg, nil
+}
+
+// catchUp writes the snapshot into f.dir and returns its last batch, whose
+// records are applied once the replica is open.
+func (f *follower) catchUp(conn net.Conn) (*replication.Batch, error) {
+	if err := os.MkdirAll(filepath.Join(f.dir, "tables"), 0o755); err != nil {
+		return nil, err
+	}
+	for {
+		msg, err := readReplMessage(conn)
+		if err != nil {
+			return nil, err
+		}
+		b := msg.Batch
+		if b == nil {
+			continue
+		}
+		for _, file := range slices.Concat(b.Files, b.Meta) {
+			if _, err := f.writeFile(file); err != nil {
+				return nil, err
+			}
+		}
+		if !b.Partial {
+			return b, nil
+		}
+	}
+}
+
+// run follows the primary from conn (nil: not connected) until Close,
+// reconnecting whenever the stream ends.
+func (f *follower) run(conn net.Conn) {
+	defer close(f.done)
+	for {
+		var err error
+		if conn == nil {
+			conn, err = f.dial(true)
+		}
+		if err == nil {
+			err = f.follow(conn)
+			_ = conn.Close()
+			conn = nil
+		}
+		select {
+		case <-f.stop:
+			return
+		default:
+		}
+		f.db.repl.update(func(st *replState) { st.err = err })
+		if errors.Is(err, ErrReplicaBehind) {
+			slog.Error("replication: replica cannot catch up with its primary", "primary", f.addr, "err", err)
+			return
+		}
+		slog.Warn("replication: lost the primary", "primary", f.addr, "err", err)
+		select {
+		case <-f.stop:
+			return
+		case <-time.After(replRetry):
+		}
+	}
+}
+
+// follow applies the batches of conn until the stream ends.
+func (f *follower) follow(conn net.Conn) error {
+	f.db.repl.update(func(st *replState) { st.err = nil })
+	for {
+		msg, err := readReplMessage(conn)
+		if err != nil {
+			return err
+		}
+		if msg.Batch == nil {
+			continue
+		}
+		if err := f.apply(msg.Batch, conn); err != nil {
+			return err
+		}
+	}
+}
+
+// apply applies b and, once the replica is consistent, acknowledges it.
+func (f *follower) apply(b *replication.Batch, conn net.Conn) error {
+	for _, file := range b.Files {
+		done, err := f.writeFile(file)
+		if err != nil {
+			return err
+		}
+		if done {
+			f.dropFileSet(file.Path)
+		}
+	}
+	if len(b.Records) > 0 {
+		if _, err := wal.ApplyRecords(b.Records, f, f.db.SM.PageCipher()); err != nil {
+			return err
+		}
+	}
+	xact, catalog := false, false
+	for _, file := range b.Meta {
+		if _, err := f.writeFile(file); err != nil {
+			return err
+		}
+		xact = xact || file.Path == "xact"
+		catalog = catalog || strings.HasSuffix(file.Path, ".json")
+	}
+	f.dropStale()
+	if xact {
+		if err := f.db.xact.Refresh(filepath.Join(f.dir, "xact")); err != nil {
+			return err
+		}
+	}
+	if catalog {
+		if err := f.db.loadCatalog(); err != nil {
+			return err
+		}
+	}
+
+	f.db.repl.update(func(st *replState) {
+		st.primaryLSN = max(st.primaryLSN, b.LSN)
+		if b.Partial {
+			return
+		}
+		st.lsn = b.LSN
+		st.delay = 0
+		if len(b.Records) > 0 {
+			st.delay = time.Since(b.At)
+		}
+	})
+	if b.Partial {
+		return nil
+	}
+	f.state.LSN = b.LSN
+	if time.Since(f.saved) >= replSaveEvery {
+		if err := f.save(); err != nil {
+			return err
+		}
+	}
+	return replication.WriteMessage(conn, &replication.Message{Ack: &replication.Ack{LSN: b.LSN}})
+}
+
+// WritePage implements wal.PageWriter: pages logged by the primary, in its
+// tables directory, go to the same file of the replica's, straight to disk.
+func (f *follower) WritePage(dir, base string, pageID uint32, page []byte) error {
+	if filepath.Base(dir) != "tables" {
+		return nil
+	}
+	fs := storage.LocalFileSet{Dir: filepath.Join(f.dir, "tables"), Base: base}
+	if err := storage.NewWALWriter(f.sm).WritePage(fs.Dir, base, pageID, page); err != nil {
+		return err
+	}
+	if err := f.db.bp.DropPage(fs, pageID); errors.Is(err, bufferpool.ErrPagePinned) {
+		f.stale = append(f.stale, stalePage{fs: fs, pageID: int64(pageID)})
+	}
+	return nil
+}
+
+// dropFileSet drops the cached pages of the file set the data file at path
+// (relative to the replica's directory) belongs to, which was replaced.
+func (f *follower) dropFileSet(path string) {
+	if replMeta(path) {
+		return
+	}
+	base := filepath.Base(path)
+	if i := strings.LastIndexByte(base, '.'); i > 0 {
+		if _, err := strconv.Atoi(base[i+1:]); err == nil {
+			base = base[:i]
+		}
+	}
+	fs := storage.LocalFileSet{Dir: filepath.Join(f.dir, "tables"), Base: base}
+	_ = f.db.SM.Release(fs)
+	if err := f.db.bp.DropFileSet(fs); errors.Is(err, bufferpool.ErrPagePinned) {
+		f.stale = append(f.stale, stalePage{fs: fs, pageID: -1})
+	}
+}
+
+// dropStale drops the stale pages that are no longer pinned.
+func (f *follower) dropStale() {
+	f.stale = slices.DeleteFunc(f.stale, func(p stalePage) bool {
+		var err error
+		if p.pageID < 0 {
+			err = f.db.bp.DropFileSet(p.fs)
+		} else {
+			err = f.db.bp.DropPage(p.fs, uint32(p.pageID))
+		}
+		return !errors.Is(err, bufferpool.ErrPagePinned)
+	})
+}
+
+// writeFile applies one piece of a file; done reports the file is in place
+// (or removed).
+func (f *follower) writeFile(file replication.File) (done bool, err error) {
+	if !filepath.IsLocal(file.Path) {
+		return false, fmt.Errorf("replication: bad file path %q", file.Path)
+	}
+	path := filepath.Join(f.dir, file.Path)
+	tmp := path + replCopySuffix
+	if file.Remove {
+		_ = os.Remove(tmp)
+		if err := os.Remove(path); err != nil && !errors.Is(err, os.ErrNotExist) {
+			return false, err
+		}
+		return true, nil
+	}
+	if err := os.MkdirAll(filepath.Dir(path), 0o755); err != nil {
+		return false, err
+	}
+	flags := os.O_WRONLY | os.O_CREATE
+	if file.Create {
+		flags |= os.O_TRUNC
+	}
+	out, err := os.OpenFile(tmp, flags, 0o644)
+	if err != nil {
+		return false, err
+	}
+	if _, err := out.WriteAt(file.Data, file.Offset); err != nil {
+		_ = out.Close()
+		return false, err
+	}
+	if file.Done {
+		if err := out.Sync(); err != nil {
+			_ = out.Close()
+			return false, err
+		}
+	}
+	if err := out.Close(); err != nil || !file.Done {
+		return false, err
+	}
+	return true, os.Rename(tmp, path)
+}
+
+// save persists the LSN f applied up to, once the files it went into are
+// on disk.
+func (f *follower) save() error {
+	if err := syncDataDir(f.sm, f.dir)(); err != nil {
+		return err
+	}
+	data, err := json.MarshalIndent(f.state, "", "  ")
+	if err != nil {
+		return err
+	}
+	f.saved = time.Now()
+	return writeFileAtomic(filepath.Join(f.dir, replicaStateFile), data, 0o644)
+}
+
+// close stops following the primary and saves where the replica stands.
+func (f *follower) close() error {
+	close(f.stop)
+	f.mu.Lock()
+	if f.conn != nil {
+		_ = f.conn.Close()
+	}
+	f.mu.Unlock()
+	<-f.done
+	return f.save()
+}
@@ -0,0 +1,93 @@
+package novasql
+
+import (
+	"net"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestReplication(t *testing.T) {
+	dir := t.TempDir()
+	primary, err := Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = primary.Close() })
+	users, err := primary.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 10 {
+		_, err := users.Insert([]any{int64(i), "u"})
+		require.NoError(t, err)
+	}
+
+	src, err := OpenReadOnly(dir)
+	require.NoError(t, err)
+	ln, err := net.Listen("tcp", "127.0.0.1:0")
+	require.NoError(t, err)
+	served := make(chan error, 1)
+	go func() { served <- src.ServeReplication(ln) }()
+	t.Cleanup(func() {
+		_ = ln.Close()
+		require.NoError(t, <-served)
+		_ = src.Close()
+	})
+
+	// A new replica starts from a snapshot.
+	rdir := t.TempDir()
+	replica, err := Options().Replica(ln.Addr().String()).Open(rdir)
+	require.NoError(t, err)
+	tbl, err := replica.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 10, countRows(t, tbl))
+	require.True(t, replica.ReadOnly())
+	_, err = replica.CreateTable("t", testSchema())
+	require.ErrorIs(t, err, ErrReadOnly)
+
+	// Changes stream in, new tables too.
+	rowsOf := func(db *Database, name string) int {
+		tbl, err := db.OpenTable(name)
+		if err != nil {
+			return -1
+		}
+		return countRows(t, tbl)
+	}
+	for i := 10; i < 20; i++ {
+		_, err := users.Insert([]any{int64(i), "u"})
+		require.NoError(t, err)
+	}
+	orders, err := primary.CreateTable("orders", testSchema())
+	require.NoError(t, err)
+	_, err = orders.Insert([]any{int64(1), "o"})
+	require.NoError(t, err)
+	require.Eventually(t, func() bool {
+		return rowsOf(replica, "users") == 20 && rowsOf(replica, "orders") == 1
+	}, 5*time.Second, 20*time.Millisecond)
+
+	require.Eventually(t, func() bool {
+		st := replica.ReplicationStatus()
+		return st.Lag == 0 && st.LSN > 0
+	}, 5*time.Second, 20*time.Millisecond)
+	st := replica.ReplicationStatus()
+	require.Equal(t, "replica", st.Role)
+	require.Equal(t, ln.Addr().String(), st.Primary)
+	require.NoError(t, st.Err)
+	pst := src.ReplicationStatus()
+	require.Equal(t, "primary", pst.Role)
+	require.Len(t, pst.Replicas, 1)
+
+	// A checkpoint, then a restart: the replica resumes where it stopped.
+	require.NoError(t, replica.Close())
+	require.NoError(t, primary.Checkpoint())
+	_, err = users.Insert([]any{int64(20), "u"})
+	require.NoError(t, err)
+	replica, err = Options().Replica(ln.Addr().String()).Open(rdir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = replica.Close() })
+	require.Eventually(t, func() bool { return rowsOf(replica, "users") == 21 }, 5*time.Second, 20*time.Millisecond)
+
+	// A directory holding another database is not a replica.
+	_, err = Options().Replica(ln.Addr().String()).Open(dir)
+	require.ErrorIs(t, err, ErrNotReplica)
+	_, err = replica.SelectDatabase("other")
+	require.ErrorIs(t, err, ErrConflictingOptions)
+}
@@ -0,0 +1,240 @@
+package novasql
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"io/fs"
+	"os"
+	"path/filepath"
+	"strings"
+
+	"github.com/tuannm99/novasql/internal/mvcc"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+var (
+	ErrBackupLabel       = errors.New("novasql: base backup has no usable backup_label")
+	ErrRestoreIncomplete = errors.New("novasql: archive holds changes the base backup cannot take")
+)
+
+// RestoreTarget is where Restore stops replaying the archive: after the last
+// record with an LSN up to LSN, or logged up to Time. The zero target
+// replays the whole archive.
+type RestoreTarget = wal.Target
+
+// Restore recreates a database at workDir from baseBackup, a copy made by
+// Database.BackupTo, and the WAL archived since then under archiveDir (the
+// storage.wal_archive_dir of the database backed up), rolled forward up to
+// target. It returns the restored database, opened with these options.
+//
+// Only page changes are replayed; indexes are rebuilt from the rows. The
+// catalog and the status of BeginSnapshot transactions are not archived, so
+// Restore fails with ErrRestoreIncomplete when the replay reaches pages of
+// tables or indexes the base backup does not have (created, renamed or
+// rewritten after it), or rows of transactions the backup's transaction log
+// does not see finish. Take a new base backup after such changes. WAL not
+// yet archived by a checkpoint is not included. The restored database does
+// not archive: it starts a new WAL history, to be archived elsewhere.
+func (o *OpenOptions) Restore(baseBackup, archiveDir, workDir string, target RestoreTarget) (*Database, error) {
+	if err := o.validate(); err != nil {
+		return nil, err
+	}
+	if mode, _ := storageMode(o.cfg); mode == storage.Memory || workDir == MemoryPath || o.readOnly {
+		return nil, fmt.Errorf("%w: Restore needs a read-write database on disk", ErrConflictingOptions)
+	}
+	src := filepath.Join(filepath.Clean(baseBackup), "default")
+	label, err := readBackupLabel(src)
+	if err != nil {
+		return nil, err
+	}
+	xact, err := os.ReadFile(filepath.Join(src, "xact"))
+	if err != nil && !errors.Is(err, os.ErrNotExist) {
+		return nil, err
+	}
+	dst := filepath.Join(filepath.Clean(workDir), "default")
+	if dirExists(filepath.Join(dst, "tables")) {
+		return nil, fmt.Errorf("%w: %s", ErrDatabaseExists, dst)
+	}
+	if err := copyTree(src, dst); err != nil {
+		_ = os.RemoveAll(dst)
+		return nil, err
+	}
+
+	ro := *o
+	ro.create = CreateNever
+	if o.cfg != nil {
+		cfg := *o.cfg
+		cfg.Storage.WALArchiveDir = ""
+		ro.cfg = &cfg
+	}
+	db, err := ro.Open(workDir)
+	if err != nil {
+		_ = os.RemoveAll(dst)
+		return nil, err
+	}
+	archive := filepath.Join(archiveDir, filepath.Base(label.DataDir))
+	if err := db.replayArchive(label, xact, archive, target); err != nil {
+		_ = db.Close()
+		_ = os.RemoveAll(dst)
+		return nil, err
+	}
+	return db, nil
+}
+
+func readBackupLabel(dir string) (*backupLabel, error) {
+	data, err := os.ReadFile(filepath.Join(dir, backupLabelFile))
+	if err != nil {
+		return nil, fmt.Errorf("%w: %w", ErrBackupLabel, err)
+	}
+	var label backupLabel
+	if err := json.Unmarshal(data, &label); err != nil || label.DataDir == "" {
+		return nil, fmt.Errorf("%w: %s", ErrBackupLabel, filepath.Join(dir, backupLabelFile))
+	}
+	return &label, nil
+}
+
+// copyTree copies the files of the base backup at src to dst, except its
+// label.
+func copyTree(src, dst string) error {
+	err := filepath.WalkDir(src, func(path string, d fs.DirEntry, err error) error {
+		if err != nil {
+			return err
+		}
+		rel, err := filepath.Rel(src, path)
+		if err != nil {
+			return err
+		}
+		target := filepath.Join(dst, rel)
+		switch {
+		case d.IsDir():
+			return os.MkdirAll(target, 0o755)
+		case rel == backupLabelFile || !d.Type().IsRegular():
+			return nil
+		}
+		return copyFile(path, target)
+	})
+	if err != nil {
+		return err
+	}
+	return storage.SyncDir(dst)
+}
+
+func copyFile(src, dst string) error {
+	in, err := os.Open(src)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = in.Close() }()
+	out, err := os.OpenFile(dst, os.O_WRONLY|os.O_CREATE|os.O_TRUNC, 0o644)
+	if err != nil {
+		return err
+	}
+	if _, err := io.Copy(out, in); err != nil {
+		_ = out.Close()
+		return err
+	}
+	return out.Close()
+}
+
+// replayArchive rolls the freshly opened copy forward: archived pages of
+// the heap and overflow files of label.DataDir are written to the same
+// files of db.DataDir, straight to disk, and the buffer pool is dropped so
+// nothing cached hides them. Index roots live in metadata the WAL does not
+// carry, so indexes are rebuilt from the rows instead. xact is the
+// transaction log of the base backup.
+func (db *Database) replayArchive(label *backupLabel, xact []byte, dir string, target RestoreTarget) error {
+	w := &restoreWriter{
+		from:     label.DataDir,
+		to:       db.DataDir,
+		tables:   make(map[string]bool),
+		overflow: make(map[string]bool),
+		indexes:  make(map[string]bool),
+		replayed: make(map[string]map[uint32]bool),
+		w:        storage.NewWALWriter(db.SM),
+	}
+	for _, meta := range db.catalogTables() {
+		w.tables[meta.Name] = true
+		w.overflow[db.overflowFileSet(meta.Name).Base] = true
+		for _, fs := range db.pagedFileSets(meta)[1:] { // after the heap: indexes
+			w.indexes[fs.Base] = true
+		}
+	}
+
+	db.stopCacheWarmup()
+	db.stopBackgroundWriter()
+	if _, err := wal.ReplayArchive(dir, label.LSN, target, w, db.SM.PageCipher()); err != nil {
+		return err
+	}
+	db.resetBufferPool()
+	for _, meta := range db.catalogTables() {
+		if err := db.checkReplayedXIDs(meta, w.replayed[meta.Name], xact); err != nil {
+			return err
+		}
+		if err := db.rebuildIndexes(meta); err != nil {
+			return err
+		}
+	}
+	return db.syncDataFiles()
+}
+
+// checkReplayedXIDs fails unless xact, the transaction log of the base
+// backup, knows how every transaction with row versions on the replayed
+// pages of meta ended. The restored log would count the others as aborted,
+// and hand their XIDs out again.
+func (db *Database) checkReplayedXIDs(meta *TableMeta, pages map[uint32]bool, xact []byte) error {
+	if len(pages) == 0 {
+		return nil
+	}
+	tbl, err := db.OpenTable(meta.Name)
+	if err != nil {
+		return err
+	}
+	for pageID := range pages {
+		xids, err := tbl.PageXIDs(pageID)
+		if err != nil {
+			return err
+		}
+		for _, x := range xids {
+			if x >= mvcc.XID(len(xact)) || mvcc.Status(xact[x]) == mvcc.StatusInProgress {
+				return fmt.Errorf("%w: %s has rows of transaction %d, which had not finished in the base backup",
+					ErrRestoreIncomplete, meta.Name, x)
+			}
+		}
+	}
+	return nil
+}
+
+// restoreWriter maps the file sets of the database backed up to those of
+// the restored one. It replays the heap and overflow pages of the tables
+// the restored catalog knows, noting the heap pages in replayed, drops the
+// pages of their indexes and fails on any other file set.
+type restoreWriter struct {
+	from, to string
+	tables   map[string]bool
+	overflow map[string]bool
+	indexes  map[string]bool
+	replayed map[string]map[uint32]bool // heap pages by table
+	w        wal.PageWriter
+}
+
+func (r *restoreWriter) WritePage(dir, base string, pageID uint32, page []byte) error {
+	rel, err := filepath.Rel(r.from, dir)
+	if err != nil || rel == ".." || strings.HasPrefix(rel, ".."+string(filepath.Separator)) {
+		return nil
+	}
+	switch {
+	case r.indexes[base]:
+		return nil
+	case r.tables[base]:
+		if r.replayed[base] == nil {
+			r.replayed[base] = make(map[uint32]bool)
+		}
+		r.replayed[base][pageID] = true
+	case !r.overflow[base]:
+		return fmt.Errorf("%w: %s is not in the base backup", ErrRestoreIncomplete, filepath.Join(rel, base))
+	}
+	return r.w.WritePage(filepath.Join(r.to, rel), base, pageID, page)
+}
@@ -0,0 +1,92 @@
+package novasql
+
+import (
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal"
+)
+
+func TestRestore_PointInTime(t *testing.T) {
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.WALArchiveDir = filepath.Join(t.TempDir(), "archive")
+	db, err := Options().Config(cfg).Open(t.TempDir())
+	require.NoError(t, err)
+
+	users, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	require.NoError(t, db.CreateIndex("users", "users_name", "name"))
+	insert := func(from, n int, name string) {
+		for i := range n {
+			_, err := users.Insert([]any{int64(from + i), name})
+			require.NoError(t, err)
+		}
+	}
+	insert(0, 100, "base")
+	base := filepath.Join(t.TempDir(), "base")
+	require.NoError(t, db.BackupTo(base))
+
+	insert(100, 100, "a")
+	require.NoError(t, db.Checkpoint())
+	target := RestoreTarget{LSN: db.WAL.LastLSN()}
+	insert(200, 100, "b")
+	require.NoError(t, db.Checkpoint())
+	all := RestoreTarget{LSN: db.WAL.LastLSN()}
+
+	// Changes Restore cannot replay: rows of a transaction the base backup
+	// did not see finish, and a table it does not have.
+	tx, err := db.BeginSnapshot()
+	require.NoError(t, err)
+	_, err = users.InsertTx(tx, []any{int64(300), "tx"})
+	require.NoError(t, err)
+	require.NoError(t, tx.Commit())
+	require.NoError(t, db.Checkpoint())
+	snapshot := RestoreTarget{LSN: db.WAL.LastLSN()}
+	later, err := db.CreateTable("later", testSchema())
+	require.NoError(t, err)
+	_, err = later.Insert([]any{int64(1), "later"})
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+
+	restore := func(target RestoreTarget, want int) {
+		t.Helper()
+		r, err := Options().Config(cfg).Restore(base, cfg.Storage.WALArchiveDir, t.TempDir(), target)
+		require.NoError(t, err)
+		defer func() { require.NoError(t, r.Close()) }()
+
+		tbl, err := r.OpenTable("users")
+		require.NoError(t, err)
+		require.Equal(t, want, countRows(t, tbl))
+		_, err = r.OpenTable("later")
+		require.Error(t, err)
+
+		tree, err := r.OpenKVTreeIndex("users", "users_name")
+		require.NoError(t, err)
+		entries := 0
+		it := tree.Scan(nil, nil)
+		for it.Next() {
+			entries++
+		}
+		require.NoError(t, it.Err())
+		require.Equal(t, want, entries)
+		require.NoError(t, tree.Close())
+	}
+	restore(target, 200)
+	restore(all, 300)
+	for _, target := range []RestoreTarget{snapshot, {}} {
+		dir := t.TempDir()
+		_, err := Options().Config(cfg).Restore(base, cfg.Storage.WALArchiveDir, dir, target)
+		require.ErrorIs(t, err, ErrRestoreIncomplete)
+		require.NoDirExists(t, filepath.Join(dir, "default", "tables"))
+	}
+
+	dir := t.TempDir()
+	r, err := Options().Restore(base, cfg.Storage.WALArchiveDir, dir, all)
+	require.NoError(t, err)
+	require.NoError(t, r.Close())
+	_, err = Options().Restore(base, cfg.Storage.WALArchiveDir, dir, all)
+	require.ErrorIs(t, err, ErrDatabaseExists)
+	_, err = Options().Restore(cfg.Storage.WALArchiveDir, cfg.Storage.WALArchiveDir, t.TempDir(), RestoreTarget{})
+	require.ErrorIs(t, err, ErrBackupLabel)
+}
@@ -0,0 +1,116 @@
+package novasql
+
+import (
+	"bytes"
+	"errors"
+	"fmt"
+	"log/slog"
+)
+
+var ErrNoSavepoint = errors.New("novasql: no such savepoint")
+
+// savepoint is a point of a transaction RollbackTo returns to: how many
+// pages the transaction had touched, and the undo list of the pages written
+// since, their pending contents before the first of those writes.
+type savepoint struct {
+	name  string
+	order int
+	undo  map[*txPage][]byte
+}
+
+// Savepoint marks the current state of the transaction as name, for
+// RollbackTo to undo the writes made after it without ending the
+// transaction. Savepoints nest; a name used again refers to the newest.
+func (tx *Tx) Savepoint(name string) error {
+	tx.mu.Lock()
+	defer tx.mu.Unlock()
+	if tx.done {
+		return ErrTxDone
+	}
+	tx.savepoints = append(tx.savepoints, savepoint{name: name, order: len(tx.order)})
+	return nil
+}
+
+// RollbackTo discards the writes made since savepoint name, which stays, and
+// releases the savepoints set after it. Pages first touched after it are
+// forgotten, so they are not checked for conflicts on Commit either.
+func (tx *Tx) RollbackTo(name string) error {
+	tx.mu.Lock()
+	defer tx.mu.Unlock()
+	if tx.done {
+		return ErrTxDone
+	}
+	i, err := tx.savepointIndex(name)
+	if err != nil {
+		return err
+	}
+	for j := len(tx.savepoints) - 1; j >= i; j-- {
+		for p, data := range tx.savepoints[j].undo {
+			p.data = data
+		}
+	}
+	sp := &tx.savepoints[i]
+	for _, tag := range tx.order[sp.order:] {
+		delete(tx.pages, tag)
+	}
+	slog.Debug("tx: rollback to savepoint", "savepoint", name, "pages", len(tx.order)-sp.order)
+	tx.order = tx.order[:sp.order]
+	sp.undo = nil
+	tx.savepoints = tx.savepoints[:i+1]
+	return nil
+}
+
+// Release forgets savepoint name and the savepoints set after it, keeping
+// their writes: an enclosing savepoint still undoes them.
+func (tx *Tx) Release(name string) error {
+	tx.mu.Lock()
+	defer tx.mu.Unlock()
+	if tx.done {
+		return ErrTxDone
+	}
+	i, err := tx.savepointIndex(name)
+	if err != nil {
+		return err
+	}
+	if i > 0 {
+		outer := &tx.savepoints[i-1]
+		for _, sp := range tx.savepoints[i:] {
+			for p, data := range sp.undo {
+				if _, ok := outer.undo[p]; !ok {
+					outer.keep(p, data)
+				}
+			}
+		}
+	}
+	tx.savepoints = tx.savepoints[:i]
+	return nil
+}
+
+// savepointIndex finds the newest savepoint called name. Caller holds tx.mu.
+func (tx *Tx) savepointIndex(name string) (int, error) {
+	for i := len(tx.savepoints) - 1; i >= 0; i-- {
+		if tx.savepoints[i].name == name {
+			return i, nil
+		}
+	}
+	return -1, fmt.Errorf("%w: %s", ErrNoSavepoint, name)
+}
+
+// remember adds p, about to be written, to the undo list of the innermost
+// savepoint unless it is already there. Caller holds tx.mu.
+func (tx *Tx) remember(p *txPage) {
+	if len(tx.savepoints) == 0 {
+		return
+	}
+	sp := &tx.savepoints[len(tx.savepoints)-1]
+	if _, ok := sp.undo[p]; !ok {
+		sp.keep(p, bytes.Clone(p.data))
+	}
+}
+
+func (sp *savepoint) keep(p *txPage, data []byte) {
+	if sp.undo == nil {
+		sp.undo = make(map[*txPage][]byte)
+	}
+	sp.undo[p] = data
+}
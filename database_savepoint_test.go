@@ -0,0 +1,62 @@
+package novasql
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+func TestTx_Savepoints(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	fs := storage.LocalFileSet{Dir: db.TableDir(), Base: "raw"}
+
+	// txTuples is page pageID as tx sees it.
+	txTuples := func(tx *Tx, pageID uint32) []string {
+		t.Helper()
+		buf, err := tx.GetPage(fs, pageID)
+		require.NoError(t, err)
+		p := &storage.Page{Buf: buf}
+		out := []string{}
+		for i := range p.NumSlots() {
+			raw, err := p.ReadTuple(i)
+			require.NoError(t, err)
+			out = append(out, string(raw))
+		}
+		return out
+	}
+
+	tx, err := db.Begin()
+	require.NoError(t, err)
+	require.NoError(t, tx.WritePage(fs, 0, txPut(t, tx, fs, 0, "a")))
+	require.NoError(t, tx.Savepoint("s1"))
+	require.NoError(t, tx.WritePage(fs, 0, txPut(t, tx, fs, 0, "b")))
+	require.NoError(t, tx.WritePage(fs, 1, txPut(t, tx, fs, 1, "x")))
+	require.NoError(t, tx.Savepoint("s2"))
+	require.NoError(t, tx.WritePage(fs, 0, txPut(t, tx, fs, 0, "c")))
+
+	// Rolling back keeps the savepoint, so it can be rolled back to again.
+	require.NoError(t, tx.RollbackTo("s2"))
+	require.Equal(t, []string{"a", "b"}, txTuples(tx, 0))
+	require.NoError(t, tx.WritePage(fs, 0, txPut(t, tx, fs, 0, "d")))
+	require.NoError(t, tx.RollbackTo("s2"))
+	require.Equal(t, []string{"a", "b"}, txTuples(tx, 0))
+
+	// Released writes stay, and the enclosing savepoint undoes them.
+	require.NoError(t, tx.WritePage(fs, 0, txPut(t, tx, fs, 0, "e")))
+	require.NoError(t, tx.Release("s2"))
+	require.Equal(t, []string{"a", "b", "e"}, txTuples(tx, 0))
+	require.ErrorIs(t, tx.RollbackTo("s2"), ErrNoSavepoint)
+	require.Equal(t, CodeNotFound, CodeOf(tx.Release("s2")))
+	require.NoError(t, tx.RollbackTo("s1"))
+	require.Equal(t, []string{"a"}, txTuples(tx, 0))
+	require.Empty(t, txTuples(tx, 1))
+
+	require.NoError(t, tx.Commit())
+	require.Equal(t, []string{"a"}, pageTuples(t, db, fs, 0))
+	require.Empty(t, pageTuples(t, db, fs, 1))
+	require.ErrorIs(t, tx.Savepoint("s3"), ErrTxDone)
+}
@@ -0,0 +1,275 @@
+package novasql
+
+import (
+	"errors"
+	"fmt"
+	"log/slog"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/mvcc"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+var (
+	ErrUnknownSetting = errors.New("novasql: unknown setting")
+	ErrSettingFixed   = errors.New("novasql: setting cannot be changed while the database is open")
+	ErrBadSetting     = errors.New("novasql: invalid setting value")
+)
+
+// Setting is a configuration parameter of the handle, as SHOW reports it.
+type Setting struct {
+	Name        string
+	Value       string // effective value, defaults applied
+	Description string
+	Changeable  bool // SetSetting and ApplyConfig change it at runtime
+}
+
+// setting is a configuration parameter: get reads its effective value, set
+// stores a new one in a config and apply makes an open handle use it. A
+// setting without set only takes effect when the database is next opened.
+type setting struct {
+	name  string
+	desc  string
+	get   func(db *Database) string
+	set   func(c *internal.NovaSqlConfig, v string) error
+	apply func(db *Database)
+}
+
+// settings is every parameter Settings lists, by name.
+var settings = []setting{
+	{
+		name: "background_writers",
+		desc: "goroutines writing dirty pages ahead of evictions (0 = none)",
+		get:  func(db *Database) string { return strconv.Itoa(db.backgroundWriters()) },
+	},
+	{
+		name: "buffer_pool_pages",
+		desc: "frames of the shared buffer pool",
+		get:  func(db *Database) string { return strconv.Itoa(db.bufferPoolPages()) },
+	},
+	{
+		name: "checkpoint_interval",
+		desc: "how often the background writers checkpoint (0 = by WAL size only)",
+		get:  func(db *Database) string { return db.checkpointInterval().String() },
+		set: func(c *internal.NovaSqlConfig, v string) error {
+			d, err := time.ParseDuration(v)
+			if err == nil && d < 0 {
+				err = errors.New("negative duration")
+			}
+			c.Storage.CheckpointInterval = d
+			return err
+		},
+		apply: func(db *Database) {
+			db.stopBackgroundWriter()
+			db.startBackgroundWriter()
+		},
+	},
+	{
+		name: "checkpoint_wal_bytes",
+		desc: "WAL size that triggers an automatic checkpoint (negative = manual only)",
+		get:  func(db *Database) string { return strconv.FormatInt(db.checkpointWALBytes(), 10) },
+		set: func(c *internal.NovaSqlConfig, v string) error {
+			n, err := parseNonZero(v)
+			c.Storage.CheckpointWALBytes = n
+			return err
+		},
+		apply: func(db *Database) {
+			if db.bp != nil {
+				db.bp.SetAutoCheckpoint(db.checkpointWALBytes(), syncDataDir(db.SM, db.DataDir))
+			}
+		},
+	},
+	{
+		name: "lock_wait_timeout",
+		desc: "how long a row lock request waits",
+		get:  func(db *Database) string { return db.lockWaitTimeout().String() },
+		set: func(c *internal.NovaSqlConfig, v string) error {
+			d, err := time.ParseDuration(v)
+			c.Transaction.LockWaitTimeout = d
+			return err
+		},
+		apply: func(db *Database) {
+			if db.xact != nil {
+				db.xact.SetLockTimeout(db.lockWaitTimeout())
+			}
+		},
+	},
+	{
+		name: "sort_mem_bytes",
+		desc: "memory one sort uses before spilling to temporary files (negative = never spill)",
+		get:  func(db *Database) string { return strconv.FormatInt(db.SortMemBytes(), 10) },
+		set: func(c *internal.NovaSqlConfig, v string) error {
+			n, err := parseNonZero(v)
+			c.Storage.SortMemBytes = n
+			return err
+		},
+	},
+	{
+		name: "statement_timeout",
+		desc: "how long a statement runs before it is cancelled (0 = no limit; a bare number is in milliseconds)",
+		get:  func(db *Database) string { return db.StatementTimeout().String() },
+		set: func(c *internal.NovaSqlConfig, v string) error {
+			d, err := parseMillis(v)
+			c.Transaction.StatementTimeout = d
+			return err
+		},
+	},
+	{
+		name: "sync_mode",
+		desc: "when commits reach the disk: full, normal or off",
+		get:  func(db *Database) string { return db.syncMode().String() },
+		set: func(c *internal.NovaSqlConfig, v string) error {
+			mode, err := wal.ParseSyncMode(v)
+			c.Storage.SyncMode = mode.String()
+			return err
+		},
+		apply: func(db *Database) {
+			if db.WAL != nil {
+				db.WAL.SetSyncMode(db.syncMode())
+			}
+		},
+	},
+	{
+		name: "trash_retention",
+		desc: "how long dropped tables stay in the trash (0 = until purged, negative = no trash)",
+		get:  func(db *Database) string { return db.trashRetention().String() },
+		set: func(c *internal.NovaSqlConfig, v string) error {
+			d, err := time.ParseDuration(v)
+			c.Catalog.TrashRetention = d
+			return err
+		},
+	},
+}
+
+// lockWaitTimeout returns transaction.lock_wait_timeout, or the default.
+func (db *Database) lockWaitTimeout() time.Duration {
+	if db.opts.cfg == nil || db.opts.cfg.Transaction.LockWaitTimeout <= 0 {
+		return mvcc.DefaultLockTimeout
+	}
+	return db.opts.cfg.Transaction.LockWaitTimeout
+}
+
+// StatementTimeout is how long the executor lets one statement run before
+// it cancels it: transaction.statement_timeout, 0 for no limit.
+func (db *Database) StatementTimeout() time.Duration {
+	if db.opts.cfg == nil || db.opts.cfg.Transaction.StatementTimeout < 0 {
+		return 0
+	}
+	return db.opts.cfg.Transaction.StatementTimeout
+}
+
+// parseMillis parses a non-negative duration, taking a bare number as
+// milliseconds the way PostgreSQL takes statement_timeout.
+func parseMillis(v string) (time.Duration, error) {
+	d, err := time.ParseDuration(v)
+	if ms, perr := strconv.ParseInt(v, 10, 64); perr == nil {
+		d, err = time.Duration(ms)*time.Millisecond, nil
+	}
+	if err == nil && d < 0 {
+		err = errors.New("negative duration")
+	}
+	return d, err
+}
+
+// parseNonZero parses a byte size other than 0, which storage.* byte
+// settings reject as Validate does: a negative one turns the feature off.
+func parseNonZero(v string) (int64, error) {
+	n, err := strconv.ParseInt(v, 10, 64)
+	if err == nil && n == 0 {
+		err = errors.New("must not be 0")
+	}
+	return n, err
+}
+
+func lookupSetting(name string) (*setting, error) {
+	for i := range settings {
+		if settings[i].name == strings.ToLower(name) {
+			return &settings[i], nil
+		}
+	}
+	return nil, fmt.Errorf("%w: %s", ErrUnknownSetting, name)
+}
+
+// Settings lists the configuration parameters of the handle, as SHOW ALL
+// does, in name order.
+func (db *Database) Settings() []Setting {
+	out := make([]Setting, len(settings))
+	for i, s := range settings {
+		out[i] = Setting{Name: s.name, Value: s.get(db), Description: s.desc, Changeable: s.set != nil}
+	}
+	return out
+}
+
+// Setting returns configuration parameter name, case-insensitive.
+func (db *Database) Setting(name string) (Setting, error) {
+	s, err := lookupSetting(name)
+	if err != nil {
+		return Setting{}, err
+	}
+	return Setting{Name: s.name, Value: s.get(db), Description: s.desc, Changeable: s.set != nil}, nil
+}
+
+// SetSetting changes configuration parameter name of this handle to value,
+// as SET does, effective from the next statement. Other handles opened with
+// the same config keep theirs. A parameter that is not Changeable fails
+// with ErrSettingFixed: change it in the config and reopen.
+func (db *Database) SetSetting(name, value string) error {
+	if err := db.ensureOpen(); err != nil {
+		return err
+	}
+	s, err := lookupSetting(name)
+	if err != nil {
+		return err
+	}
+	if s.set == nil {
+		return fmt.Errorf("%w: %s", ErrSettingFixed, s.name)
+	}
+	// Copied, so the config shared with other handles is left alone.
+	var cfg internal.NovaSqlConfig
+	if db.opts.cfg != nil {
+		cfg = *db.opts.cfg
+	}
+	if err := s.set(&cfg, strings.TrimSpace(value)); err != nil {
+		return fmt.Errorf("%w: %s = %q", ErrBadSetting, s.name, value)
+	}
+	db.opts.cfg = &cfg
+	if s.apply != nil {
+		s.apply(db)
+	}
+	return nil
+}
+
+// ApplyConfig brings the changeable parameters of the handle to their
+// values in cfg, typically just reloaded from the config file, without
+// reopening it. Parameters that differ but only take effect on open are
+// logged and left as they are. It returns the errors of the parameters it
+// could not apply.
+func (db *Database) ApplyConfig(cfg *internal.NovaSqlConfig) error {
+	probe := &Database{opts: OpenOptions{cfg: cfg}}
+	var errs []error
+	for _, s := range settings {
+		cur, next := s.get(db), s.get(probe)
+		if cur == next {
+			continue
+		}
+		if s.set == nil {
+			slog.Warn("config reload: setting takes effect on restart", "name", s.name, "value", next)
+			continue
+		}
+		errs = append(errs, db.SetSetting(s.name, next))
+	}
+	return errors.Join(errs...)
+}
+
+// ReloadConfig reads the config file at path and applies it as ApplyConfig
+// does.
+func (db *Database) ReloadConfig(path string) error {
+	cfg, err := internal.LoadConfig(path)
+	if err != nil {
+		return err
+	}
+	return db.ApplyConfig(cfg)
+}
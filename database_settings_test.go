@@ -0,0 +1,99 @@
+package novasql
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+func TestSettings(t *testing.T) {
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.SyncMode = "normal"
+	db, err := Options().Config(cfg).Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	s, err := db.Setting("SYNC_MODE")
+	require.NoError(t, err)
+	require.Equal(t, Setting{
+		Name: "sync_mode", Value: "normal", Description: s.Description, Changeable: true,
+	}, s)
+	all := db.Settings()
+	require.Len(t, all, len(settings))
+	require.Equal(t, "background_writers", all[0].Name)
+	require.False(t, all[0].Changeable)
+
+	require.NoError(t, db.SetSetting("sync_mode", "off"))
+	require.NoError(t, db.SetSetting("lock_wait_timeout", "250ms"))
+	require.NoError(t, db.SetSetting("checkpoint_wal_bytes", "-1"))
+	require.NoError(t, db.SetSetting("statement_timeout", "1500"))
+	require.Equal(t, wal.SyncOff, db.syncMode())
+	require.Equal(t, 250*time.Millisecond, db.lockWaitTimeout())
+	require.Equal(t, int64(-1), db.checkpointWALBytes())
+	require.Equal(t, 1500*time.Millisecond, db.StatementTimeout())
+	require.NoError(t, db.SetSetting("statement_timeout", "2s"))
+	require.Equal(t, 2*time.Second, db.StatementTimeout())
+	require.ErrorIs(t, db.SetSetting("statement_timeout", "-5"), ErrBadSetting)
+	require.ErrorIs(t, db.SetSetting("checkpoint_wal_bytes", "0"), ErrBadSetting)
+	require.ErrorIs(t, db.SetSetting("sort_mem_bytes", "0"), ErrBadSetting)
+	require.Equal(t, int64(-1), db.checkpointWALBytes())
+	// The config the handle was opened with is left alone.
+	require.Equal(t, "normal", cfg.Storage.SyncMode)
+
+	err = db.SetSetting("sync_mode", "always")
+	require.ErrorIs(t, err, ErrBadSetting)
+	require.Equal(t, CodeInvalid, CodeOf(err))
+	require.Equal(t, wal.SyncOff, db.syncMode())
+	err = db.SetSetting("buffer_pool_pages", "64")
+	require.ErrorIs(t, err, ErrSettingFixed)
+	_, err = db.Setting("colour")
+	require.ErrorIs(t, err, ErrUnknownSetting)
+	require.Equal(t, CodeNotFound, CodeOf(err))
+
+	// A reload applies what changed and leaves what needs a reopen.
+	path := filepath.Join(t.TempDir(), "novasql.yaml")
+	require.NoError(t, os.WriteFile(path, []byte(`storage:
+  sync_mode: full
+  buffer_pool_pages: 64
+  sort_mem_bytes: 1024
+`), 0o600))
+	require.NoError(t, db.ReloadConfig(path))
+	require.Equal(t, wal.SyncFull, db.syncMode())
+	require.Equal(t, int64(1024), db.SortMemBytes())
+	require.Equal(t, int64(defaultCheckpointWALBytes), db.checkpointWALBytes())
+	require.NotEqual(t, 64, db.bufferPoolPages())
+
+	require.Error(t, db.ReloadConfig(filepath.Join(t.TempDir(), "missing.yaml")))
+}
+
+func TestSettings_CheckpointInterval(t *testing.T) {
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.BackgroundWriters = 1
+	cfg.Storage.CheckpointInterval = time.Hour
+	db, err := Options().Config(cfg).Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	_, err = tbl.Insert([]any{int64(1), "user"})
+	require.NoError(t, err)
+
+	// A reload restarts the writers with the new interval.
+	n := db.BufferPoolStats().Checkpoints
+	next := *cfg
+	next.Storage.CheckpointInterval = time.Millisecond
+	require.NoError(t, db.ApplyConfig(&next))
+	s, err := db.Setting("checkpoint_interval")
+	require.NoError(t, err)
+	require.Equal(t, "1ms", s.Value)
+	require.Eventually(t, func() bool { return db.BufferPoolStats().Checkpoints > n },
+		5*time.Second, 10*time.Millisecond)
+
+	require.ErrorIs(t, db.SetSetting("checkpoint_interval", "-1s"), ErrBadSetting)
+	require.ErrorIs(t, db.SetSetting("background_writers", "2"), ErrSettingFixed)
+}
@@ -0,0 +1,82 @@
+package novasql
+
+import (
+	"errors"
+	"log/slog"
+	"os"
+	"runtime"
+
+	"github.com/tuannm99/novasql/internal/bufferpool"
+	"github.com/tuannm99/novasql/internal/mvcc"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+// shutdown holds what Close flushes and releases, apart from the Database:
+// the cleanup that shuts a handle down once it is garbage collected without
+// Close must not reach the handle itself, or it would never be collected.
+type shutdown struct {
+	dataDir string
+	memDir  string
+	bp      *bufferpool.GlobalPool
+	wal     *wal.Manager
+	xact    *mvcc.Manager
+	sm      *storage.StorageManager
+	lock    *storage.DirLock
+	warmup  func() // stops the cache warm-up (nil = none running)
+	writer  func() // stops the background writers (nil = none running)
+}
+
+// watchUnclosed arranges for the handle to be checkpointed and its files
+// and lock released if it becomes unreachable while still open, so an
+// embedder that forgets Close loses nothing and leaves no lock behind.
+func (db *Database) watchUnclosed() {
+	db.unclosed = &shutdown{}
+	db.noteShutdown()
+	db.cleanup = runtime.AddCleanup(db, (*shutdown).run, db.unclosed)
+}
+
+// noteShutdown records the current pool, logs and files of the handle for
+// its cleanup; call it whenever they change.
+func (db *Database) noteShutdown() {
+	if db.unclosed == nil {
+		return
+	}
+	*db.unclosed = shutdown{
+		dataDir: db.DataDir,
+		memDir:  db.memDir,
+		bp:      db.bp,
+		wal:     db.WAL,
+		xact:    db.xact,
+		sm:      db.SM,
+		lock:    db.lock,
+		warmup:  db.stopWarmup,
+		writer:  db.stopWriter,
+	}
+}
+
+// run is Close for a handle nobody closed.
+func (s *shutdown) run() {
+	if s.warmup != nil {
+		s.warmup()
+	}
+	if s.writer != nil {
+		s.writer()
+	}
+	var errs []error
+	if s.bp != nil {
+		errs = append(errs, s.bp.Checkpoint(syncDataDir(s.sm, s.dataDir)))
+	}
+	if s.wal != nil {
+		errs = append(errs, s.wal.Close())
+	}
+	if s.xact != nil {
+		errs = append(errs, s.xact.Close())
+	}
+	errs = append(errs, s.sm.Close(), s.lock.Unlock())
+	if s.memDir != "" {
+		errs = append(errs, os.RemoveAll(s.memDir))
+	}
+	slog.Warn("novasql: database garbage collected without Close; closed it",
+		"dir", s.dataDir, "err", errors.Join(errs...))
+}
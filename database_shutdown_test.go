@@ -0,0 +1,59 @@
+package novasql
+
+import (
+	"os"
+	"path/filepath"
+	"runtime"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func TestClose_Unclosed(t *testing.T) {
+	dir := t.TempDir()
+	walPath, size := openAndForget(t, dir)
+
+	// Once the handle is collected its pages are checkpointed, which
+	// truncates the WAL.
+	for range 200 {
+		runtime.GC()
+		st, err := os.Stat(walPath)
+		require.NoError(t, err)
+		if st.Size() < size {
+			break
+		}
+		time.Sleep(10 * time.Millisecond)
+	}
+	st, err := os.Stat(walPath)
+	require.NoError(t, err)
+	require.Less(t, st.Size(), size)
+
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	tbl, err := db.OpenTable("items")
+	require.NoError(t, err)
+	require.Equal(t, 100, countRows(t, tbl))
+}
+
+// openAndForget loads rows into a new database in dir and drops the handle
+// without closing it. It returns the WAL file of the database and its size.
+func openAndForget(t *testing.T, dir string) (string, int64) {
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	_, err = db.CreateTable("items", record.Schema{Cols: []record.Column{{Name: "id", Type: record.ColInt64}}})
+	require.NoError(t, err)
+	rows := make([][]any, 100)
+	for i := range rows {
+		rows[i] = []any{int64(i)}
+	}
+	_, err = db.InsertRows("items", rows)
+	require.NoError(t, err)
+
+	path := filepath.Join(db.layout.WAL, "wal.log")
+	st, err := os.Stat(path)
+	require.NoError(t, err)
+	return path, st.Size()
+}
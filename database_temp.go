@@ -0,0 +1,142 @@
+package novasql
+
+import (
+	"fmt"
+	"maps"
+	"slices"
+
+	"github.com/tuannm99/novasql/internal/bufferpool"
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+// tempPoolPages is the number of frames of the buffer pool of temporary
+// tables; colder pages go to the files in TempDir.
+const tempPoolPages = 256
+
+// tempTables are the temporary tables of a handle. Their pages live in a
+// pool of their own, without WAL, and in files under the handle's TempDir;
+// the metadata stays in memory.
+type tempTables struct {
+	pool   *bufferpool.GlobalPool // nil until the first table is created
+	tables map[string]*TableMeta
+}
+
+// CreateTempTable creates a table that only this handle sees and that is
+// discarded, pages and all, when the handle closes or selects another
+// database. Nothing about it is logged, so it is gone after a crash as well.
+//
+// Temporary tables take rows, queries and DROP TABLE like any other; they
+// cannot have indexes, constraints or foreign keys, and their names must not
+// be used by a table or view of the database.
+func (db *Database) CreateTempTable(name string, schema record.Schema) (*heap.Table, error) {
+	if err := db.ensureWritable(); err != nil {
+		return nil, err
+	}
+	if err := validateIdent(name); err != nil {
+		return nil, err
+	}
+	if db.relationExists(name) {
+		return nil, fmt.Errorf("%w: %s", ErrTableExists, name)
+	}
+	dir, err := db.TempDir()
+	if err != nil {
+		return nil, err
+	}
+	if db.temp.pool == nil {
+		db.temp.pool = bufferpool.NewGlobalPool(db.SM, tempPoolPages, nil)
+		db.temp.pool.SetMetrics(db.opts.metrics)
+		db.temp.tables = make(map[string]*TableMeta)
+	}
+
+	now := db.now()
+	db.temp.tables[name] = &TableMeta{Name: name, Schema: schema, CreatedAt: now, UpdatedAt: now}
+	db.bumpSchemaVersion()
+	return db.openTempTable(name, dir)
+}
+
+// TempTables returns the temporary tables of the handle, by name.
+func (db *Database) TempTables() []*TableMeta {
+	out := make([]*TableMeta, 0, len(db.temp.tables))
+	for _, name := range slices.Sorted(maps.Keys(db.temp.tables)) {
+		out = append(out, cloneTableMeta(db.temp.tables[name]))
+	}
+	return out
+}
+
+func (db *Database) isTempTable(name string) bool {
+	_, ok := db.temp.tables[name]
+	return ok
+}
+
+// tempFileSets returns where the pages of temporary table name live.
+func tempFileSets(dir, name string) (heapFS, ovfFS storage.LocalFileSet) {
+	return storage.LocalFileSet{Dir: dir, Base: name}, storage.LocalFileSet{Dir: dir, Base: name + "_ovf"}
+}
+
+func (db *Database) openTempTable(name, dir string) (*heap.Table, error) {
+	meta := db.temp.tables[name]
+	heapFS, ovfFS := tempFileSets(dir, name)
+	pageCount, err := db.SM.CountPages(heapFS)
+	if err != nil {
+		return nil, err
+	}
+	pageCount = max(pageCount, meta.PageCount)
+
+	ovf := storage.NewOverflowManagerWithWAL(ovfFS, nil)
+	ovf.SetStorage(db.SM)
+	tbl := heap.NewTable(name, meta.Schema, db.SM, heapFS, db.temp.pool.View(heapFS), ovf, pageCount)
+	tbl.SetPageCountHook(func(pc uint32) error {
+		meta.PageCount = pc
+		meta.UpdatedAt = db.now()
+		return nil
+	})
+	tbl.SetMVCC(db.xact)
+	return tbl, nil
+}
+
+// dropTempTable discards temporary table name.
+func (db *Database) dropTempTable(name string) error {
+	heapFS, ovfFS := tempFileSets(db.tempDir, name)
+	for _, fs := range []storage.LocalFileSet{heapFS, ovfFS} {
+		if err := db.temp.pool.DropFileSet(fs); err != nil {
+			return err
+		}
+	}
+	if err := db.removeTempFiles(name); err != nil {
+		return err
+	}
+	delete(db.temp.tables, name)
+	db.bumpSchemaVersion()
+	return nil
+}
+
+func (db *Database) removeTempFiles(name string) error {
+	heapFS, ovfFS := tempFileSets(db.tempDir, name)
+	for _, fs := range []storage.LocalFileSet{heapFS, ovfFS} {
+		if err := db.SM.Release(fs); err != nil {
+			return err
+		}
+		if err := db.SM.RemoveSegments(fs); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// discardTempTables drops every temporary table, with their pool, before the
+// handle's TempDir is removed.
+func (db *Database) discardTempTables() error {
+	if len(db.temp.tables) == 0 {
+		return nil
+	}
+	for name := range db.temp.tables {
+		if err := db.removeTempFiles(name); err != nil {
+			return err
+		}
+	}
+	db.temp = tempTables{}
+	db.bumpSchemaVersion()
+	return nil
+}
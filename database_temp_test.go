@@ -0,0 +1,59 @@
+package novasql
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestDatabase_TempTable(t *testing.T) {
+	dir := t.TempDir()
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+
+	_, err = db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	_, err = db.CreateTempTable("users", testSchema())
+	require.ErrorIs(t, err, ErrTableExists)
+
+	v := db.SchemaVersion()
+	tbl, err := db.CreateTempTable("scratch", testSchema())
+	require.NoError(t, err)
+	require.NotEqual(t, v, db.SchemaVersion())
+	for i := range 500 {
+		_, err := tbl.Insert([]any{int64(i), "row"})
+		require.NoError(t, err)
+	}
+	_, err = db.CreateTable("scratch", testSchema())
+	require.ErrorIs(t, err, ErrTableExists)
+
+	// Only the handle sees it, and not through the catalog.
+	again, err := db.OpenTable("scratch")
+	require.NoError(t, err)
+	require.Equal(t, 500, countRows(t, again))
+	require.Len(t, db.TempTables(), 1)
+	metas, err := db.ListTables()
+	require.NoError(t, err)
+	require.Len(t, metas, 1)
+	st, err := db.TableStats("scratch")
+	require.NoError(t, err)
+	require.Nil(t, st)
+
+	tmp, err := db.TempDir()
+	require.NoError(t, err)
+	require.NoError(t, db.DropTable("scratch"))
+	_, err = db.OpenTable("scratch")
+	require.ErrorIs(t, err, ErrTableNotFound)
+
+	_, err = db.CreateTempTable("scratch", testSchema())
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+	require.NoDirExists(t, tmp)
+
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+	_, err = db.OpenTable("scratch")
+	require.ErrorIs(t, err, ErrTableNotFound)
+	require.Empty(t, db.TempTables())
+}
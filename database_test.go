@@ -0,0 +1,328 @@
+package novasql
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/storage/storagetest"
+)
+
+func testSchema() record.Schema {
+	return record.Schema{
+		Cols: []record.Column{
+			{Name: "id", Type: record.ColInt64, Nullable: false},
+			{Name: "name", Type: record.ColText, Nullable: false},
+		},
+	}
+}
+
+func TestDatabase_CacheWarmupAfterReopen(t *testing.T) {
+	dir := t.TempDir()
+
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Cache.Warmup = true
+
+	db := NewDatabaseWithConfig(dir, cfg)
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 50 {
+		_, err := tbl.Insert([]any{int64(i), "user"})
+		require.NoError(t, err)
+	}
+	require.NoError(t, db.Close())
+
+	db = NewDatabaseWithConfig(dir, cfg)
+	t.Cleanup(func() { _ = db.Close() })
+
+	require.Eventually(t, func() bool {
+		return db.BufferPoolStats().Warmup.Done
+	}, 5*time.Second, 5*time.Millisecond)
+
+	st := db.BufferPoolStats()
+	require.Positive(t, st.Warmup.Loaded)
+	require.Equal(t, uint64(st.Warmup.Loaded), st.Reads)
+	require.True(t, db.bp.Contains(db.tableFileSet("users"), 0))
+}
+
+func TestDatabase_NoWarmupByDefault(t *testing.T) {
+	dir := t.TempDir()
+
+	db := NewDatabase(dir)
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	_, err = tbl.Insert([]any{int64(1), "a"})
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+
+	db = NewDatabase(dir)
+	t.Cleanup(func() { _ = db.Close() })
+
+	st := db.BufferPoolStats()
+	require.False(t, st.Warmup.Active)
+	require.Equal(t, 0, st.Resident)
+}
+
+func TestDatabase_PageVersionsSurviveReopen(t *testing.T) {
+	dir := t.TempDir()
+
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	_, err = tbl.Insert([]any{int64(1), "a"})
+	require.NoError(t, err)
+
+	fs := db.tableFileSet("users")
+	buf, v, err := db.GetPageVersioned(fs, 0)
+	require.NoError(t, err)
+	require.Positive(t, v)
+
+	nv, err := db.WritePageIfVersion(fs, 0, buf, v)
+	require.NoError(t, err)
+	require.Equal(t, v+1, nv)
+
+	_, err = db.WritePageIfVersion(fs, 0, buf, v)
+	var conflict *VersionConflictError
+	require.ErrorAs(t, err, &conflict)
+	require.Equal(t, nv, conflict.Actual)
+	require.NoError(t, db.Close())
+
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	_, v, err = db.GetPageVersioned(db.tableFileSet("users"), 0)
+	require.NoError(t, err)
+	require.Equal(t, nv, v)
+}
+
+func TestDatabase_BufferPoolPagesConfig(t *testing.T) {
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.BufferPoolPages = 4
+
+	db, err := Options().Config(cfg).Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	require.Equal(t, 4, db.BufferPoolStats().Capacity)
+
+	// More pages than frames: eviction keeps the pool at capacity and every
+	// row stays readable.
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 2000 {
+		_, err := tbl.Insert([]any{int64(i), "some user name to fill pages"})
+		require.NoError(t, err)
+	}
+	require.Equal(t, 2000, countRows(t, tbl))
+
+	st := db.BufferPoolStats()
+	require.LessOrEqual(t, st.Resident, 4)
+	require.Positive(t, st.Misses)
+}
+
+func TestDatabase_MmapMode(t *testing.T) {
+	dir := t.TempDir()
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.Mode = "mmap"
+	cfg.Storage.BufferPoolPages = 4
+
+	db, err := Options().Config(cfg).Open(dir)
+	require.NoError(t, err)
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 500 {
+		_, err := tbl.Insert([]any{int64(i), "some user name to fill pages"})
+		require.NoError(t, err)
+	}
+	require.Equal(t, 500, countRows(t, tbl))
+	require.NoError(t, db.Close())
+
+	// The files are the same as in classic mode.
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	tbl, err = db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 500, countRows(t, tbl))
+}
+
+func TestDatabase_MemoryMode(t *testing.T) {
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.BufferPoolPages = 4
+
+	db, err := Options().Config(cfg).Open(MemoryPath)
+	require.NoError(t, err)
+	require.True(t, db.SM.InMemory())
+	require.Nil(t, db.WAL)
+	memDir := db.WorkDir
+
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 500 {
+		_, err := tbl.Insert([]any{int64(i), "some user name to fill pages"})
+		require.NoError(t, err)
+	}
+	// A row large enough to spill into the overflow file.
+	_, err = tbl.Insert([]any{int64(500), string(make([]byte, 3*storage.PageSize))})
+	require.NoError(t, err)
+	require.NoError(t, db.CreateIndex("users", "users_id", "id"))
+	require.NoError(t, db.Checkpoint())
+
+	require.NoError(t, db.RenameTable("users", "people"))
+	tbl, err = db.OpenTable("people")
+	require.NoError(t, err)
+	require.Equal(t, 501, countRows(t, tbl))
+	corrupted, err := db.VerifyIntegrity()
+	require.NoError(t, err)
+	require.Empty(t, corrupted)
+
+	// Metadata files only: no segment file was written.
+	files, err := filepath.Glob(filepath.Join(db.tableDir(), "people*"))
+	require.NoError(t, err)
+	for _, f := range files {
+		require.Contains(t, filepath.Base(f), ".json")
+	}
+
+	require.NoError(t, db.DropTablePurge("people"))
+	tbl, err = db.CreateTable("people", testSchema())
+	require.NoError(t, err)
+	require.Zero(t, countRows(t, tbl))
+
+	require.NoError(t, db.Close())
+	require.NoDirExists(t, memDir)
+
+	// storage.mode: memory leaves the work directory alone.
+	dir := t.TempDir()
+	cfg.Storage.Mode = "memory"
+	db, err = Options().Config(cfg).Open(dir)
+	require.NoError(t, err)
+	_, err = db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+	ents, err := os.ReadDir(dir)
+	require.NoError(t, err)
+	require.Empty(t, ents)
+}
+
+func TestDatabase_RecoverAfterCrash(t *testing.T) {
+	dir := t.TempDir()
+
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 100 {
+		_, err := tbl.Insert([]any{int64(i), "user"})
+		require.NoError(t, err)
+	}
+	// Crash: abandon the handle without flushing the buffer pool.
+	db.closeWAL()
+	db.closed = true
+
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	tbl, err = db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 100, countRows(t, tbl))
+
+	// Recovery checkpoints the log once the pages are on disk.
+	size, err := db.WAL.Size()
+	require.NoError(t, err)
+	require.Less(t, size, int64(storage.PageSize))
+}
+
+func TestDatabase_CloseTruncatesWAL(t *testing.T) {
+	dir := t.TempDir()
+	seedDatabase(t, dir)
+
+	info, err := os.Stat(filepath.Join(dir, "default", "wal", "wal.log"))
+	require.NoError(t, err)
+	require.Less(t, info.Size(), int64(storage.PageSize))
+}
+
+func TestDatabase_AutoCheckpoint(t *testing.T) {
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.CheckpointWALBytes = 8 * storage.PageSize
+
+	db, err := Options().Config(cfg).Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+
+	// Every insert logs a page image; the WAL is truncated whenever it
+	// reaches the limit instead of growing with the table.
+	for i := range 50 {
+		_, err := tbl.Insert([]any{int64(i), "user"})
+		require.NoError(t, err)
+	}
+	require.Positive(t, db.BufferPoolStats().Checkpoints)
+	size, err := db.WAL.Size()
+	require.NoError(t, err)
+	require.Less(t, size, cfg.Storage.CheckpointWALBytes+2*storage.PageSize)
+	require.Equal(t, 50, countRows(t, tbl))
+
+	// Manual checkpoints count too and leave only the checkpoint record.
+	before := db.BufferPoolStats().Checkpoints
+	require.NoError(t, db.Checkpoint())
+	require.Equal(t, before+1, db.BufferPoolStats().Checkpoints)
+	size, err = db.WAL.Size()
+	require.NoError(t, err)
+	require.Less(t, size, int64(storage.PageSize))
+}
+
+func TestDatabase_BackgroundWriter(t *testing.T) {
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.BackgroundWriters = 2
+	cfg.Storage.CheckpointInterval = time.Millisecond
+
+	db, err := Options().Config(cfg).Open(t.TempDir())
+	require.NoError(t, err)
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 10 {
+		_, err := tbl.Insert([]any{int64(i), "user"})
+		require.NoError(t, err)
+	}
+
+	// The writers checkpoint without anybody asking, and move with the
+	// selected database.
+	require.Eventually(t, func() bool { return db.BufferPoolStats().Checkpoints > 0 },
+		5*time.Second, 10*time.Millisecond)
+	_, err = db.SelectDatabase("other")
+	require.NoError(t, err)
+	require.NotNil(t, db.stopWriter)
+	require.NoError(t, db.Close())
+	require.Nil(t, db.stopWriter)
+}
+
+func TestDatabase_BackgroundWriterSurvivesFailedSwitch(t *testing.T) {
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.BackgroundWriters = 1
+	cfg.Storage.CheckpointInterval = time.Hour
+	engine := storagetest.NewFaultEngine()
+
+	db, err := Options().Config(cfg).Engine(engine).Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	tbl, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	_, err = tbl.Insert([]any{int64(1), "user"})
+	require.NoError(t, err)
+
+	// The checkpoint before the switch fails: the handle stays on its
+	// database, writers included.
+	engine.InjectAt(1, storagetest.FailIO)
+	_, err = db.SelectDatabase("other")
+	require.Error(t, err)
+	require.Equal(t, "default", filepath.Base(db.DataDir))
+	require.NotNil(t, db.stopWriter)
+}
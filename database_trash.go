@@ -0,0 +1,370 @@
+package novasql
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io/fs"
+	"log/slog"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"time"
+
+	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+const (
+	// trashDirName lives inside the tables directory. The leading dot keeps it
+	// out of the identifier namespace, so it can never clash with a table.
+	trashDirName   = ".trash"
+	trashEntryFile = "entry.json"
+)
+
+var ErrTrashNotFound = errors.New("novasql: no such table in trash")
+
+// RestoreConflictError is returned when a trashed table cannot be restored
+// because a live table already uses its name.
+type RestoreConflictError struct {
+	Table   string
+	TrashID string
+}
+
+func (e *RestoreConflictError) Error() string {
+	return fmt.Sprintf(
+		"novasql: cannot restore %q: table already exists; restore under a new name with RestoreTableAs(%q, newName)",
+		e.Table, e.TrashID,
+	)
+}
+
+// TrashEntry describes a soft-deleted table. Its segments (heap, overflow,
+// indexes) are kept in <tables>/.trash/<ID>/ until restored or purged.
+type TrashEntry struct {
+	ID        string    `json:"id"`
+	Table     TableMeta `json:"table"`
+	DroppedAt time.Time `json:"dropped_at"`
+}
+
+// SpaceUsage reports on-disk bytes of the current database.
+type SpaceUsage struct {
+	Tables int64 // live tables, indexes and their metadata
+	Trash  int64 // soft-deleted tables waiting in the trash
+}
+
+func (db *Database) trashDir() string {
+	return filepath.Join(db.tableDir(), trashDirName)
+}
+
+func (db *Database) trashEntryDir(id string) string {
+	return filepath.Join(db.trashDir(), id)
+}
+
+func (db *Database) trashRetention() time.Duration {
+	if db.opts.cfg == nil {
+		return 0
+	}
+	return db.opts.cfg.Catalog.TrashRetention
+}
+
+// DropTable soft-deletes a table: its catalog entry moves to the trash and its
+// pages stay on disk, so RestoreTable can bring it back. Use DropTablePurge
+// (or DROP TABLE ... PURGE) to reclaim the space immediately.
+func (db *Database) DropTable(name string) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	if err := validateIdent(name); err != nil {
+		return err
+	}
+	if db.isTempTable(name) {
+		return db.dropTempTable(name)
+	}
+	if err := db.checkNotReferenced(name, ""); err != nil {
+		return err
+	}
+	if db.trashRetention() < 0 {
+		return db.DropTablePurge(name)
+	}
+
+	meta, err := db.readTableMeta(name)
+	if err != nil {
+		if errors.Is(err, os.ErrNotExist) {
+			// Nothing to keep; clean up whatever segments are left.
+			return db.DropTablePurge(name)
+		}
+		return err
+	}
+
+	entry := TrashEntry{Table: *meta, DroppedAt: db.now()}
+	entry.ID, err = db.newTrashID(name, entry.DroppedAt)
+	if err != nil {
+		return err
+	}
+	dst := db.trashEntryDir(entry.ID)
+	if err := os.MkdirAll(dst, 0o755); err != nil {
+		return err
+	}
+
+	// Record the entry first so a half-moved drop is still listed (and purgeable).
+	if err := db.writeTrashEntry(&entry); err != nil {
+		return err
+	}
+	if err := db.moveTableFiles(meta, db.tableDir(), dst, name); err != nil {
+		return err
+	}
+	entry.Table = *meta
+	if err := db.writeTrashEntry(&entry); err != nil {
+		return err
+	}
+	if err := os.Remove(db.tableMetaPath(name)); err != nil && !errors.Is(err, os.ErrNotExist) {
+		return err
+	}
+	db.forgetTableMeta(name)
+
+	if ret := db.trashRetention(); ret > 0 {
+		if _, err := db.PurgeTrash(ret); err != nil {
+			slog.Warn("trash: purge of expired entries failed", "err", err)
+		}
+	}
+	return nil
+}
+
+func (db *Database) newTrashID(name string, at time.Time) (string, error) {
+	for n := at.UnixNano(); ; n++ {
+		id := fmt.Sprintf("%s-%d", name, n)
+		if _, err := os.Stat(db.trashEntryDir(id)); errors.Is(err, os.ErrNotExist) {
+			return id, nil
+		} else if err != nil {
+			return "", err
+		}
+	}
+}
+
+func (db *Database) writeTrashEntry(e *TrashEntry) error {
+	data, err := json.MarshalIndent(e, "", "  ")
+	if err != nil {
+		return err
+	}
+	return writeFileAtomic(filepath.Join(db.trashEntryDir(e.ID), trashEntryFile), data, 0o644)
+}
+
+// moveTableFiles moves heap, overflow and btree index files of meta from
+// srcDir to dstDir, naming them after newName there. Cached pages of the
+// source are flushed and dropped first.
+func (db *Database) moveTableFiles(meta *TableMeta, srcDir, dstDir, newName string) error {
+	oldName := meta.Name
+	heapSrc := storage.LocalFileSet{Dir: srcDir, Base: oldName}
+	ovfSrc := storage.LocalFileSet{Dir: srcDir, Base: oldName + "_ovf"}
+
+	if err := db.flushAndDropFileSet(heapSrc); err != nil {
+		return err
+	}
+	if err := db.flushAndDropFileSet(ovfSrc); err != nil {
+		return err
+	}
+	for i := range meta.Indexes {
+		im := &meta.Indexes[i]
+		if !im.hasFiles() {
+			continue
+		}
+		base := im.FileBase
+		if base == "" {
+			base = db.fmtIndexBase(oldName, im.Name)
+		}
+		src := storage.LocalFileSet{Dir: srcDir, Base: base}
+		if err := db.flushAndDropFileSet(src); err != nil {
+			return err
+		}
+		newBase := db.fmtIndexBase(newName, im.Name)
+		if err := btree.RenameIndex(db.SM, src, storage.LocalFileSet{Dir: dstDir, Base: newBase}); err != nil {
+			return err
+		}
+		im.FileBase = newBase
+	}
+
+	if err := db.SM.RenameSegments(heapSrc, storage.LocalFileSet{Dir: dstDir, Base: newName}); err != nil {
+		return err
+	}
+	return db.SM.RenameSegments(ovfSrc, storage.LocalFileSet{Dir: dstDir, Base: newName + "_ovf"})
+}
+
+// ListTrash returns the soft-deleted tables of the current database, oldest first.
+func (db *Database) ListTrash() ([]TrashEntry, error) {
+	if err := db.ensureOpen(); err != nil {
+		return nil, err
+	}
+	dirs, err := os.ReadDir(db.trashDir())
+	if err != nil {
+		if errors.Is(err, os.ErrNotExist) {
+			return nil, nil
+		}
+		return nil, err
+	}
+
+	out := make([]TrashEntry, 0, len(dirs))
+	for _, d := range dirs {
+		if !d.IsDir() {
+			continue
+		}
+		data, err := os.ReadFile(filepath.Join(db.trashEntryDir(d.Name()), trashEntryFile))
+		if err != nil {
+			slog.Warn("trash: unreadable entry", "id", d.Name(), "err", err)
+			continue
+		}
+		var e TrashEntry
+		if err := json.Unmarshal(data, &e); err != nil {
+			slog.Warn("trash: corrupt entry", "id", d.Name(), "err", err)
+			continue
+		}
+		out = append(out, e)
+	}
+	sort.Slice(out, func(i, j int) bool { return out[i].DroppedAt.Before(out[j].DroppedAt) })
+	return out, nil
+}
+
+// findTrash resolves ref, either a trash ID or a table name. For a name, the
+// most recently dropped table wins.
+func (db *Database) findTrash(ref string) (*TrashEntry, error) {
+	entries, err := db.ListTrash()
+	if err != nil {
+		return nil, err
+	}
+	var found *TrashEntry
+	for i := range entries {
+		e := &entries[i]
+		if e.ID == ref {
+			return e, nil
+		}
+		if e.Table.Name == ref {
+			found = e
+		}
+	}
+	if found == nil {
+		return nil, fmt.Errorf("%w: %s", ErrTrashNotFound, ref)
+	}
+	return found, nil
+}
+
+// RestoreTable brings a dropped table back under its original name.
+// ref is a table name or a TrashEntry ID. If a live table already has that
+// name, *RestoreConflictError is returned; use RestoreTableAs instead.
+func (db *Database) RestoreTable(ref string) error {
+	return db.RestoreTableAs(ref, "")
+}
+
+// RestoreTableAs restores the trashed table ref as newName ("" keeps the
+// original name).
+func (db *Database) RestoreTableAs(ref, newName string) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	entry, err := db.findTrash(ref)
+	if err != nil {
+		return err
+	}
+	if newName == "" {
+		newName = entry.Table.Name
+	}
+	if err := validateIdent(newName); err != nil {
+		return err
+	}
+
+	if _, err := os.Stat(db.tableMetaPath(newName)); err == nil {
+		return &RestoreConflictError{Table: newName, TrashID: entry.ID}
+	} else if !errors.Is(err, os.ErrNotExist) {
+		return err
+	}
+
+	meta := entry.Table
+	if err := db.moveTableFiles(&meta, db.trashEntryDir(entry.ID), db.tableDir(), newName); err != nil {
+		return err
+	}
+	meta.Name = newName
+	if err := db.writeTableMeta(&meta); err != nil {
+		return err
+	}
+	if err := db.SM.RemoveDir(db.trashEntryDir(entry.ID)); err != nil {
+		return err
+	}
+	return os.RemoveAll(db.trashEntryDir(entry.ID))
+}
+
+// PurgeTrash permanently deletes trashed tables dropped at least olderThan
+// ago (0 purges everything) and returns how many were removed.
+func (db *Database) PurgeTrash(olderThan time.Duration) (int, error) {
+	if err := db.ensureWritable(); err != nil {
+		return 0, err
+	}
+	entries, err := db.ListTrash()
+	if err != nil {
+		return 0, err
+	}
+	cutoff := db.now().Add(-olderThan)
+	n := 0
+	for _, e := range entries {
+		if e.DroppedAt.After(cutoff) {
+			continue
+		}
+		if err := db.SM.RemoveDir(db.trashEntryDir(e.ID)); err != nil {
+			return n, err
+		}
+		if err := os.RemoveAll(db.trashEntryDir(e.ID)); err != nil {
+			return n, err
+		}
+		n++
+	}
+	return n, nil
+}
+
+// purgeExpiredTrash applies catalog.trash_retention (best-effort).
+func (db *Database) purgeExpiredTrash() {
+	ret := db.trashRetention()
+	if ret <= 0 || db.opts.readOnly {
+		return
+	}
+	if n, err := db.PurgeTrash(ret); err != nil {
+		slog.Warn("trash: purge of expired entries failed", "err", err)
+	} else if n > 0 {
+		slog.Info("trash: purged expired tables", "count", n)
+	}
+}
+
+// SpaceUsage sums file sizes of the current database, attributing trashed
+// tables separately from live ones.
+func (db *Database) SpaceUsage() (SpaceUsage, error) {
+	var u SpaceUsage
+	if err := db.ensureOpen(); err != nil {
+		return u, err
+	}
+	// Flush first so sizes reflect every write.
+	if err := db.FlushAllPools(); err != nil {
+		return u, err
+	}
+
+	root := db.tableDir()
+	trash := db.trashDir() + string(filepath.Separator)
+	err := filepath.WalkDir(root, func(path string, d fs.DirEntry, err error) error {
+		if err != nil {
+			if errors.Is(err, os.ErrNotExist) {
+				return nil
+			}
+			return err
+		}
+		if d.IsDir() {
+			return nil
+		}
+		info, err := d.Info()
+		if err != nil {
+			return err
+		}
+		if strings.HasPrefix(path, trash) {
+			u.Trash += info.Size()
+		} else {
+			u.Tables += info.Size()
+		}
+		return nil
+	})
+	return u, err
+}
@@ -0,0 +1,177 @@
+package novasql
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/heap"
+)
+
+func countRows(t *testing.T, tbl *heap.Table) int {
+	t.Helper()
+
+	n := 0
+	require.NoError(t, tbl.Scan(func(heap.TID, []any) error {
+		n++
+		return nil
+	}))
+	return n
+}
+
+func TestTrash_DropRestorePurge(t *testing.T) {
+	dir := t.TempDir()
+	seedDatabase(t, dir)
+
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	tree, err := db.CreateBTreeIndex("users", "users_id", "id")
+	require.NoError(t, err)
+	require.NoError(t, tree.Insert(1, heap.TID{PageID: 0, Slot: 1}))
+	require.NoError(t, tree.Close())
+	before, err := db.SpaceUsage()
+	require.NoError(t, err)
+	require.Zero(t, before.Trash)
+	heapInfo, err := os.Stat(filepath.Join(db.tableDir(), "users"))
+	require.NoError(t, err)
+
+	// Drop: gone from the catalog, but the bytes stay (now as trash).
+	require.NoError(t, db.DropTable("users"))
+	_, err = db.OpenTable("users")
+	require.Error(t, err)
+	tables, err := db.ListTables()
+	require.NoError(t, err)
+	require.Empty(t, tables)
+
+	entries, err := db.ListTrash()
+	require.NoError(t, err)
+	require.Len(t, entries, 1)
+	require.Equal(t, "users", entries[0].Table.Name)
+
+	after, err := db.SpaceUsage()
+	require.NoError(t, err)
+	require.GreaterOrEqual(t, after.Trash, heapInfo.Size())
+	require.Less(t, after.Tables, before.Tables)
+	trashed, err := os.Stat(filepath.Join(db.trashEntryDir(entries[0].ID), "users"))
+	require.NoError(t, err)
+	require.Equal(t, heapInfo.Size(), trashed.Size())
+
+	// Restore: rows and index are back.
+	require.NoError(t, db.RestoreTable("users"))
+	tbl, err := db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 3, countRows(t, tbl))
+	idx, err := db.OpenBTreeIndex("users", "users_id")
+	require.NoError(t, err)
+	tids, err := idx.SearchEqual(1)
+	require.NoError(t, err)
+	require.Len(t, tids, 1)
+
+	restored, err := db.SpaceUsage()
+	require.NoError(t, err)
+	require.Zero(t, restored.Trash)
+
+	// Purge: the space is released.
+	require.NoError(t, db.DropTable("users"))
+	n, err := db.PurgeTrash(0)
+	require.NoError(t, err)
+	require.Equal(t, 1, n)
+
+	purged, err := db.SpaceUsage()
+	require.NoError(t, err)
+	require.Zero(t, purged.Trash)
+	require.Less(t, purged.Tables, before.Tables)
+	require.NoFileExists(t, filepath.Join(db.tableDir(), "users"))
+
+	entries, err = db.ListTrash()
+	require.NoError(t, err)
+	require.Empty(t, entries)
+	require.ErrorIs(t, db.RestoreTable("users"), ErrTrashNotFound)
+}
+
+func TestTrash_RestoreConflict(t *testing.T) {
+	dir := t.TempDir()
+	seedDatabase(t, dir)
+
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	require.NoError(t, db.DropTable("users"))
+	_, err = db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+
+	err = db.RestoreTable("users")
+	var conflict *RestoreConflictError
+	require.ErrorAs(t, err, &conflict)
+	require.ErrorContains(t, err, "RestoreTableAs")
+
+	require.NoError(t, db.RestoreTableAs(conflict.TrashID, "users_old"))
+	tbl, err := db.OpenTable("users_old")
+	require.NoError(t, err)
+	require.Equal(t, 3, countRows(t, tbl))
+
+	tbl, err = db.OpenTable("users")
+	require.NoError(t, err)
+	require.Zero(t, countRows(t, tbl))
+}
+
+func TestTrash_Retention(t *testing.T) {
+	now := time.Date(2024, 1, 1, 0, 0, 0, 0, time.UTC)
+	clock := func() time.Time { return now }
+
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Catalog.TrashRetention = time.Hour
+
+	dir := t.TempDir()
+	seedDatabase(t, dir)
+
+	db, err := Options().Config(cfg).Clock(clock).Open(dir)
+	require.NoError(t, err)
+	require.NoError(t, db.DropTable("users"))
+	require.NoError(t, db.Close())
+
+	// Still within retention.
+	now = now.Add(30 * time.Minute)
+	db, err = Options().Config(cfg).Clock(clock).Open(dir)
+	require.NoError(t, err)
+	entries, err := db.ListTrash()
+	require.NoError(t, err)
+	require.Len(t, entries, 1)
+	require.NoError(t, db.Close())
+
+	// Expired entries are purged on open.
+	now = now.Add(time.Hour)
+	db, err = Options().Config(cfg).Clock(clock).Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	entries, err = db.ListTrash()
+	require.NoError(t, err)
+	require.Empty(t, entries)
+}
+
+func TestTrash_Disabled(t *testing.T) {
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Catalog.TrashRetention = -1
+
+	dir := t.TempDir()
+	seedDatabase(t, dir)
+
+	db, err := Options().Config(cfg).Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	require.NoError(t, db.DropTable("users"))
+	entries, err := db.ListTrash()
+	require.NoError(t, err)
+	require.Empty(t, entries)
+
+	_, err = os.Stat(filepath.Join(db.tableDir(), "users"))
+	require.ErrorIs(t, err, os.ErrNotExist)
+}
@@ -0,0 +1,56 @@
+package novasql
+
+import (
+	"errors"
+	"fmt"
+	"slices"
+	"time"
+
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+// Expiring rows
+//
+// A table with a TTL column (CREATE TABLE ... WITH (ttl_column = c), or
+// SetTTL) keeps each row until the time in c. Expired rows are deleted by
+// sweeps (executor.ReapExpired), which the server runs every
+// storage.ttl_sweep_interval; until the next sweep they read like any other
+// row. Rows whose c is NULL never expire.
+
+var ErrTTLColumn = errors.New("novasql: TTL column must be a TIMESTAMP column")
+
+// SetTTL makes column, a TIMESTAMP column of table, its TTL column; ""
+// makes the rows of table never expire.
+func (db *Database) SetTTL(table, column string) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	if err := validateIdent(table); err != nil {
+		return ErrIndexBadTable
+	}
+	meta, err := db.readTableMeta(table)
+	if err != nil {
+		return err
+	}
+	if column != "" {
+		pos := slices.IndexFunc(meta.Schema.Cols, func(c record.Column) bool { return c.Name == column })
+		if pos < 0 {
+			return fmt.Errorf("%w: %s.%s", ErrColumnNotFound, table, column)
+		}
+		if meta.Schema.Cols[pos].Type != record.ColTimestamp {
+			return fmt.Errorf("%w: %s.%s", ErrTTLColumn, table, column)
+		}
+	}
+	meta.TTLColumn = column
+	return db.writeTableMeta(meta)
+}
+
+// TTLSweepInterval is how often expired rows are swept: it is
+// storage.ttl_sweep_interval, or one minute; negative means never.
+func (db *Database) TTLSweepInterval() time.Duration {
+	if db.opts.cfg == nil || db.opts.cfg.Storage.TTLSweepInterval == 0 {
+		return internal.DefaultTTLSweepInterval
+	}
+	return db.opts.cfg.Storage.TTLSweepInterval
+}
@@ -0,0 +1,41 @@
+package novasql
+
+import (
+	"bytes"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func TestSetTTL(t *testing.T) {
+	dir := t.TempDir()
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+
+	_, err = db.CreateTable("sessions", record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64},
+		{Name: "expires_at", Type: record.ColTimestamp, Nullable: true},
+	}})
+	require.NoError(t, err)
+	require.ErrorIs(t, db.SetTTL("sessions", "id"), ErrTTLColumn)
+	require.ErrorIs(t, db.SetTTL("sessions", "nope"), ErrColumnNotFound)
+	require.Error(t, db.SetTTL("missing", "expires_at"))
+	require.NoError(t, db.SetTTL("sessions", "expires_at"))
+	require.ErrorIs(t, db.DropColumn("sessions", "expires_at"), ErrColumnInUse)
+
+	// The TTL column is kept in the metadata and dumped.
+	require.NoError(t, db.Close())
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	var out bytes.Buffer
+	require.NoError(t, db.Dump(&out))
+	require.Equal(t, "CREATE TABLE sessions (id INT NOT NULL, expires_at TIMESTAMP) WITH (ttl_column = expires_at);\n",
+		out.String())
+
+	require.NoError(t, db.SetTTL("sessions", ""))
+	require.NoError(t, db.DropColumn("sessions", "expires_at"))
+	require.Equal(t, time.Minute, db.TTLSweepInterval())
+}
@@ -0,0 +1,181 @@
+package novasql
+
+import (
+	"errors"
+	"log/slog"
+	"sync"
+	"time"
+
+	"github.com/tuannm99/novasql/internal/bufferpool"
+	"github.com/tuannm99/novasql/internal/metrics"
+	"github.com/tuannm99/novasql/internal/mvcc"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+var ErrTxDone = errors.New("novasql: transaction already committed or rolled back")
+
+// Tx buffers page writes and applies them atomically on Commit.
+//
+// Isolation is optimistic: the first time a transaction touches a page it
+// remembers the page version, and Commit fails with *VersionConflictError if
+// any of those pages was written by someone else in the meantime. Reads see
+// the transaction's own pending writes.
+type Tx struct {
+	db *Database
+
+	mu    sync.Mutex
+	pages map[bufferpool.PageTag]*txPage
+	order []bufferpool.PageTag // first-touch order, so commits are deterministic
+	done  bool
+
+	savepoints []savepoint // innermost last; see database_savepoint.go
+}
+
+type txPage struct {
+	fs      storage.FileSet
+	pageID  uint32
+	version uint64 // version seen when the page was first touched
+	data    []byte // pending contents; nil if only read
+}
+
+// Begin starts a transaction on the current database.
+func (db *Database) Begin() (*Tx, error) {
+	if err := db.ensureWritable(); err != nil {
+		return nil, err
+	}
+	return &Tx{db: db, pages: make(map[bufferpool.PageTag]*txPage)}, nil
+}
+
+// BeginSnapshot starts a row-level snapshot-isolation transaction for the
+// heap's InsertTx/GetTx/ScanTx/UpdateTx/DeleteTx. Unlike Begin it never
+// blocks or fails readers: each sees the rows committed before its snapshot.
+func (db *Database) BeginSnapshot() (*mvcc.Tx, error) {
+	if err := db.ensureWritable(); err != nil {
+		return nil, err
+	}
+	return db.xact.Begin()
+}
+
+// touch returns the transaction's entry for (fs,pageID), reading the page
+// from the pool on first use. Caller holds tx.mu.
+func (tx *Tx) touch(fs storage.FileSet, pageID uint32) (*txPage, []byte, error) {
+	key, _, ok := storage.FsKeyOf(fs)
+	if !ok {
+		return nil, nil, bufferpool.ErrUnsupportedFileSet
+	}
+	tag := bufferpool.PageTag{FSKey: key, PageID: pageID}
+	if p, ok := tx.pages[tag]; ok {
+		return p, nil, nil
+	}
+
+	buf, v, err := tx.db.GetPageVersioned(fs, pageID)
+	if err != nil {
+		return nil, nil, err
+	}
+	p := &txPage{fs: fs, pageID: pageID, version: v}
+	tx.pages[tag] = p
+	tx.order = append(tx.order, tag)
+	return p, buf, nil
+}
+
+// GetPage returns a copy of the page as this transaction sees it.
+func (tx *Tx) GetPage(fs storage.FileSet, pageID uint32) ([]byte, error) {
+	tx.mu.Lock()
+	defer tx.mu.Unlock()
+	if tx.done {
+		return nil, ErrTxDone
+	}
+
+	p, buf, err := tx.touch(fs, pageID)
+	if err != nil {
+		return nil, err
+	}
+	if p.data != nil {
+		return append([]byte(nil), p.data...), nil
+	}
+	if buf == nil {
+		// Read before, not written: read again, the version is checked at commit.
+		if buf, _, err = tx.db.GetPageVersioned(fs, pageID); err != nil {
+			return nil, err
+		}
+	}
+	return buf, nil
+}
+
+// WritePage buffers data as the new contents of the page. Nothing reaches the
+// buffer pool or the WAL before Commit.
+func (tx *Tx) WritePage(fs storage.FileSet, pageID uint32, data []byte) error {
+	if len(data) != storage.PageSize {
+		return storage.ErrWrongSize
+	}
+	tx.mu.Lock()
+	defer tx.mu.Unlock()
+	if tx.done {
+		return ErrTxDone
+	}
+
+	p, _, err := tx.touch(fs, pageID)
+	if err != nil {
+		return err
+	}
+	tx.remember(p)
+	p.data = append(p.data[:0], data...)
+	return nil
+}
+
+// Commit applies all buffered writes as one durable WAL group. On error
+// nothing is applied; the transaction is finished either way.
+func (tx *Tx) Commit() error {
+	tx.mu.Lock()
+	defer tx.mu.Unlock()
+	if tx.done {
+		return ErrTxDone
+	}
+	tx.done = true
+
+	if err := tx.db.ensureWritable(); err != nil {
+		tx.db.opts.metrics.Add(metrics.TxAborts, 1)
+		return err
+	}
+	writes := make([]bufferpool.TxWrite, 0, len(tx.order))
+	dirty := false
+	for _, tag := range tx.order {
+		p := tx.pages[tag]
+		dirty = dirty || p.data != nil
+		writes = append(writes, bufferpool.TxWrite{
+			FS:       p.fs,
+			PageID:   p.pageID,
+			Data:     p.data,
+			Expected: p.version,
+		})
+	}
+	if !dirty {
+		tx.db.opts.metrics.Add(metrics.TxCommits, 1)
+		return nil
+	}
+	start := time.Now()
+	if err := tx.db.bp.CommitTx(writes); err != nil {
+		tx.db.opts.metrics.Add(metrics.TxAborts, 1)
+		slog.Debug("tx: commit failed", "pages", len(writes), "err", err)
+		return err
+	}
+	tx.db.opts.metrics.Add(metrics.TxCommits, 1)
+	slog.Debug("tx: commit", "pages", len(writes), "duration", time.Since(start))
+	return nil
+}
+
+// Rollback discards all buffered writes.
+func (tx *Tx) Rollback() error {
+	tx.mu.Lock()
+	defer tx.mu.Unlock()
+	if tx.done {
+		return ErrTxDone
+	}
+	slog.Debug("tx: rollback", "pages", len(tx.order))
+	tx.done = true
+	tx.pages = nil
+	tx.order = nil
+	tx.savepoints = nil
+	tx.db.opts.metrics.Add(metrics.TxAborts, 1)
+	return nil
+}
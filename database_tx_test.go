@@ -0,0 +1,157 @@
+package novasql
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+// txPut returns page pageID as tx sees it with tuple data appended.
+func txPut(t *testing.T, tx *Tx, fs storage.FileSet, pageID uint32, data string) []byte {
+	t.Helper()
+
+	buf, err := tx.GetPage(fs, pageID)
+	require.NoError(t, err)
+	p := &storage.Page{Buf: buf}
+	_, err = p.InsertTuple([]byte(data))
+	require.NoError(t, err)
+	return p.Buf
+}
+
+func pageTuples(t *testing.T, db *Database, fs storage.FileSet, pageID uint32) []string {
+	t.Helper()
+
+	buf, _, err := db.GetPageVersioned(fs, pageID)
+	require.NoError(t, err)
+	p := &storage.Page{Buf: buf}
+	out := make([]string, 0, p.NumSlots())
+	for i := range p.NumSlots() {
+		raw, err := p.ReadTuple(i)
+		require.NoError(t, err)
+		out = append(out, string(raw))
+	}
+	return out
+}
+
+func TestTx_CommitAndRollback(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	fs := storage.LocalFileSet{Dir: db.TableDir(), Base: "raw"}
+
+	tx, err := db.Begin()
+	require.NoError(t, err)
+	require.NoError(t, tx.WritePage(fs, 0, txPut(t, tx, fs, 0, "a")))
+	require.NoError(t, tx.WritePage(fs, 1, txPut(t, tx, fs, 1, "b")))
+
+	// Own writes are visible inside the transaction only.
+	require.NoError(t, tx.WritePage(fs, 0, txPut(t, tx, fs, 0, "a2")))
+	require.Empty(t, pageTuples(t, db, fs, 0))
+
+	require.NoError(t, tx.Commit())
+	require.Equal(t, []string{"a", "a2"}, pageTuples(t, db, fs, 0))
+	require.Equal(t, []string{"b"}, pageTuples(t, db, fs, 1))
+	require.ErrorIs(t, tx.Commit(), ErrTxDone)
+
+	tx, err = db.Begin()
+	require.NoError(t, err)
+	require.NoError(t, tx.WritePage(fs, 0, txPut(t, tx, fs, 0, "c")))
+	require.NoError(t, tx.Rollback())
+	require.Equal(t, []string{"a", "a2"}, pageTuples(t, db, fs, 0))
+	require.ErrorIs(t, tx.WritePage(fs, 0, make([]byte, storage.PageSize)), ErrTxDone)
+}
+
+func TestTx_ConflictAppliesNothing(t *testing.T) {
+	db, err := Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	fs := storage.LocalFileSet{Dir: db.TableDir(), Base: "raw"}
+
+	tx1, err := db.Begin()
+	require.NoError(t, err)
+	require.NoError(t, tx1.WritePage(fs, 0, txPut(t, tx1, fs, 0, "tx1")))
+	require.NoError(t, tx1.WritePage(fs, 1, txPut(t, tx1, fs, 1, "tx1")))
+
+	tx2, err := db.Begin()
+	require.NoError(t, err)
+	require.NoError(t, tx2.WritePage(fs, 1, txPut(t, tx2, fs, 1, "tx2")))
+	require.NoError(t, tx2.Commit())
+
+	var conflict *VersionConflictError
+	require.ErrorAs(t, tx1.Commit(), &conflict)
+	require.Empty(t, pageTuples(t, db, fs, 0))
+	require.Equal(t, []string{"tx2"}, pageTuples(t, db, fs, 1))
+
+	// Pages that were only read are validated too.
+	tx3, err := db.Begin()
+	require.NoError(t, err)
+	_, err = tx3.GetPage(fs, 1)
+	require.NoError(t, err)
+	require.NoError(t, tx3.WritePage(fs, 0, txPut(t, tx3, fs, 0, "tx3")))
+
+	buf, v, err := db.GetPageVersioned(fs, 1)
+	require.NoError(t, err)
+	_, err = db.WritePageIfVersion(fs, 1, buf, v)
+	require.NoError(t, err)
+
+	require.ErrorAs(t, tx3.Commit(), &conflict)
+	require.Empty(t, pageTuples(t, db, fs, 0))
+}
+
+func TestTx_CommittedSurvivesCrash(t *testing.T) {
+	dir := t.TempDir()
+
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	fs := storage.LocalFileSet{Dir: db.TableDir(), Base: "raw"}
+
+	tx, err := db.Begin()
+	require.NoError(t, err)
+	require.NoError(t, tx.WritePage(fs, 0, txPut(t, tx, fs, 0, "durable")))
+	require.NoError(t, tx.WritePage(fs, 3, txPut(t, tx, fs, 3, "too")))
+	require.NoError(t, tx.Commit())
+
+	// Crash: the pool is never flushed, only the WAL has the pages.
+	db.closeWAL()
+	db.closed = true
+
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	require.Equal(t, []string{"durable"}, pageTuples(t, db, fs, 0))
+	require.Equal(t, []string{"too"}, pageTuples(t, db, fs, 3))
+}
+
+func TestBeginSnapshot_StatusSurvivesReopen(t *testing.T) {
+	dir := t.TempDir()
+	seedDatabase(t, dir)
+
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	tbl, err := db.OpenTable("users")
+	require.NoError(t, err)
+
+	done, err := db.BeginSnapshot()
+	require.NoError(t, err)
+	_, err = tbl.InsertTx(done, []any{int64(10), "committed"})
+	require.NoError(t, err)
+	require.NoError(t, done.Commit())
+	open, err := db.BeginSnapshot()
+	require.NoError(t, err)
+	_, err = tbl.InsertTx(open, []any{int64(11), "lost"})
+	require.NoError(t, err)
+	require.Equal(t, 4, countRows(t, tbl))
+	require.NoError(t, db.Close())
+
+	// The transaction left open is aborted by the reopen.
+	db, err = Options().ReadOnly(true).Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	tbl, err = db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 4, countRows(t, tbl))
+	_, err = db.BeginSnapshot()
+	require.ErrorIs(t, err, ErrReadOnly)
+}
@@ -0,0 +1,240 @@
+package novasql
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"log/slog"
+	"os"
+	"path/filepath"
+
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+var ErrVacuumBusy = errors.New("novasql: VACUUM cannot run while transactions are open")
+
+// vacuumSuffix marks the file sets a table is rewritten into. Table names
+// are identifiers, so it cannot clash with a table.
+const vacuumSuffix = ".vacuum"
+
+// VacuumStats reports what Vacuum did.
+type VacuumStats struct {
+	Tables       int    // tables rewritten
+	DeadVersions int    // row versions no snapshot could see any more
+	PagesBefore  uint32 // pages of the heap, overflow and index files, before
+	PagesAfter   uint32 // and after
+}
+
+// Vacuum compacts every table of the selected database: it drops the row
+// versions no snapshot can see (Table.Vacuum), rewrites the remaining rows
+// into packed heap and overflow files that replace the old ones, and
+// rebuilds the indexes, so the space of deleted rows, freed overflow chains
+// and index pages goes back to the OS. Rows get new TIDs, so it refuses to
+// run while a transaction is open, and tables opened before are stale.
+func (db *Database) Vacuum() (VacuumStats, error) {
+	var st VacuumStats
+	if err := db.ensureWritable(); err != nil {
+		return st, err
+	}
+	if db.xact != nil && db.xact.Running() > 0 {
+		return st, ErrVacuumBusy
+	}
+	for _, meta := range db.catalogTables() {
+		if err := db.vacuumTable(meta, &st); err != nil {
+			return st, fmt.Errorf("vacuum %s: %w", meta.Name, err)
+		}
+	}
+	return st, db.Checkpoint()
+}
+
+func (db *Database) vacuumTable(meta *TableMeta, st *VacuumStats) error {
+	if err := db.recoverRewrite(meta.Name, vacuumSuffix); err != nil {
+		return err
+	}
+	before, err := db.tablePages(meta)
+	if err != nil {
+		return err
+	}
+	tbl, err := db.OpenTable(meta.Name)
+	if err != nil {
+		return err
+	}
+	dead, err := tbl.Vacuum()
+	if err != nil {
+		return err
+	}
+
+	if err := db.rewriteTable(meta.Name, meta.Schema, vacuumSuffix, func(dst *heap.Table) error {
+		_, err := tbl.RewriteInto(dst)
+		return err
+	}); err != nil {
+		return err
+	}
+
+	// Index the rows at their new TIDs.
+	if err := db.rebuildIndexes(meta); err != nil {
+		return err
+	}
+	if _, err := db.OpenTable(meta.Name); err != nil { // records the new page count
+		return err
+	}
+
+	after, err := db.tablePages(meta)
+	if err != nil {
+		return err
+	}
+	st.Tables++
+	st.DeadVersions += dead
+	st.PagesBefore += before
+	st.PagesAfter += after
+	return nil
+}
+
+// rewriteTable replaces the heap and overflow files of table name with those
+// rewrite fills: an empty table with schema, in file sets named after
+// suffix. Once they are durable and checkpointed, a swap marker records
+// their sizes and they are renamed over the old ones; the marker goes once
+// the directory is synced. Callers run recoverRewrite with the same suffix
+// before they open the table.
+func (db *Database) rewriteTable(
+	name string,
+	schema record.Schema,
+	suffix string,
+	rewrite func(dst *heap.Table) error,
+) error {
+	heapFS, ovfFS, tmpFS, tmpOvfFS := db.rewriteFileSets(name, suffix)
+	ovf := storage.NewOverflowManagerWithWAL(tmpOvfFS, db.WAL)
+	ovf.SetStorage(db.SM)
+	dst := heap.NewTable(name, schema, db.SM, tmpFS, db.viewFor(tmpFS), ovf, 0)
+	if err := rewrite(dst); err != nil {
+		_ = db.removeFileSets(tmpFS, tmpOvfFS)
+		return err
+	}
+
+	return db.swapIn(heapFS, ovfFS, tmpFS, tmpOvfFS)
+}
+
+// swapIn writes out and fsyncs the rewritten file sets, each drop from the
+// pool checkpointing, so that the WAL holds no page of them or of the old
+// ones. It then marks the swap and renames them over the old ones.
+func (db *Database) swapIn(heapFS, ovfFS, tmpFS, tmpOvfFS storage.LocalFileSet) error {
+	for _, fs := range []storage.LocalFileSet{tmpFS, tmpOvfFS, heapFS, ovfFS} {
+		if err := db.flushAndDropFileSet(fs); err != nil {
+			return err
+		}
+	}
+	var swap rewriteSwap
+	var err error
+	if swap.HeapPages, err = db.SM.CountPages(tmpFS); err != nil {
+		return err
+	}
+	if swap.OverflowPages, err = db.SM.CountPages(tmpOvfFS); err != nil {
+		return err
+	}
+	data, err := json.Marshal(swap)
+	if err != nil {
+		return err
+	}
+	if err := writeFileAtomic(swapPath(tmpFS), data, 0o644); err != nil {
+		return err
+	}
+	return db.finishSwap(heapFS, ovfFS, tmpFS, tmpOvfFS, swap)
+}
+
+// rewriteSwap is the swap marker of rewriteTable: the size of the files
+// renamed over those of the table.
+type rewriteSwap struct {
+	HeapPages     uint32 `json:"heap_pages"`
+	OverflowPages uint32 `json:"overflow_pages"`
+}
+
+func swapPath(tmpFS storage.LocalFileSet) string {
+	return filepath.Join(tmpFS.Dir, tmpFS.Base+".swap")
+}
+
+// finishSwap renames the rewritten file sets over those of the table, syncs
+// the directory and removes the swap marker. Run again after a crash, it
+// completes the renames.
+func (db *Database) finishSwap(heapFS, ovfFS, tmpFS, tmpOvfFS storage.LocalFileSet, swap rewriteSwap) error {
+	if err := db.SM.ReplaceSegments(tmpFS, heapFS, swap.HeapPages); err != nil {
+		return err
+	}
+	if err := db.SM.ReplaceSegments(tmpOvfFS, ovfFS, swap.OverflowPages); err != nil {
+		return err
+	}
+	if err := os.Remove(swapPath(tmpFS)); err != nil {
+		return err
+	}
+	return storage.SyncDirEntries(tmpFS.Dir)
+}
+
+// rewriteFileSets returns the heap and overflow file sets of table name and
+// those rewriteTable fills in their place.
+func (db *Database) rewriteFileSets(name, suffix string) (heapFS, ovfFS, tmpFS, tmpOvfFS storage.LocalFileSet) {
+	heapFS = storage.LocalFileSet{Dir: db.tableDir(), Base: name}
+	tmpFS = storage.LocalFileSet{Dir: db.tableDir(), Base: name + suffix}
+	tmpOvfFS = storage.LocalFileSet{Dir: db.tableDir(), Base: tmpFS.Base + "_ovf"}
+	return heapFS, db.overflowFileSet(name), tmpFS, tmpOvfFS
+}
+
+// recoverRewrite deals with what an interrupted rewriteTable with suffix
+// left of table name: a swap it marked is finished. Unmarked rewritten files
+// are deleted while the table still has its own; otherwise they hold its
+// only rows and are swapped in.
+func (db *Database) recoverRewrite(name, suffix string) error {
+	heapFS, ovfFS, tmpFS, tmpOvfFS := db.rewriteFileSets(name, suffix)
+	data, err := os.ReadFile(swapPath(tmpFS))
+	if err == nil {
+		var swap rewriteSwap
+		if err := json.Unmarshal(data, &swap); err != nil {
+			return fmt.Errorf("%s: %w", swapPath(tmpFS), err)
+		}
+		return db.finishSwap(heapFS, ovfFS, tmpFS, tmpOvfFS, swap)
+	}
+	if !errors.Is(err, os.ErrNotExist) {
+		return err
+	}
+
+	own, err := db.SM.CountPages(heapFS)
+	if err != nil {
+		return err
+	}
+	left, err := db.SM.CountPages(tmpFS)
+	if err != nil {
+		return err
+	}
+	if own > 0 || left == 0 {
+		return db.removeFileSets(tmpFS, tmpOvfFS)
+	}
+	slog.Warn("vacuum: swapping in the rewritten files of a table that lost its own", "table", heapFS.Base)
+	return db.swapIn(heapFS, ovfFS, tmpFS, tmpOvfFS)
+}
+
+// removeFileSets drops the cached pages of each file set and deletes its
+// segments.
+func (db *Database) removeFileSets(sets ...storage.LocalFileSet) error {
+	for _, fs := range sets {
+		if err := db.flushAndDropFileSet(fs); err != nil {
+			return err
+		}
+		if err := db.SM.RemoveSegments(fs); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// tablePages counts the pages of the files of a table.
+func (db *Database) tablePages(meta *TableMeta) (uint32, error) {
+	var total uint32
+	for _, fs := range append(db.pagedFileSets(meta), db.overflowFileSet(meta.Name)) {
+		n, err := db.SM.CountPages(fs)
+		if err != nil {
+			return 0, err
+		}
+		total += n
+	}
+	return total, nil
+}
@@ -0,0 +1,144 @@
+package novasql
+
+import (
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+func TestVacuum_ShrinksTables(t *testing.T) {
+	dir := t.TempDir()
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	users, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	require.NoError(t, db.CreateIndex("users", "users_name", "name"))
+	big := strings.Repeat("overflow ", 2000)
+	var ids []heap.TID
+	for i := range 1000 {
+		name := "user-padding-padding-padding"
+		if i%100 == 0 {
+			name = big
+		}
+		id, err := users.Insert([]any{int64(i), name})
+		require.NoError(t, err)
+		ids = append(ids, id)
+	}
+	for i, id := range ids {
+		if i%10 != 0 {
+			require.NoError(t, users.Delete(id))
+		}
+	}
+
+	tx, err := db.BeginSnapshot()
+	require.NoError(t, err)
+	_, err = db.Vacuum()
+	require.ErrorIs(t, err, ErrVacuumBusy)
+	require.NoError(t, tx.Abort())
+
+	st, err := db.Vacuum()
+	require.NoError(t, err)
+	require.Equal(t, 1, st.Tables)
+	require.Less(t, st.PagesAfter, st.PagesBefore)
+
+	tbl, err := db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 100, countRows(t, tbl))
+	bad, err := db.VerifyIntegrity()
+	require.NoError(t, err)
+	require.Empty(t, bad)
+
+	tree, err := db.OpenKVTreeIndex("users", "users_name")
+	require.NoError(t, err)
+	entries := 0
+	for it := tree.Scan(nil, nil); it.Next(); {
+		entries++
+	}
+	require.NoError(t, tree.Close())
+	require.Equal(t, 100, entries)
+
+	// The compacted files are what a reopen sees.
+	require.NoError(t, db.Close())
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	tbl, err = db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 100, countRows(t, tbl))
+	require.Equal(t, st.PagesAfter, mustTablePages(t, db, "users"))
+}
+
+// openVacuumFixture makes table users with 10 rows in dir and returns its
+// table directory, closed.
+func openVacuumFixture(t *testing.T, dir string) string {
+	t.Helper()
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	users, err := db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	for i := range 10 {
+		_, err := users.Insert([]any{int64(i), "user"})
+		require.NoError(t, err)
+	}
+	tables := db.tableDir()
+	require.NoError(t, db.Close())
+	return tables
+}
+
+func TestVacuum_SwapsInRewriteOfTableWithoutFiles(t *testing.T) {
+	dir := t.TempDir()
+	tables := openVacuumFixture(t, dir)
+	// A crash between deleting the old files and renaming the new ones.
+	require.NoError(t, os.Rename(filepath.Join(tables, "users"), filepath.Join(tables, "users"+vacuumSuffix)))
+
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	_, err = db.Vacuum()
+	require.NoError(t, err)
+	tbl, err := db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 10, countRows(t, tbl))
+	require.NoFileExists(t, filepath.Join(tables, "users"+vacuumSuffix))
+}
+
+func TestVacuum_FinishesMarkedSwap(t *testing.T) {
+	dir := t.TempDir()
+	tables := openVacuumFixture(t, dir)
+	// A crash after marking the swap, before the renames.
+	data, err := os.ReadFile(filepath.Join(tables, "users"))
+	require.NoError(t, err)
+	require.NoError(t, os.WriteFile(filepath.Join(tables, "users"+vacuumSuffix), data, 0o644))
+	marker, err := json.Marshal(rewriteSwap{HeapPages: uint32(len(data) / storage.PageSize)})
+	require.NoError(t, err)
+	swap := filepath.Join(tables, "users"+vacuumSuffix+".swap")
+	require.NoError(t, os.WriteFile(swap, marker, 0o644))
+
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	_, err = db.Vacuum()
+	require.NoError(t, err)
+	tbl, err := db.OpenTable("users")
+	require.NoError(t, err)
+	require.Equal(t, 10, countRows(t, tbl))
+	require.NoFileExists(t, swap)
+	require.NoFileExists(t, filepath.Join(tables, "users"+vacuumSuffix))
+}
+
+func mustTablePages(t *testing.T, db *Database, name string) uint32 {
+	t.Helper()
+	meta, err := db.readTableMeta(name)
+	require.NoError(t, err)
+	n, err := db.tablePages(meta)
+	require.NoError(t, err)
+	return n
+}
@@ -0,0 +1,168 @@
+package novasql
+
+import (
+	"cmp"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"log/slog"
+	"os"
+	"path/filepath"
+	"slices"
+	"strings"
+	"time"
+)
+
+var ErrViewNotFound = errors.New("novasql: view not found")
+
+// viewMetaSuffix names the file of a view next to the table metas.
+const viewMetaSuffix = ".view.json"
+
+// ViewMeta is a view: a named SELECT that queries may use like a table. It
+// holds no rows; the planner expands it into its query every time.
+type ViewMeta struct {
+	Name      string    `json:"name"`
+	Query     string    `json:"query"` // the SELECT, without the ';'
+	CreatedAt time.Time `json:"created_at"`
+}
+
+// CreateView stores view name with its query. The caller checks the query,
+// which the database does not parse; the name must not be used by a table
+// or another view.
+//
+// A view does not keep the tables it reads from: once one of them is
+// dropped or altered, queries over the view fail or change with it.
+func (db *Database) CreateView(name, query string) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	if err := validateIdent(name); err != nil {
+		return err
+	}
+	if db.relationExists(name) {
+		return fmt.Errorf("%w: %s", ErrTableExists, name)
+	}
+	if err := os.MkdirAll(db.tableDir(), 0o755); err != nil {
+		return err
+	}
+	view := &ViewMeta{Name: name, Query: strings.TrimSpace(query), CreatedAt: db.now()}
+	data, err := json.MarshalIndent(view, "", "  ")
+	if err != nil {
+		return err
+	}
+	if err := writeFileAtomic(db.viewMetaPath(name), data, 0o644); err != nil {
+		return err
+	}
+
+	db.cat.mu.Lock()
+	defer db.cat.mu.Unlock()
+	if db.cat.views == nil {
+		db.cat.views = make(map[string]*ViewMeta)
+	}
+	db.cat.views[name] = view
+	db.cat.version++
+	return nil
+}
+
+// DropView removes view name.
+func (db *Database) DropView(name string) error {
+	if err := db.ensureWritable(); err != nil {
+		return err
+	}
+	if _, err := db.View(name); err != nil {
+		return err
+	}
+	if err := os.Remove(db.viewMetaPath(name)); err != nil {
+		return err
+	}
+
+	db.cat.mu.Lock()
+	defer db.cat.mu.Unlock()
+	delete(db.cat.views, name)
+	db.cat.version++
+	return nil
+}
+
+// View returns view name, ErrViewNotFound when there is none.
+func (db *Database) View(name string) (*ViewMeta, error) {
+	if err := db.ensureOpen(); err != nil {
+		return nil, err
+	}
+	db.cat.mu.RLock()
+	defer db.cat.mu.RUnlock()
+	v, ok := db.cat.views[name]
+	if !ok {
+		return nil, fmt.Errorf("%w: %s", ErrViewNotFound, name)
+	}
+	cp := *v
+	return &cp, nil
+}
+
+// ListViews returns the views of the current database in the order they
+// were created, so that each comes after the views it reads from.
+func (db *Database) ListViews() ([]*ViewMeta, error) {
+	if err := db.ensureOpen(); err != nil {
+		return nil, err
+	}
+	db.cat.mu.RLock()
+	defer db.cat.mu.RUnlock()
+	out := make([]*ViewMeta, 0, len(db.cat.views))
+	for _, v := range db.cat.views {
+		cp := *v
+		out = append(out, &cp)
+	}
+	slices.SortFunc(out, func(a, b *ViewMeta) int {
+		return cmp.Or(a.CreatedAt.Compare(b.CreatedAt), strings.Compare(a.Name, b.Name))
+	})
+	return out, nil
+}
+
+func (db *Database) isView(name string) bool {
+	db.cat.mu.RLock()
+	defer db.cat.mu.RUnlock()
+	_, ok := db.cat.views[name]
+	return ok
+}
+
+// relationExists reports whether a table, temporary table or view is called
+// name.
+func (db *Database) relationExists(name string) bool {
+	if db.isTempTable(name) || db.isView(name) {
+		return true
+	}
+	_, err := db.readTableMeta(name)
+	return err == nil
+}
+
+func (db *Database) viewMetaPath(name string) string {
+	return filepath.Join(db.tableDir(), name+viewMetaSuffix)
+}
+
+// loadViews reads the view files of the current database. Unreadable ones
+// are skipped.
+func (db *Database) loadViews() (map[string]*ViewMeta, error) {
+	entries, err := os.ReadDir(db.tableDir())
+	if errors.Is(err, os.ErrNotExist) {
+		return nil, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+	views := make(map[string]*ViewMeta)
+	for _, e := range entries {
+		if e.IsDir() || !strings.HasSuffix(e.Name(), viewMetaSuffix) {
+			continue
+		}
+		var v ViewMeta
+		data, err := os.ReadFile(filepath.Join(db.tableDir(), e.Name()))
+		if err == nil {
+			err = json.Unmarshal(data, &v)
+		}
+		if err != nil {
+			slog.Warn("catalog: failed to read view", "file", e.Name(), "err", err)
+			continue
+		}
+		views[v.Name] = &v
+	}
+	return views, nil
+}
@@ -0,0 +1,51 @@
+package novasql
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestDatabase_Views(t *testing.T) {
+	dir := t.TempDir()
+	db, err := Options().Open(dir)
+	require.NoError(t, err)
+
+	_, err = db.CreateTable("users", testSchema())
+	require.NoError(t, err)
+	require.ErrorIs(t, db.CreateView("users", "SELECT * FROM users"), ErrTableExists)
+
+	v := db.SchemaVersion()
+	require.NoError(t, db.CreateView("ids", "SELECT id FROM users"))
+	require.NotEqual(t, v, db.SchemaVersion())
+	require.NoError(t, db.CreateView("names", "SELECT name FROM users "))
+	require.ErrorIs(t, db.CreateView("names", "SELECT id FROM users"), ErrTableExists)
+	_, err = db.CreateTable("names", testSchema())
+	require.ErrorIs(t, err, ErrTableExists)
+	require.ErrorIs(t, db.RenameTable("users", "ids"), ErrTableExists)
+
+	view, err := db.View("names")
+	require.NoError(t, err)
+	require.Equal(t, "SELECT name FROM users", view.Query)
+	metas, err := db.ListTables()
+	require.NoError(t, err)
+	require.Len(t, metas, 1)
+	require.NoError(t, db.Close())
+
+	db, err = Options().Open(dir)
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+	views, err := db.ListViews()
+	require.NoError(t, err)
+	require.Len(t, views, 2)
+	require.Equal(t, "ids", views[0].Name)
+	require.Equal(t, "names", views[1].Name)
+
+	require.NoError(t, db.DropView("names"))
+	_, err = db.View("names")
+	require.ErrorIs(t, err, ErrViewNotFound)
+	require.Equal(t, CodeNotFound, CodeOf(db.DropView("names")))
+	views, err = db.ListViews()
+	require.NoError(t, err)
+	require.Len(t, views, 1)
+}
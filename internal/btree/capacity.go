@@ -3,27 +3,27 @@ package btree
 import "github.com/tuannm99/novasql/internal/storage"
 
 // maxEntriesPerPage returns the max number of fixed-size entries that can fit into
-// a slotted page.
+// a slotted page of sm.
 //
 // Assumptions:
-//   - Page layout is: [Header][SlotArray grows up][TupleData grows down]
+//   - Page layout is: [Header][SlotArray grows up][TupleData grows down][Trailer]
 //   - Each tuple consumes exactly 1 slot entry of size storage.SlotSize
 //   - Each tuple payload is fixed length: entrySize
-func maxEntriesPerPage(entrySize int) int {
+func maxEntriesPerPage(sm *storage.StorageManager, entrySize int) int {
 	if entrySize <= 0 {
 		return 0
 	}
-	free := storage.PageSize - storage.HeaderSize
+	free := nodeSpace(sm) // same slotted page, minus header and trailer
 	if free <= 0 {
 		return 0
 	}
 	return free / (storage.SlotSize + entrySize)
 }
 
-func maxLeafEntriesPerPage() int {
-	return maxEntriesPerPage(LeafEntrySize)
+func maxLeafEntriesPerPage(sm *storage.StorageManager) int {
+	return maxEntriesPerPage(sm, LeafEntrySize)
 }
 
-func maxInternalEntriesPerPage() int {
-	return maxEntriesPerPage(InternalEntrySize)
+func maxInternalEntriesPerPage(sm *storage.StorageManager) int {
+	return maxEntriesPerPage(sm, InternalEntrySize)
 }
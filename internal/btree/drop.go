@@ -3,29 +3,40 @@ package btree
 import (
 	"errors"
 	"os"
-	"path/filepath"
 
 	"github.com/tuannm99/novasql/internal/storage"
 )
 
-// DropIndex removes all index segments and its meta file.
+// DropIndex removes all index segments (through sm) and its meta file.
 // Works for LocalFileSet only.
-func DropIndex(lfs storage.LocalFileSet) error {
+func DropIndex(sm *storage.StorageManager, lfs storage.LocalFileSet) error {
 	// Ensure directory exists; Drop should be idempotent.
 	if err := os.MkdirAll(lfs.Dir, 0o755); err != nil {
 		return err
 	}
 
 	// Remove page segments: Base, Base.1, ...
-	if err := storage.RemoveAllSegments(lfs); err != nil {
+	if err := sm.RemoveSegments(lfs); err != nil {
 		return err
 	}
 
 	// Remove meta file: <Base>.btree.meta.json (if you use meta persistence)
-	metaPath := filepath.Join(lfs.Dir, lfs.Base+metaFileSuffix)
-	if err := os.Remove(metaPath); err != nil && !errors.Is(err, os.ErrNotExist) {
+	if err := os.Remove(MetaPath(lfs)); err != nil && !errors.Is(err, os.ErrNotExist) {
 		return err
 	}
 
 	return nil
 }
+
+// RenameIndex moves all index segments (through sm) and the meta file from
+// oldLFS to newLFS. The directories may differ. Works for LocalFileSet only.
+func RenameIndex(sm *storage.StorageManager, oldLFS, newLFS storage.LocalFileSet) error {
+	if err := sm.RenameSegments(oldLFS, newLFS); err != nil {
+		return err
+	}
+
+	if err := os.Rename(MetaPath(oldLFS), MetaPath(newLFS)); err != nil && !errors.Is(err, os.ErrNotExist) {
+		return err
+	}
+	return nil
+}
@@ -0,0 +1,122 @@
+package btree
+
+import (
+	"errors"
+	"fmt"
+	"math"
+
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/pkg/bx"
+)
+
+// Column index keys
+//
+// A KVTree index over a table column stores one entry per row: the key is
+// the column value followed by the row's TID, the value is empty. Values are
+// encoded so that bytes.Compare orders them like the values themselves,
+// which turns a range predicate on the column into a key range and keeps
+// duplicate values apart by their TID suffix.
+//
+//   - INT64:  big-endian with the sign bit flipped
+//   - FLOAT:  big-endian bits, sign bit flipped for positives, all bits
+//     flipped for negatives
+//   - TEXT:   bytes with 0x00 escaped as 0x00 0xFF, terminated by 0x00 0x01
+//   - BOOL:   one byte, 0 or 1
+//   - DATE, TIME, TIMESTAMP: as the INT64 they are stored as
+//   - DECIMAL: record.Decimal.OrderKey, the same for any scale
+//
+// NULL has no encoding: NULL values are not indexed.
+
+var ErrIndexKeyType = errors.New("btree: unsupported index key type")
+
+const tidKeySize = 4 + 2
+
+// EncodeIndexValue returns the order-preserving encoding of a column value.
+func EncodeIndexValue(v any) ([]byte, error) {
+	switch x := v.(type) {
+	case record.Date:
+		v = int64(x)
+	case record.Time:
+		v = int64(x)
+	case record.Timestamp:
+		v = int64(x)
+	case record.Decimal:
+		return x.OrderKey(), nil
+	}
+	switch x := v.(type) {
+	case int64:
+		out := make([]byte, 8)
+		bx.PutU64BE(out, uint64(x)^(1<<63))
+		return out, nil
+	case float64:
+		bits := math.Float64bits(x)
+		if bits&(1<<63) != 0 {
+			bits = ^bits
+		} else {
+			bits |= 1 << 63
+		}
+		out := make([]byte, 8)
+		bx.PutU64BE(out, bits)
+		return out, nil
+	case string:
+		out := make([]byte, 0, len(x)+2)
+		for i := 0; i < len(x); i++ {
+			out = append(out, x[i])
+			if x[i] == 0 {
+				out = append(out, 0xFF)
+			}
+		}
+		return append(out, 0x00, 0x01), nil
+	case bool:
+		if x {
+			return []byte{1}, nil
+		}
+		return []byte{0}, nil
+	default:
+		return nil, fmt.Errorf("%w: %T", ErrIndexKeyType, v)
+	}
+}
+
+// IndexEntryKey is the key of the index entry for value v of the row at tid.
+func IndexEntryKey(v any, tid heap.TID) ([]byte, error) {
+	key, err := EncodeIndexValue(v)
+	if err != nil {
+		return nil, err
+	}
+	var t [tidKeySize]byte
+	bx.PutU32BE(t[0:4], tid.PageID)
+	bx.PutU16BE(t[4:6], tid.Slot)
+	return append(key, t[:]...), nil
+}
+
+// IndexEntryTID returns the TID an index entry key points to.
+func IndexEntryTID(key []byte) (heap.TID, error) {
+	if len(key) < tidKeySize {
+		return heap.TID{}, fmt.Errorf("btree: index key too short (%d bytes)", len(key))
+	}
+	t := key[len(key)-tidKeySize:]
+	return heap.TID{PageID: bx.U32BE(t[0:4]), Slot: bx.U16BE(t[4:6])}, nil
+}
+
+// IndexEntryValue returns the encoded column value of an index entry key:
+// the entries of rows with equal values share it.
+func IndexEntryValue(key []byte) ([]byte, error) {
+	if len(key) < tidKeySize {
+		return nil, fmt.Errorf("btree: index key too short (%d bytes)", len(key))
+	}
+	return key[:len(key)-tidKeySize], nil
+}
+
+// PrefixEnd returns the smallest key greater than every key starting with
+// prefix, or nil when there is none (the prefix is all 0xFF).
+func PrefixEnd(prefix []byte) []byte {
+	end := append([]byte(nil), prefix...)
+	for i := len(end) - 1; i >= 0; i-- {
+		if end[i] < 0xFF {
+			end[i]++
+			return end[:i+1]
+		}
+	}
+	return nil
+}
@@ -0,0 +1,54 @@
+package btree
+
+import (
+	"bytes"
+	"math"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/heap"
+)
+
+func TestEncodeIndexValue_PreservesOrder(t *testing.T) {
+	ordered := [][]any{
+		{int64(math.MinInt64), int64(-5), int64(0), int64(7), int64(math.MaxInt64)},
+		{math.Inf(-1), -2.5, 0.0, 0.5, 3.0, math.Inf(1)},
+		{"", "\x00", "a", "a\x00b", "ab", "b"},
+		{false, true},
+	}
+	for _, vals := range ordered {
+		for i := 1; i < len(vals); i++ {
+			prev, err := EncodeIndexValue(vals[i-1])
+			require.NoError(t, err)
+			cur, err := EncodeIndexValue(vals[i])
+			require.NoError(t, err)
+			require.Negative(t, bytes.Compare(prev, cur), "%v < %v", vals[i-1], vals[i])
+		}
+	}
+
+	_, err := EncodeIndexValue(nil)
+	require.ErrorIs(t, err, ErrIndexKeyType)
+}
+
+func TestIndexEntryKey_TIDSuffix(t *testing.T) {
+	tid := heap.TID{PageID: 3, Slot: 9}
+	key, err := IndexEntryKey("a", tid)
+	require.NoError(t, err)
+	got, err := IndexEntryTID(key)
+	require.NoError(t, err)
+	require.Equal(t, tid, got)
+
+	// Every entry for "a" sorts inside [enc("a"), PrefixEnd(enc("a"))), and
+	// entries for "ab" do not.
+	prefix, err := EncodeIndexValue("a")
+	require.NoError(t, err)
+	other, err := IndexEntryKey("ab", tid)
+	require.NoError(t, err)
+	require.True(t, bytes.HasPrefix(key, prefix))
+	require.Negative(t, bytes.Compare(key, PrefixEnd(prefix)))
+	require.False(t, bytes.HasPrefix(other, prefix))
+
+	require.Nil(t, PrefixEnd([]byte{0xFF, 0xFF}))
+	require.Equal(t, []byte{0x01, 0x03}, PrefixEnd([]byte{0x01, 0x02, 0xFF}))
+}
@@ -0,0 +1,83 @@
+package btree
+
+import (
+	"fmt"
+
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+// KVTreeInfo describes a KVTree as its meta file and pages have it, for
+// inspection and debugging.
+type KVTreeInfo struct {
+	Root       uint32
+	Height     int
+	NextPageID uint32
+	FreePages  []uint32
+	Nodes      []KVNodeInfo // breadth first from the root
+}
+
+// KVNodeInfo is one node of a KVTree. Level counts up from the leaves, at 0.
+type KVNodeInfo struct {
+	PageID   uint32
+	Level    int
+	Keys     [][]byte
+	Children []uint32 // the child each key routes to; nil for a leaf
+}
+
+// InspectKVTree reads the meta file of the KVTree stored in fs and walks its
+// nodes from the root, reading pages with read (e.g. a StorageManager's
+// ReadPage). A node that does not decode, or is reached twice, stops the
+// walk: the error comes back with the nodes described so far.
+func InspectKVTree(fs storage.LocalFileSet, read func(pageID uint32, dst []byte) error) (*KVTreeInfo, error) {
+	m, ok, err := (&Tree{metaEnabled: true, metaPath: MetaPath(fs)}).loadMeta()
+	if err != nil {
+		return nil, err
+	}
+	info := &KVTreeInfo{Height: 1, NextPageID: 1}
+	if ok {
+		info.Root, info.Height, info.NextPageID = m.Root, max(m.Height, 1), m.NextPageID
+		info.FreePages = m.FreePages
+	}
+
+	seen := make(map[uint32]bool)
+	level := []uint32{info.Root}
+	buf := make([]byte, storage.PageSize)
+	for depth := info.Height - 1; depth >= 0 && len(level) > 0; depth-- {
+		var next []uint32
+		for _, pageID := range level {
+			if seen[pageID] {
+				return info, fmt.Errorf("%w: page %d is reached twice", storage.ErrCorruption, pageID)
+			}
+			seen[pageID] = true
+			if err := read(pageID, buf); err != nil {
+				return info, err
+			}
+			p := &storage.Page{Buf: buf}
+			if p.IsUninitialized() {
+				return info, fmt.Errorf("%w: page %d is not formatted", storage.ErrCorruption, pageID)
+			}
+			if err := p.Validate(); err != nil {
+				return info, err
+			}
+			node := KVNodeInfo{PageID: pageID, Level: depth}
+			for i := range p.NumSlots() {
+				raw, err := p.ReadTuple(i)
+				if err != nil {
+					return info, fmt.Errorf("page %d slot %d: %w", pageID, i, err)
+				}
+				e, err := decodeKVEntry(raw, depth == 0)
+				if err != nil {
+					return info, fmt.Errorf("page %d slot %d: %w", pageID, i, err)
+				}
+				node.Keys = append(node.Keys, e.key)
+				if depth > 0 {
+					node.Children = append(node.Children, e.child)
+				}
+			}
+			info.Nodes = append(info.Nodes, node)
+			next = append(next, node.Children...)
+		}
+		level = next
+	}
+	return info, nil
+}
@@ -0,0 +1,575 @@
+package btree
+
+import (
+	"bytes"
+	"errors"
+	"log/slog"
+	"sort"
+	"sync"
+
+	"github.com/tuannm99/novasql/internal/bufferpool"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/pkg/bx"
+)
+
+var (
+	ErrKeyTooLarge = errors.New("btree: key/value too large for a node")
+	ErrEmptyKey    = errors.New("btree: empty key")
+)
+
+// kvNodeSpace is the tuple area of one node page (page minus header and
+// the version/LSN trailer).
+var kvNodeSpace = func() int {
+	p, _ := storage.NewPage(make([]byte, storage.PageSize), 0)
+	return p.FreeSpace()
+}()
+
+// nodeSpace is the tuple area of the node pages of sm: kvNodeSpace, less the
+// encryption reserve when sm encrypts its pages.
+func nodeSpace(sm *storage.StorageManager) int {
+	if sm != nil && sm.Encrypted() {
+		return kvNodeSpace - storage.EncryptionReserve
+	}
+	return kvNodeSpace
+}
+
+// MaxKVEntrySize bounds len(key)+len(value). A quarter of a node guarantees
+// that any overfull node splits into two halves that both fit. The bound
+// holds for encrypted nodes too, so that the same keys fit on either storage.
+var MaxKVEntrySize = (kvNodeSpace-storage.EncryptionReserve)/4 - storage.SlotSize - 2
+
+// KVTree is a B+Tree over byte-string keys with byte-string values, ordered by
+// bytes.Compare. Unlike Tree it accepts keys in any order, replaces the value
+// on duplicate keys, and supports Delete with merge/redistribute.
+//
+// Node layout: one node per page, one tuple per entry, sorted by key.
+//   - leaf entry:     [keyLen u16][key][value]
+//   - internal entry: [keyLen u16][key][child u32]; entry i routes keys
+//     >= key_i (entry 0 routes everything below key_1).
+//
+// Pages released by merges and root collapses go to a free list that is
+// persisted in the meta file and reused before the file grows.
+type KVTree struct {
+	SM *storage.StorageManager
+	FS storage.FileSet
+	BP bufferpool.Manager
+
+	mu         sync.RWMutex
+	root       uint32
+	height     int
+	space      int // tuple area of a node; see nodeSpace
+	alloc      *storage.PageAllocator
+	metaPath   string
+	closed     bool
+}
+
+type kvEntry struct {
+	key   []byte
+	val   []byte // leaf only
+	child uint32 // internal only
+}
+
+// OpenKVTree opens the tree stored in fs, creating an empty one if fs has no
+// pages yet. Root and height persist in the same meta file as Tree.
+func OpenKVTree(sm *storage.StorageManager, fs storage.FileSet, bp bufferpool.Manager) (*KVTree, error) {
+	t := &KVTree{SM: sm, FS: fs, BP: bp, height: 1, space: nodeSpace(sm), alloc: storage.NewPageAllocator(1, nil)}
+	t.metaPath, _ = metaPathForFileSet(fs)
+
+	if t.metaPath != "" {
+		tmp := &Tree{metaEnabled: true, metaPath: t.metaPath}
+		m, ok, err := tmp.loadMeta()
+		if err != nil {
+			return nil, err
+		}
+		if ok {
+			t.root, t.height = m.Root, max(m.Height, 1)
+			t.alloc = storage.NewPageAllocator(max(m.NextPageID, 1), m.FreePages)
+		}
+	}
+	pageCount, err := sm.CountPages(fs)
+	if err != nil {
+		return nil, err
+	}
+	t.alloc.Grow(pageCount)
+
+	if pageCount == 0 {
+		if err := t.writeNode(0, true, nil); err != nil {
+			return nil, err
+		}
+	}
+	return t, t.saveMeta()
+}
+
+func (t *KVTree) saveMeta() error {
+	if t.metaPath == "" {
+		return nil
+	}
+	return writeDiskMeta(t.metaPath, diskMeta{
+		Root:       t.root,
+		Height:     t.height,
+		NextPageID: t.alloc.NextPageID(),
+		FreePages:  t.alloc.FreePages(),
+	})
+}
+
+// Close flushes the tree's pages. Further calls return ErrTreeClosed.
+func (t *KVTree) Close() error {
+	t.mu.Lock()
+	defer t.mu.Unlock()
+	if t.closed {
+		return nil
+	}
+	t.closed = true
+	return t.BP.FlushAll()
+}
+
+// Height returns the number of levels (1 = the root is a leaf).
+func (t *KVTree) Height() int {
+	t.mu.RLock()
+	defer t.mu.RUnlock()
+	return t.height
+}
+
+// ---- node I/O ----
+
+func encodeKVEntry(e kvEntry, leaf bool) []byte {
+	n := 2 + len(e.key)
+	if leaf {
+		n += len(e.val)
+	} else {
+		n += 4
+	}
+	buf := make([]byte, n)
+	bx.PutU16(buf, uint16(len(e.key)))
+	copy(buf[2:], e.key)
+	if leaf {
+		copy(buf[2+len(e.key):], e.val)
+	} else {
+		bx.PutU32(buf[2+len(e.key):], e.child)
+	}
+	return buf
+}
+
+func decodeKVEntry(b []byte, leaf bool) (kvEntry, error) {
+	if len(b) < 2 {
+		return kvEntry{}, storage.ErrCorruption
+	}
+	kl := int(bx.U16(b))
+	if 2+kl > len(b) || (!leaf && 2+kl+4 != len(b)) {
+		return kvEntry{}, storage.ErrCorruption
+	}
+	e := kvEntry{key: append([]byte(nil), b[2:2+kl]...)}
+	if leaf {
+		e.val = append([]byte(nil), b[2+kl:]...)
+	} else {
+		e.child = bx.U32(b[2+kl:])
+	}
+	return e, nil
+}
+
+func entrySize(e kvEntry, leaf bool) int {
+	n := storage.SlotSize + 2 + len(e.key)
+	if leaf {
+		return n + len(e.val)
+	}
+	return n + 4
+}
+
+func nodeSize(entries []kvEntry, leaf bool) int {
+	n := 0
+	for _, e := range entries {
+		n += entrySize(e, leaf)
+	}
+	return n
+}
+
+func (t *KVTree) readNode(pageID uint32, leaf bool) ([]kvEntry, error) {
+	p, err := t.BP.GetPage(pageID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = t.BP.Unpin(p, false) }()
+
+	out := make([]kvEntry, 0, p.NumSlots())
+	for i := range p.NumSlots() {
+		raw, err := p.ReadTuple(i)
+		if err != nil {
+			return nil, err
+		}
+		e, err := decodeKVEntry(raw, leaf)
+		if err != nil {
+			return nil, err
+		}
+		out = append(out, e)
+	}
+	return out, nil
+}
+
+func (t *KVTree) writeNode(pageID uint32, leaf bool, entries []kvEntry) error {
+	p, err := t.BP.GetPage(pageID)
+	if err != nil {
+		return err
+	}
+	p.Reset(pageID)
+	for _, e := range entries {
+		if _, err := p.InsertTuple(encodeKVEntry(e, leaf)); err != nil {
+			_ = t.BP.Unpin(p, false)
+			return err
+		}
+	}
+	return t.BP.Unpin(p, true)
+}
+
+func (t *KVTree) allocNode(leaf bool, entries []kvEntry) (uint32, error) {
+	pid := t.alloc.AllocatePage()
+	return pid, t.writeNode(pid, leaf, entries)
+}
+
+// freeNode returns a page no longer referenced by the tree to the free list.
+func (t *KVTree) freeNode(pageID uint32) error {
+	slog.Debug("btree.KVTree.free", "page", pageID)
+	return t.alloc.FreePage(pageID)
+}
+
+// search returns the index of the first entry with key >= k and whether it
+// is an exact match.
+func search(entries []kvEntry, k []byte) (int, bool) {
+	i := sort.Search(len(entries), func(i int) bool { return bytes.Compare(entries[i].key, k) >= 0 })
+	return i, i < len(entries) && bytes.Equal(entries[i].key, k)
+}
+
+// childIndex picks the internal entry that routes k.
+func childIndex(entries []kvEntry, k []byte) int {
+	i := sort.Search(len(entries), func(i int) bool { return bytes.Compare(entries[i].key, k) > 0 })
+	return max(i-1, 0)
+}
+
+// splitEntries cuts an overfull node near the middle by bytes.
+func splitEntries(entries []kvEntry, leaf bool) ([]kvEntry, []kvEntry) {
+	half := nodeSize(entries, leaf) / 2
+	acc := 0
+	for i, e := range entries {
+		acc += entrySize(e, leaf)
+		if acc >= half {
+			cut := min(max(i+1, 1), len(entries)-1)
+			return entries[:cut:cut], entries[cut:]
+		}
+	}
+	return entries, nil
+}
+
+// ---- public API ----
+
+// Get returns the value stored under key.
+func (t *KVTree) Get(key []byte) ([]byte, bool, error) {
+	t.mu.RLock()
+	defer t.mu.RUnlock()
+	if t.closed {
+		return nil, false, ErrTreeClosed
+	}
+
+	pageID := t.root
+	for level := t.height; level > 1; level-- {
+		entries, err := t.readNode(pageID, false)
+		if err != nil {
+			return nil, false, err
+		}
+		if len(entries) == 0 {
+			return nil, false, ErrInternalNodeHasNoEntries
+		}
+		pageID = entries[childIndex(entries, key)].child
+	}
+	entries, err := t.readNode(pageID, true)
+	if err != nil {
+		return nil, false, err
+	}
+	if i, ok := search(entries, key); ok {
+		return entries[i].val, true, nil
+	}
+	return nil, false, nil
+}
+
+// Insert stores value under key, replacing any previous value.
+func (t *KVTree) Insert(key, value []byte) error {
+	if len(key) == 0 {
+		return ErrEmptyKey
+	}
+	if len(key)+len(value) > MaxKVEntrySize {
+		return ErrKeyTooLarge
+	}
+
+	t.mu.Lock()
+	defer t.mu.Unlock()
+	if t.closed {
+		return ErrTreeClosed
+	}
+
+	next, free := t.alloc.NextPageID(), t.alloc.FreeCount()
+	sepKey, rightID, split, err := t.insertAt(t.root, t.height, kvEntry{key: key, val: value})
+	if err != nil {
+		return err
+	}
+	if split {
+		// Root split: grow one level. Entry 0 routes everything below sepKey.
+		left := t.root
+		rootID, err := t.allocNode(false, []kvEntry{{key: nil, child: left}, {key: sepKey, child: rightID}})
+		if err != nil {
+			return err
+		}
+		t.root = rootID
+		t.height++
+		slog.Debug("btree.KVTree.root_split", "root", rootID, "height", t.height)
+	}
+	if t.alloc.NextPageID() == next && t.alloc.FreeCount() == free {
+		return nil
+	}
+	return t.saveMeta()
+}
+
+// insertAt inserts e below pageID. If the node splits, its new right sibling
+// and the sibling's separator key are returned.
+func (t *KVTree) insertAt(pageID uint32, level int, e kvEntry) ([]byte, uint32, bool, error) {
+	leaf := level == 1
+	entries, err := t.readNode(pageID, leaf)
+	if err != nil {
+		return nil, 0, false, err
+	}
+
+	if leaf {
+		i, found := search(entries, e.key)
+		if found {
+			entries[i].val = e.val
+		} else {
+			entries = append(entries[:i], append([]kvEntry{e}, entries[i:]...)...)
+		}
+	} else {
+		if len(entries) == 0 {
+			return nil, 0, false, ErrInternalNodeHasNoEntries
+		}
+		ci := childIndex(entries, e.key)
+		sep, right, split, err := t.insertAt(entries[ci].child, level-1, e)
+		if err != nil || !split {
+			return nil, 0, false, err
+		}
+		entries = append(entries[:ci+1], append([]kvEntry{{key: sep, child: right}}, entries[ci+1:]...)...)
+	}
+
+	if nodeSize(entries, leaf) <= t.space {
+		return nil, 0, false, t.writeNode(pageID, leaf, entries)
+	}
+
+	left, right := splitEntries(entries, leaf)
+	rightID, err := t.allocNode(leaf, right)
+	if err != nil {
+		return nil, 0, false, err
+	}
+	if err := t.writeNode(pageID, leaf, left); err != nil {
+		return nil, 0, false, err
+	}
+	return right[0].key, rightID, true, nil
+}
+
+// Delete removes key and reports whether it was present. Nodes that fall
+// below a quarter full are merged with, or refilled from, a sibling.
+func (t *KVTree) Delete(key []byte) (bool, error) {
+	t.mu.Lock()
+	defer t.mu.Unlock()
+	if t.closed {
+		return false, ErrTreeClosed
+	}
+
+	free := t.alloc.FreeCount()
+	found, _, err := t.deleteAt(t.root, t.height, key)
+	if err != nil || !found {
+		return found, err
+	}
+
+	// Collapse internal roots that are left with a single child.
+	for t.height > 1 {
+		entries, err := t.readNode(t.root, false)
+		if err != nil {
+			return true, err
+		}
+		if len(entries) != 1 {
+			break
+		}
+		old := t.root
+		t.root = entries[0].child
+		t.height--
+		if err := t.freeNode(old); err != nil {
+			return true, err
+		}
+	}
+	if t.alloc.FreeCount() == free {
+		return true, nil
+	}
+	return true, t.saveMeta()
+}
+
+func (t *KVTree) underflows(entries []kvEntry, leaf bool) bool {
+	return nodeSize(entries, leaf) < t.space/4
+}
+
+// deleteAt deletes key below pageID and reports whether the node underflows.
+func (t *KVTree) deleteAt(pageID uint32, level int, key []byte) (found, under bool, err error) {
+	leaf := level == 1
+	entries, err := t.readNode(pageID, leaf)
+	if err != nil {
+		return false, false, err
+	}
+
+	if leaf {
+		i, ok := search(entries, key)
+		if !ok {
+			return false, false, nil
+		}
+		entries = append(entries[:i], entries[i+1:]...)
+		return true, t.underflows(entries, true), t.writeNode(pageID, true, entries)
+	}
+
+	if len(entries) == 0 {
+		return false, false, ErrInternalNodeHasNoEntries
+	}
+	ci := childIndex(entries, key)
+	found, childUnder, err := t.deleteAt(entries[ci].child, level-1, key)
+	if err != nil || !found {
+		return found, false, err
+	}
+	if childUnder && len(entries) > 1 {
+		if entries, err = t.rebalance(entries, ci, level-1); err != nil {
+			return true, false, err
+		}
+		if err := t.writeNode(pageID, false, entries); err != nil {
+			return true, false, err
+		}
+	}
+	return true, t.underflows(entries, false), nil
+}
+
+// rebalance fixes the underfull child ci of a parent with entries, by merging
+// it with a neighbour or redistributing their entries, and returns the
+// parent's new entries.
+func (t *KVTree) rebalance(parent []kvEntry, ci, childLevel int) ([]kvEntry, error) {
+	leaf := childLevel == 1
+	li := ci
+	if ci == len(parent)-1 {
+		li = ci - 1
+	}
+	ri := li + 1
+
+	left, err := t.readNode(parent[li].child, leaf)
+	if err != nil {
+		return nil, err
+	}
+	right, err := t.readNode(parent[ri].child, leaf)
+	if err != nil {
+		return nil, err
+	}
+	if !leaf && len(right) > 0 {
+		// The right node's first key is only a placeholder below its
+		// separator; make it real before the entries move.
+		right[0].key = parent[ri].key
+	}
+
+	combined := append(append(make([]kvEntry, 0, len(left)+len(right)), left...), right...)
+	if nodeSize(combined, leaf) <= t.space {
+		if err := t.writeNode(parent[li].child, leaf, combined); err != nil {
+			return nil, err
+		}
+		slog.Debug("btree.KVTree.merge", "left", parent[li].child, "right", parent[ri].child)
+		if err := t.freeNode(parent[ri].child); err != nil {
+			return nil, err
+		}
+		return append(parent[:ri], parent[ri+1:]...), nil
+	}
+
+	newLeft, newRight := splitEntries(combined, leaf)
+	if err := t.writeNode(parent[li].child, leaf, newLeft); err != nil {
+		return nil, err
+	}
+	if err := t.writeNode(parent[ri].child, leaf, newRight); err != nil {
+		return nil, err
+	}
+	parent[ri].key = newRight[0].key
+	return parent, nil
+}
+
+// ---- range scan ----
+
+// KVIterator walks keys in ascending order. It re-descends from the root at
+// each leaf boundary, so it holds no pins between calls.
+type KVIterator struct {
+	t    *KVTree
+	end  []byte // exclusive; nil = unbounded
+	seek []byte // next key to look up; nil once exhausted
+	buf  []kvEntry
+	cur  kvEntry
+	err  error
+}
+
+// Scan returns an iterator over start <= key < end. A nil start begins at the
+// smallest key, a nil end runs to the last one.
+func (t *KVTree) Scan(start, end []byte) *KVIterator {
+	if start == nil {
+		start = []byte{}
+	}
+	return &KVIterator{t: t, end: end, seek: start}
+}
+
+// Next advances to the next entry and reports whether there is one.
+func (it *KVIterator) Next() bool {
+	for len(it.buf) == 0 {
+		if it.err != nil || it.seek == nil {
+			return false
+		}
+		it.buf, it.seek, it.err = it.t.leafFrom(it.seek)
+	}
+	it.cur, it.buf = it.buf[0], it.buf[1:]
+	if it.end != nil && bytes.Compare(it.cur.key, it.end) >= 0 {
+		it.buf, it.seek = nil, nil
+		return false
+	}
+	return true
+}
+
+// Key returns the current key. Valid after Next returned true.
+func (it *KVIterator) Key() []byte { return it.cur.key }
+
+// Value returns the current value. Valid after Next returned true.
+func (it *KVIterator) Value() []byte { return it.cur.val }
+
+// Err returns the first error met while iterating.
+func (it *KVIterator) Err() error { return it.err }
+
+// leafFrom returns the entries >= seek of the leaf that holds seek, plus the
+// key where the following leaf starts (nil if it is the last leaf).
+func (t *KVTree) leafFrom(seek []byte) ([]kvEntry, []byte, error) {
+	t.mu.RLock()
+	defer t.mu.RUnlock()
+	if t.closed {
+		return nil, nil, ErrTreeClosed
+	}
+
+	var next []byte
+	pageID := t.root
+	for level := t.height; level > 1; level-- {
+		entries, err := t.readNode(pageID, false)
+		if err != nil {
+			return nil, nil, err
+		}
+		if len(entries) == 0 {
+			return nil, nil, ErrInternalNodeHasNoEntries
+		}
+		ci := childIndex(entries, seek)
+		if ci+1 < len(entries) {
+			next = entries[ci+1].key
+		}
+		pageID = entries[ci].child
+	}
+	entries, err := t.readNode(pageID, true)
+	if err != nil {
+		return nil, nil, err
+	}
+	i, _ := search(entries, seek)
+	return entries[i:], next, nil
+}
@@ -0,0 +1,299 @@
+package btree
+
+import (
+	"bytes"
+	"fmt"
+	"math/rand"
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/bufferpool"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+func openTestKVTree(t testing.TB, dir string) (*KVTree, *bufferpool.GlobalPool) {
+	t.Helper()
+
+	sm := storage.NewStorageManager()
+	w, err := wal.Open(filepath.Join(dir, "wal"))
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = w.Close() })
+	gp := bufferpool.NewGlobalPool(sm, bufferpool.DefaultCapacity, w)
+
+	fs := storage.LocalFileSet{Dir: dir, Base: "kv_idx"}
+	tree, err := OpenKVTree(sm, fs, gp.View(fs))
+	require.NoError(t, err)
+	return tree, gp
+}
+
+func kvKey(i int) []byte { return fmt.Appendf(nil, "key-%06d", i) }
+
+func scanKeys(t *testing.T, tree *KVTree, start, end []byte) []string {
+	t.Helper()
+
+	var out []string
+	it := tree.Scan(start, end)
+	for it.Next() {
+		out = append(out, string(it.Key()))
+	}
+	require.NoError(t, it.Err())
+	return out
+}
+
+func TestKVTree_InsertGetUpsert(t *testing.T) {
+	tree, _ := openTestKVTree(t, t.TempDir())
+	t.Cleanup(func() { _ = tree.Close() })
+
+	const n = 3000
+	for _, i := range rand.New(rand.NewSource(1)).Perm(n) {
+		require.NoError(t, tree.Insert(kvKey(i), fmt.Appendf(nil, "v%d", i)))
+	}
+	require.Greater(t, tree.Height(), 1)
+
+	for i := range n {
+		v, ok, err := tree.Get(kvKey(i))
+		require.NoError(t, err)
+		require.True(t, ok)
+		require.Equal(t, fmt.Sprintf("v%d", i), string(v))
+	}
+	_, ok, err := tree.Get([]byte("missing"))
+	require.NoError(t, err)
+	require.False(t, ok)
+
+	require.NoError(t, tree.Insert(kvKey(7), []byte("replaced")))
+	v, _, err := tree.Get(kvKey(7))
+	require.NoError(t, err)
+	require.Equal(t, "replaced", string(v))
+
+	require.ErrorIs(t, tree.Insert(nil, []byte("v")), ErrEmptyKey)
+	require.ErrorIs(t, tree.Insert([]byte("k"), make([]byte, MaxKVEntrySize)), ErrKeyTooLarge)
+}
+
+func TestKVTree_NodeSpace(t *testing.T) {
+	plain, _ := openTestKVTree(t, t.TempDir())
+	t.Cleanup(func() { _ = plain.Close() })
+	require.Equal(t, kvNodeSpace, plain.space)
+
+	// Only encrypted nodes give up the encryption reserve, and they still
+	// split and fill correctly.
+	dir := t.TempDir()
+	sm := storage.NewStorageManager()
+	require.NoError(t, sm.SetEncryptionKey(bytes.Repeat([]byte{7}, 32)))
+	gp := bufferpool.NewGlobalPool(sm, bufferpool.DefaultCapacity, nil)
+	fs := storage.LocalFileSet{Dir: dir, Base: "kv_idx"}
+	enc, err := OpenKVTree(sm, fs, gp.View(fs))
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = enc.Close() })
+	require.Equal(t, kvNodeSpace-storage.EncryptionReserve, enc.space)
+
+	value := make([]byte, MaxKVEntrySize-len(kvKey(0)))
+	for i := range 200 {
+		require.NoError(t, enc.Insert(kvKey(i), value))
+	}
+	for i := range 200 {
+		_, ok, err := enc.Get(kvKey(i))
+		require.NoError(t, err)
+		require.True(t, ok)
+	}
+}
+
+func TestKVTree_ScanRange(t *testing.T) {
+	tree, _ := openTestKVTree(t, t.TempDir())
+	t.Cleanup(func() { _ = tree.Close() })
+
+	const n = 2000
+	for _, i := range rand.New(rand.NewSource(2)).Perm(n) {
+		require.NoError(t, tree.Insert(kvKey(i), bytes.Repeat([]byte{'x'}, 40)))
+	}
+
+	all := scanKeys(t, tree, nil, nil)
+	require.Len(t, all, n)
+	for i, k := range all {
+		require.Equal(t, string(kvKey(i)), k)
+	}
+
+	got := scanKeys(t, tree, kvKey(500), kvKey(1500))
+	require.Len(t, got, 1000)
+	require.Equal(t, string(kvKey(500)), got[0])
+	require.Equal(t, string(kvKey(1499)), got[len(got)-1])
+
+	require.Empty(t, scanKeys(t, tree, []byte("zzz"), nil))
+}
+
+func TestKVTree_DeleteMergesDownToEmpty(t *testing.T) {
+	tree, _ := openTestKVTree(t, t.TempDir())
+	t.Cleanup(func() { _ = tree.Close() })
+
+	const n = 3000
+	for i := range n {
+		require.NoError(t, tree.Insert(kvKey(i), bytes.Repeat([]byte{'v'}, 60)))
+	}
+	grown := tree.Height()
+	require.Greater(t, grown, 1)
+
+	ok, err := tree.Delete([]byte("missing"))
+	require.NoError(t, err)
+	require.False(t, ok)
+
+	// Delete every other key, then the rest, checking the survivors each time.
+	order := rand.New(rand.NewSource(3)).Perm(n)
+	for step, i := range order {
+		ok, err := tree.Delete(kvKey(i))
+		require.NoError(t, err)
+		require.True(t, ok)
+
+		if step == n/2 {
+			left := scanKeys(t, tree, nil, nil)
+			require.Len(t, left, n-step-1)
+			for _, j := range order[step+1:] {
+				_, ok, err := tree.Get(kvKey(j))
+				require.NoError(t, err)
+				require.True(t, ok)
+			}
+		}
+	}
+
+	require.Empty(t, scanKeys(t, tree, nil, nil))
+	require.Equal(t, 1, tree.Height())
+	_, ok, err = tree.Get(kvKey(0))
+	require.NoError(t, err)
+	require.False(t, ok)
+}
+
+func TestKVTree_ReopenPersists(t *testing.T) {
+	dir := t.TempDir()
+	tree, _ := openTestKVTree(t, dir)
+	for i := range 1500 {
+		require.NoError(t, tree.Insert(kvKey(i), kvKey(i)))
+	}
+	for i := 0; i < 1500; i += 3 {
+		_, err := tree.Delete(kvKey(i))
+		require.NoError(t, err)
+	}
+	height := tree.Height()
+	require.NoError(t, tree.Close())
+	require.ErrorIs(t, tree.Insert(kvKey(1), nil), ErrTreeClosed)
+
+	tree, _ = openTestKVTree(t, dir)
+	t.Cleanup(func() { _ = tree.Close() })
+	require.Equal(t, height, tree.Height())
+	require.Len(t, scanKeys(t, tree, nil, nil), 1000)
+
+	v, ok, err := tree.Get(kvKey(1499))
+	require.NoError(t, err)
+	require.True(t, ok)
+	require.Equal(t, kvKey(1499), v)
+}
+
+func TestKVTree_ReusesFreedPages(t *testing.T) {
+	dir := t.TempDir()
+	tree, _ := openTestKVTree(t, dir)
+
+	const n = 3000
+	for i := range n {
+		require.NoError(t, tree.Insert(kvKey(i), kvKey(i)))
+	}
+	grown := tree.alloc.NextPageID()
+	for i := range n {
+		_, err := tree.Delete(kvKey(i))
+		require.NoError(t, err)
+	}
+	require.Equal(t, 1, tree.Height())
+	require.Positive(t, tree.alloc.FreeCount())
+
+	// The free list survives a reopen and refilling the tree reuses it.
+	require.NoError(t, tree.Close())
+	tree, _ = openTestKVTree(t, dir)
+	t.Cleanup(func() { _ = tree.Close() })
+	require.Positive(t, tree.alloc.FreeCount())
+
+	for round := range 3 {
+		for i := range n {
+			require.NoError(t, tree.Insert(kvKey(i), kvKey(i)))
+		}
+		for i := range n {
+			_, err := tree.Delete(kvKey(i))
+			require.NoError(t, err, "round %d", round)
+		}
+	}
+	require.LessOrEqual(t, tree.alloc.NextPageID(), grown)
+	require.Empty(t, scanKeys(t, tree, nil, nil))
+}
+
+func TestInspectKVTree(t *testing.T) {
+	dir := t.TempDir()
+	tree, _ := openTestKVTree(t, dir)
+	const n = 1500
+	for _, i := range rand.New(rand.NewSource(7)).Perm(n) {
+		require.NoError(t, tree.Insert(kvKey(i), bytes.Repeat([]byte{'v'}, 40)))
+	}
+	height := tree.Height()
+	require.Greater(t, height, 1)
+	require.NoError(t, tree.Close())
+
+	read := func(pageID uint32, dst []byte) error { return tree.SM.ReadPage(tree.FS, int32(pageID), dst) }
+	fs := tree.FS.(storage.LocalFileSet)
+	info, err := InspectKVTree(fs, read)
+	require.NoError(t, err)
+	require.Equal(t, height, info.Height)
+	require.Equal(t, info.Root, info.Nodes[0].PageID)
+	require.Equal(t, height-1, info.Nodes[0].Level)
+
+	// The leaves, left to right, hold every key in order.
+	var keys []string
+	for _, node := range info.Nodes {
+		if node.Level > 0 {
+			require.Len(t, node.Children, len(node.Keys))
+			continue
+		}
+		require.Nil(t, node.Children)
+		for _, k := range node.Keys {
+			keys = append(keys, string(k))
+		}
+	}
+	require.Len(t, keys, n)
+	for i, k := range keys {
+		require.Equal(t, string(kvKey(i)), k)
+	}
+
+	// A node that is not a KVTree node stops the walk.
+	bad := func(_ uint32, dst []byte) error {
+		clear(dst)
+		return nil
+	}
+	_, err = InspectKVTree(fs, bad)
+	require.ErrorIs(t, err, storage.ErrCorruption)
+}
+
+func BenchmarkKVTree_Insert(b *testing.B) {
+	tree, _ := openTestKVTree(b, b.TempDir())
+	b.Cleanup(func() { _ = tree.Close() })
+	order := rand.New(rand.NewSource(4)).Perm(b.N)
+
+	b.ResetTimer()
+	for _, i := range order {
+		if err := tree.Insert(kvKey(i), []byte("v")); err != nil {
+			b.Fatal(err)
+		}
+	}
+}
+
+func BenchmarkKVTree_Get(b *testing.B) {
+	tree, _ := openTestKVTree(b, b.TempDir())
+	b.Cleanup(func() { _ = tree.Close() })
+	const n = 20000
+	for _, i := range rand.New(rand.NewSource(5)).Perm(n) {
+		require.NoError(b, tree.Insert(kvKey(i), []byte("v")))
+	}
+	rng := rand.New(rand.NewSource(6))
+
+	b.ResetTimer()
+	for range b.N {
+		if _, ok, err := tree.Get(kvKey(rng.Intn(n))); err != nil || !ok {
+			b.Fatalf("get: found %v, err %v", ok, err)
+		}
+	}
+}
@@ -21,6 +21,14 @@ type diskMeta struct {
 	Root       uint32 `json:"root"`
 	Height     int    `json:"height"`
 	NextPageID uint32 `json:"next_page_id"`
+
+	// FreePages lists pages released by KVTree merges, waiting for reuse.
+	FreePages []uint32 `json:"free_pages,omitempty"`
+}
+
+// MetaPath is the meta file kept next to the segments of an index.
+func MetaPath(lfs storage.LocalFileSet) string {
+	return filepath.Join(lfs.Dir, lfs.Base+metaFileSuffix)
 }
 
 func metaPathForFileSet(fs storage.FileSet) (string, bool) {
@@ -29,7 +37,7 @@ func metaPathForFileSet(fs storage.FileSet) (string, bool) {
 		return "", false
 	}
 	// meta file nằm cạnh các segment: <Dir>/<Base>.btree.meta.json
-	return filepath.Join(lfs.Dir, lfs.Base+metaFileSuffix), true
+	return MetaPath(lfs), true
 }
 
 func (t *Tree) loadMeta() (diskMeta, bool, error) {
@@ -61,31 +69,35 @@ func (t *Tree) saveMeta() error {
 		return nil
 	}
 
-	m := diskMeta{
-		Version:    metaVersion,
+	return writeDiskMeta(t.metaPath, diskMeta{
 		Root:       t.Root,
 		Height:     t.Height,
 		NextPageID: t.nextPageID,
-	}
+	})
+}
 
+// writeDiskMeta atomically replaces the meta file at path with m.
+func writeDiskMeta(path string, m diskMeta) error {
+	m.Version = metaVersion
 	data, err := json.MarshalIndent(&m, "", "  ")
 	if err != nil {
 		return err
 	}
 
-	if err := os.MkdirAll(filepath.Dir(t.metaPath), 0o755); err != nil {
+	if err := os.MkdirAll(filepath.Dir(path), 0o755); err != nil {
 		return err
 	}
 
-	if err := writeFileAtomic(t.metaPath, data, 0o644); err != nil {
+	if err := writeFileAtomic(path, data, 0o644); err != nil {
 		return err
 	}
 
 	slog.Debug("btree.meta.saved",
-		"path", t.metaPath,
+		"path", path,
 		"root", m.Root,
 		"height", m.Height,
 		"nextPageID", m.NextPageID,
+		"freePages", len(m.FreePages),
 	)
 	return nil
 }
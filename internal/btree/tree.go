@@ -436,7 +436,7 @@ func (t *Tree) insertIntoLeaf(
 	entries = append(entries, leafEntry{key: key, tid: tid})
 	sortLeafEntries(entries)
 
-	maxPerPage := maxLeafEntriesPerPage()
+	maxPerPage := maxLeafEntriesPerPage(t.SM)
 	if maxPerPage <= 0 {
 		return 0, false, 0, 0, fmt.Errorf("btree: leaf page capacity is zero")
 	}
@@ -559,7 +559,7 @@ func (t *Tree) insertIntoInternal(
 		return entries[i].child < entries[j].child
 	})
 
-	maxPerPage := maxInternalEntriesPerPage()
+	maxPerPage := maxInternalEntriesPerPage(t.SM)
 	if maxPerPage <= 0 {
 		return 0, false, 0, 0, ErrInternalNodePageHasZeroCap
 	}
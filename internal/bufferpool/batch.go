@@ -0,0 +1,194 @@
+package bufferpool
+
+import (
+	"slices"
+
+	"github.com/tuannm99/novasql/internal/metrics"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+// ReadPages returns copies of the n pages of fs from first. Resident pages
+// are copied from their frames; each run of the others is read from disk
+// with storage.LoadPages and not cached, so a large read does not evict the
+// working set. The pages are not read atomically: a page written while
+// ReadPages runs may come back in either state.
+func (g *GlobalPool) ReadPages(fs storage.FileSet, first uint32, n int) ([][]byte, error) {
+	key, lfs, ok := storage.FsKeyOf(fs)
+	if !ok {
+		return nil, ErrUnsupportedFileSet
+	}
+	out := make([][]byte, n)
+	for i := range out {
+		tag := PageTag{FSKey: key, PageID: first + uint32(i)}
+		s := g.shardOf(tag)
+		s.mu.Lock()
+		if idx, ok := s.table[tag]; ok && s.frames[idx] != nil {
+			f := s.frames[idx]
+			f.LastUsed = g.nextTick()
+			s.repl.RecordAccess(idx)
+			out[i] = append([]byte(nil), f.Page.Buf...)
+			g.hit(1)
+		}
+		s.mu.Unlock()
+	}
+
+	for i := 0; i < n; {
+		if out[i] != nil {
+			i++
+			continue
+		}
+		j := i + 1
+		for j < n && out[j] == nil {
+			j++
+		}
+		pages, err := g.sm.LoadPages(lfs, first+uint32(i), j-i)
+		if err != nil {
+			return nil, err
+		}
+		for k, p := range pages {
+			out[i+k] = p.Buf
+		}
+		g.miss(uint64(j - i))
+		g.read(uint64(j - i))
+		i = j
+	}
+	return out, nil
+}
+
+// WritePages replaces the n = len(pages) pages of fs from first with pages
+// (full pages, trailers ignored) as one durable WAL group, like CommitTx
+// without version checks. Resident pages are updated in their frames and
+// flushed later as usual; the others are written straight to disk, each
+// run of them with storage.SavePages.
+func (g *GlobalPool) WritePages(fs storage.FileSet, first uint32, pages [][]byte) error {
+	ids := make([]uint32, len(pages))
+	for i := range ids {
+		ids[i] = first + uint32(i)
+	}
+	return g.WritePageList(fs, ids, pages)
+}
+
+// WritePageList is WritePages for pages ids[i], distinct but not
+// necessarily contiguous: runs of consecutive ones are read and written
+// together.
+func (g *GlobalPool) WritePageList(fs storage.FileSet, ids []uint32, pages [][]byte) error {
+	if len(pages) == 0 {
+		return nil
+	}
+	key, lfs, ok := storage.FsKeyOf(fs)
+	if !ok {
+		return ErrUnsupportedFileSet
+	}
+	for _, data := range pages {
+		if len(data) != storage.PageSize {
+			return storage.ErrWrongSize
+		}
+	}
+	if err := g.writePages(key, lfs, ids, pages); err != nil {
+		return err
+	}
+	return g.maybeCheckpoint()
+}
+
+// diskRun returns the end of the run of pages from i that have no frame and
+// consecutive IDs.
+func diskRun(ids []uint32, frames []*Frame, i int) int {
+	j := i + 1
+	for j < len(ids) && frames[j] == nil && ids[j] == ids[j-1]+1 {
+		j++
+	}
+	return j
+}
+
+func (g *GlobalPool) writePages(key string, lfs storage.LocalFileSet, ids []uint32, pages [][]byte) error {
+	tags := make([]PageTag, len(pages))
+	shards := make([]int, len(pages))
+	for i := range pages {
+		tags[i] = PageTag{FSKey: key, PageID: ids[i]}
+		shards[i] = g.shardIndex(tags[i])
+	}
+	locked := slices.Compact(slices.Sorted(slices.Values(shards)))
+	for _, i := range locked {
+		g.shards[i].mu.Lock()
+	}
+	defer func() {
+		for _, i := range locked {
+			g.shards[i].mu.Unlock()
+		}
+	}()
+
+	// The current page of each slot gives the trailer to bump: from its
+	// frame if resident, else from disk, read in runs.
+	frames := make([]*Frame, len(pages))
+	images := make([]*storage.Page, len(pages))
+	for i, tag := range tags {
+		s := g.shards[shards[i]]
+		if idx, ok := s.table[tag]; ok && s.frames[idx] != nil {
+			frames[i] = s.frames[idx]
+			images[i] = &storage.Page{Buf: append([]byte(nil), frames[i].Page.Buf...)}
+		}
+	}
+	for i := 0; i < len(pages); {
+		if images[i] != nil {
+			i++
+			continue
+		}
+		j := diskRun(ids, frames, i)
+		loaded, err := g.sm.LoadPages(lfs, ids[i], j-i)
+		if err != nil {
+			return err
+		}
+		copy(images[i:j], loaded)
+		g.read(uint64(j - i))
+		i = j
+	}
+
+	logged := make([]wal.PageImage, len(pages))
+	for i, img := range images {
+		if err := img.SetContents(pages[i]); err != nil {
+			return err
+		}
+		img.BumpVersion()
+		img.SealChecksum()
+		logged[i] = wal.PageImage{Dir: lfs.Dir, Base: lfs.Base, PageID: tags[i].PageID, Page: img.Buf}
+	}
+	var lsn uint64
+	if g.wal != nil {
+		var err error
+		if lsn, err = g.wal.AppendTx(logged); err != nil {
+			return err
+		}
+	}
+
+	// Logged: install the resident pages, write the others.
+	flushed := false
+	for i := 0; i < len(pages); {
+		if f := frames[i]; f != nil {
+			copy(f.Page.Buf, images[i].Buf)
+			f.Dirty = true
+			f.LSN = lsn
+			i++
+			continue
+		}
+		j := diskRun(ids, frames, i)
+		// The images reach the data file ahead of any checkpoint: the
+		// records must be on disk first, in any mode that syncs.
+		if g.wal != nil && !flushed {
+			if err := g.wal.Flush(lsn); err != nil {
+				return err
+			}
+			flushed = true
+		}
+		run := make([]storage.Page, 0, j-i)
+		for _, img := range images[i:j] {
+			run = append(run, *img)
+		}
+		if err := g.sm.SavePages(lfs, ids[i], run); err != nil {
+			return err
+		}
+		g.metrics.Add(metrics.PagesWritten, uint64(j-i))
+		i = j
+	}
+	return nil
+}
@@ -2,8 +2,13 @@ package bufferpool
 
 import (
 	"errors"
+	"hash/fnv"
+	"log/slog"
 	"sync"
+	"sync/atomic"
+	"time"
 
+	"github.com/tuannm99/novasql/internal/metrics"
 	"github.com/tuannm99/novasql/internal/storage"
 	"github.com/tuannm99/novasql/internal/wal"
 )
@@ -29,6 +34,13 @@ type Manager interface {
 	FlushAll() error
 }
 
+// PageWriter is a Manager that can also replace pages as one WAL group
+// (GlobalPool.WritePageList). FileSetView is one.
+type PageWriter interface {
+	Manager
+	WritePageList(ids []uint32, pages [][]byte) error
+}
+
 // ErrUnsupportedFileSet is returned when GlobalPool cannot work with a FileSet implementation.
 var ErrUnsupportedFileSet = errors.New("bufferpool: unsupported FileSet (global pool requires LocalFileSet)")
 
@@ -40,14 +52,54 @@ type PageTag struct {
 
 // GlobalPool is a single shared buffer pool for ALL relations (heap/index/ovf).
 // It mimics PostgreSQL shared_buffers at a high level.
+//
+// Pools of 2*minShardFrames frames or more are split into shards by page:
+// each shard has its own lock, frames, page table and replacer, so lookups,
+// loads and evictions of pages in different shards run concurrently. The
+// contents of a pinned page are not latched; callers that share a page
+// across goroutines still need their own locking.
 type GlobalPool struct {
-	sm *storage.StorageManager
+	sm  *storage.StorageManager
+	wal *wal.Manager
+
+	shards []*shard
+
+	// tick is a logical clock bumped on every access; frames remember the tick
+	// of their last use so the resident set can be ordered by recency.
+	tick atomic.Uint64
+
+	hits   atomic.Uint64
+	misses atomic.Uint64
+	reads  atomic.Uint64 // pages loaded from disk (misses + prefetches)
+
+	bgWrites atomic.Uint64 // pages written by the background writer
+
+	metrics *metrics.Registry // also counts the above, and pages written; see SetMetrics
+
+	// Automatic checkpoints (see SetAutoCheckpoint), guarded by cpMu.
+	cpMu            sync.Mutex
+	checkpointBytes int64
+	checkpointSync  func() error
+	checkpoints     uint64
 
-	mu     sync.Mutex
-	frames []*Frame        // len == capacity, nil == free slot
-	table  map[PageTag]int // (fsKey,pageID) -> frame index
-	repl   Replacer        // replacement policy tracks frame indices [0..cap)
-	wal    *wal.Manager
+	warm warmupState
+}
+
+// minShardFrames is the smallest shard: below it, pages of one hot shard
+// would be evicted while the other shards still have room.
+const minShardFrames = 64
+
+// maxShards bounds the shards of a pool.
+const maxShards = 16
+
+// shard is one lock domain of a GlobalPool. A page is logged (dirty unpin,
+// commit) only while its shard lock is held, which Checkpoint relies on.
+type shard struct {
+	mu      sync.Mutex
+	frames  []*Frame        // nil == free slot
+	table   map[PageTag]int // (fsKey,pageID) -> frame index
+	repl    Replacer        // replacement policy tracks frame indices [0..len(frames))
+	removed uint64          // pages taken out of table so far (see Prefetch)
 }
 
 // Frame is stored in global frames[].
@@ -59,18 +111,53 @@ type Frame struct {
 	Dirty bool
 	Pin   int32
 	LSN   uint64 // last wal lsn for this frame (0 if none)
+
+	LastUsed uint64 // pool tick of the last access (recency order)
 }
 
 func NewGlobalPool(sm *storage.StorageManager, capacity int, w *wal.Manager) *GlobalPool {
 	if capacity <= 0 {
 		capacity = DefaultCapacity
 	}
-	return &GlobalPool{
-		sm:     sm,
-		wal:    w,
-		frames: make([]*Frame, capacity),
-		table:  make(map[PageTag]int),
-		repl:   newClockAdapter(capacity),
+	n := min(max(capacity/minShardFrames, 1), maxShards)
+	g := &GlobalPool{sm: sm, wal: w, shards: make([]*shard, n)}
+	for i := range g.shards {
+		size := capacity / n
+		if i < capacity%n {
+			size++
+		}
+		g.shards[i] = &shard{
+			frames: make([]*Frame, size),
+			table:  make(map[PageTag]int),
+			repl:   newClockAdapter(size),
+		}
+	}
+	return g
+}
+
+// shardIndex is the shard of tag. Consecutive pages of a relation land in
+// consecutive shards, so a scan spreads over all of them.
+func (g *GlobalPool) shardIndex(tag PageTag) int {
+	if len(g.shards) == 1 {
+		return 0
+	}
+	h := fnv.New32a()
+	_, _ = h.Write([]byte(tag.FSKey))
+	return int((h.Sum32() + tag.PageID) % uint32(len(g.shards)))
+}
+
+func (g *GlobalPool) shardOf(tag PageTag) *shard { return g.shards[g.shardIndex(tag)] }
+
+// lockAll locks every shard, in order.
+func (g *GlobalPool) lockAll() {
+	for _, s := range g.shards {
+		s.mu.Lock()
+	}
+}
+
+func (g *GlobalPool) unlockAll() {
+	for _, s := range g.shards {
+		s.mu.Unlock()
 	}
 }
 
@@ -82,95 +169,101 @@ func (g *GlobalPool) GetPage(fs storage.FileSet, pageID uint32) (*storage.Page,
 	}
 	tag := PageTag{FSKey: key, PageID: pageID}
 
-	g.mu.Lock()
-	defer g.mu.Unlock()
+	s := g.shardOf(tag)
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	f, err := g.pinLocked(s, tag, lfs)
+	if err != nil {
+		return nil, err
+	}
+	return f.Page, nil
+}
+
+// pinLocked finds or loads the frame for tag in s, its shard, and pins it.
+// Caller holds s.mu.
+func (g *GlobalPool) pinLocked(s *shard, tag PageTag, lfs storage.LocalFileSet) (*Frame, error) {
+	pageID := tag.PageID
 
 	// 1) HIT
-	if idx, ok := g.table[tag]; ok {
-		f := g.frames[idx]
+	if idx, ok := s.table[tag]; ok {
+		f := s.frames[idx]
 		if f == nil {
 			// Inconsistent mapping -> cleanup.
-			delete(g.table, tag)
+			delete(s.table, tag)
 		} else {
 			wasZero := (f.Pin == 0)
 			f.Pin++
+			f.LastUsed = g.nextTick()
+			g.hit(1)
 
-			g.repl.RecordAccess(idx)
+			s.repl.RecordAccess(idx)
 			if wasZero {
-				g.repl.SetEvictable(idx, false)
+				s.repl.SetEvictable(idx, false)
 			}
-			return f.Page, nil
+			return f, nil
 		}
 	}
 
 	// 2) Find free slot
-	freeIdx := -1
-	for i, f := range g.frames {
-		if f == nil {
-			freeIdx = i
-			break
-		}
-	}
+	freeIdx := s.freeFrameLocked()
 	if freeIdx != -1 {
 		page, err := g.sm.LoadPage(lfs, pageID)
 		if err != nil {
 			return nil, err
 		}
-
-		g.frames[freeIdx] = &Frame{
-			Tag:   tag,
-			FS:    lfs,
-			Page:  page,
-			Dirty: false,
-			Pin:   1,
-			LSN:   0,
+		g.miss(1)
+		g.read(1)
+
+		s.frames[freeIdx] = &Frame{
+			Tag:      tag,
+			FS:       lfs,
+			Page:     page,
+			Dirty:    false,
+			Pin:      1,
+			LSN:      0,
+			LastUsed: g.nextTick(),
 		}
-		g.table[tag] = freeIdx
+		s.table[tag] = freeIdx
 
-		g.repl.RecordAccess(freeIdx)
-		g.repl.SetEvictable(freeIdx, false)
-		return page, nil
+		s.repl.RecordAccess(freeIdx)
+		s.repl.SetEvictable(freeIdx, false)
+		return s.frames[freeIdx], nil
 	}
 
 	// 3) Evict
-	victimIdx, ok := g.repl.Evict()
+	victimIdx, ok := s.repl.Evict()
 	if !ok {
 		return nil, ErrNoFreeFrame
 	}
-	victim := g.frames[victimIdx]
+	victim := s.frames[victimIdx]
 	if victim == nil || victim.Pin != 0 {
 		return nil, ErrNoFreeFrame
 	}
 
 	// Flush victim if dirty
 	if victim.Dirty {
-		if g.wal != nil && victim.LSN != 0 {
-			if err := g.wal.Flush(victim.LSN); err != nil {
-				g.repl.RecordAccess(victimIdx)
-				g.repl.SetEvictable(victimIdx, true)
-				return nil, err
-			}
-		}
-		if err := g.sm.SavePage(victim.FS, victim.Tag.PageID, *victim.Page); err != nil {
-			g.repl.RecordAccess(victimIdx)
-			g.repl.SetEvictable(victimIdx, true)
+		if err := g.writeBack(victim); err != nil {
+			s.repl.RecordAccess(victimIdx)
+			s.repl.SetEvictable(victimIdx, true)
 			return nil, err
 		}
-		victim.Dirty = false
-		victim.LSN = 0
 	}
 
 	// Load requested page
 	newPage, err := g.sm.LoadPage(lfs, pageID)
 	if err != nil {
 		// Put victim back as evictable
-		g.repl.RecordAccess(victimIdx)
-		g.repl.SetEvictable(victimIdx, true)
+		s.repl.RecordAccess(victimIdx)
+		s.repl.SetEvictable(victimIdx, true)
 		return nil, err
 	}
+	g.miss(1)
+	g.read(1)
 
 	// Remove old mapping
-	delete(g.table, victim.Tag)
+	delete(s.table, victim.Tag)
+	s.removed++
 
 	// Reuse victim frame
 	victim.Tag = tag
@@ -178,12 +271,125 @@ func (g *GlobalPool) GetPage(fs storage.FileSet, pageID uint32) (*storage.Page,
 	victim.Page = newPage
 	victim.Dirty = false
 	victim.Pin = 1
+	victim.LastUsed = g.nextTick()
 
-	g.table[tag] = victimIdx
-	g.repl.RecordAccess(victimIdx)
-	g.repl.SetEvictable(victimIdx, false)
+	s.table[tag] = victimIdx
+	s.repl.RecordAccess(victimIdx)
+	s.repl.SetEvictable(victimIdx, false)
 
-	return newPage, nil
+	return victim, nil
+}
+
+// writeBack writes the dirty frame f to disk, WAL first. Caller holds the
+// lock of its shard.
+func (g *GlobalPool) writeBack(f *Frame) error {
+	if g.wal != nil && f.LSN != 0 {
+		if err := g.wal.Flush(f.LSN); err != nil {
+			return err
+		}
+	}
+	if err := g.sm.SavePage(f.FS, f.Tag.PageID, *f.Page); err != nil {
+		return err
+	}
+	g.metrics.Add(metrics.PagesWritten, 1)
+	f.Dirty = false
+	f.LSN = 0
+	return nil
+}
+
+// SetMetrics makes the pool count its hits, misses and page reads and
+// writes into r as well. Call it before the pool is used.
+func (g *GlobalPool) SetMetrics(r *metrics.Registry) { g.metrics = r }
+
+func (g *GlobalPool) hit(n uint64) {
+	g.hits.Add(n)
+	g.metrics.Add(metrics.PoolHits, n)
+}
+
+func (g *GlobalPool) miss(n uint64) {
+	g.misses.Add(n)
+	g.metrics.Add(metrics.PoolMisses, n)
+}
+
+func (g *GlobalPool) read(n uint64) {
+	g.reads.Add(n)
+	g.metrics.Add(metrics.PagesRead, n)
+}
+
+func (s *shard) freeFrameLocked() int {
+	for i, f := range s.frames {
+		if f == nil {
+			return i
+		}
+	}
+	return -1
+}
+
+func (g *GlobalPool) nextTick() uint64 {
+	return g.tick.Add(1)
+}
+
+// Contains reports whether (fs,pageID) is resident. It does not pin the page
+// and does not count as an access.
+func (g *GlobalPool) Contains(fs storage.FileSet, pageID uint32) bool {
+	key, _, ok := storage.FsKeyOf(fs)
+	if !ok {
+		return false
+	}
+	tag := PageTag{FSKey: key, PageID: pageID}
+
+	s := g.shardOf(tag)
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	_, ok = s.table[tag]
+	return ok
+}
+
+// Stats is a point-in-time snapshot of pool counters.
+type Stats struct {
+	Capacity int
+	Resident int
+	Shards   int
+	Hits     uint64
+	Misses   uint64
+	Reads    uint64 // pages loaded from disk (misses + prefetches)
+	Warmup   WarmupStats
+
+	Checkpoints uint64 // WAL checkpoints taken, manual and automatic
+
+	BackgroundWrites uint64 // pages written by the background writer (StartWriter)
+}
+
+// Stats returns the current pool counters.
+func (g *GlobalPool) Stats() Stats {
+	capacity, resident := 0, 0
+	for _, s := range g.shards {
+		s.mu.Lock()
+		capacity += len(s.frames)
+		for _, f := range s.frames {
+			if f != nil {
+				resident++
+			}
+		}
+		s.mu.Unlock()
+	}
+	g.cpMu.Lock()
+	checkpoints := g.checkpoints
+	g.cpMu.Unlock()
+	return Stats{
+		Capacity: capacity,
+		Resident: resident,
+		Shards:   len(g.shards),
+		Hits:     g.hits.Load(),
+		Misses:   g.misses.Load(),
+		Reads:    g.reads.Load(),
+		Warmup:   g.warm.snapshot(),
+
+		Checkpoints: checkpoints,
+
+		BackgroundWrites: g.bgWrites.Load(),
+	}
 }
 
 // Unpin decreases pin count and marks dirty optionally.
@@ -197,20 +403,33 @@ func (g *GlobalPool) Unpin(fs storage.FileSet, page *storage.Page, dirty bool) e
 	}
 	tag := PageTag{FSKey: key, PageID: page.PageID()}
 
-	g.mu.Lock()
-	defer g.mu.Unlock()
+	s := g.shardOf(tag)
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	return g.unpinLocked(s, tag, dirty)
+}
 
-	idx, ok := g.table[tag]
+// unpinLocked drops one pin on tag in s, its shard; dirty marks the page
+// modified. Caller holds s.mu.
+func (g *GlobalPool) unpinLocked(s *shard, tag PageTag, dirty bool) error {
+	idx, ok := s.table[tag]
 	if !ok {
 		return nil
 	}
-	f := g.frames[idx]
+	f := s.frames[idx]
 	if f == nil {
-		delete(g.table, tag)
+		delete(s.table, tag)
 		return nil
 	}
 
 	if dirty {
+		// Every logged write bumps the page version; the WAL image carries it,
+		// so recovery replay restores the same version.
+		if f.Page != nil {
+			f.Page.BumpVersion()
+			f.Page.SealChecksum()
+		}
 		// Append WAL page image BEFORE marking dirty (WAL rule).
 		if g.wal != nil && f.Page != nil {
 			lsn, err := g.wal.AppendPageImage(f.FS.Dir, f.FS.Base, f.Tag.PageID, f.Page.Buf)
@@ -225,35 +444,114 @@ func (g *GlobalPool) Unpin(fs storage.FileSet, page *storage.Page, dirty bool) e
 	if f.Pin > 0 {
 		f.Pin--
 		if f.Pin == 0 {
-			g.repl.SetEvictable(idx, true)
+			s.repl.SetEvictable(idx, true)
 		}
 	}
 	return nil
 }
 
-// FlushAll flushes all dirty pages in the global pool.
+// FlushAll flushes all dirty pages in the global pool, one shard at a time.
 func (g *GlobalPool) FlushAll() error {
-	g.mu.Lock()
-	defer g.mu.Unlock()
+	for _, s := range g.shards {
+		if err := g.flushShard(s, ""); err != nil {
+			return err
+		}
+	}
+	return g.maybeCheckpoint()
+}
+
+// flushShard writes back the dirty frames of s of the relation key, of all
+// relations when key is "".
+func (g *GlobalPool) flushShard(s *shard, key string) error {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	return g.flushLocked(s, key)
+}
 
-	for _, f := range g.frames {
-		if f == nil || !f.Dirty {
+// flushLocked is flushShard with s.mu held.
+func (g *GlobalPool) flushLocked(s *shard, key string) error {
+	for _, f := range s.frames {
+		if f == nil || !f.Dirty || (key != "" && f.Tag.FSKey != key) {
 			continue
 		}
-		if g.wal != nil && f.LSN != 0 {
-			if err := g.wal.Flush(f.LSN); err != nil {
-				return err
-			}
-		}
-		if err := g.sm.SavePage(f.FS, f.Tag.PageID, *f.Page); err != nil {
+		if err := g.writeBack(f); err != nil {
 			return err
 		}
-		f.Dirty = false
-		f.LSN = 0
 	}
 	return nil
 }
 
+// SetAutoCheckpoint makes FlushAll and CommitTx take a checkpoint, with sync
+// as for Checkpoint, whenever the WAL has grown to maxBytes or more.
+// maxBytes <= 0 turns automatic checkpoints off.
+func (g *GlobalPool) SetAutoCheckpoint(maxBytes int64, sync func() error) {
+	g.cpMu.Lock()
+	defer g.cpMu.Unlock()
+	g.checkpointBytes = maxBytes
+	g.checkpointSync = sync
+}
+
+// maybeCheckpoint checkpoints if the WAL is over the automatic limit. Caller
+// holds no shard lock.
+func (g *GlobalPool) maybeCheckpoint() error {
+	g.cpMu.Lock()
+	limit, sync := g.checkpointBytes, g.checkpointSync
+	g.cpMu.Unlock()
+	if g.wal == nil || limit <= 0 {
+		return nil
+	}
+	size, err := g.wal.Size()
+	if errors.Is(err, wal.ErrNoWALFile) {
+		return nil // closed: nothing to truncate
+	}
+	if err != nil {
+		return err
+	}
+	if size < limit {
+		return nil
+	}
+	return g.Checkpoint(sync)
+}
+
+// Checkpoint flushes every dirty page, calls sync to make the data files
+// durable, then truncates the WAL. Every shard lock is held throughout, so no
+// page can be logged between the flush and the truncation.
+func (g *GlobalPool) Checkpoint(sync func() error) error {
+	_, err := g.CheckpointLSN(sync)
+	return err
+}
+
+// CheckpointLSN is Checkpoint returning the LSN it was taken at: the data
+// files hold every record up to it. Without a WAL it returns 0.
+func (g *GlobalPool) CheckpointLSN(sync func() error) (uint64, error) {
+	g.lockAll()
+	defer g.unlockAll()
+	start := time.Now()
+
+	for _, s := range g.shards {
+		if err := g.flushLocked(s, ""); err != nil {
+			return 0, err
+		}
+	}
+	if g.wal == nil {
+		return 0, nil
+	}
+	if sync != nil {
+		if err := sync(); err != nil {
+			return 0, err
+		}
+	}
+	lsn := g.wal.LastLSN()
+	if err := g.wal.Checkpoint(); err != nil {
+		return 0, err
+	}
+	g.cpMu.Lock()
+	g.checkpoints++
+	g.cpMu.Unlock()
+	slog.Debug("bufferpool: checkpoint", "lsn", lsn, "duration", time.Since(start))
+	return lsn, nil
+}
+
 // FlushFileSet flushes dirty pages belonging to a single relation (FileSet).
 func (g *GlobalPool) FlushFileSet(fs storage.FileSet) error {
 	key, _, ok := storage.FsKeyOf(fs)
@@ -261,26 +559,10 @@ func (g *GlobalPool) FlushFileSet(fs storage.FileSet) error {
 		return ErrUnsupportedFileSet
 	}
 
-	g.mu.Lock()
-	defer g.mu.Unlock()
-
-	for _, f := range g.frames {
-		if f == nil || !f.Dirty {
-			continue
-		}
-		if f.Tag.FSKey != key {
-			continue
-		}
-		if g.wal != nil && f.LSN != 0 {
-			if err := g.wal.Flush(f.LSN); err != nil {
-				return err
-			}
-		}
-		if err := g.sm.SavePage(f.FS, f.Tag.PageID, *f.Page); err != nil {
+	for _, s := range g.shards {
+		if err := g.flushShard(s, key); err != nil {
 			return err
 		}
-		f.Dirty = false
-		f.LSN = 0
 	}
 	return nil
 }
@@ -295,42 +577,63 @@ func (g *GlobalPool) DropFileSet(fs storage.FileSet) error {
 		return ErrUnsupportedFileSet
 	}
 
-	g.mu.Lock()
-	defer g.mu.Unlock()
+	g.lockAll()
+	defer g.unlockAll()
 
 	// First pass: detect pinned
-	for _, f := range g.frames {
-		if f == nil {
-			continue
-		}
-		if f.Tag.FSKey == key && f.Pin != 0 {
-			return ErrPagePinned
+	for _, s := range g.shards {
+		for _, f := range s.frames {
+			if f != nil && f.Tag.FSKey == key && f.Pin != 0 {
+				return ErrPagePinned
+			}
 		}
 	}
 
 	// Second pass: flush + remove
-	for i, f := range g.frames {
-		if f == nil {
-			continue
-		}
-		if f.Tag.FSKey != key {
-			continue
-		}
-
-		if f.Dirty {
-			if g.wal != nil && f.LSN != 0 {
-				if err := g.wal.Flush(f.LSN); err != nil {
+	for _, s := range g.shards {
+		for i, f := range s.frames {
+			if f == nil || f.Tag.FSKey != key {
+				continue
+			}
+			if f.Dirty {
+				if err := g.writeBack(f); err != nil {
 					return err
 				}
 			}
-			if err := g.sm.SavePage(f.FS, f.Tag.PageID, *f.Page); err != nil {
-				return err
-			}
+			delete(s.table, f.Tag)
+			s.removed++
+			s.frames[i] = nil
+			s.repl.Remove(i)
 		}
+	}
+	return nil
+}
 
-		delete(g.table, f.Tag)
-		g.frames[i] = nil
-		g.repl.Remove(i)
+// DropPage removes (fs,pageID) from the pool without writing it back, so the
+// next GetPage reads it from disk again: for pages another writer changed
+// there, such as those a replica applies. A pinned page stays and
+// ErrPagePinned is returned.
+func (g *GlobalPool) DropPage(fs storage.FileSet, pageID uint32) error {
+	key, _, ok := storage.FsKeyOf(fs)
+	if !ok {
+		return ErrUnsupportedFileSet
+	}
+	tag := PageTag{FSKey: key, PageID: pageID}
+
+	s := g.shardOf(tag)
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	idx, ok := s.table[tag]
+	if !ok {
+		return nil
+	}
+	if s.frames[idx].Pin != 0 {
+		return ErrPagePinned
 	}
+	delete(s.table, tag)
+	s.removed++
+	s.frames[idx] = nil
+	s.repl.Remove(idx)
 	return nil
 }
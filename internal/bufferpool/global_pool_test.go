@@ -1 +1,170 @@
 package bufferpool
+
+import (
+	"fmt"
+	"path/filepath"
+	"sync"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+func TestGlobalPool_Shards(t *testing.T) {
+	sm := storage.NewStorageManager()
+	require.Equal(t, 1, NewGlobalPool(sm, 100, nil).Stats().Shards)
+	require.Equal(t, maxShards, NewGlobalPool(sm, 1<<20, nil).Stats().Shards)
+
+	gp := NewGlobalPool(sm, 4*minShardFrames+3, nil)
+	st := gp.Stats()
+	require.Equal(t, 4, st.Shards)
+	require.Equal(t, 4*minShardFrames+3, st.Capacity)
+
+	// Pages of one relation spread over every shard.
+	seen := make(map[int]bool)
+	for i := range uint32(4) {
+		seen[gp.shardIndex(PageTag{FSKey: "rel", PageID: i})] = true
+	}
+	require.Len(t, seen, 4)
+}
+
+func TestGlobalPool_ConcurrentPages(t *testing.T) {
+	sm := storage.NewStorageManager()
+	fs := storage.LocalFileSet{Dir: t.TempDir(), Base: "rel"}
+	gp := NewGlobalPool(sm, 4*minShardFrames, nil)
+
+	// Each goroutine owns its pages and cycles through more of them than fit
+	// in its shards, so loads, evictions and write-backs all run concurrently.
+	const workers, pages = 8, 64
+	var wg sync.WaitGroup
+	errs := make(chan error, workers)
+	for w := range uint32(workers) {
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			for round := range 3 {
+				for i := range uint32(pages) {
+					id := w*pages + i
+					p, err := gp.GetPage(fs, id)
+					if err != nil {
+						errs <- err
+						return
+					}
+					_, err = p.InsertTuple([]byte(fmt.Sprintf("%d-%d", id, round)))
+					if uerr := gp.Unpin(fs, p, err == nil); err == nil {
+						err = uerr
+					}
+					if err != nil {
+						errs <- err
+						return
+					}
+				}
+			}
+		}()
+	}
+	wg.Wait()
+	close(errs)
+	for err := range errs {
+		require.NoError(t, err)
+	}
+	require.NoError(t, gp.FlushAll())
+
+	fresh := NewGlobalPool(sm, 16, nil)
+	for id := range uint32(workers * pages) {
+		p, err := fresh.GetPage(fs, id)
+		require.NoError(t, err)
+		got, err := p.ReadTuple(2)
+		require.NoError(t, err)
+		require.Equal(t, fmt.Sprintf("%d-2", id), string(got))
+		require.NoError(t, fresh.Unpin(fs, p, false))
+	}
+}
+
+func TestGlobalPool_DropPage(t *testing.T) {
+	sm := storage.NewStorageManager()
+	fs := storage.LocalFileSet{Dir: t.TempDir(), Base: "rel"}
+	gp := NewGlobalPool(sm, 8, nil)
+
+	p, err := gp.GetPage(fs, 0)
+	require.NoError(t, err)
+	require.ErrorIs(t, gp.DropPage(fs, 0), ErrPagePinned)
+	require.NoError(t, gp.Unpin(fs, p, false))
+
+	// Rewritten behind the pool's back: dropped, the page is read again.
+	fresh, err := storage.NewPage(make([]byte, storage.PageSize), 0)
+	require.NoError(t, err)
+	_, err = fresh.InsertTuple([]byte("new"))
+	require.NoError(t, err)
+	require.NoError(t, sm.SavePage(fs, 0, *fresh))
+	require.NoError(t, gp.DropPage(fs, 0))
+	require.False(t, gp.Contains(fs, 0))
+	require.NoError(t, gp.DropPage(fs, 0))
+
+	p, err = gp.GetPage(fs, 0)
+	require.NoError(t, err)
+	require.Equal(t, 1, p.NumSlots())
+	require.NoError(t, gp.Unpin(fs, p, false))
+}
+
+func TestWritePages_FlushesWALBeforeDataFile(t *testing.T) {
+	dir := t.TempDir()
+	sm := storage.NewStorageManager()
+	fs := storage.LocalFileSet{Dir: dir, Base: "rel"}
+	w, err := wal.Open(filepath.Join(dir, "wal"))
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = w.Close() })
+	w.SetSyncMode(wal.SyncNormal)
+	gp := NewGlobalPool(sm, 16, w)
+	page, err := storage.NewPage(make([]byte, storage.PageSize), 0)
+	require.NoError(t, err)
+
+	// Not resident: written straight to the file, so the WAL is synced first.
+	synced := w.SyncCount()
+	require.NoError(t, gp.WritePages(fs, 0, [][]byte{page.Buf}))
+	require.Equal(t, synced+1, w.SyncCount())
+
+	// Resident: only installed, write-back syncs the WAL when it happens.
+	p, err := gp.GetPage(fs, 0)
+	require.NoError(t, err)
+	require.NoError(t, gp.Unpin(fs, p, false))
+	require.NoError(t, gp.WritePages(fs, 0, [][]byte{page.Buf}))
+	require.Equal(t, synced+1, w.SyncCount())
+}
+
+// BenchmarkGlobalPool_GetPageHit measures GetPage and Unpin of pages that
+// are all cached, from one goroutine and from GOMAXPROCS of them.
+func BenchmarkGlobalPool_GetPageHit(b *testing.B) {
+	sm := storage.NewStorageManager()
+	fs := storage.LocalFileSet{Dir: b.TempDir(), Base: "rel"}
+	gp := NewGlobalPool(sm, DefaultCapacity, nil)
+	const pages = 64
+	for i := range uint32(pages) {
+		p, err := gp.GetPage(fs, i)
+		require.NoError(b, err)
+		require.NoError(b, gp.Unpin(fs, p, false))
+	}
+
+	b.Run("serial", func(b *testing.B) {
+		for i := range b.N {
+			p, err := gp.GetPage(fs, uint32(i%pages))
+			if err != nil {
+				b.Fatal(err)
+			}
+			_ = gp.Unpin(fs, p, false)
+		}
+	})
+	b.Run("parallel", func(b *testing.B) {
+		b.RunParallel(func(pb *testing.PB) {
+			for i := uint32(0); pb.Next(); i++ {
+				p, err := gp.GetPage(fs, i%pages)
+				if err != nil {
+					b.Error(err)
+					return
+				}
+				_ = gp.Unpin(fs, p, false)
+			}
+		})
+	})
+}
@@ -0,0 +1,127 @@
+package bufferpool
+
+import (
+	"slices"
+
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+// TxWrite is one page of a transaction commit.
+type TxWrite struct {
+	FS       storage.FileSet
+	PageID   uint32
+	Data     []byte // full page (trailer ignored); nil only checks the version
+	Expected uint64 // page version the transaction based its write on
+}
+
+// CommitTx applies writes all-or-nothing. Every page must still be at its
+// Expected version, otherwise *VersionConflictError is returned and nothing
+// changes. The new images are logged as one WAL group and installed under
+// the shard locks, which are released before waiting for the fsync so that
+// commits queued behind them share it. A crash either replays all of the
+// images or none. If the fsync fails the images stay installed, but they are
+// not written back before the log is: writeBack flushes the WAL to f.LSN.
+//
+// Entries with nil Data are read-set checks: they must match Expected but are
+// not written. Each page may appear at most once in writes.
+func (g *GlobalPool) CommitTx(writes []TxWrite) error {
+	tags := make([]PageTag, len(writes))
+	lfss := make([]storage.LocalFileSet, len(writes))
+	for i, w := range writes {
+		if w.Data != nil && len(w.Data) != storage.PageSize {
+			return storage.ErrWrongSize
+		}
+		key, lfs, ok := storage.FsKeyOf(w.FS)
+		if !ok {
+			return ErrUnsupportedFileSet
+		}
+		tags[i] = PageTag{FSKey: key, PageID: w.PageID}
+		lfss[i] = lfs
+	}
+
+	lsn, err := g.commitTx(writes, tags, lfss)
+	if err != nil {
+		return err
+	}
+	if lsn != 0 {
+		if err := g.wal.WaitCommit(lsn); err != nil {
+			return err
+		}
+	}
+	return g.maybeCheckpoint()
+}
+
+// commitTx is CommitTx with the shards of all pages locked, in shard order.
+// It returns the LSN of the logged group, 0 if nothing was logged.
+func (g *GlobalPool) commitTx(writes []TxWrite, tags []PageTag, lfss []storage.LocalFileSet) (uint64, error) {
+	shards := make([]int, len(tags))
+	for i, tag := range tags {
+		shards[i] = g.shardIndex(tag)
+	}
+	locked := slices.Compact(slices.Sorted(slices.Values(shards)))
+	for _, i := range locked {
+		g.shards[i].mu.Lock()
+	}
+	defer func() {
+		for _, i := range locked {
+			g.shards[i].mu.Unlock()
+		}
+	}()
+
+	frames := make([]*Frame, 0, len(writes))
+	defer func() {
+		for i := range frames {
+			_ = g.unpinLocked(g.shards[shards[i]], tags[i], false)
+		}
+	}()
+
+	images := make([]wal.PageImage, 0, len(writes))
+	written := make([]*Frame, 0, len(writes))
+	for i, w := range writes {
+		f, err := g.pinLocked(g.shards[shards[i]], tags[i], lfss[i])
+		if err != nil {
+			return 0, err
+		}
+		frames = append(frames, f)
+
+		if !f.Page.HasVersion() {
+			return 0, ErrPageUnversioned
+		}
+		if actual := f.Page.Version(); actual != w.Expected {
+			return 0, &VersionConflictError{Expected: w.Expected, Actual: actual}
+		}
+
+		if w.Data == nil {
+			continue
+		}
+
+		img := &storage.Page{Buf: append([]byte(nil), f.Page.Buf...)}
+		if err := img.SetContents(w.Data); err != nil {
+			return 0, err
+		}
+		img.BumpVersion()
+		img.SealChecksum()
+		images = append(images, wal.PageImage{Dir: f.FS.Dir, Base: f.FS.Base, PageID: w.PageID, Page: img.Buf})
+		written = append(written, f)
+	}
+	if len(images) == 0 {
+		return 0, nil
+	}
+
+	var lsn uint64
+	if g.wal != nil {
+		var err error
+		if lsn, err = g.wal.LogTx(images); err != nil {
+			return 0, err
+		}
+	}
+
+	// Logged: install the images. The deferred unpins keep Dirty set.
+	for i, f := range written {
+		copy(f.Page.Buf, images[i].Page)
+		f.Dirty = true
+		f.LSN = lsn
+	}
+	return lsn, nil
+}
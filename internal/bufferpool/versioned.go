@@ -0,0 +1,88 @@
+package bufferpool
+
+import (
+	"errors"
+	"fmt"
+
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+// ErrPageUnversioned is returned by WriteIfVersion for pages formatted before
+// page versions existed; they cannot take part in compare-and-swap updates.
+var ErrPageUnversioned = errors.New("bufferpool: page has no version counter")
+
+// VersionConflictError reports a WriteIfVersion whose expected version no
+// longer matches the page.
+type VersionConflictError struct {
+	Expected uint64
+	Actual   uint64
+}
+
+func (e *VersionConflictError) Error() string {
+	return fmt.Sprintf("bufferpool: page version conflict (expected %d, actual %d)", e.Expected, e.Actual)
+}
+
+// ReadVersioned returns a copy of page (fs,pageID) together with its version.
+// Both are read under the lock of its shard, so they always belong together.
+func (g *GlobalPool) ReadVersioned(fs storage.FileSet, pageID uint32) ([]byte, uint64, error) {
+	key, lfs, ok := storage.FsKeyOf(fs)
+	if !ok {
+		return nil, 0, ErrUnsupportedFileSet
+	}
+	tag := PageTag{FSKey: key, PageID: pageID}
+
+	s := g.shardOf(tag)
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	f, err := g.pinLocked(s, tag, lfs)
+	if err != nil {
+		return nil, 0, err
+	}
+	buf := append([]byte(nil), f.Page.Buf...)
+	version := f.Page.Version()
+	return buf, version, g.unpinLocked(s, tag, false)
+}
+
+// WriteIfVersion replaces the contents of (fs,pageID) with data only if the
+// page version still equals expected, and returns the new version.
+//
+// The check and the write happen under the lock of its shard. The trailer of data
+// (version + LSN) is ignored: the pool owns it and bumps the version exactly
+// like any other dirty unpin, including the WAL page image.
+func (g *GlobalPool) WriteIfVersion(fs storage.FileSet, pageID uint32, data []byte, expected uint64) (uint64, error) {
+	if len(data) != storage.PageSize {
+		return 0, storage.ErrWrongSize
+	}
+	key, lfs, ok := storage.FsKeyOf(fs)
+	if !ok {
+		return 0, ErrUnsupportedFileSet
+	}
+	tag := PageTag{FSKey: key, PageID: pageID}
+
+	s := g.shardOf(tag)
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	f, err := g.pinLocked(s, tag, lfs)
+	if err != nil {
+		return 0, err
+	}
+	if !f.Page.HasVersion() {
+		_ = g.unpinLocked(s, tag, false)
+		return 0, ErrPageUnversioned
+	}
+	if actual := f.Page.Version(); actual != expected {
+		_ = g.unpinLocked(s, tag, false)
+		return 0, &VersionConflictError{Expected: expected, Actual: actual}
+	}
+
+	if err := f.Page.SetContents(data); err != nil {
+		_ = g.unpinLocked(s, tag, false)
+		return 0, err
+	}
+	if err := g.unpinLocked(s, tag, true); err != nil {
+		return 0, err
+	}
+	return f.Page.Version(), nil
+}
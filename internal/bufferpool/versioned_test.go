@@ -0,0 +1,155 @@
+package bufferpool
+
+import (
+	"errors"
+	"path/filepath"
+	"sync"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
+	"github.com/tuannm99/novasql/pkg/bx"
+)
+
+// casIncrement reads the u64 counter stored in tuple 0 and writes it back +1
+// with WriteIfVersion, retrying on conflicts.
+func casIncrement(gp *GlobalPool, fs storage.FileSet) error {
+	for {
+		buf, v, err := gp.ReadVersioned(fs, 0)
+		if err != nil {
+			return err
+		}
+		p := &storage.Page{Buf: buf}
+		raw, err := p.ReadTuple(0)
+		if err != nil {
+			return err
+		}
+		next := make([]byte, 8)
+		bx.PutU64(next, bx.U64(raw)+1)
+		if err := p.UpdateTuple(0, next); err != nil {
+			return err
+		}
+
+		_, err = gp.WriteIfVersion(fs, 0, buf, v)
+		var conflict *VersionConflictError
+		if errors.As(err, &conflict) {
+			continue
+		}
+		return err
+	}
+}
+
+func TestWriteIfVersion_Conflict(t *testing.T) {
+	sm := storage.NewStorageManager()
+	fs := storage.LocalFileSet{Dir: t.TempDir(), Base: "rel"}
+	gp := NewGlobalPool(sm, 8, nil)
+
+	buf, v, err := gp.ReadVersioned(fs, 0)
+	require.NoError(t, err)
+	require.Equal(t, uint64(0), v)
+
+	nv, err := gp.WriteIfVersion(fs, 0, buf, v)
+	require.NoError(t, err)
+	require.Equal(t, uint64(1), nv)
+
+	// Stale writer loses.
+	_, err = gp.WriteIfVersion(fs, 0, buf, v)
+	var conflict *VersionConflictError
+	require.ErrorAs(t, err, &conflict)
+	require.Equal(t, uint64(0), conflict.Expected)
+	require.Equal(t, uint64(1), conflict.Actual)
+
+	// Ordinary dirty unpins bump the version too.
+	p, err := gp.GetPage(fs, 0)
+	require.NoError(t, err)
+	require.NoError(t, gp.Unpin(fs, p, true))
+	_, v, err = gp.ReadVersioned(fs, 0)
+	require.NoError(t, err)
+	require.Equal(t, uint64(2), v)
+
+	_, err = gp.WriteIfVersion(fs, 0, buf[:10], v)
+	require.ErrorIs(t, err, storage.ErrWrongSize)
+}
+
+func TestWriteIfVersion_ConcurrentCASNoLostUpdates(t *testing.T) {
+	sm := storage.NewStorageManager()
+	fs := storage.LocalFileSet{Dir: t.TempDir(), Base: "rel"}
+	gp := NewGlobalPool(sm, 8, nil)
+
+	p, err := gp.GetPage(fs, 0)
+	require.NoError(t, err)
+	_, err = p.InsertTuple(make([]byte, 8))
+	require.NoError(t, err)
+	require.NoError(t, gp.Unpin(fs, p, true))
+
+	const workers, perWorker = 2, 200
+
+	var wg sync.WaitGroup
+	errs := make(chan error, workers)
+	for range workers {
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			for range perWorker {
+				if err := casIncrement(gp, fs); err != nil {
+					errs <- err
+					return
+				}
+			}
+		}()
+	}
+	wg.Wait()
+	close(errs)
+	for err := range errs {
+		require.NoError(t, err)
+	}
+
+	buf, v, err := gp.ReadVersioned(fs, 0)
+	require.NoError(t, err)
+	raw, err := (&storage.Page{Buf: buf}).ReadTuple(0)
+	require.NoError(t, err)
+	require.Equal(t, uint64(workers*perWorker), bx.U64(raw))
+	// One bump for the seed insert plus one per successful CAS.
+	require.Equal(t, uint64(workers*perWorker+1), v)
+}
+
+func TestPageVersion_SurvivesReopenAndRecovery(t *testing.T) {
+	dir := t.TempDir()
+	sm := storage.NewStorageManager()
+	fs := storage.LocalFileSet{Dir: dir, Base: "rel"}
+
+	w, err := wal.Open(filepath.Join(dir, "wal"))
+	require.NoError(t, err)
+	gp := NewGlobalPool(sm, 8, w)
+
+	for range 3 {
+		p, err := gp.GetPage(fs, 0)
+		require.NoError(t, err)
+		require.NoError(t, gp.Unpin(fs, p, true))
+		p, err = gp.GetPage(fs, 1)
+		require.NoError(t, err)
+		require.NoError(t, gp.Unpin(fs, p, true))
+	}
+
+	// Flush both pages, then give page 1 one more write that only the WAL sees.
+	require.NoError(t, gp.FlushFileSet(fs))
+	p, err := gp.GetPage(fs, 1)
+	require.NoError(t, err)
+	require.NoError(t, gp.Unpin(fs, p, true))
+	require.NoError(t, w.Close())
+
+	w, err = wal.Open(filepath.Join(dir, "wal"))
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = w.Close() })
+	require.NoError(t, w.Recover(storage.NewWALWriter(sm)))
+
+	gp2 := NewGlobalPool(sm, 8, w)
+	_, v0, err := gp2.ReadVersioned(fs, 0)
+	require.NoError(t, err)
+	require.Equal(t, uint64(3), v0)
+	_, v1, err := gp2.ReadVersioned(fs, 1)
+	require.NoError(t, err)
+	require.Equal(t, uint64(4), v1)
+}
@@ -22,6 +22,11 @@ func (v *FileSetView) FlushAll() error {
 	return v.gp.FlushFileSet(v.fs)
 }
 
+// WritePageList is GlobalPool.WritePageList for THIS FileSet.
+func (v *FileSetView) WritePageList(ids []uint32, pages [][]byte) error {
+	return v.gp.WritePageList(v.fs, ids, pages)
+}
+
 // View returns a relation-scoped Manager backed by the shared GlobalPool.
 func (gp *GlobalPool) View(fs storage.FileSet) Manager {
 	return &FileSetView{gp: gp, fs: fs}
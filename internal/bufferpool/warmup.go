@@ -0,0 +1,291 @@
+package bufferpool
+
+import (
+	"encoding/json"
+	"errors"
+	"os"
+	"path/filepath"
+	"sort"
+	"sync"
+	"sync/atomic"
+	"time"
+
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+const (
+	manifestVersion = 1
+
+	// DefaultWarmupRate is the prefetch budget (pages/second) used when the
+	// caller does not pick one.
+	DefaultWarmupRate = 2000
+)
+
+// ErrPoolBusy is returned by Prefetch when the lock of the page's shard is
+// held by someone else. Prefetching never competes with real work for it.
+var ErrPoolBusy = errors.New("bufferpool: pool busy")
+
+// ManifestEntry identifies one cached page in a warm-up manifest.
+type ManifestEntry struct {
+	Dir    string `json:"dir"`
+	Base   string `json:"base"`
+	PageID uint32 `json:"page_id"`
+}
+
+type manifestFile struct {
+	Version int             `json:"version"`
+	Pages   []ManifestEntry `json:"pages"`
+}
+
+// WarmupStats reports progress of a background cache warm-up.
+type WarmupStats struct {
+	Active  bool // warm-up goroutine is running
+	Done    bool // last warm-up finished or was cancelled
+	Total   int  // pages listed in the manifest
+	Loaded  int  // pages made resident
+	Skipped int  // stale/out-of-range pages, or pages left out because the pool filled up
+}
+
+type warmupState struct {
+	active  atomic.Bool
+	done    atomic.Bool
+	total   atomic.Int64
+	loaded  atomic.Int64
+	skipped atomic.Int64
+}
+
+func (w *warmupState) snapshot() WarmupStats {
+	return WarmupStats{
+		Active:  w.active.Load(),
+		Done:    w.done.Load(),
+		Total:   int(w.total.Load()),
+		Loaded:  int(w.loaded.Load()),
+		Skipped: int(w.skipped.Load()),
+	}
+}
+
+// Manifest returns the resident pages, most recently used first.
+func (g *GlobalPool) Manifest() []ManifestEntry {
+	g.lockAll()
+	defer g.unlockAll()
+
+	var frames []*Frame
+	for _, s := range g.shards {
+		for _, f := range s.frames {
+			if f != nil {
+				frames = append(frames, f)
+			}
+		}
+	}
+	sort.Slice(frames, func(i, j int) bool { return frames[i].LastUsed > frames[j].LastUsed })
+
+	out := make([]ManifestEntry, 0, len(frames))
+	for _, f := range frames {
+		out = append(out, ManifestEntry{Dir: f.FS.Dir, Base: f.FS.Base, PageID: f.Tag.PageID})
+	}
+	return out
+}
+
+// SaveManifest writes the current resident set to path (atomically).
+func (g *GlobalPool) SaveManifest(path string) error {
+	data, err := json.Marshal(manifestFile{Version: manifestVersion, Pages: g.Manifest()})
+	if err != nil {
+		return err
+	}
+	if err := os.MkdirAll(filepath.Dir(path), 0o755); err != nil {
+		return err
+	}
+	tmp := path + ".tmp"
+	if err := os.WriteFile(tmp, data, 0o644); err != nil {
+		return err
+	}
+	return os.Rename(tmp, path)
+}
+
+// LoadManifest reads a manifest written by SaveManifest.
+// A missing file is not an error and yields no entries.
+func LoadManifest(path string) ([]ManifestEntry, error) {
+	data, err := os.ReadFile(path)
+	if err != nil {
+		if errors.Is(err, os.ErrNotExist) {
+			return nil, nil
+		}
+		return nil, err
+	}
+	var m manifestFile
+	if err := json.Unmarshal(data, &m); err != nil {
+		return nil, err
+	}
+	return m.Pages, nil
+}
+
+// Prefetch loads (fs,pageID) into a free frame without pinning it.
+//
+// It is the lowest-priority way to fill the pool: it never evicts
+// (ErrNoFreeFrame when the page's shard is full), never waits for a lock
+// (ErrPoolBusy) and is a no-op for pages that are already resident. The
+// page is read with no lock held, so lookups in its shard do not wait for
+// the disk. The copy read is dropped when the page was loaded meanwhile or
+// pages left the shard, as it may be stale then.
+func (g *GlobalPool) Prefetch(fs storage.FileSet, pageID uint32) (bool, error) {
+	key, lfs, ok := storage.FsKeyOf(fs)
+	if !ok {
+		return false, ErrUnsupportedFileSet
+	}
+	tag := PageTag{FSKey: key, PageID: pageID}
+
+	s := g.shardOf(tag)
+	if !s.mu.TryLock() {
+		return false, ErrPoolBusy
+	}
+	_, resident := s.table[tag]
+	full := s.freeFrameLocked() == -1
+	removed := s.removed
+	s.mu.Unlock()
+	if resident {
+		return false, nil
+	}
+	if full {
+		return false, ErrNoFreeFrame
+	}
+
+	page, err := g.sm.LoadPage(lfs, pageID)
+	if err != nil {
+		return false, err
+	}
+	g.read(1)
+
+	if !s.mu.TryLock() {
+		return false, ErrPoolBusy
+	}
+	defer s.mu.Unlock()
+	if _, ok := s.table[tag]; ok || s.removed != removed {
+		return false, nil
+	}
+	freeIdx := s.freeFrameLocked()
+	if freeIdx == -1 {
+		return false, ErrNoFreeFrame
+	}
+
+	s.frames[freeIdx] = &Frame{
+		Tag:      tag,
+		FS:       lfs,
+		Page:     page,
+		LastUsed: g.nextTick(),
+	}
+	s.table[tag] = freeIdx
+	s.repl.RecordAccess(freeIdx)
+	s.repl.SetEvictable(freeIdx, true)
+	return true, nil
+}
+
+// StartWarmup prefetches entries in the background at no more than rate
+// pages/second (DefaultWarmupRate when rate <= 0). Entries that no longer
+// exist on disk are skipped silently, and the warm-up stops as soon as the
+// pool has no free frame left.
+//
+// The returned stop function cancels the warm-up and waits for the goroutine
+// to exit. It is safe to call more than once.
+func (g *GlobalPool) StartWarmup(entries []ManifestEntry, rate int) (stop func()) {
+	if rate <= 0 {
+		rate = DefaultWarmupRate
+	}
+	interval := max(time.Second/time.Duration(rate), time.Microsecond)
+
+	g.warm.total.Store(int64(len(entries)))
+	g.warm.loaded.Store(0)
+	g.warm.skipped.Store(0)
+	g.warm.done.Store(false)
+	g.warm.active.Store(true)
+
+	quit := make(chan struct{})
+	exited := make(chan struct{})
+	go func() {
+		defer close(exited)
+		defer func() {
+			g.warm.active.Store(false)
+			g.warm.done.Store(true)
+		}()
+		g.runWarmup(entries, interval, quit)
+	}()
+
+	var once sync.Once
+	return func() {
+		once.Do(func() { close(quit) })
+		<-exited
+	}
+}
+
+func (g *GlobalPool) runWarmup(entries []ManifestEntry, interval time.Duration, quit <-chan struct{}) {
+	ticker := time.NewTicker(interval)
+	defer ticker.Stop()
+
+	// wait blocks until the next tick; false means the warm-up was cancelled.
+	wait := func() bool {
+		select {
+		case <-quit:
+			return false
+		case <-ticker.C:
+			return true
+		}
+	}
+
+	for i, e := range entries {
+		if !wait() {
+			g.warm.skipped.Add(int64(len(entries) - i))
+			return
+		}
+		if !pageOnDisk(e) {
+			g.warm.skipped.Add(1)
+			continue
+		}
+
+		fs := storage.LocalFileSet{Dir: e.Dir, Base: e.Base}
+		_, err := g.Prefetch(fs, e.PageID)
+		for errors.Is(err, ErrPoolBusy) {
+			if !wait() {
+				g.warm.skipped.Add(int64(len(entries) - i))
+				return
+			}
+			_, err = g.Prefetch(fs, e.PageID)
+		}
+
+		switch {
+		case errors.Is(err, ErrNoFreeFrame) && g.full():
+			// The pool is full of pages somebody actually asked for.
+			g.warm.skipped.Add(int64(len(entries) - i))
+			return
+		case err != nil:
+			g.warm.skipped.Add(1)
+		default:
+			g.warm.loaded.Add(1)
+		}
+	}
+}
+
+// full reports whether no shard has a free frame left.
+func (g *GlobalPool) full() bool {
+	for _, s := range g.shards {
+		s.mu.Lock()
+		free := s.freeFrameLocked()
+		s.mu.Unlock()
+		if free != -1 {
+			return false
+		}
+	}
+	return true
+}
+
+// pageOnDisk reports whether e still points inside an existing segment file.
+// It avoids FileSet.OpenSegment on purpose: that creates missing files and
+// directories, which is wrong for relations dropped since the manifest was written.
+func pageOnDisk(e ManifestEntry) bool {
+	segNo := int32(e.PageID / storage.MaxPagePerSegment)
+	inSeg := int64(e.PageID % storage.MaxPagePerSegment)
+
+	st, err := os.Stat(filepath.Join(e.Dir, storage.SegFileName(e.Base, segNo)))
+	if err != nil || st.IsDir() {
+		return false
+	}
+	return st.Size() >= (inSeg+1)*storage.PageSize
+}
@@ -0,0 +1,195 @@
+package bufferpool
+
+import (
+	"fmt"
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+// fillPages writes n pages (one tuple each) through gp and flushes them.
+func fillPages(t *testing.T, gp *GlobalPool, fs storage.LocalFileSet, n uint32) {
+	t.Helper()
+
+	for i := range n {
+		p, err := gp.GetPage(fs, i)
+		require.NoError(t, err)
+		_, err = p.InsertTuple([]byte(fmt.Sprintf("page-%d", i)))
+		require.NoError(t, err)
+		require.NoError(t, gp.Unpin(fs, p, true))
+	}
+	require.NoError(t, gp.FlushAll())
+}
+
+func waitWarmup(t *testing.T, gp *GlobalPool) {
+	t.Helper()
+	require.Eventually(t, func() bool {
+		return gp.Stats().Warmup.Done
+	}, 5*time.Second, 5*time.Millisecond)
+}
+
+func TestWarmup_RestoresHotPages(t *testing.T) {
+	dir := t.TempDir()
+	sm := storage.NewStorageManager()
+	fs := storage.LocalFileSet{Dir: dir, Base: "rel"}
+
+	gp := NewGlobalPool(sm, 16, nil)
+	fillPages(t, gp, fs, 6)
+
+	// Touch page 4 last so it heads the manifest.
+	p, err := gp.GetPage(fs, 4)
+	require.NoError(t, err)
+	require.NoError(t, gp.Unpin(fs, p, false))
+
+	path := filepath.Join(dir, "bp.manifest.json")
+	require.NoError(t, gp.SaveManifest(path))
+
+	entries, err := LoadManifest(path)
+	require.NoError(t, err)
+	require.Len(t, entries, 6)
+	require.Equal(t, uint32(4), entries[0].PageID)
+
+	// "Restart": a cold pool warmed from the manifest.
+	gp2 := NewGlobalPool(sm, 16, nil)
+	stop := gp2.StartWarmup(entries, 0)
+	t.Cleanup(stop)
+	waitWarmup(t, gp2)
+
+	st := gp2.Stats()
+	require.Equal(t, 6, st.Warmup.Loaded)
+	require.Equal(t, 0, st.Warmup.Skipped)
+	require.Equal(t, uint64(6), st.Reads)
+	require.Equal(t, uint64(0), st.Misses)
+	for i := range uint32(6) {
+		require.True(t, gp2.Contains(fs, i), "page %d should be resident", i)
+	}
+
+	// First application access is served from memory.
+	p, err = gp2.GetPage(fs, 4)
+	require.NoError(t, err)
+	require.NoError(t, gp2.Unpin(fs, p, false))
+
+	st = gp2.Stats()
+	require.Equal(t, uint64(1), st.Hits)
+	require.Equal(t, uint64(6), st.Reads)
+}
+
+func TestWarmup_StaleManifestIsSkipped(t *testing.T) {
+	dir := t.TempDir()
+	sm := storage.NewStorageManager()
+	fs := storage.LocalFileSet{Dir: dir, Base: "rel"}
+	fillPages(t, NewGlobalPool(sm, 16, nil), fs, 2)
+
+	gone := filepath.Join(dir, "dropped")
+	entries := []ManifestEntry{
+		{Dir: dir, Base: "rel", PageID: 1},
+		{Dir: dir, Base: "rel", PageID: 99}, // past the end of the relation
+		{Dir: gone, Base: "old", PageID: 0}, // relation no longer exists
+	}
+
+	gp := NewGlobalPool(sm, 16, nil)
+	stop := gp.StartWarmup(entries, 0)
+	t.Cleanup(stop)
+	waitWarmup(t, gp)
+
+	st := gp.Stats()
+	require.Equal(t, 1, st.Warmup.Loaded)
+	require.Equal(t, 2, st.Warmup.Skipped)
+	require.True(t, gp.Contains(fs, 1))
+	require.False(t, gp.Contains(fs, 99))
+
+	// Warm-up must not recreate dropped relations.
+	_, err := os.Stat(gone)
+	require.ErrorIs(t, err, os.ErrNotExist)
+}
+
+func TestWarmup_StopsWhenPoolIsFull(t *testing.T) {
+	dir := t.TempDir()
+	sm := storage.NewStorageManager()
+	fs := storage.LocalFileSet{Dir: dir, Base: "rel"}
+	fillPages(t, NewGlobalPool(sm, 16, nil), fs, 8)
+
+	var entries []ManifestEntry
+	for i := range uint32(8) {
+		entries = append(entries, ManifestEntry{Dir: dir, Base: "rel", PageID: i})
+	}
+
+	gp := NewGlobalPool(sm, 3, nil)
+	stop := gp.StartWarmup(entries, 0)
+	t.Cleanup(stop)
+	waitWarmup(t, gp)
+
+	st := gp.Stats()
+	require.Equal(t, 3, st.Resident)
+	require.Equal(t, 3, st.Warmup.Loaded)
+	require.Equal(t, 5, st.Warmup.Skipped)
+
+	// Prefetched pages are evictable: normal traffic still gets frames.
+	p, err := gp.GetPage(fs, 7)
+	require.NoError(t, err)
+	require.NoError(t, gp.Unpin(fs, p, false))
+}
+
+func TestWarmup_StopCancels(t *testing.T) {
+	dir := t.TempDir()
+	sm := storage.NewStorageManager()
+	fs := storage.LocalFileSet{Dir: dir, Base: "rel"}
+	fillPages(t, NewGlobalPool(sm, 16, nil), fs, 4)
+
+	var entries []ManifestEntry
+	for i := range uint32(4) {
+		entries = append(entries, ManifestEntry{Dir: dir, Base: "rel", PageID: i})
+	}
+
+	gp := NewGlobalPool(sm, 16, nil)
+	stop := gp.StartWarmup(entries, 1) // one page per second
+	stop()
+	stop() // idempotent
+
+	st := gp.Stats().Warmup
+	require.False(t, st.Active)
+	require.True(t, st.Done)
+	require.Equal(t, 4, st.Loaded+st.Skipped)
+}
+
+func TestPrefetch(t *testing.T) {
+	dir := t.TempDir()
+	sm := storage.NewStorageManager()
+	fs := storage.LocalFileSet{Dir: dir, Base: "rel"}
+	fillPages(t, NewGlobalPool(sm, 16, nil), fs, 3)
+
+	gp := NewGlobalPool(sm, 2, nil)
+	loaded, err := gp.Prefetch(fs, 0)
+	require.NoError(t, err)
+	require.True(t, loaded)
+	loaded, err = gp.Prefetch(fs, 0)
+	require.NoError(t, err)
+	require.False(t, loaded)
+
+	// A busy shard is left alone.
+	key, _, _ := storage.FsKeyOf(fs)
+	s := gp.shardOf(PageTag{FSKey: key, PageID: 1})
+	s.mu.Lock()
+	_, err = gp.Prefetch(fs, 1)
+	s.mu.Unlock()
+	require.ErrorIs(t, err, ErrPoolBusy)
+
+	// Nothing is evicted for it.
+	loaded, err = gp.Prefetch(fs, 1)
+	require.NoError(t, err)
+	require.True(t, loaded)
+	_, err = gp.Prefetch(fs, 2)
+	require.ErrorIs(t, err, ErrNoFreeFrame)
+	require.True(t, gp.Contains(fs, 0))
+}
+
+func TestLoadManifest_Missing(t *testing.T) {
+	entries, err := LoadManifest(filepath.Join(t.TempDir(), "nope.json"))
+	require.NoError(t, err)
+	require.Empty(t, entries)
+}
@@ -0,0 +1,142 @@
+package bufferpool
+
+import (
+	"errors"
+	"log/slog"
+	"sync"
+	"time"
+
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+// Background writer
+//
+// Without it, dirty pages reach disk when they are evicted, flushed or
+// checkpointed, all on the goroutine that happens to need the frame: once
+// the pool is full of dirty pages, every miss writes one back first. The
+// background writer keeps evictions cheap by writing dirty pages nobody has
+// pinned ahead of time, and takes the periodic checkpoints so the WAL is
+// truncated without a query paying for it.
+
+// WriterDelay is how long a background writer sleeps between rounds.
+const WriterDelay = 200 * time.Millisecond
+
+// writerBatch bounds the pages a writer writes per hold of a shard lock, so
+// lookups of that shard wait for a few writes at most.
+const writerBatch = 16
+
+// StartWriter starts workers goroutines (at most one per shard) that write
+// the dirty, unpinned pages of the pool every WriterDelay; with checkpoint
+// > 0, the first also takes a checkpoint, synced like those of
+// SetAutoCheckpoint, every checkpoint as long as pages were logged since
+// the last one. Errors are logged and the next round retries.
+//
+// The returned stop function tells the writers to finish their round and
+// waits for them to exit. It is safe to call more than once.
+func (g *GlobalPool) StartWriter(workers int, checkpoint time.Duration) (stop func()) {
+	workers = min(workers, len(g.shards))
+	if workers <= 0 {
+		return func() {}
+	}
+	quit := make(chan struct{})
+	var wg sync.WaitGroup
+	for w := range workers {
+		var shards []*shard
+		for i := w; i < len(g.shards); i += workers {
+			shards = append(shards, g.shards[i])
+		}
+		var every time.Duration
+		if w == 0 {
+			every = checkpoint
+		}
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			g.runWriter(shards, every, quit)
+		}()
+	}
+
+	var once sync.Once
+	return func() {
+		once.Do(func() { close(quit) })
+		wg.Wait()
+	}
+}
+
+func (g *GlobalPool) runWriter(shards []*shard, checkpoint time.Duration, quit <-chan struct{}) {
+	ticker := time.NewTicker(WriterDelay)
+	defer ticker.Stop()
+	last := time.Now()
+	var walSize int64 // after our last checkpoint
+	for {
+		select {
+		case <-quit:
+			return
+		case <-ticker.C:
+		}
+		for _, s := range shards {
+			if err := g.cleanShard(s, quit); err != nil {
+				slog.Warn("bufferpool: background write failed", "err", err)
+				break
+			}
+		}
+		if checkpoint <= 0 || time.Since(last) < checkpoint {
+			continue
+		}
+		last = time.Now()
+		size, err := g.wal.Size()
+		if errors.Is(err, wal.ErrNoWALFile) || (err == nil && size <= walSize) {
+			continue
+		}
+		g.cpMu.Lock()
+		sync := g.checkpointSync
+		g.cpMu.Unlock()
+		if err == nil {
+			err = g.Checkpoint(sync)
+		}
+		if err == nil {
+			walSize, err = g.wal.Size()
+		}
+		if err != nil {
+			slog.Warn("bufferpool: background checkpoint failed", "err", err)
+		}
+	}
+}
+
+// cleanShard writes back the dirty, unpinned frames of s, writerBatch at a
+// time, until none is left or quit is closed.
+func (g *GlobalPool) cleanShard(s *shard, quit <-chan struct{}) error {
+	for {
+		select {
+		case <-quit:
+			return nil
+		default:
+		}
+		n, more, err := g.cleanBatch(s)
+		g.bgWrites.Add(uint64(n))
+		if err != nil || !more {
+			return err
+		}
+	}
+}
+
+// cleanBatch writes back up to writerBatch dirty, unpinned frames of s and
+// reports how many it wrote and whether some may be left.
+func (g *GlobalPool) cleanBatch(s *shard) (int, bool, error) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	n := 0
+	for _, f := range s.frames {
+		if f == nil || !f.Dirty || f.Pin != 0 {
+			continue
+		}
+		if n == writerBatch {
+			return n, true, nil
+		}
+		if err := g.writeBack(f); err != nil {
+			return n, false, err
+		}
+		n++
+	}
+	return n, false, nil
+}
@@ -0,0 +1,61 @@
+package bufferpool
+
+import (
+	"fmt"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+func TestWriter_WritesDirtyPagesAndCheckpoints(t *testing.T) {
+	dir := t.TempDir()
+	sm := storage.NewStorageManager()
+	fs := storage.LocalFileSet{Dir: dir, Base: "rel"}
+	w, err := wal.Open(filepath.Join(dir, "wal"))
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = w.Close() })
+	gp := NewGlobalPool(sm, 2*minShardFrames, w)
+	gp.SetAutoCheckpoint(0, nil)
+
+	const pages = 20
+	for i := range uint32(pages) {
+		p, err := gp.GetPage(fs, i)
+		require.NoError(t, err)
+		_, err = p.InsertTuple([]byte(fmt.Sprintf("page-%d", i)))
+		require.NoError(t, err)
+		require.NoError(t, gp.Unpin(fs, p, true))
+	}
+	// A pinned page is left alone until it is unpinned.
+	pinned, err := gp.GetPage(fs, 0)
+	require.NoError(t, err)
+
+	written := func(n uint64) func() bool {
+		return func() bool { return gp.Stats().BackgroundWrites == n }
+	}
+	stop := gp.StartWriter(8, 0) // more workers than shards
+	require.Eventually(t, written(pages-1), 5*time.Second, 10*time.Millisecond)
+	for i := uint32(1); i < pages; i++ {
+		page, err := sm.LoadPage(fs, i)
+		require.NoError(t, err)
+		tup, err := page.ReadTuple(0)
+		require.NoError(t, err)
+		require.Equal(t, fmt.Sprintf("page-%d", i), string(tup))
+	}
+	require.NoError(t, gp.Unpin(fs, pinned, false))
+	require.Eventually(t, written(pages), 5*time.Second, 10*time.Millisecond)
+	require.Zero(t, gp.Stats().Checkpoints)
+	stop()
+	stop()
+
+	// Checkpoints are taken once the WAL has grown since the last one.
+	stop = gp.StartWriter(1, time.Millisecond)
+	t.Cleanup(stop)
+	require.Eventually(t, func() bool { return gp.Stats().Checkpoints == 1 }, 5*time.Second, 10*time.Millisecond)
+	time.Sleep(3 * WriterDelay)
+	require.Equal(t, uint64(1), gp.Stats().Checkpoints)
+}
@@ -1,9 +1,17 @@
 package internal
 
 import (
+	"errors"
 	"fmt"
+	"reflect"
+	"strings"
+	"time"
 
 	"github.com/spf13/viper"
+	"github.com/tuannm99/novasql/internal/bufferpool"
+	"github.com/tuannm99/novasql/internal/mvcc"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
 )
 
 type NovaSqlConfig struct {
@@ -13,27 +21,292 @@ type NovaSqlConfig struct {
 		Mode     string `mapstructure:"mode"`
 		Workdir  string `mapstructure:"workdir"`
 		PageSize int    `mapstructure:"page_size"`
+
+		// BufferPoolPages is the number of frames of the shared buffer pool
+		// (0 = bufferpool.DefaultCapacity).
+		BufferPoolPages int `mapstructure:"buffer_pool_pages"`
+
+		// SyncMode is "full" (default: commits wait for fsync, batched by
+		// group commit), "normal" (fsync only before data pages are written
+		// and at checkpoints) or "off" (never fsync the WAL).
+		SyncMode string `mapstructure:"sync_mode"`
+
+		// Compression is the page codec: "lz4", "none", or "zstd" (not built
+		// in). Unset keeps the codec recorded in the database header.
+		Compression string `mapstructure:"compression"`
+
+		// EncryptionKey (64 hex digits) or EncryptionKeyFile (a file holding
+		// the 32 key bytes or their hex digits) encrypts every page with
+		// AES-256-GCM. Only a new database can be encrypted, and an encrypted
+		// one opens only with its key.
+		EncryptionKey     string `mapstructure:"encryption_key"`
+		EncryptionKeyFile string `mapstructure:"encryption_key_file"`
+
+		// CheckpointWALBytes triggers an automatic checkpoint once the WAL
+		// reaches this size (16 MiB by default, negative = manual checkpoints
+		// only).
+		CheckpointWALBytes int64 `mapstructure:"checkpoint_wal_bytes"`
+
+		// BackgroundWriters is how many goroutines write dirty pages of the
+		// buffer pool ahead of evictions (0 = none: evictions write them).
+		// With at least one, CheckpointInterval also checkpoints in the
+		// background (0 = only by WAL size).
+		BackgroundWriters  int           `mapstructure:"background_writers"`
+		CheckpointInterval time.Duration `mapstructure:"checkpoint_interval"`
+
+		// DirectIO opens the data files with O_DIRECT (F_NOCACHE on macOS) so
+		// pages are cached by the buffer pool only, not the OS as well.
+		// Classic mode only; filesystems without support fail the open.
+		DirectIO bool `mapstructure:"direct_io"`
+
+		// WALArchiveDir keeps a copy of the WAL from every checkpoint, in a
+		// subdirectory per database, for point-in-time restore ("" = none).
+		WALArchiveDir string `mapstructure:"wal_archive_dir"`
+
+		// WALDir and TempDir move the WAL and the temporary (spill) files of
+		// new databases out of their directory, into a subdirectory per
+		// database ("" = inside it). The manifest of each database records
+		// where its WAL is.
+		WALDir  string `mapstructure:"wal_dir"`
+		TempDir string `mapstructure:"temp_dir"`
+
+		// SortMemBytes is how much one ORDER BY sorts in memory before it
+		// spills sorted runs to temporary files (64 MiB by default, negative
+		// = never spill).
+		SortMemBytes int64 `mapstructure:"sort_mem_bytes"`
+
+		// TTLSweepInterval is how often the server deletes the expired rows
+		// of tables with a TTL column (1m by default, negative = never).
+		TTLSweepInterval time.Duration `mapstructure:"ttl_sweep_interval"`
 	} `mapstructure:"storage"`
 
 	Server struct {
 		Port  int  `mapstructure:"port"`
 		Debug bool `mapstructure:"debug"`
+
+		// PgPort is where the PostgreSQL wire-protocol listener accepts psql
+		// and Postgres drivers (0 = disabled).
+		PgPort int `mapstructure:"pg_port"`
+
+		// MetricsPort serves the engine metrics in the Prometheus text format
+		// at http://127.0.0.1:<port>/metrics (0 = disabled).
+		MetricsPort int `mapstructure:"metrics_port"`
+
+		// ReplicationPort is where replicas connect to follow the default
+		// database (0 = disabled).
+		ReplicationPort int `mapstructure:"replication_port"`
+
+		// LogLevel is debug, info, warn or error ("" = info, or debug with
+		// Debug); LogFormat is text or json ("" = text). See NewLogger.
+		LogLevel  string `mapstructure:"log_level"`
+		LogFormat string `mapstructure:"log_format"`
 	} `mapstructure:"server"`
+
+	Cache struct {
+		// Warmup prefetches the pages that were resident at the last clean
+		// shutdown, in the background, right after the database is opened.
+		Warmup bool `mapstructure:"warmup"`
+
+		// WarmupRate caps warm-up reads in pages/second (0 = default).
+		WarmupRate int `mapstructure:"warmup_rate"`
+	} `mapstructure:"cache"`
+
+	Catalog struct {
+		// TrashRetention is how long dropped tables stay in the trash before
+		// they are purged automatically. 0 keeps them until purged explicitly;
+		// a negative value disables the trash (DROP TABLE purges immediately).
+		TrashRetention time.Duration `mapstructure:"trash_retention"`
+	} `mapstructure:"catalog"`
+
+	Transaction struct {
+		// LockWaitTimeout bounds how long a row lock request waits before
+		// failing with mvcc.ErrLockTimeout (0 = 5s).
+		LockWaitTimeout time.Duration `mapstructure:"lock_wait_timeout"`
+
+		// StatementTimeout cancels a statement still running after this
+		// long, failing it with novasql.ErrQueryCancelled (0 = no limit).
+		StatementTimeout time.Duration `mapstructure:"statement_timeout"`
+	} `mapstructure:"transaction"`
+}
+
+// Defaults of the settings whose zero value is not what an unset one
+// means; DefaultConfig and the code reading the settings use them.
+const (
+	DefaultPort               = 6543
+	DefaultWorkdir            = "./data"
+	DefaultCheckpointWALBytes = 16 << 20
+	DefaultSortMemBytes       = 64 << 20
+	DefaultTTLSweepInterval   = time.Minute
+	DefaultBackgroundWriters  = 1
+	DefaultCheckpointInterval = 5 * time.Minute
+)
+
+// DefaultConfig is the config of an empty file: every setting at the
+// default its comment documents.
+func DefaultConfig() *NovaSqlConfig {
+	var c NovaSqlConfig
+	c.AppName = "novasql"
+	c.Storage.Mode = "classic"
+	c.Storage.Workdir = DefaultWorkdir
+	c.Storage.PageSize = storage.PageSize
+	c.Storage.BufferPoolPages = bufferpool.DefaultCapacity
+	c.Storage.SyncMode = wal.SyncFull.String()
+	c.Storage.CheckpointWALBytes = DefaultCheckpointWALBytes
+	c.Storage.BackgroundWriters = DefaultBackgroundWriters
+	c.Storage.CheckpointInterval = DefaultCheckpointInterval
+	c.Storage.SortMemBytes = DefaultSortMemBytes
+	c.Storage.TTLSweepInterval = DefaultTTLSweepInterval
+	c.Server.Port = DefaultPort
+	c.Server.LogLevel = "info"
+	c.Server.LogFormat = "text"
+	c.Cache.WarmupRate = bufferpool.DefaultWarmupRate
+	c.Transaction.LockWaitTimeout = mvcc.DefaultLockTimeout
+	return &c
+}
+
+// ConfigError is a config that cannot be read, or a setting of it that
+// Validate rejects.
+type ConfigError struct {
+	Path string // the config file ("" for a config built in code)
+	Key  string // the setting, as in the file ("storage.page_size"); "" when the file is at fault
+	Err  error
+}
+
+func (e *ConfigError) Error() string {
+	var parts []string
+	for _, p := range []string{e.Path, e.Key} {
+		if p != "" {
+			parts = append(parts, p)
+		}
+	}
+	return strings.Join(append(parts, e.Err.Error()), ": ")
+}
+
+func (e *ConfigError) Unwrap() error { return e.Err }
+
+// Validate rejects settings no database or server can run with. A *ConfigError
+// names the first of them.
+func (c *NovaSqlConfig) Validate() error {
+	if err := c.validate(); err != nil {
+		return err
+	}
+	return nil
+}
+
+func (c *NovaSqlConfig) validate() *ConfigError {
+	bad := func(key, format string, args ...any) *ConfigError {
+		return &ConfigError{Key: key, Err: fmt.Errorf(format, args...)}
+	}
+	st := &c.Storage
+	mode, err := storage.GetStorageMode(st.Mode)
+	if err != nil {
+		return &ConfigError{Key: "storage.mode", Err: err}
+	}
+	if st.Workdir == "" && mode != storage.Memory {
+		return bad("storage.workdir", "must be set")
+	}
+	if st.PageSize < 512 || st.PageSize&(st.PageSize-1) != 0 {
+		return bad("storage.page_size", "%d is not a power of two of at least 512", st.PageSize)
+	}
+	if st.BufferPoolPages < 1 {
+		return bad("storage.buffer_pool_pages", "%d: the buffer pool needs at least one page", st.BufferPoolPages)
+	}
+	if _, err := wal.ParseSyncMode(st.SyncMode); err != nil {
+		return &ConfigError{Key: "storage.sync_mode", Err: err}
+	}
+	if st.EncryptionKey != "" && st.EncryptionKeyFile != "" {
+		return bad("storage.encryption_key", "set either it or storage.encryption_key_file, not both")
+	}
+	if st.CheckpointWALBytes == 0 {
+		return bad("storage.checkpoint_wal_bytes", "must not be 0 (negative = manual checkpoints only)")
+	}
+	if st.BackgroundWriters < 0 {
+		return bad("storage.background_writers", "%d is negative", st.BackgroundWriters)
+	}
+	if st.CheckpointInterval < 0 {
+		return bad("storage.checkpoint_interval", "%s is negative", st.CheckpointInterval)
+	}
+	if st.SortMemBytes == 0 {
+		return bad("storage.sort_mem_bytes", "must not be 0 (negative = never spill)")
+	}
+	if st.TTLSweepInterval == 0 {
+		return bad("storage.ttl_sweep_interval", "must not be 0 (negative = never)")
+	}
+
+	sv := &c.Server
+	if sv.Port < 1 || sv.Port > 65535 {
+		return bad("server.port", "%d is not a TCP port", sv.Port)
+	}
+	if sv.PgPort < 0 || sv.PgPort > 65535 {
+		return bad("server.pg_port", "%d is not a TCP port (0 = disabled)", sv.PgPort)
+	}
+	if sv.MetricsPort < 0 || sv.MetricsPort > 65535 {
+		return bad("server.metrics_port", "%d is not a TCP port (0 = disabled)", sv.MetricsPort)
+	}
+	if sv.ReplicationPort < 0 || sv.ReplicationPort > 65535 {
+		return bad("server.replication_port", "%d is not a TCP port (0 = disabled)", sv.ReplicationPort)
+	}
+	var ce *ConfigError
+	if _, err := c.LogLevel(); errors.As(err, &ce) {
+		return ce
+	}
+	if err := checkLogFormat(sv.LogFormat); err != nil {
+		return err
+	}
+
+	if c.Cache.WarmupRate < 0 {
+		return bad("cache.warmup_rate", "%d is negative", c.Cache.WarmupRate)
+	}
+	if c.Transaction.LockWaitTimeout < 0 {
+		return bad("transaction.lock_wait_timeout", "%s is negative", c.Transaction.LockWaitTimeout)
+	}
+	if c.Transaction.StatementTimeout < 0 {
+		return bad("transaction.statement_timeout", "%s is negative (0 = no limit)", c.Transaction.StatementTimeout)
+	}
+	return nil
 }
 
+// EnvPrefix starts the environment variables that override settings: the
+// key in upper case, "." as "_", as in NOVASQL_SERVER_PORT or
+// NOVASQL_STORAGE_BUFFER_POOL_PAGES.
+const EnvPrefix = "NOVASQL_"
+
+// LoadConfig reads the config file at path over DefaultConfig, applies the
+// environment overrides (see EnvPrefix) and validates the result. Every
+// error is a *ConfigError.
 func LoadConfig(path string) (*NovaSqlConfig, error) {
 	v := viper.New()
 	v.SetConfigFile(path)
 	v.SetConfigType("yaml")
+	eachSetting(reflect.ValueOf(DefaultConfig()).Elem(), "", func(key string, def any) {
+		v.SetDefault(key, def)
+		_ = v.BindEnv(key, EnvPrefix+strings.ToUpper(strings.ReplaceAll(key, ".", "_")))
+	})
 
 	if err := v.ReadInConfig(); err != nil {
-		return nil, fmt.Errorf("read config: %w", err)
+		return nil, &ConfigError{Path: path, Err: fmt.Errorf("read config: %w", err)}
 	}
 
 	var cfg NovaSqlConfig
 	if err := v.Unmarshal(&cfg); err != nil {
-		return nil, fmt.Errorf("unmarshal config: %w", err)
+		return nil, &ConfigError{Path: path, Err: fmt.Errorf("unmarshal config: %w", err)}
+	}
+	if err := cfg.validate(); err != nil {
+		err.Path = path
+		return nil, err
 	}
-
 	return &cfg, nil
 }
+
+// eachSetting calls fn with the key and the value of every setting of the
+// config struct v, its keys starting with prefix.
+func eachSetting(v reflect.Value, prefix string, fn func(key string, val any)) {
+	for i := range v.NumField() {
+		key := prefix + v.Type().Field(i).Tag.Get("mapstructure")
+		if f := v.Field(i); f.Kind() == reflect.Struct {
+			eachSetting(f, key+".", fn)
+		} else {
+			fn(key, f.Interface())
+		}
+	}
+}
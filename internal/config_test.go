@@ -0,0 +1,71 @@
+package internal
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func writeConfig(t *testing.T, yaml string) string {
+	t.Helper()
+	path := filepath.Join(t.TempDir(), "novasql.yaml")
+	require.NoError(t, os.WriteFile(path, []byte(yaml), 0o600))
+	return path
+}
+
+func TestLoadConfig(t *testing.T) {
+	// Unset settings take their defaults.
+	cfg, err := LoadConfig(writeConfig(t, "server:\n  pg_port: 5433\n"))
+	require.NoError(t, err)
+	want := DefaultConfig()
+	want.Server.PgPort = 5433
+	require.Equal(t, want, cfg)
+
+	// The environment overrides the file.
+	t.Setenv("NOVASQL_SERVER_PORT", "7000")
+	t.Setenv("NOVASQL_STORAGE_BUFFER_POOL_PAGES", "512")
+	t.Setenv("NOVASQL_TRANSACTION_LOCK_WAIT_TIMEOUT", "250ms")
+	cfg, err = LoadConfig(writeConfig(t, "server:\n  port: 8866\n"))
+	require.NoError(t, err)
+	require.Equal(t, 7000, cfg.Server.Port)
+	require.Equal(t, 512, cfg.Storage.BufferPoolPages)
+	require.Equal(t, 250*time.Millisecond, cfg.Transaction.LockWaitTimeout)
+}
+
+func TestLoadConfig_Invalid(t *testing.T) {
+	for yaml, key := range map[string]string{
+		"storage:\n  page_size: 3000\n":            "storage.page_size",
+		"storage:\n  buffer_pool_pages: 0\n":       "storage.buffer_pool_pages",
+		"storage:\n  mode: tape\n":                 "storage.mode",
+		"storage:\n  sync_mode: sometimes\n":       "storage.sync_mode",
+		"storage:\n  ttl_sweep_interval: 0s\n":    "storage.ttl_sweep_interval",
+		"storage:\n  background_writers: -1\n":    "storage.background_writers",
+		"server:\n  port: 0\n":                     "server.port",
+		"server:\n  metrics_port: 70000\n":         "server.metrics_port",
+		"server:\n  replication_port: -1\n":        "server.replication_port",
+		"server:\n  log_level: loud\n":             "server.log_level",
+		"transaction:\n  lock_wait_timeout: -1s\n": "transaction.lock_wait_timeout",
+		"transaction:\n  statement_timeout: -1s\n": "transaction.statement_timeout",
+	} {
+		path := writeConfig(t, yaml)
+		_, err := LoadConfig(path)
+		var ce *ConfigError
+		require.ErrorAs(t, err, &ce, yaml)
+		require.Equal(t, key, ce.Key, yaml)
+		require.Equal(t, path, ce.Path)
+		require.ErrorContains(t, err, key)
+	}
+
+	t.Setenv("NOVASQL_STORAGE_PAGE_SIZE", "100")
+	_, err := LoadConfig(writeConfig(t, ""))
+	require.ErrorContains(t, err, "storage.page_size")
+
+	_, err = LoadConfig(filepath.Join(t.TempDir(), "missing.yaml"))
+	var ce *ConfigError
+	require.ErrorAs(t, err, &ce)
+	require.Empty(t, ce.Key)
+	require.NoError(t, DefaultConfig().Validate())
+}
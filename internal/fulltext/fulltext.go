@@ -0,0 +1,210 @@
+// Package fulltext is the text search behind full-text indexes and MATCH: it
+// splits text into terms, builds the index entries of a row and ranks the
+// rows a query finds.
+//
+// A full-text index is a btree.KVTree with one entry per distinct term of
+// each row: the key is btree.IndexEntryKey of the term and the row's TID, so
+// the rows having a term are one key range; the value holds how often the
+// term occurs in the row and how many terms the row has, both as uvarints,
+// for ranking.
+package fulltext
+
+import (
+	"bytes"
+	"cmp"
+	"encoding/binary"
+	"errors"
+	"math"
+	"slices"
+	"strings"
+	"unicode"
+
+	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/heap"
+)
+
+// MaxTermBytes bounds the terms that are indexed; longer words are left out
+// of text and queries alike.
+const MaxTermBytes = 64
+
+// BM25 parameters: how fast repeats of a term stop counting, and how much
+// the length of a row weighs.
+const (
+	bm25K1 = 1.2
+	bm25B  = 0.75
+)
+
+var ErrBadEntry = errors.New("fulltext: malformed index entry")
+
+// Terms splits s into its terms: the runs of letters and digits, in lower
+// case, in order and with repeats.
+func Terms(s string) []string {
+	var out []string
+	var word strings.Builder
+	flush := func() {
+		if word.Len() > 0 && word.Len() <= MaxTermBytes {
+			out = append(out, word.String())
+		}
+		word.Reset()
+	}
+	for _, r := range s {
+		if unicode.IsLetter(r) || unicode.IsDigit(r) {
+			word.WriteRune(unicode.ToLower(r))
+			continue
+		}
+		flush()
+	}
+	flush()
+	return out
+}
+
+// queryTerms returns the distinct terms of query, sorted.
+func queryTerms(query string) []string {
+	terms := Terms(query)
+	slices.Sort(terms)
+	return slices.Compact(terms)
+}
+
+// Match reports whether text has every term of query, as MATCH does. A
+// query without terms matches nothing.
+func Match(text, query string) bool {
+	want := queryTerms(query)
+	if len(want) == 0 {
+		return false
+	}
+	have := make(map[string]bool)
+	for _, t := range Terms(text) {
+		have[t] = true
+	}
+	for _, t := range want {
+		if !have[t] {
+			return false
+		}
+	}
+	return true
+}
+
+// Entry is an entry of a full-text index.
+type Entry struct {
+	Key   []byte
+	Value []byte
+}
+
+// Entries returns the index entries of text, the value of the row at tid,
+// sorted by key. Text without terms has none.
+func Entries(text string, tid heap.TID) ([]Entry, error) {
+	terms := Terms(text)
+	counts := make(map[string]uint64, len(terms))
+	for _, t := range terms {
+		counts[t]++
+	}
+	out := make([]Entry, 0, len(counts))
+	for t, n := range counts {
+		key, err := btree.IndexEntryKey(t, tid)
+		if err != nil {
+			return nil, err
+		}
+		val := binary.AppendUvarint(nil, n)
+		val = binary.AppendUvarint(val, uint64(len(terms)))
+		out = append(out, Entry{Key: key, Value: val})
+	}
+	slices.SortFunc(out, func(a, b Entry) int { return bytes.Compare(a.Key, b.Key) })
+	return out, nil
+}
+
+// posting is what an entry says about the row it points to.
+type posting struct {
+	count  uint64 // occurrences of the term
+	length uint64 // terms of the row
+}
+
+func decodePosting(v []byte) (posting, error) {
+	count, n := binary.Uvarint(v)
+	if n <= 0 {
+		return posting{}, ErrBadEntry
+	}
+	length, m := binary.Uvarint(v[n:])
+	if m <= 0 || n+m != len(v) {
+		return posting{}, ErrBadEntry
+	}
+	return posting{count: count, length: length}, nil
+}
+
+// Hit is a row a query found, and how well it matches: the higher Score, the
+// better.
+type Hit struct {
+	TID   heap.TID
+	Score float64
+}
+
+// Search returns the rows of the full-text index tree that have every term
+// of query, best match first, then in TID order. Rows are ranked with BM25
+// over the rows having any of the terms: a term counts more the rarer it is
+// among them and the more often it occurs in a row, and less in a longer
+// row.
+func Search(tree *btree.KVTree, query string) ([]Hit, error) {
+	terms := queryTerms(query)
+	if len(terms) == 0 {
+		return nil, nil
+	}
+	lists := make([]map[heap.TID]posting, len(terms))
+	lengths := make(map[heap.TID]uint64)
+	for i, t := range terms {
+		prefix, err := btree.EncodeIndexValue(t)
+		if err != nil {
+			return nil, err
+		}
+		lists[i] = make(map[heap.TID]posting)
+		it := tree.Scan(prefix, btree.PrefixEnd(prefix))
+		for it.Next() {
+			tid, err := btree.IndexEntryTID(it.Key())
+			if err != nil {
+				return nil, err
+			}
+			p, err := decodePosting(it.Value())
+			if err != nil {
+				return nil, err
+			}
+			lists[i][tid] = p
+			lengths[tid] = p.length
+		}
+		if err := it.Err(); err != nil {
+			return nil, err
+		}
+		if len(lists[i]) == 0 {
+			return nil, nil
+		}
+	}
+
+	rows := float64(len(lengths))
+	var total uint64
+	for _, l := range lengths {
+		total += l
+	}
+	avgLength := float64(total) / rows
+
+	var hits []Hit
+next:
+	for tid := range lists[0] {
+		score := 0.0
+		for _, list := range lists {
+			p, ok := list[tid]
+			if !ok {
+				continue next
+			}
+			df := float64(len(list))
+			idf := math.Log(1 + (rows-df+0.5)/(df+0.5))
+			tf := float64(p.count)
+			norm := 1 - bm25B + bm25B*float64(p.length)/avgLength
+			score += idf * tf * (bm25K1 + 1) / (tf + bm25K1*norm)
+		}
+		hits = append(hits, Hit{TID: tid, Score: score})
+	}
+	slices.SortFunc(hits, func(a, b Hit) int {
+		if c := cmp.Compare(b.Score, a.Score); c != 0 {
+			return c
+		}
+		return cmp.Or(cmp.Compare(a.TID.PageID, b.TID.PageID), cmp.Compare(a.TID.Slot, b.TID.Slot))
+	})
+	return hits, nil
+}
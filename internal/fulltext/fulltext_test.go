@@ -0,0 +1,68 @@
+package fulltext
+
+import (
+	"path/filepath"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/bufferpool"
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/internal/wal"
+)
+
+func TestTermsAndMatch(t *testing.T) {
+	require.Equal(t, []string{"disk", "full", "on", "node", "42", "disk"}, Terms("Disk full on node-42: DISK!"))
+	require.Equal(t, []string{"café", "naïve"}, Terms("Café, naïve"))
+	require.Empty(t, Terms(strings.Repeat("x", MaxTermBytes+1)))
+
+	require.True(t, Match("Disk full on node-42", "full disk"))
+	require.False(t, Match("Disk full on node-42", "disk error"))
+	require.False(t, Match("Disk full", "  "), "a query without terms matches nothing")
+}
+
+func TestSearch(t *testing.T) {
+	dir := t.TempDir()
+	sm := storage.NewStorageManager()
+	w, err := wal.Open(filepath.Join(dir, "wal"))
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = w.Close() })
+	gp := bufferpool.NewGlobalPool(sm, bufferpool.DefaultCapacity, w)
+	fs := storage.LocalFileSet{Dir: dir, Base: "fts"}
+	tree, err := btree.OpenKVTree(sm, fs, gp.View(fs))
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = tree.Close() })
+
+	docs := []string{
+		"disk error on node 1",
+		"error error error: disk",
+		"network timeout",
+		"disk replaced after a long and unrelated maintenance window, error cleared",
+	}
+	for i, doc := range docs {
+		entries, err := Entries(doc, heap.TID{PageID: 0, Slot: uint16(i)})
+		require.NoError(t, err)
+		for _, e := range entries {
+			require.NoError(t, tree.Insert(e.Key, e.Value))
+		}
+	}
+
+	slots := func(query string) []uint16 {
+		hits, err := Search(tree, query)
+		require.NoError(t, err)
+		var out []uint16
+		for _, h := range hits {
+			out = append(out, h.TID.Slot)
+		}
+		return out
+	}
+	// Rows with more of the terms, in fewer words, rank first.
+	require.Equal(t, []uint16{1, 0, 3}, slots("Disk ERROR"))
+	require.Equal(t, []uint16{2}, slots("timeout"))
+	require.Empty(t, slots("disk timeout"))
+	require.Empty(t, slots("missing"))
+	require.Empty(t, slots(""))
+}
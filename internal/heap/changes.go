@@ -0,0 +1,74 @@
+package heap
+
+import (
+	"slices"
+
+	"github.com/tuannm99/novasql/internal/mvcc"
+)
+
+// Change hooks
+//
+// A table with a change hook (SetChangeHook) reports every row it writes
+// once the write succeeded: the values inserted and, for updates and
+// deletes, the values they replaced, read from the page before it changed.
+// Rows are only decoded for the hook while it is watching, so an idle hook
+// costs a call per write.
+
+// ChangeKind is what a write did to a row.
+type ChangeKind uint8
+
+const (
+	ChangeInsert ChangeKind = iota + 1
+	ChangeUpdate
+	ChangeDelete
+)
+
+// Change is one row a table wrote.
+type Change struct {
+	Kind ChangeKind
+	TID  TID      // the new version (inserts, updates) or the deleted one
+	Old  []any    // updates and deletes
+	New  []any    // inserts and updates
+	XID  mvcc.XID // transaction of the *Tx write, 0 without one
+}
+
+// ChangeHook is told of the rows a table writes.
+type ChangeHook interface {
+	// Watching reports whether changes are wanted now.
+	Watching() bool
+	// RowChanged is called after each write, with the name of the table.
+	RowChanged(table string, c Change)
+}
+
+// SetChangeHook makes the table report its writes to h (nil = none).
+func (t *Table) SetChangeHook(h ChangeHook) {
+	t.changes = h
+}
+
+func (t *Table) watching() bool {
+	return t.changes != nil && t.changes.Watching()
+}
+
+// noteChange reports c, with copies of its rows: callers may reuse theirs.
+func (t *Table) noteChange(c Change) {
+	c.Old, c.New = slices.Clone(c.Old), slices.Clone(c.New)
+	t.changes.RowChanged(t.Name, c)
+}
+
+// oldRow decodes raw for a hook that is watching, nil otherwise.
+func (t *Table) oldRow(raw []byte, watch bool) ([]any, error) {
+	if !watch {
+		return nil, nil
+	}
+	return t.decodeRowWithOverflow(raw)
+}
+
+// noteInserts reports the rows of a batch insert.
+func (t *Table) noteInserts(tids []TID, rows [][]any, xid mvcc.XID) {
+	if !t.watching() {
+		return
+	}
+	for i, id := range tids {
+		t.noteChange(Change{Kind: ChangeInsert, TID: id, New: rows[i], XID: xid})
+	}
+}
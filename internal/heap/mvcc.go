@@ -0,0 +1,333 @@
+package heap
+
+import (
+	"errors"
+	"fmt"
+
+	"github.com/tuannm99/novasql/internal/mvcc"
+	"github.com/tuannm99/novasql/internal/storage"
+	"github.com/tuannm99/novasql/pkg/bx"
+)
+
+// Row versions
+//
+// Rows written through a transaction (InsertTx, UpdateTx, DeleteTx) carry
+// the XIDs that created (xmin) and deleted (xmax) them. DeleteTx only sets
+// xmax, so readers with an older snapshot keep seeing the row; UpdateTx
+// deletes the old version and inserts a new one. Readers never wait for
+// writers: visibility is decided per tuple from the snapshot.
+//
+// Rows written by Insert/Update/Delete carry no versions and are visible to
+// every snapshot as soon as they are written, like before MVCC.
+
+const versionHeaderSize = 1 + 8 + 8
+
+// SetMVCC attaches the transaction status log that decides visibility.
+func (t *Table) SetMVCC(m *mvcc.Manager) {
+	t.xact = m
+}
+
+// readSnapshot is the snapshot of a single non-transactional read.
+func (t *Table) readSnapshot() *mvcc.Snapshot {
+	if t.xact == nil {
+		return nil
+	}
+	return t.xact.Snapshot()
+}
+
+func versionedTuple(xmin, xmax mvcc.XID, inner []byte) []byte {
+	out := make([]byte, versionHeaderSize, versionHeaderSize+len(inner))
+	out[0] = rowKindVersioned
+	bx.PutU64(out[1:9], uint64(xmin))
+	bx.PutU64(out[9:17], uint64(xmax))
+	return append(out, inner...)
+}
+
+// splitVersioned returns the header and inner tuple of a versioned tuple.
+func splitVersioned(raw []byte) (xmin, xmax mvcc.XID, inner []byte, err error) {
+	if len(raw) < versionHeaderSize+1 || raw[0] != rowKindVersioned {
+		return 0, 0, nil, fmt.Errorf("heap: invalid versioned tuple")
+	}
+	return mvcc.XID(bx.U64(raw[1:9])), mvcc.XID(bx.U64(raw[9:17])), raw[versionHeaderSize:], nil
+}
+
+// rowVersion is splitVersioned for any tuple: unversioned tuples are
+// created by XID 0 and never deleted.
+func rowVersion(raw []byte) (xmin, xmax mvcc.XID, inner []byte) {
+	if len(raw) == 0 || raw[0] != rowKindVersioned {
+		return 0, 0, raw
+	}
+	xmin, xmax, inner, err := splitVersioned(raw)
+	if err != nil {
+		return 0, 0, raw
+	}
+	return xmin, xmax, inner
+}
+
+// visibleTo reports whether snap sees the tuple. Without a snapshot a
+// version is visible until something deletes it.
+func visibleTo(snap *mvcc.Snapshot, raw []byte) bool {
+	xmin, xmax, _ := rowVersion(raw)
+	if snap == nil {
+		return xmax == 0
+	}
+	return snap.Visible(xmin, xmax)
+}
+
+// overflowRefOf returns the overflow chain a tuple points to, if any.
+func overflowRefOf(raw []byte) *storage.OverflowRef {
+	_, _, inner := rowVersion(raw)
+	if len(inner) < 1+8 || inner[0] != rowKindOverflow {
+		return nil
+	}
+	return &storage.OverflowRef{FirstPageID: bx.U32(inner[1:5]), Length: bx.U32(inner[5:9])}
+}
+
+// InsertTx inserts a row that only tx sees until it commits.
+func (t *Table) InsertTx(tx *mvcc.Tx, values []any) (TID, error) {
+	if err := t.ensureOpen(); err != nil {
+		return TID{}, err
+	}
+	if t.readOnly {
+		return TID{}, ErrTableReadOnly
+	}
+	if tx.Done() {
+		return TID{}, mvcc.ErrTxDone
+	}
+
+	inner, err := t.encodeRowWithOverflow(values)
+	if err != nil {
+		return TID{}, err
+	}
+	id, err := t.insertTuple(versionedTuple(tx.XID(), 0, inner))
+	if err == nil && t.watching() {
+		t.noteChange(Change{Kind: ChangeInsert, TID: id, New: values, XID: tx.XID()})
+	}
+	return id, err
+}
+
+// InsertBatchTx is InsertBatch for rows that only tx sees until it commits.
+func (t *Table) InsertBatchTx(tx *mvcc.Tx, rows [][]any) ([]TID, error) {
+	if err := t.ensureOpen(); err != nil {
+		return nil, err
+	}
+	if t.readOnly {
+		return nil, ErrTableReadOnly
+	}
+	if tx.Done() {
+		return nil, mvcc.ErrTxDone
+	}
+
+	tuples := make([][]byte, len(rows))
+	for i, values := range rows {
+		inner, err := t.encodeRowWithOverflow(values)
+		if err != nil {
+			return nil, err
+		}
+		tuples[i] = versionedTuple(tx.XID(), 0, inner)
+	}
+	tids, err := t.insertTuples(tuples)
+	if err == nil {
+		t.noteInserts(tids, rows, tx.XID())
+	}
+	return tids, err
+}
+
+// GetTx is Get as tx's snapshot sees the table.
+func (t *Table) GetTx(tx *mvcc.Tx, id TID) ([]any, error) {
+	return t.get(tx.Snapshot(), id)
+}
+
+// ScanTx is Scan as tx's snapshot sees the table.
+func (t *Table) ScanTx(tx *mvcc.Tx, fn func(id TID, row []any) error) error {
+	return t.scan(tx.Snapshot(), fn)
+}
+
+// LockTx takes a row lock on id for tx, held until tx ends. DeleteTx and
+// UpdateTx take the exclusive lock themselves; a shared lock keeps other
+// transactions from changing a row tx has read.
+func (t *Table) LockTx(tx *mvcc.Tx, id TID, mode mvcc.LockMode) error {
+	return tx.Lock(mvcc.RowKey{Rel: t.Name, Page: id.PageID, Slot: id.Slot}, mode)
+}
+
+// DeleteTx marks the row version at id as deleted by tx. It first takes the
+// row's exclusive lock, so a concurrent writer of the same row is waited for
+// rather than failed. Rows tx cannot see fail with storage.ErrBadSlot; rows
+// another transaction deleted or updated and committed fail with
+// mvcc.ErrWriteConflict.
+func (t *Table) DeleteTx(tx *mvcc.Tx, id TID) error {
+	watch := t.watching()
+	old, err := t.deleteTx(tx, id, watch)
+	if err == nil && watch {
+		t.noteChange(Change{Kind: ChangeDelete, TID: id, Old: old, XID: tx.XID()})
+	}
+	return err
+}
+
+// deleteTx is DeleteTx; with watch it returns the row deleted.
+func (t *Table) deleteTx(tx *mvcc.Tx, id TID, watch bool) ([]any, error) {
+	if err := t.ensureOpen(); err != nil {
+		return nil, err
+	}
+	if t.readOnly {
+		return nil, ErrTableReadOnly
+	}
+	if err := t.LockTx(tx, id, mvcc.LockExclusive); err != nil {
+		return nil, err
+	}
+
+	p, err := t.BP.GetPage(id.PageID)
+	if err != nil {
+		return nil, err
+	}
+	pinned := true
+	defer func() {
+		if pinned {
+			_ = t.BP.Unpin(p, false)
+		}
+	}()
+
+	raw, err := p.ReadTuple(int(id.Slot))
+	if err != nil {
+		return nil, err
+	}
+	xmin, xmax, inner := rowVersion(raw)
+	snap := tx.Snapshot()
+	if !snap.Sees(xmin) || (xmax != 0 && xmax != tx.XID() && snap.Sees(xmax)) {
+		return nil, storage.ErrBadSlot
+	}
+	if err := tx.CanDelete(xmax); err != nil {
+		return nil, err
+	}
+	old, err := t.oldRow(inner, watch)
+	if err != nil {
+		return nil, err
+	}
+
+	// inner is a view of the page; copy it before the page changes.
+	tuple := versionedTuple(xmin, tx.XID(), inner)
+	if err := p.UpdateTuple(int(id.Slot), tuple); err != nil {
+		return nil, err
+	}
+	// Unpin before flushing, as insertTuple does: the dirty unpin logs the
+	// page and marks it dirty, so a flush before it would miss the change.
+	pinned = false
+	if err := t.BP.Unpin(p, true); err != nil {
+		return nil, err
+	}
+	return old, t.Flush()
+}
+
+// UpdateTx replaces the row version at id by a new one and returns its TID.
+// Snapshots older than tx keep seeing the old version.
+func (t *Table) UpdateTx(tx *mvcc.Tx, id TID, values []any) (TID, error) {
+	watch := t.watching()
+	old, err := t.deleteTx(tx, id, watch)
+	if err != nil {
+		return TID{}, err
+	}
+	if tx.Done() {
+		return TID{}, mvcc.ErrTxDone
+	}
+	inner, err := t.encodeRowWithOverflow(values)
+	if err != nil {
+		return TID{}, err
+	}
+	nid, err := t.insertTuple(versionedTuple(tx.XID(), 0, inner))
+	if err == nil && watch {
+		t.noteChange(Change{Kind: ChangeUpdate, TID: nid, Old: old, New: values, XID: tx.XID()})
+	}
+	return nid, err
+}
+
+// Vacuum removes row versions no snapshot can see any more: versions whose
+// creating transaction aborted, and versions deleted by a transaction that
+// committed below the manager's horizon. It returns how many it removed.
+func (t *Table) Vacuum() (int, error) {
+	if err := t.ensureOpen(); err != nil {
+		return 0, err
+	}
+	if t.readOnly {
+		return 0, ErrTableReadOnly
+	}
+	if t.xact == nil {
+		return 0, nil
+	}
+	horizon := t.xact.Horizon()
+
+	removed := 0
+	for pageID := uint32(0); pageID < t.PageCount; pageID++ {
+		dead, err := t.deadVersions(pageID, horizon)
+		if err != nil {
+			return removed, err
+		}
+		for _, id := range dead {
+			if err := t.Delete(id); err != nil {
+				return removed, err
+			}
+			removed++
+		}
+	}
+	return removed, nil
+}
+
+func (t *Table) deadVersions(pageID uint32, horizon mvcc.XID) ([]TID, error) {
+	p, err := t.BP.GetPage(pageID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = t.BP.Unpin(p, false) }()
+
+	var dead []TID
+	moved := p.RedirectTargets()
+	for slot := 0; slot < p.NumSlots(); slot++ {
+		if slot < len(moved) && moved[slot] {
+			continue
+		}
+		raw, err := p.ReadTuple(slot)
+		if errors.Is(err, storage.ErrBadSlot) {
+			continue
+		}
+		if err != nil {
+			return nil, err
+		}
+		xmin, xmax, _ := rowVersion(raw)
+		aborted := t.xact.Status(xmin) == mvcc.StatusAborted
+		deleted := xmax != 0 && xmax < horizon && t.xact.Status(xmax) == mvcc.StatusCommitted
+		if aborted || deleted {
+			dead = append(dead, TID{PageID: pageID, Slot: uint16(slot)})
+		}
+	}
+	return dead, nil
+}
+
+// PageXIDs returns the transactions that created or deleted the row versions
+// on page pageID, XID 0 aside, with repeats.
+func (t *Table) PageXIDs(pageID uint32) ([]mvcc.XID, error) {
+	if err := t.ensureOpen(); err != nil {
+		return nil, err
+	}
+	p, err := t.BP.GetPage(pageID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = t.BP.Unpin(p, false) }()
+
+	var xids []mvcc.XID
+	for slot := 0; slot < p.NumSlots(); slot++ {
+		raw, err := p.ReadTuple(slot)
+		if errors.Is(err, storage.ErrBadSlot) {
+			continue
+		}
+		if err != nil {
+			return nil, err
+		}
+		xmin, xmax, _ := rowVersion(raw)
+		for _, x := range []mvcc.XID{xmin, xmax} {
+			if x != 0 {
+				xids = append(xids, x)
+			}
+		}
+	}
+	return xids, nil
+}
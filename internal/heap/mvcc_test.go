@@ -0,0 +1,135 @@
+package heap
+
+import (
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/mvcc"
+)
+
+func txNames(t *testing.T, tbl *Table, tx *mvcc.Tx) []string {
+	t.Helper()
+
+	var names []string
+	scan := tbl.Scan
+	if tx != nil {
+		scan = func(fn func(TID, []any) error) error { return tbl.ScanTx(tx, fn) }
+	}
+	require.NoError(t, scan(func(_ TID, row []any) error {
+		names = append(names, row[1].(string))
+		return nil
+	}))
+	return names
+}
+
+func TestTable_SnapshotIsolation(t *testing.T) {
+	tbl, _, _ := newTestTable(t, "users")
+	m, err := mvcc.Open(filepath.Join(t.TempDir(), "xact"), false)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = m.Close() })
+	tbl.SetMVCC(m)
+
+	legacy, err := tbl.Insert([]any{int64(1), "legacy", true})
+	require.NoError(t, err)
+
+	w, err := m.Begin()
+	require.NoError(t, err)
+	bob, err := tbl.InsertTx(w, []any{int64(2), "bob", true})
+	require.NoError(t, err)
+	require.Equal(t, []string{"legacy", "bob"}, txNames(t, tbl, w))
+	require.Equal(t, []string{"legacy"}, txNames(t, tbl, nil))
+	require.NoError(t, w.Commit())
+
+	// r's snapshot predates the writes below and keeps seeing the old rows.
+	r, err := m.Begin()
+	require.NoError(t, err)
+	w, err = m.Begin()
+	require.NoError(t, err)
+	_, err = tbl.UpdateTx(w, bob, []any{int64(2), "bobby", true})
+	require.NoError(t, err)
+	require.NoError(t, tbl.DeleteTx(w, legacy))
+
+	// First updater wins: r waits for w's row lock, then conflicts once w
+	// has committed.
+	m.SetLockTimeout(20 * time.Millisecond)
+	require.ErrorIs(t, tbl.DeleteTx(r, bob), mvcc.ErrLockTimeout)
+	require.NoError(t, w.Commit())
+	require.ErrorIs(t, tbl.DeleteTx(r, bob), mvcc.ErrWriteConflict)
+	require.Equal(t, []string{"legacy", "bob"}, txNames(t, tbl, r))
+	require.Equal(t, []string{"bobby"}, txNames(t, tbl, nil))
+	row, err := tbl.GetTx(r, legacy)
+	require.NoError(t, err)
+	require.Equal(t, "legacy", row[1])
+
+	// Aborted writes never show up.
+	a, err := m.Begin()
+	require.NoError(t, err)
+	_, err = tbl.InsertTx(a, []any{int64(3), "ghost", true})
+	require.NoError(t, err)
+	require.NoError(t, a.Abort())
+	require.Equal(t, []string{"bobby"}, txNames(t, tbl, nil))
+
+	// The old versions stay while r may need them; the aborted row does not.
+	n, err := tbl.Vacuum()
+	require.NoError(t, err)
+	require.Equal(t, 1, n)
+	require.NoError(t, r.Commit())
+	n, err = tbl.Vacuum()
+	require.NoError(t, err)
+	require.Equal(t, 2, n)
+	require.Equal(t, []string{"bobby"}, txNames(t, tbl, nil))
+}
+
+type recordingHook struct {
+	watch   bool
+	changes []Change
+}
+
+func (h *recordingHook) Watching() bool { return h.watch }
+
+func (h *recordingHook) RowChanged(table string, c Change) {
+	if table == "users" {
+		h.changes = append(h.changes, c)
+	}
+}
+
+func TestTable_ChangeHook(t *testing.T) {
+	tbl, _, _ := newTestTable(t, "users")
+	m, err := mvcc.Open(filepath.Join(t.TempDir(), "xact"), false)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = m.Close() })
+	tbl.SetMVCC(m)
+	h := &recordingHook{}
+	tbl.SetChangeHook(h)
+
+	// Nothing is reported while the hook is not watching.
+	id, err := tbl.Insert([]any{int64(1), "ann", true})
+	require.NoError(t, err)
+	require.Empty(t, h.changes)
+
+	h.watch = true
+	row := []any{int64(1), "anna", true}
+	require.NoError(t, tbl.Update(id, row))
+	row[1] = "reused"
+	require.NoError(t, tbl.Delete(id))
+	tx, err := m.Begin()
+	require.NoError(t, err)
+	bob, err := tbl.InsertTx(tx, []any{int64(2), "bob", true})
+	require.NoError(t, err)
+	bobby, err := tbl.UpdateTx(tx, bob, []any{int64(2), "bobby", true})
+	require.NoError(t, err)
+	require.NoError(t, tx.Commit())
+
+	require.Equal(t, []Change{
+		{Kind: ChangeUpdate, TID: id, Old: []any{int64(1), "ann", true}, New: []any{int64(1), "anna", true}},
+		{Kind: ChangeDelete, TID: id, Old: []any{int64(1), "anna", true}},
+		{Kind: ChangeInsert, TID: bob, New: []any{int64(2), "bob", true}, XID: tx.XID()},
+		{
+			Kind: ChangeUpdate, TID: bobby, XID: tx.XID(),
+			Old: []any{int64(2), "bob", true}, New: []any{int64(2), "bobby", true},
+		},
+	}, h.changes)
+}
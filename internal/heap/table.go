@@ -4,9 +4,11 @@ import (
 	"errors"
 	"fmt"
 	"log/slog"
+	"slices"
 	"sync/atomic"
 
 	"github.com/tuannm99/novasql/internal/bufferpool"
+	"github.com/tuannm99/novasql/internal/mvcc"
 	"github.com/tuannm99/novasql/internal/record"
 	"github.com/tuannm99/novasql/internal/storage"
 	"github.com/tuannm99/novasql/pkg/bx"
@@ -21,11 +23,15 @@ type TID struct {
 }
 
 const (
-	rowKindInline   = byte(0)
-	rowKindOverflow = byte(1)
+	rowKindInline    = byte(0)
+	rowKindOverflow  = byte(1)
+	rowKindVersioned = byte(2) // xmin(8) + xmax(8), then an inline or overflow tuple
 )
 
-var ErrTableClosed = errors.New("heap: table is closed")
+var (
+	ErrTableClosed   = errors.New("heap: table is closed")
+	ErrTableReadOnly = errors.New("heap: table is read-only")
+)
 
 // Table represents heap file logic: name, schema, StorageManager, FileSet, PageCount.
 type Table struct {
@@ -43,6 +49,21 @@ type Table struct {
 	// (usually when allocating a new page).
 	pageCountHook func(pageCount uint32) error
 
+	// readOnly rejects Insert/Update/Delete with ErrTableReadOnly.
+	readOnly bool
+
+	// reclaimed lists pages before the last one that Delete made room on.
+	// Insert fills them before appending, so delete-heavy tables do not grow
+	// without bound. In-memory only: forgotten on reopen.
+	reclaimed []uint32
+
+	// xact decides which row versions are visible (nil: every version
+	// that was not deleted is). See mvcc.go.
+	xact *mvcc.Manager
+
+	// changes is told of every row written (nil = none). See changes.go.
+	changes ChangeHook
+
 	closed atomic.Bool
 }
 
@@ -70,12 +91,34 @@ func (t *Table) SetPageCountHook(fn func(pageCount uint32) error) {
 	t.pageCountHook = fn
 }
 
+// SetReadOnly makes every mutating call fail with ErrTableReadOnly.
+func (t *Table) SetReadOnly(v bool) {
+	t.readOnly = v
+}
+
 // Insert inserts a new row into the heap.
 func (t *Table) Insert(values []any) (TID, error) {
 	if err := t.ensureOpen(); err != nil {
 		return TID{}, err
 	}
+	if t.readOnly {
+		return TID{}, ErrTableReadOnly
+	}
+
+	tuple, err := t.encodeRowWithOverflow(values)
+	if err != nil {
+		return TID{}, err
+	}
+	id, err := t.insertTuple(tuple)
+	if err == nil && t.watching() {
+		t.noteChange(Change{Kind: ChangeInsert, TID: id, New: values})
+	}
+	return id, err
+}
 
+// insertTuple stores an encoded tuple, preferring reclaimed pages, then the
+// last page, then a new one.
+func (t *Table) insertTuple(tuple []byte) (TID, error) {
 	oldPageCount := t.PageCount
 
 	var pageID uint32
@@ -86,9 +129,11 @@ func (t *Table) Insert(values []any) (TID, error) {
 		pageID = t.PageCount - 1
 	}
 
-	tuple, err := t.encodeRowWithOverflow(values)
-	if err != nil {
-		return TID{}, err
+	if id, ok, err := t.insertReclaimed(tuple); err != nil || ok {
+		if err != nil {
+			return TID{}, err
+		}
+		return id, t.Flush()
 	}
 
 	for {
@@ -129,8 +174,181 @@ func (t *Table) Insert(values []any) (TID, error) {
 	}
 }
 
-// Get reads a single row by TID.
+// InsertBatch inserts rows into the heap as Insert does, but fills each page
+// before writing it, so that the page is logged once for the batch rather
+// than once per row; with a PageWriter the pages of the batch are one WAL
+// group. The rows are encoded before any of them is stored, so a row that
+// does not fit the schema inserts none of them.
+func (t *Table) InsertBatch(rows [][]any) ([]TID, error) {
+	if err := t.ensureOpen(); err != nil {
+		return nil, err
+	}
+	if t.readOnly {
+		return nil, ErrTableReadOnly
+	}
+
+	tuples := make([][]byte, len(rows))
+	for i, values := range rows {
+		tuple, err := t.encodeRowWithOverflow(values)
+		if err != nil {
+			return nil, err
+		}
+		tuples[i] = tuple
+	}
+	tids, err := t.insertTuples(tuples)
+	if err == nil {
+		t.noteInserts(tids, rows, 0)
+	}
+	return tids, err
+}
+
+// insertTuples stores encoded tuples in the order insertTuple would, on
+// reclaimed pages, then the last page, then new ones, and flushes once. On
+// a PageWriter the pages are filled as copies and written as one WAL group.
+func (t *Table) insertTuples(tuples [][]byte) ([]TID, error) {
+	w, ok := t.BP.(bufferpool.PageWriter)
+	if !ok {
+		tids, err := t.placeTuples(t.fillPage, tuples)
+		if err != nil {
+			return nil, err
+		}
+		return tids, t.Flush()
+	}
+
+	// Nothing is written before the batch: a failure leaves the table as it
+	// was.
+	pageCount, reclaimed := t.PageCount, slices.Clone(t.reclaimed)
+	batch := &pageBatch{t: t, w: w, fresh: t.PageCount}
+	tids, err := t.placeTuples(batch.fillPage, tuples)
+	if err == nil {
+		err = batch.write()
+	}
+	if err != nil {
+		t.PageCount, t.reclaimed = pageCount, reclaimed
+		return nil, err
+	}
+	return tids, t.Flush()
+}
+
+// placeTuples is insertTuples with fill storing tuples on a page.
+func (t *Table) placeTuples(fill func(pageID uint32, tuples [][]byte) ([]TID, error), tuples [][]byte) ([]TID, error) {
+	tids := make([]TID, 0, len(tuples))
+	for len(tuples) > 0 && len(t.reclaimed) > 0 {
+		pageID := t.reclaimed[len(t.reclaimed)-1]
+		ids, err := fill(pageID, tuples)
+		if err != nil {
+			return nil, err
+		}
+		if len(ids) < len(tuples) {
+			t.reclaimed = t.reclaimed[:len(t.reclaimed)-1]
+		}
+		tids, tuples = append(tids, ids...), tuples[len(ids):]
+	}
+
+	if len(tuples) > 0 && t.PageCount == 0 {
+		t.PageCount = 1 // first page will be created lazily
+	}
+	for fresh := false; len(tuples) > 0; fresh = true {
+		pageID := t.PageCount - 1
+		ids, err := fill(pageID, tuples)
+		if err != nil {
+			return nil, err
+		}
+		if len(ids) == 0 && fresh {
+			return nil, storage.ErrNoSpace
+		}
+		tids, tuples = append(tids, ids...), tuples[len(ids):]
+		if len(tuples) > 0 {
+			t.PageCount++
+		}
+	}
+	return tids, nil
+}
+
+// fillPage inserts as many of tuples, from the first, as fit on page pageID.
+// The page is only dirtied when one of them does.
+func (t *Table) fillPage(pageID uint32, tuples [][]byte) ([]TID, error) {
+	p, err := t.BP.GetPage(pageID)
+	if err != nil {
+		return nil, err
+	}
+	tids, err := fillTuples(p, pageID, tuples)
+	if uerr := t.BP.Unpin(p, len(tids) > 0); err == nil {
+		err = uerr
+	}
+	if err != nil {
+		return nil, err
+	}
+	return tids, nil
+}
+
+// fillTuples inserts as many of tuples, from the first, as fit on p, page
+// pageID, and returns their TIDs, those inserted so far on error.
+func fillTuples(p *storage.Page, pageID uint32, tuples [][]byte) ([]TID, error) {
+	var tids []TID
+	for _, tuple := range tuples {
+		slot, err := p.InsertTuple(tuple)
+		if err == storage.ErrNoSpace {
+			break
+		}
+		if err != nil {
+			return tids, err
+		}
+		tids = append(tids, TID{PageID: pageID, Slot: uint16(slot)})
+	}
+	return tids, nil
+}
+
+// pageBatch is the fillPage of insertTuples on a PageWriter: it fills copies
+// of the pages, which write logs and stores together.
+type pageBatch struct {
+	t     *Table
+	w     bufferpool.PageWriter
+	fresh uint32 // pages from fresh on are new: not read, started empty
+	ids   []uint32
+	pages [][]byte
+}
+
+func (b *pageBatch) fillPage(pageID uint32, tuples [][]byte) ([]TID, error) {
+	var p *storage.Page
+	staged := slices.Index(b.ids, pageID)
+	switch {
+	case staged >= 0:
+		p = &storage.Page{Buf: b.pages[staged]}
+	case pageID >= b.fresh:
+		p = b.t.SM.NewPage(pageID)
+	default:
+		cached, err := b.w.GetPage(pageID)
+		if err != nil {
+			return nil, err
+		}
+		p = &storage.Page{Buf: append([]byte(nil), cached.Buf...)}
+		if err := b.w.Unpin(cached, false); err != nil {
+			return nil, err
+		}
+	}
+	tids, err := fillTuples(p, pageID, tuples)
+	if err != nil {
+		return nil, err
+	}
+	if len(tids) > 0 && staged < 0 {
+		b.ids = append(b.ids, pageID)
+		b.pages = append(b.pages, p.Buf)
+	}
+	return tids, nil
+}
+
+func (b *pageBatch) write() error {
+	return b.w.WritePageList(b.ids, b.pages)
+}
+
+// Get reads a single row by TID. Row versions invisible to a fresh
+// snapshot fail with storage.ErrBadSlot, like deleted rows.
 func (t *Table) Get(id TID) ([]any, error) {
+	return t.get(t.readSnapshot(), id)
+}
+
+func (t *Table) get(snap *mvcc.Snapshot, id TID) ([]any, error) {
 	if err := t.ensureOpen(); err != nil {
 		return nil, err
 	}
@@ -146,6 +364,9 @@ func (t *Table) Get(id TID) ([]any, error) {
 	if err != nil {
 		return nil, err
 	}
+	if !visibleTo(snap, raw) {
+		return nil, storage.ErrBadSlot
+	}
 	return t.decodeRowWithOverflow(raw)
 }
 
@@ -154,6 +375,9 @@ func (t *Table) Update(id TID, values []any) error {
 	if err := t.ensureOpen(); err != nil {
 		return err
 	}
+	if t.readOnly {
+		return ErrTableReadOnly
+	}
 
 	p, err := t.BP.GetPage(id.PageID)
 	if err != nil {
@@ -163,14 +387,15 @@ func (t *Table) Update(id TID, values []any) error {
 	dirty := false
 	defer func() { _ = t.BP.Unpin(p, dirty) }()
 
-	// 1) capture old overflow ref (if any)
+	// 1) capture old overflow ref (if any), and the old row for the hook
 	var oldRef *storage.OverflowRef
-	oldRaw, err := p.ReadTuple(int(id.Slot))
-	if err == nil && len(oldRaw) >= 1+8 && oldRaw[0] == rowKindOverflow {
-		first := bx.U32(oldRaw[1:5])
-		length := bx.U32(oldRaw[5:9])
-		ref := storage.OverflowRef{FirstPageID: first, Length: length}
-		oldRef = &ref
+	var old []any
+	watch := t.watching()
+	if oldRaw, err := p.ReadTuple(int(id.Slot)); err == nil {
+		oldRef = overflowRefOf(oldRaw)
+		if old, err = t.oldRow(oldRaw, watch); err != nil {
+			return err
+		}
 	}
 
 	// 2) encode new tuple
@@ -195,7 +420,13 @@ func (t *Table) Update(id TID, values []any) error {
 		}
 	}
 
-	return t.Flush()
+	if err := t.Flush(); err != nil {
+		return err
+	}
+	if watch {
+		t.noteChange(Change{Kind: ChangeUpdate, TID: id, Old: old, New: values})
+	}
+	return nil
 }
 
 // Delete marks a single row identified by TID as deleted.
@@ -203,6 +434,9 @@ func (t *Table) Delete(id TID) error {
 	if err := t.ensureOpen(); err != nil {
 		return err
 	}
+	if t.readOnly {
+		return ErrTableReadOnly
+	}
 
 	p, err := t.BP.GetPage(id.PageID)
 	if err != nil {
@@ -212,20 +446,24 @@ func (t *Table) Delete(id TID) error {
 	dirty := false
 	defer func() { _ = t.BP.Unpin(p, dirty) }()
 
-	// capture overflow ref before delete
+	// capture overflow ref (and the row, for the hook) before delete
 	var oldRef *storage.OverflowRef
-	oldRaw, err := p.ReadTuple(int(id.Slot))
-	if err == nil && len(oldRaw) >= 1+8 && oldRaw[0] == rowKindOverflow {
-		first := bx.U32(oldRaw[1:5])
-		length := bx.U32(oldRaw[5:9])
-		ref := storage.OverflowRef{FirstPageID: first, Length: length}
-		oldRef = &ref
+	var old []any
+	watch := t.watching()
+	if oldRaw, err := p.ReadTuple(int(id.Slot)); err == nil {
+		oldRef = overflowRefOf(oldRaw)
+		if old, err = t.oldRow(oldRaw, watch); err != nil {
+			return err
+		}
 	}
 
 	if err := p.DeleteTuple(int(id.Slot)); err != nil {
 		return err
 	}
 	dirty = true
+	if id.PageID+1 < t.PageCount && !slices.Contains(t.reclaimed, id.PageID) {
+		t.reclaimed = append(t.reclaimed, id.PageID)
+	}
 
 	if oldRef != nil && t.Overflow != nil && oldRef.Length > 0 {
 		if err := t.Overflow.Free(*oldRef); err != nil {
@@ -236,12 +474,191 @@ func (t *Table) Delete(id TID) error {
 		}
 	}
 
-	return t.Flush()
+	if err := t.Flush(); err != nil {
+		return err
+	}
+	if watch {
+		t.noteChange(Change{Kind: ChangeDelete, TID: id, Old: old})
+	}
+	return nil
+}
+
+// insertReclaimed places tuple on one of the reclaimed pages, dropping the
+// ones that turn out to be too full.
+func (t *Table) insertReclaimed(tuple []byte) (TID, bool, error) {
+	for len(t.reclaimed) > 0 {
+		pageID := t.reclaimed[len(t.reclaimed)-1]
+		p, err := t.BP.GetPage(pageID)
+		if err != nil {
+			return TID{}, false, err
+		}
+		slot, err := p.InsertTuple(tuple)
+		if err == storage.ErrNoSpace {
+			_ = t.BP.Unpin(p, false)
+			t.reclaimed = t.reclaimed[:len(t.reclaimed)-1]
+			continue
+		}
+		if err != nil {
+			_ = t.BP.Unpin(p, false)
+			return TID{}, false, err
+		}
+		if err := t.BP.Unpin(p, true); err != nil {
+			return TID{}, false, err
+		}
+		return TID{PageID: pageID, Slot: uint16(slot)}, true, nil
+	}
+	return TID{}, false, nil
+}
+
+// RewriteInto copies every row version of t into dst, an empty table,
+// filling its pages one after the other, and moves overflow values to the
+// overflow file of dst. Versions keep their transaction stamps but get new
+// TIDs. It returns how many versions it copied.
+func (t *Table) RewriteInto(dst *Table) (int, error) {
+	return t.rewrite(dst, func(raw []byte) ([]byte, error) { return t.moveOverflow(raw, dst) })
+}
+
+// RewriteRowsInto is RewriteInto for a schema change: each row version is
+// decoded with the schema of t, passed through f and encoded with the schema
+// of dst, keeping its transaction stamps.
+func (t *Table) RewriteRowsInto(dst *Table, f func(row []any) ([]any, error)) (int, error) {
+	return t.rewrite(dst, func(raw []byte) ([]byte, error) {
+		row, err := t.decodeRowWithOverflow(raw)
+		if err != nil {
+			return nil, err
+		}
+		if row, err = f(row); err != nil {
+			return nil, err
+		}
+		tuple, err := dst.encodeRowWithOverflow(row)
+		if err != nil {
+			return nil, err
+		}
+		if raw[0] != rowKindVersioned {
+			return tuple, nil
+		}
+		xmin, xmax, _ := rowVersion(raw)
+		return versionedTuple(xmin, xmax, tuple), nil
+	})
+}
+
+// rewrite appends the tuple move makes of each tuple of t to dst.
+func (t *Table) rewrite(dst *Table, move func(raw []byte) ([]byte, error)) (int, error) {
+	if err := t.ensureOpen(); err != nil {
+		return 0, err
+	}
+	if err := dst.ensureOpen(); err != nil {
+		return 0, err
+	}
+	if dst.readOnly {
+		return 0, ErrTableReadOnly
+	}
+	if dst.PageCount != 0 {
+		return 0, fmt.Errorf("heap: rewrite into non-empty table %s", dst.Name)
+	}
+
+	copied := 0
+	for pageID := uint32(0); pageID < t.PageCount; pageID++ {
+		tuples, err := t.pageTuples(pageID)
+		if err != nil {
+			return copied, err
+		}
+		for _, raw := range tuples {
+			tuple, err := move(raw)
+			if err != nil {
+				return copied, err
+			}
+			if err := dst.appendTuple(tuple); err != nil {
+				return copied, err
+			}
+			copied++
+		}
+	}
+	return copied, dst.Flush()
+}
+
+// pageTuples returns copies of the tuples stored on a page, visible or not.
+func (t *Table) pageTuples(pageID uint32) ([][]byte, error) {
+	p, err := t.BP.GetPage(pageID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = t.BP.Unpin(p, false) }()
+
+	var out [][]byte
+	moved := p.RedirectTargets()
+	for slot := 0; slot < p.NumSlots(); slot++ {
+		if slot < len(moved) && moved[slot] {
+			continue
+		}
+		raw, err := p.ReadTuple(slot)
+		if errors.Is(err, storage.ErrBadSlot) {
+			continue
+		}
+		if err != nil {
+			return nil, err
+		}
+		out = append(out, slices.Clone(raw))
+	}
+	return out, nil
+}
+
+// moveOverflow returns raw with its overflow value, if any, copied to the
+// overflow file of dst.
+func (t *Table) moveOverflow(raw []byte, dst *Table) ([]byte, error) {
+	ref := overflowRefOf(raw)
+	if ref == nil {
+		return raw, nil
+	}
+	if t.Overflow == nil || dst.Overflow == nil {
+		return nil, fmt.Errorf("heap: overflow manager is nil for table %s", t.Name)
+	}
+	data, err := t.Overflow.Read(*ref)
+	if err != nil {
+		return nil, err
+	}
+	moved, err := dst.Overflow.Write(data)
+	if err != nil {
+		return nil, err
+	}
+	if raw[0] != rowKindVersioned {
+		return overflowTuple(moved), nil
+	}
+	xmin, xmax, _ := rowVersion(raw)
+	return versionedTuple(xmin, xmax, overflowTuple(moved)), nil
+}
+
+// appendTuple stores tuple on the last page, or a new one when it is full.
+func (t *Table) appendTuple(tuple []byte) error {
+	if t.PageCount == 0 {
+		t.PageCount = 1
+	}
+	for {
+		p, err := t.BP.GetPage(t.PageCount - 1)
+		if err != nil {
+			return err
+		}
+		_, err = p.InsertTuple(tuple)
+		if err == storage.ErrNoSpace && p.NumSlots() > 0 {
+			_ = t.BP.Unpin(p, false)
+			t.PageCount++
+			continue
+		}
+		if err != nil {
+			_ = t.BP.Unpin(p, false)
+			return err
+		}
+		return t.BP.Unpin(p, true)
+	}
 }
 
 // Scan iterates through all visible rows in the table.
 // It skips deleted slots (ErrBadSlot) and returns other errors.
 func (t *Table) Scan(fn func(id TID, row []any) error) error {
+	return t.scan(t.readSnapshot(), fn)
+}
+
+func (t *Table) scan(snap *mvcc.Snapshot, fn func(id TID, row []any) error) error {
 	if err := t.ensureOpen(); err != nil {
 		return err
 	}
@@ -252,7 +669,12 @@ func (t *Table) Scan(fn func(id TID, row []any) error) error {
 			return err
 		}
 
+		moved := p.RedirectTargets()
 		for slot := 0; slot < p.NumSlots(); slot++ {
+			if slot < len(moved) && moved[slot] {
+				// Reached through the slot that redirects here.
+				continue
+			}
 			raw, err := p.ReadTuple(slot)
 			if errors.Is(err, storage.ErrBadSlot) {
 				// Deleted tuple -> skip
@@ -262,6 +684,9 @@ func (t *Table) Scan(fn func(id TID, row []any) error) error {
 				_ = t.BP.Unpin(p, false)
 				return err
 			}
+			if !visibleTo(snap, raw) {
+				continue
+			}
 
 			row, err := t.decodeRowWithOverflow(raw)
 			if err != nil {
@@ -281,6 +706,76 @@ func (t *Table) Scan(fn func(id TID, row []any) error) error {
 	return t.Flush()
 }
 
+// Iterator walks a table's visible rows one at a time. Each page is decoded
+// as a whole and unpinned before its rows are handed out, so the caller may
+// update or delete rows between calls to Next.
+type Iterator struct {
+	t      *Table
+	snap   *mvcc.Snapshot
+	pageID uint32
+	tids   []TID
+	rows   [][]any
+}
+
+// Iter returns an iterator positioned before the first row. It sees the
+// table as of the call.
+func (t *Table) Iter() *Iterator {
+	return &Iterator{t: t, snap: t.readSnapshot()}
+}
+
+// Next returns the next row; ok is false once the table is exhausted.
+func (it *Iterator) Next() (id TID, row []any, ok bool, err error) {
+	for len(it.rows) == 0 {
+		if err := it.t.ensureOpen(); err != nil {
+			return TID{}, nil, false, err
+		}
+		if it.pageID >= it.t.PageCount {
+			return TID{}, nil, false, nil
+		}
+		if err := it.loadPage(); err != nil {
+			return TID{}, nil, false, err
+		}
+	}
+	id, row = it.tids[0], it.rows[0]
+	it.tids, it.rows = it.tids[1:], it.rows[1:]
+	return id, row, true, nil
+}
+
+func (it *Iterator) loadPage() error {
+	pageID := it.pageID
+	it.pageID++
+
+	p, err := it.t.BP.GetPage(pageID)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = it.t.BP.Unpin(p, false) }()
+
+	moved := p.RedirectTargets()
+	for slot := 0; slot < p.NumSlots(); slot++ {
+		if slot < len(moved) && moved[slot] {
+			continue
+		}
+		raw, err := p.ReadTuple(slot)
+		if errors.Is(err, storage.ErrBadSlot) {
+			continue
+		}
+		if err != nil {
+			return err
+		}
+		if !visibleTo(it.snap, raw) {
+			continue
+		}
+		row, err := it.t.decodeRowWithOverflow(raw)
+		if err != nil {
+			return err
+		}
+		it.tids = append(it.tids, TID{PageID: pageID, Slot: uint16(slot)})
+		it.rows = append(it.rows, row)
+	}
+	return nil
+}
+
 func (t *Table) Flush() error {
 	if err := t.BP.FlushAll(); err != nil {
 		return err
@@ -305,8 +800,11 @@ func (t *Table) encodeRowWithOverflow(values []any) ([]byte, error) {
 	// 2) If small enough for inline storage: prefix rowKindInline.
 	// maxInline in Page.InsertTuple:
 	//   maxInline := PageSize - HeaderSize - SlotSize
-	// Here we need +1 for rowKind.
-	maxInline := storage.PageSize - storage.HeaderSize - storage.SlotSize
+	// Here we need +1 for rowKind, and room for a version header so the
+	// tuple still fits once a transaction stamps it (see mvcc.go), and on
+	// encrypted pages.
+	maxInline := storage.PageSize - storage.HeaderSize - storage.SlotSize - versionHeaderSize -
+		storage.EncryptionReserve
 	if len(encoded)+1 <= maxInline {
 		out := make([]byte, 0, len(encoded)+1)
 		out = append(out, rowKindInline)
@@ -324,7 +822,13 @@ func (t *Table) encodeRowWithOverflow(values []any) ([]byte, error) {
 		return nil, err
 	}
 
-	// 4) On heap page we store pointer only: kind + ref (FirstPageID + Length).
+	// 4) On heap page we store pointer only.
+	return overflowTuple(ref), nil
+}
+
+// overflowTuple is the tuple stored on the heap page for a row spilled to
+// overflow: kind + ref (FirstPageID + Length).
+func overflowTuple(ref storage.OverflowRef) []byte {
 	out := make([]byte, 0, 1+4+4)
 	out = append(out, rowKindOverflow)
 
@@ -336,7 +840,7 @@ func (t *Table) encodeRowWithOverflow(values []any) ([]byte, error) {
 	bx.PutU32(buf[:], ref.Length)
 	out = append(out, buf[:]...)
 
-	return out, nil
+	return out
 }
 
 // decodeRowWithOverflow decodes a tuple which may be inline or overflow-backed.
@@ -345,6 +849,14 @@ func (t *Table) decodeRowWithOverflow(raw []byte) ([]any, error) {
 		return nil, fmt.Errorf("heap: empty tuple raw")
 	}
 
+	if raw[0] == rowKindVersioned {
+		_, _, inner, err := splitVersioned(raw)
+		if err != nil {
+			return nil, err
+		}
+		raw = inner
+	}
+
 	kind := raw[0]
 	payload := raw[1:]
 
@@ -3,6 +3,7 @@ package heap
 import (
 	"fmt"
 	"path/filepath"
+	"strings"
 	"testing"
 
 	"github.com/stretchr/testify/require"
@@ -259,3 +260,230 @@ func TestTable_DeleteAndScan(t *testing.T) {
 	require.True(t, found[5])
 	require.Len(t, found, 4)
 }
+
+func TestTable_UpdateRedirect_NoDuplicatesOrGhosts(t *testing.T) {
+	tbl, _, _ := newTestTable(t, "users_redirect")
+
+	var tids []TID
+	for i := 1; i <= 3; i++ {
+		tid, err := tbl.Insert([]any{int64(i), fmt.Sprintf("user-%d", i), true})
+		require.NoError(t, err)
+		tids = append(tids, tid)
+	}
+
+	count := func() map[int64]int {
+		seen := make(map[int64]int)
+		require.NoError(t, tbl.Scan(func(_ TID, row []any) error {
+			seen[row[0].(int64)]++
+			return nil
+		}))
+		return seen
+	}
+
+	// Grow the row twice: each time it moves to a new slot behind a redirect.
+	require.NoError(t, tbl.Update(tids[0], []any{int64(1), "user-1-a-good-deal-longer", true}))
+	require.NoError(t, tbl.Update(tids[0], []any{int64(1), "user-1-longer-still-than-that-one", true}))
+	require.Equal(t, map[int64]int{1: 1, 2: 1, 3: 1}, count())
+
+	// Deleting through the original TID removes the moved tuple as well.
+	require.NoError(t, tbl.Delete(tids[0]))
+	require.Equal(t, map[int64]int{2: 1, 3: 1}, count())
+}
+
+func TestTable_Iter_DeleteWhileIterating(t *testing.T) {
+	tbl, _, _ := newTestTable(t, "users_iter")
+
+	const n = 300 // spans several pages
+	for i := range n {
+		_, err := tbl.Insert([]any{int64(i), fmt.Sprintf("user-%03d-%s", i, "padding-padding"), true})
+		require.NoError(t, err)
+	}
+	require.Greater(t, tbl.PageCount, uint32(1))
+
+	it := tbl.Iter()
+	seen := 0
+	for {
+		id, row, ok, err := it.Next()
+		require.NoError(t, err)
+		if !ok {
+			break
+		}
+		require.Equal(t, int64(seen), row[0])
+		seen++
+		if row[0].(int64)%2 == 0 {
+			require.NoError(t, tbl.Delete(id))
+		}
+	}
+	require.Equal(t, n, seen)
+
+	left := 0
+	require.NoError(t, tbl.Scan(func(_ TID, row []any) error {
+		require.Equal(t, int64(1), row[0].(int64)%2)
+		left++
+		return nil
+	}))
+	require.Equal(t, n/2, left)
+}
+
+func TestTable_InsertReusesDeletedSpace(t *testing.T) {
+	tbl, _, _ := newTestTable(t, "users_reuse")
+
+	row := func(i int) []any { return []any{int64(i), fmt.Sprintf("user-%04d-%s", i, "padding-padding"), true} }
+
+	const n = 600
+	var ids []TID
+	for i := range n {
+		id, err := tbl.Insert(row(i))
+		require.NoError(t, err)
+		ids = append(ids, id)
+	}
+	pages := tbl.PageCount
+	require.Greater(t, pages, uint32(2))
+
+	// Delete and refill the same number of rows a few times: the table must
+	// not grow past its original size.
+	for round := range 3 {
+		for _, id := range ids[:n/2] {
+			require.NoError(t, tbl.Delete(id))
+		}
+		ids = ids[n/2:]
+		for i := range n / 2 {
+			id, err := tbl.Insert(row(n*(round+1) + i))
+			require.NoError(t, err)
+			ids = append(ids, id)
+		}
+		require.LessOrEqual(t, tbl.PageCount, pages+1, "round %d", round)
+	}
+
+	count := 0
+	require.NoError(t, tbl.Scan(func(_ TID, _ []any) error {
+		count++
+		return nil
+	}))
+	require.Equal(t, n, count)
+}
+
+func TestTable_InsertBatch(t *testing.T) {
+	tbl, _, _ := newTestTable(t, "users_batch")
+	one, _, _ := newTestTable(t, "users_one")
+
+	row := func(i int) []any { return []any{int64(i), fmt.Sprintf("user-%04d-%s", i, "padding-padding"), true} }
+
+	const n = 600
+	rows := make([][]any, n)
+	for i := range rows {
+		rows[i] = row(i)
+		_, err := one.Insert(rows[i])
+		require.NoError(t, err)
+	}
+	ids, err := tbl.InsertBatch(rows)
+	require.NoError(t, err)
+	require.Len(t, ids, n)
+	require.Equal(t, one.PageCount, tbl.PageCount, "a batch fills pages as single inserts do")
+	for i, id := range ids {
+		got, err := tbl.Get(id)
+		require.NoError(t, err)
+		require.Equal(t, rows[i], got)
+	}
+
+	// Deleted space is reused, and a row that does not fit the schema
+	// inserts none of the batch.
+	for _, id := range ids[:10] {
+		require.NoError(t, tbl.Delete(id))
+	}
+	pages := tbl.PageCount
+	more, err := tbl.InsertBatch([][]any{row(n), row(n + 1)})
+	require.NoError(t, err)
+	require.Equal(t, ids[9].PageID, more[0].PageID)
+	require.Equal(t, pages, tbl.PageCount)
+
+	_, err = tbl.InsertBatch([][]any{row(n + 2), {"not-an-id", "x", true}})
+	require.Error(t, err)
+	count := 0
+	require.NoError(t, tbl.Scan(func(_ TID, _ []any) error {
+		count++
+		return nil
+	}))
+	require.Equal(t, n-10+2, count)
+}
+
+func TestTable_RewriteInto(t *testing.T) {
+	src, _, _ := newTestTable(t, "users_src")
+	dst, _, _ := newTestTable(t, "users_dst")
+
+	big := strings.Repeat("x", 3*storage.PageSize)
+	want := make(map[int64]string)
+	var ids []TID
+	for i := range 400 {
+		name := fmt.Sprintf("user-%04d-padding-padding", i)
+		if i%100 == 0 {
+			name = big
+		}
+		id, err := src.Insert([]any{int64(i), name, true})
+		require.NoError(t, err)
+		ids = append(ids, id)
+		want[int64(i)] = name
+	}
+	for i, id := range ids {
+		if i%4 != 0 {
+			require.NoError(t, src.Delete(id))
+			delete(want, int64(i))
+		}
+	}
+	require.NoError(t, src.Update(ids[4], []any{int64(4), "renamed", false}))
+	want[4] = "renamed"
+
+	copied, err := src.RewriteInto(dst)
+	require.NoError(t, err)
+	require.Equal(t, len(want), copied)
+	require.Less(t, dst.PageCount, src.PageCount)
+
+	got := make(map[int64]string)
+	require.NoError(t, dst.Scan(func(_ TID, row []any) error {
+		got[row[0].(int64)] = row[1].(string)
+		return nil
+	}))
+	require.Equal(t, want, got)
+
+	_, err = src.RewriteInto(dst)
+	require.Error(t, err)
+}
+
+func TestTable_RewriteRowsInto(t *testing.T) {
+	src, _, _ := newTestTable(t, "users_src")
+	dst, _, _ := newTestTable(t, "users_dst")
+	dst.Schema = record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64},
+		{Name: "name", Type: record.ColText},
+		{Name: "age", Type: record.ColInt64, Nullable: true},
+	}}
+
+	big := strings.Repeat("x", 3*storage.PageSize)
+	for i := range 10 {
+		name := fmt.Sprintf("user-%d", i)
+		if i == 5 {
+			name = big
+		}
+		_, err := src.Insert([]any{int64(i), name, true})
+		require.NoError(t, err)
+	}
+
+	copied, err := src.RewriteRowsInto(dst, func(row []any) ([]any, error) {
+		return []any{row[0], row[1], row[0].(int64) * 10}, nil
+	})
+	require.NoError(t, err)
+	require.Equal(t, 10, copied)
+
+	n := 0
+	require.NoError(t, dst.Scan(func(_ TID, row []any) error {
+		id := row[0].(int64)
+		require.Len(t, row, 3)
+		require.Equal(t, id*10, row[2])
+		if id == 5 {
+			require.Equal(t, big, row[1])
+		}
+		n++
+		return nil
+	}))
+	require.Equal(t, 10, n)
+}
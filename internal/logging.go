@@ -0,0 +1,72 @@
+package internal
+
+import (
+	"fmt"
+	"io"
+	"log/slog"
+	"strings"
+)
+
+// NewLogger builds the logger of server.log_level and server.log_format:
+// levels debug, info (default), warn and error; formats text (default) and
+// json. server.debug alone still means debug.
+func (c *NovaSqlConfig) NewLogger(w io.Writer) (*slog.Logger, error) {
+	lv, err := c.LogLevel()
+	if err != nil {
+		return nil, err
+	}
+	return c.NewLevelLogger(w, lv)
+}
+
+// NewLevelLogger is NewLogger logging at level instead of server.log_level,
+// so a slog.LevelVar can move it when the config is reloaded.
+func (c *NovaSqlConfig) NewLevelLogger(w io.Writer, level slog.Leveler) (*slog.Logger, error) {
+	format := ""
+	if c != nil {
+		format = c.Server.LogFormat
+	}
+	if err := checkLogFormat(format); err != nil {
+		return nil, err
+	}
+	opts := &slog.HandlerOptions{Level: level}
+	if strings.ToLower(format) == "json" {
+		return slog.New(slog.NewJSONHandler(w, opts)), nil
+	}
+	return slog.New(slog.NewTextHandler(w, opts)), nil
+}
+
+// checkLogFormat rejects a server.log_format other than text and json.
+func checkLogFormat(format string) *ConfigError {
+	switch strings.ToLower(format) {
+	case "", "text", "json":
+		return nil
+	}
+	return &ConfigError{Key: "server.log_format", Err: fmt.Errorf("unknown format %q (want text or json)", format)}
+}
+
+// LogLevel is the level of server.log_level, or of server.debug when it is
+// unset.
+func (c *NovaSqlConfig) LogLevel() (slog.Level, error) {
+	level, debug := "", false
+	if c != nil {
+		level, debug = c.Server.LogLevel, c.Server.Debug
+	}
+	if level == "" && debug {
+		level = "debug"
+	}
+
+	switch strings.ToLower(level) {
+	case "debug":
+		return slog.LevelDebug, nil
+	case "", "info":
+		return slog.LevelInfo, nil
+	case "warn", "warning":
+		return slog.LevelWarn, nil
+	case "error":
+		return slog.LevelError, nil
+	}
+	return 0, &ConfigError{
+		Key: "server.log_level",
+		Err: fmt.Errorf("unknown level %q (want debug, info, warn or error)", level),
+	}
+}
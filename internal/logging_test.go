@@ -0,0 +1,58 @@
+package internal
+
+import (
+	"bytes"
+	"log/slog"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestNewLogger(t *testing.T) {
+	var buf bytes.Buffer
+	cfg := &NovaSqlConfig{}
+	cfg.Server.LogFormat = "json"
+	log, err := cfg.NewLogger(&buf)
+	require.NoError(t, err)
+	log.Debug("hidden")
+	log.Info("query", "sql", "SELECT 1;")
+	require.NotContains(t, buf.String(), "hidden")
+	require.Contains(t, buf.String(), `"msg":"query","sql":"SELECT 1;"`)
+
+	buf.Reset()
+	cfg.Server.LogFormat = ""
+	cfg.Server.Debug = true
+	log, err = cfg.NewLogger(&buf)
+	require.NoError(t, err)
+	log.Debug("shown")
+	require.Contains(t, buf.String(), "level=DEBUG msg=shown")
+
+	cfg.Server.LogLevel = "loud"
+	_, err = cfg.NewLogger(&buf)
+	require.ErrorContains(t, err, "server.log_level")
+	cfg.Server.LogLevel, cfg.Server.LogFormat = "warn", "xml"
+	_, err = cfg.NewLogger(&buf)
+	require.ErrorContains(t, err, "server.log_format")
+}
+
+func TestNewLevelLogger(t *testing.T) {
+	var buf bytes.Buffer
+	cfg := &NovaSqlConfig{}
+	cfg.Server.LogLevel = "warn"
+	lv, err := cfg.LogLevel()
+	require.NoError(t, err)
+	var level slog.LevelVar
+	level.Set(lv)
+	log, err := cfg.NewLevelLogger(&buf, &level)
+	require.NoError(t, err)
+	log.Info("hidden")
+	require.Empty(t, buf.String())
+
+	// A reload moves the level of the logger already handed out.
+	cfg.Server.LogLevel = "info"
+	lv, err = cfg.LogLevel()
+	require.NoError(t, err)
+	level.Set(lv)
+	log.Info("shown")
+	require.Contains(t, buf.String(), "level=INFO msg=shown")
+}
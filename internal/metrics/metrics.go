@@ -0,0 +1,163 @@
+// Package metrics counts what the engine does: buffer pool traffic, WAL
+// writes and fsyncs, transactions and statements. Database.Metrics reads a
+// Registry and the server exposes one in the Prometheus text format.
+//
+// A Registry is safe for concurrent use, and a nil *Registry ignores every
+// update, so instrumented code never has to check for one.
+package metrics
+
+import (
+	"fmt"
+	"io"
+	"sync/atomic"
+	"time"
+)
+
+// Counter names one of the counters of a Registry.
+type Counter int
+
+const (
+	PoolHits     Counter = iota // page lookups served by the buffer pool
+	PoolMisses                  // page lookups that had to read the page
+	PagesRead                   // pages read from data files (misses, prefetches, batch reads)
+	PagesWritten                // pages written to data files
+	WALBytes                    // bytes appended to the WAL
+	WALSyncs                    // fsyncs of the WAL
+	TxCommits                   // transactions committed (page and row level)
+	TxAborts                    // transactions rolled back or failed to commit
+	numCounters
+)
+
+var counterInfo = [numCounters]struct{ name, help string }{
+	PoolHits:     {"buffer_pool_hits_total", "Page lookups served by the buffer pool."},
+	PoolMisses:   {"buffer_pool_misses_total", "Page lookups that had to read the page."},
+	PagesRead:    {"pages_read_total", "Pages read from data files."},
+	PagesWritten: {"pages_written_total", "Pages written to data files."},
+	WALBytes:     {"wal_bytes_total", "Bytes appended to the WAL."},
+	WALSyncs:     {"wal_fsyncs_total", "Fsyncs of the WAL."},
+	TxCommits:    {"tx_commits_total", "Transactions committed."},
+	TxAborts:     {"tx_aborts_total", "Transactions rolled back or failed to commit."},
+}
+
+// QueryBuckets are the upper bounds of the statement latency histogram.
+var QueryBuckets = []time.Duration{
+	100 * time.Microsecond, 500 * time.Microsecond,
+	time.Millisecond, 5 * time.Millisecond, 10 * time.Millisecond, 50 * time.Millisecond,
+	100 * time.Millisecond, 500 * time.Millisecond, time.Second, 5 * time.Second, 10 * time.Second,
+}
+
+// Registry holds the counters of one database handle, or of all the handles
+// it is shared by.
+type Registry struct {
+	counters [numCounters]atomic.Uint64
+
+	queries     atomic.Uint64
+	queryErrors atomic.Uint64
+	queryNanos  atomic.Uint64
+	buckets     []atomic.Uint64 // per QueryBuckets bound, not cumulative
+}
+
+// New returns a Registry with every counter at zero.
+func New() *Registry {
+	return &Registry{buckets: make([]atomic.Uint64, len(QueryBuckets))}
+}
+
+// Add adds n to counter c.
+func (r *Registry) Add(c Counter, n uint64) {
+	if r == nil {
+		return
+	}
+	r.counters[c].Add(n)
+}
+
+// ObserveQuery records a statement that took d; failed counts it as an error
+// too.
+func (r *Registry) ObserveQuery(d time.Duration, failed bool) {
+	if r == nil {
+		return
+	}
+	r.queries.Add(1)
+	if failed {
+		r.queryErrors.Add(1)
+	}
+	r.queryNanos.Add(uint64(max(d, 0)))
+	for i, bound := range QueryBuckets {
+		if d <= bound {
+			r.buckets[i].Add(1)
+			break
+		}
+	}
+}
+
+// Snapshot is the state of a Registry at one point in time.
+type Snapshot struct {
+	Counters [numCounters]uint64
+
+	Queries     uint64
+	QueryErrors uint64
+	QueryTime   time.Duration // total time spent in statements
+	// QueryBuckets[i] counts the statements that took at most
+	// metrics.QueryBuckets[i] (cumulative, as Prometheus wants them).
+	QueryBuckets []uint64
+}
+
+// Snapshot reads r; the zero Snapshot for nil.
+func (r *Registry) Snapshot() Snapshot {
+	s := Snapshot{QueryBuckets: make([]uint64, len(QueryBuckets))}
+	if r == nil {
+		return s
+	}
+	for c := range r.counters {
+		s.Counters[c] = r.counters[c].Load()
+	}
+	s.Queries = r.queries.Load()
+	s.QueryErrors = r.queryErrors.Load()
+	s.QueryTime = time.Duration(r.queryNanos.Load())
+	var sum uint64
+	for i := range r.buckets {
+		sum += r.buckets[i].Load()
+		s.QueryBuckets[i] = sum
+	}
+	return s
+}
+
+// Get is the value of counter c.
+func (s Snapshot) Get(c Counter) uint64 { return s.Counters[c] }
+
+// HitRatio is the share of page lookups the buffer pool served, 0 before
+// any lookup.
+func (s Snapshot) HitRatio() float64 {
+	hits, misses := s.Counters[PoolHits], s.Counters[PoolMisses]
+	if hits+misses == 0 {
+		return 0
+	}
+	return float64(hits) / float64(hits+misses)
+}
+
+// WritePrometheus writes s in the Prometheus text exposition format, every
+// metric prefixed with "novasql_".
+func (s Snapshot) WritePrometheus(w io.Writer) error {
+	var err error
+	printf := func(format string, args ...any) {
+		if err == nil {
+			_, err = fmt.Fprintf(w, format, args...)
+		}
+	}
+	for c, info := range counterInfo {
+		printf("# HELP novasql_%s %s\n# TYPE novasql_%s counter\nnovasql_%s %d\n",
+			info.name, info.help, info.name, info.name, s.Counters[c])
+	}
+	printf("# HELP novasql_buffer_pool_hit_ratio Share of page lookups served by the buffer pool.\n")
+	printf("# TYPE novasql_buffer_pool_hit_ratio gauge\nnovasql_buffer_pool_hit_ratio %g\n", s.HitRatio())
+	printf("# HELP novasql_query_errors_total Statements that failed.\n")
+	printf("# TYPE novasql_query_errors_total counter\nnovasql_query_errors_total %d\n", s.QueryErrors)
+	printf("# HELP novasql_query_duration_seconds Statement latency.\n")
+	printf("# TYPE novasql_query_duration_seconds histogram\n")
+	for i, bound := range QueryBuckets {
+		printf("novasql_query_duration_seconds_bucket{le=\"%g\"} %d\n", bound.Seconds(), s.QueryBuckets[i])
+	}
+	printf("novasql_query_duration_seconds_bucket{le=\"+Inf\"} %d\n", s.Queries)
+	printf("novasql_query_duration_seconds_sum %g\n", s.QueryTime.Seconds())
+	printf("novasql_query_duration_seconds_count %d\n", s.Queries)
+	return err
+}
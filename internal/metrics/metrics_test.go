@@ -0,0 +1,44 @@
+package metrics
+
+import (
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestRegistry(t *testing.T) {
+	r := New()
+	r.Add(PoolHits, 3)
+	r.Add(PoolMisses, 1)
+	r.Add(WALBytes, 100)
+	r.ObserveQuery(50*time.Microsecond, false)
+	r.ObserveQuery(2*time.Millisecond, true)
+	r.ObserveQuery(time.Minute, false)
+
+	s := r.Snapshot()
+	require.Equal(t, uint64(3), s.Get(PoolHits))
+	require.Equal(t, uint64(100), s.Get(WALBytes))
+	require.InDelta(t, 0.75, s.HitRatio(), 1e-9)
+	require.Equal(t, uint64(3), s.Queries)
+	require.Equal(t, uint64(1), s.QueryErrors)
+	require.Equal(t, uint64(1), s.QueryBuckets[0])
+	require.Equal(t, uint64(2), s.QueryBuckets[len(QueryBuckets)-1])
+
+	var b strings.Builder
+	require.NoError(t, s.WritePrometheus(&b))
+	out := b.String()
+	require.Contains(t, out, "novasql_buffer_pool_hits_total 3\n")
+	require.Contains(t, out, "novasql_buffer_pool_hit_ratio 0.75\n")
+	require.Contains(t, out, "novasql_query_duration_seconds_bucket{le=\"0.005\"} 2\n")
+	require.Contains(t, out, "novasql_query_duration_seconds_bucket{le=\"+Inf\"} 3\n")
+	require.Contains(t, out, "novasql_query_duration_seconds_count 3\n")
+
+	// A nil registry ignores updates and reads as zero.
+	var none *Registry
+	none.Add(TxCommits, 1)
+	none.ObserveQuery(time.Second, false)
+	require.Zero(t, none.Snapshot().Queries)
+	require.Zero(t, none.Snapshot().HitRatio())
+}
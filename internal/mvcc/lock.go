@@ -0,0 +1,205 @@
+package mvcc
+
+import (
+	"errors"
+	"sync"
+	"time"
+)
+
+var (
+	ErrDeadlock    = errors.New("mvcc: deadlock detected, transaction chosen as victim")
+	ErrLockTimeout = errors.New("mvcc: lock wait timeout")
+)
+
+// DefaultLockTimeout is how long Lock waits when no timeout is configured.
+const DefaultLockTimeout = 5 * time.Second
+
+type LockMode uint8
+
+const (
+	LockShared LockMode = iota + 1
+	LockExclusive
+)
+
+// RowKey names one row: a relation and the row's position in it.
+type RowKey struct {
+	Rel  string
+	Page uint32
+	Slot uint16
+}
+
+// LockManager grants shared/exclusive row locks to transactions. Locks are
+// held until the transaction calls ReleaseAll (Tx.Commit/Tx.Abort do).
+//
+// A transaction that has to wait adds edges to the waits-for graph from
+// itself to the holders that block it. When that closes a cycle, the
+// youngest transaction of the cycle (highest XID) is the victim: its Lock
+// returns ErrDeadlock, and the others keep waiting for it to release.
+type LockManager struct {
+	mu       sync.Mutex
+	timeout  time.Duration
+	locks    map[RowKey]map[XID]LockMode
+	held     map[XID][]RowKey
+	waitsFor map[XID][]XID
+	victims  map[XID]bool
+	changed  chan struct{} // closed and replaced whenever locks or victims change
+}
+
+func NewLockManager() *LockManager {
+	return &LockManager{
+		timeout:  DefaultLockTimeout,
+		locks:    make(map[RowKey]map[XID]LockMode),
+		held:     make(map[XID][]RowKey),
+		waitsFor: make(map[XID][]XID),
+		victims:  make(map[XID]bool),
+		changed:  make(chan struct{}),
+	}
+}
+
+// SetTimeout changes how long Lock waits; d <= 0 restores the default.
+func (lm *LockManager) SetTimeout(d time.Duration) {
+	lm.mu.Lock()
+	defer lm.mu.Unlock()
+	if d <= 0 {
+		d = DefaultLockTimeout
+	}
+	lm.timeout = d
+}
+
+// Lock blocks until xid holds key in mode (or stronger). Holding a shared
+// lock alone upgrades it in place.
+func (lm *LockManager) Lock(xid XID, key RowKey, mode LockMode) error {
+	lm.mu.Lock()
+	defer lm.mu.Unlock()
+
+	var deadline <-chan time.Time
+	for {
+		if lm.victims[xid] {
+			delete(lm.victims, xid)
+			delete(lm.waitsFor, xid)
+			return ErrDeadlock
+		}
+		blockers := lm.blockers(xid, key, mode)
+		if len(blockers) == 0 {
+			lm.grant(xid, key, mode)
+			delete(lm.waitsFor, xid)
+			return nil
+		}
+
+		lm.waitsFor[xid] = blockers
+		if cycle := lm.cycleFrom(xid); cycle != nil {
+			victim := cycle[0]
+			for _, x := range cycle {
+				victim = max(victim, x)
+			}
+			if victim == xid {
+				delete(lm.waitsFor, xid)
+				return ErrDeadlock
+			}
+			lm.victims[victim] = true
+			lm.notify()
+		}
+
+		if deadline == nil {
+			timer := time.NewTimer(lm.timeout)
+			defer timer.Stop()
+			deadline = timer.C
+		}
+		changed := lm.changed
+		lm.mu.Unlock()
+		select {
+		case <-changed:
+			lm.mu.Lock()
+		case <-deadline:
+			lm.mu.Lock()
+			delete(lm.waitsFor, xid)
+			return ErrLockTimeout
+		}
+	}
+}
+
+// ReleaseAll drops every lock of xid and wakes the waiters.
+func (lm *LockManager) ReleaseAll(xid XID) {
+	lm.mu.Lock()
+	defer lm.mu.Unlock()
+	for _, key := range lm.held[xid] {
+		holders := lm.locks[key]
+		delete(holders, xid)
+		if len(holders) == 0 {
+			delete(lm.locks, key)
+		}
+	}
+	delete(lm.held, xid)
+	delete(lm.waitsFor, xid)
+	delete(lm.victims, xid)
+	lm.notify()
+}
+
+// Held returns the mode in which xid holds key (0 = not held).
+func (lm *LockManager) Held(xid XID, key RowKey) LockMode {
+	lm.mu.Lock()
+	defer lm.mu.Unlock()
+	return lm.locks[key][xid]
+}
+
+// blockers lists the holders of key that conflict with xid taking mode.
+func (lm *LockManager) blockers(xid XID, key RowKey, mode LockMode) []XID {
+	var out []XID
+	for holder, held := range lm.locks[key] {
+		if holder == xid {
+			continue
+		}
+		if mode == LockExclusive || held == LockExclusive {
+			out = append(out, holder)
+		}
+	}
+	return out
+}
+
+func (lm *LockManager) grant(xid XID, key RowKey, mode LockMode) {
+	holders := lm.locks[key]
+	if holders == nil {
+		holders = make(map[XID]LockMode)
+		lm.locks[key] = holders
+	}
+	cur, ok := holders[xid]
+	if !ok {
+		lm.held[xid] = append(lm.held[xid], key)
+	}
+	holders[xid] = max(cur, mode)
+}
+
+// cycleFrom returns the transactions on a waits-for cycle through start, or
+// nil when there is none.
+func (lm *LockManager) cycleFrom(start XID) []XID {
+	var path []XID
+	onPath := make(map[XID]bool)
+	seen := make(map[XID]bool)
+
+	var visit func(x XID) bool
+	visit = func(x XID) bool {
+		path = append(path, x)
+		onPath[x] = true
+		for _, next := range lm.waitsFor[x] {
+			if next == start {
+				return true
+			}
+			if !onPath[next] && !seen[next] && visit(next) {
+				return true
+			}
+		}
+		seen[x] = true
+		onPath[x] = false
+		path = path[:len(path)-1]
+		return false
+	}
+	if visit(start) {
+		return path
+	}
+	return nil
+}
+
+func (lm *LockManager) notify() {
+	close(lm.changed)
+	lm.changed = make(chan struct{})
+}
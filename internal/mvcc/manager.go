@@ -0,0 +1,406 @@
+package mvcc
+
+import (
+	"errors"
+	"log/slog"
+	"os"
+	"slices"
+	"sync"
+	"time"
+
+	"github.com/tuannm99/novasql/internal/metrics"
+)
+
+var (
+	ErrTxDone        = errors.New("mvcc: transaction already committed or aborted")
+	ErrWriteConflict = errors.New("mvcc: row was changed by a concurrent transaction")
+	ErrReadOnly      = errors.New("mvcc: transaction log is read-only")
+)
+
+// XID identifies a transaction. XID 0 is the bootstrap transaction: rows
+// written outside any transaction (and rows from before MVCC) carry it and
+// are visible to everyone.
+type XID uint64
+
+// Status is the outcome of a transaction as recorded in the status log.
+type Status uint8
+
+const (
+	StatusInProgress Status = iota
+	StatusCommitted
+	StatusAborted
+)
+
+// Manager hands out XIDs and records their status in a log file with one
+// byte per XID (the byte at offset x is the status of x). Transactions that
+// were still in progress when the file was last closed are aborted on open.
+type Manager struct {
+	mu       sync.Mutex
+	f        File        // nil for read-only managers
+	status   []Status    // indexed by XID
+	active   map[XID]XID // running transaction -> xmin of its snapshot
+	readOnly bool
+
+	locks    *LockManager
+	log      Log                           // flushed before a commit is recorded; see SetLog
+	metrics  *metrics.Registry             // commits and aborts; see SetMetrics
+	onFinish func(xid XID, committed bool) // see SetFinishHook
+}
+
+// Log is the write-ahead log that the pages written by transactions go to.
+type Log interface {
+	LastLSN() uint64
+	Flush(upto uint64) error
+}
+
+// File is the status log as Manager writes it; *os.File is one.
+type File interface {
+	WriteAt(b []byte, off int64) (int, error)
+	Sync() error
+	Close() error
+}
+
+// OpenFunc opens the status log as os.OpenFile does.
+type OpenFunc func(name string, flag int, perm os.FileMode) (File, error)
+
+// Open loads the status log at path, creating it unless readOnly.
+func Open(path string, readOnly bool) (*Manager, error) {
+	return OpenWith(path, readOnly, func(name string, flag int, perm os.FileMode) (File, error) {
+		f, err := os.OpenFile(name, flag, perm)
+		if err != nil {
+			return nil, err
+		}
+		return f, nil
+	})
+}
+
+// OpenWith is Open with the status log opened for writing by open, such as
+// a storage engine that injects faults.
+func OpenWith(path string, readOnly bool, open OpenFunc) (*Manager, error) {
+	m := &Manager{active: make(map[XID]XID), readOnly: readOnly, locks: NewLockManager()}
+
+	data, err := os.ReadFile(path)
+	if err != nil && !errors.Is(err, os.ErrNotExist) {
+		return nil, err
+	}
+	m.status = loadStatus(data)
+	if readOnly {
+		return m, nil
+	}
+
+	f, err := open(path, os.O_RDWR|os.O_CREATE, 0o644)
+	if err != nil {
+		return nil, err
+	}
+	m.f = f
+	// Record the aborts loadStatus made, so that copies of the log agree.
+	for x, b := range data {
+		if x > 0 && Status(b) == StatusInProgress {
+			if err := m.writeStatus(XID(x), StatusAborted); err != nil {
+				_ = f.Close()
+				return nil, err
+			}
+		}
+	}
+	if err := m.writeStatus(0, StatusCommitted); err != nil {
+		_ = f.Close()
+		return nil, err
+	}
+	return m, nil
+}
+
+// loadStatus reads the status log data. Transactions in progress in it
+// count as aborted: they crashed before commit, or are still running in
+// another process that owns the log.
+func loadStatus(data []byte) []Status {
+	status := make([]Status, max(len(data), 1))
+	for i, b := range data {
+		status[i] = Status(b)
+		if status[i] == StatusInProgress {
+			status[i] = StatusAborted
+		}
+	}
+	status[0] = StatusCommitted
+	return status
+}
+
+// Refresh reloads a read-only manager from the status log at path, which
+// another process keeps writing, such as the replication of a primary.
+// Snapshots taken afterwards see the transactions committed since. A
+// read-write manager owns its log and is left as it is.
+func (m *Manager) Refresh(path string) error {
+	if !m.readOnly {
+		return nil
+	}
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return err
+	}
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	m.status = loadStatus(data)
+	return nil
+}
+
+func (m *Manager) Close() error {
+	if m == nil {
+		return nil
+	}
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	if m.f == nil {
+		return nil
+	}
+	err := m.f.Close()
+	m.f = nil
+	return err
+}
+
+// SetLockTimeout sets how long a row lock request waits; d <= 0 is
+// DefaultLockTimeout.
+func (m *Manager) SetLockTimeout(d time.Duration) { m.locks.SetTimeout(d) }
+
+// Begin starts a transaction and takes its snapshot.
+func (m *Manager) Begin() (*Tx, error) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	if m.readOnly || m.f == nil {
+		return nil, ErrReadOnly
+	}
+
+	xid := XID(len(m.status))
+	if err := m.writeStatus(xid, StatusInProgress); err != nil {
+		return nil, err
+	}
+	m.status = append(m.status, StatusInProgress)
+	snap := m.snapshotLocked(xid)
+	m.active[xid] = snap.xmin
+	return &Tx{m: m, xid: xid, snap: snap}, nil
+}
+
+// Snapshot is a fresh read-only snapshot: everything committed so far.
+func (m *Manager) Snapshot() *Snapshot {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	return m.snapshotLocked(0)
+}
+
+func (m *Manager) snapshotLocked(own XID) *Snapshot {
+	s := &Snapshot{m: m, own: own, xmax: XID(len(m.status))}
+	s.xmin = s.xmax
+	for x := range m.active {
+		s.active = append(s.active, x)
+		s.xmin = min(s.xmin, x)
+	}
+	slices.Sort(s.active)
+	return s
+}
+
+// Status reports the recorded status of xid. Unknown XIDs are aborted.
+func (m *Manager) Status(xid XID) Status {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	if int(xid) >= len(m.status) {
+		return StatusAborted
+	}
+	return m.status[xid]
+}
+
+// Horizon is the oldest XID any running transaction may still need: a row
+// version deleted by a transaction below it that committed is invisible to
+// every snapshot, present or future, and can be vacuumed.
+func (m *Manager) Horizon() XID {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	h := XID(len(m.status))
+	for _, xmin := range m.active {
+		h = min(h, xmin)
+	}
+	return h
+}
+
+// Running is the number of transactions begun and not yet finished.
+func (m *Manager) Running() int {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	return len(m.active)
+}
+
+// finish records the outcome of xid. Commits are fsynced before returning.
+func (m *Manager) finish(xid XID, st Status) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	done := false
+	defer func() {
+		if m.onFinish != nil {
+			m.onFinish(xid, done && st == StatusCommitted)
+		}
+	}()
+	if m.f == nil {
+		return ErrReadOnly
+	}
+	if err := m.writeStatus(xid, st); err != nil {
+		return err
+	}
+	if st == StatusCommitted {
+		if err := m.f.Sync(); err != nil {
+			return err
+		}
+		m.metrics.Add(metrics.TxCommits, 1)
+	} else {
+		m.metrics.Add(metrics.TxAborts, 1)
+	}
+	m.status[xid] = st
+	delete(m.active, xid)
+	done = true
+	slog.Debug("mvcc: transaction finished", "xid", xid, "committed", st == StatusCommitted)
+	return nil
+}
+
+// SetFinishHook makes the manager call fn whenever a transaction ends, with
+// whether it committed (a commit that fails to reach the disk did not). fn
+// runs with the manager locked, in the order the transactions end, and must
+// not call back into it.
+func (m *Manager) SetFinishHook(fn func(xid XID, committed bool)) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	m.onFinish = fn
+}
+
+// SetLog makes commits flush l before they are recorded, so that a commit
+// that survives a crash never lacks the row versions it wrote.
+func (m *Manager) SetLog(l Log) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	m.log = l
+}
+
+// flushLog makes the log durable up to the last record of a committing
+// transaction. Every page it wrote was logged when it was unpinned, before
+// the commit, so the last LSN of the log bounds them all.
+func (m *Manager) flushLog() error {
+	m.mu.Lock()
+	l := m.log
+	m.mu.Unlock()
+	if l == nil {
+		return nil
+	}
+	return l.Flush(l.LastLSN())
+}
+
+// SetMetrics makes the manager count the transactions that commit and abort
+// into r.
+func (m *Manager) SetMetrics(r *metrics.Registry) {
+	if m == nil {
+		return
+	}
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	m.metrics = r
+}
+
+func (m *Manager) writeStatus(xid XID, st Status) error {
+	_, err := m.f.WriteAt([]byte{byte(st)}, int64(xid))
+	return err
+}
+
+// Snapshot decides which row versions a reader sees: those written by
+// transactions that committed before it was taken, plus its own writes.
+type Snapshot struct {
+	m      *Manager
+	own    XID   // transaction that owns the snapshot (0 = none)
+	xmin   XID   // every XID below this had finished when it was taken
+	xmax   XID   // first XID not yet assigned when it was taken
+	active []XID // in progress when it was taken (sorted)
+}
+
+// Sees reports whether the effects of xid are visible to the snapshot.
+func (s *Snapshot) Sees(xid XID) bool {
+	if xid == 0 || xid == s.own {
+		return true
+	}
+	if xid >= s.xmax {
+		return false
+	}
+	if xid >= s.xmin {
+		if _, found := slices.BinarySearch(s.active, xid); found {
+			return false
+		}
+	}
+	return s.m.Status(xid) == StatusCommitted
+}
+
+// Visible reports whether a row version created by xmin and deleted by xmax
+// (0 = not deleted) is visible to the snapshot.
+func (s *Snapshot) Visible(xmin, xmax XID) bool {
+	return s.Sees(xmin) && (xmax == 0 || !s.Sees(xmax))
+}
+
+// Tx is a snapshot-isolation transaction. Reads see the snapshot taken by
+// Begin; writes conflict (first updater wins) with any concurrent
+// transaction that changed the same row.
+type Tx struct {
+	m    *Manager
+	xid  XID
+	snap *Snapshot
+	done bool
+}
+
+func (tx *Tx) XID() XID            { return tx.xid }
+func (tx *Tx) Snapshot() *Snapshot { return tx.snap }
+
+// CanDelete reports whether tx may set the xmax of a row version whose xmax
+// is currently cur. A version already deleted by a committed or running
+// transaction other than tx is a write conflict.
+func (tx *Tx) CanDelete(cur XID) error {
+	if tx.done {
+		return ErrTxDone
+	}
+	if cur == 0 || cur == tx.xid {
+		return nil
+	}
+	if tx.m.Status(cur) == StatusAborted {
+		return nil
+	}
+	return ErrWriteConflict
+}
+
+// Lock takes a row lock, waiting for conflicting holders to finish. It fails
+// with ErrDeadlock when tx is chosen as a deadlock victim and with
+// ErrLockTimeout after the lock wait timeout; tx should then be aborted.
+func (tx *Tx) Lock(key RowKey, mode LockMode) error {
+	if tx.done {
+		return ErrTxDone
+	}
+	return tx.m.locks.Lock(tx.xid, key, mode)
+}
+
+// Commit makes the transaction's writes visible to snapshots taken
+// afterwards and releases its row locks. If the log cannot be flushed the
+// transaction is aborted instead.
+func (tx *Tx) Commit() error {
+	if tx.done {
+		return ErrTxDone
+	}
+	tx.done = true
+	defer tx.m.locks.ReleaseAll(tx.xid)
+	if err := tx.m.flushLog(); err != nil {
+		_ = tx.m.finish(tx.xid, StatusAborted)
+		return err
+	}
+	return tx.m.finish(tx.xid, StatusCommitted)
+}
+
+// Abort discards the transaction's writes: its row versions become invisible
+// to everyone and are removed by the next vacuum. Its row locks are released.
+func (tx *Tx) Abort() error {
+	if tx.done {
+		return ErrTxDone
+	}
+	tx.done = true
+	defer tx.m.locks.ReleaseAll(tx.xid)
+	return tx.m.finish(tx.xid, StatusAborted)
+}
+
+// Done reports whether the transaction has committed or aborted.
+func (tx *Tx) Done() bool { return tx.done }
@@ -0,0 +1,116 @@
+package mvcc
+
+import (
+	"errors"
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestSnapshot_Visibility(t *testing.T) {
+	m, err := Open(filepath.Join(t.TempDir(), "xact"), false)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = m.Close() })
+
+	t1, err := m.Begin()
+	require.NoError(t, err)
+	t2, err := m.Begin()
+	require.NoError(t, err)
+	require.NoError(t, t1.Commit())
+
+	// t2 started while t1 was running: t1 stays invisible to it.
+	require.False(t, t2.Snapshot().Sees(t1.XID()))
+	require.True(t, t2.Snapshot().Sees(t2.XID()))
+	require.True(t, t2.Snapshot().Sees(0))
+	require.True(t, m.Snapshot().Sees(t1.XID()))
+	require.False(t, m.Snapshot().Sees(t2.XID()))
+
+	// Deleted by t1 before the snapshot: gone; by running t2: still there.
+	snap := m.Snapshot()
+	require.False(t, snap.Visible(0, t1.XID()))
+	require.True(t, snap.Visible(0, t2.XID()))
+
+	require.ErrorIs(t, t2.CanDelete(t1.XID()), ErrWriteConflict)
+	t3, err := m.Begin()
+	require.NoError(t, err)
+	require.NoError(t, t3.Abort())
+	require.NoError(t, t2.CanDelete(t3.XID()))
+	require.False(t, m.Snapshot().Sees(t3.XID()))
+
+	// t2 still needs everything from its own snapshot on.
+	require.Equal(t, t1.XID(), m.Horizon())
+	require.ErrorIs(t, t1.Commit(), ErrTxDone)
+}
+
+func TestManager_Reopen(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "xact")
+	m, err := Open(path, false)
+	require.NoError(t, err)
+	committed, err := m.Begin()
+	require.NoError(t, err)
+	require.NoError(t, committed.Commit())
+	running, err := m.Begin()
+	require.NoError(t, err)
+	require.NoError(t, m.Close())
+
+	// A transaction still running at close counts as aborted.
+	m, err = Open(path, true)
+	require.NoError(t, err)
+	require.Equal(t, StatusCommitted, m.Status(committed.XID()))
+	require.Equal(t, StatusAborted, m.Status(running.XID()))
+	_, err = m.Begin()
+	require.ErrorIs(t, err, ErrReadOnly)
+
+	m, err = Open(path, false)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = m.Close() })
+	ro, err := Open(path, true)
+	require.NoError(t, err)
+	next, err := m.Begin()
+	require.NoError(t, err)
+	require.Greater(t, next.XID(), running.XID())
+
+	// A reader of the log sees new commits once refreshed.
+	require.NoError(t, next.Commit())
+	require.False(t, ro.Snapshot().Sees(next.XID()))
+	require.NoError(t, ro.Refresh(path))
+	require.True(t, ro.Snapshot().Sees(next.XID()))
+}
+
+type fakeLog struct {
+	last    uint64
+	flushed uint64
+	err     error
+}
+
+func (l *fakeLog) LastLSN() uint64 { return l.last }
+func (l *fakeLog) Flush(upto uint64) error {
+	if l.err != nil {
+		return l.err
+	}
+	l.flushed = upto
+	return nil
+}
+
+func TestManager_CommitFlushesLog(t *testing.T) {
+	m, err := Open(filepath.Join(t.TempDir(), "xact"), false)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = m.Close() })
+	l := &fakeLog{last: 42}
+	m.SetLog(l)
+
+	tx, err := m.Begin()
+	require.NoError(t, err)
+	require.NoError(t, tx.Commit())
+	require.Equal(t, uint64(42), l.flushed)
+	require.Equal(t, StatusCommitted, m.Status(tx.XID()))
+
+	// A commit whose rows cannot reach the log is not recorded.
+	l.err = errors.New("disk gone")
+	tx, err = m.Begin()
+	require.NoError(t, err)
+	require.ErrorIs(t, tx.Commit(), l.err)
+	require.Equal(t, StatusAborted, m.Status(tx.XID()))
+	require.Zero(t, m.Running())
+}
@@ -0,0 +1,225 @@
+package record
+
+import (
+	"errors"
+	"fmt"
+	"math"
+	"math/big"
+	"strconv"
+	"strings"
+)
+
+// Decimal is an exact number, Coef / 10^Scale: the value of a DECIMAL(p, s)
+// column, which stores the Coef of its values at scale s as 8 bytes. 1.5
+// and 1.50 are the same number at two scales.
+type Decimal struct {
+	Coef  int64
+	Scale uint8
+}
+
+// MaxDecimalPrecision is the most digits a Decimal has, and a DECIMAL
+// column may ask for.
+const MaxDecimalPrecision = 18
+
+// divScale is how many more places than its operands a quotient has.
+const divScale = 6
+
+var (
+	ErrBadDecimal      = errors.New("record: malformed decimal")
+	ErrNumericOverflow = errors.New("record: numeric value out of range")
+)
+
+var (
+	bigTen   = big.NewInt(10)
+	maxCoef  = pow10(MaxDecimalPrecision) // the first number of too many digits
+	keyShift = new(big.Int).Lsh(big.NewInt(1), 127)
+)
+
+func pow10(n int) *big.Int { return new(big.Int).Exp(bigTen, big.NewInt(int64(n)), nil) }
+
+// ParseDecimal reads a decimal number, such as "-12.50", with at most
+// MaxDecimalPrecision digits; places beyond those are rounded off.
+func ParseDecimal(s string) (Decimal, error) {
+	bad := fmt.Errorf("%w: %q", ErrBadDecimal, s)
+	t := strings.TrimSpace(s)
+	sign := ""
+	if t != "" && (t[0] == '-' || t[0] == '+') {
+		sign, t = t[:1], t[1:]
+	}
+	whole, frac, _ := strings.Cut(t, ".")
+	digits := whole + frac
+	if digits == "" || strings.Trim(digits, "0123456789") != "" {
+		return Decimal{}, bad
+	}
+	c, _ := new(big.Int).SetString(sign+digits, 10)
+	d, err := makeDecimal(c, len(frac), 0)
+	if err != nil {
+		return Decimal{}, fmt.Errorf("%w: %q", err, s)
+	}
+	return d, nil
+}
+
+// FloatDecimal is f as the shortest decimal that reads back as it, so that
+// the float 0.1 is the decimal 0.1.
+func FloatDecimal(f float64) (Decimal, error) {
+	if math.IsNaN(f) || math.IsInf(f, 0) {
+		return Decimal{}, fmt.Errorf("%w: %v", ErrNumericOverflow, f)
+	}
+	return ParseDecimal(strconv.FormatFloat(f, 'f', -1, 64))
+}
+
+// DecimalValue is v as a value of the DECIMAL column c: a Decimal, an
+// integer, a float64 or text ParseDecimal reads, rounded to the scale of c.
+// It is ErrNumericOverflow when that has more digits than c allows.
+func DecimalValue(c Column, v any) (Decimal, error) {
+	var d Decimal
+	var err error
+	switch x := v.(type) {
+	case Decimal:
+		d = x
+	case string:
+		d, err = ParseDecimal(x)
+	case float64:
+		d, err = FloatDecimal(x)
+	default:
+		n, ok := asInt64(v)
+		if !ok {
+			return Decimal{}, ErrSchemaMismatchNotDecimal
+		}
+		d = Decimal{Coef: n}
+	}
+	if err != nil {
+		return Decimal{}, err
+	}
+	return c.fitDecimal(d)
+}
+
+// fitDecimal is d rounded to the scale of c, a DECIMAL column.
+func (c Column) fitDecimal(d Decimal) (Decimal, error) {
+	r, err := d.Rescale(int(c.Scale))
+	if err != nil || r.abs().Cmp(pow10(int(c.Precision))) >= 0 {
+		return Decimal{}, fmt.Errorf("%w: %s does not fit DECIMAL(%d, %d)", ErrNumericOverflow, d, c.Precision, c.Scale)
+	}
+	return r, nil
+}
+
+// makeDecimal is c / 10^scale, with places rounded off, but not below
+// minScale, until it has at most MaxDecimalPrecision digits.
+func makeDecimal(c *big.Int, scale, minScale int) (Decimal, error) {
+	for drop := max(scale-MaxDecimalPrecision, 0); drop <= scale; drop++ {
+		r := roundDiv(c, pow10(drop))
+		if new(big.Int).Abs(r).Cmp(maxCoef) < 0 {
+			return Decimal{Coef: r.Int64(), Scale: uint8(scale - drop)}, nil
+		}
+		if scale-drop <= minScale {
+			break
+		}
+	}
+	return Decimal{}, ErrNumericOverflow
+}
+
+// roundDiv is n / d rounded half away from zero, for d > 0.
+func roundDiv(n, d *big.Int) *big.Int {
+	q, r := new(big.Int).QuoRem(n, d, new(big.Int))
+	if r.Abs(r).Lsh(r, 1).Cmp(d) >= 0 {
+		q.Add(q, big.NewInt(int64(n.Sign())))
+	}
+	return q
+}
+
+func (d Decimal) abs() *big.Int { return new(big.Int).Abs(big.NewInt(d.Coef)) }
+
+// at is the coefficient of d at scale s, at least its own.
+func (d Decimal) at(s int) *big.Int {
+	return new(big.Int).Mul(big.NewInt(d.Coef), pow10(s-int(d.Scale)))
+}
+
+// String is d with its places, such as "-12.50".
+func (d Decimal) String() string {
+	s := strconv.FormatInt(d.Coef, 10)
+	if d.Scale == 0 {
+		return s
+	}
+	sign := ""
+	if d.Coef < 0 {
+		sign, s = "-", s[1:]
+	}
+	if n := int(d.Scale) + 1 - len(s); n > 0 {
+		s = strings.Repeat("0", n) + s
+	}
+	return sign + s[:len(s)-int(d.Scale)] + "." + s[len(s)-int(d.Scale):]
+}
+
+// MarshalText writes d as String does, so that it is a JSON string and
+// keeps its places.
+func (d Decimal) MarshalText() ([]byte, error) { return []byte(d.String()), nil }
+
+// Float64 is the float nearest to d.
+func (d Decimal) Float64() float64 {
+	f, _ := strconv.ParseFloat(d.String(), 64)
+	return f
+}
+
+// Int64 is d rounded half away from zero to an integer.
+func (d Decimal) Int64() int64 { return roundDiv(big.NewInt(d.Coef), pow10(int(d.Scale))).Int64() }
+
+// Rescale is d at scale s, rounded half away from zero when that has fewer
+// places.
+func (d Decimal) Rescale(s int) (Decimal, error) {
+	if s >= int(d.Scale) {
+		return makeDecimal(d.at(s), s, s)
+	}
+	return makeDecimal(roundDiv(big.NewInt(d.Coef), pow10(int(d.Scale)-s)), s, s)
+}
+
+// Cmp compares d with e by value: -1, 0 or 1.
+func (d Decimal) Cmp(e Decimal) int {
+	s := int(max(d.Scale, e.Scale))
+	return d.at(s).Cmp(e.at(s))
+}
+
+// Neg is -d.
+func (d Decimal) Neg() Decimal { return Decimal{Coef: -d.Coef, Scale: d.Scale} }
+
+// Add is d + e, at the larger of their scales.
+func (d Decimal) Add(e Decimal) (Decimal, error) {
+	s := int(max(d.Scale, e.Scale))
+	return makeDecimal(new(big.Int).Add(d.at(s), e.at(s)), s, s)
+}
+
+// Sub is d - e, at the larger of their scales.
+func (d Decimal) Sub(e Decimal) (Decimal, error) { return d.Add(e.Neg()) }
+
+// Mul is d × e with all its places, or as many as fit, at least those of
+// the larger scale.
+func (d Decimal) Mul(e Decimal) (Decimal, error) {
+	p := new(big.Int).Mul(big.NewInt(d.Coef), big.NewInt(e.Coef))
+	return makeDecimal(p, int(d.Scale)+int(e.Scale), int(max(d.Scale, e.Scale)))
+}
+
+// Div is d / e, which must not be zero, rounded to divScale places more
+// than the larger scale, or as many as fit.
+func (d Decimal) Div(e Decimal) (Decimal, error) {
+	s := int(max(d.Scale, e.Scale))
+	places := min(s+divScale, MaxDecimalPrecision)
+	n, m := d.at(places+int(e.Scale)), big.NewInt(e.Coef)
+	if m.Sign() < 0 {
+		n.Neg(n)
+		m.Neg(m)
+	}
+	return makeDecimal(roundDiv(n, m), places, s)
+}
+
+// Mod is the remainder of d / e, which must not be zero, with the sign of
+// d, at the larger of their scales.
+func (d Decimal) Mod(e Decimal) (Decimal, error) {
+	s := int(max(d.Scale, e.Scale))
+	return makeDecimal(new(big.Int).Rem(d.at(s), e.at(s)), s, s)
+}
+
+// OrderKey is 16 bytes that order as the values they are of do, whatever
+// their scales: equal numbers have the same key.
+func (d Decimal) OrderKey() []byte {
+	n := d.at(MaxDecimalPrecision)
+	return n.Add(n, keyShift).FillBytes(make([]byte, 16))
+}
@@ -0,0 +1,101 @@
+package record
+
+import (
+	"bytes"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func mustDecimal(t *testing.T, s string) Decimal {
+	t.Helper()
+	d, err := ParseDecimal(s)
+	require.NoError(t, err)
+	return d
+}
+
+func TestDecimal_ParseAndString(t *testing.T) {
+	for s, want := range map[string]Decimal{
+		"12.50": {Coef: 1250, Scale: 2},
+		"-0.05": {Coef: -5, Scale: 2},
+		"+7":    {Coef: 7},
+		" 3. ":  {Coef: 3},
+		".25":   {Coef: 25, Scale: 2},
+	} {
+		require.Equal(t, want, mustDecimal(t, s), s)
+	}
+	// Places beyond MaxDecimalPrecision digits are rounded off.
+	require.Equal(t, Decimal{Coef: 123456789012345679, Scale: 18}, mustDecimal(t, "0.1234567890123456789"))
+	require.Equal(t, "-0.05", Decimal{Coef: -5, Scale: 2}.String())
+	require.Equal(t, "12.50", Decimal{Coef: 1250, Scale: 2}.String())
+
+	for _, s := range []string{"", ".", "1.2.3", "1e3", "--1", "abc"} {
+		_, err := ParseDecimal(s)
+		require.ErrorIs(t, err, ErrBadDecimal, s)
+	}
+	_, err := ParseDecimal("1234567890123456789")
+	require.ErrorIs(t, err, ErrNumericOverflow)
+
+	d, err := FloatDecimal(0.1)
+	require.NoError(t, err)
+	require.Equal(t, Decimal{Coef: 1, Scale: 1}, d)
+	require.InDelta(t, 12.5, Decimal{Coef: 1250, Scale: 2}.Float64(), 1e-9)
+	require.Equal(t, int64(-3), mustDecimal(t, "-2.5").Int64())
+}
+
+func TestDecimal_Arithmetic(t *testing.T) {
+	a, b := mustDecimal(t, "10.25"), mustDecimal(t, "0.1")
+
+	sum, err := a.Add(b)
+	require.NoError(t, err)
+	require.Equal(t, "10.35", sum.String())
+	diff, err := b.Sub(a)
+	require.NoError(t, err)
+	require.Equal(t, "-10.15", diff.String())
+	prod, err := a.Mul(b)
+	require.NoError(t, err)
+	require.Equal(t, "1.025", prod.String())
+	quo, err := mustDecimal(t, "1").Div(mustDecimal(t, "-3"))
+	require.NoError(t, err)
+	require.Equal(t, "-0.333333", quo.String())
+	rem, err := mustDecimal(t, "-7.5").Mod(mustDecimal(t, "2"))
+	require.NoError(t, err)
+	require.Equal(t, "-1.5", rem.String())
+
+	// Places are given up for digits, but not those of the operands.
+	wide := mustDecimal(t, "123456789.123456789")
+	prod, err = wide.Mul(mustDecimal(t, "2.000000000"))
+	require.NoError(t, err)
+	require.Equal(t, "246913578.246913578", prod.String())
+	_, err = mustDecimal(t, "999999999999999999").Add(mustDecimal(t, "1"))
+	require.ErrorIs(t, err, ErrNumericOverflow)
+
+	require.Equal(t, 0, mustDecimal(t, "1.5").Cmp(mustDecimal(t, "1.50")))
+	require.Equal(t, -1, mustDecimal(t, "-2").Cmp(mustDecimal(t, "1.5")))
+	require.Equal(t, mustDecimal(t, "1.5").OrderKey(), mustDecimal(t, "1.500").OrderKey())
+	require.Equal(t, -1, bytes.Compare(mustDecimal(t, "-0.01").OrderKey(), mustDecimal(t, "0").OrderKey()))
+}
+
+func TestEncodeDecodeRow_Decimal(t *testing.T) {
+	schema := Schema{Cols: []Column{{Name: "price", Type: ColDecimal, Precision: 6, Scale: 2}}}
+
+	for _, v := range []any{mustDecimal(t, "19.999"), int64(20)} {
+		buf, err := EncodeRow(schema, []any{v})
+		require.NoError(t, err)
+		row, err := DecodeRow(schema, buf)
+		require.NoError(t, err)
+		require.Equal(t, []any{Decimal{Coef: 2000, Scale: 2}}, row)
+	}
+
+	_, err := EncodeRow(schema, []any{mustDecimal(t, "10000")})
+	require.ErrorIs(t, err, ErrNumericOverflow)
+	_, err = EncodeRow(schema, []any{"1.5"})
+	require.ErrorIs(t, err, ErrSchemaMismatchNotDecimal)
+
+	d, err := DecimalValue(schema.Cols[0], "1.005")
+	require.NoError(t, err)
+	require.Equal(t, "1.01", d.String())
+	d, err = DecimalValue(schema.Cols[0], 0.1)
+	require.NoError(t, err)
+	require.Equal(t, "0.10", d.String())
+}
@@ -3,6 +3,7 @@ package record
 import (
 	"errors"
 	"math"
+	"time"
 
 	"github.com/tuannm99/novasql/pkg/bx"
 )
@@ -14,8 +15,13 @@ const (
 	ColInt64
 	ColBool
 	ColFloat64
-	ColText  // UTF-8
-	ColBytes // opaque bytes
+	ColText      // UTF-8
+	ColBytes     // opaque bytes
+	ColJSON      // JSON text, stored like ColText
+	ColDate      // Date, stored like ColInt64
+	ColTime      // Time, stored like ColInt64
+	ColTimestamp // Timestamp, stored like ColInt64
+	ColDecimal   // Decimal, its Coef stored like ColInt64 at the scale of the column
 )
 
 // ---- Errors ----
@@ -28,6 +34,8 @@ var (
 	ErrSchemaMismatchNotFloat64   = errors.New("rowcodec: schema/values mismatch not float64")
 	ErrSchemaMismatchNotText      = errors.New("rowcodec: schema/values mismatch not text")
 	ErrSchemaMismatchNotBytes     = errors.New("rowcodec: schema/values mismatch not bytes")
+	ErrSchemaMismatchNotTemporal  = errors.New("rowcodec: schema/values mismatch not date/time")
+	ErrSchemaMismatchNotDecimal   = errors.New("rowcodec: schema/values mismatch not decimal")
 
 	ErrBadBuffer       = errors.New("rowcodec: buffer underflow/overflow")
 	ErrVarTooLong      = errors.New("rowcodec: variable length exceeds u16")
@@ -38,6 +46,15 @@ type Column struct {
 	Name     string
 	Type     ColumnType
 	Nullable bool
+
+	// Precision and Scale are those of DECIMAL(p, s): how many digits its
+	// values have, and how many of them are places.
+	Precision uint8 `json:",omitempty"`
+	Scale     uint8 `json:",omitempty"`
+
+	// Collation is the collation a TEXT column compares, sorts and indexes
+	// its values by, upper case; "" is BINARY.
+	Collation string `json:",omitempty"`
 }
 
 type Schema struct {
@@ -94,6 +111,31 @@ func EncodeRow(s Schema, values []any) ([]byte, error) {
 			bx.PutU64(b[:], uint64(x))
 			out = append(out, b[:]...)
 
+		case ColDate, ColTime, ColTimestamp:
+			x, ok := asTemporal(col.Type, v)
+			if !ok {
+				return nil, ErrSchemaMismatchNotTemporal
+			}
+			var b [8]byte
+			bx.PutU64(b[:], uint64(x))
+			out = append(out, b[:]...)
+
+		case ColDecimal:
+			d, ok := v.(Decimal)
+			if n, isInt := asInt64(v); isInt {
+				d, ok = Decimal{Coef: n}, true
+			}
+			if !ok {
+				return nil, ErrSchemaMismatchNotDecimal
+			}
+			d, err := col.fitDecimal(d)
+			if err != nil {
+				return nil, err
+			}
+			var b [8]byte
+			bx.PutU64(b[:], uint64(d.Coef))
+			out = append(out, b[:]...)
+
 		case ColBool:
 			x, ok := v.(bool)
 			if !ok {
@@ -114,7 +156,7 @@ func EncodeRow(s Schema, values []any) ([]byte, error) {
 			bx.PutU64(b[:], math.Float64bits(x))
 			out = append(out, b[:]...)
 
-		case ColText:
+		case ColText, ColJSON:
 			// expect string -> UTF-8 bytes
 			str, ok := v.(string)
 			if !ok {
@@ -182,6 +224,28 @@ func DecodeRow(s Schema, buf []byte) ([]any, error) {
 			out[colIdx] = int64(bx.U64(buf[i : i+8]))
 			i += 8
 
+		case ColDate, ColTime, ColTimestamp:
+			if i+8 > len(buf) {
+				return nil, ErrBadBuffer
+			}
+			x := int64(bx.U64(buf[i : i+8]))
+			switch col.Type {
+			case ColDate:
+				out[colIdx] = Date(x)
+			case ColTime:
+				out[colIdx] = Time(x)
+			default:
+				out[colIdx] = Timestamp(x)
+			}
+			i += 8
+
+		case ColDecimal:
+			if i+8 > len(buf) {
+				return nil, ErrBadBuffer
+			}
+			out[colIdx] = Decimal{Coef: int64(bx.U64(buf[i : i+8])), Scale: col.Scale}
+			i += 8
+
 		case ColBool:
 			if i+1 > len(buf) {
 				return nil, ErrBadBuffer
@@ -196,7 +260,7 @@ func DecodeRow(s Schema, buf []byte) ([]any, error) {
 			out[colIdx] = math.Float64frombits(bx.U64(buf[i : i+8]))
 			i += 8
 
-		case ColText:
+		case ColText, ColJSON:
 			if i+2 > len(buf) {
 				return nil, ErrBadBuffer
 			}
@@ -261,6 +325,25 @@ func asInt64(v any) (int64, bool) {
 	return 0, false
 }
 
+// asTemporal accepts the value type of t, or a time.Time for a date or a
+// timestamp.
+func asTemporal(t ColumnType, v any) (int64, bool) {
+	switch x := v.(type) {
+	case Date:
+		return int64(x), t == ColDate
+	case Time:
+		return int64(x), t == ColTime
+	case Timestamp:
+		return int64(x), t == ColTimestamp
+	case time.Time:
+		if t == ColDate {
+			return int64(DateOf(x)), true
+		}
+		return int64(TimestampOf(x)), t == ColTimestamp
+	}
+	return 0, false
+}
+
 func asFloat64(v any) (float64, bool) {
 	switch x := v.(type) {
 	case float64:
@@ -4,6 +4,7 @@ import (
 	"math"
 	"strings"
 	"testing"
+	"time"
 
 	"github.com/stretchr/testify/require"
 )
@@ -160,3 +161,72 @@ func TestDecodeRow_BadBuffer(t *testing.T) {
 		require.ErrorIs(t, err, ErrBadBuffer)
 	})
 }
+
+func TestEncodeDecodeRow_Temporal(t *testing.T) {
+	schema := Schema{
+		Cols: []Column{
+			{Name: "d", Type: ColDate},
+			{Name: "t", Type: ColTime},
+			{Name: "ts", Type: ColTimestamp},
+			{Name: "at", Type: ColTimestamp},
+		},
+	}
+	d, err := ParseDate("1969-12-31")
+	require.NoError(t, err)
+	require.Equal(t, Date(-1), d)
+	tm, err := ParseTime("10:30:00.25")
+	require.NoError(t, err)
+	ts, err := ParseTimestamp("2024-03-09 10:30:00")
+	require.NoError(t, err)
+	at := time.Date(2024, 3, 9, 12, 30, 0, 0, time.FixedZone("", 2*60*60))
+
+	buf, err := EncodeRow(schema, []any{d, tm, ts, at})
+	require.NoError(t, err)
+	row, err := DecodeRow(schema, buf)
+	require.NoError(t, err)
+	require.Equal(t, []any{d, tm, ts, ts}, row)
+	require.Equal(t, "1969-12-31", d.String())
+	require.Equal(t, "10:30:00.25", tm.String())
+	require.Equal(t, "2024-03-09 10:30:00", ts.String())
+	require.Equal(t, Date(19791), ts.Date())
+	require.Equal(t, "10:30:00", ts.Time().String())
+
+	_, err = EncodeRow(schema, []any{ts, tm, ts, ts})
+	require.ErrorIs(t, err, ErrSchemaMismatchNotTemporal)
+	_, err = ParseDate("2024-02-30")
+	require.ErrorIs(t, err, ErrBadTemporal)
+}
+
+// FuzzEncodeDecodeRow checks that rows decode to values that encode back to
+// the same bytes.
+func FuzzEncodeDecodeRow(f *testing.F) {
+	f.Add(int32(42), int64(-1), true, 3.14159, "hello", []byte{1, 2, 3})
+	f.Add(int32(math.MinInt32), int64(math.MaxInt64), false, math.NaN(), "", []byte(nil))
+	f.Fuzz(func(t *testing.T, id32 int32, id64 int64, active bool, score float64, name string, blob []byte) {
+		schema := makeTestSchema()
+		buf, err := EncodeRow(schema, []any{id32, id64, active, score, name, blob})
+		if err != nil {
+			require.ErrorIs(t, err, ErrVarTooLong)
+			return
+		}
+		row, err := DecodeRow(schema, buf)
+		require.NoError(t, err)
+		again, err := EncodeRow(schema, row)
+		require.NoError(t, err)
+		require.Equal(t, buf, again)
+	})
+}
+
+// FuzzDecodeRow checks that no buffer makes DecodeRow panic.
+func FuzzDecodeRow(f *testing.F) {
+	buf, err := EncodeRow(makeTestSchema(), []any{int32(1), int64(2), true, 0.5, nil, []byte("x")})
+	require.NoError(f, err)
+	f.Add(buf)
+	f.Add([]byte{})
+	f.Fuzz(func(t *testing.T, buf []byte) {
+		row, err := DecodeRow(makeTestSchema(), buf)
+		if err == nil {
+			require.Len(t, row, len(makeTestSchema().Cols))
+		}
+	})
+}
@@ -0,0 +1,264 @@
+package record
+
+import (
+	"errors"
+	"fmt"
+	"math"
+	"slices"
+	"strconv"
+	"strings"
+	"time"
+)
+
+// Values of the temporal column types. All of them are stored as the int64
+// they are, and order like it.
+type (
+	Date      int64 // days since 1970-01-01
+	Time      int64 // microseconds since midnight
+	Timestamp int64 // microseconds since 1970-01-01 00:00:00 UTC
+)
+
+// Interval is a span of time that dates and times can be moved by. It is
+// not a column type. Months and days are kept apart from the rest, as their
+// length depends on the date they are added to.
+type Interval struct {
+	Months int64
+	Days   int64
+	Micros int64
+}
+
+var ErrBadTemporal = errors.New("record: malformed date or time")
+
+const (
+	microsPerDay = 24 * int64(time.Hour/time.Microsecond)
+
+	dateLayout      = "2006-01-02"
+	timeLayout      = "15:04:05.999999"
+	timestampLayout = dateLayout + " " + timeLayout
+)
+
+// DateOf is the date of t in UTC.
+func DateOf(t time.Time) Date {
+	return Date(floorDiv(t.Unix(), 24*60*60))
+}
+
+// TimeOf is the time of day of t in UTC.
+func TimeOf(t time.Time) Time {
+	return Time(t.UnixMicro() - floorDiv(t.UnixMicro(), microsPerDay)*microsPerDay)
+}
+
+// TimestampOf is t, to the microsecond.
+func TimestampOf(t time.Time) Timestamp { return Timestamp(t.UnixMicro()) }
+
+// UTC is the start of d.
+func (d Date) UTC() time.Time { return time.Unix(int64(d)*24*60*60, 0).UTC() }
+
+// UTC is ts as a time.Time.
+func (ts Timestamp) UTC() time.Time { return time.UnixMicro(int64(ts)).UTC() }
+
+// Duration is the time since midnight.
+func (t Time) Duration() time.Duration { return time.Duration(t) * time.Microsecond }
+
+// Date is the day ts falls on.
+func (ts Timestamp) Date() Date { return Date(floorDiv(int64(ts), microsPerDay)) }
+
+// Time is the time of day of ts.
+func (ts Timestamp) Time() Time { return Time(int64(ts) - int64(ts.Date())*microsPerDay) }
+
+// Timestamp is the start of d.
+func (d Date) Timestamp() Timestamp { return Timestamp(int64(d) * microsPerDay) }
+
+// String is d as "2006-01-02".
+func (d Date) String() string { return d.UTC().Format(dateLayout) }
+
+// String is t as "15:04:05", with the fraction of a second if it has one.
+func (t Time) String() string { return time.Unix(0, 0).UTC().Add(t.Duration()).Format(timeLayout) }
+
+// String is ts as "2006-01-02 15:04:05", with the fraction of a second if it
+// has one.
+func (ts Timestamp) String() string { return ts.UTC().Format(timestampLayout) }
+
+// MarshalText writes the values as String does, so that they are JSON
+// strings.
+func (d Date) MarshalText() ([]byte, error)       { return []byte(d.String()), nil }
+func (t Time) MarshalText() ([]byte, error)       { return []byte(t.String()), nil }
+func (ts Timestamp) MarshalText() ([]byte, error) { return []byte(ts.String()), nil }
+
+// ParseDate reads a date, "2006-01-02".
+func ParseDate(s string) (Date, error) {
+	t, err := time.Parse(dateLayout, strings.TrimSpace(s))
+	if err != nil {
+		return 0, fmt.Errorf("%w: DATE %q", ErrBadTemporal, s)
+	}
+	return DateOf(t), nil
+}
+
+// ParseTime reads a time of day, "15:04:05" with an optional fraction of a
+// second, or "15:04".
+func ParseTime(s string) (Time, error) {
+	for _, layout := range []string{timeLayout, "15:04"} {
+		if t, err := time.Parse(layout, strings.TrimSpace(s)); err == nil {
+			return TimeOf(t), nil
+		}
+	}
+	return 0, fmt.Errorf("%w: TIME %q", ErrBadTemporal, s)
+}
+
+// ParseTimestamp reads a timestamp: "2006-01-02 15:04:05" with an optional
+// fraction of a second, RFC 3339 with a time zone, or a date for its start.
+func ParseTimestamp(s string) (Timestamp, error) {
+	for _, layout := range []string{timestampLayout, "2006-01-02 15:04", time.RFC3339Nano, dateLayout} {
+		if t, err := time.Parse(layout, strings.TrimSpace(s)); err == nil {
+			return TimestampOf(t), nil
+		}
+	}
+	return 0, fmt.Errorf("%w: TIMESTAMP %q", ErrBadTemporal, s)
+}
+
+// TemporalValue is v as a value of the temporal column type t: a value of
+// that type, a time.Time, or text ParseDate, ParseTime or ParseTimestamp
+// reads. A Date is a Timestamp too, of its start.
+func TemporalValue(t ColumnType, v any) (any, error) {
+	if s, ok := v.(string); ok {
+		switch t {
+		case ColDate:
+			return ParseDate(s)
+		case ColTime:
+			return ParseTime(s)
+		default:
+			return ParseTimestamp(s)
+		}
+	}
+	if d, ok := v.(Date); ok && t == ColTimestamp {
+		return d.Timestamp(), nil
+	}
+	x, ok := asTemporal(t, v)
+	if !ok {
+		return nil, ErrSchemaMismatchNotTemporal
+	}
+	switch t {
+	case ColDate:
+		return Date(x), nil
+	case ColTime:
+		return Time(x), nil
+	default:
+		return Timestamp(x), nil
+	}
+}
+
+type intervalUnit struct {
+	name  string
+	field int // 0 Months, 1 Days, 2 Micros
+	n     int64
+}
+
+// intervalUnits are the units an interval is written in, with the field of
+// Interval they count and how many of it one is.
+var intervalUnits = []intervalUnit{
+	{"YEAR", 0, 12},
+	{"MONTH", 0, 1},
+	{"WEEK", 1, 7},
+	{"DAY", 1, 1},
+	{"HOUR", 2, int64(time.Hour / time.Microsecond)},
+	{"MINUTE", 2, int64(time.Minute / time.Microsecond)},
+	{"SECOND", 2, int64(time.Second / time.Microsecond)},
+	{"MILLISECOND", 2, 1000},
+	{"MICROSECOND", 2, 1},
+}
+
+// ParseInterval reads an interval: numbers each followed by a unit, such as
+// "1 day 2 hours" or "-3 MONTHS". The units are those of intervalUnits, in
+// the singular or the plural; only a number of seconds may have a fraction.
+func ParseInterval(s string) (Interval, error) {
+	bad := fmt.Errorf("%w: INTERVAL %q", ErrBadTemporal, s)
+	var iv Interval
+	fields := strings.Fields(s)
+	if len(fields) == 0 || len(fields)%2 != 0 {
+		return iv, bad
+	}
+	for i := 0; i < len(fields); i += 2 {
+		name := strings.TrimSuffix(strings.ToUpper(fields[i+1]), "S")
+		j := slices.IndexFunc(intervalUnits, func(u intervalUnit) bool { return u.name == name })
+		if j < 0 {
+			return iv, bad
+		}
+		u := intervalUnits[j]
+		n, err := strconv.ParseInt(fields[i], 10, 64)
+		if err != nil && u.name == "SECOND" {
+			var f float64
+			if f, err = strconv.ParseFloat(fields[i], 64); err == nil {
+				n, u.n = int64(math.Round(f*1e6)), 1
+			}
+		}
+		if err != nil {
+			return iv, bad
+		}
+		switch u.field {
+		case 0:
+			iv.Months += n * u.n
+		case 1:
+			iv.Days += n * u.n
+		default:
+			iv.Micros += n * u.n
+		}
+	}
+	return iv, nil
+}
+
+// String is iv as ParseInterval reads it, such as "1 year 2 days 3 hours".
+func (iv Interval) String() string {
+	var parts []string
+	add := func(n int64, unit string) {
+		if n == 0 {
+			return
+		}
+		if n != 1 && n != -1 {
+			unit += "s"
+		}
+		parts = append(parts, strconv.FormatInt(n, 10)+" "+unit)
+	}
+	add(iv.Months/12, "year")
+	add(iv.Months%12, "month")
+	add(iv.Days, "day")
+	add(iv.Micros/int64(time.Hour/time.Microsecond), "hour")
+	add(iv.Micros/int64(time.Minute/time.Microsecond)%60, "minute")
+	if us := iv.Micros % int64(time.Minute/time.Microsecond); us%1e6 == 0 {
+		add(us/1e6, "second")
+	} else {
+		parts = append(parts, strconv.FormatFloat(float64(us)/1e6, 'f', -1, 64)+" seconds")
+	}
+	if len(parts) == 0 {
+		return "0 seconds"
+	}
+	return strings.Join(parts, " ")
+}
+
+// Neg is -iv.
+func (iv Interval) Neg() Interval { return Interval{-iv.Months, -iv.Days, -iv.Micros} }
+
+// Add is ts moved by iv: by its months first, a month after January 31
+// being the end of February, then by its days and the rest.
+func (ts Timestamp) Add(iv Interval) Timestamp {
+	t := ts.UTC()
+	first := time.Date(t.Year(), t.Month()+time.Month(iv.Months), 1, 0, 0, 0, 0, time.UTC)
+	day := min(t.Day(), first.AddDate(0, 1, -1).Day())
+	t = time.Date(first.Year(), first.Month(), day, t.Hour(), t.Minute(), t.Second(), t.Nanosecond(), time.UTC)
+	return TimestampOf(t.AddDate(0, 0, int(iv.Days))) + Timestamp(iv.Micros)
+}
+
+// Add is t moved by the hours, minutes and seconds of iv, around the clock.
+func (t Time) Add(iv Interval) Time {
+	x := (int64(t) + iv.Micros) % microsPerDay
+	if x < 0 {
+		x += microsPerDay
+	}
+	return Time(x)
+}
+
+func floorDiv(a, b int64) int64 {
+	q := a / b
+	if a%b != 0 && a < 0 {
+		q--
+	}
+	return q
+}
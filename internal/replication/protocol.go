@@ -0,0 +1,129 @@
+// Package replication is the protocol a primary streams its database to
+// read-only replicas with (see Database.ServeReplication and
+// OpenOptions.Replica). Messages are length-prefixed JSON frames, like those
+// of novasqlwire.
+//
+// A replica connects and sends Hello; the primary answers Welcome, then a
+// Batch whenever its database changed. A new replica first gets a snapshot:
+// Partial batches that copy every file of the database. The replica
+// acknowledges each whole batch it applied with an Ack. A message with Error
+// set ends the stream.
+package replication
+
+import (
+	"encoding/binary"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"time"
+)
+
+// MaxFrameSize bounds one message: a batch carries at most a WAL's worth of
+// records, base64-encoded.
+const MaxFrameSize = 256 << 20
+
+var ErrFrameTooLarge = errors.New("replication: frame too large")
+
+// Message is one frame; exactly one field is set.
+type Message struct {
+	Hello   *Hello   `json:"hello,omitempty"`
+	Welcome *Welcome `json:"welcome,omitempty"`
+	Batch   *Batch   `json:"batch,omitempty"`
+	Ack     *Ack     `json:"ack,omitempty"`
+	Error   string   `json:"error,omitempty"`
+	// Behind marks an Error that asks for a new replica: the changes since
+	// its LSN are no longer in the primary's log or archive.
+	Behind bool `json:"behind,omitempty"`
+}
+
+// Hello opens the stream. A new replica sends it empty to get a snapshot;
+// one that reconnects sets Resume, with the LSN it applied up to and the
+// files it has.
+type Hello struct {
+	Resume bool       `json:"resume,omitempty"`
+	LSN    uint64     `json:"lsn"`
+	Files  []FileSize `json:"files,omitempty"`
+}
+
+// FileSize is a file of a replica, by path relative to its database
+// directory.
+type FileSize struct {
+	Path string `json:"path"`
+	Size int64  `json:"size"`
+}
+
+// Welcome answers Hello; Snapshot says a snapshot follows.
+type Welcome struct {
+	Snapshot bool `json:"snapshot"`
+}
+
+// Batch is what changed on the primary since the previous batch, applied in
+// order: Files (whole files, copied), Records (WAL records, encoded as in
+// the log), then Meta (metadata files, which may refer to pages of both).
+// LSN is the last LSN the primary logged, and At when. A Partial batch
+// leaves the replica inconsistent until the next batch without it: files
+// are still being copied.
+type Batch struct {
+	Partial bool      `json:"partial,omitempty"`
+	Files   []File    `json:"files,omitempty"`
+	Records []byte    `json:"records,omitempty"`
+	Meta    []File    `json:"meta,omitempty"`
+	LSN     uint64    `json:"lsn"`
+	At      time.Time `json:"at"`
+}
+
+// File is a piece of a file, by path relative to the database directory,
+// written at Offset. A copy starts with Create, which empties the file, and
+// replaces the file in place with Done; copies larger than a batch span
+// several of them. Remove removes the file.
+type File struct {
+	Path   string `json:"path"`
+	Offset int64  `json:"offset,omitempty"`
+	Data   []byte `json:"data,omitempty"`
+	Create bool   `json:"create,omitempty"`
+	Done   bool   `json:"done,omitempty"`
+	Remove bool   `json:"remove,omitempty"`
+}
+
+// Ack reports the LSN a replica applied up to.
+type Ack struct {
+	LSN uint64 `json:"lsn"`
+}
+
+// ReadMessage reads one frame.
+func ReadMessage(r io.Reader) (*Message, error) {
+	var hdr [4]byte
+	if _, err := io.ReadFull(r, hdr[:]); err != nil {
+		return nil, err
+	}
+	n := binary.BigEndian.Uint32(hdr[:])
+	if n > MaxFrameSize {
+		return nil, fmt.Errorf("%w: %d > %d", ErrFrameTooLarge, n, MaxFrameSize)
+	}
+	buf := make([]byte, n)
+	if _, err := io.ReadFull(r, buf); err != nil {
+		return nil, err
+	}
+	var m Message
+	if err := json.Unmarshal(buf, &m); err != nil {
+		return nil, fmt.Errorf("replication: bad frame: %w", err)
+	}
+	return &m, nil
+}
+
+// WriteMessage writes m as one frame.
+func WriteMessage(w io.Writer, m *Message) error {
+	b, err := json.Marshal(m)
+	if err != nil {
+		return err
+	}
+	if len(b) > MaxFrameSize {
+		return fmt.Errorf("%w: %d > %d", ErrFrameTooLarge, len(b), MaxFrameSize)
+	}
+	buf := make([]byte, 4+len(b))
+	binary.BigEndian.PutUint32(buf, uint32(len(b)))
+	copy(buf[4:], b)
+	_, err = w.Write(buf)
+	return err
+}
@@ -0,0 +1,42 @@
+package replication
+
+import (
+	"bytes"
+	"encoding/binary"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestMessage_RoundTrip(t *testing.T) {
+	at := time.Date(2024, 5, 1, 12, 0, 0, 0, time.UTC)
+	msgs := []*Message{
+		{Hello: &Hello{Resume: true, LSN: 7, Files: []FileSize{{Path: "tables/t", Size: 8192}}}},
+		{Welcome: &Welcome{Snapshot: true}},
+		{Batch: &Batch{Partial: true, Files: []File{{Path: "xact", Offset: 4, Data: []byte{1}}}}},
+		{Batch: &Batch{
+			Files:   []File{{Path: "tables/t", Data: []byte{1, 2, 3}, Create: true, Done: true}},
+			Records: []byte("records"),
+			Meta:    []File{{Path: "tables/t.meta.json", Remove: true}},
+			LSN:     9,
+			At:      at,
+		}},
+		{Ack: &Ack{LSN: 9}},
+		{Error: "too far behind", Behind: true},
+	}
+	var buf bytes.Buffer
+	for _, m := range msgs {
+		require.NoError(t, WriteMessage(&buf, m))
+	}
+	for _, want := range msgs {
+		got, err := ReadMessage(&buf)
+		require.NoError(t, err)
+		require.Equal(t, want, got)
+	}
+
+	var hdr [4]byte
+	binary.BigEndian.PutUint32(hdr[:], MaxFrameSize+1)
+	_, err := ReadMessage(bytes.NewReader(hdr[:]))
+	require.ErrorIs(t, err, ErrFrameTooLarge)
+}
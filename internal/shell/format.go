@@ -0,0 +1,163 @@
+package shell
+
+import (
+	"fmt"
+	"io"
+	"strings"
+
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/executor"
+)
+
+// CompactOneLine joins a multi-line statement into one line with single spaces.
+func CompactOneLine(s string) string {
+	// replace newlines/tabs with spaces, then collapse multiple spaces
+	s = strings.ReplaceAll(s, "\r\n", " ")
+	s = strings.ReplaceAll(s, "\n", " ")
+	s = strings.ReplaceAll(s, "\t", " ")
+	s = strings.TrimSpace(s)
+
+	var b strings.Builder
+	b.Grow(len(s))
+	space := false
+	for _, r := range s {
+		if r == ' ' {
+			if !space {
+				b.WriteByte(' ')
+				space = true
+			}
+			continue
+		}
+		space = false
+		b.WriteRune(r)
+	}
+	return b.String()
+}
+
+// StatementComplete checks if we have a terminating ';' outside single quotes.
+func StatementComplete(buf string) bool {
+	inQuote := false
+	escaped := false
+
+	for _, r := range buf {
+		if escaped {
+			escaped = false
+			continue
+		}
+		if r == '\\' {
+			escaped = true
+			continue
+		}
+		if r == '\'' {
+			inQuote = !inQuote
+			continue
+		}
+		if r == ';' && !inQuote {
+			return true
+		}
+	}
+	return false
+}
+
+// PrintResult writes res as an aligned table, or an "OK" line for
+// statements that return no columns.
+func PrintResult(w io.Writer, res *executor.Result) {
+	if len(res.Columns) == 0 {
+		// DDL/DML
+		_, _ = fmt.Fprintf(w, "OK (%d affected)\n", res.AffectedRows)
+		return
+	}
+
+	cols := res.Columns
+	cells := make([][]string, len(res.Rows))
+
+	// 1) render cells and compute widths
+	widths := make([]int, len(cols))
+	for i, c := range cols {
+		widths[i] = len(c)
+	}
+	for r, row := range res.Rows {
+		cells[r] = make([]string, len(cols))
+		for i := range cols {
+			s := "NULL"
+			if i < len(row) && row[i] != nil {
+				s = fmt.Sprintf("%v", row[i])
+			}
+			cells[r][i] = s
+			widths[i] = max(widths[i], len(s))
+		}
+	}
+
+	printRow := func(values []string) {
+		var b strings.Builder
+		for i := range cols {
+			if i > 0 {
+				b.WriteString(" | ")
+			}
+			b.WriteString(padRight(values[i], widths[i]))
+		}
+		_, _ = fmt.Fprintln(w, strings.TrimRight(b.String(), " "))
+	}
+
+	// 2) header, separator ----+----, rows
+	printRow(cols)
+	seps := make([]string, len(cols))
+	for i := range cols {
+		seps[i] = strings.Repeat("-", widths[i])
+	}
+	_, _ = fmt.Fprintln(w, strings.Join(seps, "-+-"))
+	for _, row := range cells {
+		printRow(row)
+	}
+
+	_, _ = fmt.Fprintf(w, "(%d rows)\n", res.AffectedRows)
+}
+
+func padRight(s string, w int) string {
+	if len(s) >= w {
+		return s
+	}
+	return s + strings.Repeat(" ", w-len(s))
+}
+
+// SQLType is the CREATE TABLE spelling of the type of column c.
+func SQLType(c record.Column) string {
+	switch t := c.Type; t {
+	case record.ColInt32:
+		return "INT32"
+	case record.ColInt64:
+		return "INT"
+	case record.ColBool:
+		return "BOOL"
+	case record.ColFloat64:
+		return "FLOAT"
+	case record.ColText:
+		return "TEXT"
+	case record.ColBytes:
+		return "BYTES"
+	case record.ColJSON:
+		return "JSON"
+	case record.ColDate:
+		return "DATE"
+	case record.ColTime:
+		return "TIME"
+	case record.ColTimestamp:
+		return "TIMESTAMP"
+	case record.ColDecimal:
+		return fmt.Sprintf("DECIMAL(%d, %d)", c.Precision, c.Scale)
+	default:
+		return fmt.Sprintf("TYPE(%d)", t)
+	}
+}
+
+// CreateTableSQL renders schema as the CREATE TABLE statement for table name.
+func CreateTableSQL(name string, schema record.Schema) string {
+	defs := make([]string, len(schema.Cols))
+	for i, c := range schema.Cols {
+		defs[i] = c.Name + " " + SQLType(c)
+		if !c.Nullable {
+			defs[i] += " NOT NULL"
+		}
+	}
+	return fmt.Sprintf("CREATE TABLE %s (%s);", name, strings.Join(defs, ", "))
+}
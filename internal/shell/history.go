@@ -0,0 +1,98 @@
+package shell
+
+import (
+	"bufio"
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+)
+
+// History is the statement history of an interactive session, kept in
+// memory and appended to a file (one statement per line).
+type History struct {
+	path  string
+	lines []string
+}
+
+func NewHistory(path string) *History {
+	return &History{path: path}
+}
+
+// DefaultHistoryPath is ~/.novasql_history, or a file in the working
+// directory when there is no home directory.
+func DefaultHistoryPath() string {
+	home, err := os.UserHomeDir()
+	if err != nil || home == "" {
+		return ".novasql_history"
+	}
+	return filepath.Join(home, ".novasql_history")
+}
+
+// Lines returns the statements loaded or appended so far, oldest first.
+func (h *History) Lines() []string { return h.lines }
+
+func (h *History) Load(max int) error {
+	if h.path == "" {
+		return nil
+	}
+	f, err := os.Open(h.path)
+	if err != nil {
+		if errors.Is(err, os.ErrNotExist) {
+			return nil
+		}
+		return err
+	}
+	defer func() { _ = f.Close() }()
+
+	sc := bufio.NewScanner(f)
+	for sc.Scan() {
+		s := strings.TrimSpace(sc.Text())
+		if s == "" {
+			continue
+		}
+		h.lines = append(h.lines, s)
+		if max > 0 && len(h.lines) > max {
+			h.lines = h.lines[len(h.lines)-max:]
+		}
+	}
+	return sc.Err()
+}
+
+func (h *History) Append(stmt string) error {
+	stmt = strings.TrimSpace(stmt)
+	if stmt == "" || h.path == "" {
+		return nil
+	}
+
+	// store single-line; collapse whitespace/newlines
+	stmt = CompactOneLine(stmt)
+
+	// ensure dir exists
+	if err := os.MkdirAll(filepath.Dir(h.path), 0o755); err != nil {
+		return err
+	}
+
+	f, err := os.OpenFile(h.path, os.O_CREATE|os.O_APPEND|os.O_WRONLY, 0o644)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = f.Close() }()
+
+	if _, err := fmt.Fprintln(f, stmt); err != nil {
+		return err
+	}
+	h.lines = append(h.lines, stmt)
+	return nil
+}
+
+func (h *History) Print(last int) {
+	if last <= 0 || last > len(h.lines) {
+		last = len(h.lines)
+	}
+	start := len(h.lines) - last
+	for i := start; i < len(h.lines); i++ {
+		fmt.Printf("%5d  %s\n", i+1, h.lines[i])
+	}
+}
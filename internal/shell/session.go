@@ -0,0 +1,206 @@
+package shell
+
+import (
+	"fmt"
+	"io"
+	"os"
+	"strings"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/sql/executor"
+)
+
+const (
+	Prompt             = "novasql> "
+	ContinuationPrompt = "...> "
+)
+
+const helpText = `meta commands:
+  .tables                list tables of the current database
+  .schema [name]         show CREATE TABLE/VIEW for one or all tables and views
+  .backup DIR            copy the current database to workdir DIR
+  .dump [FILE]           write SQL that rebuilds the database (to stdout or FILE)
+  .restore FILE          run a .dump script; any error undoes all of it
+  .help                  show help
+  .quit | .exit | \q     quit
+
+sql:
+  end statement with ';' (parser requires it)
+  multiline is supported (the prompt waits until ';')`
+
+// Session is a local interactive session over an open Database: it buffers
+// input lines until a statement is complete, runs statements through the
+// executor and handles dot meta-commands. Output and errors go to Out.
+type Session struct {
+	DB   *novasql.Database
+	Exec *executor.Executor
+	Out  io.Writer
+
+	// Executed is called with every complete statement before it runs
+	// (history recording); may be nil.
+	Executed func(stmt string)
+
+	buf strings.Builder
+}
+
+func NewSession(db *novasql.Database, out io.Writer) *Session {
+	return &Session{DB: db, Exec: executor.NewExecutor(db), Out: out}
+}
+
+// Prompt is the prompt to show before the next line.
+func (s *Session) Prompt() string {
+	if s.buf.Len() > 0 {
+		return ContinuationPrompt
+	}
+	return Prompt
+}
+
+// Reset drops a partially typed statement (Ctrl+C).
+func (s *Session) Reset() { s.buf.Reset() }
+
+// Feed handles one input line and reports whether the session should end.
+func (s *Session) Feed(line string) (quit bool) {
+	line = strings.TrimSpace(line)
+	if line == "" {
+		return false
+	}
+	if s.buf.Len() == 0 && (strings.HasPrefix(line, ".") || line == `\q`) {
+		return s.meta(line)
+	}
+
+	if s.buf.Len() > 0 {
+		s.buf.WriteByte(' ')
+	}
+	s.buf.WriteString(line)
+	if !StatementComplete(s.buf.String()) {
+		return false
+	}
+
+	stmt := strings.TrimSpace(s.buf.String())
+	s.buf.Reset()
+	if s.Executed != nil {
+		s.Executed(stmt)
+	}
+	res, err := s.Exec.ExecSQL(stmt)
+	if err != nil {
+		_, _ = fmt.Fprintf(s.Out, "error: %v\n", err)
+		return false
+	}
+	PrintResult(s.Out, res)
+	return false
+}
+
+func (s *Session) meta(line string) bool {
+	fields := strings.Fields(line)
+	switch fields[0] {
+	case ".quit", ".exit", `\q`:
+		return true
+	case ".help":
+		_, _ = fmt.Fprintln(s.Out, helpText)
+	case ".tables":
+		metas, err := s.DB.ListTables()
+		if err != nil {
+			_, _ = fmt.Fprintf(s.Out, "error: %v\n", err)
+			return false
+		}
+		for _, m := range metas {
+			_, _ = fmt.Fprintln(s.Out, m.Name)
+		}
+	case ".schema":
+		s.schema(fields[1:])
+	case ".backup":
+		if len(fields) != 2 {
+			_, _ = fmt.Fprintln(s.Out, "usage: .backup DIR")
+			return false
+		}
+		if err := s.DB.BackupTo(fields[1]); err != nil {
+			_, _ = fmt.Fprintf(s.Out, "error: %v\n", err)
+			return false
+		}
+		_, _ = fmt.Fprintf(s.Out, "backed up to %s\n", fields[1])
+	case ".dump":
+		if len(fields) > 2 {
+			_, _ = fmt.Fprintln(s.Out, "usage: .dump [FILE]")
+			return false
+		}
+		s.dump(fields[1:])
+	case ".restore":
+		if len(fields) != 2 {
+			_, _ = fmt.Fprintln(s.Out, "usage: .restore FILE")
+			return false
+		}
+		s.restore(fields[1])
+	default:
+		_, _ = fmt.Fprintf(s.Out, "unknown command: %s (try .help)\n", fields[0])
+	}
+	return false
+}
+
+func (s *Session) schema(names []string) {
+	if len(names) == 0 {
+		metas, err := s.DB.ListTables()
+		if err != nil {
+			_, _ = fmt.Fprintf(s.Out, "error: %v\n", err)
+			return
+		}
+		for _, m := range metas {
+			names = append(names, m.Name)
+		}
+		views, err := s.DB.ListViews()
+		if err != nil {
+			_, _ = fmt.Fprintf(s.Out, "error: %v\n", err)
+			return
+		}
+		for _, v := range views {
+			names = append(names, v.Name)
+		}
+	}
+	for _, name := range names {
+		if v, err := s.DB.View(name); err == nil {
+			_, _ = fmt.Fprintf(s.Out, "CREATE VIEW %s AS %s;\n", v.Name, v.Query)
+			continue
+		}
+		schema, err := s.DB.TableSchema(name)
+		if err != nil {
+			_, _ = fmt.Fprintf(s.Out, "error: %v\n", err)
+			continue
+		}
+		_, _ = fmt.Fprintln(s.Out, CreateTableSQL(name, schema))
+	}
+}
+
+func (s *Session) dump(file []string) {
+	if len(file) == 0 {
+		if err := s.DB.Dump(s.Out); err != nil {
+			_, _ = fmt.Fprintf(s.Out, "error: %v\n", err)
+		}
+		return
+	}
+	f, err := os.Create(file[0])
+	if err == nil {
+		err = s.DB.Dump(f)
+		if cerr := f.Close(); err == nil {
+			err = cerr
+		}
+	}
+	if err != nil {
+		_, _ = fmt.Fprintf(s.Out, "error: %v\n", err)
+		return
+	}
+	_, _ = fmt.Fprintf(s.Out, "dumped to %s\n", file[0])
+}
+
+func (s *Session) restore(file string) {
+	f, err := os.Open(file)
+	if err != nil {
+		_, _ = fmt.Fprintf(s.Out, "error: %v\n", err)
+		return
+	}
+	defer func() { _ = f.Close() }()
+	n, err := s.Exec.Restore(f)
+	if err != nil {
+		_, _ = fmt.Fprintf(s.Out, "error: %v\n", err)
+		return
+	}
+	_, _ = fmt.Fprintf(s.Out, "restored %d statements from %s\n", n, file)
+}
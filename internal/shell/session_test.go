@@ -0,0 +1,99 @@
+package shell
+
+import (
+	"bytes"
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/sql/executor"
+)
+
+func TestSession_StatementsAndMetaCommands(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+
+	var out bytes.Buffer
+	s := NewSession(db, &out)
+	var executed []string
+	s.Executed = func(stmt string) { executed = append(executed, stmt) }
+
+	require.False(t, s.Feed("CREATE TABLE users (id INT NOT NULL,"))
+	require.Equal(t, ContinuationPrompt, s.Prompt())
+	require.False(t, s.Feed("  name TEXT);"))
+	require.Equal(t, Prompt, s.Prompt())
+	require.False(t, s.Feed("INSERT INTO users VALUES (1, 'a;b');"))
+	require.Equal(t, []string{
+		"CREATE TABLE users (id INT NOT NULL, name TEXT);",
+		"INSERT INTO users VALUES (1, 'a;b');",
+	}, executed)
+
+	out.Reset()
+	s.Feed("SELECT * FROM users;")
+	require.Equal(t, "id | name\n---+-----\n1  | a;b\n(1 rows)\n", out.String())
+
+	out.Reset()
+	s.Feed(".tables")
+	s.Feed(".schema users")
+	require.Equal(t, "users\nCREATE TABLE users (id INT NOT NULL, name TEXT);\n", out.String())
+
+	out.Reset()
+	dest := t.TempDir()
+	s.Feed(".backup " + dest)
+	require.Equal(t, "backed up to "+dest+"\n", out.String())
+
+	out.Reset()
+	s.Feed(".dump")
+	require.Equal(t, "CREATE TABLE users (id INT NOT NULL, name TEXT);\n"+
+		"INSERT INTO users VALUES (1, 'a;b');\n", out.String())
+	out.Reset()
+	script := filepath.Join(t.TempDir(), "dump.sql")
+	s.Feed(".dump " + script)
+	other, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = other.Close() })
+	NewSession(other, &out).Feed(".restore " + script)
+	require.Equal(t, "dumped to "+script+"\nrestored 2 statements from "+script+"\n", out.String())
+
+	out.Reset()
+	s.Feed("CREATE VIEW names AS SELECT name FROM users;")
+	out.Reset()
+	s.Feed(".schema")
+	require.Equal(t, "CREATE TABLE users (id INT NOT NULL, name TEXT);\n"+
+		"CREATE VIEW names AS SELECT name FROM users;\n", out.String())
+
+	out.Reset()
+	s.Feed("SELECT * FROM missing;")
+	s.Feed(".bogus")
+	require.Contains(t, out.String(), "error: ")
+	require.Contains(t, out.String(), "unknown command: .bogus")
+
+	s.Feed("SELECT *")
+	s.Reset()
+	require.Equal(t, Prompt, s.Prompt())
+	require.True(t, s.Feed(".quit"))
+}
+
+func TestPrintResult_NullsAndDML(t *testing.T) {
+	var out bytes.Buffer
+	PrintResult(&out, &executor.Result{AffectedRows: 3})
+	require.Equal(t, "OK (3 affected)\n", out.String())
+
+	out.Reset()
+	PrintResult(&out, &executor.Result{
+		Columns:      []string{"a", "long_name"},
+		Rows:         [][]any{{int64(10), nil}},
+		AffectedRows: 1,
+	})
+	require.Equal(t, "a  | long_name\n---+----------\n10 | NULL\n(1 rows)\n", out.String())
+}
+
+func TestStatementComplete(t *testing.T) {
+	require.False(t, StatementComplete("SELECT * FROM t"))
+	require.True(t, StatementComplete("SELECT * FROM t;"))
+	require.False(t, StatementComplete("INSERT INTO t VALUES ('a;"))
+	require.True(t, StatementComplete("INSERT INTO t VALUES ('it''s');"))
+}
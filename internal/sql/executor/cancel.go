@@ -0,0 +1,71 @@
+package executor
+
+import (
+	"context"
+	"errors"
+	"fmt"
+
+	"github.com/tuannm99/novasql"
+)
+
+// statement is what stops a running statement: its context is done once it
+// is cancelled, runs past statement_timeout or the caller's context ends.
+type statement struct {
+	ctx    context.Context
+	cancel context.CancelCauseFunc
+	stop   context.CancelFunc // releases the statement_timeout timer
+}
+
+// begin starts a statement stopped by ctx, by Cancel and by the
+// statement_timeout of the handle, and makes it the one operators check.
+func (e *Executor) begin(ctx context.Context) *statement {
+	st := &statement{}
+	st.ctx, st.cancel = context.WithCancelCause(ctx)
+	st.stop = func() {}
+	if e.raw != nil {
+		if d := e.raw.StatementTimeout(); d > 0 {
+			cause := fmt.Errorf("%w: statement_timeout (%s) reached", novasql.ErrQueryCancelled, d)
+			st.ctx, st.stop = context.WithTimeoutCause(st.ctx, d, cause)
+		}
+	}
+	e.running.Store(st)
+	return st
+}
+
+// end releases st; statements started since stay the running one.
+func (e *Executor) end(st *statement) {
+	e.running.CompareAndSwap(st, nil)
+	st.stop()
+	st.cancel(nil)
+}
+
+// Cancel stops the statement the executor is running, from any goroutine:
+// it fails with novasql.ErrQueryCancelled before the next row its scans
+// read. Without a running statement it does nothing; the next statement
+// is not affected. Rows changed by a cancelled UPDATE or DELETE before it
+// stopped stay changed, as after any error midway; run it in a
+// transaction to undo them.
+func (e *Executor) Cancel() {
+	if st := e.running.Load(); st != nil {
+		st.cancel(novasql.ErrQueryCancelled)
+	}
+}
+
+// checkInterrupt fails once the running statement is stopped.
+func (e *Executor) checkInterrupt() error {
+	st := e.running.Load()
+	if st == nil || st.ctx.Err() == nil {
+		return nil
+	}
+	err := context.Cause(st.ctx)
+	if !errors.Is(err, novasql.ErrQueryCancelled) {
+		err = fmt.Errorf("%w: %w", novasql.ErrQueryCancelled, err)
+	}
+	return err
+}
+
+// checked puts a CheckOp over the scan op, so the statement stops between
+// the rows it reads.
+func (e *Executor) checked(op Operator) Operator {
+	return &CheckOp{Child: op, Check: e.checkInterrupt}
+}
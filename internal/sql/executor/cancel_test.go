@@ -0,0 +1,104 @@
+package executor
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql"
+)
+
+// slowJoin pairs every row of big with every other through a nested-loop
+// join none of whose pairs match, so it reads big once per row of it.
+const slowJoin = "SELECT COUNT(*) FROM big a JOIN big b ON a.id < b.v;"
+
+// cancelExecutor is an executor on a database whose table big has n rows.
+func cancelExecutor(t *testing.T, n int) *Executor {
+	t.Helper()
+
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+	_, err = ex.ExecSQL("CREATE TABLE big (id INT, v INT);")
+	require.NoError(t, err)
+	rows := make([][]any, n)
+	for i := range rows {
+		rows[i] = []any{int64(i), int64(-1)}
+	}
+	_, err = db.InsertRows("big", rows)
+	require.NoError(t, err)
+	return ex
+}
+
+func TestStatementTimeout(t *testing.T) {
+	ex := cancelExecutor(t, 1000)
+
+	_, err := ex.ExecSQL("SET statement_timeout = 1;")
+	require.NoError(t, err)
+	start := time.Now()
+	_, err = ex.ExecSQL(slowJoin)
+	require.ErrorIs(t, err, novasql.ErrQueryCancelled)
+	require.ErrorContains(t, err, "statement_timeout (1ms)")
+	require.Equal(t, novasql.CodeCancelled, novasql.CodeOf(err))
+	require.Less(t, time.Since(start), time.Second)
+
+	// Statements within the limit complete, as all do without one.
+	_, err = ex.ExecSQL("SET statement_timeout TO '1m';")
+	require.NoError(t, err)
+	res, err := ex.ExecSQL(slowJoin)
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{int64(0)}}, res.Rows)
+	_, err = ex.ExecSQL("SET statement_timeout = 0;")
+	require.NoError(t, err)
+	res, err = ex.ExecSQL("SHOW statement_timeout;")
+	require.NoError(t, err)
+	require.Equal(t, "0s", res.Rows[0][1])
+}
+
+func TestCancel(t *testing.T) {
+	ex := cancelExecutor(t, 1000)
+
+	// From another goroutine while the statement runs.
+	done, stopped := make(chan struct{}), make(chan struct{})
+	go func() {
+		defer close(stopped)
+		for {
+			select {
+			case <-done:
+				return
+			case <-time.After(time.Millisecond):
+				ex.Cancel()
+			}
+		}
+	}()
+	_, err := ex.ExecSQL(slowJoin)
+	close(done)
+	<-stopped
+	require.ErrorIs(t, err, novasql.ErrQueryCancelled)
+
+	// Between statements it does nothing.
+	ex.Cancel()
+	res, err := ex.ExecSQL("SELECT COUNT(*) FROM big;")
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{int64(1000)}}, res.Rows)
+
+	// Rows read a row at a time stop at the next one.
+	stmt, err := ex.Prepare("SELECT id FROM big;")
+	require.NoError(t, err)
+	rows, err := stmt.Query()
+	require.NoError(t, err)
+	require.True(t, rows.Next())
+	stmt.Cancel()
+	require.False(t, rows.Next())
+	require.ErrorIs(t, rows.Err(), novasql.ErrQueryCancelled)
+
+	// A done context cancels too, with its error in the chain.
+	ctx, cancel := context.WithCancel(context.Background())
+	cancel()
+	_, err = ex.ExecContext(ctx, "SELECT id FROM big;")
+	require.ErrorIs(t, err, novasql.ErrQueryCancelled)
+	require.ErrorIs(t, err, context.Canceled)
+}
@@ -0,0 +1,142 @@
+package executor
+
+import (
+	"errors"
+	"maps"
+	"strings"
+
+	"github.com/tuannm99/novasql"
+)
+
+var (
+	ErrTxActive = errors.New("executor: a transaction is already active")
+	ErrNoTx     = errors.New("executor: no transaction is active")
+)
+
+// maxCachedStmts bounds the prepared statements a Conn keeps.
+const maxCachedStmts = 128
+
+// Conn is one session on a Database. Any number of Conns share a Database,
+// each with its own state: the last insert ID, the cache of prepared
+// statements, the cursors, the session settings and the current
+// transaction. A Conn costs a few maps, so open one per client rather than a
+// Database per client.
+//
+// Like the Database, a Conn is not safe for concurrent use, and the Conns of
+// one Database must not run at the same time; callers serialize them. The
+// selected database (USE) is still the Database's and so shared.
+type Conn struct {
+	*Executor
+
+	stmts    map[string]*Stmt // by SQL text
+	version  uint64           // SchemaVersion the cached statements were planned at
+	settings map[string]string
+	tx       *novasql.Tx
+}
+
+// Connect opens a session on db.
+func Connect(db *novasql.Database) *Conn {
+	return &Conn{
+		Executor: NewExecutor(db),
+		stmts:    make(map[string]*Stmt),
+		version:  db.SchemaVersion(),
+		settings: make(map[string]string),
+	}
+}
+
+// Prepare returns the prepared statement for sql, planning it only the
+// first time: the same SQL text gives the same *Stmt, so bind its values
+// right before each Execute. The cache is dropped whenever a schema of the
+// database changes, through this Conn or any other.
+func (c *Conn) Prepare(sql string) (*Stmt, error) {
+	if v := c.raw.SchemaVersion(); v != c.version {
+		clear(c.stmts)
+		c.version = v
+	}
+	if s, ok := c.stmts[sql]; ok {
+		return s, nil
+	}
+	s, err := c.Executor.Prepare(sql)
+	if err != nil {
+		return nil, err
+	}
+	if len(c.stmts) >= maxCachedStmts {
+		for k := range c.stmts {
+			delete(c.stmts, k)
+			break
+		}
+	}
+	c.stmts[sql] = s
+	return s, nil
+}
+
+// Set sets the session setting name, case-insensitive, to value.
+func (c *Conn) Set(name, value string) {
+	c.settings[strings.ToLower(name)] = value
+}
+
+// Setting returns the session setting name and whether it is set.
+func (c *Conn) Setting(name string) (string, bool) {
+	v, ok := c.settings[strings.ToLower(name)]
+	return v, ok
+}
+
+// Reset unsets the session setting name.
+func (c *Conn) Reset(name string) {
+	delete(c.settings, strings.ToLower(name))
+}
+
+// Settings returns a copy of the session settings.
+func (c *Conn) Settings() map[string]string {
+	return maps.Clone(c.settings)
+}
+
+// Begin starts the transaction of the session; see novasql.Database.Begin.
+func (c *Conn) Begin() (*novasql.Tx, error) {
+	if c.tx != nil {
+		return nil, novasql.NewError(novasql.CodeInvalid, ErrTxActive)
+	}
+	tx, err := c.raw.Begin()
+	if err != nil {
+		return nil, err
+	}
+	c.tx = tx
+	return tx, nil
+}
+
+// Tx is the current transaction of the session, nil when none is active.
+func (c *Conn) Tx() *novasql.Tx { return c.tx }
+
+// Commit commits the current transaction. It is finished either way.
+func (c *Conn) Commit() error {
+	if c.tx == nil {
+		return novasql.NewError(novasql.CodeInvalid, ErrNoTx)
+	}
+	tx := c.tx
+	c.tx = nil
+	return tx.Commit()
+}
+
+// Rollback rolls the current transaction back.
+func (c *Conn) Rollback() error {
+	if c.tx == nil {
+		return novasql.NewError(novasql.CodeInvalid, ErrNoTx)
+	}
+	tx := c.tx
+	c.tx = nil
+	return tx.Rollback()
+}
+
+// Close ends the session: the current transaction is rolled back, the
+// cursors are closed and the cached statements and settings are dropped.
+// The Database stays open.
+func (c *Conn) Close() error {
+	var err error
+	if c.tx != nil {
+		err = c.Rollback()
+	}
+	err = errors.Join(err, c.CloseCursors())
+	clear(c.stmts)
+	clear(c.settings)
+	return err
+}
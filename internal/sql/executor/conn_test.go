@@ -0,0 +1,73 @@
+package executor
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+func TestConn(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	a, b := Connect(db), Connect(db)
+
+	_, err = a.ExecSQL("CREATE TABLE users (id INT PRIMARY KEY AUTOINCREMENT, name TEXT);")
+	require.NoError(t, err)
+	_, err = a.ExecSQL("INSERT INTO users (name) VALUES ('ann');")
+	require.NoError(t, err)
+	_, err = b.ExecSQL("INSERT INTO users (name) VALUES ('bob');")
+	require.NoError(t, err)
+	_, err = a.ExecSQL("INSERT INTO users (name) VALUES ('cat');")
+	require.NoError(t, err)
+	require.Equal(t, int64(3), a.LastInsertID())
+	require.Equal(t, int64(2), b.LastInsertID())
+
+	// Prepared statements are cached by SQL text until a schema changes.
+	sel, err := a.Prepare("SELECT name FROM users WHERE id = ?;")
+	require.NoError(t, err)
+	again, err := a.Prepare("SELECT name FROM users WHERE id = ?;")
+	require.NoError(t, err)
+	require.Same(t, sel, again)
+	_, err = b.ExecSQL("ALTER TABLE users ADD COLUMN age INT;")
+	require.NoError(t, err)
+	again, err = a.Prepare("SELECT name FROM users WHERE id = ?;")
+	require.NoError(t, err)
+	require.NotSame(t, sel, again)
+	require.NoError(t, again.Bind(2))
+	res, err := again.Execute()
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{"bob"}}, res.Rows)
+
+	a.Set("Search_Path", "main")
+	v, ok := a.Setting("search_path")
+	require.True(t, ok)
+	require.Equal(t, "main", v)
+	_, ok = b.Setting("search_path")
+	require.False(t, ok)
+	a.Reset("search_path")
+	require.Empty(t, a.Settings())
+
+	// One transaction per session; Close rolls it back.
+	tx, err := a.Begin()
+	require.NoError(t, err)
+	_, err = a.Begin()
+	require.ErrorIs(t, err, ErrTxActive)
+	require.Same(t, tx, a.Tx())
+	require.Nil(t, b.Tx())
+	require.NoError(t, a.Commit())
+	require.ErrorIs(t, a.Commit(), ErrNoTx)
+	require.Equal(t, novasql.CodeInvalid, novasql.CodeOf(a.Rollback()))
+
+	tx, err = b.Begin()
+	require.NoError(t, err)
+	fs := storage.LocalFileSet{Dir: db.TableDir(), Base: "users"}
+	_, err = tx.GetPage(fs, 0)
+	require.NoError(t, err)
+	require.NoError(t, b.Close())
+	require.Nil(t, b.Tx())
+	require.ErrorIs(t, tx.Commit(), novasql.ErrTxDone)
+}
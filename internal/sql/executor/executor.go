@@ -1,15 +1,26 @@
 package executor
 
 import (
+	"cmp"
+	"context"
 	"errors"
 	"fmt"
+	"io"
 	"log/slog"
+	"os"
+	"slices"
+	"strings"
+	"sync/atomic"
+	"time"
 
 	"github.com/tuannm99/novasql"
 	"github.com/tuannm99/novasql/internal/btree"
 	"github.com/tuannm99/novasql/internal/bufferpool"
+	"github.com/tuannm99/novasql/internal/fulltext"
 	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/mvcc"
 	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/expr"
 	"github.com/tuannm99/novasql/internal/sql/parser"
 	"github.com/tuannm99/novasql/internal/sql/planner"
 	"github.com/tuannm99/novasql/internal/storage"
@@ -20,7 +31,8 @@ type Result struct {
 	Columns []string
 	Rows    [][]any
 
-	// For DML:
+	// For DML: the rows INSERT, UPDATE, DELETE or COPY wrote. With
+	// RETURNING, Columns and Rows hold their RETURNING columns.
 	AffectedRows int64
 }
 
@@ -31,10 +43,35 @@ type executorDB interface {
 	SelectDatabase(name string) (any, error)
 
 	CreateTable(table string, schema record.Schema) (any, error)
+	CreateTempTable(table string, schema record.Schema) (any, error)
 	DropTable(table string) error
+	DropTablePurge(table string) error
 	OpenTable(table string) (*heap.Table, error)
+	RenameTable(oldName, newName string) error
+	AddColumn(table string, col record.Column, def any) error
+	DropColumn(table, column string) error
+	CreateView(name, query string) error
+	DropView(name string) error
+
+	CreateIndex(table, index, column string) error
+	CreateUniqueIndex(table, index, column string) error
+	CreateExpressionIndex(table, index, expression string) error
+	CreateFullTextIndex(table, index, column string) error
+	AddPrimaryKey(table, column string) error
+	AddForeignKey(table string, fk novasql.ForeignKey) error
+	SetAutoIncrement(table, column string) error
+	NextAutoIncrement(table string) (int64, error)
+	ObserveAutoIncrement(table string, v int64) error
+	SetTTL(table, column string) error
+	DropIndex(table, index string) error
 
 	ListTables() ([]*novasql.TableMeta, error)
+	BeginSnapshot() (*mvcc.Tx, error)
+	TempTables() []*novasql.TableMeta
+	Vacuum() (novasql.VacuumStats, error)
+	Analyze(tables ...string) error
+	ImportCSV(table string, r io.Reader, opts novasql.CSVOptions) (int64, error)
+	ExportCSV(table string, w io.Writer, opts novasql.CSVOptions) (int64, error)
 
 	TableDir() string
 	BufferView(fs storage.FileSet) bufferpool.Manager
@@ -58,12 +95,68 @@ func (r realDB) SelectDatabase(name string) (any, error) {
 func (r realDB) CreateTable(table string, schema record.Schema) (any, error) {
 	return r.db.CreateTable(table, schema)
 }
-func (r realDB) DropTable(table string) error { return r.db.DropTable(table) }
+func (r realDB) CreateTempTable(table string, schema record.Schema) (any, error) {
+	return r.db.CreateTempTable(table, schema)
+}
+func (r realDB) DropTable(table string) error      { return r.db.DropTable(table) }
+func (r realDB) DropTablePurge(table string) error { return r.db.DropTablePurge(table) }
 func (r realDB) OpenTable(table string) (*heap.Table, error) {
 	return r.db.OpenTable(table)
 }
+func (r realDB) RenameTable(oldName, newName string) error {
+	return r.db.RenameTable(oldName, newName)
+}
+func (r realDB) AddColumn(table string, col record.Column, def any) error {
+	return r.db.AddColumn(table, col, def)
+}
+func (r realDB) DropColumn(table, column string) error {
+	return r.db.DropColumn(table, column)
+}
+func (r realDB) CreateView(name, query string) error { return r.db.CreateView(name, query) }
+func (r realDB) DropView(name string) error         { return r.db.DropView(name) }
+func (r realDB) CreateIndex(table, index, column string) error {
+	return r.db.CreateIndex(table, index, column)
+}
+func (r realDB) CreateUniqueIndex(table, index, column string) error {
+	return r.db.CreateUniqueIndex(table, index, column)
+}
+func (r realDB) CreateExpressionIndex(table, index, expression string) error {
+	return r.db.CreateExpressionIndex(table, index, expression)
+}
+func (r realDB) CreateFullTextIndex(table, index, column string) error {
+	return r.db.CreateFullTextIndex(table, index, column)
+}
+func (r realDB) AddPrimaryKey(table, column string) error {
+	return r.db.AddPrimaryKey(table, column)
+}
+func (r realDB) AddForeignKey(table string, fk novasql.ForeignKey) error {
+	return r.db.AddForeignKey(table, fk)
+}
+func (r realDB) SetAutoIncrement(table, column string) error {
+	return r.db.SetAutoIncrement(table, column)
+}
+func (r realDB) NextAutoIncrement(table string) (int64, error) {
+	return r.db.NextAutoIncrement(table)
+}
+func (r realDB) ObserveAutoIncrement(table string, v int64) error {
+	return r.db.ObserveAutoIncrement(table, v)
+}
+func (r realDB) SetTTL(table, column string) error {
+	return r.db.SetTTL(table, column)
+}
+func (r realDB) DropIndex(table, index string) error       { return r.db.DropIndex(table, index) }
 func (r realDB) ListTables() ([]*novasql.TableMeta, error) { return r.db.ListTables() }
+func (r realDB) BeginSnapshot() (*mvcc.Tx, error)          { return r.db.BeginSnapshot() }
+func (r realDB) TempTables() []*novasql.TableMeta          { return r.db.TempTables() }
 func (r realDB) TableDir() string                          { return r.db.TableDir() }
+func (r realDB) Vacuum() (novasql.VacuumStats, error)      { return r.db.Vacuum() }
+func (r realDB) Analyze(tables ...string) error            { return r.db.Analyze(tables...) }
+func (r realDB) ImportCSV(table string, rd io.Reader, opts novasql.CSVOptions) (int64, error) {
+	return r.db.ImportCSV(table, rd, opts)
+}
+func (r realDB) ExportCSV(table string, w io.Writer, opts novasql.CSVOptions) (int64, error) {
+	return r.db.ExportCSV(table, w, opts)
+}
 func (r realDB) BufferView(fs storage.FileSet) bufferpool.Manager {
 	return r.db.BufferView(fs)
 }
@@ -79,6 +172,19 @@ type Executor struct {
 
 	// for unit-test: inject btree insert behavior
 	btreeInsertFn func(im novasql.IndexMeta, key int64, tid heap.TID) error
+
+	// lastInsertID is the AUTOINCREMENT value of the last row inserted.
+	lastInsertID int64
+
+	// cursors are the rows of the open cursors, by name.
+	cursors map[string]*Rows
+
+	// log receives a debug record per statement (nil = slog.Default()).
+	log *slog.Logger
+
+	// running is the statement being executed, nil between statements; see
+	// Cancel.
+	running atomic.Pointer[statement]
 }
 
 func NewExecutor(db *novasql.Database) *Executor {
@@ -102,13 +208,77 @@ func NewExecutorForTest(db executorDB, raw *novasql.Database) *Executor {
 	return ex
 }
 
+// LastInsertID is the AUTOINCREMENT value of the last row the executor
+// inserted, as SELECT last_insert_rowid() returns it; 0 before any.
+func (e *Executor) LastInsertID() int64 { return e.lastInsertID }
+
+// SetLogger sends the statement records of the executor to l, usually
+// slog.Default() with attributes of the session; nil restores the default.
+func (e *Executor) SetLogger(l *slog.Logger) { e.log = l }
+
+func (e *Executor) logger() *slog.Logger {
+	if e.log != nil {
+		return e.log
+	}
+	return slog.Default()
+}
+
 // ExecSQL is the top-level entry: SQL string -> Result.
 func (e *Executor) ExecSQL(sql string) (*Result, error) {
+	return e.ExecContext(context.Background(), sql)
+}
+
+// ExecContext is ExecSQL stopped, like by Cancel, once ctx is done.
+func (e *Executor) ExecContext(ctx context.Context, sql string) (*Result, error) {
+	start := time.Now()
+	st := e.begin(ctx)
+	res, err := e.execSQL(sql)
+	e.end(st)
+	e.observe(start, sql, err)
+	return res, err
+}
+
+func (e *Executor) execSQL(sql string) (*Result, error) {
 	stmt, err := parser.Parse(sql)
+	if err != nil {
+		return nil, syntaxError(err)
+	}
+	return e.execStatement(stmt)
+}
+
+// observe records the statement sql, started at start, in the metrics of
+// the database and logs it at debug level.
+func (e *Executor) observe(start time.Time, sql string, err error) {
+	d := time.Since(start)
+	e.raw.MetricsRegistry().ObserveQuery(d, err != nil)
+	if err != nil {
+		e.logger().Debug("query failed", "sql", sql, "duration", d, "code", novasql.CodeOf(err), "err", err)
+		return
+	}
+	e.logger().Debug("query", "sql", sql, "duration", d)
+}
+
+// syntaxError is a parser error as a *novasql.Error with CodeSyntax and the
+// position of the *parser.SyntaxError in it.
+func syntaxError(err error) error {
+	e := novasql.NewError(novasql.CodeSyntax, err)
+	var se *parser.SyntaxError
+	if errors.As(err, &se) {
+		e.Pos = se.Pos
+	}
+	return e
+}
+
+func (e *Executor) execStatement(stmt parser.Statement) (*Result, error) {
+	plan, err := e.planStatement(stmt)
 	if err != nil {
 		return nil, err
 	}
+	return e.execPlan(plan)
+}
 
+// planStatement plans stmt, which must not have placeholders.
+func (e *Executor) planStatement(stmt parser.Statement) (planner.Plan, error) {
 	if e.raw == nil {
 		return nil, fmt.Errorf("executor: raw database is nil (planner requires *novasql.Database)")
 	}
@@ -117,7 +287,10 @@ func (e *Executor) ExecSQL(sql string) (*Result, error) {
 	if err != nil {
 		return nil, err
 	}
-	return e.execPlan(plan)
+	if n := planner.NumParams(plan); n > 0 {
+		return nil, fmt.Errorf("executor: statement has %d parameters; use Prepare to bind them", n)
+	}
+	return plan, nil
 }
 
 func (e *Executor) execPlan(p planner.Plan) (*Result, error) {
@@ -128,24 +301,49 @@ func (e *Executor) execPlan(p planner.Plan) (*Result, error) {
 		return e.execDropDatabase(plan)
 	case *planner.UseDatabasePlan:
 		return e.execUseDatabase(plan)
+	case *planner.SetPlan:
+		return e.execSet(plan)
+	case *planner.ShowPlan:
+		return e.execShow(plan)
 
 	case *planner.CreateTablePlan:
 		return e.execCreateTable(plan)
 	case *planner.DropTablePlan:
 		return e.execDropTable(plan)
-
-	case *planner.InsertPlan:
-		return e.execInsert(plan)
-
-	case *planner.IndexLookupPlan:
-		return e.execIndexLookup(plan)
-	case *planner.SeqScanPlan:
-		return e.execSeqScan(plan)
-
-	case *planner.UpdatePlan:
-		return e.execUpdate(plan)
-	case *planner.DeletePlan:
-		return e.execDelete(plan)
+	case *planner.AlterTablePlan:
+		return e.execAlterTable(plan)
+	case *planner.CreateViewPlan:
+		return &Result{}, e.DB.CreateView(plan.Name, plan.Query)
+	case *planner.DropViewPlan:
+		return &Result{}, e.DB.DropView(plan.Name)
+
+	case *planner.CreateIndexPlan:
+		return e.execCreateIndex(plan)
+	case *planner.DropIndexPlan:
+		return e.execDropIndex(plan)
+
+	case *planner.VacuumPlan:
+		return e.execVacuum()
+	case *planner.AnalyzePlan:
+		return e.execAnalyze(plan)
+	case *planner.CopyPlan:
+		return e.execCopy(plan)
+	case *planner.LastInsertRowIDPlan:
+		return &Result{Columns: []string{"last_insert_rowid"}, Rows: [][]any{{e.lastInsertID}}}, nil
+
+	case *planner.InsertPlan,
+		*planner.IndexLookupPlan, *planner.IndexScanPlan, *planner.JoinPlan, *planner.SeqScanPlan,
+		*planner.ViewScanPlan, *planner.UpdatePlan, *planner.DeletePlan:
+		return e.execQuery(p)
+	case *planner.ExplainPlan:
+		return e.execExplain(plan)
+
+	case *planner.DeclareCursorPlan:
+		return e.declareCursor(plan)
+	case *planner.FetchPlan:
+		return e.fetch(plan)
+	case *planner.CloseCursorPlan:
+		return &Result{}, e.closeCursor(plan.Name)
 
 	default:
 		return nil, fmt.Errorf("executor: unsupported plan type %T", p)
@@ -173,229 +371,576 @@ func (e *Executor) execUseDatabase(p *planner.UseDatabasePlan) (*Result, error)
 	return &Result{AffectedRows: 0}, nil
 }
 
+// execCreateTable creates the table, the unique indexes behind its
+// constraints and its foreign keys; if one of those fails, the table is
+// dropped again. Temporary tables have none of those.
 func (e *Executor) execCreateTable(p *planner.CreateTablePlan) (*Result, error) {
+	if p.Temp {
+		if _, err := e.DB.CreateTempTable(p.TableName, p.Schema); err != nil {
+			return nil, err
+		}
+		return &Result{AffectedRows: 0}, nil
+	}
 	_, err := e.DB.CreateTable(p.TableName, p.Schema)
 	if err != nil {
 		return nil, err
 	}
+	err = func() error {
+		if p.PrimaryKey != "" {
+			if err := e.DB.AddPrimaryKey(p.TableName, p.PrimaryKey); err != nil {
+				return err
+			}
+		}
+		if p.AutoIncrement != "" {
+			if err := e.DB.SetAutoIncrement(p.TableName, p.AutoIncrement); err != nil {
+				return err
+			}
+		}
+		for _, col := range p.Unique {
+			if err := e.DB.CreateUniqueIndex(p.TableName, p.TableName+"_"+col+"_key", col); err != nil {
+				return err
+			}
+		}
+		for _, fk := range p.ForeignKeys {
+			if err := e.DB.AddForeignKey(p.TableName, fk); err != nil {
+				return err
+			}
+		}
+		if p.TTLColumn != "" {
+			return e.DB.SetTTL(p.TableName, p.TTLColumn)
+		}
+		return nil
+	}()
+	if err != nil {
+		_ = e.DB.DropTablePurge(p.TableName)
+		return nil, err
+	}
 	return &Result{AffectedRows: 0}, nil
 }
 
 func (e *Executor) execDropTable(p *planner.DropTablePlan) (*Result, error) {
-	if err := e.DB.DropTable(p.TableName); err != nil {
+	drop := e.DB.DropTable
+	if p.Purge {
+		drop = e.DB.DropTablePurge
+	}
+	if err := drop(p.TableName); err != nil {
 		return nil, err
 	}
 	return &Result{AffectedRows: 0}, nil
 }
 
-func (e *Executor) execInsert(p *planner.InsertPlan) (*Result, error) {
-	tbl, err := e.DB.OpenTable(p.TableName)
+func (e *Executor) execAlterTable(p *planner.AlterTablePlan) (*Result, error) {
+	var err error
+	switch {
+	case p.RenameTo != "":
+		err = e.DB.RenameTable(p.TableName, p.RenameTo)
+	case p.DropColumn != "":
+		err = e.DB.DropColumn(p.TableName, p.DropColumn)
+	case p.AddColumn == nil:
+		err = e.DB.AddForeignKey(p.TableName, *p.ForeignKey)
+	default:
+		err = e.addColumn(p)
+	}
 	if err != nil {
 		return nil, err
 	}
+	return &Result{AffectedRows: 0}, nil
+}
 
-	// Evaluate literal expressions only (phase 1).
-	raw := make([]any, len(p.Values))
-	for i, expr := range p.Values {
-		lit, ok := expr.(*parser.LiteralExpr)
-		if !ok {
-			return nil, fmt.Errorf("executor: only literal expressions supported in INSERT")
-		}
-		raw[i] = lit.Value
+// addColumn adds the column of p, then its constraints; the column goes
+// again when a constraint cannot be added.
+func (e *Executor) addColumn(p *planner.AlterTablePlan) error {
+	col := p.AddColumn.Name
+	if err := e.DB.AddColumn(p.TableName, *p.AddColumn, p.Default); err != nil {
+		return err
 	}
-
-	// Normalize int -> int64 (strict type checks follow schema).
-	values, err := coerceInsertValues(tbl.Schema, raw)
+	err := func() error {
+		switch {
+		case p.PrimaryKey:
+			if err := e.DB.AddPrimaryKey(p.TableName, col); err != nil {
+				return err
+			}
+		case p.Unique:
+			if err := e.DB.CreateUniqueIndex(p.TableName, p.TableName+"_"+col+"_key", col); err != nil {
+				return err
+			}
+		}
+		if p.ForeignKey != nil {
+			return e.DB.AddForeignKey(p.TableName, *p.ForeignKey)
+		}
+		return nil
+	}()
 	if err != nil {
-		return nil, err
+		_ = e.DB.DropColumn(p.TableName, col)
 	}
+	return err
+}
 
-	tid, err := tbl.Insert(values)
-	if err != nil {
+func (e *Executor) execCreateIndex(p *planner.CreateIndexPlan) (*Result, error) {
+	create, key := e.DB.CreateIndex, p.Column
+	switch {
+	case p.Expression != "":
+		create, key = e.DB.CreateExpressionIndex, p.Expression
+	case p.Unique:
+		create = e.DB.CreateUniqueIndex
+	case p.FullText:
+		create = e.DB.CreateFullTextIndex
+	}
+	if err := create(p.TableName, p.IndexName, key); err != nil {
 		return nil, err
 	}
+	return &Result{AffectedRows: 0}, nil
+}
 
-	// Maintain btree indexes on INSERT (only int64 key columns for now).
-	if err := e.syncBTreeIndexesOnInsert(p.TableName, tbl.Schema, values, tid); err != nil {
+func (e *Executor) execDropIndex(p *planner.DropIndexPlan) (*Result, error) {
+	if err := e.DB.DropIndex(p.TableName, p.IndexName); err != nil {
 		return nil, err
 	}
-
-	return &Result{AffectedRows: 1}, nil
+	return &Result{AffectedRows: 0}, nil
 }
 
-func (e *Executor) execSeqScan(p *planner.SeqScanPlan) (*Result, error) {
-	tbl, err := e.DB.OpenTable(p.TableName)
+// execVacuum reports the pages reclaimed as affected rows.
+func (e *Executor) execVacuum() (*Result, error) {
+	st, err := e.DB.Vacuum()
 	if err != nil {
 		return nil, err
 	}
+	return &Result{AffectedRows: int64(st.PagesBefore) - int64(st.PagesAfter)}, nil
+}
 
-	res := &Result{}
-	for _, col := range tbl.Schema.Cols {
-		res.Columns = append(res.Columns, col.Name)
+func (e *Executor) execAnalyze(p *planner.AnalyzePlan) (*Result, error) {
+	var tables []string
+	if p.TableName != "" {
+		tables = append(tables, p.TableName)
+	}
+	if err := e.DB.Analyze(tables...); err != nil {
+		return nil, err
 	}
+	return &Result{AffectedRows: 0}, nil
+}
 
-	err = tbl.Scan(func(id heap.TID, row []any) error {
-		if p.Where != nil {
-			ok, err := matchWhere(tbl.Schema, p.Where, row)
-			if err != nil {
-				return err
-			}
-			if !ok {
-				return nil
-			}
+// execCopy runs COPY; the affected-row count is the number of rows read or
+// written.
+func (e *Executor) execCopy(p *planner.CopyPlan) (*Result, error) {
+	var n int64
+	if p.From {
+		f, err := os.Open(p.File)
+		if err != nil {
+			return nil, err
+		}
+		defer func() { _ = f.Close() }()
+		if n, err = e.DB.ImportCSV(p.TableName, f, p.Options); err != nil {
+			return nil, err
 		}
+		return &Result{AffectedRows: n}, nil
+	}
 
-		// avoid slice aliasing
-		cp := make([]any, len(row))
-		copy(cp, row)
-		res.Rows = append(res.Rows, cp)
-		return nil
-	})
+	f, err := os.Create(p.File)
 	if err != nil {
 		return nil, err
 	}
+	n, err = e.DB.ExportCSV(p.TableName, f, p.Options)
+	if cerr := f.Close(); err == nil {
+		err = cerr
+	}
+	if err != nil {
+		return nil, err
+	}
+	return &Result{AffectedRows: n}, nil
+}
 
+// execQuery runs a query or DML plan: a query returns its rows, DML the
+// number of rows it changed.
+func (e *Executor) execQuery(p planner.Plan) (*Result, error) {
+	op, cols, err := e.planOp(p)
+	if err != nil {
+		return nil, err
+	}
+	rows, err := drain(op)
+	if err != nil {
+		return nil, err
+	}
+	if cols == nil {
+		return &Result{AffectedRows: int64(len(rows))}, nil
+	}
+
+	res := &Result{Columns: cols, Rows: make([][]any, 0, len(rows))}
+	for _, r := range rows {
+		res.Rows = append(res.Rows, r.Values)
+	}
 	res.AffectedRows = int64(len(res.Rows))
 	return res, nil
 }
 
-func (e *Executor) execIndexLookup(p *planner.IndexLookupPlan) (*Result, error) {
+// planOp builds the operators of a query or DML plan, not opened yet. cols
+// are the columns a query returns, nil for DML without RETURNING.
+func (e *Executor) planOp(p planner.Plan) (op Operator, cols []string, err error) {
+	switch plan := p.(type) {
+	case *planner.IndexLookupPlan:
+		return e.indexLookupOp(plan)
+	case *planner.IndexScanPlan:
+		return e.indexScanOp(plan)
+	case *planner.JoinPlan:
+		return e.joinPlanOp(plan)
+	case *planner.SeqScanPlan:
+		return e.seqScanOp(plan)
+	case *planner.ViewScanPlan:
+		return e.viewScanOp(plan)
+	case *planner.InsertPlan:
+		op, err = e.insertOp(plan)
+		return returning(op, plan.Returning, err)
+	case *planner.UpdatePlan:
+		op, err = e.updateOp(plan)
+		return returning(op, plan.Returning, err)
+	case *planner.DeletePlan:
+		op, err = e.deleteOp(plan)
+		return returning(op, plan.Returning, err)
+	default:
+		return nil, nil, fmt.Errorf("executor: plan type %T has no operators", p)
+	}
+}
+
+// returning projects the rows a DML op writes onto the RETURNING columns
+// of ret; without RETURNING op returns no columns.
+func returning(op Operator, ret *planner.Returning, err error) (Operator, []string, error) {
+	if err != nil || ret == nil {
+		return op, nil, err
+	}
+	return &ProjectionOp{Child: op, Positions: ret.Positions}, ret.Columns, nil
+}
+
+func (e *Executor) insertOp(p *planner.InsertPlan) (Operator, error) {
 	tbl, err := e.DB.OpenTable(p.TableName)
 	if err != nil {
 		return nil, err
 	}
 
-	idxFS := storage.LocalFileSet{
-		Dir:  e.DB.TableDir(),
-		Base: p.IndexFileBase,
+	// Evaluate literal expressions only (phase 1).
+	raw := make([]any, len(p.Values))
+	for i, v := range p.Values {
+		lit, ok := v.(*parser.LiteralExpr)
+		if !ok {
+			return nil, fmt.Errorf("executor: only literal expressions supported in INSERT")
+		}
+		raw[i] = lit.Value
+	}
+	if p.Columns != nil {
+		if raw, err = columnListValues(tbl.Schema, p.Columns, raw); err != nil {
+			return nil, err
+		}
 	}
-	idxBP := e.DB.BufferView(idxFS)
 
-	tree, err := btree.OpenTree(e.DB.StorageManager(), idxFS, idxBP)
+	// A NULL in the AUTOINCREMENT column gets the next value when the row is
+	// inserted.
+	meta, err := e.tableMeta(p.TableName)
 	if err != nil {
 		return nil, err
 	}
-	defer func() { _ = tree.Close() }()
+	schema, auto := tbl.Schema, -1
+	if meta.AutoIncrement != nil {
+		auto = colPos(schema, meta.AutoIncrement.Column)
+	}
+	if auto >= 0 && raw[auto] == nil {
+		schema.Cols = slices.Clone(schema.Cols)
+		schema.Cols[auto].Nullable = true
+	}
 
-	tids, err := tree.SearchEqual(p.Key)
+	// Normalize int -> int64 (strict type checks follow schema).
+	values, err := coerceInsertValues(p.TableName, schema, raw)
 	if err != nil {
 		return nil, err
 	}
 
-	res := &Result{}
-	for _, col := range tbl.Schema.Cols {
-		res.Columns = append(res.Columns, col.Name)
+	return &InsertOp{
+		Child: &ValuesOp{Rows: [][]any{values}},
+		Table: tbl,
+		Check: func(values []any) error {
+			if auto >= 0 {
+				if err := e.autoIncrement(p.TableName, values, auto); err != nil {
+					return err
+				}
+			}
+			if err := e.checkUnique(p.TableName, tbl, heap.TID{}, nil, values); err != nil {
+				return err
+			}
+			return e.checkReferences(p.TableName, tbl.Schema, nil, values)
+		},
+		// Maintain btree indexes on INSERT (only int64 key columns for now).
+		After: func(values []any, tid heap.TID) error {
+			if auto >= 0 {
+				e.lastInsertID, _ = values[auto].(int64)
+			}
+			if err := e.syncBTreeIndexesOnInsert(p.TableName, tbl.Schema, values, tid); err != nil {
+				return err
+			}
+			return e.syncKVIndexes(p.TableName, tbl.Schema, tid, nil, values)
+		},
+	}, nil
+}
+
+func (e *Executor) seqScanOp(p *planner.SeqScanPlan) (Operator, []string, error) {
+	tbl, err := e.DB.OpenTable(p.TableName)
+	if err != nil {
+		return nil, nil, err
+	}
+
+	op := e.filterOp(e.checked(&SeqScanOp{Table: tbl}), tbl.Schema, p.Where)
+	return e.selectOp(op, tbl.Schema, p.Shape)
+}
+
+// viewScanOp runs the query of a view and filters and shapes its rows.
+func (e *Executor) viewScanOp(p *planner.ViewScanPlan) (Operator, []string, error) {
+	op, _, err := e.planOp(p.Plan)
+	if err != nil {
+		return nil, nil, err
 	}
+	return e.selectOp(e.filterOp(op, p.Schema, p.Where), p.Schema, p.Shape)
+}
 
-	for _, tid := range tids {
-		row, err := tbl.Get(tid)
+func (e *Executor) joinPlanOp(p *planner.JoinPlan) (Operator, []string, error) {
+	op, _, err := e.scanOp(p.From)
+	if err != nil {
+		return nil, nil, err
+	}
+	for _, j := range p.Joins {
+		right, width, err := e.scanOp(j.Table)
 		if err != nil {
-			// stale/dangling index entry: ignore
-			continue
+			_ = op.Close()
+			return nil, nil, err
 		}
-		// SAFETY: re-check predicate to avoid returning wrong row if index stale after UPDATE
-		if p.Where != nil {
-			ok, err := matchWhere(tbl.Schema, p.Where, row)
-			if err != nil {
-				return nil, err
+		op = joinOp(op, right, width, j)
+	}
+	if p.Layout != nil {
+		op = &ProjectionOp{Child: op, Positions: p.Layout}
+	}
+	return e.selectOp(e.filterOp(op, p.Schema, p.Where), p.Schema, p.Shape)
+}
+
+// scanOp reads every row of the table or view of a join, and returns how
+// many columns they have.
+func (e *Executor) scanOp(ref planner.TableRef) (Operator, int, error) {
+	if ref.View != nil {
+		op, cols, err := e.planOp(ref.View)
+		return op, len(cols), err
+	}
+	tbl, err := e.DB.OpenTable(ref.Name)
+	if err != nil {
+		return nil, 0, err
+	}
+	return e.checked(&SeqScanOp{Table: tbl}), len(tbl.Schema.Cols), nil
+}
+
+// joinOp joins right to left as j says: a hash join on the equality
+// conditions, with the other conditions checked per matching pair, or a
+// nested-loop join when there is no equality.
+func joinOp(left, right Operator, rightWidth int, j planner.Join) Operator {
+	spec := JoinSpec{Left: left, Right: right, LeftOuter: j.Left, RightWidth: rightWidth}
+	var leftKeys, rightKeys []int
+	var rest []planner.JoinCond
+	for _, c := range j.On {
+		if c.Op == "=" {
+			leftKeys = append(leftKeys, c.Left)
+			rightKeys = append(rightKeys, c.Right)
+		} else {
+			rest = append(rest, c)
+		}
+	}
+	if len(leftKeys) == 0 {
+		spec.On = joinPredicate(j.On)
+		return &NestedLoopJoinOp{JoinSpec: spec}
+	}
+	spec.On = joinPredicate(rest)
+	return &HashJoinOp{JoinSpec: spec, LeftKeys: leftKeys, RightKeys: rightKeys}
+}
+
+// joinPredicate checks conds on a pair of rows; NULL never matches.
+func joinPredicate(conds []planner.JoinCond) func(left, right []any) bool {
+	if len(conds) == 0 {
+		return nil
+	}
+	return func(left, right []any) bool {
+		for _, c := range conds {
+			l, r := left[c.Left], right[c.Right]
+			if l == nil || r == nil {
+				return false
 			}
-			if !ok {
-				continue
+			if ok, err := compareOp(c.Op, compareValues(l, r)); err != nil || !ok {
+				return false
 			}
 		}
-		cp := make([]any, len(row))
-		copy(cp, row)
-		res.Rows = append(res.Rows, cp)
+		return true
 	}
-
-	res.AffectedRows = int64(len(res.Rows))
-	return res, nil
 }
 
-func (e *Executor) execUpdate(p *planner.UpdatePlan) (*Result, error) {
+func (e *Executor) indexLookupOp(p *planner.IndexLookupPlan) (Operator, []string, error) {
 	tbl, err := e.DB.OpenTable(p.TableName)
 	if err != nil {
-		return nil, err
+		return nil, nil, err
 	}
 
-	var affected int64
+	idxFS := storage.LocalFileSet{
+		Dir:  e.DB.TableDir(),
+		Base: p.IndexFileBase,
+	}
+	idxBP := e.DB.BufferView(idxFS)
 
-	// Scan and update tuples in heap.
-	err = tbl.Scan(func(id heap.TID, row []any) error {
-		if p.Where != nil {
-			ok, err := matchWhere(tbl.Schema, p.Where, row)
-			if err != nil {
-				return err
-			}
-			if !ok {
-				return nil
-			}
-		}
+	tree, err := btree.OpenTree(e.DB.StorageManager(), idxFS, idxBP)
+	if err != nil {
+		return nil, nil, err
+	}
 
-		newRow := make([]any, len(row))
-		copy(newRow, row)
+	// SAFETY: re-check predicate to avoid returning wrong row if index stale after UPDATE
+	op := e.filterOp(e.checked(&IndexScanOp{Table: tbl, Tree: tree, Key: p.Key}), tbl.Schema, p.Where)
+	return e.selectOp(op, tbl.Schema, p.Shape)
+}
 
-		// Apply assignments by column name
-		for _, a := range p.Assigns {
-			pos := colPos(tbl.Schema, a.Column)
-			if pos < 0 {
-				return fmt.Errorf("executor: unknown column in UPDATE: %s", a.Column)
-			}
-			newRow[pos] = a.Value
+func (e *Executor) indexScanOp(p *planner.IndexScanPlan) (Operator, []string, error) {
+	tbl, err := e.DB.OpenTable(p.TableName)
+	if err != nil {
+		return nil, nil, err
+	}
+	var start, end []byte
+	if p.Where.Op != "MATCH" {
+		coll, err := e.indexCollation(p.TableName, tbl.Schema, p.IndexFileBase)
+		if err != nil {
+			return nil, nil, err
 		}
-
-		if err := tbl.Update(id, newRow); err != nil {
-			return err
+		if start, end, err = indexRange(p.Where.Op, coll.IndexKey(p.Where.Value)); err != nil {
+			return nil, nil, err
 		}
+	}
+	tree, err := e.openKVIndex(p.IndexFileBase)
+	if err != nil {
+		return nil, nil, err
+	}
 
-		// Optional: insert new index entry for updated indexed key (old stays stale).
-		// Correctness is preserved because index lookup re-checks WHERE against heap row.
-		if err := e.syncBTreeIndexesOnUpdateMaybeInsert(p.TableName, tbl.Schema, newRow, id, p.Assigns); err != nil {
-			return err
-		}
+	var op Operator = &IndexRangeScanOp{Table: tbl, Tree: tree, Start: start, End: end}
+	if p.Where.Op == "MATCH" {
+		query, _ := p.Where.Value.(string)
+		op = &FullTextScanOp{Table: tbl, Tree: tree, Query: query}
+	}
+	return e.selectOp(e.filterOp(e.checked(op), tbl.Schema, p.Where), tbl.Schema, p.Shape)
+}
 
-		affected++
-		return nil
-	})
+// indexCollation is the collation of the KVTree index of table stored at
+// base, nil for bytewise.
+func (e *Executor) indexCollation(table string, schema record.Schema, base string) (*expr.Collation, error) {
+	idxs, err := e.listIndexes(table, novasql.IndexKindKVTree)
 	if err != nil {
 		return nil, err
 	}
+	for _, im := range idxs {
+		if im.FileBase == base {
+			return im.Collation(schema)
+		}
+	}
+	return nil, nil
+}
 
-	return &Result{AffectedRows: affected}, nil
+// indexRange returns the KVTree key range [start, end) (nil = unbounded)
+// holding the index entries whose value v' satisfies "v' <op> v".
+func indexRange(op string, v any) (start, end []byte, err error) {
+	key, err := btree.EncodeIndexValue(v)
+	if err != nil {
+		return nil, nil, err
+	}
+	switch op {
+	case "", "=":
+		return key, btree.PrefixEnd(key), nil
+	case "<":
+		return nil, key, nil
+	case "<=":
+		return nil, btree.PrefixEnd(key), nil
+	case ">":
+		after := btree.PrefixEnd(key)
+		if after == nil {
+			return key, key, nil // nothing sorts above v
+		}
+		return after, nil, nil
+	case ">=":
+		return key, nil, nil
+	default:
+		return nil, nil, fmt.Errorf("executor: unsupported WHERE operator %q", op)
+	}
+}
+
+// sortBudget is storage.sort_mem_bytes, spilling to the handle's TempDir.
+func (e *Executor) sortBudget() sortBudget {
+	if e.raw == nil || e.raw.SortMemBytes() < 0 {
+		return sortBudget{}
+	}
+	return sortBudget{memBytes: e.raw.SortMemBytes(), tempDir: e.raw.TempDir}
 }
 
-func (e *Executor) execDelete(p *planner.DeletePlan) (*Result, error) {
+// selectOp adds the SELECT shape on top of op.
+func (e *Executor) selectOp(op Operator, schema record.Schema, shape planner.Shape) (Operator, []string, error) {
+	shaped, cols, err := shapeOps(op, schema, shape, e.sortBudget())
+	if err != nil {
+		_ = op.Close()
+		return nil, nil, err
+	}
+	return shaped, cols, nil
+}
+
+func (e *Executor) updateOp(p *planner.UpdatePlan) (Operator, error) {
 	tbl, err := e.DB.OpenTable(p.TableName)
 	if err != nil {
 		return nil, err
 	}
 
-	var affected int64
-
-	err = tbl.Scan(func(id heap.TID, row []any) error {
-		if p.Where != nil {
-			ok, err := matchWhere(tbl.Schema, p.Where, row)
-			if err != nil {
+	return &UpdateOp{
+		Child: e.filterOp(e.checked(&SeqScanOp{Table: tbl}), tbl.Schema, p.Where),
+		Table: tbl,
+		Assign: func(row []any) ([]any, error) {
+			newRow := make([]any, len(row))
+			copy(newRow, row)
+
+			// Apply assignments by column name
+			for _, a := range p.Assigns {
+				pos := colPos(tbl.Schema, a.Column)
+				if pos < 0 {
+					return nil, fmt.Errorf("executor: unknown column in UPDATE: %s", a.Column)
+				}
+				if a.Value == nil && !tbl.Schema.Cols[pos].Nullable {
+					return nil, novasql.NotNullError(p.TableName, a.Column)
+				}
+				newRow[pos] = a.Value
+			}
+			return newRow, nil
+		},
+		Check: func(oldRow, newRow []any, id heap.TID) error {
+			if err := e.checkUnique(p.TableName, tbl, id, oldRow, newRow); err != nil {
 				return err
 			}
-			if !ok {
-				return nil
+			if err := e.checkReferences(p.TableName, tbl.Schema, oldRow, newRow); err != nil {
+				return err
 			}
-		}
+			return e.checkReferenced(p.TableName, tbl.Schema, id, oldRow, newRow)
+		},
+		// KVTree indexes move the row's entry to its new key. Legacy btree
+		// indexes keep the old entry; lookups re-check WHERE against the heap row.
+		After: func(oldRow, newRow []any, id heap.TID) error {
+			return e.syncKVIndexes(p.TableName, tbl.Schema, id, oldRow, newRow)
+		},
+	}, nil
+}
 
-		if err := tbl.Delete(id); err != nil {
-			return err
-		}
-		// NOTE: index delete not implemented yet -> index entries become stale.
-		// Correctness: IndexLookupPlan filters by heap.Get + matchWhere.
-		affected++
-		return nil
-	})
+func (e *Executor) deleteOp(p *planner.DeletePlan) (Operator, error) {
+	tbl, err := e.DB.OpenTable(p.TableName)
 	if err != nil {
 		return nil, err
 	}
 
-	return &Result{AffectedRows: affected}, nil
+	return &DeleteOp{
+		Child: e.filterOp(e.checked(&SeqScanOp{Table: tbl}), tbl.Schema, p.Where),
+		Table: tbl,
+		Before: func(values []any, id heap.TID) error {
+			return e.onDelete(p.TableName, tbl.Schema, id, values, make(map[rowRef]bool))
+		},
+		After: func(values []any, id heap.TID) error {
+			return e.syncKVIndexes(p.TableName, tbl.Schema, id, values, nil)
+		},
+	}, nil
 }
 
 func colPos(schema record.Schema, name string) int {
@@ -407,7 +952,72 @@ func colPos(schema record.Schema, name string) int {
 	return -1
 }
 
-func matchWhere(schema record.Schema, w *planner.WhereEq, row []any) (bool, error) {
+// compareValues orders NULL first, then by value for same-typed operands.
+func compareValues(a, b any) int {
+	switch {
+	case a == nil && b == nil:
+		return 0
+	case a == nil:
+		return -1
+	case b == nil:
+		return 1
+	}
+	switch av := a.(type) {
+	case int64:
+		if bv, ok := b.(int64); ok {
+			return cmp.Compare(av, bv)
+		}
+	case float64:
+		if bv, ok := b.(float64); ok {
+			return cmp.Compare(av, bv)
+		}
+	case string:
+		if bv, ok := b.(string); ok {
+			return strings.Compare(av, bv)
+		}
+	case bool:
+		if bv, ok := b.(bool); ok {
+			switch {
+			case av == bv:
+				return 0
+			case !av:
+				return -1
+			default:
+				return 1
+			}
+		}
+	case record.Date:
+		if bv, ok := b.(record.Date); ok {
+			return cmp.Compare(av, bv)
+		}
+	case record.Time:
+		if bv, ok := b.(record.Time); ok {
+			return cmp.Compare(av, bv)
+		}
+	case record.Timestamp:
+		if bv, ok := b.(record.Timestamp); ok {
+			return cmp.Compare(av, bv)
+		}
+	case record.Decimal:
+		if bv, ok := b.(record.Decimal); ok {
+			return av.Cmp(bv)
+		}
+	}
+	return 0
+}
+
+// compareCollated is compareValues, with text ordered by coll (nil:
+// bytewise).
+func compareCollated(coll *expr.Collation, a, b any) int {
+	if x, ok := a.(string); ok && coll != nil {
+		if y, ok := b.(string); ok {
+			return coll.Compare(x, y)
+		}
+	}
+	return compareValues(a, b)
+}
+
+func matchWhere(schema record.Schema, w *planner.Where, row []any) (bool, error) {
 	pos := colPos(schema, w.Column)
 	if pos < 0 {
 		return false, fmt.Errorf("executor: unknown column in WHERE: %s", w.Column)
@@ -415,39 +1025,77 @@ func matchWhere(schema record.Schema, w *planner.WhereEq, row []any) (bool, erro
 	got := row[pos]
 	want := w.Value
 
-	// NULL handling
+	// NULL handling: "col = NULL" matches NULLs, ordering comparisons never match.
 	if got == nil || want == nil {
-		return got == nil && want == nil, nil
+		return (w.Op == "" || w.Op == "=") && got == nil && want == nil, nil
+	}
+	if !hasColumnType(schema.Cols[pos].Type, got) || !hasColumnType(schema.Cols[pos].Type, want) {
+		return false, fmt.Errorf("executor: WHERE type mismatch on %s", w.Column)
 	}
+	coll, _ := expr.LookupCollation(schema.Cols[pos].Collation)
+	return compareOp(w.Op, compareCollated(coll, got, want))
+}
+
+// compareOp applies comparison op to c, the result of compareValues.
+func compareOp(op string, c int) (bool, error) {
+	switch op {
+	case "", "=":
+		return c == 0, nil
+	case "<":
+		return c < 0, nil
+	case "<=":
+		return c <= 0, nil
+	case ">":
+		return c > 0, nil
+	case ">=":
+		return c >= 0, nil
+	default:
+		return false, fmt.Errorf("executor: unsupported comparison operator %q", op)
+	}
+}
 
-	switch schema.Cols[pos].Type {
+// hasColumnType reports whether v is the Go type values of column type t have.
+func hasColumnType(t record.ColumnType, v any) bool {
+	var ok bool
+	switch t {
 	case record.ColInt64:
-		g, ok1 := got.(int64)
-		wv, ok2 := want.(int64)
-		if !ok1 || !ok2 {
-			return false, fmt.Errorf("executor: WHERE type mismatch on %s", w.Column)
-		}
-		return g == wv, nil
-	case record.ColText:
-		g, ok1 := got.(string)
-		wv, ok2 := want.(string)
-		if !ok1 || !ok2 {
-			return false, fmt.Errorf("executor: WHERE type mismatch on %s", w.Column)
-		}
-		return g == wv, nil
+		_, ok = v.(int64)
+	case record.ColFloat64:
+		_, ok = v.(float64)
+	case record.ColText, record.ColJSON:
+		_, ok = v.(string)
 	case record.ColBool:
-		g, ok1 := got.(bool)
-		wv, ok2 := want.(bool)
-		if !ok1 || !ok2 {
-			return false, fmt.Errorf("executor: WHERE type mismatch on %s", w.Column)
+		_, ok = v.(bool)
+	case record.ColDate:
+		_, ok = v.(record.Date)
+	case record.ColTime:
+		_, ok = v.(record.Time)
+	case record.ColTimestamp:
+		_, ok = v.(record.Timestamp)
+	case record.ColDecimal:
+		_, ok = v.(record.Decimal)
+	}
+	return ok
+}
+
+// columnListValues puts the values of an INSERT with a column list in table
+// order, with NULL for the columns left out.
+func columnListValues(schema record.Schema, cols []string, vals []any) ([]any, error) {
+	if len(cols) != len(vals) {
+		return nil, fmt.Errorf("executor: insert has %d columns but %d values", len(cols), len(vals))
+	}
+	out := make([]any, len(schema.Cols))
+	for i, c := range cols {
+		pos := colPos(schema, c)
+		if pos < 0 {
+			return nil, fmt.Errorf("executor: unknown column in INSERT: %s", c)
 		}
-		return g == wv, nil
-	default:
-		return false, fmt.Errorf("executor: unsupported WHERE type on %s", w.Column)
+		out[pos] = vals[i]
 	}
+	return out, nil
 }
 
-func coerceInsertValues(schema record.Schema, raw []any) ([]any, error) {
+func coerceInsertValues(tableName string, schema record.Schema, raw []any) ([]any, error) {
 	if len(raw) != len(schema.Cols) {
 		return nil, fmt.Errorf("executor: insert values count %d != schema %d", len(raw), len(schema.Cols))
 	}
@@ -457,7 +1105,7 @@ func coerceInsertValues(schema record.Schema, raw []any) ([]any, error) {
 		col := schema.Cols[i]
 		if v == nil {
 			if !col.Nullable {
-				return nil, fmt.Errorf("executor: column %s is NOT NULL", col.Name)
+				return nil, novasql.NotNullError(tableName, col.Name)
 			}
 			out[i] = nil
 			continue
@@ -474,6 +1122,17 @@ func coerceInsertValues(schema record.Schema, raw []any) ([]any, error) {
 			default:
 				return nil, fmt.Errorf("executor: column %s expects INT64, got %T", col.Name, v)
 			}
+		case record.ColFloat64:
+			switch x := v.(type) {
+			case float64:
+				out[i] = x
+			case int64:
+				out[i] = float64(x)
+			case int:
+				out[i] = float64(x)
+			default:
+				return nil, fmt.Errorf("executor: column %s expects FLOAT, got %T", col.Name, v)
+			}
 		case record.ColText:
 			s, ok := v.(string)
 			if !ok {
@@ -486,6 +1145,28 @@ func coerceInsertValues(schema record.Schema, raw []any) ([]any, error) {
 				return nil, fmt.Errorf("executor: column %s expects BOOL, got %T", col.Name, v)
 			}
 			out[i] = b
+		case record.ColJSON:
+			s, ok := v.(string)
+			if !ok {
+				return nil, fmt.Errorf("executor: column %s expects JSON, got %T", col.Name, v)
+			}
+			doc, err := expr.NormalizeJSON(s)
+			if err != nil {
+				return nil, fmt.Errorf("executor: column %s: %w", col.Name, err)
+			}
+			out[i] = doc
+		case record.ColDate, record.ColTime, record.ColTimestamp:
+			t, err := record.TemporalValue(col.Type, v)
+			if err != nil {
+				return nil, fmt.Errorf("executor: column %s: %w", col.Name, err)
+			}
+			out[i] = t
+		case record.ColDecimal:
+			d, err := record.DecimalValue(col, v)
+			if err != nil {
+				return nil, fmt.Errorf("executor: column %s: %w", col.Name, err)
+			}
+			out[i] = d
 		default:
 			return nil, fmt.Errorf("executor: unsupported column type %v", col.Type)
 		}
@@ -566,40 +1247,231 @@ func (e *Executor) syncBTreeIndexesOnInsert(
 	return nil
 }
 
-// syncBTreeIndexesOnUpdateMaybeInsert best-effort inserts new entries when an indexed column is updated.
-// NOTE: This does NOT delete old entries, so indexes can become stale/bloated.
-func (e *Executor) syncBTreeIndexesOnUpdateMaybeInsert(
-	tableName string,
-	schema record.Schema,
-	newRow []any,
-	tid heap.TID,
-	assigns []planner.Assignment,
-) error {
-	// Minimal safe behavior: do nothing for now.
+// syncKVIndexes applies one row change to the table's KVTree indexes, the
+// full-text ones too: the entry for oldRow (nil = inserted row) is removed
+// and one for newRow (nil = deleted row) is added. Indexes whose key did not
+// change are skipped, and NULL keys have no entries.
+func (e *Executor) syncKVIndexes(tableName string, schema record.Schema, tid heap.TID, oldRow, newRow []any) error {
+	idxs, err := e.listIndexes(tableName, novasql.IndexKindKVTree)
+	if err != nil {
+		return err
+	}
+	texts, err := e.listIndexes(tableName, novasql.IndexKindFullText)
+	if err != nil {
+		return err
+	}
+
+	for _, im := range append(idxs, texts...) {
+		key, err := im.KeyFunc(schema)
+		if err != nil {
+			slog.Warn("executor: index refers to unknown column",
+				"table", tableName, "index", im.Name, "err", err)
+			continue
+		}
+		var oldVal, newVal any
+		if oldRow != nil {
+			if oldVal, err = key(oldRow); err != nil {
+				return fmt.Errorf("executor: index %s: %w", im.Name, err)
+			}
+		}
+		if newRow != nil {
+			if newVal, err = key(newRow); err != nil {
+				return fmt.Errorf("executor: index %s: %w", im.Name, err)
+			}
+		}
+		if oldRow != nil && newRow != nil && compareValues(oldVal, newVal) == 0 {
+			continue
+		}
+
+		if err := e.updateKVIndex(im, tid, oldVal, newVal); err != nil {
+			return fmt.Errorf("executor: index %s: %w", im.Name, err)
+		}
+	}
+	return nil
+}
+
+// autoIncrement gives values, a row about to be inserted, the next value of
+// the AUTOINCREMENT column auto when it has none, or else makes sure the
+// counter never hands out the one it has.
+func (e *Executor) autoIncrement(tableName string, values []any, auto int) error {
+	if v, ok := values[auto].(int64); ok {
+		return e.DB.ObserveAutoIncrement(tableName, v)
+	}
+	next, err := e.DB.NextAutoIncrement(tableName)
+	if err != nil {
+		return err
+	}
+	values[auto] = next
+	return nil
+}
+
+// checkUnique fails with a ConstraintError when newRow repeats the value of
+// another live row in a UNIQUE or PRIMARY KEY index of the table. oldRow is
+// the row at self before an update, nil for an insert; values it already had
+// are not checked again.
+func (e *Executor) checkUnique(tableName string, tbl *heap.Table, self heap.TID, oldRow, newRow []any) error {
+	idxs, err := e.listIndexes(tableName, novasql.IndexKindKVTree)
+	if err != nil {
+		return err
+	}
+	for _, im := range idxs {
+		pos := colPos(tbl.Schema, im.KeyColumn)
+		if im.Constraint == "" || pos < 0 || newRow[pos] == nil {
+			continue
+		}
+		if oldRow != nil && compareValues(oldRow[pos], newRow[pos]) == 0 {
+			continue
+		}
+		rows, err := e.indexRows(im, tbl, newRow[pos])
+		if err != nil {
+			return fmt.Errorf("executor: index %s: %w", im.Name, err)
+		}
+		if slices.ContainsFunc(rows, func(r tableRow) bool { return oldRow == nil || r.tid != self }) {
+			return &novasql.ConstraintError{
+				Kind:       im.Constraint,
+				Constraint: im.Name,
+				Table:      tableName,
+				Column:     im.KeyColumn,
+			}
+		}
+	}
+	return nil
+}
+
+// tableRow is a row of a table with its TID.
+type tableRow struct {
+	tid    heap.TID
+	values []any
+}
+
+// indexRows returns the rows of tbl the KVTree index im has an entry for v
+// for, skipping entries whose row is gone.
+func (e *Executor) indexRows(im novasql.IndexMeta, tbl *heap.Table, v any) ([]tableRow, error) {
+	coll, err := im.Collation(tbl.Schema)
+	if err != nil {
+		return nil, err
+	}
+	start, end, err := indexRange("=", coll.IndexKey(v))
+	if err != nil {
+		return nil, err
+	}
+	tree, err := e.openKVIndex(im.FileBase)
+	if err != nil {
+		return nil, err
+	}
+	var rows []tableRow
+	err = func() error {
+		it := tree.Scan(start, end)
+		for it.Next() {
+			id, err := btree.IndexEntryTID(it.Key())
+			if err != nil {
+				return err
+			}
+			if row, err := tbl.Get(id); err == nil {
+				rows = append(rows, tableRow{tid: id, values: row})
+			}
+		}
+		return it.Err()
+	}()
+	if cerr := tree.Close(); err == nil {
+		err = cerr
+	}
+	return rows, err
+}
+
+func (e *Executor) updateKVIndex(im novasql.IndexMeta, tid heap.TID, oldVal, newVal any) error {
+	tree, err := e.openKVIndex(im.FileBase)
+	if err != nil {
+		return err
+	}
+	err = func() error {
+		if im.Kind == novasql.IndexKindFullText {
+			return updateFullText(tree, tid, oldVal, newVal)
+		}
+		if oldVal != nil {
+			key, err := btree.IndexEntryKey(oldVal, tid)
+			if err != nil {
+				return err
+			}
+			if _, err := tree.Delete(key); err != nil {
+				return err
+			}
+		}
+		if newVal == nil {
+			return nil
+		}
+		key, err := btree.IndexEntryKey(newVal, tid)
+		if err != nil {
+			return err
+		}
+		return tree.Insert(key, nil)
+	}()
+	if cerr := tree.Close(); err == nil {
+		err = cerr
+	}
+	return err
+}
+
+// updateFullText replaces the entries of oldVal, the text of the row at tid
+// or NULL, in the full-text index tree by those of newVal.
+func updateFullText(tree *btree.KVTree, tid heap.TID, oldVal, newVal any) error {
+	if text, ok := oldVal.(string); ok {
+		entries, err := fulltext.Entries(text, tid)
+		if err != nil {
+			return err
+		}
+		for _, en := range entries {
+			if _, err := tree.Delete(en.Key); err != nil {
+				return err
+			}
+		}
+	}
+	text, ok := newVal.(string)
+	if !ok {
+		return nil
+	}
+	entries, err := fulltext.Entries(text, tid)
+	if err != nil {
+		return err
+	}
+	for _, en := range entries {
+		if err := tree.Insert(en.Key, en.Value); err != nil {
+			return err
+		}
+	}
 	return nil
 }
 
 // ---- helpers ----
 
 func (e *Executor) listBTreeIndexes(tableName string) ([]novasql.IndexMeta, error) {
+	return e.listIndexes(tableName, novasql.IndexKindBTree)
+}
+
+// tableMeta returns the catalog entry of tableName.
+func (e *Executor) tableMeta(tableName string) (*novasql.TableMeta, error) {
 	metas, err := e.DB.ListTables()
 	if err != nil {
 		return nil, err
 	}
-	var tm *novasql.TableMeta
-	for _, m := range metas {
+	for _, m := range append(metas, e.DB.TempTables()...) {
 		if m != nil && m.Name == tableName {
-			tm = m
-			break
+			return m, nil
 		}
 	}
-	if tm == nil {
-		return nil, fmt.Errorf("executor: table meta not found: %s", tableName)
+	return nil, fmt.Errorf("executor: table meta not found: %s", tableName)
+}
+
+// listIndexes returns the indexes of the given kind on tableName.
+func (e *Executor) listIndexes(tableName string, kind novasql.IndexKind) ([]novasql.IndexMeta, error) {
+	tm, err := e.tableMeta(tableName)
+	if err != nil {
+		return nil, err
 	}
 
 	out := make([]novasql.IndexMeta, 0, len(tm.Indexes))
 	for _, im := range tm.Indexes {
-		if im.Kind != novasql.IndexKindBTree {
+		if im.Kind != kind {
 			continue
 		}
 		out = append(out, im)
@@ -627,3 +1499,14 @@ func (e *Executor) btreeInsert(im novasql.IndexMeta, key int64, tid heap.TID) er
 
 	return tree.Insert(key, tid)
 }
+
+func (e *Executor) openKVIndex(base string) (*btree.KVTree, error) {
+	if base == "" {
+		return nil, fmt.Errorf("executor: index missing file base")
+	}
+	idxFS := storage.LocalFileSet{
+		Dir:  e.DB.TableDir(),
+		Base: base,
+	}
+	return btree.OpenKVTree(e.DB.StorageManager(), idxFS, e.DB.BufferView(idxFS))
+}
@@ -2,6 +2,7 @@ package executor
 
 import (
 	"errors"
+	"io"
 	"testing"
 
 	"github.com/stretchr/testify/require"
@@ -10,6 +11,7 @@ import (
 	"github.com/tuannm99/novasql/internal/btree"
 	"github.com/tuannm99/novasql/internal/bufferpool"
 	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/mvcc"
 	"github.com/tuannm99/novasql/internal/record"
 	"github.com/tuannm99/novasql/internal/sql/planner"
 	"github.com/tuannm99/novasql/internal/storage"
@@ -32,10 +34,40 @@ func (f *fakeDB) SelectDatabase(name string) (any, error) { return nil, nil }
 func (f *fakeDB) CreateTable(table string, schema record.Schema) (any, error) {
 	return nil, nil
 }
-func (f *fakeDB) DropTable(table string) error                { return nil }
-func (f *fakeDB) OpenTable(table string) (*heap.Table, error) { return nil, nil }
-func (f *fakeDB) ListTables() ([]*novasql.TableMeta, error)   { return f.metas, nil }
-func (f *fakeDB) TableDir() string                            { return f.dir }
+func (f *fakeDB) CreateTempTable(table string, schema record.Schema) (any, error) {
+	return nil, nil
+}
+func (f *fakeDB) DropTable(table string) error                                { return nil }
+func (f *fakeDB) DropTablePurge(table string) error                           { return nil }
+func (f *fakeDB) OpenTable(table string) (*heap.Table, error)                 { return nil, nil }
+func (f *fakeDB) RenameTable(oldName, newName string) error                   { return nil }
+func (f *fakeDB) AddColumn(table string, col record.Column, def any) error    { return nil }
+func (f *fakeDB) DropColumn(table, column string) error                       { return nil }
+func (f *fakeDB) CreateView(name, query string) error                         { return nil }
+func (f *fakeDB) DropView(name string) error                                  { return nil }
+func (f *fakeDB) CreateIndex(table, index, column string) error               { return nil }
+func (f *fakeDB) CreateUniqueIndex(table, index, column string) error         { return nil }
+func (f *fakeDB) CreateExpressionIndex(table, index, expression string) error { return nil }
+func (f *fakeDB) CreateFullTextIndex(table, index, column string) error       { return nil }
+func (f *fakeDB) AddPrimaryKey(table, column string) error                    { return nil }
+func (f *fakeDB) AddForeignKey(table string, fk novasql.ForeignKey) error     { return nil }
+func (f *fakeDB) SetAutoIncrement(table, column string) error                 { return nil }
+func (f *fakeDB) NextAutoIncrement(table string) (int64, error)               { return 0, nil }
+func (f *fakeDB) ObserveAutoIncrement(table string, v int64) error            { return nil }
+func (f *fakeDB) SetTTL(table, column string) error                           { return nil }
+func (f *fakeDB) DropIndex(table, index string) error                         { return nil }
+func (f *fakeDB) ListTables() ([]*novasql.TableMeta, error)                   { return f.metas, nil }
+func (f *fakeDB) BeginSnapshot() (*mvcc.Tx, error)                            { return nil, nil }
+func (f *fakeDB) TempTables() []*novasql.TableMeta                            { return nil }
+func (f *fakeDB) Vacuum() (novasql.VacuumStats, error)                        { return novasql.VacuumStats{}, nil }
+func (f *fakeDB) Analyze(tables ...string) error                              { return nil }
+func (f *fakeDB) TableDir() string                                            { return f.dir }
+func (f *fakeDB) ImportCSV(table string, r io.Reader, opts novasql.CSVOptions) (int64, error) {
+	return 0, nil
+}
+func (f *fakeDB) ExportCSV(table string, w io.Writer, opts novasql.CSVOptions) (int64, error) {
+	return 0, nil
+}
 func (f *fakeDB) BufferView(fs storage.FileSet) bufferpool.Manager {
 	return f.bp
 }
@@ -310,7 +342,7 @@ func TestCoerceInsertValues_IntToInt64(t *testing.T) {
 		},
 	}
 
-	out, err := coerceInsertValues(schema, []any{123, true, "abc"})
+	out, err := coerceInsertValues("t", schema, []any{123, true, "abc"})
 	require.NoError(t, err)
 	require.Equal(t, int64(123), out[0])
 	require.Equal(t, true, out[1])
@@ -323,7 +355,7 @@ func TestCoerceInsertValues_CountMismatch(t *testing.T) {
 			{Name: "id", Type: record.ColInt64, Nullable: true},
 		},
 	}
-	_, err := coerceInsertValues(schema, []any{})
+	_, err := coerceInsertValues("t", schema, []any{})
 	require.Error(t, err)
 	require.Contains(t, err.Error(), "values count")
 }
@@ -334,7 +366,7 @@ func TestCoerceInsertValues_NotNullViolation(t *testing.T) {
 			{Name: "id", Type: record.ColInt64, Nullable: false},
 		},
 	}
-	_, err := coerceInsertValues(schema, []any{nil})
+	_, err := coerceInsertValues("t", schema, []any{nil})
 	require.Error(t, err)
 	require.Contains(t, err.Error(), "NOT NULL")
 }
@@ -345,7 +377,7 @@ func TestCoerceInsertValues_TypeMismatch(t *testing.T) {
 			{Name: "id", Type: record.ColInt64, Nullable: true},
 		},
 	}
-	_, err := coerceInsertValues(schema, []any{"abc"})
+	_, err := coerceInsertValues("t", schema, []any{"abc"})
 	require.Error(t, err)
 	require.Contains(t, err.Error(), "expects INT64")
 }
@@ -370,7 +402,7 @@ func TestMatchWhere_Int64Equal(t *testing.T) {
 			{Name: "id", Type: record.ColInt64, Nullable: true},
 		},
 	}
-	w := &planner.WhereEq{Column: "id", Value: int64(10)}
+	w := &planner.Where{Column: "id", Value: int64(10)}
 
 	ok, err := matchWhere(schema, w, []any{int64(10)})
 	require.NoError(t, err)
@@ -387,7 +419,7 @@ func TestMatchWhere_NullHandling(t *testing.T) {
 			{Name: "id", Type: record.ColInt64, Nullable: true},
 		},
 	}
-	w := &planner.WhereEq{Column: "id", Value: nil}
+	w := &planner.Where{Column: "id", Value: nil}
 
 	ok, err := matchWhere(schema, w, []any{nil})
 	require.NoError(t, err)
@@ -398,13 +430,40 @@ func TestMatchWhere_NullHandling(t *testing.T) {
 	require.False(t, ok)
 }
 
+func TestMatchWhere_Comparisons(t *testing.T) {
+	schema := record.Schema{
+		Cols: []record.Column{
+			{Name: "name", Type: record.ColText, Nullable: true},
+		},
+	}
+	for op, want := range map[string][]bool{
+		"<":  {true, false, false},
+		"<=": {true, true, false},
+		">":  {false, false, true},
+		">=": {false, true, true},
+	} {
+		w := &planner.Where{Column: "name", Op: op, Value: "b"}
+		for i, v := range []any{"a", "b", "c"} {
+			ok, err := matchWhere(schema, w, []any{v})
+			require.NoError(t, err)
+			require.Equal(t, want[i], ok, "%v %s b", v, op)
+		}
+		ok, err := matchWhere(schema, w, []any{nil})
+		require.NoError(t, err)
+		require.False(t, ok)
+	}
+
+	_, err := matchWhere(schema, &planner.Where{Column: "name", Op: "<", Value: int64(1)}, []any{"a"})
+	require.Error(t, err)
+}
+
 func TestMatchWhere_UnknownColumn(t *testing.T) {
 	schema := record.Schema{
 		Cols: []record.Column{
 			{Name: "id", Type: record.ColInt64, Nullable: true},
 		},
 	}
-	w := &planner.WhereEq{Column: "missing", Value: int64(1)}
+	w := &planner.Where{Column: "missing", Value: int64(1)}
 
 	_, err := matchWhere(schema, w, []any{int64(1)})
 	require.Error(t, err)
@@ -0,0 +1,215 @@
+package executor
+
+import (
+	"fmt"
+	"strings"
+	"time"
+
+	"github.com/tuannm99/novasql/internal/sql/planner"
+)
+
+// execExplain lists the operators that run p.Plan, one "QUERY PLAN" row each,
+// children indented under their parent. With Analyze the plan runs first
+// (DML changes rows as it would without EXPLAIN) and every operator is
+// annotated with the rows it produced and the time spent in it, its
+// children included.
+func (e *Executor) execExplain(p *planner.ExplainPlan) (*Result, error) {
+	op, _, err := e.planOp(p.Plan)
+	if err != nil {
+		return nil, err
+	}
+	if p.Analyze {
+		op = instrument(op)
+		if _, err := drain(op); err != nil {
+			return nil, err
+		}
+	} else if err := closeLeaves(op); err != nil {
+		return nil, err
+	}
+
+	res := &Result{Columns: []string{"QUERY PLAN"}}
+	explainLines(op, 0, func(line string) { res.Rows = append(res.Rows, []any{line}) })
+	res.AffectedRows = int64(len(res.Rows))
+	return res, nil
+}
+
+// analyzeOp counts the rows Child produces and the time spent in it.
+type analyzeOp struct {
+	Child   Operator
+	rows    int64
+	elapsed time.Duration
+}
+
+func (o *analyzeOp) Open() error {
+	defer o.track(time.Now())
+	return o.Child.Open()
+}
+
+func (o *analyzeOp) Next() (Row, bool, error) {
+	defer o.track(time.Now())
+	r, ok, err := o.Child.Next()
+	if ok {
+		o.rows++
+	}
+	return r, ok, err
+}
+
+func (o *analyzeOp) Close() error {
+	defer o.track(time.Now())
+	return o.Child.Close()
+}
+
+func (o *analyzeOp) track(start time.Time) { o.elapsed += time.Since(start) }
+
+// instrument wraps op and every operator below it, but the CheckOps EXPLAIN
+// does not list, in an analyzeOp.
+func instrument(op Operator) Operator {
+	for _, c := range children(op) {
+		*c = instrument(*c)
+	}
+	if _, ok := op.(*CheckOp); ok {
+		return op
+	}
+	return &analyzeOp{Child: op}
+}
+
+// closeLeaves releases what the leaves of an operator tree that never ran
+// hold (the index scans keep their tree open).
+func closeLeaves(op Operator) error {
+	kids := children(op)
+	if len(kids) == 0 {
+		return op.Close()
+	}
+	for _, c := range kids {
+		if err := closeLeaves(*c); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// children returns the child slots of op, in the order EXPLAIN lists them.
+func children(op Operator) []*Operator {
+	switch o := op.(type) {
+	case *FilterOp:
+		return []*Operator{&o.Child}
+	case *ComputeOp:
+		return []*Operator{&o.Child}
+	case *ProjectionOp:
+		return []*Operator{&o.Child}
+	case *LimitOp:
+		return []*Operator{&o.Child}
+	case *SortOp:
+		return []*Operator{&o.Child}
+	case *HashAggregateOp:
+		return []*Operator{&o.Child}
+	case *NestedLoopJoinOp:
+		return []*Operator{&o.Left, &o.Right}
+	case *HashJoinOp:
+		return []*Operator{&o.Left, &o.Right}
+	case *InsertOp:
+		return []*Operator{&o.Child}
+	case *UpdateOp:
+		return []*Operator{&o.Child}
+	case *DeleteOp:
+		return []*Operator{&o.Child}
+	case *analyzeOp:
+		return []*Operator{&o.Child}
+	case *CheckOp:
+		return []*Operator{&o.Child}
+	}
+	return nil
+}
+
+// explainLines emits one line for op and then the lines of its children.
+func explainLines(op Operator, depth int, emit func(string)) {
+	if c, ok := op.(*CheckOp); ok {
+		explainLines(c.Child, depth, emit)
+		return
+	}
+	stats := ""
+	if a, ok := op.(*analyzeOp); ok {
+		op = a.Child
+		stats = fmt.Sprintf(" (rows=%d time=%s)", a.rows, a.elapsed.Round(time.Microsecond))
+	}
+	line := describe(op) + stats
+	if depth > 0 {
+		line = strings.Repeat("  ", depth-1) + "-> " + line
+	}
+	emit(line)
+	for _, c := range children(op) {
+		explainLines(*c, depth+1, emit)
+	}
+}
+
+// describe names op and what it works on.
+func describe(op Operator) string {
+	switch o := op.(type) {
+	case *SeqScanOp:
+		return "Seq Scan on " + o.Table.Name
+	case *IndexScanOp:
+		return fmt.Sprintf("Index Lookup on %s (key = %d)", o.Table.Name, o.Key)
+	case *IndexRangeScanOp:
+		return "Index Range Scan on " + o.Table.Name
+	case *FullTextScanOp:
+		return fmt.Sprintf("Full-Text Scan on %s (%s)", o.Table.Name, sqlLiteral(o.Query))
+	case *ValuesOp:
+		return "Values (" + plural(len(o.Rows), "row") + ")"
+	case *FilterOp:
+		if o.Cond == "" {
+			return "Filter"
+		}
+		return "Filter (" + o.Cond + ")"
+	case *ComputeOp:
+		return "Compute (" + plural(len(o.Exprs), "expression") + ")"
+	case *ProjectionOp:
+		return "Projection (" + plural(len(o.Positions), "column") + ")"
+	case *LimitOp:
+		return fmt.Sprintf("Limit (%d)", o.N)
+	case *SortOp:
+		if o.Spilled > 0 {
+			return "Sort (" + plural(len(o.Keys), "key") + ", " + plural(o.Spilled, "run") + " spilled)"
+		}
+		return "Sort (" + plural(len(o.Keys), "key") + ")"
+	case *HashAggregateOp:
+		return "Hash Aggregate (" + plural(len(o.GroupBy), "group key") + ", " + plural(len(o.Aggs), "aggregate") + ")"
+	case *NestedLoopJoinOp:
+		return joinName("Nested Loop", o.LeftOuter)
+	case *HashJoinOp:
+		return joinName("Hash", o.LeftOuter)
+	case *InsertOp:
+		return "Insert on " + o.Table.Name
+	case *UpdateOp:
+		return "Update on " + o.Table.Name
+	case *DeleteOp:
+		return "Delete on " + o.Table.Name
+	default:
+		return fmt.Sprintf("%T", op)
+	}
+}
+
+func joinName(kind string, leftOuter bool) string {
+	if leftOuter {
+		return kind + " Left Join"
+	}
+	return kind + " Join"
+}
+
+func plural(n int, what string) string {
+	if n == 1 {
+		return "1 " + what
+	}
+	return fmt.Sprintf("%d %ss", n, what)
+}
+
+// sqlLiteral writes v the way SQL spells it.
+func sqlLiteral(v any) string {
+	switch x := v.(type) {
+	case nil:
+		return "NULL"
+	case string:
+		return "'" + strings.ReplaceAll(x, "'", "''") + "'"
+	default:
+		return fmt.Sprint(x)
+	}
+}
@@ -0,0 +1,205 @@
+package executor
+
+import (
+	"fmt"
+	"slices"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+// Foreign keys
+//
+// A foreign key (novasql.ForeignKey) ties a column of a child table to the
+// PRIMARY KEY or UNIQUE column of a parent table. The child side is checked
+// when a row gets a new value (checkReferences), the parent side when a
+// referenced value changes (checkReferenced) or its row is deleted
+// (onDelete, which also carries out CASCADE and SET NULL).
+
+// rowRef names one row of one table.
+type rowRef struct {
+	table string
+	tid   heap.TID
+}
+
+// referrer is a foreign key of table that references another table.
+type referrer struct {
+	table string
+	fk    novasql.ForeignKey
+}
+
+// referencing returns the foreign keys, of any table, that reference table.
+func (e *Executor) referencing(table string) ([]referrer, error) {
+	metas, err := e.DB.ListTables()
+	if err != nil {
+		return nil, err
+	}
+	var out []referrer
+	for _, m := range metas {
+		for _, fk := range m.ForeignKeys {
+			if fk.RefTable == table {
+				out = append(out, referrer{table: m.Name, fk: fk})
+			}
+		}
+	}
+	return out, nil
+}
+
+// checkReferences fails with a ConstraintError when newRow has a value in a
+// foreign key column that no row of the parent table has. oldRow is the row
+// before an update, nil for an insert; values it already had are not checked
+// again.
+func (e *Executor) checkReferences(tableName string, schema record.Schema, oldRow, newRow []any) error {
+	meta, err := e.tableMeta(tableName)
+	if err != nil {
+		return err
+	}
+	for _, fk := range meta.ForeignKeys {
+		pos := colPos(schema, fk.Column)
+		if pos < 0 || newRow[pos] == nil {
+			continue
+		}
+		if oldRow != nil && compareValues(oldRow[pos], newRow[pos]) == 0 {
+			continue
+		}
+		refPos := colPos(schema, fk.RefColumn)
+		if fk.RefTable == tableName && refPos >= 0 && compareValues(newRow[refPos], newRow[pos]) == 0 {
+			continue // the row references itself
+		}
+		_, rows, err := e.rowsWithValue(fk.RefTable, fk.RefColumn, newRow[pos])
+		if err != nil {
+			return fmt.Errorf("executor: foreign key %s: %w", fk.Name, err)
+		}
+		if len(rows) == 0 {
+			return foreignKeyError(tableName, fk)
+		}
+	}
+	return nil
+}
+
+// checkReferenced fails with a ConstraintError when the update of the row at
+// self from oldRow to newRow changes a value other rows reference.
+func (e *Executor) checkReferenced(tableName string, schema record.Schema, self heap.TID, oldRow, newRow []any) error {
+	refs, err := e.referencing(tableName)
+	if err != nil {
+		return err
+	}
+	for _, r := range refs {
+		pos := colPos(schema, r.fk.RefColumn)
+		if pos < 0 || oldRow[pos] == nil || compareValues(oldRow[pos], newRow[pos]) == 0 {
+			continue
+		}
+		_, rows, err := e.rowsWithValue(r.table, r.fk.Column, oldRow[pos])
+		if err != nil {
+			return fmt.Errorf("executor: foreign key %s: %w", r.fk.Name, err)
+		}
+		if slices.ContainsFunc(rows, func(c tableRow) bool { return r.table != tableName || c.tid != self }) {
+			return foreignKeyError(r.table, r.fk)
+		}
+	}
+	return nil
+}
+
+// onDelete applies the ON DELETE action of every foreign key that references
+// row, about to be deleted from tableName at tid: RESTRICT fails with a
+// ConstraintError while other rows reference it, CASCADE deletes those rows
+// (applying their own actions first) and SET NULL clears their column.
+// deleting holds the rows being deleted already, which do not count.
+func (e *Executor) onDelete(
+	tableName string,
+	schema record.Schema,
+	tid heap.TID,
+	row []any,
+	deleting map[rowRef]bool,
+) error {
+	deleting[rowRef{table: tableName, tid: tid}] = true
+	refs, err := e.referencing(tableName)
+	if err != nil {
+		return err
+	}
+	for _, r := range refs {
+		pos := colPos(schema, r.fk.RefColumn)
+		if pos < 0 || row[pos] == nil {
+			continue
+		}
+		child, rows, err := e.rowsWithValue(r.table, r.fk.Column, row[pos])
+		if err != nil {
+			return fmt.Errorf("executor: foreign key %s: %w", r.fk.Name, err)
+		}
+		for _, c := range rows {
+			if deleting[rowRef{table: r.table, tid: c.tid}] {
+				continue
+			}
+			switch r.fk.OnDelete {
+			case novasql.ForeignKeyCascade:
+				if err := e.onDelete(r.table, child.Schema, c.tid, c.values, deleting); err != nil {
+					return err
+				}
+				if err := child.Delete(c.tid); err != nil {
+					return err
+				}
+				if err := e.syncKVIndexes(r.table, child.Schema, c.tid, c.values, nil); err != nil {
+					return err
+				}
+			case novasql.ForeignKeySetNull:
+				newRow := slices.Clone(c.values)
+				newRow[colPos(child.Schema, r.fk.Column)] = nil
+				if err := child.Update(c.tid, newRow); err != nil {
+					return err
+				}
+				if err := e.syncKVIndexes(r.table, child.Schema, c.tid, c.values, newRow); err != nil {
+					return err
+				}
+			default:
+				return foreignKeyError(r.table, r.fk)
+			}
+		}
+	}
+	return nil
+}
+
+// rowsWithValue opens table and returns its rows whose column equals v,
+// through a KVTree index on column when there is one.
+func (e *Executor) rowsWithValue(table, column string, v any) (*heap.Table, []tableRow, error) {
+	tbl, err := e.DB.OpenTable(table)
+	if err != nil {
+		return nil, nil, err
+	}
+	pos := colPos(tbl.Schema, column)
+	if pos < 0 {
+		return nil, nil, fmt.Errorf("executor: unknown column %s.%s", table, column)
+	}
+	idxs, err := e.listIndexes(table, novasql.IndexKindKVTree)
+	if err != nil {
+		return nil, nil, err
+	}
+	if i := slices.IndexFunc(idxs, func(im novasql.IndexMeta) bool { return im.KeyColumn == column }); i >= 0 {
+		rows, err := e.indexRows(idxs[i], tbl, v)
+		return tbl, rows, err
+	}
+
+	var rows []tableRow
+	it := tbl.Iter()
+	for {
+		id, row, ok, err := it.Next()
+		if err != nil {
+			return nil, nil, err
+		}
+		if !ok {
+			return tbl, rows, nil
+		}
+		if row[pos] != nil && compareValues(row[pos], v) == 0 {
+			rows = append(rows, tableRow{tid: id, values: row})
+		}
+	}
+}
+
+func foreignKeyError(table string, fk novasql.ForeignKey) *novasql.ConstraintError {
+	return &novasql.ConstraintError{
+		Kind:       novasql.ConstraintForeignKey,
+		Constraint: fk.Name,
+		Table:      table,
+		Column:     fk.Column,
+	}
+}
@@ -0,0 +1,1016 @@
+package executor
+
+import (
+	"cmp"
+	"errors"
+	"fmt"
+	"os"
+	"sort"
+	"strconv"
+
+	"github.com/tuannm99/novasql/internal/btree"
+	"github.com/tuannm99/novasql/internal/fulltext"
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/expr"
+	"github.com/tuannm99/novasql/internal/sql/planner"
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+// Row is one tuple flowing between operators. TID is set by operators that
+// read from a heap and carried through so DML operators can address the row.
+type Row struct {
+	TID    heap.TID
+	Values []any
+}
+
+// Operator is a physical plan node in the iterator (volcano) model: Open
+// prepares it, each Next pulls one row from its child, Close releases it.
+type Operator interface {
+	Open() error
+	Next() (Row, bool, error)
+	Close() error
+}
+
+// drain runs op to completion and returns every row it produced. op is
+// closed on every path, including a failed Open.
+func drain(op Operator) ([]Row, error) {
+	if err := op.Open(); err != nil {
+		_ = op.Close()
+		return nil, err
+	}
+	var rows []Row
+	for {
+		r, ok, err := op.Next()
+		if err != nil {
+			_ = op.Close()
+			return nil, err
+		}
+		if !ok {
+			break
+		}
+		rows = append(rows, r)
+	}
+	return rows, op.Close()
+}
+
+// ---- leaves ----
+
+// SeqScanOp reads every visible row of a heap table.
+type SeqScanOp struct {
+	Table *heap.Table
+	it    *heap.Iterator
+}
+
+func (o *SeqScanOp) Open() error {
+	o.it = o.Table.Iter()
+	return nil
+}
+
+func (o *SeqScanOp) Next() (Row, bool, error) {
+	id, row, ok, err := o.it.Next()
+	if err != nil || !ok {
+		return Row{}, false, err
+	}
+	return Row{TID: id, Values: row}, true, nil
+}
+
+func (o *SeqScanOp) Close() error {
+	o.it = nil
+	return nil
+}
+
+// IndexScanOp reads the heap rows a btree index lists for Key. Dangling
+// index entries (rows deleted since) are skipped.
+type IndexScanOp struct {
+	Table *heap.Table
+	Tree  *btree.Tree
+	Key   int64
+	tids  []heap.TID
+}
+
+func (o *IndexScanOp) Open() error {
+	tids, err := o.Tree.SearchEqual(o.Key)
+	o.tids = tids
+	return err
+}
+
+func (o *IndexScanOp) Next() (Row, bool, error) {
+	for len(o.tids) > 0 {
+		id := o.tids[0]
+		o.tids = o.tids[1:]
+		row, err := o.Table.Get(id)
+		if err != nil {
+			// stale/dangling index entry: ignore
+			continue
+		}
+		return Row{TID: id, Values: row}, true, nil
+	}
+	return Row{}, false, nil
+}
+
+func (o *IndexScanOp) Close() error {
+	o.tids = nil
+	return o.Tree.Close()
+}
+
+// IndexRangeScanOp reads the heap rows whose entries in a KVTree column index
+// fall in [Start, End) (nil = unbounded), in index order. Entries whose row
+// is gone or not visible are skipped; any other read error fails the scan.
+type IndexRangeScanOp struct {
+	Table *heap.Table
+	Tree  *btree.KVTree
+	Start []byte
+	End   []byte
+	it    *btree.KVIterator
+}
+
+func (o *IndexRangeScanOp) Open() error {
+	o.it = o.Tree.Scan(o.Start, o.End)
+	return nil
+}
+
+func (o *IndexRangeScanOp) Next() (Row, bool, error) {
+	for o.it.Next() {
+		id, err := btree.IndexEntryTID(o.it.Key())
+		if err != nil {
+			return Row{}, false, err
+		}
+		row, err := o.Table.Get(id)
+		if errors.Is(err, storage.ErrBadSlot) {
+			continue
+		}
+		if err != nil {
+			return Row{}, false, err
+		}
+		return Row{TID: id, Values: row}, true, nil
+	}
+	return Row{}, false, o.it.Err()
+}
+
+func (o *IndexRangeScanOp) Close() error {
+	o.it = nil
+	return o.Tree.Close()
+}
+
+// FullTextScanOp reads the heap rows whose text has every term of Query, as
+// the full-text index Tree finds them, best match first (see
+// fulltext.Search). Entries whose row is gone are skipped.
+type FullTextScanOp struct {
+	Table *heap.Table
+	Tree  *btree.KVTree
+	Query string
+	hits  []fulltext.Hit
+	pos   int
+}
+
+func (o *FullTextScanOp) Open() error {
+	hits, err := fulltext.Search(o.Tree, o.Query)
+	o.hits, o.pos = hits, 0
+	return err
+}
+
+func (o *FullTextScanOp) Next() (Row, bool, error) {
+	for o.pos < len(o.hits) {
+		id := o.hits[o.pos].TID
+		o.pos++
+		row, err := o.Table.Get(id)
+		if err != nil {
+			continue
+		}
+		return Row{TID: id, Values: row}, true, nil
+	}
+	return Row{}, false, nil
+}
+
+func (o *FullTextScanOp) Close() error {
+	o.hits = nil
+	return o.Tree.Close()
+}
+
+// ValuesOp produces constant rows, e.g. the VALUES list of an INSERT.
+type ValuesOp struct {
+	Rows [][]any
+	pos  int
+}
+
+func (o *ValuesOp) Open() error {
+	o.pos = 0
+	return nil
+}
+
+func (o *ValuesOp) Next() (Row, bool, error) {
+	if o.pos >= len(o.Rows) {
+		return Row{}, false, nil
+	}
+	o.pos++
+	return Row{Values: o.Rows[o.pos-1]}, true, nil
+}
+
+func (o *ValuesOp) Close() error { return nil }
+
+// ---- row transforms ----
+
+// FilterOp passes through the rows Pred accepts. Cond describes Pred for
+// EXPLAIN.
+type FilterOp struct {
+	Child Operator
+	Pred  func(row []any) (bool, error)
+	Cond  string
+}
+
+func (o *FilterOp) Open() error { return o.Child.Open() }
+
+func (o *FilterOp) Next() (Row, bool, error) {
+	for {
+		r, ok, err := o.Child.Next()
+		if err != nil || !ok {
+			return Row{}, false, err
+		}
+		keep, err := o.Pred(r.Values)
+		if err != nil {
+			return Row{}, false, err
+		}
+		if keep {
+			return r, true, nil
+		}
+	}
+}
+
+func (o *FilterOp) Close() error { return o.Child.Close() }
+
+// ComputeOp appends the values of Exprs, computed from each row, to it.
+type ComputeOp struct {
+	Child Operator
+	Exprs []*expr.Expr
+}
+
+func (o *ComputeOp) Open() error { return o.Child.Open() }
+
+func (o *ComputeOp) Next() (Row, bool, error) {
+	r, ok, err := o.Child.Next()
+	if err != nil || !ok {
+		return Row{}, false, err
+	}
+	out := make([]any, len(r.Values), len(r.Values)+len(o.Exprs))
+	copy(out, r.Values)
+	for _, e := range o.Exprs {
+		v, err := e.Eval(r.Values)
+		if err != nil {
+			return Row{}, false, err
+		}
+		out = append(out, v)
+	}
+	return Row{TID: r.TID, Values: out}, true, nil
+}
+
+func (o *ComputeOp) Close() error { return o.Child.Close() }
+
+// ProjectionOp keeps the columns at Positions, in that order.
+type ProjectionOp struct {
+	Child     Operator
+	Positions []int
+}
+
+func (o *ProjectionOp) Open() error { return o.Child.Open() }
+
+func (o *ProjectionOp) Next() (Row, bool, error) {
+	r, ok, err := o.Child.Next()
+	if err != nil || !ok {
+		return Row{}, false, err
+	}
+	out := make([]any, len(o.Positions))
+	for i, p := range o.Positions {
+		out[i] = r.Values[p]
+	}
+	return Row{TID: r.TID, Values: out}, true, nil
+}
+
+func (o *ProjectionOp) Close() error { return o.Child.Close() }
+
+// LimitOp stops after N rows without pulling any more from its child.
+type LimitOp struct {
+	Child Operator
+	N     int64
+	seen  int64
+}
+
+func (o *LimitOp) Open() error {
+	o.seen = 0
+	return o.Child.Open()
+}
+
+func (o *LimitOp) Next() (Row, bool, error) {
+	if o.seen >= o.N {
+		return Row{}, false, nil
+	}
+	r, ok, err := o.Child.Next()
+	if err != nil || !ok {
+		return Row{}, false, err
+	}
+	o.seen++
+	return r, true, nil
+}
+
+func (o *LimitOp) Close() error { return o.Child.Close() }
+
+// CheckOp calls Check before pulling each row from Child and fails with its
+// error. The executor puts one over every table and index scan, so Cancel
+// and statement_timeout stop a statement between the rows it reads.
+type CheckOp struct {
+	Child Operator
+	Check func() error
+}
+
+func (o *CheckOp) Open() error { return o.Child.Open() }
+
+func (o *CheckOp) Next() (Row, bool, error) {
+	if err := o.Check(); err != nil {
+		return Row{}, false, err
+	}
+	return o.Child.Next()
+}
+
+func (o *CheckOp) Close() error { return o.Child.Close() }
+
+// SortOp is a blocking operator: Open drains the child, then rows come out
+// ordered by Keys (column positions), text by Collations (nil, or a nil
+// one: bytewise), stable for ties.
+//
+// With a MemBytes budget and a TempDir, rows past the budget are sorted in
+// runs written to temporary files, and Next merges the runs (an external
+// merge sort), so the input never has to fit in memory.
+type SortOp struct {
+	Child      Operator
+	Keys       []int
+	Desc       []bool
+	Collations []*expr.Collation
+
+	MemBytes int64                  // <= 0: sort in memory
+	TempDir  func() (string, error) // where runs are spilled (nil: sort in memory)
+	Spilled  int                    // runs the last Open wrote
+
+	rows  []Row
+	dir   string
+	runs  []*sortRun
+	merge *runMerger
+}
+
+func (o *SortOp) less(a, b Row) bool {
+	for i, pos := range o.Keys {
+		var coll *expr.Collation
+		if i < len(o.Collations) {
+			coll = o.Collations[i]
+		}
+		c := compareCollated(coll, a.Values[pos], b.Values[pos])
+		if c == 0 {
+			continue
+		}
+		if o.Desc[i] {
+			return c > 0
+		}
+		return c < 0
+	}
+	return false
+}
+
+func (o *SortOp) Open() error {
+	o.Spilled = 0
+	if err := o.Child.Open(); err != nil {
+		_ = o.Child.Close()
+		return err
+	}
+	err := o.fill()
+	if cerr := o.Child.Close(); err == nil {
+		err = cerr
+	}
+	if err == nil {
+		sort.SliceStable(o.rows, func(a, b int) bool { return o.less(o.rows[a], o.rows[b]) })
+	}
+	if err == nil && len(o.runs) > 0 {
+		// The rows left in memory come last in the input, so their run
+		// is the last one.
+		o.runs = append(o.runs, &sortRun{rows: o.rows})
+		o.rows = nil
+		o.merge, err = newRunMerger(o.runs, o.less)
+	}
+	if err != nil {
+		_ = o.Close()
+	}
+	return err
+}
+
+// fill reads the child, spilling a sorted run whenever the rows held go
+// over the budget.
+func (o *SortOp) fill() error {
+	var size int64
+	for {
+		r, ok, err := o.Child.Next()
+		if err != nil || !ok {
+			return err
+		}
+		o.rows = append(o.rows, r)
+		size += rowBytes(r)
+		if o.TempDir != nil && o.MemBytes > 0 && size > o.MemBytes {
+			if err := o.spill(); err != nil {
+				return err
+			}
+			size = 0
+		}
+	}
+}
+
+// spill writes the rows held as a sorted run, first merging the runs so far
+// into one when there are maxMergeRuns of them.
+func (o *SortOp) spill() error {
+	if o.dir == "" {
+		base, err := o.TempDir()
+		if err != nil {
+			return err
+		}
+		if o.dir, err = os.MkdirTemp(base, "sort-"); err != nil {
+			return err
+		}
+	}
+	if len(o.runs) == maxMergeRuns {
+		run, err := mergeRuns(o.dir, o.runs, o.less)
+		o.runs = nil
+		if err != nil {
+			return err
+		}
+		o.runs = append(o.runs, run)
+	}
+	sort.SliceStable(o.rows, func(a, b int) bool { return o.less(o.rows[a], o.rows[b]) })
+	run, err := writeRun(o.dir, o.rows)
+	if err != nil {
+		return err
+	}
+	o.runs = append(o.runs, run)
+	o.rows = o.rows[:0:0]
+	o.Spilled++
+	return nil
+}
+
+func (o *SortOp) Next() (Row, bool, error) {
+	if o.merge != nil {
+		return o.merge.next()
+	}
+	if len(o.rows) == 0 {
+		return Row{}, false, nil
+	}
+	r := o.rows[0]
+	o.rows = o.rows[1:]
+	return r, true, nil
+}
+
+// Close drops the rows and removes the spilled runs.
+func (o *SortOp) Close() error {
+	o.rows, o.merge = nil, nil
+	var err error
+	for _, r := range o.runs {
+		if cerr := r.close(); err == nil {
+			err = cerr
+		}
+	}
+	o.runs = nil
+	if o.dir != "" {
+		if rerr := os.RemoveAll(o.dir); err == nil {
+			err = rerr
+		}
+		o.dir = ""
+	}
+	return err
+}
+
+// JoinSpec is what the join operators have in common: they emit Left rows
+// followed by the values of the matching Right rows, a pair matching when On
+// accepts it (nil On accepts every pair). Right is read into memory first.
+// With LeftOuter, a Left row without any match is emitted once, padded with
+// RightWidth NULLs.
+type JoinSpec struct {
+	Left       Operator
+	Right      Operator
+	On         func(left, right []any) bool
+	LeftOuter  bool
+	RightWidth int
+
+	cur     []any // current left row (nil = fetch the next one)
+	cands   []Row // right rows still to try against cur
+	matched bool
+}
+
+// next emits the next joined row; candidates lists the right rows that may
+// match a left row.
+func (s *JoinSpec) next(candidates func(left []any) []Row) (Row, bool, error) {
+	for {
+		if s.cur == nil {
+			r, ok, err := s.Left.Next()
+			if err != nil || !ok {
+				return Row{}, false, err
+			}
+			s.cur, s.cands, s.matched = r.Values, candidates(r.Values), false
+		}
+		for len(s.cands) > 0 {
+			right := s.cands[0].Values
+			s.cands = s.cands[1:]
+			if s.On == nil || s.On(s.cur, right) {
+				s.matched = true
+				return Row{Values: joinValues(s.cur, right)}, true, nil
+			}
+		}
+		left := s.cur
+		s.cur = nil
+		if s.LeftOuter && !s.matched {
+			return Row{Values: joinValues(left, make([]any, s.RightWidth))}, true, nil
+		}
+	}
+}
+
+func joinValues(left, right []any) []any {
+	out := make([]any, 0, len(left)+len(right))
+	return append(append(out, left...), right...)
+}
+
+// NestedLoopJoinOp tries every Left row against every Right row.
+type NestedLoopJoinOp struct {
+	JoinSpec
+	inner []Row
+}
+
+func (o *NestedLoopJoinOp) Open() error {
+	inner, err := drain(o.Right)
+	if err != nil {
+		return err
+	}
+	o.inner, o.cur = inner, nil
+	return o.Left.Open()
+}
+
+func (o *NestedLoopJoinOp) Next() (Row, bool, error) {
+	return o.next(func([]any) []Row { return o.inner })
+}
+
+func (o *NestedLoopJoinOp) Close() error {
+	o.inner = nil
+	return o.Left.Close()
+}
+
+// HashJoinOp is an equi-join: Open builds a hash table of the Right rows on
+// RightKeys, and each Left row only meets the rows with equal values at
+// LeftKeys. NULL keys match nothing.
+type HashJoinOp struct {
+	JoinSpec
+	LeftKeys  []int
+	RightKeys []int
+	buckets   map[string][]Row
+}
+
+func (o *HashJoinOp) Open() error {
+	rows, err := drain(o.Right)
+	if err != nil {
+		return err
+	}
+	o.buckets, o.cur = make(map[string][]Row), nil
+	for _, r := range rows {
+		if !hasNull(r.Values, o.RightKeys) {
+			key := groupKey(r.Values, o.RightKeys)
+			o.buckets[key] = append(o.buckets[key], r)
+		}
+	}
+	return o.Left.Open()
+}
+
+func (o *HashJoinOp) Next() (Row, bool, error) {
+	return o.next(func(left []any) []Row {
+		if hasNull(left, o.LeftKeys) {
+			return nil
+		}
+		return o.buckets[groupKey(left, o.LeftKeys)]
+	})
+}
+
+func (o *HashJoinOp) Close() error {
+	o.buckets = nil
+	return o.Left.Close()
+}
+
+func hasNull(row []any, positions []int) bool {
+	for _, pos := range positions {
+		if row[pos] == nil {
+			return true
+		}
+	}
+	return false
+}
+
+// AggSpec is one aggregate of a HashAggregateOp: Func over the column at
+// Pos (-1 = COUNT(*)), or Custom, an aggregate added to the database.
+type AggSpec struct {
+	Func   string
+	Pos    int
+	Custom *expr.Aggregate
+}
+
+// HashAggregateOp is a blocking operator: Open consumes the child into one
+// set of accumulators per distinct GroupBy key, then emits one row per group
+// (in first-seen order) holding the group values followed by the aggregates.
+// Without GroupBy there is exactly one group, even for no input rows.
+type HashAggregateOp struct {
+	Child   Operator
+	GroupBy []int // column positions
+	Aggs    []AggSpec
+	rows    []Row
+}
+
+func (o *HashAggregateOp) Open() error {
+	err := o.consume()
+	if cerr := o.Child.Close(); err == nil {
+		err = cerr
+	}
+	return err
+}
+
+func (o *HashAggregateOp) consume() error {
+	if err := o.Child.Open(); err != nil {
+		return err
+	}
+	index := make(map[string]int)
+	var groups [][]any
+	var states [][]aggState
+	for {
+		r, ok, err := o.Child.Next()
+		if err != nil {
+			return err
+		}
+		if !ok {
+			break
+		}
+		key := groupKey(r.Values, o.GroupBy)
+		i, seen := index[key]
+		if !seen {
+			i = len(groups)
+			index[key] = i
+			g := make([]any, len(o.GroupBy), len(o.GroupBy)+len(o.Aggs))
+			for j, pos := range o.GroupBy {
+				g[j] = r.Values[pos]
+			}
+			groups = append(groups, g)
+			states = append(states, make([]aggState, len(o.Aggs)))
+		}
+		for j, a := range o.Aggs {
+			if err := states[i][j].add(a, r.Values); err != nil {
+				return err
+			}
+		}
+	}
+	if len(groups) == 0 && len(o.GroupBy) == 0 {
+		groups = [][]any{make([]any, 0, len(o.Aggs))}
+		states = [][]aggState{make([]aggState, len(o.Aggs))}
+	}
+
+	o.rows = make([]Row, 0, len(groups))
+	for i, vals := range groups {
+		for j, a := range o.Aggs {
+			v, err := states[i][j].result(a)
+			if err != nil {
+				return err
+			}
+			vals = append(vals, v)
+		}
+		o.rows = append(o.rows, Row{Values: vals})
+	}
+	return nil
+}
+
+func (o *HashAggregateOp) Next() (Row, bool, error) {
+	if len(o.rows) == 0 {
+		return Row{}, false, nil
+	}
+	r := o.rows[0]
+	o.rows = o.rows[1:]
+	return r, true, nil
+}
+
+func (o *HashAggregateOp) Close() error {
+	o.rows = nil
+	return nil
+}
+
+// groupKey encodes the values at positions so that rows of the same group,
+// and only those, get the same key. NULLs form one group, and so do equal
+// decimals of different scales.
+func groupKey(row []any, positions []int) string {
+	var b []byte
+	for _, pos := range positions {
+		v := fmt.Sprintf("%T:%v", row[pos], row[pos])
+		if d, ok := row[pos].(record.Decimal); ok {
+			v = "decimal:" + string(d.OrderKey())
+		}
+		b = strconv.AppendInt(b, int64(len(v)), 10)
+		b = append(b, ':')
+		b = append(b, v...)
+	}
+	return string(b)
+}
+
+// aggState accumulates one aggregate of one group. NULL inputs are ignored
+// except by COUNT(*).
+type aggState struct {
+	n      int64 // non-NULL inputs (all rows for COUNT(*))
+	sumInt int64
+	sumFlt float64
+	sumDec record.Decimal
+	float  bool             // some input was a float64
+	dec    bool             // the inputs are record.Decimal
+	best   any              // MIN/MAX so far
+	acc    expr.Accumulator // of a Custom aggregate, once it has a value
+}
+
+func (s *aggState) add(a AggSpec, row []any) error {
+	if a.Pos < 0 {
+		s.n++
+		return nil
+	}
+	v := row[a.Pos]
+	if v == nil {
+		return nil
+	}
+	s.n++
+	if a.Custom != nil {
+		if s.acc == nil {
+			s.acc = a.Custom.New()
+		}
+		return a.Custom.Step(s.acc, v)
+	}
+	switch a.Func {
+	case "SUM", "AVG":
+		switch x := v.(type) {
+		case int64:
+			s.sumInt += x
+		case float64:
+			s.sumFlt += x
+			s.float = true
+		case record.Decimal:
+			sum, err := s.sumDec.Add(x)
+			if err != nil {
+				return fmt.Errorf("executor: %s: %w", a.Func, err)
+			}
+			s.sumDec, s.dec = sum, true
+		}
+	case "MIN":
+		if s.best == nil || compareValues(v, s.best) < 0 {
+			s.best = v
+		}
+	case "MAX":
+		if s.best == nil || compareValues(v, s.best) > 0 {
+			s.best = v
+		}
+	}
+	return nil
+}
+
+func (s *aggState) result(a AggSpec) (any, error) {
+	if a.Custom != nil {
+		if s.acc == nil {
+			s.acc = a.Custom.New() // a group without values
+		}
+		return a.Custom.Finish(s.acc)
+	}
+	if a.Func == "COUNT" {
+		return s.n, nil
+	}
+	if s.n == 0 {
+		return nil, nil
+	}
+	switch a.Func {
+	case "SUM":
+		switch {
+		case s.dec:
+			return s.sumDec, nil
+		case s.float:
+			return float64(s.sumInt) + s.sumFlt, nil
+		}
+		return s.sumInt, nil
+	case "AVG":
+		if s.dec {
+			return s.sumDec.Div(record.Decimal{Coef: s.n})
+		}
+		return (float64(s.sumInt) + s.sumFlt) / float64(s.n), nil
+	default:
+		return s.best, nil
+	}
+}
+
+// ---- DML ----
+
+// InsertOp inserts each child row into Table and emits it with its new TID.
+// Check runs before each insert (constraints), After once per inserted row
+// (index maintenance).
+type InsertOp struct {
+	Child Operator
+	Table *heap.Table
+	Check func(values []any) error
+	After func(values []any, tid heap.TID) error
+}
+
+func (o *InsertOp) Open() error { return o.Child.Open() }
+
+func (o *InsertOp) Next() (Row, bool, error) {
+	r, ok, err := o.Child.Next()
+	if err != nil || !ok {
+		return Row{}, false, err
+	}
+	if o.Check != nil {
+		if err := o.Check(r.Values); err != nil {
+			return Row{}, false, err
+		}
+	}
+	tid, err := o.Table.Insert(r.Values)
+	if err != nil {
+		return Row{}, false, err
+	}
+	if o.After != nil {
+		if err := o.After(r.Values, tid); err != nil {
+			return Row{}, false, err
+		}
+	}
+	return Row{TID: tid, Values: r.Values}, true, nil
+}
+
+func (o *InsertOp) Close() error { return o.Child.Close() }
+
+// UpdateOp rewrites each child row with Assign and emits the new row.
+// Check and After run once per updated row with its old and new values,
+// before and after the heap write.
+type UpdateOp struct {
+	Child  Operator
+	Table  *heap.Table
+	Assign func(row []any) ([]any, error)
+	Check  func(oldRow, newRow []any, tid heap.TID) error
+	After  func(oldRow, newRow []any, tid heap.TID) error
+}
+
+func (o *UpdateOp) Open() error { return o.Child.Open() }
+
+func (o *UpdateOp) Next() (Row, bool, error) {
+	r, ok, err := o.Child.Next()
+	if err != nil || !ok {
+		return Row{}, false, err
+	}
+	newRow, err := o.Assign(r.Values)
+	if err != nil {
+		return Row{}, false, err
+	}
+	if o.Check != nil {
+		if err := o.Check(r.Values, newRow, r.TID); err != nil {
+			return Row{}, false, err
+		}
+	}
+	if err := o.Table.Update(r.TID, newRow); err != nil {
+		return Row{}, false, err
+	}
+	if o.After != nil {
+		if err := o.After(r.Values, newRow, r.TID); err != nil {
+			return Row{}, false, err
+		}
+	}
+	return Row{TID: r.TID, Values: newRow}, true, nil
+}
+
+func (o *UpdateOp) Close() error { return o.Child.Close() }
+
+// DeleteOp deletes each child row from Table and emits it. Before and After
+// run once per deleted row, before and after the heap delete.
+type DeleteOp struct {
+	Child  Operator
+	Table  *heap.Table
+	Before func(values []any, tid heap.TID) error
+	After  func(values []any, tid heap.TID) error
+}
+
+func (o *DeleteOp) Open() error { return o.Child.Open() }
+
+func (o *DeleteOp) Next() (Row, bool, error) {
+	r, ok, err := o.Child.Next()
+	if err != nil || !ok {
+		return Row{}, false, err
+	}
+	if o.Before != nil {
+		if err := o.Before(r.Values, r.TID); err != nil {
+			return Row{}, false, err
+		}
+	}
+	if err := o.Table.Delete(r.TID); err != nil {
+		return Row{}, false, err
+	}
+	// NOTE: legacy btree indexes keep the entry (they cannot delete);
+	// IndexLookupPlan skips it because heap.Get fails.
+	if o.After != nil {
+		if err := o.After(r.Values, r.TID); err != nil {
+			return Row{}, false, err
+		}
+	}
+	return r, true, nil
+}
+
+func (o *DeleteOp) Close() error { return o.Child.Close() }
+
+// ---- plan helpers ----
+
+// whereFilter wraps child in a FilterOp for w (nil w = no filter).
+func whereFilter(child Operator, schema record.Schema, w *planner.Where) Operator {
+	if w == nil {
+		return child
+	}
+	if w.Expr != nil {
+		return &FilterOp{Child: child, Pred: w.Expr.Match, Cond: w.Expr.String()}
+	}
+	return &FilterOp{
+		Child: child,
+		Pred:  func(row []any) (bool, error) { return matchWhere(schema, w, row) },
+		Cond:  fmt.Sprintf("%s %s %s", w.Column, cmp.Or(w.Op, "="), sqlLiteral(w.Value)),
+	}
+}
+
+// sortBudget is the memory a SortOp may hold and where it spills the rest
+// (zero: sort in memory).
+type sortBudget struct {
+	memBytes int64
+	tempDir  func() (string, error)
+}
+
+// shapeOps stacks HashAggregate (plus the HAVING filter), Compute, Sort,
+// Limit and Projection over child for a SELECT and returns the output column
+// names.
+func shapeOps(
+	child Operator, schema record.Schema, shape planner.Shape, budget sortBudget,
+) (Operator, []string, error) {
+	rowSchema, err := shape.RowSchema(schema)
+	if err != nil {
+		return nil, nil, err
+	}
+	op := child
+	if shape.Aggregate != nil {
+		out, err := shape.Aggregate.OutputSchema(schema)
+		if err != nil {
+			return nil, nil, err
+		}
+		agg := &HashAggregateOp{Child: op}
+		for _, g := range shape.Aggregate.GroupBy {
+			agg.GroupBy = append(agg.GroupBy, colPos(schema, g))
+		}
+		for _, f := range shape.Aggregate.Funcs {
+			spec := AggSpec{Func: f.Func, Pos: colPos(schema, f.Column), Custom: shape.Aggregate.Custom[f.Func]}
+			agg.Aggs = append(agg.Aggs, spec)
+		}
+		op = whereFilter(agg, out, shape.Aggregate.Having)
+	}
+	if len(shape.Exprs) > 0 {
+		c := &ComputeOp{Child: op}
+		for _, x := range shape.Exprs {
+			c.Exprs = append(c.Exprs, x.Expr)
+		}
+		op = c
+	}
+	schema = rowSchema
+	if len(shape.OrderBy) > 0 {
+		s := &SortOp{Child: op, MemBytes: budget.memBytes, TempDir: budget.tempDir}
+		for _, ob := range shape.OrderBy {
+			pos := colPos(schema, ob.Column)
+			if pos < 0 {
+				return nil, nil, fmt.Errorf("executor: unknown column in ORDER BY: %s", ob.Column)
+			}
+			coll := ob.Collation
+			if coll == nil {
+				coll, _ = expr.LookupCollation(schema.Cols[pos].Collation)
+			}
+			s.Keys = append(s.Keys, pos)
+			s.Desc = append(s.Desc, ob.Desc)
+			s.Collations = append(s.Collations, coll)
+		}
+		op = s
+	}
+	if shape.Limit != nil {
+		op = &LimitOp{Child: op, N: *shape.Limit}
+	}
+
+	if shape.Columns == nil {
+		cols := make([]string, 0, len(schema.Cols))
+		for _, c := range schema.Cols {
+			cols = append(cols, c.Name)
+		}
+		return op, cols, nil
+	}
+	proj := &ProjectionOp{Child: op}
+	for _, c := range shape.Columns {
+		pos := colPos(schema, c)
+		if pos < 0 {
+			return nil, nil, fmt.Errorf("executor: unknown column in SELECT: %s", c)
+		}
+		proj.Positions = append(proj.Positions, pos)
+	}
+	if shape.Names != nil {
+		return proj, append([]string(nil), shape.Names...), nil
+	}
+	return proj, append([]string(nil), shape.Columns...), nil
+}
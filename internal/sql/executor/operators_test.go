@@ -0,0 +1,1660 @@
+package executor
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"slices"
+	"strconv"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/expr"
+	"github.com/tuannm99/novasql/internal/sql/parser"
+	"github.com/tuannm99/novasql/internal/sql/planner"
+)
+
+// countingOp counts how many rows were pulled from it.
+type countingOp struct {
+	Operator
+	pulled int
+}
+
+func (o *countingOp) Next() (Row, bool, error) {
+	r, ok, err := o.Operator.Next()
+	if ok {
+		o.pulled++
+	}
+	return r, ok, err
+}
+
+func TestShapeOps_OrderLimitProject(t *testing.T) {
+	schema := record.Schema{
+		Cols: []record.Column{
+			{Name: "id", Type: record.ColInt64, Nullable: false},
+			{Name: "name", Type: record.ColText, Nullable: true},
+		},
+	}
+	src := &ValuesOp{Rows: [][]any{
+		{int64(1), "b"},
+		{int64(2), nil},
+		{int64(3), "a"},
+		{int64(4), "b"},
+	}}
+	limit := int64(3)
+	shape := planner.Shape{
+		Columns: []string{"id"},
+		OrderBy: []planner.OrderBy{{Column: "name", Desc: true}, {Column: "id"}},
+		Limit:   &limit,
+	}
+
+	op, cols, err := shapeOps(src, schema, shape, sortBudget{})
+	require.NoError(t, err)
+	require.Equal(t, []string{"id"}, cols)
+
+	rows, err := drain(op)
+	require.NoError(t, err)
+	got := make([][]any, 0, len(rows))
+	for _, r := range rows {
+		got = append(got, r.Values)
+	}
+	require.Equal(t, [][]any{{int64(1)}, {int64(4)}, {int64(3)}}, got)
+}
+
+func TestSortOp_Spill(t *testing.T) {
+	var in, want [][]any
+	for i := range 500 {
+		in = append(in, []any{int64(i % 7), "row " + strconv.Itoa(i), nil})
+	}
+	for k := range 7 {
+		for _, r := range in {
+			if r[0] == int64(k) {
+				want = append(want, r)
+			}
+		}
+	}
+
+	dir := t.TempDir()
+	sortOp := &SortOp{
+		Child:    &ValuesOp{Rows: in},
+		Keys:     []int{0},
+		Desc:     []bool{false},
+		MemBytes: 300, // a few rows per run, so runs get merged as well
+		TempDir:  func() (string, error) { return dir, nil },
+	}
+	rows, err := drain(sortOp)
+	require.NoError(t, err)
+	got := make([][]any, 0, len(rows))
+	for _, r := range rows {
+		got = append(got, r.Values)
+	}
+	require.Equal(t, want, got, "spilled sort orders like the in-memory one, ties included")
+	require.Greater(t, sortOp.Spilled, maxMergeRuns)
+
+	left, err := os.ReadDir(dir)
+	require.NoError(t, err)
+	require.Empty(t, left, "Close removes the runs")
+}
+
+func TestLimitOp_StopsPulling(t *testing.T) {
+	src := &countingOp{Operator: &ValuesOp{Rows: [][]any{{1}, {2}, {3}, {4}}}}
+	rows, err := drain(&LimitOp{Child: src, N: 2})
+	require.NoError(t, err)
+	require.Len(t, rows, 2)
+	require.Equal(t, 2, src.pulled)
+}
+
+func TestExecSQL_Pipeline(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	exec("CREATE DATABASE app;")
+	exec("USE app;")
+	exec("CREATE TABLE users (id INT, name TEXT, active BOOL);")
+	for _, q := range []string{
+		"INSERT INTO users VALUES (1, 'carol', true);",
+		"INSERT INTO users VALUES (2, 'alice', false);",
+		"INSERT INTO users VALUES (3, 'bob', true);",
+		"INSERT INTO users VALUES (4, 'dave', true);",
+	} {
+		require.Equal(t, int64(1), exec(q).AffectedRows)
+	}
+
+	res := exec("SELECT name, id FROM users WHERE active = true ORDER BY name LIMIT 2;")
+	require.Equal(t, []string{"name", "id"}, res.Columns)
+	require.Equal(t, [][]any{{"bob", int64(3)}, {"carol", int64(1)}}, res.Rows)
+
+	require.Equal(t, int64(1), exec("UPDATE users SET active = false WHERE id = 4;").AffectedRows)
+	require.Equal(t, int64(2), exec("DELETE FROM users WHERE active = false;").AffectedRows)
+
+	res = exec("SELECT * FROM users ORDER BY id DESC;")
+	require.Equal(t, []string{"id", "name", "active"}, res.Columns)
+	require.Equal(t, [][]any{{int64(3), "bob", true}, {int64(1), "carol", true}}, res.Rows)
+}
+
+func TestExecSQL_FloatAndBigint(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	for _, q := range []string{
+		"CREATE TABLE scores (id BIGINT, score FLOAT, note TEXT);",
+		"INSERT INTO scores VALUES (9000000000, 2.5, 'a');",
+		"INSERT INTO scores VALUES (2, -1, NULL);",
+		"INSERT INTO scores VALUES (3, 10.25, 'c');",
+	} {
+		_, err := ex.ExecSQL(q)
+		require.NoError(t, err, q)
+	}
+
+	res, err := ex.ExecSQL("SELECT id, note FROM scores WHERE score = 2.5;")
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{int64(9000000000), "a"}}, res.Rows)
+
+	res, err = ex.ExecSQL("SELECT * FROM scores ORDER BY score;")
+	require.NoError(t, err)
+	require.Equal(t, [][]any{
+		{int64(2), float64(-1), nil},
+		{int64(9000000000), 2.5, "a"},
+		{int64(3), 10.25, "c"},
+	}, res.Rows)
+
+	_, err = ex.ExecSQL("INSERT INTO scores VALUES (4, 'x', 'd');")
+	require.Error(t, err)
+}
+
+func TestExecSQL_SecondaryIndex(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+	plan := func(sql string) planner.Plan {
+		t.Helper()
+		stmt, err := parser.Parse(sql)
+		require.NoError(t, err)
+		p, err := planner.BuildPlan(stmt, db)
+		require.NoError(t, err)
+		return p
+	}
+	ids := func(res *Result) []any {
+		var out []any
+		for _, r := range res.Rows {
+			out = append(out, r[0])
+		}
+		return out
+	}
+
+	exec("CREATE TABLE users (id INT, name TEXT, age INT);")
+	exec("INSERT INTO users VALUES (1, 'carol', 30);")
+	exec("INSERT INTO users VALUES (2, 'alice', 25);")
+	require.IsType(t, &planner.SeqScanPlan{}, plan("SELECT * FROM users WHERE age >= 26;"))
+
+	// The index is backfilled from the rows already there, then maintained.
+	exec("CREATE INDEX users_age ON users (age);")
+	exec("INSERT INTO users VALUES (3, 'bob', 41);")
+	exec("INSERT INTO users VALUES (4, 'dave', 25);")
+	exec("INSERT INTO users VALUES (5, 'erin', NULL);")
+	require.IsType(t, &planner.IndexScanPlan{}, plan("SELECT * FROM users WHERE age >= 26;"))
+	require.IsType(t, &planner.SeqScanPlan{}, plan("SELECT * FROM users WHERE name = 'bob';"))
+
+	require.Equal(t, []any{int64(2), int64(4)}, ids(exec("SELECT id FROM users WHERE age = 25 ORDER BY id;")))
+	require.Equal(t, []any{int64(1), int64(3)}, ids(exec("SELECT id FROM users WHERE age > 25;")))
+	require.Equal(t, []any{int64(2), int64(4), int64(1)}, ids(exec("SELECT id FROM users WHERE age <= 30;")))
+	require.Empty(t, exec("SELECT id FROM users WHERE age < 25;").Rows)
+
+	// UPDATE moves the entry, DELETE removes it: the index holds exactly the
+	// non-NULL ages.
+	exec("UPDATE users SET age = 50 WHERE id = 2;")
+	exec("DELETE FROM users WHERE id = 3;")
+	require.Equal(t, []any{int64(4)}, ids(exec("SELECT id FROM users WHERE age = 25;")))
+	require.Equal(t, []any{int64(2)}, ids(exec("SELECT id FROM users WHERE age > 30;")))
+
+	tree, err := db.OpenKVTreeIndex("users", "users_age")
+	require.NoError(t, err)
+	n := 0
+	for it := tree.Scan(nil, nil); it.Next(); {
+		n++
+	}
+	require.NoError(t, tree.Close())
+	require.Equal(t, 3, n)
+
+	exec("DROP INDEX users_age ON users;")
+	require.IsType(t, &planner.SeqScanPlan{}, plan("SELECT * FROM users WHERE age > 30;"))
+	_, err = ex.ExecSQL("CREATE INDEX bad ON users (nope);")
+	require.ErrorIs(t, err, novasql.ErrIndexBadColumn)
+}
+
+func TestExecSQL_Aggregates(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	exec("CREATE TABLE emp (name TEXT, dept TEXT, salary INT, bonus FLOAT);")
+	for _, q := range []string{
+		"INSERT INTO emp VALUES ('ann', 'eng', 100, 1.5);",
+		"INSERT INTO emp VALUES ('bob', 'eng', 120, NULL);",
+		"INSERT INTO emp VALUES ('cat', 'ops', 90, 2.0);",
+		"INSERT INTO emp VALUES ('dan', 'eng', 80, 0.5);",
+		"INSERT INTO emp VALUES ('eve', NULL, 70, NULL);",
+	} {
+		exec(q)
+	}
+
+	res := exec("SELECT COUNT(*), COUNT(bonus), SUM(salary), AVG(salary), MIN(name), MAX(bonus) FROM emp;")
+	require.Equal(t, []string{"count(*)", "count(bonus)", "sum(salary)", "avg(salary)", "min(name)", "max(bonus)"},
+		res.Columns)
+	require.Equal(t, [][]any{{int64(5), int64(3), int64(460), 92.0, "ann", 2.0}}, res.Rows)
+
+	res = exec("SELECT dept, COUNT(*), SUM(bonus) FROM emp GROUP BY dept ORDER BY dept;")
+	require.Equal(t, [][]any{
+		{nil, int64(1), nil},
+		{"eng", int64(3), 2.0},
+		{"ops", int64(1), 2.0},
+	}, res.Rows)
+
+	res = exec("SELECT dept, MAX(salary) FROM emp WHERE salary > 75 GROUP BY dept " +
+		"HAVING COUNT(*) >= 2 ORDER BY MAX(salary) DESC;")
+	require.Equal(t, [][]any{{"eng", int64(120)}}, res.Rows)
+
+	// No rows: one group without GROUP BY, none with it.
+	require.Equal(t, [][]any{{int64(0), nil}}, exec("SELECT COUNT(*), SUM(salary) FROM emp WHERE salary > 999;").Rows)
+	require.Empty(t, exec("SELECT dept, COUNT(*) FROM emp WHERE salary > 999 GROUP BY dept;").Rows)
+
+	_, err = ex.ExecSQL("SELECT name, COUNT(*) FROM emp GROUP BY dept;")
+	require.Error(t, err)
+}
+
+func TestExecSQL_Joins(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	exec("CREATE TABLE users (id INT, name TEXT);")
+	exec("CREATE TABLE orders (id INT, user_id INT, total INT);")
+	for _, q := range []string{
+		"INSERT INTO users VALUES (1, 'ann');",
+		"INSERT INTO users VALUES (2, 'bob');",
+		"INSERT INTO users VALUES (3, 'cat');",
+		"INSERT INTO orders VALUES (10, 1, 50);",
+		"INSERT INTO orders VALUES (11, 1, 20);",
+		"INSERT INTO orders VALUES (12, 2, 70);",
+		"INSERT INTO orders VALUES (13, NULL, 5);",
+	} {
+		exec(q)
+	}
+
+	// Hash join; the ON operands may come in either order.
+	res := exec("SELECT u.name, o.total FROM users u JOIN orders o ON o.user_id = u.id ORDER BY o.total;")
+	require.Equal(t, []string{"u.name", "o.total"}, res.Columns)
+	require.Equal(t, [][]any{{"ann", int64(20)}, {"ann", int64(50)}, {"bob", int64(70)}}, res.Rows)
+
+	res = exec("SELECT name, total FROM users LEFT JOIN orders ON users.id = orders.user_id ORDER BY name, total;")
+	require.Equal(t, []string{"users.name", "orders.total"}, res.Columns)
+	require.Equal(t, [][]any{
+		{"ann", int64(20)}, {"ann", int64(50)}, {"bob", int64(70)}, {"cat", nil},
+	}, res.Rows)
+
+	// Nested-loop join on a non-equality condition.
+	res = exec("SELECT u.name, o.id FROM users u JOIN orders o ON o.user_id < u.id ORDER BY u.name, o.id;")
+	require.Equal(t, [][]any{
+		{"bob", int64(10)}, {"bob", int64(11)}, {"cat", int64(10)}, {"cat", int64(11)}, {"cat", int64(12)},
+	}, res.Rows)
+
+	res = exec("SELECT a.name, b.name FROM users a JOIN users b ON a.id < b.id WHERE a.id = 1 ORDER BY b.name;")
+	require.Equal(t, [][]any{{"ann", "bob"}, {"ann", "cat"}}, res.Rows)
+
+	res = exec("SELECT u.name, COUNT(o.id), SUM(total) FROM users u LEFT JOIN orders o ON u.id = o.user_id " +
+		"WHERE u.id < 10 GROUP BY u.name ORDER BY u.name;")
+	require.Equal(t, []string{"u.name", "count(o.id)", "sum(o.total)"}, res.Columns)
+	require.Equal(t, [][]any{
+		{"ann", int64(2), int64(70)}, {"bob", int64(1), int64(70)}, {"cat", int64(0), nil},
+	}, res.Rows)
+
+	// Conditions besides the equality are checked per matching pair.
+	res = exec("SELECT a.name, b.id FROM users a LEFT JOIN users b ON a.name = b.name AND a.id > b.id ORDER BY a.name;")
+	require.Equal(t, [][]any{{"ann", nil}, {"bob", nil}, {"cat", nil}}, res.Rows)
+
+	res = exec("SELECT * FROM users u JOIN orders o ON u.id = o.user_id WHERE o.total > 30 ORDER BY o.id;")
+	require.Equal(t, []string{"u.id", "u.name", "o.id", "o.user_id", "o.total"}, res.Columns)
+	require.Equal(t, [][]any{
+		{int64(1), "ann", int64(10), int64(1), int64(50)},
+		{int64(2), "bob", int64(12), int64(2), int64(70)},
+	}, res.Rows)
+
+	// A single table may be qualified by its alias too.
+	require.Equal(t, [][]any{{"bob"}}, exec("SELECT u.name FROM users u WHERE u.id = 2;").Rows)
+
+	for _, q := range []string{
+		"SELECT id FROM users JOIN orders ON users.id = orders.user_id;",
+		"SELECT * FROM users JOIN orders ON users.id = users.id;",
+		"SELECT * FROM users JOIN orders ON users.name = orders.id;",
+		"SELECT * FROM users JOIN users ON users.id = users.id;",
+		"SELECT x.name FROM users u;",
+	} {
+		_, err := ex.ExecSQL(q)
+		require.Error(t, err, q)
+	}
+}
+
+func TestExecSQL_Vacuum(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	exec("CREATE TABLE users (id INT, name TEXT, age INT);")
+	exec("CREATE INDEX users_age ON users (age);")
+	for i := range 300 {
+		exec(fmt.Sprintf("INSERT INTO users VALUES (%d, 'user-%d-padding-padding-padding', %d);", i, i, i%50))
+	}
+	exec("DELETE FROM users WHERE id > 9;")
+
+	res := exec("VACUUM;")
+	require.Positive(t, res.AffectedRows)
+
+	// Rows moved: the rebuilt index finds them at their new TIDs.
+	res = exec("SELECT id FROM users WHERE age = 7;")
+	require.Equal(t, [][]any{{int64(7)}}, res.Rows)
+	require.Len(t, exec("SELECT * FROM users;").Rows, 10)
+}
+
+func TestExecSQL_Explain(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+	lines := func(res *Result) []string {
+		require.Equal(t, []string{"QUERY PLAN"}, res.Columns)
+		out := make([]string, 0, len(res.Rows))
+		for _, r := range res.Rows {
+			out = append(out, r[0].(string))
+		}
+		return out
+	}
+
+	exec("CREATE TABLE users (id INT, name TEXT, age INT);")
+	exec("CREATE INDEX users_age ON users (age);")
+	for i := range 20 {
+		exec(fmt.Sprintf("INSERT INTO users VALUES (%d, 'user-%d', %d);", i, i, i%5))
+	}
+
+	require.Equal(t, []string{
+		"Projection (1 column)",
+		"-> Limit (3)",
+		"  -> Sort (1 key)",
+		"    -> Filter (name > 'user-1')",
+		"      -> Seq Scan on users",
+	}, lines(exec("EXPLAIN SELECT id FROM users WHERE name > 'user-1' ORDER BY id DESC LIMIT 3;")))
+	require.Equal(t, []string{
+		"Filter (age = 2)",
+		"-> Index Range Scan on users",
+	}, lines(exec("EXPLAIN SELECT * FROM users WHERE age = 2;")))
+
+	got := lines(exec("EXPLAIN ANALYZE SELECT * FROM users WHERE age = 2;"))
+	require.Len(t, got, 2)
+	require.Regexp(t, `^Filter \(age = 2\) \(rows=4 time=.+\)$`, got[0])
+	require.Regexp(t, `^-> Index Range Scan on users \(rows=4 time=.+\)$`, got[1])
+
+	// EXPLAIN alone changes nothing; EXPLAIN ANALYZE runs the statement.
+	exec("EXPLAIN DELETE FROM users WHERE age = 0;")
+	require.Len(t, exec("SELECT * FROM users;").Rows, 20)
+	got = lines(exec("EXPLAIN ANALYZE DELETE FROM users WHERE age = 0;"))
+	require.Regexp(t, `^Delete on users \(rows=4 `, got[0])
+	require.Len(t, exec("SELECT * FROM users;").Rows, 16)
+}
+
+func TestExecSQL_AnalyzeCosts(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+	// scans lists the leaves of the plan of sql, in order.
+	scans := func(sql string) []string {
+		t.Helper()
+		var out []string
+		for _, r := range exec("EXPLAIN " + sql).Rows {
+			if line := strings.TrimSpace(r[0].(string)); strings.Contains(line, "Scan on") {
+				out = append(out, line)
+			}
+		}
+		return out
+	}
+
+	exec("CREATE TABLE users (id INT, name TEXT, age INT);")
+	exec("CREATE TABLE teams (id INT, name TEXT);")
+	exec("CREATE INDEX users_id ON users (id);")
+	for i := range 400 {
+		exec(fmt.Sprintf("INSERT INTO users VALUES (%d, 'user-%d', %d);", i, i, i%100))
+	}
+	for i := range 4 {
+		exec(fmt.Sprintf("INSERT INTO teams VALUES (%d, 'team-%d');", i, i))
+	}
+	join := "SELECT t.name, u.id FROM users u JOIN teams t ON u.age = t.id WHERE u.id < 6 ORDER BY u.id;"
+	rev := "SELECT t.name, u.id FROM teams t JOIN users u ON t.id = u.age WHERE u.id < 6 ORDER BY u.id;"
+	want := [][]any{{"team-0", int64(0)}, {"team-1", int64(1)}, {"team-2", int64(2)}, {"team-3", int64(3)}}
+
+	// Without statistics any usable index is taken and tables are joined in
+	// query order, the later one hashed.
+	require.Equal(t, []string{"-> Index Range Scan on users"}, scans("SELECT * FROM users WHERE id > 1;"))
+	require.Equal(t, []string{"-> Seq Scan on teams", "-> Seq Scan on users"}, scans(rev))
+
+	exec("ANALYZE;")
+	require.Equal(t, []string{"-> Seq Scan on users"}, scans("SELECT * FROM users WHERE id > 1;"))
+	require.Equal(t, []string{"-> Index Range Scan on users"}, scans("SELECT * FROM users WHERE id = 7;"))
+
+	// The small table is hashed whichever order the query names them in.
+	for _, q := range []string{join, rev} {
+		require.Equal(t, []string{"-> Seq Scan on users", "-> Seq Scan on teams"}, scans(q), q)
+		require.Equal(t, want, exec(q).Rows, q)
+	}
+	require.Equal(t, []string{"t.name", "u.id"}, exec(rev).Columns)
+}
+
+func TestExecSQL_Constraints(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+	violates := func(sql, constraint string) {
+		t.Helper()
+		_, err := ex.ExecSQL(sql)
+		var ce *novasql.ConstraintError
+		require.ErrorAs(t, err, &ce, sql)
+		require.ErrorIs(t, err, novasql.ErrConstraintViolation)
+		require.Equal(t, constraint, ce.Constraint)
+	}
+
+	exec("CREATE TABLE users (id INT PRIMARY KEY, email TEXT UNIQUE, name TEXT NOT NULL);")
+	exec("INSERT INTO users VALUES (1, 'a@x', 'ann');")
+	exec("INSERT INTO users VALUES (2, 'b@x', 'bob');")
+
+	violates("INSERT INTO users VALUES (1, 'c@x', 'cat');", "users_pkey")
+	violates("INSERT INTO users VALUES (3, 'a@x', 'cat');", "users_email_key")
+	violates("INSERT INTO users VALUES (3, 'c@x', NULL);", "users_name_not_null")
+	violates("INSERT INTO users VALUES (NULL, 'c@x', 'cat');", "users_id_not_null")
+	violates("UPDATE users SET email = 'a@x' WHERE id = 2;", "users_email_key")
+	violates("UPDATE users SET name = NULL WHERE id = 2;", "users_name_not_null")
+
+	// UNIQUE allows any number of NULLs, and a row may keep its own value.
+	exec("INSERT INTO users VALUES (3, NULL, 'cat');")
+	exec("INSERT INTO users VALUES (4, NULL, 'dan');")
+	exec("UPDATE users SET email = 'b@x', name = 'bo' WHERE id = 2;")
+	require.Len(t, exec("SELECT * FROM users;").Rows, 4)
+
+	// A deleted row frees its value.
+	exec("DELETE FROM users WHERE id = 1;")
+	exec("INSERT INTO users VALUES (1, 'a@x', 'amy');")
+
+	exec("CREATE UNIQUE INDEX users_by_name ON users (name);")
+	violates("INSERT INTO users VALUES (5, 'e@x', 'amy');", "users_by_name")
+	exec("CREATE TABLE tags (name TEXT);")
+	exec("INSERT INTO tags VALUES ('x');")
+	exec("INSERT INTO tags VALUES ('x');")
+	violates("CREATE UNIQUE INDEX tags_name ON tags (name);", "tags_name")
+}
+
+func TestExecSQL_ForeignKeys(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+	violates := func(sql, constraint string) {
+		t.Helper()
+		_, err := ex.ExecSQL(sql)
+		var ce *novasql.ConstraintError
+		require.ErrorAs(t, err, &ce, sql)
+		require.Equal(t, novasql.ConstraintForeignKey, ce.Kind)
+		require.Equal(t, constraint, ce.Constraint)
+	}
+	rows := func(sql string) []string {
+		t.Helper()
+		var out []string
+		for _, r := range exec(sql).Rows {
+			out = append(out, fmt.Sprint(r))
+		}
+		return out
+	}
+
+	exec("CREATE TABLE teams (id INT PRIMARY KEY, name TEXT);")
+	exec("CREATE TABLE users (id INT PRIMARY KEY, team INT REFERENCES teams ON DELETE SET NULL, " +
+		"boss INT, FOREIGN KEY (boss) REFERENCES users (id) ON DELETE CASCADE);")
+	exec("CREATE TABLE badges (user_id INT REFERENCES users (id), label TEXT);")
+	exec("INSERT INTO teams VALUES (1, 'core');")
+	exec("INSERT INTO teams VALUES (2, 'web');")
+	exec("INSERT INTO users VALUES (1, 1, 1);") // references itself
+	exec("INSERT INTO users VALUES (2, 1, 1);")
+	exec("INSERT INTO users VALUES (3, 2, 2);")
+	exec("INSERT INTO users VALUES (4, NULL, NULL);")
+	exec("INSERT INTO badges VALUES (4, 'gold');")
+
+	violates("INSERT INTO users VALUES (5, 9, NULL);", "users_team_fkey")
+	violates("UPDATE users SET boss = 9 WHERE id = 4;", "users_boss_fkey")
+	violates("UPDATE users SET id = 40 WHERE id = 4;", "badges_user_id_fkey")
+	violates("DELETE FROM users WHERE id = 4;", "badges_user_id_fkey")
+	_, err = ex.ExecSQL("DROP TABLE teams;")
+	require.ErrorIs(t, err, novasql.ErrReferenced)
+
+	// SET NULL clears the team of its users; CASCADE deletes the users 1
+	// manages, and the ones they manage in turn.
+	exec("DELETE FROM teams WHERE id = 1;")
+	require.Equal(t, []string{"[1 <nil> 1]", "[2 <nil> 1]", "[3 2 2]", "[4 <nil> <nil>]"},
+		rows("SELECT * FROM users ORDER BY id;"))
+	exec("DELETE FROM users WHERE id = 1;")
+	require.Equal(t, []string{"[4 <nil> <nil>]"}, rows("SELECT * FROM users ORDER BY id;"))
+
+	// A row that references only itself may change its key.
+	exec("INSERT INTO users VALUES (5, 2, NULL);")
+	exec("UPDATE users SET boss = 5 WHERE id = 5;")
+	exec("UPDATE users SET id = 50, boss = 50 WHERE id = 5;")
+	require.Equal(t, []string{"[4 <nil> <nil>]", "[50 2 50]"}, rows("SELECT * FROM users ORDER BY id;"))
+}
+
+func TestExecSQL_AutoIncrement(t *testing.T) {
+	dir := t.TempDir()
+	db, err := novasql.Options().Open(dir)
+	require.NoError(t, err)
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+	lastID := func() any {
+		t.Helper()
+		res := exec("SELECT last_insert_rowid();")
+		require.Equal(t, []string{"last_insert_rowid"}, res.Columns)
+		return res.Rows[0][0]
+	}
+
+	exec("CREATE TABLE items (id INT PRIMARY KEY AUTOINCREMENT, name TEXT);")
+	require.Equal(t, int64(0), lastID())
+	exec("INSERT INTO items (name) VALUES ('a');")
+	require.Equal(t, int64(1), lastID())
+	exec("INSERT INTO items VALUES (NULL, 'b');")
+	require.Equal(t, int64(2), ex.LastInsertID())
+
+	// Explicit values move the counter past them.
+	exec("INSERT INTO items (id, name) VALUES (10, 'c');")
+	require.Equal(t, int64(10), lastID())
+	exec("INSERT INTO items (name) VALUES ('d');")
+	require.Equal(t, int64(11), lastID())
+	_, err = ex.ExecSQL("INSERT INTO items VALUES (11, 'dup');")
+	require.ErrorIs(t, err, novasql.ErrConstraintViolation)
+	require.Equal(t, int64(11), lastID())
+
+	// Values are not reused, not even after a restart.
+	exec("DELETE FROM items WHERE id = 11;")
+	require.NoError(t, db.Close())
+	db, err = novasql.Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex = NewExecutor(db)
+	exec("INSERT INTO items (name) VALUES ('e');")
+	require.Equal(t, int64(12), lastID())
+	require.Len(t, exec("SELECT * FROM items;").Rows, 4)
+}
+
+func TestExecSQL_AlterTable(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	exec("CREATE TABLE users (id INT PRIMARY KEY, name TEXT);")
+	exec("INSERT INTO users VALUES (1, 'ann');")
+	exec("INSERT INTO users VALUES (2, 'bob');")
+
+	exec("ALTER TABLE users ADD COLUMN age INT NOT NULL DEFAULT 18;")
+	res := exec("SELECT * FROM users WHERE id = 2;")
+	require.Equal(t, []string{"id", "name", "age"}, res.Columns)
+	require.Equal(t, [][]any{{int64(2), "bob", int64(18)}}, res.Rows)
+	exec("INSERT INTO users VALUES (3, 'cat', 30);")
+	require.Len(t, exec("SELECT * FROM users WHERE age = 18;").Rows, 2)
+
+	_, err = ex.ExecSQL("ALTER TABLE users ADD COLUMN score FLOAT NOT NULL;")
+	require.ErrorIs(t, err, novasql.ErrColumnDefault)
+	_, err = ex.ExecSQL("ALTER TABLE users ADD COLUMN age INT;")
+	require.ErrorIs(t, err, novasql.ErrColumnExists)
+
+	// A constraint the default breaks takes the column with it.
+	_, err = ex.ExecSQL("ALTER TABLE users ADD COLUMN email TEXT UNIQUE DEFAULT 'x';")
+	require.ErrorIs(t, err, novasql.ErrConstraintViolation)
+	require.Equal(t, []string{"id", "name", "age"}, exec("SELECT * FROM users;").Columns)
+	exec("ALTER TABLE users ADD email TEXT UNIQUE;")
+	exec("UPDATE users SET email = 'ann@x' WHERE id = 1;")
+	_, err = ex.ExecSQL("UPDATE users SET email = 'ann@x' WHERE id = 2;")
+	require.ErrorIs(t, err, novasql.ErrConstraintViolation)
+
+	_, err = ex.ExecSQL("ALTER TABLE users DROP COLUMN id;")
+	require.ErrorIs(t, err, novasql.ErrColumnInUse)
+	exec("CREATE INDEX users_name_idx ON users (name);")
+	exec("ALTER TABLE users DROP COLUMN name;")
+	res = exec("SELECT * FROM users WHERE id = 1;")
+	require.Equal(t, []string{"id", "age", "email"}, res.Columns)
+	require.Equal(t, [][]any{{int64(1), int64(18), "ann@x"}}, res.Rows)
+
+	exec("ALTER TABLE users RENAME TO people;")
+	require.Len(t, exec("SELECT * FROM people;").Rows, 3)
+	_, err = ex.ExecSQL("SELECT * FROM users;")
+	require.Error(t, err)
+}
+
+func TestExecSQL_Copy(t *testing.T) {
+	dir := t.TempDir()
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	exec("CREATE TABLE users (id INT PRIMARY KEY, name TEXT, age INT);")
+	exec("INSERT INTO users VALUES (1, 'ann', 30);")
+	exec("INSERT INTO users VALUES (2, 'bob, jr', NULL);")
+
+	file := filepath.Join(dir, "users.csv")
+	res := exec(fmt.Sprintf("COPY users TO '%s' WITH (HEADER, NULL 'null');", file))
+	require.Equal(t, int64(2), res.AffectedRows)
+	data, err := os.ReadFile(file)
+	require.NoError(t, err)
+	require.Equal(t, "id,name,age\n1,ann,30\n2,\"bob, jr\",null\n", string(data))
+
+	exec("CREATE TABLE people (id INT PRIMARY KEY, name TEXT, age INT);")
+	res = exec(fmt.Sprintf("COPY people FROM '%s' (HEADER TRUE, NULL 'null');", file))
+	require.Equal(t, int64(2), res.AffectedRows)
+	require.Equal(t, [][]any{{int64(2), "bob, jr", nil}}, exec("SELECT * FROM people WHERE id = 2;").Rows)
+
+	// The file is loaded as a whole or not at all.
+	_, err = ex.ExecSQL(fmt.Sprintf("COPY people FROM '%s' (HEADER, NULL 'null');", file))
+	require.ErrorIs(t, err, novasql.ErrConstraintViolation)
+	require.Len(t, exec("SELECT * FROM people;").Rows, 2)
+
+	_, err = ex.ExecSQL(fmt.Sprintf("COPY people FROM '%s';", filepath.Join(dir, "missing.csv")))
+	require.ErrorIs(t, err, os.ErrNotExist)
+}
+
+func TestExecSQL_ErrorCodes(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	_, err = ex.ExecSQL("SELECT * FROM users WHERE id = ;")
+	e := novasql.AsError(err)
+	require.Equal(t, novasql.CodeSyntax, e.Code)
+	require.Equal(t, 31, e.Pos)
+	_, err = ex.Prepare("SELECT 'abc;")
+	require.Equal(t, 7, novasql.AsError(err).Pos)
+
+	_, err = ex.ExecSQL("SELECT * FROM users;")
+	e = novasql.AsError(err)
+	require.Equal(t, novasql.CodeTableNotFound, e.Code)
+	require.Equal(t, "users", e.Table)
+
+	_, err = ex.ExecSQL("CREATE TABLE users (id INT PRIMARY KEY);")
+	require.NoError(t, err)
+	_, err = ex.ExecSQL("INSERT INTO users VALUES (1);")
+	require.NoError(t, err)
+	_, err = ex.ExecSQL("INSERT INTO users VALUES (1);")
+	e = novasql.AsError(err)
+	require.Equal(t, novasql.CodeConstraint, e.Code)
+	require.Equal(t, "users", e.Table)
+}
+
+func TestExecSQL_TempTable(t *testing.T) {
+	dir := t.TempDir()
+	cfg := &internal.NovaSqlConfig{}
+	cfg.Storage.SortMemBytes = 512
+	db, err := novasql.Options().Config(cfg).Open(dir)
+	require.NoError(t, err)
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	exec("CREATE TEMP TABLE scratch (id INT, name TEXT);")
+	for i := range 100 {
+		exec(fmt.Sprintf("INSERT INTO scratch VALUES (%d, 'n%02d');", i, 99-i))
+	}
+	res := exec("SELECT id FROM scratch WHERE id >= 95 ORDER BY name;")
+	require.Equal(t, [][]any{{int64(99)}, {int64(98)}, {int64(97)}, {int64(96)}, {int64(95)}}, res.Rows)
+
+	res = exec("EXPLAIN ANALYZE SELECT * FROM scratch ORDER BY name LIMIT 1;")
+	require.Contains(t, fmt.Sprint(res.Rows), "spilled")
+
+	_, err = ex.ExecSQL("CREATE TEMP TABLE t (id INT PRIMARY KEY);")
+	require.Error(t, err)
+
+	require.NoError(t, db.Close())
+	db, err = novasql.Options().Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	_, err = NewExecutor(db).ExecSQL("SELECT * FROM scratch;")
+	require.Equal(t, novasql.CodeTableNotFound, novasql.CodeOf(err))
+}
+
+func TestExecSQL_Subqueries(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	exec("CREATE TABLE users (id INT, name TEXT);")
+	exec("CREATE TABLE orders (id INT, user_id INT, total INT);")
+	for _, q := range []string{
+		"INSERT INTO users VALUES (1, 'ann');",
+		"INSERT INTO users VALUES (2, 'bob');",
+		"INSERT INTO users VALUES (3, 'cat');",
+		"INSERT INTO orders VALUES (10, 1, 50);",
+		"INSERT INTO orders VALUES (11, 1, 20);",
+		"INSERT INTO orders VALUES (12, 2, 70);",
+		"INSERT INTO orders VALUES (13, NULL, 5);",
+	} {
+		exec(q)
+	}
+
+	res := exec("SELECT name FROM users WHERE id IN (SELECT user_id FROM orders) ORDER BY name;")
+	require.Equal(t, [][]any{{"ann"}, {"bob"}}, res.Rows)
+	res = exec("SELECT name FROM users WHERE id NOT IN (SELECT user_id FROM orders WHERE total > 10);")
+	require.Equal(t, [][]any{{"cat"}}, res.Rows)
+	// With a NULL among them, no value is known to be NOT IN the orders.
+	res = exec("SELECT name FROM users WHERE id NOT IN (SELECT user_id FROM orders);")
+	require.Empty(t, res.Rows)
+
+	// Correlated, by alias or by table name.
+	res = exec("SELECT name FROM users u WHERE NOT EXISTS (SELECT * FROM orders o WHERE o.user_id = u.id);")
+	require.Equal(t, [][]any{{"cat"}}, res.Rows)
+	res = exec("SELECT id FROM orders WHERE EXISTS (SELECT * FROM users WHERE users.id = orders.user_id) ORDER BY id;")
+	require.Equal(t, [][]any{{int64(10)}, {int64(11)}, {int64(12)}}, res.Rows)
+	res = exec("SELECT u.name, o.total FROM users u JOIN orders o ON o.user_id = u.id " +
+		"WHERE o.total < (SELECT MAX(total) FROM orders p WHERE p.user_id = u.id);")
+	require.Equal(t, [][]any{{"ann", int64(20)}}, res.Rows)
+
+	// Scalar subqueries: numeric types mix, no row is NULL.
+	res = exec("SELECT id FROM orders WHERE total > (SELECT AVG(total) FROM orders) ORDER BY id;")
+	require.Equal(t, [][]any{{int64(10)}, {int64(12)}}, res.Rows)
+	res = exec("SELECT id FROM users WHERE id = (SELECT user_id FROM orders WHERE total > 100);")
+	require.Empty(t, res.Rows)
+	_, err = ex.ExecSQL("SELECT * FROM users WHERE id = (SELECT user_id FROM orders);")
+	require.ErrorIs(t, err, errSubqueryRows)
+
+	res = exec("EXPLAIN SELECT * FROM users u WHERE EXISTS (SELECT * FROM orders o WHERE o.user_id = u.id);")
+	require.Contains(t, fmt.Sprint(res.Rows), "EXISTS (subquery) on u.id")
+
+	res = exec("DELETE FROM orders WHERE user_id IN (SELECT id FROM users WHERE name = 'ann');")
+	require.Equal(t, int64(2), res.AffectedRows)
+
+	for _, q := range []string{
+		"SELECT * FROM users WHERE id IN (SELECT * FROM orders);",
+		"SELECT * FROM users WHERE name IN (SELECT id FROM orders);",
+		"SELECT * FROM users WHERE id = name;",
+		"SELECT * FROM users u WHERE EXISTS (SELECT * FROM orders o WHERE o.user_id = u.nope);",
+		"SELECT name, COUNT(*) FROM users GROUP BY name HAVING COUNT(*) > (SELECT id FROM users);",
+	} {
+		_, err := ex.ExecSQL(q)
+		require.Error(t, err, q)
+	}
+}
+
+func TestExecSQL_Views(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	exec("CREATE TABLE users (id INT, name TEXT);")
+	exec("CREATE TABLE orders (id INT, user_id INT, total INT);")
+	for _, q := range []string{
+		"INSERT INTO users VALUES (1, 'ann');",
+		"INSERT INTO users VALUES (2, 'bob');",
+		"INSERT INTO users VALUES (3, 'cat');",
+		"INSERT INTO orders VALUES (10, 1, 50);",
+		"INSERT INTO orders VALUES (11, 1, 20);",
+		"INSERT INTO orders VALUES (12, 2, 70);",
+	} {
+		exec(q)
+	}
+
+	exec("CREATE VIEW big AS SELECT id, user_id FROM orders WHERE total > 30;")
+	exec("CREATE VIEW spent AS SELECT u.name, o.total FROM users u JOIN orders o ON o.user_id = u.id;")
+	exec("CREATE VIEW totals AS SELECT user_id, SUM(total) FROM orders GROUP BY user_id;")
+
+	res := exec("SELECT * FROM big ORDER BY id DESC;")
+	require.Equal(t, []string{"id", "user_id"}, res.Columns)
+	require.Equal(t, [][]any{{int64(12), int64(2)}, {int64(10), int64(1)}}, res.Rows)
+	res = exec("SELECT name FROM spent WHERE total < 60 ORDER BY name;")
+	require.Equal(t, [][]any{{"ann"}, {"ann"}}, res.Rows)
+	res = exec("SELECT * FROM totals WHERE user_id = 1;")
+	require.Equal(t, [][]any{{int64(1), int64(70)}}, res.Rows)
+
+	// A view reads the tables as they are now.
+	exec("INSERT INTO orders VALUES (13, 3, 90);")
+	res = exec("SELECT COUNT(*) FROM big;")
+	require.Equal(t, [][]any{{int64(3)}}, res.Rows)
+
+	// Joined, in a subquery and read by another view.
+	res = exec("SELECT u.name FROM users u JOIN big b ON b.user_id = u.id WHERE b.id > 10 ORDER BY u.name;")
+	require.Equal(t, [][]any{{"bob"}, {"cat"}}, res.Rows)
+	res = exec("SELECT name FROM users WHERE id NOT IN (SELECT user_id FROM big);")
+	require.Empty(t, res.Rows)
+	exec("CREATE VIEW bigger AS SELECT id FROM big WHERE user_id > 1;")
+	res = exec("SELECT * FROM bigger ORDER BY id;")
+	require.Equal(t, [][]any{{int64(12)}, {int64(13)}}, res.Rows)
+
+	for _, q := range []string{
+		"CREATE VIEW big AS SELECT * FROM users;",
+		"CREATE VIEW bad AS SELECT nope FROM users;",
+		"CREATE VIEW pair AS SELECT u.id, o.id FROM users u JOIN orders o ON o.user_id = u.id;",
+		"CREATE TABLE big (id INT);",
+		"SELECT total FROM big;",
+		"INSERT INTO big VALUES (1, 2);",
+		"DELETE FROM big;",
+		"DROP VIEW users;",
+	} {
+		_, err := ex.ExecSQL(q)
+		require.Error(t, err, q)
+	}
+
+	exec("DROP VIEW bigger;")
+	_, err = ex.ExecSQL("SELECT * FROM bigger;")
+	require.Error(t, err)
+	exec("DROP TABLE orders;")
+	_, err = ex.ExecSQL("SELECT * FROM big;")
+	require.ErrorIs(t, err, novasql.ErrTableNotFound)
+}
+
+func TestExecSQL_Expressions(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	exec("CREATE TABLE items (id INT, name TEXT, price FLOAT, qty INT, added TEXT);")
+	for _, q := range []string{
+		"INSERT INTO items VALUES (1, 'Apple', 1.5, 4, '2024-03-09 10:30:00');",
+		"INSERT INTO items VALUES (2, 'pear', 2.0, 0, '2023-12-31');",
+		"INSERT INTO items VALUES (3, 'Plum', 0.5, NULL, NULL);",
+	} {
+		exec(q)
+	}
+
+	res := exec("SELECT id, price * qty AS total, UPPER(name), SUBSTR(name, 2, 3) FROM items ORDER BY id;")
+	require.Equal(t, []string{"id", "total", "UPPER(name)", "SUBSTR(name, 2, 3)"}, res.Columns)
+	require.Equal(t, [][]any{
+		{int64(1), 6.0, "APPLE", "ppl"},
+		{int64(2), 0.0, "PEAR", "ear"},
+		{int64(3), nil, "PLUM", "lum"},
+	}, res.Rows)
+
+	res = exec("SELECT name FROM items WHERE qty > 0 OR price < 1 ORDER BY name;")
+	require.Equal(t, [][]any{{"Apple"}, {"Plum"}}, res.Rows)
+	res = exec("SELECT id FROM items WHERE LOWER(name) LIKE 'p%' AND qty IS NOT NULL;")
+	require.Equal(t, [][]any{{int64(2)}}, res.Rows)
+	res = exec("SELECT id FROM items WHERE name <> 'pear' AND NOT id = 3;")
+	require.Equal(t, [][]any{{int64(1)}}, res.Rows)
+
+	res = exec("SELECT CASE WHEN qty > 0 THEN 'in stock' WHEN qty = 0 THEN 'sold out' ELSE 'unknown' END AS stock, " +
+		"CONCAT(name, '#', id) FROM items ORDER BY stock;")
+	require.Equal(t, [][]any{{"in stock", "Apple#1"}, {"sold out", "pear#2"}, {"unknown", "Plum#3"}}, res.Rows)
+
+	res = exec("SELECT YEAR(added), MONTH(added), DAY(added), DATE(added), TIME(added), LENGTH(name) || 'x' " +
+		"FROM items WHERE id = 1;")
+	day, err := record.ParseDate("2024-03-09")
+	require.NoError(t, err)
+	clock, err := record.ParseTime("10:30:00")
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{int64(2024), int64(3), int64(9), day, clock, "5x"}}, res.Rows)
+	res = exec("SELECT NOW() > TIMESTAMP '2024-01-01 00:00:00' FROM items WHERE id = 1;")
+	require.Equal(t, [][]any{{true}}, res.Rows)
+
+	// Expressions over aggregates, ordered by their alias.
+	exec("INSERT INTO items VALUES (4, 'pear', 3.0, 2, NULL);")
+	res = exec("SELECT LOWER(name) AS n, SUM(qty) * 10 AS q FROM items WHERE qty IS NOT NULL " +
+		"GROUP BY name ORDER BY q DESC;")
+	require.Equal(t, []string{"n", "q"}, res.Columns)
+	require.Equal(t, [][]any{{"apple", int64(40)}, {"pear", int64(20)}}, res.Rows)
+
+	// Two columns of the same row.
+	res = exec("SELECT id FROM items WHERE qty < id ORDER BY id;")
+	require.Equal(t, [][]any{{int64(2)}, {int64(4)}}, res.Rows)
+
+	for _, bad := range []string{
+		"SELECT name + 1 FROM items;",
+		"SELECT id FROM items WHERE price;",
+		"SELECT nope(id) FROM items;",
+		"SELECT name, SUM(qty) + price FROM items GROUP BY name;",
+	} {
+		_, err := ex.ExecSQL(bad)
+		require.Error(t, err, bad)
+	}
+	_, err = ex.ExecSQL("SELECT id / 0 FROM items;")
+	require.ErrorIs(t, err, expr.ErrDivisionByZero)
+}
+
+func TestExecSQL_Temporal(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+	date := func(s string) any {
+		v, err := record.ParseDate(s)
+		require.NoError(t, err)
+		return v
+	}
+	clock := func(s string) any {
+		v, err := record.ParseTime(s)
+		require.NoError(t, err)
+		return v
+	}
+	stamp := func(s string) any {
+		v, err := record.ParseTimestamp(s)
+		require.NoError(t, err)
+		return v
+	}
+
+	exec("CREATE TABLE events (id INT, day DATE, at TIMESTAMP, starts TIME);")
+	for _, q := range []string{
+		"INSERT INTO events VALUES (1, '2024-01-31', '2024-01-31 22:15:00', '09:00:00');",
+		"INSERT INTO events VALUES (2, DATE '2024-02-29', TIMESTAMP '2024-02-29 08:00:00.5', TIME '23:30');",
+		"INSERT INTO events VALUES (3, NULL, NULL, NULL);",
+	} {
+		exec(q)
+	}
+
+	res := exec("SELECT day, at, starts FROM events WHERE id = 2;")
+	require.Equal(t, [][]any{{date("2024-02-29"), stamp("2024-02-29 08:00:00.5"), clock("23:30:00")}}, res.Rows)
+
+	// Text is read as a date or a time where it is compared with one.
+	res = exec("SELECT id FROM events WHERE day > '2024-02-01';")
+	require.Equal(t, [][]any{{int64(2)}}, res.Rows)
+	res = exec("SELECT id FROM events WHERE at < DATE '2024-02-01';")
+	require.Equal(t, [][]any{{int64(1)}}, res.Rows)
+	res = exec("SELECT id FROM events WHERE day + 1 = '2024-03-01' OR starts < '09:30';")
+	require.Equal(t, [][]any{{int64(1)}, {int64(2)}}, res.Rows)
+
+	res = exec("SELECT day + 1, day - DATE '2024-01-01', at + INTERVAL '1 day 2 hours', " +
+		"day + INTERVAL 1 MONTH, starts - INTERVAL '90 minutes', DATE_SUB(at, INTERVAL 1 YEAR) " +
+		"FROM events WHERE id = 1;")
+	require.Equal(t, [][]any{{
+		date("2024-02-01"), int64(30), stamp("2024-02-02 00:15:00"),
+		stamp("2024-02-29 00:00:00"), clock("07:30:00"), stamp("2023-01-31 22:15:00"),
+	}}, res.Rows)
+	res = exec("SELECT starts + INTERVAL 90 MINUTE, DATE_ADD(day, INTERVAL '-1 week') FROM events WHERE id = 2;")
+	require.Equal(t, [][]any{{clock("01:00:00"), stamp("2024-02-22 00:00:00")}}, res.Rows)
+
+	res = exec("SELECT YEAR(at), MONTH(day), DAY(at), HOUR(at), MINUTE(starts), DATE(at), TIME(at), " +
+		"EPOCH(DATE '1970-01-02'), DATE_FORMAT(at, '%Y/%m/%d %H:%M:%S.%f %a %b %j %%') FROM events WHERE id = 2;")
+	require.Equal(t, [][]any{{
+		int64(2024), int64(2), int64(29), int64(8), int64(30), date("2024-02-29"), clock("08:00:00.5"),
+		int64(86400), "2024/02/29 08:00:00.500000 Thu Feb 060 %",
+	}}, res.Rows)
+	res = exec("SELECT CONCAT(day, ' ', starts) FROM events WHERE id = 1;")
+	require.Equal(t, [][]any{{"2024-01-31 09:00:00"}}, res.Rows)
+
+	res = exec("SELECT MIN(day), MAX(at) FROM events;")
+	require.Equal(t, [][]any{{date("2024-01-31"), stamp("2024-02-29 08:00:00.5")}}, res.Rows)
+	res = exec("SELECT id FROM events WHERE starts IS NOT NULL ORDER BY starts DESC;")
+	require.Equal(t, [][]any{{int64(2)}, {int64(1)}}, res.Rows)
+
+	// An index over a date column finds the rows of a range.
+	exec("CREATE INDEX events_day ON events (day);")
+	res = exec("SELECT id FROM events WHERE day >= '2024-02-01';")
+	require.Equal(t, [][]any{{int64(2)}}, res.Rows)
+	res = exec("EXPLAIN SELECT id FROM events WHERE day >= '2024-02-01';")
+	require.Contains(t, fmt.Sprint(res.Rows), "Index Range Scan on events")
+
+	for _, bad := range []string{
+		"INSERT INTO events VALUES (4, '2024-02-30', NULL, NULL);",
+		"SELECT DATE '2024-13-01' FROM events;",
+		"SELECT day * 2 FROM events;",
+		"SELECT at - at FROM events;",
+		"SELECT INTERVAL '1 day' FROM events;",
+		"SELECT id FROM events WHERE starts > '25:00';",
+		"SELECT id FROM events WHERE day = 1;",
+		"SELECT DATE_FORMAT(at, '%Q') FROM events WHERE id = 1;",
+	} {
+		_, err := ex.ExecSQL(bad)
+		require.Error(t, err, bad)
+	}
+}
+
+func TestExecSQL_Decimal(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+	dec := func(s string) any {
+		v, err := record.ParseDecimal(s)
+		require.NoError(t, err)
+		return v
+	}
+
+	exec("CREATE TABLE items (id INT, price DECIMAL(8, 2), qty INT, rate FLOAT);")
+	for _, q := range []string{
+		"INSERT INTO items VALUES (1, 19.99, 3, 0.5);",
+		"INSERT INTO items VALUES (2, '0.105', 10, 1.25);",
+		"INSERT INTO items VALUES (3, DECIMAL '123456.785', NULL, NULL);",
+	} {
+		exec(q)
+	}
+
+	// Values are rounded to the scale of the column.
+	res := exec("SELECT price FROM items ORDER BY id;")
+	require.Equal(t, [][]any{{dec("19.99")}, {dec("0.11")}, {dec("123456.79")}}, res.Rows)
+
+	// Arithmetic on decimals is exact, and a number next to one is one.
+	res = exec("SELECT price * qty, price + 0.01, price / 4, price % 1, -price, " +
+		"CAST(0.1 AS DECIMAL(3, 1)) + 0.2 FROM items WHERE id = 1;")
+	require.Equal(t, [][]any{{
+		dec("59.97"), dec("20.00"), dec("4.99750000"), dec("0.99"), dec("-19.99"), dec("0.3"),
+	}}, res.Rows)
+	res = exec("SELECT price * rate FROM items WHERE id = 1;")
+	require.InDelta(t, 9.995, res.Rows[0][0], 1e-9)
+
+	res = exec("SELECT id FROM items WHERE price = 19.99;")
+	require.Equal(t, [][]any{{int64(1)}}, res.Rows)
+	res = exec("SELECT id FROM items WHERE price * 2 > 39.97 ORDER BY id;")
+	require.Equal(t, [][]any{{int64(1)}, {int64(3)}}, res.Rows)
+	res = exec("SELECT id FROM items WHERE price > rate;")
+	require.Equal(t, [][]any{{int64(1)}}, res.Rows)
+
+	res = exec("SELECT SUM(price), AVG(price), MIN(price), MAX(price) FROM items;")
+	require.Equal(t, [][]any{{dec("123476.89"), dec("41158.96333333"), dec("0.11"), dec("123456.79")}}, res.Rows)
+
+	res = exec("SELECT CAST(price AS INT), CAST(rate AS DECIMAL(4, 1)), CAST(qty AS DECIMAL(5, 2)), " +
+		"CAST(price AS FLOAT), CAST('2.5' AS NUMERIC(3, 1)), CAST(price AS TEXT) FROM items WHERE id = 2;")
+	require.Equal(t, [][]any{{int64(0), dec("1.3"), dec("10.00"), 0.11, dec("2.5"), "0.11"}}, res.Rows)
+
+	exec("UPDATE items SET price = price * 1.1 WHERE id = 1;")
+	res = exec("SELECT price FROM items WHERE id = 1;")
+	require.Equal(t, [][]any{{dec("21.99")}}, res.Rows)
+
+	// An index over a decimal column finds the rows of a range.
+	exec("CREATE INDEX items_price ON items (price);")
+	res = exec("SELECT id FROM items WHERE price >= 21.99 ORDER BY id;")
+	require.Equal(t, [][]any{{int64(1)}, {int64(3)}}, res.Rows)
+	res = exec("EXPLAIN SELECT id FROM items WHERE price >= 21.99;")
+	require.Contains(t, fmt.Sprint(res.Rows), "Index Range Scan on items")
+
+	for _, bad := range []string{
+		"INSERT INTO items VALUES (4, 1000000.00, 1, 1.0);",
+		"SELECT CAST(price AS DECIMAL(3, 2)) FROM items;",
+		"SELECT CAST(rate * 10000000000000000000.0 AS INT) FROM items WHERE id = 2;",
+		"SELECT price * price * price FROM items WHERE id = 3;",
+	} {
+		_, err := ex.ExecSQL(bad)
+		require.ErrorIs(t, err, record.ErrNumericOverflow, bad)
+	}
+	for _, bad := range []string{
+		"SELECT price / 0 FROM items;",
+		"SELECT CAST(DATE '2024-01-01' AS DECIMAL) FROM items;",
+		"SELECT CAST('abc' AS INT) FROM items;",
+		"CREATE TABLE bad (x DECIMAL(20, 2));",
+	} {
+		_, err := ex.ExecSQL(bad)
+		require.Error(t, err, bad)
+	}
+}
+
+func TestExecSQL_ExpressionIndex(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	exec("CREATE TABLE users (id INT, email TEXT);")
+	exec("INSERT INTO users VALUES (1, 'Ann@Example.com');")
+	exec("CREATE INDEX users_email ON users (lower(email));")
+	exec("INSERT INTO users VALUES (2, 'BOB@example.com');")
+	exec("INSERT INTO users VALUES (3, NULL);")
+
+	idxs, err := db.ListIndexes("users")
+	require.NoError(t, err)
+	require.Len(t, idxs, 1)
+	require.Equal(t, "LOWER(email)", idxs[0].Expression)
+
+	explain := func(sql string) string {
+		t.Helper()
+		var lines []string
+		for _, r := range exec("EXPLAIN " + sql).Rows {
+			lines = append(lines, r[0].(string))
+		}
+		return strings.Join(lines, "\n")
+	}
+	q := "SELECT id FROM users WHERE LOWER(email) = 'bob@example.com';"
+	require.Contains(t, explain(q), "Index Range Scan on users")
+	require.Equal(t, [][]any{{int64(2)}}, exec(q).Rows)
+
+	exec("UPDATE users SET email = 'ann@new.org' WHERE id = 1;")
+	require.Empty(t, exec("SELECT id FROM users WHERE LOWER(email) = 'ann@example.com';").Rows)
+	require.Equal(t, [][]any{{int64(1)}}, exec("SELECT id FROM users WHERE LOWER(email) >= 'ann' AND id < 2;").Rows)
+	exec("DELETE FROM users WHERE id = 2;")
+	require.Empty(t, exec(q).Rows)
+
+	_, err = ex.ExecSQL("CREATE UNIQUE INDEX users_u ON users (LOWER(email));")
+	require.Error(t, err)
+	_, err = ex.ExecSQL("CREATE INDEX users_now ON users (NOW());")
+	require.Error(t, err)
+
+	// Dropping the column drops the index on its expression.
+	exec("ALTER TABLE users DROP COLUMN email;")
+	idxs, err = db.ListIndexes("users")
+	require.NoError(t, err)
+	require.Empty(t, idxs)
+}
+
+func TestExecSQL_FullTextSearch(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+	ids := func(sql string) []int64 {
+		t.Helper()
+		var out []int64
+		for _, r := range exec(sql).Rows {
+			out = append(out, r[0].(int64))
+		}
+		return out
+	}
+
+	exec("CREATE TABLE logs (id INT, body TEXT);")
+	exec("INSERT INTO logs VALUES (1, 'disk error on node 1');")
+	exec("INSERT INTO logs VALUES (2, 'Error, error, ERROR: disk');")
+	exec("INSERT INTO logs VALUES (3, 'network timeout');")
+	exec("INSERT INTO logs VALUES (4, NULL);")
+
+	// Without an index MATCH filters the scanned rows, in table order.
+	q := "SELECT id FROM logs WHERE body MATCH 'disk error';"
+	require.Equal(t, []int64{1, 2}, ids(q))
+
+	exec("CREATE INDEX logs_body ON logs USING fulltext (body);")
+	exec("INSERT INTO logs VALUES (5, 'disk replaced after a long maintenance window, error cleared');")
+	var plan []string
+	for _, r := range exec("EXPLAIN " + q).Rows {
+		plan = append(plan, r[0].(string))
+	}
+	require.Contains(t, strings.Join(plan, "\n"), "Full-Text Scan on logs ('disk error')")
+	require.Equal(t, []int64{2, 1, 5}, ids(q), "best match first")
+	require.Equal(t, []int64{2}, ids("SELECT id FROM logs WHERE body MATCH 'disk error' LIMIT 1;"))
+	require.Equal(t, []int64{1, 5}, ids("SELECT id FROM logs WHERE body MATCH 'disk error' AND id <> 2 ORDER BY id;"))
+
+	exec("UPDATE logs SET body = 'network error' WHERE id = 2;")
+	require.Equal(t, []int64{1, 5}, ids(q))
+	require.Equal(t, []int64{2, 3}, ids("SELECT id FROM logs WHERE body MATCH 'NETWORK' ORDER BY id;"))
+	exec("DELETE FROM logs WHERE id = 1;")
+	require.Equal(t, []int64{5}, ids(q))
+
+	_, err = ex.ExecSQL("CREATE INDEX logs_id ON logs USING fulltext (id);")
+	require.ErrorIs(t, err, novasql.ErrIndexBadKeyCol)
+	_, err = ex.ExecSQL("CREATE UNIQUE INDEX logs_u ON logs USING fulltext (body);")
+	require.Error(t, err)
+	_, err = ex.ExecSQL("SELECT id FROM logs WHERE id MATCH 'x';")
+	require.Error(t, err)
+
+	// VACUUM moves the rows and rebuilds the index for their new TIDs.
+	exec("VACUUM;")
+	require.Equal(t, []int64{5}, ids(q))
+}
+
+func TestExecSQL_PatternsAndCollations(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+	ids := func(sql string) []int64 {
+		t.Helper()
+		var out []int64
+		for _, r := range exec(sql).Rows {
+			out = append(out, r[0].(int64))
+		}
+		return out
+	}
+
+	exec("CREATE TABLE users (id INT, name TEXT COLLATE NOCASE, code TEXT);")
+	exec("INSERT INTO users VALUES (1, 'alice', 'A10');")
+	exec("INSERT INTO users VALUES (2, 'Bob', 'b2');")
+	exec("INSERT INTO users VALUES (3, 'ALICE', 'A1%');")
+	exec("INSERT INTO users VALUES (4, 'carol', 'C-3');")
+
+	// A NOCASE column compares and sorts without regard to case, unless
+	// COLLATE says otherwise.
+	require.Equal(t, []int64{1, 3}, ids("SELECT id FROM users WHERE name = 'Alice';"))
+	require.Equal(t, []int64{1, 3}, ids("SELECT id FROM users WHERE name LIKE 'a%';"))
+	require.Equal(t, []int64{1, 3, 2, 4}, ids("SELECT id FROM users ORDER BY name, id;"))
+	require.Equal(t, []int64{3, 2, 1, 4}, ids("SELECT id FROM users ORDER BY name COLLATE binary;"))
+	require.Equal(t, []int64{1}, ids("SELECT id FROM users WHERE name COLLATE BINARY = 'alice';"))
+	require.Equal(t, []int64{1}, ids("SELECT id FROM users WHERE code = 'a10' COLLATE NOCASE;"))
+	require.Empty(t, ids("SELECT id FROM users WHERE code LIKE 'a1%';"))
+
+	// Its index holds the keys in that order too.
+	exec("CREATE INDEX users_name ON users (name);")
+	q := "SELECT id FROM users WHERE name = 'ALICE';"
+	var plan []string
+	for _, r := range exec("EXPLAIN " + q).Rows {
+		plan = append(plan, r[0].(string))
+	}
+	require.Contains(t, strings.Join(plan, "\n"), "Index Range Scan on users")
+	require.Equal(t, []int64{1, 3}, ids(q))
+	require.Equal(t, []int64{4}, ids("SELECT id FROM users WHERE name > 'BOB';"))
+	exec("CREATE TABLE tags (name TEXT COLLATE NOCASE UNIQUE);")
+	exec("INSERT INTO tags VALUES ('Go');")
+	_, err = ex.ExecSQL("INSERT INTO tags VALUES ('GO');")
+	require.ErrorIs(t, err, novasql.ErrConstraintViolation)
+	var dump strings.Builder
+	require.NoError(t, db.Dump(&dump))
+	require.Contains(t, dump.String(), "name TEXT COLLATE NOCASE, code TEXT")
+
+	// LIKE with ESCAPE, and GLOB, which always minds case.
+	require.Equal(t, []int64{1, 3}, ids("SELECT id FROM users WHERE code LIKE 'A1%';"))
+	require.Equal(t, []int64{3}, ids("SELECT id FROM users WHERE code LIKE 'A1!%' ESCAPE '!';"))
+	require.Equal(t, []int64{1, 3}, ids("SELECT id FROM users WHERE code GLOB '[A-C][0-9]*';"))
+	require.Equal(t, []int64{2, 4}, ids("SELECT id FROM users WHERE code NOT GLOB 'A*';"))
+	require.Equal(t, []int64{4}, ids("SELECT id FROM users WHERE code GLOB '?-[^a-z]';"))
+	require.Empty(t, ids("SELECT id FROM users WHERE name GLOB 'a*' AND id = 3;"))
+
+	// A collation added to the handle orders like any other.
+	reverse := func(a, b string) int { return strings.Compare(b, a) }
+	require.NoError(t, db.CreateCollation("reverse", reverse))
+	require.Equal(t, []int64{2, 4, 1, 3}, ids("SELECT id FROM users ORDER BY code COLLATE reverse;"))
+	require.ErrorIs(t, db.CreateCollation("nocase", reverse), novasql.ErrBadCollation)
+
+	_, err = ex.ExecSQL("SELECT id FROM users WHERE code LIKE 'x' ESCAPE '!!';")
+	require.ErrorIs(t, err, expr.ErrBadEscape)
+	for _, bad := range []string{
+		"CREATE TABLE bad (n INT COLLATE NOCASE);",
+		"CREATE TABLE bad (s TEXT COLLATE reverse);",
+		"SELECT id FROM users ORDER BY name COLLATE nope;",
+		"SELECT id FROM users WHERE id COLLATE NOCASE = 1;",
+	} {
+		_, err := ex.ExecSQL(bad)
+		require.Error(t, err, bad)
+	}
+}
+
+// medianAgg is a user aggregate: the median of its values.
+type medianAgg struct{ vals []float64 }
+
+func (m *medianAgg) Step(v any) error {
+	m.vals = append(m.vals, v.(float64))
+	return nil
+}
+
+func (m *medianAgg) Result() (any, error) {
+	if len(m.vals) == 0 {
+		return nil, nil
+	}
+	slices.Sort(m.vals)
+	n := len(m.vals)
+	return (m.vals[(n-1)/2] + m.vals[n/2]) / 2, nil
+}
+
+func TestExecSQL_UserFunctions(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	errOdd := errors.New("odd input")
+	require.NoError(t, db.CreateFunction("double", novasql.Function{
+		Args:   []record.ColumnType{record.ColFloat64},
+		Result: record.ColFloat64,
+		Call:   func(a []any) (any, error) { return a[0].(float64) * 2, nil },
+	}))
+	require.NoError(t, db.CreateFunction("evens_only", novasql.Function{
+		Args:   []record.ColumnType{record.ColInt64},
+		Result: record.ColInt64,
+		Call: func(a []any) (any, error) {
+			if a[0].(int64)%2 != 0 {
+				return nil, errOdd
+			}
+			return int(a[0].(int64)), nil // converted to int64
+		},
+	}))
+	require.NoError(t, db.CreateFunction("coalesce_text", novasql.Function{
+		Args:     []record.ColumnType{record.ColText},
+		Variadic: true,
+		Nulls:    true,
+		Result:   record.ColText,
+		Call: func(a []any) (any, error) {
+			for _, v := range a {
+				if v != nil {
+					return v, nil
+				}
+			}
+			return nil, nil
+		},
+	}))
+	require.NoError(t, db.CreateAggregate("median", novasql.AggregateFunction{
+		Arg:    record.ColFloat64,
+		Result: record.ColFloat64,
+		New:    func() novasql.Aggregator { return &medianAgg{} },
+	}))
+
+	exec("CREATE TABLE scores (id INT, team TEXT, points INT, nick TEXT);")
+	for _, q := range []string{
+		"INSERT INTO scores VALUES (1, 'red', 4, NULL);",
+		"INSERT INTO scores VALUES (2, 'red', 10, 'ace');",
+		"INSERT INTO scores VALUES (3, 'blue', 7, NULL);",
+		"INSERT INTO scores VALUES (4, 'red', 1, NULL);",
+	} {
+		exec(q)
+	}
+
+	res := exec("SELECT id, DOUBLE(points), coalesce_text(nick, team, 'none') FROM scores " +
+		"WHERE double(id) > 4 ORDER BY id;")
+	require.Equal(t, [][]any{{int64(3), 14.0, "blue"}, {int64(4), 2.0, "red"}}, res.Rows)
+	res = exec("SELECT evens_only(points) FROM scores WHERE id < 3 ORDER BY id;")
+	require.Equal(t, [][]any{{int64(4)}, {int64(10)}}, res.Rows)
+	_, err = ex.ExecSQL("SELECT evens_only(points) FROM scores;")
+	require.ErrorIs(t, err, errOdd)
+
+	res = exec("SELECT team, median(points) AS m, median(points) * 10, COUNT(*) FROM scores GROUP BY team ORDER BY m;")
+	require.Equal(t, []string{"team", "m", "median(points) * 10", "count(*)"}, res.Columns)
+	require.Equal(t, [][]any{{"red", 4.0, 40.0, int64(3)}, {"blue", 7.0, 70.0, int64(1)}}, res.Rows)
+	res = exec("SELECT median(points) FROM scores WHERE id > 10;")
+	require.Equal(t, [][]any{{nil}}, res.Rows)
+
+	for _, q := range []string{
+		"SELECT double(team) FROM scores;",
+		"SELECT double(points, 1) FROM scores;",
+		"SELECT median(team) FROM scores;",
+		"SELECT median(points + 1) FROM scores;",
+		"SELECT id, median(points) FROM scores;",
+		"CREATE INDEX scores_double ON scores (double(points));",
+	} {
+		_, err := ex.ExecSQL(q)
+		require.Error(t, err, q)
+	}
+
+	// Calls are checked against the declared result type.
+	require.NoError(t, db.CreateFunction("double", novasql.Function{
+		Args:   []record.ColumnType{record.ColFloat64},
+		Result: record.ColFloat64,
+		Call:   func([]any) (any, error) { return "two", nil },
+	}))
+	_, err = ex.ExecSQL("SELECT double(points) FROM scores;")
+	require.ErrorContains(t, err, "DOUBLE returned string, not FLOAT")
+
+	fn := novasql.Function{Result: record.ColInt64, Call: func([]any) (any, error) { return 1, nil }}
+	require.ErrorIs(t, db.CreateFunction("lower", fn), novasql.ErrBadFunction)
+	require.ErrorIs(t, db.CreateFunction("sum", fn), novasql.ErrBadFunction)
+	require.ErrorIs(t, db.CreateFunction("median", fn), novasql.ErrBadFunction)
+	require.ErrorIs(t, db.CreateFunction("bad name", fn), novasql.ErrBadIdent)
+	require.ErrorIs(t, db.CreateFunction("nothing", novasql.Function{}), novasql.ErrBadFunction)
+}
+
+func TestExecSQL_JSON(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	exec("CREATE TABLE events (id INT, doc JSON);")
+	for _, q := range []string{
+		`INSERT INTO events VALUES (1, '{"user": {"name": "ann", "age": 31}, "tags": ["a", "b"], "ok": true}');`,
+		`INSERT INTO events VALUES (2, '{"user": {"name": "bob", "age": 25.5}, "tags": []}');`,
+		`INSERT INTO events VALUES (3, '[1, 2, 3]');`,
+		`INSERT INTO events VALUES (4, NULL);`,
+	} {
+		exec(q)
+	}
+
+	// Values are checked and stored without whitespace.
+	res := exec("SELECT doc FROM events WHERE id = 3;")
+	require.Equal(t, [][]any{{"[1,2,3]"}}, res.Rows)
+	_, err = ex.ExecSQL("INSERT INTO events VALUES (5, '{bad');")
+	require.ErrorIs(t, err, expr.ErrBadJSON)
+	_, err = ex.ExecSQL("UPDATE events SET doc = 'nope' WHERE id = 1;")
+	require.ErrorIs(t, err, expr.ErrBadJSON)
+
+	res = exec("SELECT id, doc->'user', doc->'user'->>'name', doc->>'$.tags[1]', json_extract(doc, '$.user.age') " +
+		"FROM events ORDER BY id;")
+	require.Equal(t, [][]any{
+		{int64(1), `{"name":"ann","age":31}`, "ann", "b", "31"},
+		{int64(2), `{"name":"bob","age":25.5}`, "bob", nil, "25.5"},
+		{int64(3), nil, nil, nil, nil},
+		{int64(4), nil, nil, nil, nil},
+	}, res.Rows)
+	res = exec("SELECT doc->1, doc->>'$[2]', json_type(doc), json_type(doc, '$.ok') FROM events " +
+		"WHERE id >= 1 ORDER BY id;")
+	require.Equal(t, [][]any{
+		{nil, nil, "object", "true"},
+		{nil, nil, "object", nil},
+		{"2", "3", "array", nil},
+		{nil, nil, nil, nil},
+	}, res.Rows)
+
+	// JSON values compare as the SQL values they hold.
+	res = exec("SELECT id FROM events WHERE doc->'user'->'age' > 30;")
+	require.Equal(t, [][]any{{int64(1)}}, res.Rows)
+	res = exec("SELECT id FROM events WHERE json_extract(doc, '$.user.name') = 'bob' OR doc->'ok' = TRUE ORDER BY id;")
+	require.Equal(t, [][]any{{int64(1)}, {int64(2)}}, res.Rows)
+	res = exec("SELECT id FROM events WHERE doc->'user'->'name' > 1;")
+	require.Empty(t, res.Rows)
+
+	res = exec(`SELECT json_valid('{"a":1}'), json_valid('{a}'), JSON(' [ 1 ] ') FROM events WHERE id = 1;`)
+	require.Equal(t, [][]any{{true, false, "[1]"}}, res.Rows)
+
+	// An index on the text of a member.
+	exec("CREATE INDEX events_name ON events ((doc->>'$.user.name'));")
+	q := "SELECT id FROM events WHERE doc->>'$.user.name' = 'ann';"
+	require.Contains(t, exec("EXPLAIN "+q).Rows, []any{"-> Index Range Scan on events"})
+	require.Equal(t, [][]any{{int64(1)}}, exec(q).Rows)
+
+	for _, bad := range []string{
+		"SELECT doc->TRUE FROM events;",
+		"SELECT id->'a' FROM events;",
+		"SELECT doc LIKE 'x' FROM events;",
+		"SELECT json_extract(doc, 'user') FROM events WHERE id = 1;",
+		"SELECT doc->'$.' FROM events WHERE id = 1;",
+	} {
+		_, err := ex.ExecSQL(bad)
+		require.Error(t, err, bad)
+	}
+}
+
+func TestExecSQL_Returning(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+	exec("CREATE TABLE items (id INT PRIMARY KEY AUTOINCREMENT, name TEXT, qty INT);")
+
+	// The rows written, with the AUTOINCREMENT values they got.
+	res := exec("INSERT INTO items (name, qty) VALUES ('a', 1) RETURNING id;")
+	require.Equal(t, []string{"id"}, res.Columns)
+	require.Equal(t, [][]any{{int64(1)}}, res.Rows)
+	require.Equal(t, int64(1), res.AffectedRows)
+	res = exec("INSERT INTO items (name, qty) VALUES ('b', 2) RETURNING *;")
+	require.Equal(t, []string{"id", "name", "qty"}, res.Columns)
+	require.Equal(t, [][]any{{int64(2), "b", int64(2)}}, res.Rows)
+
+	// UPDATE returns the new values, DELETE the rows deleted.
+	res = exec("UPDATE items SET qty = 5 WHERE id = 2 RETURNING qty, name;")
+	require.Equal(t, []string{"qty", "name"}, res.Columns)
+	require.Equal(t, [][]any{{int64(5), "b"}}, res.Rows)
+	res = exec("UPDATE items SET name = 'x' WHERE qty > 9 RETURNING id;")
+	require.Empty(t, res.Rows)
+	require.Equal(t, int64(0), res.AffectedRows)
+	res = exec("DELETE FROM items WHERE qty < 5 RETURNING name;")
+	require.Equal(t, [][]any{{"a"}}, res.Rows)
+	require.Equal(t, int64(1), res.AffectedRows)
+
+	// Without RETURNING there are only the affected rows.
+	res = exec("UPDATE items SET qty = 6 WHERE id = 2;")
+	require.Nil(t, res.Columns)
+	require.Equal(t, int64(1), res.AffectedRows)
+
+	_, err = ex.ExecSQL("DELETE FROM items RETURNING nope;")
+	require.ErrorContains(t, err, "unknown column in RETURNING: nope")
+	require.Len(t, exec("SELECT * FROM items;").Rows, 1)
+}
@@ -0,0 +1,90 @@
+package executor
+
+import (
+	"context"
+	"fmt"
+	"time"
+
+	"github.com/tuannm99/novasql/internal/sql/parser"
+	"github.com/tuannm99/novasql/internal/sql/planner"
+)
+
+// Stmt is a prepared statement: Prepare parses and plans it once, Bind
+// substitutes values into that plan, and each Execute runs the bound plan. Placeholders ("?" in
+// order, or "$1", "$2", ...) stand for literals, so values never go through
+// the SQL text.
+//
+// The plan is not revalidated: prepare the statement again after changing
+// the schema of a table it uses.
+type Stmt struct {
+	e     *Executor
+	sql   string
+	plan  planner.Plan
+	n     int
+	bound planner.Plan // nil until Bind succeeds, unless n == 0
+}
+
+// Prepare parses and plans sql, which may contain placeholders.
+func (e *Executor) Prepare(sql string) (*Stmt, error) {
+	stmt, err := parser.Parse(sql)
+	if err != nil {
+		return nil, syntaxError(err)
+	}
+	if e.raw == nil {
+		return nil, fmt.Errorf("executor: raw database is nil (planner requires *novasql.Database)")
+	}
+	plan, err := planner.BuildPlan(stmt, e.raw)
+	if err != nil {
+		return nil, err
+	}
+	s := &Stmt{e: e, sql: sql, plan: plan, n: planner.NumParams(plan)}
+	if s.n == 0 {
+		s.bound = plan
+	}
+	return s, nil
+}
+
+// NumParams is the number of values Bind expects.
+func (s *Stmt) NumParams() int { return s.n }
+
+// Bind sets the placeholder values used by the following Execute calls.
+// Values are coerced to the types of their columns; on error the previous
+// values stay bound.
+func (s *Stmt) Bind(args ...any) error {
+	bound, err := planner.BindParams(s.plan, args)
+	if err != nil {
+		return err
+	}
+	s.bound = bound
+	return nil
+}
+
+// Execute runs the statement with the bound values.
+func (s *Stmt) Execute() (*Result, error) {
+	if s.bound == nil {
+		return nil, s.unbound()
+	}
+	start := time.Now()
+	st := s.e.begin(context.Background())
+	res, err := s.e.execPlan(s.bound)
+	s.e.end(st)
+	s.e.observe(start, s.sql, err)
+	return res, err
+}
+
+// Query runs the statement with the bound values and returns its rows as
+// they are read; see Executor.Query.
+func (s *Stmt) Query() (*Rows, error) {
+	if s.bound == nil {
+		return nil, s.unbound()
+	}
+	return s.e.query(s.bound, s.sql, time.Now())
+}
+
+// Cancel stops the statement the executor of s is running, s or another;
+// see Executor.Cancel. It is safe to call from any goroutine.
+func (s *Stmt) Cancel() { s.e.Cancel() }
+
+func (s *Stmt) unbound() error {
+	return fmt.Errorf("executor: statement has %d parameters and none are bound", s.n)
+}
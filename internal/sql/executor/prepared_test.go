@@ -0,0 +1,143 @@
+package executor
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql"
+)
+
+func TestPrepare_BindExecute(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	_, err = ex.ExecSQL("CREATE TABLE users (id INT, name TEXT);")
+	require.NoError(t, err)
+	_, err = ex.ExecSQL("CREATE INDEX users_id ON users (id);")
+	require.NoError(t, err)
+
+	ins, err := ex.Prepare("INSERT INTO users VALUES (?, ?);")
+	require.NoError(t, err)
+	require.Equal(t, 2, ins.NumParams())
+	for i, name := range []string{"ann", "bob", "o'brien; DROP TABLE users; --"} {
+		require.NoError(t, ins.Bind(i+1, name))
+		_, err := ins.Execute()
+		require.NoError(t, err)
+	}
+
+	sel, err := ex.Prepare("SELECT name FROM users WHERE id >= $1 ORDER BY id;")
+	require.NoError(t, err)
+	require.NoError(t, sel.Bind(2))
+	res, err := sel.Execute()
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{"bob"}, {"o'brien; DROP TABLE users; --"}}, res.Rows)
+
+	// Executing again sees the current data; binding again changes the values.
+	upd, err := ex.Prepare("UPDATE users SET name = $2 WHERE id = $1;")
+	require.NoError(t, err)
+	require.NoError(t, upd.Bind(3, "cat"))
+	res, err = upd.Execute()
+	require.NoError(t, err)
+	require.Equal(t, int64(1), res.AffectedRows)
+	res, err = sel.Execute()
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{"bob"}, {"cat"}}, res.Rows)
+	require.NoError(t, sel.Bind(3))
+	res, err = sel.Execute()
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{"cat"}}, res.Rows)
+
+	// A failed Bind keeps the previous values.
+	require.Error(t, sel.Bind())
+	require.Error(t, sel.Bind("three"))
+	res, err = sel.Execute()
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{"cat"}}, res.Rows)
+
+	del, err := ex.Prepare("DELETE FROM users WHERE id = ?;")
+	require.NoError(t, err)
+	_, err = del.Execute()
+	require.Error(t, err, "nothing bound")
+	_, err = ex.ExecSQL("SELECT * FROM users WHERE id = ?;")
+	require.Error(t, err)
+}
+
+func TestExecutor_QueryMetrics(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	_, err = ex.ExecSQL("CREATE TABLE users (id INT, name TEXT);")
+	require.NoError(t, err)
+	_, err = ex.ExecSQL("SELEC 1;")
+	require.Error(t, err)
+	ins, err := ex.Prepare("INSERT INTO users VALUES (?, ?);")
+	require.NoError(t, err)
+	require.NoError(t, ins.Bind(1, "ann"))
+	_, err = ins.Execute()
+	require.NoError(t, err)
+
+	m := db.Metrics()
+	require.Equal(t, uint64(3), m.Queries)
+	require.Equal(t, uint64(1), m.QueryErrors)
+	require.Positive(t, m.QueryTime)
+}
+
+// benchExecutor returns an executor on a new database with a users table
+// keyed by id holding n rows.
+func benchExecutor(b *testing.B, n int) *Executor {
+	db, err := novasql.Options().Open(b.TempDir())
+	require.NoError(b, err)
+	b.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+	_, err = ex.ExecSQL("CREATE TABLE users (id INT PRIMARY KEY, name TEXT);")
+	require.NoError(b, err)
+	if n > 0 {
+		rows := make([][]any, n)
+		for i := range rows {
+			rows[i] = []any{int64(i), "user"}
+		}
+		_, err = db.InsertRows("users", rows)
+		require.NoError(b, err)
+	}
+	return ex
+}
+
+func BenchmarkStmt_Insert(b *testing.B) {
+	ex := benchExecutor(b, 0)
+	ins, err := ex.Prepare("INSERT INTO users VALUES (?, ?);")
+	require.NoError(b, err)
+
+	b.ResetTimer()
+	for i := range b.N {
+		if err := ins.Bind(i, "user"); err != nil {
+			b.Fatal(err)
+		}
+		if _, err := ins.Execute(); err != nil {
+			b.Fatal(err)
+		}
+	}
+	b.ReportMetric(float64(b.N)/b.Elapsed().Seconds(), "stmts/s")
+}
+
+func BenchmarkStmt_PointSelect(b *testing.B) {
+	const n = 10000
+	ex := benchExecutor(b, n)
+	sel, err := ex.Prepare("SELECT name FROM users WHERE id = ?;")
+	require.NoError(b, err)
+
+	b.ResetTimer()
+	for i := range b.N {
+		if err := sel.Bind((i * 7919) % n); err != nil {
+			b.Fatal(err)
+		}
+		if _, err := sel.Execute(); err != nil {
+			b.Fatal(err)
+		}
+	}
+	b.ReportMetric(float64(b.N)/b.Elapsed().Seconds(), "stmts/s")
+}
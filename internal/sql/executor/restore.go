@@ -0,0 +1,115 @@
+package executor
+
+import (
+	"fmt"
+	"io"
+	"slices"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/sql/parser"
+)
+
+// Restore runs a script written by novasql.Database.Dump and returns how
+// many statements it ran. The script may only create tables and then insert
+// into them, index them, add foreign keys to them and create views, and none
+// of the tables or views may exist yet. Either all of it
+// applies or none does: the whole script is parsed and checked first, and
+// when a statement fails the tables and views created before it are dropped
+// again.
+func (e *Executor) Restore(r io.Reader) (int, error) {
+	src, err := io.ReadAll(r)
+	if err != nil {
+		return 0, err
+	}
+	stmts, err := parser.ParseScript(string(src))
+	if err != nil {
+		return 0, syntaxError(err)
+	}
+	metas, err := e.DB.ListTables()
+	if err != nil {
+		return 0, err
+	}
+	existing := make(map[string]bool, len(metas))
+	for _, m := range metas {
+		existing[m.Name] = true
+	}
+	if err := checkRestoreScript(stmts, existing); err != nil {
+		return 0, err
+	}
+
+	var created, views []string
+	for i, stmt := range stmts {
+		if _, err := e.execStatement(stmt); err != nil {
+			for _, v := range views {
+				_ = e.DB.DropView(v)
+			}
+			e.dropTables(created)
+			return 0, fmt.Errorf("statement %d: %w", i+1, err)
+		}
+		switch s := stmt.(type) {
+		case *parser.CreateTableStmt:
+			created = append(created, s.TableName)
+		case *parser.CreateViewStmt:
+			views = append(views, s.Name)
+		}
+	}
+	return len(stmts), nil
+}
+
+// dropTables purges tables, a table referenced by the foreign keys of
+// others after them.
+func (e *Executor) dropTables(tables []string) {
+	for len(tables) > 0 {
+		var left []string
+		for _, table := range slices.Backward(tables) {
+			if err := e.DB.DropTablePurge(table); err != nil {
+				left = append(left, table)
+			}
+		}
+		if len(left) == len(tables) {
+			return
+		}
+		tables = left
+	}
+}
+
+// checkRestoreScript fails unless every statement of stmts creates a table
+// or view not in existing or works on a table an earlier one created, which
+// Restore can undo.
+func checkRestoreScript(stmts []parser.Statement, existing map[string]bool) error {
+	created := make(map[string]bool)
+	for i, stmt := range stmts {
+		var table string
+		switch s := stmt.(type) {
+		case *parser.CreateTableStmt:
+			if existing[s.TableName] || created[s.TableName] {
+				return novasql.NewError(novasql.CodeExists,
+					fmt.Errorf("executor: statement %d: table %s already exists", i+1, s.TableName))
+			}
+			created[s.TableName] = true
+			continue
+		case *parser.CreateViewStmt:
+			if existing[s.Name] || created[s.Name] {
+				return novasql.NewError(novasql.CodeExists,
+					fmt.Errorf("executor: statement %d: %s already exists", i+1, s.Name))
+			}
+			created[s.Name] = true
+			continue
+		case *parser.InsertStmt:
+			table = s.TableName
+		case *parser.CreateIndexStmt:
+			table = s.TableName
+		case *parser.AlterTableStmt:
+			if s.AddForeignKey == nil {
+				return fmt.Errorf("executor: statement %d: only ALTER TABLE ... ADD FOREIGN KEY can be restored", i+1)
+			}
+			table = s.TableName
+		default:
+			return fmt.Errorf("executor: statement %d: %T cannot be restored", i+1, stmt)
+		}
+		if !created[table] {
+			return fmt.Errorf("executor: statement %d: table %s is not created by the script", i+1, table)
+		}
+	}
+	return nil
+}
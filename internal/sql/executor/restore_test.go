@@ -0,0 +1,90 @@
+package executor
+
+import (
+	"bytes"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql"
+)
+
+func TestRestore(t *testing.T) {
+	open := func() (*novasql.Database, *Executor) {
+		t.Helper()
+		db, err := novasql.Options().Open(t.TempDir())
+		require.NoError(t, err)
+		t.Cleanup(func() { _ = db.Close() })
+		return db, NewExecutor(db)
+	}
+	src, ex := open()
+	for _, sql := range []string{
+		"CREATE TABLE users (id INT PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL UNIQUE);",
+		"INSERT INTO users (name) VALUES ('ann');",
+		"INSERT INTO users (name) VALUES ('bob');",
+		"CREATE TABLE nodes (id INT PRIMARY KEY, parent INT REFERENCES nodes ON DELETE SET NULL, " +
+			"owner INT REFERENCES users);",
+		"INSERT INTO nodes VALUES (1, NULL, 1);",
+		"INSERT INTO nodes VALUES (2, 1, 2);",
+		"UPDATE nodes SET owner = 2 WHERE id = 1;",
+		"CREATE INDEX nodes_owner_idx ON nodes (owner);",
+		"CREATE VIEW owners AS SELECT n.id, u.name FROM nodes n JOIN users u ON n.owner = u.id;",
+	} {
+		_, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+	}
+	var dump bytes.Buffer
+	require.NoError(t, src.Dump(&dump))
+
+	// A restored database dumps the same, constraints included.
+	dst, rex := open()
+	n, err := rex.Restore(bytes.NewReader(dump.Bytes()))
+	require.NoError(t, err)
+	require.Equal(t, strings.Count(dump.String(), ";\n"), n)
+	var again bytes.Buffer
+	require.NoError(t, dst.Dump(&again))
+	require.Equal(t, dump.String(), again.String())
+	res, err := rex.ExecSQL("SELECT name FROM owners WHERE id = 1;")
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{"bob"}}, res.Rows)
+	_, err = rex.ExecSQL("INSERT INTO nodes VALUES (3, 9, 1);")
+	require.ErrorIs(t, err, novasql.ErrConstraintViolation)
+	_, err = rex.ExecSQL("INSERT INTO users (name) VALUES ('cat');")
+	require.NoError(t, err)
+	require.Equal(t, int64(3), rex.LastInsertID())
+
+	// A failing statement drops the tables the script created.
+	other, oex := open()
+	_, err = oex.Restore(strings.NewReader(dump.String() + "INSERT INTO users VALUES (1, 'dup');\n"))
+	require.ErrorIs(t, err, novasql.ErrConstraintViolation)
+	require.ErrorContains(t, err, "statement ")
+	tables, err := other.ListTables()
+	require.NoError(t, err)
+	require.Empty(t, tables)
+	views, err := other.ListViews()
+	require.NoError(t, err)
+	require.Empty(t, views)
+
+	// Scripts that Restore could not undo are refused before they run.
+	for _, script := range []string{
+		"CREATE TABLE t (id INT); DROP TABLE t;",
+		"INSERT INTO users VALUES (4, 'dan');",
+		"CREATE TABLE t (id INT); ALTER TABLE t DROP COLUMN id;",
+		"CREATE TABLE t (id INT)",
+		"CREATE TABLE t (id INT); CREATE TABLE t (id INT);",
+	} {
+		_, err := oex.Restore(strings.NewReader(script))
+		require.Error(t, err, script)
+	}
+	tables, err = other.ListTables()
+	require.NoError(t, err)
+	require.Empty(t, tables)
+
+	// Tables that exist already are left alone.
+	_, err = rex.Restore(strings.NewReader("CREATE TABLE fresh (id INT); CREATE TABLE users (id INT);"))
+	require.Equal(t, novasql.CodeExists, novasql.CodeOf(err))
+	tables, err = dst.ListTables()
+	require.NoError(t, err)
+	require.Len(t, tables, 2)
+}
@@ -0,0 +1,206 @@
+package executor
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"time"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/sql/parser"
+	"github.com/tuannm99/novasql/internal/sql/planner"
+)
+
+var (
+	ErrCursorExists = errors.New("executor: cursor already exists")
+	ErrNoCursor     = errors.New("executor: no such cursor")
+)
+
+// Rows is the result of a query read a row at a time: its operators run as
+// Next asks for rows, so the result is never held in memory as a whole,
+// unless a sort or an aggregate of the query needs all of it. Rows read to
+// the end or to an error close themselves; close them when stopping before.
+//
+// The tables the rows are read from should not be changed while they are
+// open: the rows may or may not see the changes.
+type Rows struct {
+	op     Operator
+	cols   []string
+	row    []any
+	err    error
+	closed bool
+	done   func(err error) // called once closed, with the error that ended the rows
+}
+
+// Columns are the names of the columns of the rows.
+func (r *Rows) Columns() []string { return r.cols }
+
+// Next advances to the next row. It is false at the end of the rows or on
+// an error, which Err then returns.
+func (r *Rows) Next() bool {
+	if r.closed {
+		return false
+	}
+	row, ok, err := r.op.Next()
+	if err != nil || !ok {
+		r.err = err
+		_ = r.Close()
+		return false
+	}
+	r.row = row.Values
+	return true
+}
+
+// Values is the current row. It stays valid after the next call to Next.
+func (r *Rows) Values() []any { return r.row }
+
+// Err is the error that ended the rows, nil when they were read to the end
+// or are still being read.
+func (r *Rows) Err() error { return r.err }
+
+// Fetch reads the next n rows, all of the rest when n is negative; fewer
+// than n only at the end of the rows.
+func (r *Rows) Fetch(n int) ([][]any, error) {
+	var out [][]any
+	for (n < 0 || len(out) < n) && r.Next() {
+		out = append(out, r.row)
+	}
+	return out, r.err
+}
+
+// Close stops reading the rows and releases what their operators hold.
+// Closing them again does nothing.
+func (r *Rows) Close() error {
+	if r.closed {
+		return nil
+	}
+	r.closed = true
+	err := r.op.Close()
+	if r.err == nil {
+		r.err = err
+	}
+	if r.done != nil {
+		r.done(r.err)
+	}
+	return err
+}
+
+// Query runs sql and returns its rows as they are read, rather than all at
+// once as ExecSQL does. A statement other than a query runs to completion
+// and its Result is read back as the rows.
+func (e *Executor) Query(sql string) (*Rows, error) {
+	start := time.Now()
+	stmt, err := parser.Parse(sql)
+	if err != nil {
+		err = syntaxError(err)
+		e.observe(start, sql, err)
+		return nil, err
+	}
+	plan, err := e.planStatement(stmt)
+	if err != nil {
+		e.observe(start, sql, err)
+		return nil, err
+	}
+	return e.query(plan, sql, start)
+}
+
+// query opens the rows of plan p, which run as the statement of the executor
+// Cancel stops and are observed as statement sql, started at start, once
+// they are closed.
+func (e *Executor) query(p planner.Plan, sql string, start time.Time) (*Rows, error) {
+	st := e.begin(context.Background())
+	rows, err := e.openRows(p)
+	if err != nil {
+		e.end(st)
+		e.observe(start, sql, err)
+		return nil, err
+	}
+	rows.done = func(err error) {
+		e.end(st)
+		e.observe(start, sql, err)
+	}
+	return rows, nil
+}
+
+// openRows opens the operators of a query plan as Rows. Any other plan is
+// run, and its Result returned as them.
+func (e *Executor) openRows(p planner.Plan) (*Rows, error) {
+	var op Operator
+	var cols []string
+	switch p.(type) {
+	case *planner.IndexLookupPlan, *planner.IndexScanPlan, *planner.JoinPlan, *planner.SeqScanPlan,
+		*planner.ViewScanPlan:
+		var err error
+		if op, cols, err = e.planOp(p); err != nil {
+			return nil, err
+		}
+	default:
+		res, err := e.execPlan(p)
+		if err != nil {
+			return nil, err
+		}
+		op, cols = &ValuesOp{Rows: res.Rows}, res.Columns
+	}
+	if err := op.Open(); err != nil {
+		_ = op.Close()
+		return nil, err
+	}
+	return &Rows{op: op, cols: cols}, nil
+}
+
+// ---- cursors ----
+
+// declareCursor opens the rows of a DECLARE CURSOR, which its FETCHes read
+// until CLOSE.
+func (e *Executor) declareCursor(p *planner.DeclareCursorPlan) (*Result, error) {
+	if _, ok := e.cursors[p.Name]; ok {
+		return nil, novasql.NewError(novasql.CodeExists, fmt.Errorf("%w: %s", ErrCursorExists, p.Name))
+	}
+	rows, err := e.openRows(p.Query)
+	if err != nil {
+		return nil, err
+	}
+	if e.cursors == nil {
+		e.cursors = make(map[string]*Rows)
+	}
+	e.cursors[p.Name] = rows
+	return &Result{}, nil
+}
+
+// fetch reads the next rows of a cursor. A cursor that fails is closed.
+func (e *Executor) fetch(p *planner.FetchPlan) (*Result, error) {
+	rows, ok := e.cursors[p.Cursor]
+	if !ok {
+		return nil, novasql.NewError(novasql.CodeNotFound, fmt.Errorf("%w: %s", ErrNoCursor, p.Cursor))
+	}
+	vals, err := rows.Fetch(p.Count)
+	if err != nil {
+		delete(e.cursors, p.Cursor)
+		return nil, err
+	}
+	return &Result{Columns: rows.Columns(), Rows: vals, AffectedRows: int64(len(vals))}, nil
+}
+
+// closeCursor closes cursor name, or every cursor when name is empty.
+func (e *Executor) closeCursor(name string) error {
+	if name == "" {
+		return e.CloseCursors()
+	}
+	rows, ok := e.cursors[name]
+	if !ok {
+		return novasql.NewError(novasql.CodeNotFound, fmt.Errorf("%w: %s", ErrNoCursor, name))
+	}
+	delete(e.cursors, name)
+	return rows.Close()
+}
+
+// CloseCursors closes every cursor the executor has open, as CLOSE ALL
+// does. Sessions call it when they end.
+func (e *Executor) CloseCursors() error {
+	var errs []error
+	for name, rows := range e.cursors {
+		errs = append(errs, rows.Close())
+		delete(e.cursors, name)
+	}
+	return errors.Join(errs...)
+}
@@ -0,0 +1,125 @@
+package executor
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql"
+)
+
+func TestQuery_Rows(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	_, err = ex.ExecSQL("CREATE TABLE nums (n INT, sq INT);")
+	require.NoError(t, err)
+	ins, err := ex.Prepare("INSERT INTO nums VALUES (?, ?);")
+	require.NoError(t, err)
+	for i := 1; i <= 10; i++ {
+		require.NoError(t, ins.Bind(i, i*i))
+		_, err = ins.Execute()
+		require.NoError(t, err)
+	}
+
+	rows, err := ex.Query("SELECT sq FROM nums WHERE n > 3 ORDER BY n;")
+	require.NoError(t, err)
+	require.Equal(t, []string{"sq"}, rows.Columns())
+	require.True(t, rows.Next())
+	require.Equal(t, []any{int64(16)}, rows.Values())
+	page, err := rows.Fetch(2)
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{int64(25)}, {int64(36)}}, page)
+	page, err = rows.Fetch(-1)
+	require.NoError(t, err)
+	require.Len(t, page, 4)
+	require.False(t, rows.Next())
+	require.NoError(t, rows.Err())
+	require.NoError(t, rows.Close())
+
+	// Stopping early, a prepared query, and a statement that is not a query.
+	sel, err := ex.Prepare("SELECT n FROM nums WHERE n <= ?;")
+	require.NoError(t, err)
+	require.NoError(t, sel.Bind(5))
+	rows, err = sel.Query()
+	require.NoError(t, err)
+	page, err = rows.Fetch(2)
+	require.NoError(t, err)
+	require.Len(t, page, 2)
+	require.NoError(t, rows.Close())
+	require.False(t, rows.Next())
+
+	rows, err = ex.Query("DELETE FROM nums WHERE n > 5;")
+	require.NoError(t, err)
+	require.Nil(t, rows.Columns())
+	require.False(t, rows.Next())
+	res, err := ex.ExecSQL("SELECT COUNT(*) FROM nums;")
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{int64(5)}}, res.Rows)
+
+	_, err = ex.Query("SELECT * FROM missing;")
+	require.Error(t, err)
+	_, err = ex.Query("SELECT * FROM nums WHERE n = ?;")
+	require.Error(t, err)
+}
+
+func TestCursors(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	_, err = ex.ExecSQL("CREATE TABLE t (id INT);")
+	require.NoError(t, err)
+	for _, id := range []string{"1", "2", "3", "4", "5"} {
+		_, err = ex.ExecSQL("INSERT INTO t VALUES (" + id + ");")
+		require.NoError(t, err)
+	}
+
+	_, err = ex.ExecSQL("DECLARE c CURSOR FOR SELECT id FROM t ORDER BY id DESC;")
+	require.NoError(t, err)
+	_, err = ex.ExecSQL("DECLARE c CURSOR FOR SELECT id FROM t;")
+	require.ErrorIs(t, err, ErrCursorExists)
+	require.Equal(t, novasql.CodeExists, novasql.CodeOf(err))
+
+	res, err := ex.ExecSQL("FETCH 2 FROM c;")
+	require.NoError(t, err)
+	require.Equal(t, []string{"id"}, res.Columns)
+	require.Equal(t, [][]any{{int64(5)}, {int64(4)}}, res.Rows)
+	res, err = ex.ExecSQL("FETCH NEXT FROM c;")
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{int64(3)}}, res.Rows)
+	res, err = ex.ExecSQL("FETCH ALL FROM c;")
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{int64(2)}, {int64(1)}}, res.Rows)
+	res, err = ex.ExecSQL("FETCH c;")
+	require.NoError(t, err)
+	require.Empty(t, res.Rows)
+
+	// A prepared DECLARE binds the values of its query.
+	decl, err := ex.Prepare("DECLARE d CURSOR FOR SELECT id FROM t WHERE id > ? ORDER BY id;")
+	require.NoError(t, err)
+	require.NoError(t, decl.Bind(3))
+	_, err = decl.Execute()
+	require.NoError(t, err)
+	res, err = ex.ExecSQL("FETCH 10 FROM d;")
+	require.NoError(t, err)
+	require.Equal(t, [][]any{{int64(4)}, {int64(5)}}, res.Rows)
+
+	_, err = ex.ExecSQL("CLOSE c;")
+	require.NoError(t, err)
+	_, err = ex.ExecSQL("FETCH c;")
+	require.ErrorIs(t, err, ErrNoCursor)
+	require.Equal(t, novasql.CodeNotFound, novasql.CodeOf(err))
+	_, err = ex.ExecSQL("CLOSE c;")
+	require.ErrorIs(t, err, ErrNoCursor)
+	_, err = ex.ExecSQL("CLOSE ALL;")
+	require.NoError(t, err)
+	_, err = ex.ExecSQL("FETCH d;")
+	require.ErrorIs(t, err, ErrNoCursor)
+	_, err = ex.ExecSQL("DECLARE e CURSOR FOR SELECT * FROM missing;")
+	require.Error(t, err)
+	require.NoError(t, ex.CloseCursors())
+}
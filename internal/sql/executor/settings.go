@@ -0,0 +1,32 @@
+package executor
+
+import (
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/sql/planner"
+)
+
+// settingColumns are the columns SHOW returns.
+var settingColumns = []string{"name", "setting", "description"}
+
+// execSet changes a configuration parameter of the database handle; see
+// novasql.Database.SetSetting.
+func (e *Executor) execSet(p *planner.SetPlan) (*Result, error) {
+	return &Result{}, e.raw.SetSetting(p.Name, p.Value)
+}
+
+// execShow lists one configuration parameter, or all of them for SHOW ALL.
+func (e *Executor) execShow(p *planner.ShowPlan) (*Result, error) {
+	settings := e.raw.Settings()
+	if p.Name != "" {
+		s, err := e.raw.Setting(p.Name)
+		if err != nil {
+			return nil, err
+		}
+		settings = []novasql.Setting{s}
+	}
+	res := &Result{Columns: settingColumns}
+	for _, s := range settings {
+		res.Rows = append(res.Rows, []any{s.Name, s.Value, s.Description})
+	}
+	return res, nil
+}
@@ -0,0 +1,38 @@
+package executor
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql"
+)
+
+func TestSetShow(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	_, err = ex.ExecSQL("SET sync_mode = normal;")
+	require.NoError(t, err)
+	_, err = ex.ExecSQL("SET lock_wait_timeout TO '2s';")
+	require.NoError(t, err)
+	res, err := ex.ExecSQL("SHOW sync_mode;")
+	require.NoError(t, err)
+	require.Equal(t, []string{"name", "setting", "description"}, res.Columns)
+	require.Len(t, res.Rows, 1)
+	require.Equal(t, []any{"sync_mode", "normal"}, res.Rows[0][:2])
+
+	res, err = ex.ExecSQL("SHOW ALL;")
+	require.NoError(t, err)
+	require.Len(t, res.Rows, len(db.Settings()))
+	require.Equal(t, []any{"lock_wait_timeout", "2s"}, res.Rows[4][:2])
+
+	_, err = ex.ExecSQL("SET buffer_pool_pages = 64;")
+	require.ErrorIs(t, err, novasql.ErrSettingFixed)
+	_, err = ex.ExecSQL("SET sync_mode = sometimes;")
+	require.Equal(t, novasql.CodeInvalid, novasql.CodeOf(err))
+	_, err = ex.ExecSQL("SHOW colour;")
+	require.Equal(t, novasql.CodeNotFound, novasql.CodeOf(err))
+}
@@ -0,0 +1,352 @@
+package executor
+
+import (
+	"bufio"
+	"encoding/binary"
+	"errors"
+	"fmt"
+	"io"
+	"math"
+	"os"
+
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+// maxMergeRuns bounds the runs an external sort merges at once, and so the
+// files it keeps open: once that many are spilled, they are merged into one.
+const maxMergeRuns = 64
+
+var errBadRun = errors.New("executor: corrupt sort run")
+
+// Value tags of the spilled row format.
+const (
+	tagNull byte = iota
+	tagInt32
+	tagInt64
+	tagFloat64
+	tagBool
+	tagText
+	tagBytes
+	tagDate
+	tagTime
+	tagTimestamp
+	tagDecimal
+)
+
+// rowBytes estimates the memory r holds.
+func rowBytes(r Row) int64 {
+	n := int64(32 + 16*len(r.Values)) // Row, plus an interface per value
+	for _, v := range r.Values {
+		switch v := v.(type) {
+		case string:
+			n += int64(len(v))
+		case []byte:
+			n += int64(len(v))
+		}
+	}
+	return n
+}
+
+// appendRow encodes r at the end of dst: TID, value count, then a tag and
+// the bytes of each value.
+func appendRow(dst []byte, r Row) ([]byte, error) {
+	dst = binary.AppendUvarint(dst, uint64(r.TID.PageID))
+	dst = binary.AppendUvarint(dst, uint64(r.TID.Slot))
+	dst = binary.AppendUvarint(dst, uint64(len(r.Values)))
+	for _, v := range r.Values {
+		switch v := v.(type) {
+		case nil:
+			dst = append(dst, tagNull)
+		case int32:
+			dst = binary.AppendVarint(append(dst, tagInt32), int64(v))
+		case int64:
+			dst = binary.AppendVarint(append(dst, tagInt64), v)
+		case float64:
+			dst = binary.LittleEndian.AppendUint64(append(dst, tagFloat64), math.Float64bits(v))
+		case bool:
+			b := byte(0)
+			if v {
+				b = 1
+			}
+			dst = append(dst, tagBool, b)
+		case string:
+			dst = append(binary.AppendUvarint(append(dst, tagText), uint64(len(v))), v...)
+		case []byte:
+			dst = append(binary.AppendUvarint(append(dst, tagBytes), uint64(len(v))), v...)
+		case record.Date:
+			dst = binary.AppendVarint(append(dst, tagDate), int64(v))
+		case record.Time:
+			dst = binary.AppendVarint(append(dst, tagTime), int64(v))
+		case record.Timestamp:
+			dst = binary.AppendVarint(append(dst, tagTimestamp), int64(v))
+		case record.Decimal:
+			dst = append(binary.AppendVarint(append(dst, tagDecimal), v.Coef), v.Scale)
+		default:
+			return nil, fmt.Errorf("executor: cannot spill a %T value", v)
+		}
+	}
+	return dst, nil
+}
+
+// runReader decodes one spilled row; the first error sticks.
+type runReader struct {
+	b   []byte
+	err error
+}
+
+func (d *runReader) uvarint() uint64 {
+	v, n := binary.Uvarint(d.b)
+	if d.err != nil || n <= 0 {
+		d.err = errBadRun
+		return 0
+	}
+	d.b = d.b[n:]
+	return v
+}
+
+func (d *runReader) varint() int64 {
+	v, n := binary.Varint(d.b)
+	if d.err != nil || n <= 0 {
+		d.err = errBadRun
+		return 0
+	}
+	d.b = d.b[n:]
+	return v
+}
+
+func (d *runReader) bytes(n uint64) []byte {
+	if d.err != nil || uint64(len(d.b)) < n {
+		d.err = errBadRun
+		return nil
+	}
+	out := d.b[:n:n]
+	d.b = d.b[n:]
+	return out
+}
+
+func (d *runReader) u8() byte {
+	if b := d.bytes(1); b != nil {
+		return b[0]
+	}
+	return 0
+}
+
+func (d *runReader) u64() uint64 {
+	if b := d.bytes(8); b != nil {
+		return binary.LittleEndian.Uint64(b)
+	}
+	return 0
+}
+
+func decodeRow(b []byte) (Row, error) {
+	d := &runReader{b: b}
+	r := Row{TID: heap.TID{PageID: uint32(d.uvarint()), Slot: uint16(d.uvarint())}}
+	n := d.uvarint()
+	if n > uint64(len(b)) {
+		return Row{}, errBadRun
+	}
+	r.Values = make([]any, 0, n)
+	for range n {
+		switch d.u8() {
+		case tagNull:
+			r.Values = append(r.Values, nil)
+		case tagInt32:
+			r.Values = append(r.Values, int32(d.varint()))
+		case tagInt64:
+			r.Values = append(r.Values, d.varint())
+		case tagFloat64:
+			r.Values = append(r.Values, math.Float64frombits(d.u64()))
+		case tagBool:
+			r.Values = append(r.Values, d.u8() != 0)
+		case tagText:
+			r.Values = append(r.Values, string(d.bytes(d.uvarint())))
+		case tagBytes:
+			r.Values = append(r.Values, d.bytes(d.uvarint()))
+		case tagDate:
+			r.Values = append(r.Values, record.Date(d.varint()))
+		case tagTime:
+			r.Values = append(r.Values, record.Time(d.varint()))
+		case tagTimestamp:
+			r.Values = append(r.Values, record.Timestamp(d.varint()))
+		case tagDecimal:
+			r.Values = append(r.Values, record.Decimal{Coef: d.varint(), Scale: d.u8()})
+		default:
+			d.err = errBadRun
+		}
+		if d.err != nil {
+			return Row{}, d.err
+		}
+	}
+	return r, nil
+}
+
+// sortRun is one sorted run of an external sort: a temporary file, or the
+// rows still in memory when f is nil. head is its next row while ok.
+type sortRun struct {
+	f    *os.File
+	r    *bufio.Reader
+	rows []Row
+	head Row
+	ok   bool
+}
+
+// runWriter writes a run file, one length-prefixed row after the other.
+type runWriter struct {
+	run *sortRun
+	w   *bufio.Writer
+	buf []byte
+}
+
+func createRun(dir string) (*runWriter, error) {
+	f, err := os.CreateTemp(dir, "run-")
+	if err != nil {
+		return nil, err
+	}
+	return &runWriter{run: &sortRun{f: f}, w: bufio.NewWriter(f)}, nil
+}
+
+func (w *runWriter) write(r Row) error {
+	rec, err := appendRow(w.buf[:0], r)
+	if err != nil {
+		return err
+	}
+	w.buf = rec
+	if _, err := w.w.Write(binary.AppendUvarint(nil, uint64(len(rec)))); err != nil {
+		return err
+	}
+	_, err = w.w.Write(rec)
+	return err
+}
+
+// finish flushes the file and rewinds it for reading; on error the file is
+// removed.
+func (w *runWriter) finish() (*sortRun, error) {
+	err := w.w.Flush()
+	if err == nil {
+		_, err = w.run.f.Seek(0, io.SeekStart)
+	}
+	if err != nil {
+		_ = w.run.close()
+		return nil, err
+	}
+	w.run.r = bufio.NewReader(w.run.f)
+	return w.run, nil
+}
+
+// writeRun writes the sorted rows to a new run file in dir.
+func writeRun(dir string, rows []Row) (*sortRun, error) {
+	w, err := createRun(dir)
+	if err != nil {
+		return nil, err
+	}
+	for _, r := range rows {
+		if err := w.write(r); err != nil {
+			_ = w.run.close()
+			return nil, err
+		}
+	}
+	return w.finish()
+}
+
+// advance moves head to the next row of the run.
+func (r *sortRun) advance() error {
+	if r.f == nil {
+		r.ok = len(r.rows) > 0
+		if r.ok {
+			r.head, r.rows = r.rows[0], r.rows[1:]
+		}
+		return nil
+	}
+	n, err := binary.ReadUvarint(r.r)
+	if errors.Is(err, io.EOF) {
+		r.ok = false
+		return nil
+	}
+	if err != nil {
+		return err
+	}
+	rec := make([]byte, n)
+	if _, err := io.ReadFull(r.r, rec); err != nil {
+		return err
+	}
+	r.head, err = decodeRow(rec)
+	r.ok = err == nil
+	return err
+}
+
+// close closes and removes the file of the run.
+func (r *sortRun) close() error {
+	if r.f == nil {
+		return nil
+	}
+	err := r.f.Close()
+	if rerr := os.Remove(r.f.Name()); err == nil {
+		err = rerr
+	}
+	r.f = nil
+	return err
+}
+
+// runMerger merges sorted runs. Ties go to the earlier run, which keeps the
+// sort stable when the runs are in input order.
+type runMerger struct {
+	runs []*sortRun
+	less func(a, b Row) bool
+}
+
+func newRunMerger(runs []*sortRun, less func(a, b Row) bool) (*runMerger, error) {
+	for _, r := range runs {
+		if err := r.advance(); err != nil {
+			return nil, err
+		}
+	}
+	return &runMerger{runs: runs, less: less}, nil
+}
+
+func (m *runMerger) next() (Row, bool, error) {
+	best := -1
+	for i, r := range m.runs {
+		if r.ok && (best < 0 || m.less(r.head, m.runs[best].head)) {
+			best = i
+		}
+	}
+	if best < 0 {
+		return Row{}, false, nil
+	}
+	row := m.runs[best].head
+	if err := m.runs[best].advance(); err != nil {
+		return Row{}, false, err
+	}
+	return row, true, nil
+}
+
+// mergeRuns merges runs into a single run file in dir and closes them.
+func mergeRuns(dir string, runs []*sortRun, less func(a, b Row) bool) (*sortRun, error) {
+	defer func() {
+		for _, r := range runs {
+			_ = r.close()
+		}
+	}()
+	m, err := newRunMerger(runs, less)
+	if err != nil {
+		return nil, err
+	}
+	w, err := createRun(dir)
+	if err != nil {
+		return nil, err
+	}
+	for {
+		r, ok, err := m.next()
+		if err == nil && ok {
+			err = w.write(r)
+		}
+		if err != nil {
+			_ = w.run.close()
+			return nil, err
+		}
+		if !ok {
+			return w.finish()
+		}
+	}
+}
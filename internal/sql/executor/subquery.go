@@ -0,0 +1,170 @@
+package executor
+
+import (
+	"errors"
+	"fmt"
+
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/planner"
+)
+
+var errSubqueryRows = errors.New("executor: subquery used as a value returned more than one row")
+
+// filterOp wraps child in a FilterOp for w, which may test a subquery (nil
+// w = no filter).
+func (e *Executor) filterOp(child Operator, schema record.Schema, w *planner.Where) Operator {
+	if w == nil || w.Sub == nil {
+		return whereFilter(child, schema, w)
+	}
+
+	pos := colPos(schema, w.Column) // -1 for EXISTS
+	cond := fmt.Sprintf("%s %s (subquery)", w.Column, w.Op)
+	if w.Column == "" {
+		cond = w.Op + " (subquery)"
+	}
+	if w.Sub.Outer != nil {
+		cond += " on " + w.Sub.Outer.Column
+	}
+
+	// An uncorrelated subquery runs once, when the first row is checked; a
+	// correlated one once for each value of its outer column.
+	results := make(map[any]*subqueryResult)
+	return &FilterOp{
+		Child: child,
+		Cond:  cond,
+		Pred: func(row []any) (bool, error) {
+			var v, key any
+			if pos >= 0 {
+				v = row[pos]
+			}
+			if w.Sub.Outer != nil {
+				if key = row[w.Sub.Outer.Pos]; key == nil {
+					// No row of the subquery matches a NULL.
+					return (&subqueryResult{}).match(w.Op, v)
+				}
+			}
+			r, ok := results[key]
+			if !ok {
+				plan := w.Sub.Plan
+				if key != nil {
+					plan = planner.BindOuter(plan, key)
+				}
+				var err error
+				if r, err = e.runSubquery(w.Op, plan); err != nil {
+					return false, err
+				}
+				results[key] = r
+			}
+			return r.match(w.Op, v)
+		},
+	}
+}
+
+// subqueryResult is what a Where needs of the rows of a subquery.
+type subqueryResult struct {
+	rows   bool
+	values map[any]bool // IN, NOT IN: the non-NULL values
+	null   bool         // IN, NOT IN: a NULL among them
+	value  any          // comparison: the only value, NULL for no row
+}
+
+// runSubquery runs the plan of a subquery and keeps what op needs: whether
+// there is a row for EXISTS, the set of values for IN, one value otherwise.
+func (e *Executor) runSubquery(op string, p planner.Plan) (*subqueryResult, error) {
+	limit := -1
+	switch op {
+	case "EXISTS", "NOT EXISTS":
+		limit = 1
+	case "IN", "NOT IN":
+	default:
+		limit = 2 // one more than a value may have, to tell
+	}
+	rows, err := e.subqueryRows(p, limit)
+	if err != nil {
+		return nil, err
+	}
+
+	r := &subqueryResult{rows: len(rows) > 0}
+	switch limit {
+	case 1:
+	case 2:
+		if len(rows) > 1 {
+			return nil, errSubqueryRows
+		}
+		if len(rows) == 1 {
+			r.value = rows[0][0]
+		}
+	default:
+		r.values = make(map[any]bool, len(rows))
+		for _, row := range rows {
+			if row[0] == nil {
+				r.null = true
+			} else {
+				r.values[row[0]] = true
+			}
+		}
+	}
+	return r, nil
+}
+
+// subqueryRows returns the first limit rows of the query p, all of them when
+// limit is negative.
+func (e *Executor) subqueryRows(p planner.Plan, limit int) ([][]any, error) {
+	op, _, err := e.planOp(p)
+	if err != nil {
+		return nil, err
+	}
+	if err := op.Open(); err != nil {
+		_ = op.Close()
+		return nil, err
+	}
+	var rows [][]any
+	for limit < 0 || len(rows) < limit {
+		r, ok, err := op.Next()
+		if err != nil {
+			_ = op.Close()
+			return nil, err
+		}
+		if !ok {
+			break
+		}
+		rows = append(rows, r.Values)
+	}
+	return rows, op.Close()
+}
+
+// match applies op to v, the value of the row's column, and the rows of the
+// subquery, as SQL does with NULLs: NULL is in no set, and a value missing
+// from a set with a NULL is not known to be NOT IN it.
+func (r *subqueryResult) match(op string, v any) (bool, error) {
+	switch op {
+	case "EXISTS":
+		return r.rows, nil
+	case "NOT EXISTS":
+		return !r.rows, nil
+	case "IN":
+		return v != nil && r.values[v], nil
+	case "NOT IN":
+		return !r.rows || v != nil && !r.null && !r.values[v], nil
+	}
+	if v == nil || r.value == nil {
+		return false, nil
+	}
+	a, b := numericPair(v, r.value)
+	return compareOp(op, compareValues(a, b))
+}
+
+// numericPair converts an int64 compared with a float64 to float64.
+func numericPair(a, b any) (any, any) {
+	switch x := a.(type) {
+	case int64:
+		if _, ok := b.(float64); ok {
+			return float64(x), b
+		}
+	case float64:
+		if y, ok := b.(int64); ok {
+			return a, float64(y)
+		}
+	}
+	return a, b
+}
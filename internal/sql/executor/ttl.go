@@ -0,0 +1,103 @@
+package executor
+
+import (
+	"fmt"
+	"time"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+// ttlBatch is how many expired rows ReapExpired deletes per transaction.
+const ttlBatch = 256
+
+// expiredRow is a row ReapExpired deletes.
+type expiredRow struct {
+	tid    heap.TID
+	values []any
+}
+
+// ReapExpired deletes the rows of the selected database whose TTL column
+// (see novasql.Database.SetTTL) is at or before now, in snapshot
+// transactions of up to ttlBatch rows, and returns how many it deleted.
+// Foreign keys referencing the rows act as they do on DELETE; their
+// cascades are not part of the batch.
+func (e *Executor) ReapExpired(now time.Time) (int, error) {
+	metas, err := e.DB.ListTables()
+	if err != nil {
+		return 0, err
+	}
+	n := 0
+	for _, meta := range metas {
+		if meta.TTLColumn == "" {
+			continue
+		}
+		reaped, err := e.reapTable(meta, record.TimestampOf(now))
+		n += reaped
+		if err != nil {
+			return n, fmt.Errorf("executor: ttl %s: %w", meta.Name, err)
+		}
+	}
+	return n, nil
+}
+
+// reapTable deletes the expired rows of one table in a single pass.
+func (e *Executor) reapTable(meta *novasql.TableMeta, now record.Timestamp) (int, error) {
+	tbl, err := e.DB.OpenTable(meta.Name)
+	if err != nil {
+		return 0, err
+	}
+	pos := colPos(tbl.Schema, meta.TTLColumn)
+	if pos < 0 {
+		return 0, fmt.Errorf("%w: %s.%s", novasql.ErrColumnNotFound, meta.Name, meta.TTLColumn)
+	}
+
+	n := 0
+	batch := make([]expiredRow, 0, ttlBatch)
+	it := tbl.Iter()
+	for {
+		id, row, ok, err := it.Next()
+		if err != nil {
+			return n, err
+		}
+		if ok {
+			if ts, isTS := row[pos].(record.Timestamp); !isTS || ts > now {
+				continue
+			}
+			batch = append(batch, expiredRow{tid: id, values: row})
+		}
+		if len(batch) == ttlBatch || (!ok && len(batch) > 0) {
+			if err := e.reapBatch(meta.Name, tbl, batch); err != nil {
+				return n, err
+			}
+			n += len(batch)
+			batch = batch[:0]
+		}
+		if !ok {
+			return n, nil
+		}
+	}
+}
+
+// reapBatch deletes rows in one transaction. Their index entries stay, as
+// the snapshots that still see the rows need them; lookups skip the entries
+// of deleted rows, and Vacuum, which frees the rows, rebuilds the indexes.
+func (e *Executor) reapBatch(table string, tbl *heap.Table, rows []expiredRow) error {
+	tx, err := e.DB.BeginSnapshot()
+	if err != nil {
+		return err
+	}
+	deleting := make(map[rowRef]bool, len(rows))
+	for _, r := range rows {
+		err := e.onDelete(table, tbl.Schema, r.tid, r.values, deleting)
+		if err == nil {
+			err = tbl.DeleteTx(tx, r.tid)
+		}
+		if err != nil {
+			_ = tx.Abort()
+			return err
+		}
+	}
+	return tx.Commit()
+}
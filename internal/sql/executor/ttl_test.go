@@ -0,0 +1,73 @@
+package executor
+
+import (
+	"fmt"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/btree"
+)
+
+func TestReapExpired(t *testing.T) {
+	db, err := novasql.Options().Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	ex := NewExecutor(db)
+
+	exec := func(sql string) *Result {
+		t.Helper()
+		res, err := ex.ExecSQL(sql)
+		require.NoError(t, err, sql)
+		return res
+	}
+
+	exec("CREATE TABLE sessions (id INT PRIMARY KEY, expires_at TIMESTAMP) WITH (ttl_column = expires_at);")
+	exec("CREATE TABLE keep (id INT, at TIMESTAMP);")
+	expired := ttlBatch + 10
+	for i := range expired {
+		exec(fmt.Sprintf("INSERT INTO sessions VALUES (%d, '2024-06-01 00:00:00');", i))
+	}
+	exec("INSERT INTO sessions VALUES (1000, '2026-01-01 00:00:00');")
+	exec("INSERT INTO sessions VALUES (1001, NULL);")
+	exec("INSERT INTO keep VALUES (1, '2024-06-01 00:00:00');")
+
+	// A snapshot taken before reaping still finds the rows through the index.
+	snap, err := db.BeginSnapshot()
+	require.NoError(t, err)
+	now := time.Date(2025, 1, 1, 0, 0, 0, 0, time.UTC)
+	n, err := ex.ReapExpired(now)
+	require.NoError(t, err)
+	require.Equal(t, expired, n)
+	require.Equal(t, [][]any{{int64(1000)}, {int64(1001)}}, exec("SELECT id FROM sessions;").Rows)
+	require.Len(t, exec("SELECT id FROM keep;").Rows, 1, "keep has no TTL column")
+
+	tbl, err := db.OpenTable("sessions")
+	require.NoError(t, err)
+	tree, err := db.OpenKVTreeIndex("sessions", novasql.PrimaryKeyName("sessions"))
+	require.NoError(t, err)
+	seen := 0
+	for it := tree.Scan(nil, nil); it.Next(); {
+		id, err := btree.IndexEntryTID(it.Key())
+		require.NoError(t, err)
+		if _, err := tbl.GetTx(snap, id); err == nil {
+			seen++
+		}
+	}
+	require.NoError(t, tree.Close())
+	require.Equal(t, expired+2, seen)
+	require.NoError(t, snap.Abort())
+
+	// The index entries of reaped rows no longer count: their keys are free.
+	exec("INSERT INTO sessions VALUES (0, '2026-01-01 00:00:00');")
+	n, err = ex.ReapExpired(now)
+	require.NoError(t, err)
+	require.Zero(t, n)
+
+	_, err = ex.ExecSQL("CREATE TABLE bad (id INT, ttl INT) WITH (ttl_column = ttl);")
+	require.ErrorIs(t, err, novasql.ErrTTLColumn)
+	_, err = db.OpenTable("bad")
+	require.ErrorIs(t, err, novasql.ErrTableNotFound)
+}
@@ -0,0 +1,82 @@
+package expr
+
+import "strings"
+
+// Collation orders text. A comparison of TEXT values, LIKE too, uses the
+// collation COLLATE names for one of its operands, else that of a column
+// among them (record.Column.Collation), else BINARY. ORDER BY and the index
+// of a column order its values by the collation of the column.
+type Collation struct {
+	Name    string // upper case
+	Compare func(a, b string) int
+
+	// Key maps strings to keys that order bytewise as Compare orders the
+	// strings, which is what an index keeps; nil for a collation no index
+	// can use.
+	Key func(s string) string
+}
+
+var (
+	// Binary orders strings bytewise.
+	Binary = &Collation{Name: "BINARY", Compare: strings.Compare, Key: func(s string) string { return s }}
+	// NoCase orders strings as their lower case does.
+	NoCase = &Collation{Name: "NOCASE", Compare: compareNoCase, Key: strings.ToLower}
+)
+
+var collations = map[string]*Collation{Binary.Name: Binary, NoCase.Name: NoCase}
+
+func compareNoCase(a, b string) int { return strings.Compare(strings.ToLower(a), strings.ToLower(b)) }
+
+// LookupCollation returns the built-in collation called name (in any case).
+func LookupCollation(name string) (*Collation, bool) {
+	c, ok := collations[strings.ToUpper(name)]
+	return c, ok
+}
+
+// IndexKey is v as an index ordered by c keeps it: a string mapped by Key,
+// anything else as it is. A nil c is BINARY.
+func (c *Collation) IndexKey(v any) any {
+	if s, ok := v.(string); ok && c != nil && c.Key != nil {
+		return c.Key(s)
+	}
+	return v
+}
+
+// compare orders two non-NULL values as Compare does, strings by c (nil:
+// bytewise).
+func (c *Collation) compare(a, b any) int {
+	if x, ok := a.(string); ok && c != nil {
+		if y, ok := b.(string); ok {
+			return c.Compare(x, y)
+		}
+	}
+	return Compare(a, b)
+}
+
+// fold is s as LIKE matches it under c: mapped by Key, so that NOCASE
+// ignores case; a collation without one matches bytewise.
+func (c *Collation) fold(s string) string {
+	if c == nil || c.Key == nil {
+		return s
+	}
+	return c.Key(s)
+}
+
+// sameCollation reports whether a and b order text alike, nil being BINARY.
+func sameCollation(a, b *Collation) bool {
+	return a == b || a == nil && b == Binary || a == Binary && b == nil
+}
+
+// collationOf is the collation of a comparison of l and r: one COLLATE
+// names, the left one first, else that of the left, then the right operand.
+func collationOf(l, r *Expr) *Collation {
+	switch {
+	case l.collated:
+		return l.coll
+	case r.collated:
+		return r.coll
+	case l.coll != nil:
+		return l.coll
+	}
+	return r.coll
+}
@@ -0,0 +1,209 @@
+package expr
+
+import (
+	"fmt"
+	"math"
+	"strconv"
+	"strings"
+
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/parser"
+)
+
+// defaultDecimalPrecision is the precision of DECIMAL without one.
+const defaultDecimalPrecision = 10
+
+// SQLType is a column of the type SQL calls name, with the precision and
+// scale of DECIMAL(p, s); DECIMAL alone is DECIMAL(10, 0).
+func SQLType(name string, precision, scale int) (record.Column, error) {
+	var t record.ColumnType
+	switch strings.ToUpper(name) {
+	case "INT", "INTEGER", "BIGINT":
+		t = record.ColInt64
+	case "FLOAT", "DOUBLE", "REAL":
+		t = record.ColFloat64
+	case "TEXT":
+		t = record.ColText
+	case "BOOL", "BOOLEAN":
+		t = record.ColBool
+	case "JSON":
+		t = record.ColJSON
+	case "DATE":
+		t = record.ColDate
+	case "TIME":
+		t = record.ColTime
+	case "TIMESTAMP", "DATETIME":
+		t = record.ColTimestamp
+	case "DECIMAL", "NUMERIC":
+		if precision == 0 {
+			precision = defaultDecimalPrecision
+		}
+		return record.Column{Type: record.ColDecimal, Precision: uint8(precision), Scale: uint8(scale)}, nil
+	default:
+		return record.Column{}, fmt.Errorf("unsupported column type: %s", name)
+	}
+	return record.Column{Type: t}, nil
+}
+
+// decimalConst is c, when it is a number constant next to d, a DECIMAL, as
+// a DECIMAL constant: "price * 1.1" is then exact, and "price = 9.99" a
+// comparison an index over price can find the rows of.
+func decimalConst(c, d *Expr) *Expr {
+	if c.kind != kindConst || c.null || d.null || d.typ != record.ColDecimal {
+		return c
+	}
+	var v record.Decimal
+	var err error
+	switch x := c.value.(type) {
+	case int64:
+		v, err = record.ParseDecimal(strconv.FormatInt(x, 10))
+	case float64:
+		v, err = record.FloatDecimal(x)
+	default:
+		return c
+	}
+	if err != nil {
+		return c // compared as it is
+	}
+	e, _ := Const(v)
+	return e
+}
+
+// toDecimal is v, an int64 or a record.Decimal, as the latter.
+func toDecimal(v any) record.Decimal {
+	if i, ok := v.(int64); ok {
+		return record.Decimal{Coef: i}
+	}
+	return v.(record.Decimal)
+}
+
+// decimalArith applies arithmetic operator op to two decimals, with the
+// scales record.Decimal gives its results.
+func decimalArith(op string, x, y record.Decimal) (any, error) {
+	var d record.Decimal
+	var err error
+	switch op {
+	case "+":
+		d, err = x.Add(y)
+	case "-":
+		d, err = x.Sub(y)
+	case "*":
+		d, err = x.Mul(y)
+	default:
+		if y.Coef == 0 {
+			return nil, ErrDivisionByZero
+		}
+		if op == "/" {
+			d, err = x.Div(y)
+		} else {
+			d, err = x.Mod(y)
+		}
+	}
+	if err != nil {
+		return nil, fmt.Errorf("expr: %s %s %s: %w", x, op, y, err)
+	}
+	return d, nil
+}
+
+// cast binds "CAST(x AS type)" as a call of a function that converts its
+// argument.
+func (b *binder) cast(x *parser.CastExpr) (*Expr, error) {
+	arg, err := b.bind(x.X)
+	if err != nil {
+		return nil, err
+	}
+	to, err := SQLType(x.Type, x.Precision, x.Scale)
+	if err != nil {
+		return nil, fmt.Errorf("expr: %w", err)
+	}
+	name := typeName(to.Type)
+	if to.Type == record.ColDecimal {
+		name = fmt.Sprintf("DECIMAL(%d, %d)", to.Precision, to.Scale)
+	}
+	if !arg.null && !castable(arg.typ, to.Type) {
+		return nil, fmt.Errorf("expr: cannot cast %s to %s", arg, name)
+	}
+	fn := &Func{Name: "CAST", AnyType: true, Result: to.Type, Call: func(a []any) (any, error) {
+		return castValue(to, a[0])
+	}}
+	return &Expr{
+		kind: kindCall,
+		fn:   fn,
+		typ:  to.Type,
+		args: []*Expr{arg},
+		text: "CAST(" + arg.text + " AS " + name + ")",
+	}, nil
+}
+
+// castable reports whether CAST makes values of type to of those of type
+// from: numbers and booleans one of another, anything TEXT and TEXT
+// anything, and dates and times what functions take them as.
+func castable(from, to record.ColumnType) bool {
+	number := func(t record.ColumnType) bool { return isNumeric(t) || t == record.ColBool }
+	return from == to || from == record.ColText || to == record.ColText ||
+		number(from) && number(to) || convertsTemporal(from, to)
+}
+
+// castValue is v as a value of the column to, of a type castable allows:
+// a FLOAT made an INT is rounded half away from zero, as a DECIMAL is, and
+// a number that does not fit the type is record.ErrNumericOverflow.
+func castValue(to record.Column, v any) (any, error) {
+	if b, ok := v.(bool); ok && to.Type != record.ColBool && to.Type != record.ColText {
+		v = int64(0)
+		if b {
+			v = int64(1)
+		}
+	}
+	switch to.Type {
+	case record.ColText:
+		return Text(v), nil
+	case record.ColInt64:
+		return castInt(v)
+	case record.ColFloat64:
+		if s, ok := v.(string); ok {
+			f, err := strconv.ParseFloat(strings.TrimSpace(s), 64)
+			if err != nil {
+				return nil, fmt.Errorf("%q is not a FLOAT", s)
+			}
+			return f, nil
+		}
+		return toFloat(v), nil
+	case record.ColDecimal:
+		return record.DecimalValue(to, v)
+	case record.ColBool:
+		if s, ok := v.(string); ok {
+			b, err := strconv.ParseBool(strings.TrimSpace(s))
+			if err != nil {
+				return nil, fmt.Errorf("%q is not a BOOL", s)
+			}
+			return b, nil
+		}
+		if b, ok := v.(bool); ok {
+			return b, nil
+		}
+		return toFloat(v) != 0, nil
+	case record.ColJSON:
+		return NormalizeJSON(v.(string))
+	}
+	return toTemporal(to.Type, v)
+}
+
+func castInt(v any) (any, error) {
+	switch x := v.(type) {
+	case int64:
+		return x, nil
+	case record.Decimal:
+		return x.Int64(), nil
+	case string:
+		n, err := strconv.ParseInt(strings.TrimSpace(x), 10, 64)
+		if err != nil {
+			return nil, fmt.Errorf("%q is not an INT", x)
+		}
+		return n, nil
+	}
+	f := math.Round(toFloat(v))
+	if math.IsNaN(f) || f < math.MinInt64 || f >= math.MaxInt64 {
+		return nil, fmt.Errorf("%w: %v does not fit INT", record.ErrNumericOverflow, v)
+	}
+	return int64(f), nil
+}
@@ -0,0 +1,32 @@
+package expr
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func TestSQLType(t *testing.T) {
+	for name, want := range map[string]record.ColumnType{
+		"INT": record.ColInt64, "integer": record.ColInt64, "BIGINT": record.ColInt64,
+		"FLOAT": record.ColFloat64, "double": record.ColFloat64, "REAL": record.ColFloat64,
+		"TeXt": record.ColText, "BOOL": record.ColBool, "boolean": record.ColBool,
+		"DATETIME": record.ColTimestamp,
+	} {
+		got, err := SQLType(name, 0, 0)
+		require.NoError(t, err, name)
+		require.Equal(t, record.Column{Type: want}, got, name)
+	}
+
+	got, err := SQLType("numeric", 12, 4)
+	require.NoError(t, err)
+	require.Equal(t, record.Column{Type: record.ColDecimal, Precision: 12, Scale: 4}, got)
+	got, err = SQLType("DECIMAL", 0, 0)
+	require.NoError(t, err)
+	require.Equal(t, record.Column{Type: record.ColDecimal, Precision: 10}, got)
+
+	_, err = SQLType("MONEY", 0, 0)
+	require.Error(t, err)
+}
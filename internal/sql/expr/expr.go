@@ -0,0 +1,1054 @@
+// Package expr evaluates the expressions of SQL statements: parser.Expr
+// trees bound to the columns of a row, with their types checked.
+package expr
+
+import (
+	"errors"
+	"fmt"
+	"math"
+	"strconv"
+	"strings"
+	"unicode/utf8"
+
+	"github.com/tuannm99/novasql/internal/fulltext"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/parser"
+)
+
+var (
+	ErrDivisionByZero = errors.New("expr: division by zero")
+	ErrBadEscape      = errors.New("expr: ESCAPE needs one character")
+)
+
+type kind uint8
+
+const (
+	kindConst kind = iota
+	kindColumn
+	kindUnary
+	kindBinary
+	kindCall
+	kindCase
+)
+
+// Expr is an expression bound to the columns of a schema: Eval computes it
+// for a row of that schema. Its values are int64, float64, string, bool,
+// record.Decimal, Date, Time or Timestamp, or nil for NULL, and all of them
+// are of type Type.
+type Expr struct {
+	kind kind
+	op   string // kindUnary, kindBinary: the operator
+	typ  record.ColumnType
+	null bool   // an untyped NULL, or an expression of them only
+	text string // the expression as SQL
+
+	value   any   // kindConst
+	pos     int   // kindColumn: the position in the row
+	fn      *Func // kindCall
+	args    []*Expr
+	operand *Expr // kindCase: the operand, if any; args are WHEN, THEN pairs
+	els     *Expr // kindCase: the ELSE, if any
+
+	// coll is the collation of the text of e, a column's or one COLLATE
+	// names (collated), or the one a comparison or LIKE uses; nil for none.
+	coll     *Collation
+	collated bool
+}
+
+// Bind binds e to the columns of schema, which resolve finds by name, and
+// checks the types of its operands. Aggregates are columns of schema too,
+// named by parser.Aggregate.Name. Calls are of the built-in functions or
+// those of funcs, which may be nil.
+func Bind(
+	e parser.Expr, schema record.Schema, resolve func(name string) (int, error), funcs *Registry,
+) (*Expr, error) {
+	b := binder{schema: schema, resolve: resolve, funcs: funcs}
+	x, err := b.bind(e)
+	if err == nil && x.typ == colInterval && !x.null {
+		return nil, fmt.Errorf("expr: an interval can only be added to a date or a time: %s", x)
+	}
+	return x, err
+}
+
+// colInterval is the type of record.Interval values, which no column has.
+const colInterval record.ColumnType = 255
+
+type binder struct {
+	schema  record.Schema
+	resolve func(name string) (int, error)
+	funcs   *Registry
+}
+
+func (b *binder) bind(e parser.Expr) (*Expr, error) {
+	switch x := e.(type) {
+	case *parser.LiteralExpr:
+		return Const(x.Value)
+	case *parser.ParamExpr:
+		return nil, fmt.Errorf("expr: placeholders are not supported in expressions")
+	case *parser.ColumnExpr:
+		pos, err := b.resolve(x.Name)
+		if err != nil {
+			return nil, err
+		}
+		col := b.schema.Cols[pos]
+		typ := col.Type
+		if typ == record.ColInt32 {
+			typ = record.ColInt64 // read as int64, see Eval
+		}
+		out := &Expr{kind: kindColumn, pos: pos, typ: typ, text: col.Name}
+		if col.Collation != "" {
+			coll, ok := LookupCollation(col.Collation)
+			if !ok {
+				return nil, fmt.Errorf("expr: unknown collation %s of column %s", col.Collation, col.Name)
+			}
+			out.coll = coll
+		}
+		return out, nil
+	case *parser.CollateExpr:
+		return b.collate(x)
+	case *parser.UnaryExpr:
+		return b.unary(x)
+	case *parser.BinaryExpr:
+		return b.binary(x)
+	case *parser.FuncExpr:
+		return b.call(x)
+	case *parser.CaseExpr:
+		return b.caseExpr(x)
+	case *parser.CastExpr:
+		return b.cast(x)
+	default:
+		return nil, fmt.Errorf("expr: unsupported expression %T", e)
+	}
+}
+
+// Const is the expression of the value v.
+func Const(v any) (*Expr, error) {
+	e := &Expr{kind: kindConst, value: v, text: sqlLiteral(v)}
+	switch x := v.(type) {
+	case nil:
+		e.null = true
+	case int64:
+		e.typ = record.ColInt64
+	case int:
+		e.typ, e.value = record.ColInt64, int64(x)
+	case float64:
+		e.typ = record.ColFloat64
+	case string:
+		e.typ = record.ColText
+	case bool:
+		e.typ = record.ColBool
+	case record.Decimal:
+		e.typ = record.ColDecimal
+	case record.Date:
+		e.typ = record.ColDate
+	case record.Time:
+		e.typ = record.ColTime
+	case record.Timestamp:
+		e.typ = record.ColTimestamp
+	case record.Interval:
+		e.typ = colInterval
+	default:
+		return nil, fmt.Errorf("expr: unsupported value %T", v)
+	}
+	return e, nil
+}
+
+// collate binds "x COLLATE name": x, with the collation. The collation is
+// a built-in one or one of b.funcs.
+func (b *binder) collate(x *parser.CollateExpr) (*Expr, error) {
+	arg, err := b.bind(x.X)
+	if err != nil {
+		return nil, err
+	}
+	if !arg.is(record.ColText) {
+		return nil, fmt.Errorf("expr: COLLATE needs text, %s is not", arg)
+	}
+	coll, ok := b.funcs.Collation(x.Collation)
+	if !ok {
+		return nil, fmt.Errorf("expr: unknown collation %s", x.Collation)
+	}
+	e := *arg
+	e.coll, e.collated = coll, true
+	e.text = arg.paren() + " COLLATE " + coll.Name
+	return &e, nil
+}
+
+func (b *binder) unary(x *parser.UnaryExpr) (*Expr, error) {
+	arg, err := b.bind(x.X)
+	if err != nil {
+		return nil, err
+	}
+	e := &Expr{kind: kindUnary, op: x.Op, args: []*Expr{arg}, typ: record.ColBool}
+	switch x.Op {
+	case "-":
+		if !arg.null && !isNumeric(arg.typ) && arg.typ != colInterval {
+			return nil, fmt.Errorf("expr: cannot negate %s: not a number", arg)
+		}
+		e.typ, e.null = arg.typ, arg.null
+		e.text = "-" + arg.paren()
+		if strings.HasPrefix(arg.text, "-") {
+			e.text = "-(" + arg.text + ")" // not "--", a comment
+		}
+	case "NOT":
+		if !arg.is(record.ColBool) {
+			return nil, fmt.Errorf("expr: NOT needs a boolean, %s is not", arg)
+		}
+		e.null = arg.null
+		e.text = "NOT " + arg.paren()
+	case "IS NULL", "IS NOT NULL":
+		e.text = arg.paren() + " " + x.Op
+	default:
+		return nil, fmt.Errorf("expr: unsupported operator %s", x.Op)
+	}
+	return e, nil
+}
+
+func (b *binder) binary(x *parser.BinaryExpr) (*Expr, error) {
+	l, err := b.bind(x.Left)
+	if err != nil {
+		return nil, err
+	}
+	r, err := b.bind(x.Right)
+	if err != nil {
+		return nil, err
+	}
+	if _, ok := mirrorOps[x.Op]; ok || x.Op == "<>" {
+		if l, err = temporalConst(l, r); err != nil {
+			return nil, err
+		}
+		if r, err = temporalConst(r, l); err != nil {
+			return nil, err
+		}
+	}
+	switch x.Op {
+	case "=", "<>", "<", "<=", ">", ">=", "+", "-", "*", "/", "%":
+		l, r = decimalConst(l, r), decimalConst(r, l)
+	}
+	e := &Expr{
+		kind: kindBinary,
+		op:   x.Op,
+		args: []*Expr{l, r},
+		typ:  record.ColBool,
+		null: l.null && r.null,
+		text: l.paren() + " " + x.Op + " " + r.paren(),
+	}
+	if x.Escape != nil {
+		esc, err := b.bind(x.Escape)
+		if err != nil {
+			return nil, err
+		}
+		if !esc.is(record.ColText) {
+			return nil, fmt.Errorf("expr: ESCAPE needs text, %s is not", esc)
+		}
+		e.args = append(e.args, esc)
+		e.text += " ESCAPE " + esc.paren()
+	}
+	switch x.Op {
+	case "+", "-", "*", "/", "%":
+		if t, ok := temporalArith(x.Op, l, r); ok {
+			e.typ = t
+			break
+		}
+		if !l.null && !isNumeric(l.typ) || !r.null && !isNumeric(r.typ) {
+			return nil, fmt.Errorf("expr: %s needs numbers: %s", x.Op, e)
+		}
+		e.typ = record.ColInt64
+		for _, a := range e.args {
+			if !a.null {
+				e.typ = widerNumeric(e.typ, a.typ)
+			}
+		}
+	case "||":
+		e.typ = record.ColText
+	case "->", "->>":
+		if !l.is(record.ColJSON) && !l.is(record.ColText) || !r.is(record.ColText) && !r.is(record.ColInt64) {
+			return nil, fmt.Errorf("expr: %s needs JSON and a path, a key or an index: %s", x.Op, e)
+		}
+		e.typ = record.ColJSON
+		if x.Op == "->>" {
+			e.typ = record.ColText
+		}
+	case "=", "<>", "<", "<=", ">", ">=":
+		if !comparableTypes(l, r) {
+			return nil, fmt.Errorf("expr: cannot compare %s with %s: types differ", l, r)
+		}
+		e.coll = collationOf(l, r)
+	case "LIKE", "NOT LIKE", "GLOB", "NOT GLOB", "MATCH":
+		if !l.is(record.ColText) || !r.is(record.ColText) {
+			return nil, fmt.Errorf("expr: %s needs text: %s", x.Op, e)
+		}
+		if strings.HasSuffix(x.Op, "LIKE") {
+			e.coll = collationOf(l, r)
+		}
+	case "AND", "OR":
+		if !l.is(record.ColBool) || !r.is(record.ColBool) {
+			return nil, fmt.Errorf("expr: %s needs booleans: %s", x.Op, e)
+		}
+	default:
+		return nil, fmt.Errorf("expr: unsupported operator %s", x.Op)
+	}
+	return e, nil
+}
+
+func (b *binder) call(x *parser.FuncExpr) (*Expr, error) {
+	fn, ok := b.funcs.Func(x.Name)
+	if !ok {
+		return nil, fmt.Errorf("expr: unknown function %s", x.Name)
+	}
+	e := &Expr{kind: kindCall, fn: fn, typ: fn.Result}
+	texts := make([]string, 0, len(x.Args))
+	for _, a := range x.Args {
+		arg, err := b.bind(a)
+		if err != nil {
+			return nil, err
+		}
+		e.args = append(e.args, arg)
+		texts = append(texts, arg.text)
+	}
+	e.text = fn.Name + "(" + strings.Join(texts, ", ") + ")"
+	if err := fn.check(e.args); err != nil {
+		return nil, err
+	}
+	return e, nil
+}
+
+func (b *binder) caseExpr(x *parser.CaseExpr) (*Expr, error) {
+	e := &Expr{kind: kindCase, null: true}
+	var text strings.Builder
+	text.WriteString("CASE")
+	var err error
+	if x.Operand != nil {
+		if e.operand, err = b.bind(x.Operand); err != nil {
+			return nil, err
+		}
+		text.WriteString(" " + e.operand.text)
+	}
+	var results []*Expr
+	for _, w := range x.Whens {
+		when, err := b.bind(w.When)
+		if err != nil {
+			return nil, err
+		}
+		if e.operand != nil {
+			if when, err = temporalConst(when, e.operand); err != nil {
+				return nil, err
+			}
+			when = decimalConst(when, e.operand)
+		}
+		switch {
+		case e.operand != nil && !comparableTypes(e.operand, when):
+			return nil, fmt.Errorf("expr: cannot compare %s with %s: types differ", e.operand, when)
+		case e.operand == nil && !when.is(record.ColBool):
+			return nil, fmt.Errorf("expr: WHEN needs a boolean, %s is not", when)
+		}
+		then, err := b.bind(w.Then)
+		if err != nil {
+			return nil, err
+		}
+		e.args = append(e.args, when, then)
+		results = append(results, then)
+		text.WriteString(" WHEN " + when.text + " THEN " + then.text)
+	}
+	if x.Else != nil {
+		if e.els, err = b.bind(x.Else); err != nil {
+			return nil, err
+		}
+		results = append(results, e.els)
+		text.WriteString(" ELSE " + e.els.text)
+	}
+	text.WriteString(" END")
+	e.text = text.String()
+
+	// The result has the type of the branches, numbers the widest type of
+	// them: FLOAT, then DECIMAL.
+	for _, r := range results {
+		switch {
+		case r.null:
+		case e.null:
+			e.typ, e.null = r.typ, false
+		case r.typ == e.typ:
+		case isNumeric(r.typ) && isNumeric(e.typ):
+			e.typ = widerNumeric(e.typ, r.typ)
+		default:
+			return nil, fmt.Errorf("expr: CASE results have different types: %s", e)
+		}
+	}
+	return e, nil
+}
+
+// Type is the type of the values of e. An expression that can only be NULL
+// has none, and reads as TEXT.
+func (e *Expr) Type() record.ColumnType {
+	if e.null {
+		return record.ColText
+	}
+	return e.typ
+}
+
+// String is e as SQL, with the column names of the schema it is bound to.
+// Expressions that are the same have the same String.
+func (e *Expr) String() string { return e.text }
+
+// Volatile reports whether e may get another value for the same row, as
+// NOW() does.
+func (e *Expr) Volatile() bool {
+	if e.kind == kindCall && e.fn.Volatile {
+		return true
+	}
+	for _, a := range e.children() {
+		if a.Volatile() {
+			return true
+		}
+	}
+	return false
+}
+
+// Columns returns the positions of the columns e reads.
+func (e *Expr) Columns() []int {
+	if e.kind == kindColumn {
+		return []int{e.pos}
+	}
+	var out []int
+	for _, a := range e.children() {
+		out = append(out, a.Columns()...)
+	}
+	return out
+}
+
+// mirrorOps flips a comparison for swapped operands: a < b is b > a.
+var mirrorOps = map[string]string{"=": "=", "<": ">", "<=": ">=", ">": "<", ">=": "<="}
+
+// Comparison returns left, op and v when e is "<left> <op> <v>", or "<v>
+// <op'> <left>", for op one of "=", "<", "<=", ">", ">=" and v a non-NULL
+// constant of the type of left, compared by the collation of left: a
+// condition an index over left can find the rows of.
+func (e *Expr) Comparison() (left *Expr, op string, v any, ok bool) {
+	op, ok = mirrorOps[e.op]
+	if e.kind != kindBinary || !ok {
+		return nil, "", nil, false
+	}
+	l, r := e.args[0], e.args[1]
+	if l.kind == kindConst && r.kind != kindConst {
+		l, r = r, l
+	} else {
+		op = e.op
+	}
+	if r.kind != kindConst || r.null || l.null || r.typ != l.typ || !sameCollation(e.coll, l.Collation()) {
+		return nil, "", nil, false
+	}
+	return l, op, r.value, true
+}
+
+// Collation is the collation the text of e is compared and sorted by: that
+// of its column, or one COLLATE names; nil for bytewise.
+func (e *Expr) Collation() *Collation {
+	if e.kind == kindColumn || e.collated {
+		return e.coll
+	}
+	return nil
+}
+
+// FullTextMatch returns column and query when e is "<column> MATCH <query>",
+// with query a TEXT constant: a condition a full-text index on column can
+// find the rows of.
+func (e *Expr) FullTextMatch() (column *Expr, query string, ok bool) {
+	if e.kind != kindBinary || e.op != "MATCH" || e.args[0].kind != kindColumn {
+		return nil, "", false
+	}
+	query, ok = e.args[1].value.(string)
+	if e.args[1].kind != kindConst || !ok {
+		return nil, "", false
+	}
+	return e.args[0], query, true
+}
+
+// Conjuncts splits e into the conditions it ANDs together.
+func (e *Expr) Conjuncts() []*Expr {
+	if e.kind != kindBinary || e.op != "AND" {
+		return []*Expr{e}
+	}
+	return append(e.args[0].Conjuncts(), e.args[1].Conjuncts()...)
+}
+
+func (e *Expr) children() []*Expr {
+	out := make([]*Expr, 0, len(e.args)+2)
+	if e.operand != nil {
+		out = append(out, e.operand)
+	}
+	out = append(out, e.args...)
+	if e.els != nil {
+		out = append(out, e.els)
+	}
+	return out
+}
+
+// is reports whether e has type t, or is NULL and could.
+func (e *Expr) is(t record.ColumnType) bool { return e.null || e.typ == t }
+
+// paren is the text of e as an operand: parenthesized when it is an
+// operation itself.
+func (e *Expr) paren() string {
+	if e.kind == kindBinary || e.kind == kindUnary && e.op != "-" {
+		return "(" + e.text + ")"
+	}
+	return e.text
+}
+
+func isNumeric(t record.ColumnType) bool {
+	return t == record.ColInt64 || t == record.ColFloat64 || t == record.ColDecimal
+}
+
+// widerNumeric is the type of arithmetic on numbers of types a and b: FLOAT
+// if either is, else DECIMAL if either is, else INT.
+func widerNumeric(a, b record.ColumnType) record.ColumnType {
+	switch {
+	case a == record.ColFloat64 || b == record.ColFloat64:
+		return record.ColFloat64
+	case a == record.ColDecimal || b == record.ColDecimal:
+		return record.ColDecimal
+	}
+	return record.ColInt64
+}
+
+// isTemporal reports whether t is DATE, TIME or TIMESTAMP.
+func isTemporal(t record.ColumnType) bool {
+	return t == record.ColDate || t == record.ColTime || t == record.ColTimestamp
+}
+
+// comparableTypes reports whether a and b may be compared: both numbers, a
+// date and a timestamp, or of the same type. Intervals cannot be.
+func comparableTypes(a, b *Expr) bool {
+	if a.typ == colInterval && !a.null || b.typ == colInterval && !b.null {
+		return false
+	}
+	return a.null || b.null || a.typ == b.typ || isNumeric(a.typ) && isNumeric(b.typ) ||
+		a.typ == record.ColJSON || b.typ == record.ColJSON ||
+		isDateLike(a.typ) && isDateLike(b.typ)
+}
+
+// isDateLike reports whether t is DATE or TIMESTAMP: a date is a timestamp
+// too, of its start.
+func isDateLike(t record.ColumnType) bool {
+	return t == record.ColDate || t == record.ColTimestamp
+}
+
+// temporalConst is c, when it is a TEXT constant compared with t, a date or a
+// time, as a constant of the type of t: "d > '2024-01-01'" compares dates.
+func temporalConst(c, t *Expr) (*Expr, error) {
+	if c.kind != kindConst || c.null || c.typ != record.ColText || t.null || !isTemporal(t.typ) {
+		return c, nil
+	}
+	v, err := record.TemporalValue(t.typ, c.value)
+	if err != nil {
+		return nil, fmt.Errorf("expr: %w", err)
+	}
+	return Const(v)
+}
+
+// sameKind reports whether a and b are both numbers or of the same type.
+func sameKind(a, b any) bool {
+	switch a.(type) {
+	case int64, float64, record.Decimal:
+		switch b.(type) {
+		case int64, float64, record.Decimal:
+			return true
+		}
+		return false
+	}
+	return fmt.Sprintf("%T", a) == fmt.Sprintf("%T", b)
+}
+
+// Eval computes e for row. A NULL operand makes most operations NULL, as in
+// SQL: "NULL = NULL" is NULL, "NULL AND FALSE" is FALSE.
+func (e *Expr) Eval(row []any) (any, error) {
+	switch e.kind {
+	case kindConst:
+		return e.value, nil
+	case kindColumn:
+		if v, ok := row[e.pos].(int32); ok {
+			return int64(v), nil
+		}
+		return row[e.pos], nil
+	case kindUnary:
+		return e.evalUnary(row)
+	case kindBinary:
+		return e.evalBinary(row)
+	case kindCall:
+		return e.evalCall(row)
+	default:
+		return e.evalCase(row)
+	}
+}
+
+// Match computes the condition e for row: true only when it is TRUE, not
+// FALSE or NULL.
+func (e *Expr) Match(row []any) (bool, error) {
+	v, err := e.Eval(row)
+	if err != nil {
+		return false, err
+	}
+	b, _ := v.(bool)
+	return b, nil
+}
+
+func (e *Expr) evalUnary(row []any) (any, error) {
+	v, err := e.args[0].Eval(row)
+	if err != nil {
+		return nil, err
+	}
+	switch e.op {
+	case "IS NULL":
+		return v == nil, nil
+	case "IS NOT NULL":
+		return v != nil, nil
+	}
+	switch x := v.(type) {
+	case int64:
+		return -x, nil
+	case float64:
+		return -x, nil
+	case record.Decimal:
+		return x.Neg(), nil
+	case bool:
+		return !x, nil
+	case record.Interval:
+		return x.Neg(), nil
+	}
+	return nil, nil
+}
+
+func (e *Expr) evalBinary(row []any) (any, error) {
+	l, err := e.args[0].Eval(row)
+	if err != nil {
+		return nil, err
+	}
+	// AND and OR do not need the right operand when the left one decides.
+	switch {
+	case e.op == "AND" && l == false:
+		return false, nil
+	case e.op == "OR" && l == true:
+		return true, nil
+	}
+	r, err := e.args[1].Eval(row)
+	if err != nil {
+		return nil, err
+	}
+	switch e.op {
+	case "AND":
+		if r == false {
+			return false, nil
+		}
+		if l == nil || r == nil {
+			return nil, nil
+		}
+		return true, nil
+	case "OR":
+		if r == true {
+			return true, nil
+		}
+		if l == nil || r == nil {
+			return nil, nil
+		}
+		return false, nil
+	}
+	if l == nil || r == nil {
+		return nil, nil
+	}
+
+	switch e.op {
+	case "+", "-", "*", "/", "%":
+		return arith(e.op, l, r)
+	case "||":
+		return Text(l) + Text(r), nil
+	case "->", "->>":
+		return jsonArrow(l, r, e.op == "->>")
+	case "LIKE", "NOT LIKE":
+		return e.like(l.(string), r.(string), row)
+	case "GLOB":
+		return Glob(l.(string), r.(string)), nil
+	case "NOT GLOB":
+		return !Glob(l.(string), r.(string)), nil
+	case "MATCH":
+		return fulltext.Match(l.(string), r.(string)), nil
+	}
+	// A JSON operand compares as its SQL value, and not at all with a value
+	// of another kind.
+	if e.args[0].typ == record.ColJSON || e.args[1].typ == record.ColJSON {
+		if e.args[0].typ == record.ColJSON {
+			l = jsonScalar(l.(string))
+		}
+		if e.args[1].typ == record.ColJSON {
+			r = jsonScalar(r.(string))
+		}
+		if l == nil || r == nil || !sameKind(l, r) {
+			return nil, nil
+		}
+	}
+	c := e.coll.compare(l, r)
+	switch e.op {
+	case "=":
+		return c == 0, nil
+	case "<>":
+		return c != 0, nil
+	case "<":
+		return c < 0, nil
+	case "<=":
+		return c <= 0, nil
+	case ">":
+		return c > 0, nil
+	default:
+		return c >= 0, nil
+	}
+}
+
+// like computes e, a LIKE or a NOT LIKE, for s and pattern: with the ESCAPE
+// character, if e has one, and ignoring case under NOCASE.
+func (e *Expr) like(s, pattern string, row []any) (any, error) {
+	var escape rune
+	if len(e.args) > 2 {
+		v, err := e.args[2].Eval(row)
+		if v == nil || err != nil {
+			return nil, err
+		}
+		esc := e.coll.fold(v.(string))
+		if utf8.RuneCountInString(esc) != 1 {
+			return nil, fmt.Errorf("%w: %s", ErrBadEscape, sqlLiteral(v))
+		}
+		escape, _ = utf8.DecodeRuneInString(esc)
+	}
+	return Like(e.coll.fold(s), e.coll.fold(pattern), escape) == (e.op == "LIKE"), nil
+}
+
+func (e *Expr) evalCall(row []any) (any, error) {
+	args := make([]any, len(e.args))
+	for i, a := range e.args {
+		v, err := a.Eval(row)
+		if err != nil {
+			return nil, err
+		}
+		if v == nil && !e.fn.Nulls {
+			return nil, nil
+		}
+		if args[i], err = e.fn.convert(i, v); err != nil {
+			return nil, fmt.Errorf("expr: %s: %w", e.fn.Name, err)
+		}
+	}
+	v, err := e.fn.Call(args)
+	if err != nil {
+		return nil, fmt.Errorf("expr: %s: %w", e.fn.Name, err)
+	}
+	return resultValue(e.fn.Name, e.fn.Result, v)
+}
+
+func (e *Expr) evalCase(row []any) (any, error) {
+	var operand any
+	if e.operand != nil {
+		var err error
+		if operand, err = e.operand.Eval(row); err != nil {
+			return nil, err
+		}
+	}
+	result := e.els
+	for i := 0; i < len(e.args); i += 2 {
+		w, err := e.args[i].Eval(row)
+		if err != nil {
+			return nil, err
+		}
+		if e.operand != nil {
+			w = operand != nil && w != nil && collationOf(e.operand, e.args[i]).compare(operand, w) == 0
+		}
+		if w == true {
+			result = e.args[i+1]
+			break
+		}
+	}
+	if result == nil {
+		return nil, nil
+	}
+	v, err := result.Eval(row)
+	return widen(e.typ, v), err
+}
+
+// widen is v as a value of t, when it is a number and t a wider numeric type.
+func widen(t record.ColumnType, v any) any {
+	switch x := v.(type) {
+	case int64:
+		switch t {
+		case record.ColFloat64:
+			return float64(x)
+		case record.ColDecimal:
+			return record.Decimal{Coef: x}
+		}
+	case record.Decimal:
+		if t == record.ColFloat64 {
+			return x.Float64()
+		}
+	}
+	return v
+}
+
+// arith applies arithmetic operator op to two numbers: int64 when both are,
+// float64 when either is, and a record.Decimal otherwise. Dates and times
+// are left to dateArith.
+func arith(op string, l, r any) (any, error) {
+	if v, ok := dateArith(op, l, r); ok {
+		return v, nil
+	}
+	x, xok := l.(int64)
+	y, yok := r.(int64)
+	if xok && yok {
+		switch op {
+		case "+":
+			return x + y, nil
+		case "-":
+			return x - y, nil
+		case "*":
+			return x * y, nil
+		}
+		if y == 0 {
+			return nil, ErrDivisionByZero
+		}
+		if op == "/" {
+			return x / y, nil
+		}
+		return x % y, nil
+	}
+	_, lf := l.(float64)
+	_, rf := r.(float64)
+	if !lf && !rf {
+		return decimalArith(op, toDecimal(l), toDecimal(r))
+	}
+	f, g := toFloat(l), toFloat(r)
+	switch op {
+	case "+":
+		return f + g, nil
+	case "-":
+		return f - g, nil
+	case "*":
+		return f * g, nil
+	}
+	if g == 0 {
+		return nil, ErrDivisionByZero
+	}
+	if op == "/" {
+		return f / g, nil
+	}
+	return math.Mod(f, g), nil
+}
+
+func toFloat(v any) float64 {
+	switch x := v.(type) {
+	case int64:
+		return float64(x)
+	case record.Decimal:
+		return x.Float64()
+	}
+	f, _ := v.(float64)
+	return f
+}
+
+// Compare orders two non-NULL values of comparable types: numbers by value,
+// whatever their type, exactly when neither is a float, strings bytewise,
+// FALSE before TRUE, and dates and times in time, a date as the start of its
+// day.
+func Compare(a, b any) int {
+	if d, ok := a.(record.Date); ok {
+		if _, ok := b.(record.Timestamp); ok {
+			a = d.Timestamp()
+		}
+	}
+	if d, ok := b.(record.Date); ok {
+		if _, ok := a.(record.Timestamp); ok {
+			b = d.Timestamp()
+		}
+	}
+	switch x := a.(type) {
+	case int64:
+		switch y := b.(type) {
+		case int64:
+			return cmpOrdered(x, y)
+		case record.Decimal:
+			return record.Decimal{Coef: x}.Cmp(y)
+		}
+		return cmpOrdered(float64(x), toFloat(b))
+	case float64:
+		return cmpOrdered(x, toFloat(b))
+	case record.Decimal:
+		switch y := b.(type) {
+		case int64:
+			return x.Cmp(record.Decimal{Coef: y})
+		case record.Decimal:
+			return x.Cmp(y)
+		}
+		return cmpOrdered(x.Float64(), toFloat(b))
+	case string:
+		y, _ := b.(string)
+		return strings.Compare(x, y)
+	case bool:
+		y, _ := b.(bool)
+		switch {
+		case x == y:
+			return 0
+		case !x:
+			return -1
+		}
+		return 1
+	case record.Date:
+		y, _ := b.(record.Date)
+		return cmpOrdered(int64(x), int64(y))
+	case record.Time:
+		y, _ := b.(record.Time)
+		return cmpOrdered(int64(x), int64(y))
+	case record.Timestamp:
+		y, _ := b.(record.Timestamp)
+		return cmpOrdered(int64(x), int64(y))
+	}
+	return 0
+}
+
+func cmpOrdered[T int64 | float64](a, b T) int {
+	switch {
+	case a < b:
+		return -1
+	case a > b:
+		return 1
+	}
+	return 0
+}
+
+// Text is v as "||" and CONCAT join it: TRUE is "true", numbers are written
+// as SQL does, dates and times as "2006-01-02 15:04:05".
+func Text(v any) string {
+	switch x := v.(type) {
+	case string:
+		return x
+	case int64:
+		return strconv.FormatInt(x, 10)
+	case float64:
+		return strconv.FormatFloat(x, 'g', -1, 64)
+	case bool:
+		return strconv.FormatBool(x)
+	case record.Decimal, record.Date, record.Time, record.Timestamp, record.Interval:
+		return fmt.Sprint(x)
+	}
+	return ""
+}
+
+// Like reports whether s matches the LIKE pattern, where "%" matches any
+// run of characters and "_" any one character. Unless escape is 0, it makes
+// the character after it match only itself. Case matters.
+func Like(s, pattern string, escape rune) bool {
+	sr, pr := []rune(s), []rune(pattern)
+	// match[j]: pattern[:i] matches s[:j], for the i pattern runes so far.
+	match := make([]bool, len(sr)+1)
+	match[0] = true
+	for i := 0; i < len(pr); i++ {
+		p, wild := pr[i], true
+		if escape != 0 && p == escape && i+1 < len(pr) {
+			i++
+			p, wild = pr[i], false
+		}
+		next := make([]bool, len(sr)+1)
+		if wild && p == '%' {
+			seen := false
+			for j := range match {
+				seen = seen || match[j]
+				next[j] = seen
+			}
+		} else {
+			for j := 1; j <= len(sr); j++ {
+				next[j] = match[j-1] && (wild && p == '_' || sr[j-1] == p)
+			}
+		}
+		match = next
+	}
+	return match[len(sr)]
+}
+
+// Glob reports whether s matches the GLOB pattern, where "*" matches any
+// run of characters, "?" any one character, and "[...]" one character of
+// those listed, such as "[abc]" or "[a-z]", or of those not listed after
+// "[^". Case matters, whatever the collation.
+func Glob(s, pattern string) bool {
+	sr, pr := []rune(s), []rune(pattern)
+	// After a "*" at star, the rest of the pattern is tried from ever later
+	// runes of s, the one at from next.
+	i, j, star, from := 0, 0, -1, 0
+	for i < len(sr) {
+		if j < len(pr) {
+			switch n, ok := globClass(pr[j:], sr[i]); {
+			case pr[j] == '*':
+				star, from = j, i
+				j++
+				continue
+			case n > 0:
+				if ok {
+					i, j = i+1, j+n
+					continue
+				}
+			case pr[j] == '?' || pr[j] == sr[i]:
+				i, j = i+1, j+1
+				continue
+			}
+		}
+		if star < 0 {
+			return false
+		}
+		from++
+		i, j = from, star+1
+	}
+	for j < len(pr) && pr[j] == '*' {
+		j++
+	}
+	return j == len(pr)
+}
+
+// globClass matches c against the "[...]" class p starts with, returning
+// the runes the class takes up, 0 when p does not start with one.
+func globClass(p []rune, c rune) (n int, ok bool) {
+	if len(p) == 0 || p[0] != '[' {
+		return 0, false
+	}
+	i := 1
+	negate := i < len(p) && p[i] == '^'
+	if negate {
+		i++
+	}
+	for first := i; i < len(p); i++ {
+		if p[i] == ']' && i > first {
+			return i + 1, ok != negate
+		}
+		lo, hi := p[i], p[i]
+		if i+2 < len(p) && p[i+1] == '-' && p[i+2] != ']' {
+			hi = p[i+2]
+			i += 2
+		}
+		ok = ok || lo <= c && c <= hi
+	}
+	return 0, false
+}
+
+// sqlLiteral writes v the way SQL spells it, as a literal of its type.
+func sqlLiteral(v any) string {
+	if s, ok := temporalLiteral(v); ok {
+		return s
+	}
+	switch x := v.(type) {
+	case nil:
+		return "NULL"
+	case string:
+		return "'" + strings.ReplaceAll(x, "'", "''") + "'"
+	case bool:
+		return strings.ToUpper(strconv.FormatBool(x))
+	case record.Decimal:
+		return "DECIMAL '" + x.String() + "'"
+	case float64:
+		s := strconv.FormatFloat(x, 'f', -1, 64)
+		if !strings.Contains(s, ".") {
+			s += ".0"
+		}
+		return s
+	default:
+		return fmt.Sprint(x)
+	}
+}
@@ -0,0 +1,187 @@
+package expr
+
+import (
+	"errors"
+	"fmt"
+	"slices"
+	"strings"
+
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+// Func is a scalar function expressions may call.
+type Func struct {
+	Name     string              // upper case
+	Args     []record.ColumnType // of the arguments; see check for the types each takes
+	Optional int                 // how many of the last Args may be left out
+	Variadic bool                // the last of Args may repeat, or be left out
+	AnyType  bool                // the arguments may be of any type (Args is ignored)
+	Result   record.ColumnType
+
+	// Nulls: Call gets NULL arguments too. Otherwise a call with one is NULL
+	// without Call being made.
+	Nulls bool
+	// Volatile: the result may change between calls with the same arguments,
+	// so an index cannot be built over it.
+	Volatile bool
+
+	Call func(args []any) (any, error)
+}
+
+var funcs = map[string]*Func{}
+
+func init() {
+	for _, f := range slices.Concat(builtins, jsonFuncs, temporalFuncs) {
+		funcs[f.Name] = f
+	}
+}
+
+// LookupFunc returns the function called name (in any case).
+func LookupFunc(name string) (*Func, bool) {
+	f, ok := funcs[strings.ToUpper(name)]
+	return f, ok
+}
+
+// check checks the arguments of a call of f. A FLOAT argument takes INTs
+// and DECIMALs too, a JSON one TEXT, and a date or a time TEXT or another one
+// convert makes of it.
+func (f *Func) check(args []*Expr) error {
+	n := len(f.Args)
+	switch {
+	case f.AnyType && f.Variadic:
+	case f.Variadic && len(args) >= n-1:
+	case len(args) <= n && len(args) >= n-f.Optional:
+	default:
+		return fmt.Errorf("expr: %s takes %s, not %d", f.Name, f.arity(), len(args))
+	}
+	if f.AnyType {
+		return nil
+	}
+	for i, a := range args {
+		want := f.Args[min(i, n-1)]
+		if a.null || a.typ == want || want == record.ColFloat64 && isNumeric(a.typ) ||
+			want == record.ColJSON && a.typ == record.ColText || convertsTemporal(a.typ, want) {
+			continue
+		}
+		return fmt.Errorf("expr: argument %d of %s must be %s: %s", i+1, f.Name, typeName(want), a)
+	}
+	return nil
+}
+
+// arity describes how many arguments f takes.
+func (f *Func) arity() string {
+	n := len(f.Args)
+	switch {
+	case f.Variadic:
+		return fmt.Sprintf("at least %d arguments", max(n-1, 0))
+	case f.Optional > 0:
+		return fmt.Sprintf("%d to %d arguments", n-f.Optional, n)
+	case n == 1:
+		return "1 argument"
+	default:
+		return fmt.Sprintf("%d arguments", n)
+	}
+}
+
+// convert is argument i of a call of f as Call gets it: an INT or a DECIMAL
+// for a FLOAT argument becomes a float64, and TEXT or another date or time
+// for a temporal one a value of its type.
+func (f *Func) convert(i int, v any) (any, error) {
+	if f.AnyType || len(f.Args) == 0 {
+		return v, nil
+	}
+	want := f.Args[min(i, len(f.Args)-1)]
+	switch v.(type) {
+	case int64, record.Decimal:
+		if want == record.ColFloat64 {
+			return toFloat(v), nil
+		}
+	}
+	if isTemporal(want) {
+		return toTemporal(want, v)
+	}
+	return v, nil
+}
+
+func typeName(t record.ColumnType) string {
+	switch t {
+	case record.ColInt64:
+		return "INT"
+	case record.ColFloat64:
+		return "FLOAT"
+	case record.ColDecimal:
+		return "DECIMAL"
+	case record.ColText:
+		return "TEXT"
+	case record.ColBool:
+		return "BOOL"
+	case record.ColJSON:
+		return "JSON"
+	case record.ColDate:
+		return "DATE"
+	case record.ColTime:
+		return "TIME"
+	case record.ColTimestamp:
+		return "TIMESTAMP"
+	case colInterval:
+		return "INTERVAL"
+	default:
+		return fmt.Sprintf("type %d", t)
+	}
+}
+
+// ----- built-in functions -----
+
+var errNegativeLength = errors.New("negative substring length")
+
+var textArg = []record.ColumnType{record.ColText}
+
+var builtins = []*Func{
+	{Name: "LOWER", Args: textArg, Result: record.ColText, Call: func(a []any) (any, error) {
+		return strings.ToLower(a[0].(string)), nil
+	}},
+	{Name: "UPPER", Args: textArg, Result: record.ColText, Call: func(a []any) (any, error) {
+		return strings.ToUpper(a[0].(string)), nil
+	}},
+	{Name: "LENGTH", Args: textArg, Result: record.ColInt64, Call: func(a []any) (any, error) {
+		return int64(len([]rune(a[0].(string)))), nil
+	}},
+	{
+		Name:     "SUBSTR",
+		Args:     []record.ColumnType{record.ColText, record.ColInt64, record.ColInt64},
+		Optional: 1,
+		Result:   record.ColText,
+		Call:     substr,
+	},
+	{Name: "CONCAT", AnyType: true, Variadic: true, Nulls: true, Result: record.ColText, Call: concat},
+}
+
+// substr is SUBSTR(s, start[, length]): the characters of s from start,
+// counted from 1, length of them or all the rest. As in PostgreSQL, a start
+// before 1 counts the characters before 1 against length.
+func substr(a []any) (any, error) {
+	r := []rune(a[0].(string))
+	from := a[1].(int64)
+	to := int64(len(r)) + 1
+	if len(a) == 3 {
+		n := a[2].(int64)
+		if n < 0 {
+			return nil, errNegativeLength
+		}
+		to = min(to, from+n)
+	}
+	from = max(from, 1)
+	if to <= from {
+		return "", nil
+	}
+	return string(r[from-1 : to-1]), nil
+}
+
+// concat joins the text of its arguments, skipping NULLs.
+func concat(a []any) (any, error) {
+	var b strings.Builder
+	for _, v := range a {
+		b.WriteString(Text(v))
+	}
+	return b.String(), nil
+}
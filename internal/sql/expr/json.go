@@ -0,0 +1,227 @@
+package expr
+
+import (
+	"bytes"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"strconv"
+	"strings"
+	"unicode"
+
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+// ErrBadJSON is the error of a value that should be JSON but is not.
+var ErrBadJSON = errors.New("expr: malformed JSON")
+
+// NormalizeJSON checks that s is a JSON document and returns it as a JSON
+// column stores it: without insignificant whitespace, keys in their order.
+func NormalizeJSON(s string) (string, error) {
+	var b bytes.Buffer
+	if err := json.Compact(&b, []byte(s)); err != nil {
+		return "", fmt.Errorf("%w: %v", ErrBadJSON, err)
+	}
+	return b.String(), nil
+}
+
+// jsonPath reads the right operand of -> and ->>, or the path of
+// JSON_EXTRACT: an array index, a path such as "$.a.b[0]", or else the key
+// of an object member.
+func jsonPath(p any) ([]any, error) {
+	switch x := p.(type) {
+	case int64:
+		return []any{x}, nil
+	case string:
+		if !strings.HasPrefix(x, "$") {
+			return []any{x}, nil
+		}
+		return parseJSONPath(x)
+	}
+	return nil, fmt.Errorf("JSON path must be text or an integer")
+}
+
+// parseJSONPath reads "$" followed by steps ".key", `."a key"` and "[n]".
+func parseJSONPath(path string) ([]any, error) {
+	bad := fmt.Errorf("bad JSON path %q", path)
+	var steps []any
+	rest := path[1:]
+	for rest != "" {
+		switch rest[0] {
+		case '.':
+			rest = rest[1:]
+			if strings.HasPrefix(rest, `"`) {
+				end := strings.IndexByte(rest[1:], '"')
+				if end < 0 {
+					return nil, bad
+				}
+				steps = append(steps, rest[1:end+1])
+				rest = rest[end+2:]
+				continue
+			}
+			end := strings.IndexFunc(rest, func(r rune) bool {
+				return r != '_' && !unicode.IsLetter(r) && !unicode.IsDigit(r)
+			})
+			if end < 0 {
+				end = len(rest)
+			}
+			if end == 0 {
+				return nil, bad
+			}
+			steps = append(steps, rest[:end])
+			rest = rest[end:]
+		case '[':
+			end := strings.IndexByte(rest, ']')
+			if end < 0 {
+				return nil, bad
+			}
+			n, err := strconv.ParseInt(rest[1:end], 10, 64)
+			if err != nil || n < 0 {
+				return nil, bad
+			}
+			steps = append(steps, n)
+			rest = rest[end+1:]
+		default:
+			return nil, bad
+		}
+	}
+	return steps, nil
+}
+
+// jsonExtract returns the element of doc at the end of path, as JSON text;
+// false when there is none.
+func jsonExtract(doc string, path []any) (string, bool, error) {
+	raw := json.RawMessage(doc)
+	if !json.Valid(raw) {
+		return "", false, ErrBadJSON
+	}
+	for _, step := range path {
+		switch k := step.(type) {
+		case string:
+			var obj map[string]json.RawMessage
+			if json.Unmarshal(raw, &obj) != nil || obj == nil {
+				return "", false, nil
+			}
+			var ok bool
+			if raw, ok = obj[k]; !ok {
+				return "", false, nil
+			}
+		case int64:
+			var arr []json.RawMessage
+			if json.Unmarshal(raw, &arr) != nil || k < 0 || k >= int64(len(arr)) {
+				return "", false, nil
+			}
+			raw = arr[k]
+		}
+	}
+	var b bytes.Buffer
+	if err := json.Compact(&b, raw); err != nil {
+		return "", false, err
+	}
+	return b.String(), true, nil
+}
+
+// jsonScalar is the SQL value of the JSON document doc: a string is TEXT, a
+// number INT or FLOAT, true and false BOOL and null NULL. An object or an
+// array stays JSON text.
+func jsonScalar(doc string) any {
+	var v any
+	d := json.NewDecoder(strings.NewReader(doc))
+	d.UseNumber()
+	if d.Decode(&v) != nil {
+		return doc
+	}
+	switch x := v.(type) {
+	case json.Number:
+		if n, err := x.Int64(); err == nil {
+			return n
+		}
+		f, _ := x.Float64()
+		return f
+	case string, bool, nil:
+		return x
+	}
+	return doc
+}
+
+// jsonType is the type of the JSON document doc, as JSON_TYPE names it.
+func jsonType(doc string) string {
+	switch v := jsonScalar(doc).(type) {
+	case nil:
+		return "null"
+	case int64:
+		return "integer"
+	case float64:
+		return "real"
+	case bool:
+		return strconv.FormatBool(v)
+	}
+	switch strings.TrimSpace(doc)[0] {
+	case '[':
+		return "array"
+	case '{':
+		return "object"
+	}
+	return "text"
+}
+
+// jsonArrow computes "doc -> path" and, with text set, "doc ->> path".
+func jsonArrow(doc, path any, text bool) (any, error) {
+	steps, err := jsonPath(path)
+	if err != nil {
+		return nil, err
+	}
+	elem, ok, err := jsonExtract(doc.(string), steps)
+	if err != nil || !ok {
+		return nil, err
+	}
+	if !text {
+		return elem, nil
+	}
+	v := jsonScalar(elem)
+	if _, isText := v.(string); v == nil || isText {
+		return v, nil
+	}
+	return elem, nil
+}
+
+// jsonFuncs are the built-in functions over JSON. Their JSON arguments may
+// be TEXT too, which is parsed when they are called.
+var jsonFuncs = []*Func{
+	{Name: "JSON", Args: textArg, Result: record.ColJSON, Call: func(a []any) (any, error) {
+		return NormalizeJSON(a[0].(string))
+	}},
+	{Name: "JSON_VALID", Args: textArg, Result: record.ColBool, Call: func(a []any) (any, error) {
+		return json.Valid([]byte(a[0].(string))), nil
+	}},
+	{
+		Name:   "JSON_EXTRACT",
+		Args:   []record.ColumnType{record.ColJSON, record.ColText},
+		Result: record.ColJSON,
+		Call: func(a []any) (any, error) {
+			if !strings.HasPrefix(a[1].(string), "$") {
+				return nil, fmt.Errorf("bad JSON path %q", a[1])
+			}
+			return jsonArrow(a[0], a[1], false)
+		},
+	},
+	{
+		Name:     "JSON_TYPE",
+		Args:     []record.ColumnType{record.ColJSON, record.ColText},
+		Optional: 1,
+		Result:   record.ColText,
+		Call: func(a []any) (any, error) {
+			doc := a[0]
+			if len(a) == 2 {
+				var err error
+				if doc, err = jsonArrow(a[0], a[1], false); doc == nil || err != nil {
+					return nil, err
+				}
+			}
+			if !json.Valid([]byte(doc.(string))) {
+				return nil, ErrBadJSON
+			}
+			return jsonType(doc.(string)), nil
+		},
+	},
+}
@@ -0,0 +1,225 @@
+package expr
+
+import (
+	"fmt"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/parser"
+)
+
+// Aggregate is an aggregate function of one column that a Registry adds to
+// COUNT, SUM, AVG, MIN and MAX. Every group gets an Accumulator of its own
+// from New.
+type Aggregate struct {
+	Name   string            // upper case
+	Arg    record.ColumnType // of the column; a FLOAT one takes INTs and DECIMALs too
+	Result record.ColumnType
+	New    func() Accumulator
+}
+
+// Accumulator computes an Aggregate for one group: Step gets every non-NULL
+// value of the column, then Result the value of the group, which may be NULL.
+type Accumulator interface {
+	Step(v any) error
+	Result() (any, error)
+}
+
+// Step adds v, a value of the column, to acc.
+func (a *Aggregate) Step(acc Accumulator, v any) error {
+	if x, ok := v.(int32); ok {
+		v = int64(x)
+	}
+	if a.Arg == record.ColFloat64 {
+		v = widen(record.ColFloat64, v)
+	}
+	if err := acc.Step(v); err != nil {
+		return fmt.Errorf("expr: %s: %w", a.Name, err)
+	}
+	return nil
+}
+
+// Finish is the result of acc, checked against a.Result.
+func (a *Aggregate) Finish(acc Accumulator) (any, error) {
+	v, err := acc.Result()
+	if err != nil {
+		return nil, fmt.Errorf("expr: %s: %w", a.Name, err)
+	}
+	return resultValue(a.Name, a.Result, v)
+}
+
+// Registry holds the functions and collations a database adds to the
+// built-in ones. It is safe for concurrent use; a nil *Registry has none.
+type Registry struct {
+	mu    sync.RWMutex
+	funcs map[string]*Func
+	aggs  map[string]*Aggregate
+	colls map[string]*Collation
+}
+
+// Register adds f, replacing a function of the same name that was added
+// before. Built-in functions and aggregates cannot be replaced.
+func (r *Registry) Register(f *Func) error {
+	f.Name = strings.ToUpper(f.Name)
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	if _, taken := r.aggs[f.Name]; taken {
+		return fmt.Errorf("expr: %s is already an aggregate", f.Name)
+	}
+	if err := checkName(f.Name); err != nil {
+		return err
+	}
+	if r.funcs == nil {
+		r.funcs = make(map[string]*Func)
+	}
+	r.funcs[f.Name] = f
+	return nil
+}
+
+// RegisterAggregate adds a, like Register.
+func (r *Registry) RegisterAggregate(a *Aggregate) error {
+	a.Name = strings.ToUpper(a.Name)
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	if _, taken := r.funcs[a.Name]; taken {
+		return fmt.Errorf("expr: %s is already a scalar function", a.Name)
+	}
+	if err := checkName(a.Name); err != nil {
+		return err
+	}
+	if r.aggs == nil {
+		r.aggs = make(map[string]*Aggregate)
+	}
+	r.aggs[a.Name] = a
+	return nil
+}
+
+// RegisterCollation adds c, replacing a collation of the same name that was
+// added before. Built-in collations cannot be replaced.
+func (r *Registry) RegisterCollation(c *Collation) error {
+	c.Name = strings.ToUpper(c.Name)
+	if _, ok := collations[c.Name]; ok {
+		return fmt.Errorf("expr: %s is a built-in collation", c.Name)
+	}
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	if r.colls == nil {
+		r.colls = make(map[string]*Collation)
+	}
+	r.colls[c.Name] = c
+	return nil
+}
+
+// checkName checks that name is not the name of a built-in function.
+func checkName(name string) error {
+	if _, ok := funcs[name]; ok || parser.IsAggregate(name) {
+		return fmt.Errorf("expr: %s is a built-in function", name)
+	}
+	return nil
+}
+
+// Func returns the function called name (in any case): a built-in one or
+// one of r.
+func (r *Registry) Func(name string) (*Func, bool) {
+	if f, ok := LookupFunc(name); ok {
+		return f, true
+	}
+	if r == nil {
+		return nil, false
+	}
+	r.mu.RLock()
+	defer r.mu.RUnlock()
+	f, ok := r.funcs[strings.ToUpper(name)]
+	return f, ok
+}
+
+// Aggregate returns the aggregate of r called name (in any case).
+func (r *Registry) Aggregate(name string) (*Aggregate, bool) {
+	if r == nil {
+		return nil, false
+	}
+	r.mu.RLock()
+	defer r.mu.RUnlock()
+	a, ok := r.aggs[strings.ToUpper(name)]
+	return a, ok
+}
+
+// Collation returns the collation called name (in any case): a built-in one
+// or one of r.
+func (r *Registry) Collation(name string) (*Collation, bool) {
+	if c, ok := LookupCollation(name); ok {
+		return c, true
+	}
+	if r == nil {
+		return nil, false
+	}
+	r.mu.RLock()
+	defer r.mu.RUnlock()
+	c, ok := r.colls[strings.ToUpper(name)]
+	return c, ok
+}
+
+// resultValue is v, the result of function name, as a value of type t: Go
+// integers become int64, floats float64, a time.Time a record.Date or
+// Timestamp, and a number for a DECIMAL a record.Decimal. Any other type is
+// an error.
+func resultValue(name string, t record.ColumnType, v any) (any, error) {
+	switch x := v.(type) {
+	case nil:
+		return nil, nil
+	case int:
+		v = int64(x)
+	case int32:
+		v = int64(x)
+	case float32:
+		v = float64(x)
+	case time.Time:
+		if t == record.ColDate {
+			return record.DateOf(x), nil
+		}
+		v = record.TimestampOf(x)
+	}
+	switch x := v.(type) {
+	case int64:
+		if t == record.ColFloat64 || t == record.ColDecimal {
+			return widen(t, x), nil
+		}
+		if t == record.ColInt64 {
+			return x, nil
+		}
+	case float64:
+		if t == record.ColFloat64 {
+			return x, nil
+		}
+		if t == record.ColDecimal {
+			return record.FloatDecimal(x)
+		}
+	case record.Decimal:
+		if t == record.ColDecimal {
+			return x, nil
+		}
+	case string:
+		if t == record.ColText || t == record.ColJSON {
+			return x, nil
+		}
+	case bool:
+		if t == record.ColBool {
+			return x, nil
+		}
+	case record.Date:
+		if t == record.ColDate {
+			return x, nil
+		}
+	case record.Time:
+		if t == record.ColTime {
+			return x, nil
+		}
+	case record.Timestamp:
+		if t == record.ColTimestamp {
+			return x, nil
+		}
+	}
+	return nil, fmt.Errorf("expr: %s returned %T, not %s", name, v, typeName(t))
+}
@@ -0,0 +1,254 @@
+package expr
+
+import (
+	"fmt"
+	"strings"
+	"time"
+
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+// convertsTemporal reports whether an argument of type t may stand for one
+// of type want, a date or a time: TEXT is read as one, a DATE is the
+// TIMESTAMP of its start, and a TIMESTAMP has a DATE and a TIME.
+func convertsTemporal(t, want record.ColumnType) bool {
+	switch {
+	case !isTemporal(want):
+		return false
+	case t == record.ColText:
+		return true
+	case want == record.ColTime:
+		return t == record.ColTimestamp
+	}
+	return isDateLike(t)
+}
+
+// toTemporal converts v, a value of a type convertsTemporal allows, to a
+// value of type want.
+func toTemporal(want record.ColumnType, v any) (any, error) {
+	if s, ok := v.(string); ok {
+		t, err := record.TemporalValue(want, s)
+		if err == nil || want == record.ColTimestamp {
+			return t, err
+		}
+		// A timestamp has a date and a time too.
+		ts, tsErr := record.ParseTimestamp(s)
+		if tsErr != nil {
+			return nil, err
+		}
+		v = ts
+	}
+	switch x := v.(type) {
+	case record.Date:
+		if want == record.ColTimestamp {
+			return x.Timestamp(), nil
+		}
+	case record.Timestamp:
+		switch want {
+		case record.ColDate:
+			return x.Date(), nil
+		case record.ColTime:
+			return x.Time(), nil
+		}
+	}
+	return v, nil
+}
+
+// temporalArith is the type of "l op r" when it is arithmetic on dates and
+// times:
+//
+//	DATE + INT, INT + DATE, DATE - INT               DATE, days later or earlier
+//	DATE - DATE                                      INT, the days between them
+//	DATE or TIMESTAMP ± INTERVAL, INTERVAL + either  TIMESTAMP
+//	TIME ± INTERVAL, INTERVAL + TIME                 TIME, around the clock
+//	INTERVAL ± INTERVAL                              INTERVAL
+//
+// A NULL operand has the type of the other one.
+func temporalArith(op string, l, r *Expr) (record.ColumnType, bool) {
+	lt, rt := l.typ, r.typ
+	switch {
+	case op != "+" && op != "-", l.null && r.null:
+		return 0, false
+	case l.null:
+		lt = rt
+	case r.null:
+		rt = lt
+	}
+	plus := op == "+"
+	switch {
+	case lt == record.ColDate && rt == record.ColInt64, plus && lt == record.ColInt64 && rt == record.ColDate:
+		return record.ColDate, true
+	case !plus && lt == record.ColDate && rt == record.ColDate:
+		return record.ColInt64, true
+	case isDateLike(lt) && rt == colInterval, plus && lt == colInterval && isDateLike(rt):
+		return record.ColTimestamp, true
+	case lt == record.ColTime && rt == colInterval, plus && lt == colInterval && rt == record.ColTime:
+		return record.ColTime, true
+	case lt == colInterval && rt == colInterval:
+		return colInterval, true
+	}
+	return 0, false
+}
+
+// dateArith computes "l op r" for the operands temporalArith allows; false
+// when they are numbers.
+func dateArith(op string, l, r any) (any, bool) {
+	if op == "-" {
+		switch y := r.(type) {
+		case record.Interval:
+			r = y.Neg()
+		case record.Date:
+			x, _ := l.(record.Date)
+			return int64(x - y), true
+		case int64:
+			if x, ok := l.(record.Date); ok {
+				return x - record.Date(y), true
+			}
+		}
+	}
+	// Addition is the same either way round.
+	switch l.(type) {
+	case record.Interval, int64:
+		l, r = r, l
+	}
+	switch x := l.(type) {
+	case record.Date:
+		switch y := r.(type) {
+		case int64:
+			return x + record.Date(y), true
+		case record.Interval:
+			return x.Timestamp().Add(y), true
+		}
+	case record.Timestamp:
+		return x.Add(r.(record.Interval)), true
+	case record.Time:
+		return x.Add(r.(record.Interval)), true
+	case record.Interval:
+		y := r.(record.Interval)
+		return record.Interval{Months: x.Months + y.Months, Days: x.Days + y.Days, Micros: x.Micros + y.Micros}, true
+	}
+	return nil, false
+}
+
+var (
+	dateArg      = []record.ColumnType{record.ColDate}
+	timeArg      = []record.ColumnType{record.ColTime}
+	timestampArg = []record.ColumnType{record.ColTimestamp}
+)
+
+// temporalFuncs are the built-in functions over dates and times, which are
+// in UTC. Their date and time arguments may be TEXT, or another date or time
+// a value of the type the argument needs can be made of: DATE(ts) is the day
+// of a timestamp, YEAR(ts) the year of that day.
+var temporalFuncs = []*Func{
+	{Name: "NOW", Result: record.ColTimestamp, Volatile: true, Call: func([]any) (any, error) {
+		return record.TimestampOf(time.Now()), nil
+	}},
+	{Name: "DATE", Args: dateArg, Result: record.ColDate, Call: first},
+	{Name: "TIME", Args: timeArg, Result: record.ColTime, Call: first},
+	{Name: "TIMESTAMP", Args: timestampArg, Result: record.ColTimestamp, Call: first},
+	{Name: "YEAR", Args: dateArg, Result: record.ColInt64, Call: datePart(time.Time.Year)},
+	{Name: "MONTH", Args: dateArg, Result: record.ColInt64, Call: datePart(month)},
+	{Name: "DAY", Args: dateArg, Result: record.ColInt64, Call: datePart(time.Time.Day)},
+	{Name: "HOUR", Args: timeArg, Result: record.ColInt64, Call: timePart(time.Hour, 24*time.Hour)},
+	{Name: "MINUTE", Args: timeArg, Result: record.ColInt64, Call: timePart(time.Minute, time.Hour)},
+	{Name: "SECOND", Args: timeArg, Result: record.ColInt64, Call: timePart(time.Second, time.Minute)},
+	{Name: "EPOCH", Args: timestampArg, Result: record.ColInt64, Call: func(a []any) (any, error) {
+		return a[0].(record.Timestamp).UTC().Unix(), nil
+	}},
+	{
+		Name:   "DATE_ADD",
+		Args:   []record.ColumnType{record.ColTimestamp, colInterval},
+		Result: record.ColTimestamp,
+		Call: func(a []any) (any, error) {
+			return a[0].(record.Timestamp).Add(a[1].(record.Interval)), nil
+		},
+	},
+	{
+		Name:   "DATE_SUB",
+		Args:   []record.ColumnType{record.ColTimestamp, colInterval},
+		Result: record.ColTimestamp,
+		Call: func(a []any) (any, error) {
+			return a[0].(record.Timestamp).Add(a[1].(record.Interval).Neg()), nil
+		},
+	},
+	{
+		Name:   "DATE_FORMAT",
+		Args:   []record.ColumnType{record.ColTimestamp, record.ColText},
+		Result: record.ColText,
+		Call: func(a []any) (any, error) {
+			return formatTimestamp(a[0].(record.Timestamp).UTC(), a[1].(string))
+		},
+	},
+}
+
+func first(a []any) (any, error) { return a[0], nil }
+
+func month(t time.Time) int { return int(t.Month()) }
+
+func datePart(part func(time.Time) int) func([]any) (any, error) {
+	return func(a []any) (any, error) {
+		return int64(part(a[0].(record.Date).UTC())), nil
+	}
+}
+
+// timePart is the number of whole units in a time of day, less the whole
+// ones of outer: HOUR is from 0 to 23, MINUTE and SECOND from 0 to 59.
+func timePart(unit, outer time.Duration) func([]any) (any, error) {
+	return func(a []any) (any, error) {
+		d := a[0].(record.Time).Duration()
+		return int64(d % outer / unit), nil
+	}
+}
+
+// strftimeLayouts are the codes of DATE_FORMAT, as strftime has them, with
+// the time.Format layouts they stand for.
+var strftimeLayouts = map[byte]string{
+	'Y': "2006", 'm': "01", 'd': "02", 'H': "15", 'M': "04", 'S': "05", 'j': "002",
+	'a': "Mon", 'A': "Monday", 'b': "Jan", 'B': "January", 'I': "03", 'p': "PM",
+}
+
+// formatTimestamp writes t as format says: a code of strftimeLayouts after
+// a "%" is replaced, "%f" is the microseconds and "%%" a "%".
+func formatTimestamp(t time.Time, format string) (string, error) {
+	var b strings.Builder
+	for i := 0; i < len(format); i++ {
+		if format[i] != '%' {
+			b.WriteByte(format[i])
+			continue
+		}
+		if i++; i == len(format) {
+			return "", fmt.Errorf("format %q ends in %%", format)
+		}
+		switch c := format[i]; {
+		case c == '%':
+			b.WriteByte('%')
+		case c == 'f':
+			b.WriteString(t.Format(".000000")[1:])
+		case strftimeLayouts[c] != "":
+			b.WriteString(t.Format(strftimeLayouts[c]))
+		default:
+			return "", fmt.Errorf("unknown format code %%%c in %q", c, format)
+		}
+	}
+	return b.String(), nil
+}
+
+// temporalLiteral writes v, a date, a time or an interval, as the typed
+// literal the parser reads.
+func temporalLiteral(v any) (string, bool) {
+	var kw string
+	switch v.(type) {
+	case record.Date:
+		kw = "DATE"
+	case record.Time:
+		kw = "TIME"
+	case record.Timestamp:
+		kw = "TIMESTAMP"
+	case record.Interval:
+		kw = "INTERVAL"
+	default:
+		return "", false
+	}
+	return kw + " '" + fmt.Sprint(v) + "'", true
+}
@@ -1,5 +1,7 @@
 package parser
 
+import "strings"
+
 // Statement is the root interface for all SQL statements.
 type Statement interface {
 	stmtNode()
@@ -26,41 +28,272 @@ type UseDatabaseStmt struct {
 
 func (*UseDatabaseStmt) stmtNode() {}
 
+// ----- SET / SHOW -----
+
+// SetStmt is "SET <name> {= | TO} <value>": change a configuration parameter.
+type SetStmt struct {
+	Name  string
+	Value string
+}
+
+func (*SetStmt) stmtNode() {}
+
+// ShowStmt is "SHOW <name>", or "SHOW ALL" with Name empty.
+type ShowStmt struct {
+	Name string
+}
+
+func (*ShowStmt) stmtNode() {}
+
 // ----- CREATE TABLE / DROP TABLE -----
 
 type ColumnDef struct {
-	Name string
-	Type string // "INT", "TEXT", "BOOL"
-	// TODO: nullable, default, primary key, ...
+	Name          string
+	Type          string // "INT", "BIGINT", "FLOAT", "TEXT", "BOOL", "JSON", "DATE", "TIMESTAMP", "DECIMAL", ...
+	Precision     int    // of DECIMAL(p, s); 0 when not given
+	Scale         int
+	NotNull       bool
+	PrimaryKey    bool // implies NotNull
+	Unique        bool
+	AutoIncrement bool
+	References    *ForeignKeyDef
+	Default       *LiteralExpr // DEFAULT <literal>; only in ALTER TABLE ADD COLUMN
+	Collate       string       // COLLATE <collation>, upper case
+}
+
+// ForeignKeyDef is "REFERENCES <table> [(<column>)] [ON DELETE <action>]".
+type ForeignKeyDef struct {
+	Table    string
+	Column   string // "" = the primary key of Table
+	OnDelete string // "RESTRICT" (also for "" and NO ACTION), "CASCADE" or "SET NULL"
 }
 
 type CreateTableStmt struct {
 	TableName string
 	Columns   []ColumnDef
+	Temp      bool   // CREATE TEMP (or TEMPORARY) TABLE
+	TTLColumn string // WITH (ttl_column = <column>)
 }
 
 func (*CreateTableStmt) stmtNode() {}
 
 type DropTableStmt struct {
 	TableName string
+	Purge     bool // DROP TABLE t PURGE: skip the trash
 }
 
 func (*DropTableStmt) stmtNode() {}
 
+// ----- CREATE VIEW / DROP VIEW -----
+
+// CreateViewStmt is "CREATE VIEW <name> AS SELECT ...". SQL is the text of
+// Query as written, which is what the view stores.
+type CreateViewStmt struct {
+	Name  string
+	Query *SelectStmt
+	SQL   string
+}
+
+func (*CreateViewStmt) stmtNode() {}
+
+type DropViewStmt struct {
+	Name string
+}
+
+func (*DropViewStmt) stmtNode() {}
+
+// ----- ALTER TABLE -----
+
+// AlterTableStmt is "ALTER TABLE <table>" with one action: "ADD [COLUMN]
+// <column def>", "ADD [CONSTRAINT <name>] FOREIGN KEY (<column>) REFERENCES
+// ...", "DROP [COLUMN] <column>" or "RENAME TO <name>".
+type AlterTableStmt struct {
+	TableName  string
+	AddColumn  *ColumnDef
+	DropColumn string
+	RenameTo   string
+
+	AddForeignKey    *ForeignKeyDef
+	ForeignKeyColumn string // the column of AddForeignKey
+	ForeignKeyName   string // "" = <table>_<column>_fkey
+}
+
+func (*AlterTableStmt) stmtNode() {}
+
+// ----- COPY -----
+
+// CopyStmt is "COPY <table> FROM '<file>'", which loads a CSV file into the
+// table, or "COPY <table> TO '<file>'", which writes the table to one, with
+// optional "[WITH] (HEADER [TRUE | FALSE], DELIMITER '<char>', NULL '<text>')".
+type CopyStmt struct {
+	TableName string
+	From      bool // FROM (import) rather than TO (export)
+	File      string
+	Header    bool
+	Delimiter string // "" = ","
+	Null      string // the text of a NULL field
+}
+
+func (*CopyStmt) stmtNode() {}
+
+// ----- CREATE INDEX / DROP INDEX -----
+
+// CreateIndexStmt is "CREATE [UNIQUE] INDEX <name> ON <table> [USING BTREE
+// | FULLTEXT] (<column>)", or an index over an expression of the columns:
+// "(LOWER(name))".
+type CreateIndexStmt struct {
+	IndexName string
+	TableName string
+	Column    string
+	Expr      Expr // set instead of Column
+	Unique    bool
+	FullText  bool // USING FULLTEXT
+}
+
+func (*CreateIndexStmt) stmtNode() {}
+
+// DropIndexStmt is "DROP INDEX <name> ON <table>".
+type DropIndexStmt struct {
+	IndexName string
+	TableName string
+}
+
+func (*DropIndexStmt) stmtNode() {}
+
+// ----- VACUUM -----
+
+// VacuumStmt is "VACUUM": compact every table of the current database.
+type VacuumStmt struct{}
+
+func (*VacuumStmt) stmtNode() {}
+
+// ----- ANALYZE -----
+
+// AnalyzeStmt is "ANALYZE [<table>]": collect the statistics the planner
+// uses, for one table or, without a name, every table.
+type AnalyzeStmt struct {
+	TableName string // empty = every table
+}
+
+func (*AnalyzeStmt) stmtNode() {}
+
+// ----- EXPLAIN -----
+
+// ExplainStmt is "EXPLAIN [ANALYZE] <stmt>" for a SELECT, INSERT, UPDATE or
+// DELETE. With Analyze the statement is also run.
+type ExplainStmt struct {
+	Analyze bool
+	Stmt    Statement
+}
+
+func (*ExplainStmt) stmtNode() {}
+
+// ----- Cursors -----
+
+// DeclareCursorStmt is "DECLARE <name> CURSOR FOR <select>": it opens a
+// cursor over the rows of Query, which FETCH then reads a few at a time.
+type DeclareCursorStmt struct {
+	Name  string
+	Query *SelectStmt
+}
+
+func (*DeclareCursorStmt) stmtNode() {}
+
+// FetchStmt is "FETCH [NEXT | ALL | <n>] [FROM | IN] <cursor>": the next
+// Count rows of Cursor, one for NEXT, all of the rest when Count is -1.
+type FetchStmt struct {
+	Cursor string
+	Count  int
+}
+
+func (*FetchStmt) stmtNode() {}
+
+// CloseCursorStmt is "CLOSE <cursor>", or "CLOSE ALL" with Name empty.
+type CloseCursorStmt struct {
+	Name string
+}
+
+func (*CloseCursorStmt) stmtNode() {}
+
 // ----- INSERT -----
 
+// InsertStmt is "INSERT INTO <table> [(<column>, ...)] VALUES (...)".
+// Columns left out of the column list get NULL.
 type InsertStmt struct {
 	TableName string
-	Values    []Expr // only constant expr for now
+	Columns   []string // nil = every column, in table order
+	Values    []Expr   // literals or placeholders
+	Returning []string // see UpdateStmt
 }
 
 func (*InsertStmt) stmtNode() {}
 
+// LastInsertRowIDStmt is "SELECT last_insert_rowid()": the AUTOINCREMENT
+// value the last INSERT of the session got.
+type LastInsertRowIDStmt struct{}
+
+func (*LastInsertRowIDStmt) stmtNode() {}
+
 // ----- SELECT -----
 
+// SelectStmt is a SELECT over TableName and the tables it joins. Aggregate
+// calls in the SELECT list, HAVING and ORDER BY are collected in Aggregates;
+// those clauses refer to them by Aggregate.Name, like to a column. Column
+// names may be qualified by a table name or alias ("u.id"). The SELECT list
+// entries computed by an expression are in Exprs.
 type SelectStmt struct {
+	TableName  string
+	Alias      string // optional
+	Joins      []JoinClause
+	Columns    []string // nil = SELECT *
+	Exprs      []SelectExpr
+	Where      *Where // optional
+	GroupBy    []string
+	Having     *Where // optional
+	Aggregates []Aggregate
+	OrderBy    []OrderByItem
+	Limit      *int64 // optional
+}
+
+// JoinClause is "[INNER | LEFT [OUTER]] JOIN <table> [alias] ON <cond> [AND ...]".
+type JoinClause struct {
 	TableName string
-	Where     *WhereEq // optional
+	Alias     string // optional
+	Left      bool   // LEFT JOIN
+	On        []JoinCond
+}
+
+// JoinCond compares two columns: "u.id = o.user_id".
+type JoinCond struct {
+	Left  string
+	Op    string
+	Right string
+}
+
+// Aggregate is an aggregate call such as COUNT(*) or SUM(age).
+type Aggregate struct {
+	Func   string // COUNT, SUM, AVG, MIN or MAX
+	Column string // "*" only for COUNT(*)
+}
+
+// Name is how the query refers to the aggregate, and the result column name:
+// "count(*)", "sum(age)".
+func (a Aggregate) Name() string {
+	return strings.ToLower(a.Func) + "(" + a.Column + ")"
+}
+
+// SelectExpr is an entry of the SELECT list that is an expression rather
+// than a column or an aggregate: Columns[Index] is its name in the result,
+// the alias of "<expr> AS <alias>" or else the expression as written.
+type SelectExpr struct {
+	Index int
+	Expr  Expr
+}
+
+type OrderByItem struct {
+	Column  string
+	Desc    bool
+	Collate string // COLLATE <collation>, upper case
 }
 
 func (*SelectStmt) stmtNode() {}
@@ -69,13 +302,14 @@ func (*SelectStmt) stmtNode() {}
 
 type Assignment struct {
 	Column string
-	Value  Expr // literal only
+	Value  Expr // literal or placeholder
 }
 
 type UpdateStmt struct {
 	TableName   string
 	Assignments []Assignment
-	Where       *WhereEq // optional
+	Where       *Where   // optional
+	Returning   []string // RETURNING columns, "*" for all of them; nil without RETURNING
 }
 
 func (*UpdateStmt) stmtNode() {}
@@ -84,16 +318,25 @@ func (*UpdateStmt) stmtNode() {}
 
 type DeleteStmt struct {
 	TableName string
-	Where     *WhereEq // optional
+	Where     *Where   // optional
+	Returning []string // see UpdateStmt
 }
 
 func (*DeleteStmt) stmtNode() {}
 
-// ----- WHERE (phase 1: only col = literal) -----
-
-type WhereEq struct {
-	Column string
-	Value  Expr // literal only
+// ----- WHERE (col <op> literal) -----
+
+// Where is a single predicate. Op is one of "=", "<", "<=", ">", ">=" when
+// Column is compared with Value, or with the one value of a Subquery; "IN"
+// or "NOT IN" when Column is looked up in the rows of Subquery; "EXISTS" or
+// "NOT EXISTS", without Column, when Subquery is checked for rows. Any other
+// condition is a boolean Expr, with neither Column nor Op.
+type Where struct {
+	Column   string
+	Op       string
+	Value    Expr        // literal, placeholder or column of an enclosing query
+	Subquery *SelectStmt // "(SELECT ...)" in place of Value
+	Expr     Expr
 }
 
 // ----- Expressions -----
@@ -102,8 +345,93 @@ type Expr interface {
 	exprNode()
 }
 
+// LiteralExpr is a constant: nil for NULL, int64, float64, string, bool, or
+// the record.Date, Time, Timestamp, Interval or Decimal of a typed literal.
 type LiteralExpr struct {
 	Value any
 }
 
 func (*LiteralExpr) exprNode() {}
+
+// ParamExpr is a placeholder for a value bound at execution time: "?" (the
+// placeholders are numbered in order) or "$n".
+type ParamExpr struct {
+	Index int // 1-based
+}
+
+func (*ParamExpr) exprNode() {}
+
+// ColumnExpr is a column used as a value: in a subquery, "o.user_id = u.id"
+// compares with the column of the current row of the enclosing query. In
+// the SELECT list an aggregate is a ColumnExpr named by Aggregate.Name.
+type ColumnExpr struct {
+	Name string // as written, possibly qualified
+}
+
+func (*ColumnExpr) exprNode() {}
+
+// BinaryExpr is "<left> <op> <right>". Op is an arithmetic operator ("+",
+// "-", "*", "/", "%"), "||" (string concatenation), a comparison ("=", "<>",
+// "<", "<=", ">", ">="), "LIKE", "NOT LIKE", "GLOB", "NOT GLOB", "MATCH",
+// "AND" or "OR".
+type BinaryExpr struct {
+	Op     string
+	Left   Expr
+	Right  Expr
+	Escape Expr // LIKE, NOT LIKE: the character of "ESCAPE <escape>", if any
+}
+
+func (*BinaryExpr) exprNode() {}
+
+// CollateExpr is "<x> COLLATE <collation>": x, compared and sorted by the
+// collation.
+type CollateExpr struct {
+	X         Expr
+	Collation string // upper case
+}
+
+func (*CollateExpr) exprNode() {}
+
+// UnaryExpr is "-<x>", "NOT <x>", "<x> IS NULL" or "<x> IS NOT NULL"; Op
+// is "-", "NOT", "IS NULL" or "IS NOT NULL".
+type UnaryExpr struct {
+	Op string
+	X  Expr
+}
+
+func (*UnaryExpr) exprNode() {}
+
+// FuncExpr is a call of a scalar function, such as "LOWER(name)".
+type FuncExpr struct {
+	Name string // upper case
+	Args []Expr
+}
+
+func (*FuncExpr) exprNode() {}
+
+// CastExpr is "CAST(<x> AS <type>)", with Type, Precision and Scale as a
+// ColumnDef has them.
+type CastExpr struct {
+	X         Expr
+	Type      string
+	Precision int
+	Scale     int
+}
+
+func (*CastExpr) exprNode() {}
+
+// CaseExpr is "CASE [<operand>] WHEN <when> THEN <then> ... [ELSE <else>]
+// END". Without Operand each When is a condition; with it, the first When
+// equal to Operand is picked.
+type CaseExpr struct {
+	Operand Expr // optional
+	Whens   []CaseWhen
+	Else    Expr // optional: NULL
+}
+
+type CaseWhen struct {
+	When Expr
+	Then Expr
+}
+
+func (*CaseExpr) exprNode() {}
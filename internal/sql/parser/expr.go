@@ -0,0 +1,372 @@
+package parser
+
+import (
+	"slices"
+	"strings"
+)
+
+// ParseExpr parses a standalone expression, such as the expression of an
+// index as the catalog stores it.
+func ParseExpr(src string) (Expr, error) {
+	toks, err := lex(src)
+	if err != nil {
+		return nil, err
+	}
+	p := &parser{toks: toks, src: src}
+	e, err := p.expr()
+	if err != nil {
+		return nil, err
+	}
+	if p.peek().kind != tokEOF {
+		return nil, p.errorf("expected end of expression")
+	}
+	if hasParam(e) {
+		return nil, syntaxErrorf(0, "an expression cannot have placeholders")
+	}
+	return e, nil
+}
+
+// reservedWords cannot be column names in an expression, so that a missing
+// operand reads as an error rather than as a column.
+var reservedWords = []string{
+	"SELECT", "FROM", "WHERE", "GROUP", "HAVING", "ORDER", "LIMIT", "JOIN", "ON", "AS",
+	"AND", "OR", "NOT", "IS", "IN", "LIKE", "GLOB", "ESCAPE", "MATCH", "COLLATE", "WHEN", "THEN",
+	"ELSE", "END", "RETURNING",
+}
+
+// expr reads an expression. Operators bind, from loosest to tightest: OR;
+// AND; NOT; comparisons, [NOT] LIKE (with ESCAPE), [NOT] GLOB, MATCH and IS
+// [NOT] NULL; + - ||; * / %; the JSON operators -> and ->>; unary minus;
+// COLLATE. In the SELECT list (p.aggs set) aggregate calls are read into
+// p.aggs.Aggregates; anywhere else they are an error.
+func (p *parser) expr() (Expr, error) {
+	left, err := p.andExpr()
+	if err != nil {
+		return nil, err
+	}
+	for p.acceptKeyword("OR") {
+		right, err := p.andExpr()
+		if err != nil {
+			return nil, err
+		}
+		left = &BinaryExpr{Op: "OR", Left: left, Right: right}
+	}
+	return left, nil
+}
+
+func (p *parser) andExpr() (Expr, error) {
+	left, err := p.notExpr()
+	if err != nil {
+		return nil, err
+	}
+	for p.acceptKeyword("AND") {
+		right, err := p.notExpr()
+		if err != nil {
+			return nil, err
+		}
+		left = &BinaryExpr{Op: "AND", Left: left, Right: right}
+	}
+	return left, nil
+}
+
+func (p *parser) notExpr() (Expr, error) {
+	if p.acceptKeyword("NOT") {
+		x, err := p.notExpr()
+		if err != nil {
+			return nil, err
+		}
+		return &UnaryExpr{Op: "NOT", X: x}, nil
+	}
+	return p.cmpExpr()
+}
+
+// cmpOps are the comparisons of expressions; "!=" reads as "<>".
+var cmpOps = []string{"=", "<>", "!=", "<", "<=", ">", ">="}
+
+func (p *parser) cmpExpr() (Expr, error) {
+	left, err := p.addExpr()
+	if err != nil {
+		return nil, err
+	}
+	t := p.peek()
+	op := ""
+	switch {
+	case t.kind == tokSymbol && slices.Contains(cmpOps, t.text):
+		op = t.text
+		if op == "!=" {
+			op = "<>"
+		}
+		p.next()
+	case p.acceptKeyword("LIKE"):
+		op = "LIKE"
+	case p.acceptKeyword("GLOB"):
+		op = "GLOB"
+	case p.acceptKeyword("MATCH"):
+		op = "MATCH"
+	case t.isKeyword("NOT") && (p.toks[p.pos+1].isKeyword("LIKE") || p.toks[p.pos+1].isKeyword("GLOB")):
+		op = "NOT " + strings.ToUpper(p.toks[p.pos+1].text)
+		p.pos += 2
+	case p.acceptKeyword("IS"):
+		op = "IS NULL"
+		if p.acceptKeyword("NOT") {
+			op = "IS NOT NULL"
+		}
+		if err := p.expectKeyword("NULL"); err != nil {
+			return nil, err
+		}
+		return &UnaryExpr{Op: op, X: left}, nil
+	default:
+		return left, nil
+	}
+	right, err := p.addExpr()
+	if err != nil {
+		return nil, err
+	}
+	b := &BinaryExpr{Op: op, Left: left, Right: right}
+	if strings.HasSuffix(op, "LIKE") && p.acceptKeyword("ESCAPE") {
+		if b.Escape, err = p.addExpr(); err != nil {
+			return nil, err
+		}
+	}
+	return b, nil
+}
+
+func (p *parser) addExpr() (Expr, error) {
+	left, err := p.mulExpr()
+	if err != nil {
+		return nil, err
+	}
+	for {
+		t := p.peek()
+		if !t.isSymbol("+") && !t.isSymbol("-") && !t.isSymbol("||") {
+			return left, nil
+		}
+		p.next()
+		right, err := p.mulExpr()
+		if err != nil {
+			return nil, err
+		}
+		left = &BinaryExpr{Op: t.text, Left: left, Right: right}
+	}
+}
+
+func (p *parser) mulExpr() (Expr, error) {
+	left, err := p.jsonExpr()
+	if err != nil {
+		return nil, err
+	}
+	for {
+		t := p.peek()
+		if !t.isSymbol("*") && !t.isSymbol("/") && !t.isSymbol("%") {
+			return left, nil
+		}
+		p.next()
+		right, err := p.jsonExpr()
+		if err != nil {
+			return nil, err
+		}
+		left = &BinaryExpr{Op: t.text, Left: left, Right: right}
+	}
+}
+
+func (p *parser) jsonExpr() (Expr, error) {
+	left, err := p.unaryExpr()
+	if err != nil {
+		return nil, err
+	}
+	for {
+		t := p.peek()
+		if !t.isSymbol("->") && !t.isSymbol("->>") {
+			return left, nil
+		}
+		p.next()
+		right, err := p.unaryExpr()
+		if err != nil {
+			return nil, err
+		}
+		left = &BinaryExpr{Op: t.text, Left: left, Right: right}
+	}
+}
+
+func (p *parser) unaryExpr() (Expr, error) {
+	if !p.peek().isSymbol("-") {
+		return p.collateExpr()
+	}
+	if p.toks[p.pos+1].kind == tokNumber {
+		return p.parseLiteralExpr() // a negative number
+	}
+	p.next()
+	x, err := p.unaryExpr()
+	if err != nil {
+		return nil, err
+	}
+	return &UnaryExpr{Op: "-", X: x}, nil
+}
+
+func (p *parser) collateExpr() (Expr, error) {
+	x, err := p.primary()
+	if err != nil {
+		return nil, err
+	}
+	for p.peek().isKeyword("COLLATE") {
+		name, err := p.collateClause()
+		if err != nil {
+			return nil, err
+		}
+		x = &CollateExpr{X: x, Collation: name}
+	}
+	return x, nil
+}
+
+// collateClause reads "COLLATE <collation>" if it comes next, and returns
+// the collation, upper case: "" without the clause.
+func (p *parser) collateClause() (string, error) {
+	if !p.acceptKeyword("COLLATE") {
+		return "", nil
+	}
+	name, err := p.ident("collation name")
+	return strings.ToUpper(name), err
+}
+
+// primary reads a literal, typed ones too, a placeholder, a column, a
+// function call, a CASE or a parenthesized expression.
+func (p *parser) primary() (Expr, error) {
+	t := p.peek()
+	switch {
+	case t.isSymbol("("):
+		if p.toks[p.pos+1].isKeyword("SELECT") {
+			return nil, p.errorf("a subquery is only supported as a WHERE condition")
+		}
+		p.next()
+		e, err := p.expr()
+		if err != nil {
+			return nil, err
+		}
+		return e, p.expectSymbol(")")
+	case t.isKeyword("CASE"):
+		p.next()
+		return p.caseExpr()
+	case t.isKeyword("CAST") && p.toks[p.pos+1].isSymbol("("):
+		p.pos += 2
+		return p.castExpr()
+	case t.kind != tokIdent || t.isKeyword("NULL") || t.isKeyword("TRUE") || t.isKeyword("FALSE") ||
+		p.atTypedLiteral():
+		return p.parseLiteralExpr()
+	}
+	for _, kw := range reservedWords {
+		if t.isKeyword(kw) {
+			return nil, p.errorf("expected an expression")
+		}
+	}
+	if !p.toks[p.pos+1].isSymbol("(") {
+		name, err := p.columnRef("column")
+		if err != nil {
+			return nil, err
+		}
+		return &ColumnExpr{Name: name}, nil
+	}
+
+	fn := strings.ToUpper(t.text)
+	if slices.Contains(aggregateFuncs, fn) {
+		if p.aggs == nil {
+			return nil, p.errorf("aggregate %s is only allowed in the SELECT list, HAVING and ORDER BY", fn)
+		}
+		name, err := p.selectTerm(p.aggs, "aggregate")
+		if err != nil {
+			return nil, err
+		}
+		return &ColumnExpr{Name: name}, nil
+	}
+	p.pos += 2
+	call := &FuncExpr{Name: fn}
+	if p.acceptSymbol(")") {
+		return call, nil
+	}
+	for {
+		arg, err := p.expr()
+		if err != nil {
+			return nil, err
+		}
+		call.Args = append(call.Args, arg)
+		if !p.acceptSymbol(",") {
+			break
+		}
+	}
+	return call, p.expectSymbol(")")
+}
+
+// castExpr reads the rest of "CAST(<x> AS <type>)".
+func (p *parser) castExpr() (Expr, error) {
+	x, err := p.expr()
+	if err != nil {
+		return nil, err
+	}
+	if err := p.expectKeyword("AS"); err != nil {
+		return nil, err
+	}
+	c := &CastExpr{X: x}
+	if c.Type, c.Precision, c.Scale, err = p.typeName(); err != nil {
+		return nil, err
+	}
+	return c, p.expectSymbol(")")
+}
+
+// caseExpr reads the rest of "CASE [<operand>] WHEN ... THEN ... [ELSE ...]
+// END".
+func (p *parser) caseExpr() (Expr, error) {
+	c := &CaseExpr{}
+	var err error
+	if !p.peek().isKeyword("WHEN") {
+		if c.Operand, err = p.expr(); err != nil {
+			return nil, err
+		}
+	}
+	for p.acceptKeyword("WHEN") {
+		var w CaseWhen
+		if w.When, err = p.expr(); err != nil {
+			return nil, err
+		}
+		if err := p.expectKeyword("THEN"); err != nil {
+			return nil, err
+		}
+		if w.Then, err = p.expr(); err != nil {
+			return nil, err
+		}
+		c.Whens = append(c.Whens, w)
+	}
+	if len(c.Whens) == 0 {
+		return nil, p.errorf("expected WHEN")
+	}
+	if p.acceptKeyword("ELSE") {
+		if c.Else, err = p.expr(); err != nil {
+			return nil, err
+		}
+	}
+	return c, p.expectKeyword("END")
+}
+
+// hasParam reports whether e has a placeholder.
+func hasParam(e Expr) bool {
+	switch x := e.(type) {
+	case *ParamExpr:
+		return true
+	case *BinaryExpr:
+		return hasParam(x.Left) || hasParam(x.Right) || hasParam(x.Escape)
+	case *UnaryExpr:
+		return hasParam(x.X)
+	case *CollateExpr:
+		return hasParam(x.X)
+	case *FuncExpr:
+		return slices.ContainsFunc(x.Args, hasParam)
+	case *CaseExpr:
+		if x.Operand != nil && hasParam(x.Operand) || x.Else != nil && hasParam(x.Else) {
+			return true
+		}
+		for _, w := range x.Whens {
+			if hasParam(w.When) || hasParam(w.Then) {
+				return true
+			}
+		}
+	}
+	return false
+}
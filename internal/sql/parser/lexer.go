@@ -0,0 +1,176 @@
+package parser
+
+import (
+	"fmt"
+	"slices"
+	"strings"
+	"unicode"
+)
+
+type tokenKind int
+
+const (
+	tokEOF tokenKind = iota
+	tokIdent
+	tokNumber
+	tokString
+	tokSymbol
+	tokParam // "$n"; text is n
+)
+
+// token is one lexeme. Keywords are plain identifiers; the parser matches
+// them case-insensitively, so "select" and "SELECT" read the same.
+type token struct {
+	kind tokenKind
+	text string // identifier/number text, unquoted string, or the symbol
+	pos  int    // byte offset in the input
+}
+
+func (t token) String() string {
+	switch t.kind {
+	case tokEOF:
+		return "end of input"
+	case tokString:
+		return fmt.Sprintf("'%s'", t.text)
+	case tokParam:
+		return "$" + t.text
+	default:
+		return fmt.Sprintf("%q", t.text)
+	}
+}
+
+// isKeyword reports whether t is the identifier kw (case-insensitive).
+func (t token) isKeyword(kw string) bool {
+	return t.kind == tokIdent && strings.EqualFold(t.text, kw)
+}
+
+func (t token) isSymbol(s string) bool {
+	return t.kind == tokSymbol && t.text == s
+}
+
+// end is the byte offset just past t in src, the input it was lexed from.
+func (t token) end(src string) int {
+	switch t.kind {
+	case tokString:
+		for i := t.pos + 1; i < len(src); i++ {
+			if src[i] != '\'' {
+				continue
+			}
+			if i+1 < len(src) && src[i+1] == '\'' {
+				i++
+				continue
+			}
+			return i + 1
+		}
+		return len(src)
+	case tokParam:
+		return t.pos + 1 + len(t.text)
+	}
+	return t.pos + len(t.text)
+}
+
+// twoCharSymbols are the symbols of two characters; "->>" is the only one
+// of three.
+var twoCharSymbols = []string{"<=", ">=", "<>", "!=", "||", "->"}
+
+// lex splits sql into tokens, ending with a tokEOF.
+//
+//   - identifiers: letter or '_' first, then letters, digits, '_'
+//   - numbers: digits with an optional fraction ("12", "1.5")
+//   - strings: single-quoted, '' is an escaped quote
+//   - symbols: ( ) , ; = * - < > <= >= <> != . ? + / % || -> ->>
+//   - placeholders: "$" and digits ("$1")
+//   - "--" starts a comment that runs to end of line
+func lex(sql string) ([]token, error) {
+	var toks []token
+	rs := []rune(sql)
+	// byte offsets, so errors point into the original string
+	offs := make([]int, len(rs)+1)
+	for i, off := 0, 0; i < len(rs); i++ {
+		offs[i] = off
+		off += len(string(rs[i]))
+		offs[i+1] = off
+	}
+
+	for i := 0; i < len(rs); {
+		r := rs[i]
+		switch {
+		case unicode.IsSpace(r):
+			i++
+
+		case r == '-' && i+1 < len(rs) && rs[i+1] == '-':
+			for i < len(rs) && rs[i] != '\n' {
+				i++
+			}
+
+		case unicode.IsLetter(r) || r == '_':
+			start := i
+			for i < len(rs) && (unicode.IsLetter(rs[i]) || unicode.IsDigit(rs[i]) || rs[i] == '_') {
+				i++
+			}
+			toks = append(toks, token{kind: tokIdent, text: string(rs[start:i]), pos: offs[start]})
+
+		case unicode.IsDigit(r):
+			start := i
+			for i < len(rs) && unicode.IsDigit(rs[i]) {
+				i++
+			}
+			if i+1 < len(rs) && rs[i] == '.' && unicode.IsDigit(rs[i+1]) {
+				i++
+				for i < len(rs) && unicode.IsDigit(rs[i]) {
+					i++
+				}
+			}
+			if i < len(rs) && (unicode.IsLetter(rs[i]) || rs[i] == '_') {
+				return nil, syntaxErrorf(offs[start], "invalid token %q at offset %d", string(rs[start:i+1]), offs[start])
+			}
+			toks = append(toks, token{kind: tokNumber, text: string(rs[start:i]), pos: offs[start]})
+
+		case r == '\'':
+			start := i
+			var b strings.Builder
+			i++
+			for {
+				if i >= len(rs) {
+					return nil, syntaxErrorf(offs[start], "unterminated string at offset %d", offs[start])
+				}
+				if rs[i] == '\'' {
+					if i+1 < len(rs) && rs[i+1] == '\'' {
+						b.WriteRune('\'')
+						i += 2
+						continue
+					}
+					i++
+					break
+				}
+				b.WriteRune(rs[i])
+				i++
+			}
+			toks = append(toks, token{kind: tokString, text: b.String(), pos: offs[start]})
+
+		case r == '$' && i+1 < len(rs) && unicode.IsDigit(rs[i+1]):
+			start := i
+			i++
+			for i < len(rs) && unicode.IsDigit(rs[i]) {
+				i++
+			}
+			toks = append(toks, token{kind: tokParam, text: string(rs[start+1 : i]), pos: offs[start]})
+
+		case i+2 < len(rs) && string(rs[i:i+3]) == "->>":
+			toks = append(toks, token{kind: tokSymbol, text: "->>", pos: offs[i]})
+			i += 3
+
+		case i+1 < len(rs) && slices.Contains(twoCharSymbols, string(rs[i:i+2])):
+			toks = append(toks, token{kind: tokSymbol, text: string(rs[i : i+2]), pos: offs[i]})
+			i += 2
+
+		case strings.ContainsRune("(),;=*-<>.?+/%", r):
+			toks = append(toks, token{kind: tokSymbol, text: string(r), pos: offs[i]})
+			i++
+
+		default:
+			return nil, syntaxErrorf(offs[i], "unexpected character %q at offset %d", r, offs[i])
+		}
+	}
+	return append(toks, token{kind: tokEOF, pos: len(sql)}), nil
+}
@@ -2,415 +2,1528 @@ package parser
 
 import (
 	"fmt"
+	"slices"
 	"strconv"
 	"strings"
-	"unicode"
+
+	"github.com/tuannm99/novasql/internal/record"
 )
 
-// parseIdent validates an identifier (db/table/column name) for phase 1.
-// Rules (simple):
-//   - must be exactly one token (no spaces)
-//   - first char: letter or '_'
-//   - rest: letter/digit/'_'
-func parseIdent(s string) (string, error) {
-	s = strings.TrimSpace(s)
-	if s == "" {
-		return "", fmt.Errorf("missing identifier")
+// Parse parses a single SQL statement into an AST.
+// Policy: statement MUST end with ';'
+func Parse(sql string) (Statement, error) {
+	if strings.TrimSpace(sql) == "" {
+		return nil, syntaxErrorf(0, "empty statement")
 	}
 
-	parts := strings.Fields(s)
-	if len(parts) != 1 {
-		return "", fmt.Errorf("invalid identifier %q", s)
+	toks, err := lex(sql)
+	if err != nil {
+		return nil, err
+	}
+	// Require ';' at the end (nothing but the terminator may follow the statement)
+	if len(toks) < 2 || !toks[len(toks)-2].isSymbol(";") {
+		return nil, syntaxErrorf(len(sql), "missing ';' terminator")
+	}
+	if len(toks) == 2 {
+		return nil, syntaxErrorf(0, "empty statement")
 	}
-	id := parts[0]
 
-	for i, r := range id {
-		if i == 0 {
-			if !unicode.IsLetter(r) && r != '_' {
-				return "", fmt.Errorf("invalid identifier %q", id)
-			}
+	p := &parser{toks: toks, src: sql}
+	stmt, err := p.parseStatement()
+	if err != nil {
+		return nil, err
+	}
+	if p.pos != len(toks)-2 {
+		return nil, p.errorf("expected end of statement")
+	}
+	return stmt, nil
+}
+
+// ParseScript parses a sequence of ';'-terminated statements, such as a
+// dump. An error names the statement it is in, counting from 1.
+func ParseScript(sql string) ([]Statement, error) {
+	toks, err := lex(sql)
+	if err != nil {
+		return nil, err
+	}
+	var stmts []Statement
+	for start := 0; start < len(toks)-1; {
+		end := slices.IndexFunc(toks[start:], func(t token) bool { return t.isSymbol(";") || t.kind == tokEOF })
+		end += start
+		if !toks[end].isSymbol(";") {
+			return nil, syntaxErrorf(len(sql), "statement %d: missing ';' terminator", len(stmts)+1)
+		}
+		if end == start {
+			start++
 			continue
 		}
-
-		if !unicode.IsLetter(r) && !unicode.IsDigit(r) && r != '_' {
-			return "", fmt.Errorf("invalid identifier %q", id)
+		p := &parser{toks: append(slices.Clone(toks[start:end+1]), toks[len(toks)-1]), src: sql}
+		stmt, err := p.parseStatement()
+		if err == nil && p.pos != end-start {
+			err = p.errorf("expected end of statement")
+		}
+		if err != nil {
+			return nil, fmt.Errorf("statement %d: %w", len(stmts)+1, err)
 		}
+		stmts = append(stmts, stmt)
+		start = end + 1
 	}
+	return stmts, nil
+}
+
+// parser is a recursive-descent parser over the token stream.
+type parser struct {
+	toks []token
+	pos  int
+	src  string // the input the token positions are offsets in
+	stmt string // statement being parsed, for error messages
 
-	return id, nil
+	params   int  // highest placeholder number so far
+	numbered bool // placeholders are "$n" rather than "?"
+
+	aggs *SelectStmt // the query whose SELECT list is being read, see expr
 }
 
-// Parse parses a single SQL statement into an AST.
-// Policy: statement MUST end with ';'
-func Parse(sql string) (Statement, error) {
-	s := strings.TrimSpace(sql)
-	if s == "" {
-		return nil, fmt.Errorf("empty statement")
+func (p *parser) peek() token { return p.toks[p.pos] }
+
+func (p *parser) next() token {
+	t := p.toks[p.pos]
+	if t.kind != tokEOF {
+		p.pos++
 	}
+	return t
+}
+
+// SyntaxError is SQL the parser rejects. Pos is the byte offset in the
+// input where it went wrong, -1 when the error is not about one place.
+type SyntaxError struct {
+	Pos int
+	Msg string
+}
+
+func (e *SyntaxError) Error() string { return e.Msg }
 
-	// Require ';' at the end (after trimming spaces/newlines)
-	if !strings.HasSuffix(s, ";") {
-		return nil, fmt.Errorf("missing ';' terminator")
+func syntaxErrorf(pos int, format string, args ...any) error {
+	return &SyntaxError{Pos: pos, Msg: fmt.Sprintf(format, args...)}
+}
+
+// errorf is a SyntaxError at the next token.
+func (p *parser) errorf(format string, args ...any) error {
+	msg := fmt.Sprintf(format, args...)
+	if p.stmt != "" {
+		return syntaxErrorf(p.peek().pos, "invalid %s syntax: %s, got %s", p.stmt, msg, p.peek())
 	}
+	return syntaxErrorf(p.peek().pos, "%s, got %s", msg, p.peek())
+}
 
-	// Strip the trailing ';' and trim again
-	s = strings.TrimSpace(strings.TrimSuffix(s, ";"))
-	if s == "" {
-		return nil, fmt.Errorf("empty statement")
+// acceptKeyword consumes kw if it is next.
+func (p *parser) acceptKeyword(kw string) bool {
+	if p.peek().isKeyword(kw) {
+		p.pos++
+		return true
 	}
+	return false
+}
 
-	up := strings.ToUpper(s)
+func (p *parser) expectKeyword(kw string) error {
+	if !p.acceptKeyword(kw) {
+		return p.errorf("expected %s", kw)
+	}
+	return nil
+}
 
-	switch {
-	// database
-	case strings.HasPrefix(up, "CREATE DATABASE"):
-		return parseCreateDatabase(s)
-	case strings.HasPrefix(up, "DROP DATABASE"):
-		return parseDropDatabase(s)
-	case strings.HasPrefix(up, "USE "):
-		return parseUseDatabase(s)
-
-	// table
-	case strings.HasPrefix(up, "CREATE TABLE"):
-		return parseCreateTable(s)
-	case strings.HasPrefix(up, "DROP TABLE"):
-		return parseDropTable(s)
-
-	case strings.HasPrefix(up, "INSERT INTO"):
-		return parseInsert(s)
-	case strings.HasPrefix(up, "SELECT"):
-		return parseSelect(s)
-	case strings.HasPrefix(up, "UPDATE"):
-		return parseUpdate(s)
-	case strings.HasPrefix(up, "DELETE FROM"):
-		return parseDelete(s)
+func (p *parser) acceptSymbol(s string) bool {
+	if p.peek().isSymbol(s) {
+		p.pos++
+		return true
+	}
+	return false
+}
 
-	default:
-		return nil, fmt.Errorf("unsupported statement: %q", sql)
+func (p *parser) expectSymbol(s string) error {
+	if !p.acceptSymbol(s) {
+		return p.errorf("expected %q", s)
 	}
+	return nil
 }
 
-func parseCreateDatabase(sql string) (Statement, error) {
-	rest := strings.TrimSpace(sql[len("CREATE DATABASE"):])
-	name, err := parseIdent(rest)
+// ident reads an identifier (db/table/column name).
+func (p *parser) ident(what string) (string, error) {
+	t := p.peek()
+	if t.kind != tokIdent {
+		return "", p.errorf("expected %s", what)
+	}
+	p.pos++
+	return t.text, nil
+}
+
+// str reads a quoted string.
+func (p *parser) str(what string) (string, error) {
+	t := p.peek()
+	if t.kind != tokString {
+		return "", p.errorf("expected %s", what)
+	}
+	p.pos++
+	return t.text, nil
+}
+
+func (p *parser) parseStatement() (Statement, error) {
+	t := p.peek()
+	switch {
+	case t.isKeyword("CREATE"):
+		p.next()
+		switch {
+		case p.acceptKeyword("DATABASE"):
+			p.stmt = "CREATE DATABASE"
+			name, err := p.ident("database name")
+			return &CreateDatabaseStmt{Name: name}, err
+		case p.acceptKeyword("TABLE"):
+			p.stmt = "CREATE TABLE"
+			return p.parseCreateTable()
+		case p.acceptKeyword("TEMP") || p.acceptKeyword("TEMPORARY"):
+			p.stmt = "CREATE TABLE"
+			if err := p.expectKeyword("TABLE"); err != nil {
+				return nil, err
+			}
+			stmt, err := p.parseCreateTable()
+			if s, ok := stmt.(*CreateTableStmt); ok {
+				s.Temp = true
+			}
+			return stmt, err
+		case p.acceptKeyword("INDEX"):
+			p.stmt = "CREATE INDEX"
+			return p.parseCreateIndex()
+		case p.acceptKeyword("VIEW"):
+			p.stmt = "CREATE VIEW"
+			return p.parseCreateView()
+		case p.acceptKeyword("UNIQUE"):
+			p.stmt = "CREATE INDEX"
+			if err := p.expectKeyword("INDEX"); err != nil {
+				return nil, err
+			}
+			stmt, err := p.parseCreateIndex()
+			if s, ok := stmt.(*CreateIndexStmt); ok {
+				s.Unique = true
+			}
+			return stmt, err
+		}
+	case t.isKeyword("DROP"):
+		p.next()
+		switch {
+		case p.acceptKeyword("DATABASE"):
+			p.stmt = "DROP DATABASE"
+			name, err := p.ident("database name")
+			return &DropDatabaseStmt{Name: name}, err
+		case p.acceptKeyword("TABLE"):
+			p.stmt = "DROP TABLE"
+			name, err := p.ident("table name")
+			if err != nil {
+				return nil, err
+			}
+			return &DropTableStmt{TableName: name, Purge: p.acceptKeyword("PURGE")}, nil
+		case p.acceptKeyword("INDEX"):
+			p.stmt = "DROP INDEX"
+			return p.parseDropIndex()
+		case p.acceptKeyword("VIEW"):
+			p.stmt = "DROP VIEW"
+			name, err := p.ident("view name")
+			return &DropViewStmt{Name: name}, err
+		}
+	case t.isKeyword("ALTER"):
+		p.next()
+		p.stmt = "ALTER TABLE"
+		if err := p.expectKeyword("TABLE"); err != nil {
+			return nil, err
+		}
+		return p.parseAlterTable()
+	case t.isKeyword("COPY"):
+		p.next()
+		p.stmt = "COPY"
+		return p.parseCopy()
+	case t.isKeyword("USE"):
+		p.next()
+		p.stmt = "USE"
+		name, err := p.ident("database name")
+		return &UseDatabaseStmt{Name: name}, err
+	case t.isKeyword("SET"):
+		p.next()
+		p.stmt = "SET"
+		return p.parseSet()
+	case t.isKeyword("SHOW"):
+		p.next()
+		p.stmt = "SHOW"
+		if p.acceptKeyword("ALL") {
+			return &ShowStmt{}, nil
+		}
+		name, err := p.ident("setting name")
+		return &ShowStmt{Name: name}, err
+	case t.isKeyword("VACUUM"):
+		p.next()
+		p.stmt = "VACUUM"
+		return &VacuumStmt{}, nil
+	case t.isKeyword("ANALYZE"):
+		p.next()
+		p.stmt = "ANALYZE"
+		if p.peek().kind != tokIdent {
+			return &AnalyzeStmt{}, nil
+		}
+		name, err := p.ident("table name")
+		return &AnalyzeStmt{TableName: name}, err
+	case t.isKeyword("EXPLAIN"):
+		p.next()
+		return p.parseExplain()
+	case t.isKeyword("DECLARE"):
+		p.next()
+		p.stmt = "DECLARE"
+		return p.parseDeclareCursor()
+	case t.isKeyword("FETCH"):
+		p.next()
+		p.stmt = "FETCH"
+		return p.parseFetch()
+	case t.isKeyword("CLOSE"):
+		p.next()
+		p.stmt = "CLOSE"
+		if p.acceptKeyword("ALL") {
+			return &CloseCursorStmt{}, nil
+		}
+		name, err := p.ident("cursor name")
+		return &CloseCursorStmt{Name: name}, err
+	case t.isKeyword("INSERT"):
+		p.next()
+		p.stmt = "INSERT"
+		return p.parseInsert()
+	case t.isKeyword("SELECT"):
+		p.next()
+		p.stmt = "SELECT"
+		return p.parseSelect()
+	case t.isKeyword("UPDATE"):
+		p.next()
+		p.stmt = "UPDATE"
+		return p.parseUpdate()
+	case t.isKeyword("DELETE"):
+		p.next()
+		p.stmt = "DELETE"
+		return p.parseDelete()
+	}
+	return nil, syntaxErrorf(t.pos, "unsupported statement starting at %s", t)
+}
+
+// CREATE VIEW v AS SELECT ...
+func (p *parser) parseCreateView() (Statement, error) {
+	name, err := p.ident("view name")
+	if err != nil {
+		return nil, err
+	}
+	if err := p.expectKeyword("AS"); err != nil {
+		return nil, err
+	}
+	start := p.peek().pos
+	if err := p.expectKeyword("SELECT"); err != nil {
+		return nil, err
+	}
+	stmt, err := p.parseSelect()
 	if err != nil {
-		return nil, fmt.Errorf("invalid CREATE DATABASE syntax: %w", err)
+		return nil, err
+	}
+	q, ok := stmt.(*SelectStmt)
+	if !ok {
+		return nil, p.errorf("expected a query")
+	}
+	if p.params > 0 {
+		return nil, syntaxErrorf(start, "a view cannot have placeholders")
 	}
-	return &CreateDatabaseStmt{Name: name}, nil
+	return &CreateViewStmt{Name: name, Query: q, SQL: p.src[start:p.toks[p.pos-1].end(p.src)]}, nil
 }
 
-func parseDropDatabase(sql string) (Statement, error) {
-	rest := strings.TrimSpace(sql[len("DROP DATABASE"):])
-	name, err := parseIdent(rest)
+// EXPLAIN ANALYZE SELECT * FROM users WHERE id = 1
+func (p *parser) parseExplain() (Statement, error) {
+	analyze := p.acceptKeyword("ANALYZE")
+	t := p.peek()
+	if !t.isKeyword("SELECT") && !t.isKeyword("INSERT") && !t.isKeyword("UPDATE") && !t.isKeyword("DELETE") {
+		p.stmt = "EXPLAIN"
+		return nil, p.errorf("expected SELECT, INSERT, UPDATE or DELETE")
+	}
+	stmt, err := p.parseStatement()
 	if err != nil {
-		return nil, fmt.Errorf("invalid DROP DATABASE syntax: %w", err)
+		return nil, err
 	}
-	return &DropDatabaseStmt{Name: name}, nil
+	return &ExplainStmt{Analyze: analyze, Stmt: stmt}, nil
 }
 
-func parseUseDatabase(sql string) (Statement, error) {
-	rest := strings.TrimSpace(sql[len("USE "):])
-	name, err := parseIdent(rest)
+// DECLARE c CURSOR FOR SELECT * FROM users ORDER BY id
+func (p *parser) parseDeclareCursor() (Statement, error) {
+	name, err := p.ident("cursor name")
+	if err != nil {
+		return nil, err
+	}
+	if err := p.expectKeyword("CURSOR"); err != nil {
+		return nil, err
+	}
+	if err := p.expectKeyword("FOR"); err != nil {
+		return nil, err
+	}
+	if err := p.expectKeyword("SELECT"); err != nil {
+		return nil, err
+	}
+	stmt, err := p.parseSelect()
 	if err != nil {
-		return nil, fmt.Errorf("invalid USE syntax: %w", err)
+		return nil, err
 	}
-	return &UseDatabaseStmt{Name: name}, nil
+	q, ok := stmt.(*SelectStmt)
+	if !ok {
+		return nil, p.errorf("expected a query")
+	}
+	return &DeclareCursorStmt{Name: name, Query: q}, nil
 }
 
-func parseCreateTable(sql string) (Statement, error) {
-	// Very naive: "CREATE TABLE users (id INT, name TEXT, active BOOL)"
-	withoutPrefix := strings.TrimSpace(sql[len("CREATE TABLE"):])
-	parts := strings.SplitN(withoutPrefix, "(", 2)
-	if len(parts) != 2 {
-		return nil, fmt.Errorf("invalid CREATE TABLE syntax")
+// FETCH 100 FROM c, FETCH NEXT IN c, FETCH ALL c
+func (p *parser) parseFetch() (Statement, error) {
+	s := &FetchStmt{Count: 1}
+	switch {
+	case p.acceptKeyword("NEXT"):
+	case p.acceptKeyword("ALL"):
+		s.Count = -1
+	case p.peek().kind == tokNumber:
+		n, err := p.intArg("row count")
+		if err != nil {
+			return nil, err
+		}
+		s.Count = n
 	}
+	if !p.acceptKeyword("FROM") {
+		p.acceptKeyword("IN")
+	}
+	var err error
+	s.Cursor, err = p.ident("cursor name")
+	return s, err
+}
 
-	tableName, err := parseIdent(parts[0])
+// SET sync_mode = normal
+// SET lock_wait_timeout TO '2s'
+//
+// The value is a word, a number or a quoted string.
+func (p *parser) parseSet() (Statement, error) {
+	name, err := p.ident("setting name")
 	if err != nil {
-		return nil, fmt.Errorf("invalid CREATE TABLE syntax: %w", err)
+		return nil, err
+	}
+	if !p.acceptSymbol("=") {
+		if err := p.expectKeyword("TO"); err != nil {
+			return nil, err
+		}
 	}
+	sign := ""
+	if p.acceptSymbol("-") {
+		sign = "-"
+	}
+	t := p.peek()
+	if t.kind != tokNumber && (sign != "" || (t.kind != tokIdent && t.kind != tokString)) {
+		return nil, p.errorf("expected setting value")
+	}
+	p.next()
+	return &SetStmt{Name: name, Value: sign + t.text}, nil
+}
 
-	defPart := strings.TrimSuffix(strings.TrimSpace(parts[1]), ")")
-	defPart = strings.TrimSpace(defPart)
-	if defPart == "" {
-		return nil, fmt.Errorf("invalid CREATE TABLE syntax: empty column list")
+// CREATE TABLE users (id INT PRIMARY KEY, name TEXT NOT NULL UNIQUE, team INT REFERENCES teams (id))
+// CREATE TEMP TABLE scratch (id INT, note TEXT)
+// CREATE TABLE sessions (id INT, expires_at TIMESTAMP) WITH (ttl_column = expires_at)
+//
+// A constraint may also follow the columns as "PRIMARY KEY (col)", "UNIQUE
+// (col)" or "FOREIGN KEY (col) REFERENCES ..."; constraints span a single
+// column.
+func (p *parser) parseCreateTable() (Statement, error) {
+	name, err := p.ident("table name")
+	if err != nil {
+		return nil, err
+	}
+	if err := p.expectSymbol("("); err != nil {
+		return nil, err
 	}
 
-	colDefs := strings.Split(defPart, ",")
 	var cols []ColumnDef
-	for _, def := range colDefs {
-		def = strings.TrimSpace(def)
-		toks := strings.Fields(def)
-		if len(toks) < 2 {
-			return nil, fmt.Errorf("invalid column def: %q", def)
+	for {
+		if t := p.peek(); t.isKeyword("PRIMARY") || t.isKeyword("UNIQUE") || t.isKeyword("FOREIGN") {
+			if err := p.parseTableConstraint(cols); err != nil {
+				return nil, err
+			}
+		} else {
+			def, err := p.parseColumnDef()
+			if err != nil {
+				return nil, err
+			}
+			cols = append(cols, def)
 		}
 
-		colName, err := parseIdent(toks[0])
-		if err != nil {
-			return nil, fmt.Errorf("invalid column name: %w", err)
+		if !p.acceptSymbol(",") {
+			break
 		}
-
-		cols = append(cols, ColumnDef{
-			Name: colName,
-			Type: strings.ToUpper(toks[1]),
-		})
 	}
+	if err := p.expectSymbol(")"); err != nil {
+		return nil, err
+	}
+	primaryKeys := 0
+	for _, c := range cols {
+		if c.PrimaryKey {
+			primaryKeys++
+		}
+		if c.Default != nil {
+			return nil, syntaxErrorf(-1, "invalid CREATE TABLE syntax: DEFAULT is only supported by ALTER TABLE ADD COLUMN")
+		}
+	}
+	if primaryKeys > 1 {
+		return nil, syntaxErrorf(-1, "invalid CREATE TABLE syntax: multiple primary keys for table %s", name)
+	}
+	stmt := &CreateTableStmt{TableName: name, Columns: cols}
+	if p.acceptKeyword("WITH") {
+		if err := p.parseTableOptions(stmt); err != nil {
+			return nil, err
+		}
+	}
+	return stmt, nil
+}
 
-	return &CreateTableStmt{
-		TableName: tableName,
-		Columns:   cols,
-	}, nil
+// parseTableOptions reads "(<option> = <value>, ...)" after CREATE TABLE
+// ... WITH.
+func (p *parser) parseTableOptions(stmt *CreateTableStmt) error {
+	if err := p.expectSymbol("("); err != nil {
+		return err
+	}
+	for {
+		var err error
+		switch {
+		case p.acceptKeyword("TTL_COLUMN"):
+			if err := p.expectSymbol("="); err != nil {
+				return err
+			}
+			stmt.TTLColumn, err = p.ident("column name")
+		default:
+			return p.errorf("expected TTL_COLUMN")
+		}
+		if err != nil {
+			return err
+		}
+		if !p.acceptSymbol(",") {
+			break
+		}
+	}
+	return p.expectSymbol(")")
 }
 
-func parseDropTable(sql string) (Statement, error) {
-	rest := strings.TrimSpace(sql[len("DROP TABLE"):])
-	name, err := parseIdent(rest)
+// ALTER TABLE users ADD COLUMN age INT NOT NULL DEFAULT 0
+// ALTER TABLE users DROP COLUMN age
+// ALTER TABLE users RENAME TO people
+func (p *parser) parseAlterTable() (Statement, error) {
+	name, err := p.ident("table name")
 	if err != nil {
-		return nil, fmt.Errorf("invalid DROP TABLE syntax: %w", err)
+		return nil, err
+	}
+	stmt := &AlterTableStmt{TableName: name}
+	switch {
+	case p.acceptKeyword("ADD"):
+		if p.peek().isKeyword("CONSTRAINT") || p.peek().isKeyword("FOREIGN") {
+			if err := p.parseAddForeignKey(stmt); err != nil {
+				return nil, err
+			}
+			return stmt, nil
+		}
+		p.acceptKeyword("COLUMN")
+		def, err := p.parseColumnDef()
+		if err != nil {
+			return nil, err
+		}
+		stmt.AddColumn = &def
+	case p.acceptKeyword("DROP"):
+		p.acceptKeyword("COLUMN")
+		stmt.DropColumn, err = p.ident("column name")
+	case p.acceptKeyword("RENAME"):
+		if err := p.expectKeyword("TO"); err != nil {
+			return nil, err
+		}
+		stmt.RenameTo, err = p.ident("table name")
+	default:
+		return nil, p.errorf("expected ADD, DROP or RENAME")
 	}
-	return &DropTableStmt{TableName: name}, nil
+	if err != nil {
+		return nil, err
+	}
+	return stmt, nil
 }
 
-func parseInsert(sql string) (Statement, error) {
-	// "INSERT INTO users VALUES (1, 'abc', true, null)"
-	rest := strings.TrimSpace(sql[len("INSERT INTO"):])
-
-	// Case-insensitive VALUES using splitKeyword.
-	tablePart, valPart := splitKeyword(rest, "VALUES")
-	if strings.TrimSpace(valPart) == "" {
-		return nil, fmt.Errorf("invalid INSERT syntax")
+// parseAddForeignKey reads "[CONSTRAINT <name>] FOREIGN KEY (<column>)
+// REFERENCES ..." after ALTER TABLE ... ADD.
+func (p *parser) parseAddForeignKey(stmt *AlterTableStmt) error {
+	var err error
+	if p.acceptKeyword("CONSTRAINT") {
+		if stmt.ForeignKeyName, err = p.ident("constraint name"); err != nil {
+			return err
+		}
+	}
+	if err := p.expectKeyword("FOREIGN"); err != nil {
+		return err
+	}
+	if err := p.expectKeyword("KEY"); err != nil {
+		return err
+	}
+	if err := p.expectSymbol("("); err != nil {
+		return err
+	}
+	if stmt.ForeignKeyColumn, err = p.ident("column name"); err != nil {
+		return err
 	}
+	if p.peek().isSymbol(",") {
+		return p.errorf("constraints on more than one column are not supported")
+	}
+	if err := p.expectSymbol(")"); err != nil {
+		return err
+	}
+	if err := p.expectKeyword("REFERENCES"); err != nil {
+		return err
+	}
+	stmt.AddForeignKey, err = p.parseReferences()
+	return err
+}
 
-	tableName, err := parseIdent(tablePart)
+// COPY users FROM 'users.csv' WITH (HEADER true, DELIMITER ';', NULL '\N')
+// COPY users TO 'users.csv'
+func (p *parser) parseCopy() (Statement, error) {
+	name, err := p.ident("table name")
 	if err != nil {
-		return nil, fmt.Errorf("invalid INSERT syntax: %w", err)
+		return nil, err
 	}
-
-	valPart = strings.TrimSpace(valPart)
-	if !strings.HasPrefix(valPart, "(") || !strings.HasSuffix(valPart, ")") {
-		return nil, fmt.Errorf("invalid INSERT values syntax")
+	stmt := &CopyStmt{TableName: name}
+	switch {
+	case p.acceptKeyword("FROM"):
+		stmt.From = true
+	case p.acceptKeyword("TO"):
+	default:
+		return nil, p.errorf("expected FROM or TO")
 	}
-	valPart = strings.TrimSpace(valPart[1 : len(valPart)-1])
-
-	rawVals := splitComma(valPart)
-	var exprs []Expr
-	for _, rv := range rawVals {
-		lit, err := parseLiteral(strings.TrimSpace(rv))
+	if stmt.File, err = p.str("file name"); err != nil {
+		return nil, err
+	}
+	if !p.acceptKeyword("WITH") && !p.peek().isSymbol("(") {
+		return stmt, nil
+	}
+	if err := p.expectSymbol("("); err != nil {
+		return nil, err
+	}
+	for {
+		switch {
+		case p.acceptKeyword("HEADER"):
+			stmt.Header = true
+			if t := p.peek(); t.isKeyword("TRUE") || t.isKeyword("FALSE") {
+				stmt.Header = p.next().isKeyword("TRUE")
+			}
+		case p.acceptKeyword("DELIMITER"):
+			stmt.Delimiter, err = p.str("delimiter")
+		case p.acceptKeyword("NULL"):
+			stmt.Null, err = p.str("NULL string")
+		default:
+			return nil, p.errorf("expected HEADER, DELIMITER or NULL")
+		}
 		if err != nil {
 			return nil, err
 		}
-		exprs = append(exprs, &LiteralExpr{Value: lit})
+		if !p.acceptSymbol(",") {
+			break
+		}
 	}
-
-	return &InsertStmt{
-		TableName: tableName,
-		Values:    exprs,
-	}, nil
+	if err := p.expectSymbol(")"); err != nil {
+		return nil, err
+	}
+	return stmt, nil
 }
 
-func parseSelect(sql string) (Statement, error) {
-	// "SELECT * FROM users [WHERE col = literal]"
-	up := strings.ToUpper(sql)
+// parseColumnDef reads "<name> <type>" and its constraints, in any order.
+func (p *parser) parseColumnDef() (ColumnDef, error) {
+	colName, err := p.ident("column name")
+	if err != nil {
+		return ColumnDef{}, err
+	}
+	def := ColumnDef{Name: colName}
+	if def.Type, def.Precision, def.Scale, err = p.typeName(); err != nil {
+		return ColumnDef{}, err
+	}
+	for {
+		switch {
+		case p.acceptKeyword("NOT"):
+			if err := p.expectKeyword("NULL"); err != nil {
+				return ColumnDef{}, err
+			}
+			def.NotNull = true
+		case p.acceptKeyword("NULL"): // explicit default
+		case p.acceptKeyword("PRIMARY"):
+			if err := p.expectKeyword("KEY"); err != nil {
+				return ColumnDef{}, err
+			}
+			def.PrimaryKey, def.NotNull = true, true
+		case p.acceptKeyword("UNIQUE"):
+			def.Unique = true
+		case p.acceptKeyword("AUTOINCREMENT"):
+			def.AutoIncrement = true
+		case p.acceptKeyword("REFERENCES"):
+			if def.References, err = p.parseReferences(); err != nil {
+				return ColumnDef{}, err
+			}
+		case p.acceptKeyword("DEFAULT"):
+			v, err := p.literal()
+			if err != nil {
+				return ColumnDef{}, err
+			}
+			def.Default = &LiteralExpr{Value: v}
+		case p.peek().isKeyword("COLLATE"):
+			if def.Collate, err = p.collateClause(); err != nil {
+				return ColumnDef{}, err
+			}
+		default:
+			return def, nil
+		}
+	}
+}
 
-	if !strings.HasPrefix(up, "SELECT * FROM ") {
-		return nil, fmt.Errorf("only SELECT * FROM <table> supported for now")
+// typeName reads a type, upper-cased, with the precision and scale of
+// "DECIMAL(p[, s])" or "NUMERIC(p[, s])".
+func (p *parser) typeName() (name string, precision, scale int, err error) {
+	if name, err = p.ident("column type"); err != nil {
+		return "", 0, 0, err
+	}
+	name = strings.ToUpper(name)
+	if !p.acceptSymbol("(") {
+		return name, 0, 0, nil
+	}
+	if name != "DECIMAL" && name != "NUMERIC" {
+		return "", 0, 0, p.errorf("type %s takes no precision", name)
 	}
+	if precision, err = p.intArg("precision"); err != nil {
+		return "", 0, 0, err
+	}
+	if p.acceptSymbol(",") {
+		if scale, err = p.intArg("scale"); err != nil {
+			return "", 0, 0, err
+		}
+	}
+	if precision < 1 || precision > record.MaxDecimalPrecision || scale > precision {
+		return "", 0, 0, p.errorf("%s(%d, %d) needs a precision from 1 to %d and a scale of at most that",
+			name, precision, scale, record.MaxDecimalPrecision)
+	}
+	return name, precision, scale, p.expectSymbol(")")
+}
 
-	rest := strings.TrimSpace(sql[len("SELECT * FROM "):])
-	tablePart, wherePart := splitKeyword(rest, "WHERE")
+// intArg reads a number without a sign or a fraction.
+func (p *parser) intArg(what string) (int, error) {
+	t := p.peek()
+	n, err := strconv.Atoi(t.text)
+	if t.kind != tokNumber || err != nil {
+		return 0, p.errorf("expected %s", what)
+	}
+	p.next()
+	return n, nil
+}
 
-	tableName, err := parseIdent(tablePart)
+// parseTableConstraint reads "PRIMARY KEY (col)", "UNIQUE (col)" or
+// "FOREIGN KEY (col) REFERENCES ..." and marks the column, which must be
+// defined before it.
+func (p *parser) parseTableConstraint(cols []ColumnDef) error {
+	kind := strings.ToUpper(p.next().text)
+	if kind != "UNIQUE" {
+		if err := p.expectKeyword("KEY"); err != nil {
+			return err
+		}
+	}
+	if err := p.expectSymbol("("); err != nil {
+		return err
+	}
+	col, err := p.ident("column name")
 	if err != nil {
-		return nil, fmt.Errorf("invalid SELECT syntax: %w", err)
+		return err
+	}
+	if p.peek().isSymbol(",") {
+		return p.errorf("constraints on more than one column are not supported")
+	}
+	if err := p.expectSymbol(")"); err != nil {
+		return err
+	}
+	var ref *ForeignKeyDef
+	if kind == "FOREIGN" {
+		if err := p.expectKeyword("REFERENCES"); err != nil {
+			return err
+		}
+		if ref, err = p.parseReferences(); err != nil {
+			return err
+		}
+	}
+	i := slices.IndexFunc(cols, func(c ColumnDef) bool { return c.Name == col })
+	if i < 0 {
+		return syntaxErrorf(p.peek().pos, "invalid CREATE TABLE syntax: constraint on unknown column %s", col)
+	}
+	switch kind {
+	case "PRIMARY":
+		cols[i].PrimaryKey, cols[i].NotNull = true, true
+	case "UNIQUE":
+		cols[i].Unique = true
+	default:
+		cols[i].References = ref
 	}
+	return nil
+}
 
-	var w *WhereEq
-	if strings.TrimSpace(wherePart) != "" {
-		we, err := parseWhereEq(wherePart)
-		if err != nil {
+// parseReferences reads what follows REFERENCES: "<table> [(<column>)] [ON
+// DELETE RESTRICT | CASCADE | SET NULL | NO ACTION]".
+func (p *parser) parseReferences() (*ForeignKeyDef, error) {
+	ref := &ForeignKeyDef{}
+	var err error
+	if ref.Table, err = p.ident("referenced table name"); err != nil {
+		return nil, err
+	}
+	if p.acceptSymbol("(") {
+		if ref.Column, err = p.ident("referenced column name"); err != nil {
+			return nil, err
+		}
+		if err := p.expectSymbol(")"); err != nil {
+			return nil, err
+		}
+	}
+	if !p.acceptKeyword("ON") {
+		return ref, nil
+	}
+	if err := p.expectKeyword("DELETE"); err != nil {
+		return nil, err
+	}
+	switch {
+	case p.acceptKeyword("RESTRICT"):
+		ref.OnDelete = "RESTRICT"
+	case p.acceptKeyword("CASCADE"):
+		ref.OnDelete = "CASCADE"
+	case p.acceptKeyword("SET"):
+		if err := p.expectKeyword("NULL"); err != nil {
 			return nil, err
 		}
-		w = we
+		ref.OnDelete = "SET NULL"
+	case p.acceptKeyword("NO"):
+		if err := p.expectKeyword("ACTION"); err != nil {
+			return nil, err
+		}
+		ref.OnDelete = "RESTRICT"
+	default:
+		return nil, p.errorf("expected RESTRICT, CASCADE, SET NULL or NO ACTION")
 	}
+	return ref, nil
+}
 
-	return &SelectStmt{TableName: tableName, Where: w}, nil
+// CREATE INDEX users_name ON users (name)
+// CREATE INDEX posts_body ON posts USING fulltext (body)
+func (p *parser) parseCreateIndex() (Statement, error) {
+	s := &CreateIndexStmt{}
+	var err error
+	if s.IndexName, err = p.ident("index name"); err != nil {
+		return nil, err
+	}
+	if err := p.expectKeyword("ON"); err != nil {
+		return nil, err
+	}
+	if s.TableName, err = p.ident("table name"); err != nil {
+		return nil, err
+	}
+	if p.acceptKeyword("USING") {
+		switch {
+		case p.acceptKeyword("BTREE"):
+		case p.acceptKeyword("FULLTEXT"):
+			s.FullText = true
+		default:
+			return nil, p.errorf("expected BTREE or FULLTEXT")
+		}
+	}
+	if err := p.expectSymbol("("); err != nil {
+		return nil, err
+	}
+	if p.peek().kind == tokIdent && p.toks[p.pos+1].isSymbol(")") {
+		s.Column = p.next().text
+	} else {
+		start := p.peek().pos
+		if s.Expr, err = p.expr(); err != nil {
+			return nil, err
+		}
+		if hasParam(s.Expr) {
+			return nil, syntaxErrorf(start, "an index expression cannot have placeholders")
+		}
+	}
+	if err := p.expectSymbol(")"); err != nil {
+		return nil, err
+	}
+	return s, nil
 }
 
-func parseUpdate(sql string) (Statement, error) {
-	// "UPDATE t SET a=1, b='x' [WHERE id=1]"
-	rest := strings.TrimSpace(sql[len("UPDATE"):])
-	tablePart, afterTable := splitKeyword(rest, "SET")
+// DROP INDEX users_name ON users
+func (p *parser) parseDropIndex() (Statement, error) {
+	s := &DropIndexStmt{}
+	var err error
+	if s.IndexName, err = p.ident("index name"); err != nil {
+		return nil, err
+	}
+	if err := p.expectKeyword("ON"); err != nil {
+		return nil, err
+	}
+	if s.TableName, err = p.ident("table name"); err != nil {
+		return nil, err
+	}
+	return s, nil
+}
 
-	tableName, err := parseIdent(tablePart)
+// INSERT INTO users VALUES (1, 'abc', true, null) [RETURNING * | col, ...]
+func (p *parser) parseInsert() (Statement, error) {
+	if err := p.expectKeyword("INTO"); err != nil {
+		return nil, err
+	}
+	name, err := p.ident("table name")
 	if err != nil {
-		return nil, fmt.Errorf("invalid UPDATE syntax: %w", err)
+		return nil, err
 	}
-
-	setPart, wherePart := splitKeyword(afterTable, "WHERE")
-	setPart = strings.TrimSpace(setPart)
-	if setPart == "" {
-		return nil, fmt.Errorf("invalid UPDATE syntax: missing SET")
+	var cols []string
+	if p.acceptSymbol("(") {
+		for {
+			col, err := p.ident("column name")
+			if err != nil {
+				return nil, err
+			}
+			if slices.Contains(cols, col) {
+				return nil, syntaxErrorf(p.peek().pos, "invalid INSERT syntax: column %s listed twice", col)
+			}
+			cols = append(cols, col)
+			if !p.acceptSymbol(",") {
+				break
+			}
+		}
+		if err := p.expectSymbol(")"); err != nil {
+			return nil, err
+		}
+	}
+	if err := p.expectKeyword("VALUES"); err != nil {
+		return nil, err
+	}
+	if err := p.expectSymbol("("); err != nil {
+		return nil, err
 	}
 
-	assignStrs := splitComma(setPart)
-	assigns := make([]Assignment, 0, len(assignStrs))
-	for _, a := range assignStrs {
-		a = strings.TrimSpace(a)
-		kv := strings.SplitN(a, "=", 2)
-		if len(kv) != 2 {
-			return nil, fmt.Errorf("invalid assignment: %q", a)
+	var values []Expr
+	for {
+		e, err := p.parseLiteralExpr()
+		if err != nil {
+			return nil, err
 		}
+		values = append(values, e)
+		if !p.acceptSymbol(",") {
+			break
+		}
+	}
+	if err := p.expectSymbol(")"); err != nil {
+		return nil, err
+	}
+	if cols != nil && len(cols) != len(values) {
+		return nil, syntaxErrorf(p.peek().pos, "invalid INSERT syntax: %d columns but %d values", len(cols), len(values))
+	}
+	ret, err := p.parseReturning()
+	if err != nil {
+		return nil, err
+	}
+	return &InsertStmt{TableName: name, Columns: cols, Values: values, Returning: ret}, nil
+}
 
-		col, err := parseIdent(kv[0])
+// parseReturning reads "RETURNING * | col, ..." if present.
+func (p *parser) parseReturning() ([]string, error) {
+	if !p.acceptKeyword("RETURNING") {
+		return nil, nil
+	}
+	if p.acceptSymbol("*") {
+		return []string{"*"}, nil
+	}
+	var cols []string
+	for {
+		col, err := p.ident("column name")
 		if err != nil {
-			return nil, fmt.Errorf("invalid assignment column: %w", err)
+			return nil, err
+		}
+		cols = append(cols, col)
+		if !p.acceptSymbol(",") {
+			return cols, nil
 		}
+	}
+}
 
-		valRaw := strings.TrimSpace(kv[1])
-		lit, err := parseLiteral(valRaw)
-		if err != nil {
+// SELECT * | item, ... FROM t [alias] [join ...] [WHERE cond]
+// [GROUP BY col, ...] [HAVING term <op> lit] [ORDER BY term [ASC|DESC], ...]
+// [LIMIT n]
+//
+// An item is an expression with an optional "AS alias" (see selectItem), a
+// term is a column or an aggregate call (see selectTerm), a join is
+// "[INNER | LEFT [OUTER]] JOIN t [alias] ON col <op> col [AND ...]".
+func (p *parser) parseSelect() (Statement, error) {
+	if p.peek().isKeyword("LAST_INSERT_ROWID") && p.toks[p.pos+1].isSymbol("(") {
+		p.pos += 2
+		return &LastInsertRowIDStmt{}, p.expectSymbol(")")
+	}
+	s := &SelectStmt{}
+	if !p.acceptSymbol("*") {
+		for {
+			if err := p.selectItem(s); err != nil {
+				return nil, err
+			}
+			if !p.acceptSymbol(",") {
+				break
+			}
+		}
+	}
+
+	if err := p.expectKeyword("FROM"); err != nil {
+		return nil, err
+	}
+	var err error
+	if s.TableName, err = p.ident("table name"); err != nil {
+		return nil, err
+	}
+	if s.Alias, err = p.optionalAlias(); err != nil {
+		return nil, err
+	}
+joins:
+	for {
+		j := JoinClause{}
+		switch {
+		case p.acceptKeyword("LEFT"):
+			p.acceptKeyword("OUTER")
+			j.Left = true
+		case p.acceptKeyword("INNER"):
+		case p.peek().isKeyword("JOIN"):
+		default:
+			break joins
+		}
+		if err := p.expectKeyword("JOIN"); err != nil {
+			return nil, err
+		}
+		if err := p.parseJoin(&j); err != nil {
 			return nil, err
 		}
+		s.Joins = append(s.Joins, j)
+	}
 
-		assigns = append(assigns, Assignment{
-			Column: col,
-			Value:  &LiteralExpr{Value: lit},
-		})
+	if s.Where, err = p.parseOptionalWhere(); err != nil {
+		return nil, err
 	}
 
-	var w *WhereEq
-	if strings.TrimSpace(wherePart) != "" {
-		we, err := parseWhereEq(wherePart)
+	if p.acceptKeyword("GROUP") {
+		if err := p.expectKeyword("BY"); err != nil {
+			return nil, err
+		}
+		for {
+			col, err := p.columnRef("GROUP BY column")
+			if err != nil {
+				return nil, err
+			}
+			s.GroupBy = append(s.GroupBy, col)
+			if !p.acceptSymbol(",") {
+				break
+			}
+		}
+	}
+	if p.acceptKeyword("HAVING") {
+		col, err := p.selectTerm(s, "HAVING column or aggregate")
 		if err != nil {
 			return nil, err
 		}
-		w = we
+		if s.Having, err = p.comparison(col); err != nil {
+			return nil, err
+		}
+	}
+
+	if p.acceptKeyword("ORDER") {
+		if err := p.expectKeyword("BY"); err != nil {
+			return nil, err
+		}
+		for {
+			col, err := p.selectTerm(s, "ORDER BY column")
+			if err != nil {
+				return nil, err
+			}
+			item := OrderByItem{Column: col}
+			if item.Collate, err = p.collateClause(); err != nil {
+				return nil, err
+			}
+			if p.acceptKeyword("DESC") {
+				item.Desc = true
+			} else {
+				p.acceptKeyword("ASC")
+			}
+			s.OrderBy = append(s.OrderBy, item)
+			if !p.acceptSymbol(",") {
+				break
+			}
+		}
+	}
+
+	if p.acceptKeyword("LIMIT") {
+		t := p.peek()
+		n, err := strconv.ParseInt(t.text, 10, 64)
+		if t.kind != tokNumber || err != nil {
+			return nil, p.errorf("expected non-negative integer LIMIT")
+		}
+		p.next()
+		s.Limit = &n
+	}
+	return s, nil
+}
+
+// clauseKeywords end a table reference: any other identifier right after a
+// table name is its alias.
+var clauseKeywords = []string{"WHERE", "JOIN", "INNER", "LEFT", "ON", "GROUP", "HAVING", "ORDER", "LIMIT"}
+
+// optionalAlias reads "[AS] alias" after a table name, if present.
+func (p *parser) optionalAlias() (string, error) {
+	if p.acceptKeyword("AS") {
+		return p.ident("alias")
+	}
+	t := p.peek()
+	if t.kind != tokIdent {
+		return "", nil
+	}
+	for _, kw := range clauseKeywords {
+		if t.isKeyword(kw) {
+			return "", nil
+		}
+	}
+	p.next()
+	return t.text, nil
+}
+
+// parseJoin reads "t [alias] ON col <op> col [AND ...]" after JOIN.
+func (p *parser) parseJoin(j *JoinClause) error {
+	var err error
+	if j.TableName, err = p.ident("table name"); err != nil {
+		return err
+	}
+	if j.Alias, err = p.optionalAlias(); err != nil {
+		return err
+	}
+	if err := p.expectKeyword("ON"); err != nil {
+		return err
+	}
+	for {
+		var c JoinCond
+		if c.Left, err = p.columnRef("ON column"); err != nil {
+			return err
+		}
+		if c.Op, err = p.compareOp(); err != nil {
+			return err
+		}
+		if c.Right, err = p.columnRef("ON column"); err != nil {
+			return err
+		}
+		j.On = append(j.On, c)
+		if !p.acceptKeyword("AND") {
+			return nil
+		}
+	}
+}
+
+// columnRef reads a column name, optionally qualified by a table name or
+// alias: "id", "u.id".
+func (p *parser) columnRef(what string) (string, error) {
+	name, err := p.ident(what)
+	if err != nil || !p.acceptSymbol(".") {
+		return name, err
+	}
+	col, err := p.ident("column name after '.'")
+	if err != nil {
+		return "", err
+	}
+	return name + "." + col, nil
+}
+
+// aggregateFuncs are the aggregate functions a select term may call.
+var aggregateFuncs = []string{"COUNT", "SUM", "AVG", "MIN", "MAX"}
+
+// IsAggregate reports whether name (in any case) is a built-in aggregate.
+func IsAggregate(name string) bool { return slices.Contains(aggregateFuncs, strings.ToUpper(name)) }
+
+// selectTerm reads a column reference or an aggregate call: COUNT(*) or one
+// of aggregateFuncs over a column. Calls are added to s.Aggregates and the
+// term is their name.
+func (p *parser) selectTerm(s *SelectStmt, what string) (string, error) {
+	fn := strings.ToUpper(p.peek().text)
+	if p.peek().kind != tokIdent || !slices.Contains(aggregateFuncs, fn) || !p.toks[p.pos+1].isSymbol("(") {
+		return p.columnRef(what)
 	}
+	p.pos += 2
 
-	return &UpdateStmt{
-		TableName:   tableName,
-		Assignments: assigns,
-		Where:       w,
-	}, nil
+	agg := Aggregate{Func: fn}
+	var err error
+	if fn == "COUNT" && p.acceptSymbol("*") {
+		agg.Column = "*"
+	} else if agg.Column, err = p.columnRef("aggregate column"); err != nil {
+		return "", err
+	}
+	if err := p.expectSymbol(")"); err != nil {
+		return "", err
+	}
+	if !slices.Contains(s.Aggregates, agg) {
+		s.Aggregates = append(s.Aggregates, agg)
+	}
+	return agg.Name(), nil
 }
 
-func parseDelete(sql string) (Statement, error) {
-	// "DELETE FROM t [WHERE col=literal]"
-	rest := strings.TrimSpace(sql[len("DELETE FROM"):])
-	tablePart, wherePart := splitKeyword(rest, "WHERE")
+// selectItem reads an entry of the SELECT list into s: a column or an
+// aggregate goes to s.Columns as it is, anything else, or an entry with an
+// alias, to s.Exprs as well.
+func (p *parser) selectItem(s *SelectStmt) error {
+	start := p.peek().pos
+	p.aggs = s
+	e, err := p.expr()
+	p.aggs = nil
+	if err != nil {
+		return err
+	}
+	name := p.src[start:p.toks[p.pos-1].end(p.src)]
+	if p.acceptKeyword("AS") {
+		if name, err = p.ident("alias"); err != nil {
+			return err
+		}
+	} else if c, ok := e.(*ColumnExpr); ok {
+		s.Columns = append(s.Columns, c.Name)
+		return nil
+	}
+	if hasParam(e) {
+		return syntaxErrorf(start, "placeholders are not supported in the SELECT list")
+	}
+	s.Exprs = append(s.Exprs, SelectExpr{Index: len(s.Columns), Expr: e})
+	s.Columns = append(s.Columns, name)
+	return nil
+}
 
-	tableName, err := parseIdent(tablePart)
+// UPDATE t SET a=1, b='x' [WHERE id=1] [RETURNING * | col, ...]
+func (p *parser) parseUpdate() (Statement, error) {
+	name, err := p.ident("table name")
 	if err != nil {
-		return nil, fmt.Errorf("invalid DELETE syntax: %w", err)
+		return nil, err
+	}
+	if err := p.expectKeyword("SET"); err != nil {
+		return nil, err
 	}
 
-	var w *WhereEq
-	if strings.TrimSpace(wherePart) != "" {
-		we, err := parseWhereEq(wherePart)
+	var assigns []Assignment
+	for {
+		col, err := p.ident("assignment column")
 		if err != nil {
 			return nil, err
 		}
-		w = we
+		if err := p.expectSymbol("="); err != nil {
+			return nil, err
+		}
+		v, err := p.parseLiteralExpr()
+		if err != nil {
+			return nil, err
+		}
+		assigns = append(assigns, Assignment{Column: col, Value: v})
+		if !p.acceptSymbol(",") {
+			break
+		}
 	}
 
-	return &DeleteStmt{TableName: tableName, Where: w}, nil
+	w, err := p.parseOptionalWhere()
+	if err != nil {
+		return nil, err
+	}
+	ret, err := p.parseReturning()
+	if err != nil {
+		return nil, err
+	}
+	return &UpdateStmt{TableName: name, Assignments: assigns, Where: w, Returning: ret}, nil
 }
 
-func parseWhereEq(s string) (*WhereEq, error) {
-	// very naive: "col = literal"
-	s = strings.TrimSpace(s)
-	kv := strings.SplitN(s, "=", 2)
-	if len(kv) != 2 {
-		return nil, fmt.Errorf("only WHERE <col> = <literal> supported")
+// DELETE FROM t [WHERE col=literal] [RETURNING * | col, ...]
+func (p *parser) parseDelete() (Statement, error) {
+	if err := p.expectKeyword("FROM"); err != nil {
+		return nil, err
 	}
+	name, err := p.ident("table name")
+	if err != nil {
+		return nil, err
+	}
+	w, err := p.parseOptionalWhere()
+	if err != nil {
+		return nil, err
+	}
+	ret, err := p.parseReturning()
+	if err != nil {
+		return nil, err
+	}
+	return &DeleteStmt{TableName: name, Where: w, Returning: ret}, nil
+}
+
+// whereOps are the comparisons WHERE accepts.
+var whereOps = []string{"=", "<", "<=", ">", ">="}
 
-	col, err := parseIdent(kv[0])
+// parseOptionalWhere reads "WHERE col [NOT] IN (SELECT ...)", "WHERE [NOT]
+// EXISTS (SELECT ...)", "WHERE col <op> (SELECT ...)" or "WHERE <cond>" if
+// present.
+func (p *parser) parseOptionalWhere() (*Where, error) {
+	if !p.acceptKeyword("WHERE") {
+		return nil, nil
+	}
+	if p.peek().isKeyword("EXISTS") || p.peek().isKeyword("NOT") && p.toks[p.pos+1].isKeyword("EXISTS") {
+		op := "EXISTS"
+		if p.acceptKeyword("NOT") {
+			op = "NOT EXISTS"
+		}
+		p.next()
+		sub, err := p.subquery()
+		if err != nil {
+			return nil, err
+		}
+		return &Where{Op: op, Subquery: sub}, nil
+	}
+	start := p.pos
+	if col, err := p.columnRef("WHERE column"); err == nil {
+		op := "IN"
+		t := p.peek()
+		switch {
+		case p.acceptKeyword("IN"):
+		case t.isKeyword("NOT") && p.toks[p.pos+1].isKeyword("IN"):
+			p.pos += 2
+			op = "NOT IN"
+		case t.kind == tokSymbol && slices.Contains(whereOps, t.text) &&
+			p.toks[p.pos+1].isSymbol("(") && p.toks[p.pos+2].isKeyword("SELECT"):
+			return p.comparison(col)
+		default:
+			op = ""
+		}
+		if op != "" {
+			sub, err := p.subquery()
+			if err != nil {
+				return nil, err
+			}
+			return &Where{Column: col, Op: op, Subquery: sub}, nil
+		}
+	}
+	p.pos = start
+	pos := p.peek().pos
+	e, err := p.expr()
 	if err != nil {
-		return nil, fmt.Errorf("invalid WHERE column: %w", err)
+		return nil, err
 	}
+	return whereOf(e, pos)
+}
 
-	valRaw := strings.TrimSpace(kv[1])
-	lit, err := parseLiteral(valRaw)
+// whereOf is the Where of condition e, found at pos. A comparison of a
+// column with a literal, a placeholder or another column stays one, which
+// the planner may use an index for, or correlate a subquery with; anything
+// else is kept as an expression, which may not have placeholders.
+func whereOf(e Expr, pos int) (*Where, error) {
+	if b, ok := e.(*BinaryExpr); ok && slices.Contains(whereOps, b.Op) {
+		col, ok := b.Left.(*ColumnExpr)
+		switch b.Right.(type) {
+		case *LiteralExpr, *ParamExpr, *ColumnExpr:
+			if ok {
+				return &Where{Column: col.Name, Op: b.Op, Value: b.Right}, nil
+			}
+		}
+	}
+	if hasParam(e) {
+		return nil, syntaxErrorf(pos, "placeholders are only supported in a comparison of a column with a value")
+	}
+	return &Where{Expr: e}, nil
+}
+
+// comparison reads "<op> value" following col, where value is a literal, a
+// placeholder, a column or a subquery.
+func (p *parser) comparison(col string) (*Where, error) {
+	op, err := p.compareOp()
+	if err != nil {
+		return nil, err
+	}
+	w := &Where{Column: col, Op: op}
+	t := p.peek()
+	switch {
+	case t.isSymbol("(") && p.toks[p.pos+1].isKeyword("SELECT"):
+		w.Subquery, err = p.subquery()
+	case t.kind == tokIdent && !t.isKeyword("NULL") && !t.isKeyword("TRUE") && !t.isKeyword("FALSE") &&
+		!p.atTypedLiteral():
+		var name string
+		name, err = p.columnRef("column")
+		w.Value = &ColumnExpr{Name: name}
+	default:
+		w.Value, err = p.parseLiteralExpr()
+	}
 	if err != nil {
 		return nil, err
 	}
+	return w, nil
+}
 
-	return &WhereEq{
-		Column: col,
-		Value:  &LiteralExpr{Value: lit},
-	}, nil
+// subquery reads "(SELECT ...)". Its placeholders are numbered along with
+// those of the enclosing statement.
+func (p *parser) subquery() (*SelectStmt, error) {
+	if err := p.expectSymbol("("); err != nil {
+		return nil, err
+	}
+	if err := p.expectKeyword("SELECT"); err != nil {
+		return nil, err
+	}
+	stmt, err := p.parseSelect()
+	if err != nil {
+		return nil, err
+	}
+	s, ok := stmt.(*SelectStmt)
+	if !ok {
+		return nil, p.errorf("expected a query in the subquery")
+	}
+	return s, p.expectSymbol(")")
 }
 
-func parseLiteral(rv string) (any, error) {
-	up := strings.ToUpper(rv)
+// compareOp reads one of whereOps.
+func (p *parser) compareOp() (string, error) {
+	op := p.peek()
+	if op.kind != tokSymbol || !slices.Contains(whereOps, op.text) {
+		return "", p.errorf("expected comparison (= < <= > >=)")
+	}
+	p.next()
+	return op.text, nil
+}
 
-	// NULL
-	if up == "NULL" {
-		return nil, nil
+// parseLiteralExpr reads a literal or a placeholder.
+func (p *parser) parseLiteralExpr() (Expr, error) {
+	t := p.peek()
+	switch {
+	case t.isSymbol("?"):
+		if p.numbered {
+			return nil, p.errorf("cannot mix ? and $n placeholders")
+		}
+		p.next()
+		p.params++
+		return &ParamExpr{Index: p.params}, nil
+	case t.kind == tokParam:
+		n, err := strconv.Atoi(t.text)
+		if err != nil || n < 1 {
+			return nil, p.errorf("invalid placeholder $%s", t.text)
+		}
+		if p.params > 0 && !p.numbered {
+			return nil, p.errorf("cannot mix ? and $n placeholders")
+		}
+		p.next()
+		p.numbered = true
+		p.params = max(p.params, n)
+		return &ParamExpr{Index: n}, nil
 	}
 
-	// BOOL
-	if up == "TRUE" {
-		return true, nil
+	v, err := p.literal()
+	if err != nil {
+		return nil, err
 	}
-	if up == "FALSE" {
+	return &LiteralExpr{Value: v}, nil
+}
+
+// literal reads NULL, TRUE/FALSE, a quoted string, an optionally negative
+// number (int64 without a fraction, float64 with one) or a typed literal.
+func (p *parser) literal() (any, error) {
+	t := p.peek()
+	switch {
+	case p.atTypedLiteral():
+		return p.typedLiteral()
+	case t.isKeyword("NULL"):
+		p.next()
+		return nil, nil
+	case t.isKeyword("TRUE"):
+		p.next()
+		return true, nil
+	case t.isKeyword("FALSE"):
+		p.next()
 		return false, nil
+	case t.kind == tokString:
+		p.next()
+		return t.text, nil
 	}
 
-	// STRING (single quotes)
-	if len(rv) >= 2 && rv[0] == '\'' && rv[len(rv)-1] == '\'' {
-		// NOTE: minimal; no escape support yet
-		return rv[1 : len(rv)-1], nil
+	text := ""
+	if t.isSymbol("-") {
+		p.next()
+		text = "-"
 	}
-
-	// INT64
-	if i, err := strconv.ParseInt(rv, 10, 64); err == nil {
-		return i, nil
+	t = p.peek()
+	if t.kind == tokNumber {
+		num := text + t.text
+		if strings.Contains(num, ".") {
+			if f, err := strconv.ParseFloat(num, 64); err == nil {
+				p.next()
+				return f, nil
+			}
+		} else if i, err := strconv.ParseInt(num, 10, 64); err == nil {
+			p.next()
+			return i, nil
+		}
+		return nil, syntaxErrorf(p.peek().pos, "unsupported literal: %q", num)
 	}
+	return nil, syntaxErrorf(p.peek().pos, "unsupported literal: %s", t)
+}
 
-	return nil, fmt.Errorf("unsupported literal: %q", rv)
+// atTypedLiteral reports whether a typed literal comes next: DATE, TIME,
+// TIMESTAMP or DECIMAL followed by a quoted string, or INTERVAL followed by
+// a quoted string or a number.
+func (p *parser) atTypedLiteral() bool {
+	t, next := p.peek(), p.toks[min(p.pos+1, len(p.toks)-1)]
+	switch {
+	case t.isKeyword("DATE"), t.isKeyword("TIME"), t.isKeyword("TIMESTAMP"), t.isKeyword("DECIMAL"):
+		return next.kind == tokString
+	case t.isKeyword("INTERVAL"):
+		return next.kind == tokString || next.kind == tokNumber || next.isSymbol("-")
+	}
+	return false
 }
 
-// splitKeyword splits "X <keyword> Y" case-insensitively.
-// returns (X, Y). If keyword not present => (s, "").
-//
-// NOTE (phase 1 limitation):
-//   - requires spaces around keyword (" WHERE ").
-func splitKeyword(s, keyword string) (string, string) {
-	up := strings.ToUpper(s)
-	k := " " + strings.ToUpper(keyword) + " "
-	idx := strings.Index(up, k)
-	if idx < 0 {
-		return s, ""
-	}
-	left := strings.TrimSpace(s[:idx])
-	right := strings.TrimSpace(s[idx+len(k):])
-	return left, right
-}
-
-// splitComma splits a comma-separated list, ignoring commas inside quotes (simple version).
-func splitComma(s string) []string {
-	parts := []string{}
-	cur := strings.Builder{}
-	inQuote := false
-	for _, r := range s {
-		switch r {
-		case '\'':
-			inQuote = !inQuote
-			cur.WriteRune(r)
-		case ',':
-			if inQuote {
-				cur.WriteRune(r)
-			} else {
-				parts = append(parts, cur.String())
-				cur.Reset()
+// typedLiteral reads "DATE '2006-01-02'", "TIME '15:04:05'", "TIMESTAMP
+// '2006-01-02 15:04:05'", "DECIMAL '12.50'", "INTERVAL '1 day 2 hours'" or
+// "INTERVAL -3 DAY", as the record value it stands for.
+func (p *parser) typedLiteral() (any, error) {
+	t := p.next()
+	text := p.next().text
+	var v any
+	var err error
+	switch strings.ToUpper(t.text) {
+	case "DATE":
+		v, err = record.ParseDate(text)
+	case "TIME":
+		v, err = record.ParseTime(text)
+	case "TIMESTAMP":
+		v, err = record.ParseTimestamp(text)
+	case "DECIMAL":
+		v, err = record.ParseDecimal(text)
+	default:
+		if text == "-" {
+			text += p.next().text
+		}
+		if p.toks[p.pos-1].kind == tokNumber {
+			unit := p.next()
+			if unit.kind != tokIdent {
+				return nil, syntaxErrorf(unit.pos, "expected an interval unit, got %s", unit)
 			}
-		default:
-			cur.WriteRune(r)
+			text += " " + unit.text
 		}
+		v, err = record.ParseInterval(text)
+	}
+	if err != nil {
+		return nil, syntaxErrorf(t.pos, "invalid %s literal %q", strings.ToUpper(t.text), text)
+	}
+	return v, nil
+}
+
+// parseLiteral parses a standalone literal such as "'abc'" or "-7".
+func parseLiteral(rv string) (any, error) {
+	toks, err := lex(rv)
+	if err != nil {
+		return nil, err
+	}
+	p := &parser{toks: toks}
+	v, err := p.literal()
+	if err != nil {
+		return nil, err
 	}
-	if cur.Len() > 0 {
-		parts = append(parts, cur.String())
+	if p.peek().kind != tokEOF {
+		return nil, syntaxErrorf(-1, "unsupported literal: %q", rv)
 	}
-	return parts
+	return v, nil
 }
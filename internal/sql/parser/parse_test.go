@@ -1,11 +1,14 @@
 package parser
 
 import (
+	"errors"
 	"reflect"
 	"testing"
 
 	"github.com/stretchr/testify/assert"
 	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/record"
 )
 
 func TestParse_RequireSemicolon(t *testing.T) {
@@ -14,6 +17,23 @@ func TestParse_RequireSemicolon(t *testing.T) {
 	require.Contains(t, err.Error(), "missing ';'")
 }
 
+func TestParse_SyntaxErrorPos(t *testing.T) {
+	for _, tc := range []struct {
+		sql string
+		pos int
+	}{
+		{"SELECT * FROM users WHERE id = ;", 31},
+		{"SELECT 'abc;", 7},
+		{"SELECT 1", 8},
+		{"GRANT ALL;", 0},
+	} {
+		_, err := Parse(tc.sql)
+		var se *SyntaxError
+		require.ErrorAs(t, err, &se, tc.sql)
+		assert.Equal(t, tc.pos, se.Pos, tc.sql)
+	}
+}
+
 func TestParse_CreateDatabase(t *testing.T) {
 	stmt, err := Parse("CREATE DATABASE testdb;")
 	require.NoError(t, err)
@@ -69,6 +89,54 @@ func TestParse_CreateTable(t *testing.T) {
 	assert.Equal(t, ColumnDef{Name: "id", Type: "INT"}, s.Columns[0])
 	assert.Equal(t, ColumnDef{Name: "name", Type: "TEXT"}, s.Columns[1])
 	assert.Equal(t, ColumnDef{Name: "active", Type: "BOOL"}, s.Columns[2])
+
+	stmt, err = Parse("CREATE TABLE t (id BIGINT NOT NULL, score FLOAT NULL, price DECIMAL(10, 2), n numeric(5));")
+	require.NoError(t, err)
+	s, ok = stmt.(*CreateTableStmt)
+	require.True(t, ok, "want *CreateTableStmt, got %T", stmt)
+	assert.Equal(t, []ColumnDef{
+		{Name: "id", Type: "BIGINT", NotNull: true},
+		{Name: "score", Type: "FLOAT"},
+		{Name: "price", Type: "DECIMAL", Precision: 10, Scale: 2},
+		{Name: "n", Type: "NUMERIC", Precision: 5},
+	}, s.Columns)
+}
+
+func TestParse_CreateTempTable(t *testing.T) {
+	for _, sql := range []string{
+		"CREATE TEMP TABLE scratch (id INT);",
+		"create temporary table scratch (id INT);",
+	} {
+		stmt, err := Parse(sql)
+		require.NoError(t, err, sql)
+		s, ok := stmt.(*CreateTableStmt)
+		require.True(t, ok, "want *CreateTableStmt, got %T", stmt)
+		assert.Equal(t, &CreateTableStmt{
+			TableName: "scratch",
+			Columns:   []ColumnDef{{Name: "id", Type: "INT"}},
+			Temp:      true,
+		}, s)
+	}
+
+	_, err := Parse("CREATE TEMP scratch (id INT);")
+	require.Error(t, err)
+}
+
+func TestParse_CreateTableTTL(t *testing.T) {
+	stmt, err := Parse("CREATE TABLE sessions (id INT, expires_at TIMESTAMP) WITH (ttl_column = expires_at);")
+	require.NoError(t, err)
+	s, ok := stmt.(*CreateTableStmt)
+	require.True(t, ok, "want *CreateTableStmt, got %T", stmt)
+	assert.Equal(t, "expires_at", s.TTLColumn)
+
+	for _, sql := range []string{
+		"CREATE TABLE s (id INT) WITH ttl_column = id;",
+		"CREATE TABLE s (id INT) WITH (ttl_column id);",
+		"CREATE TABLE s (id INT) WITH (fillfactor = 70);",
+	} {
+		_, err := Parse(sql)
+		require.Error(t, err, sql)
+	}
 }
 
 func TestParse_CreateTable_Invalid(t *testing.T) {
@@ -90,6 +158,50 @@ func TestParse_CreateTable_InvalidColumnName(t *testing.T) {
 	require.Error(t, err)
 }
 
+func TestParse_CreateTable_Constraints(t *testing.T) {
+	stmt, err := Parse("CREATE TABLE users (id INT PRIMARY KEY, email TEXT UNIQUE NOT NULL, name TEXT);")
+	require.NoError(t, err)
+	s, ok := stmt.(*CreateTableStmt)
+	require.True(t, ok, "want *CreateTableStmt, got %T", stmt)
+	assert.Equal(t, []ColumnDef{
+		{Name: "id", Type: "INT", NotNull: true, PrimaryKey: true},
+		{Name: "email", Type: "TEXT", NotNull: true, Unique: true},
+		{Name: "name", Type: "TEXT"},
+	}, s.Columns)
+
+	stmt, err = Parse("CREATE TABLE t (a INT, b TEXT, PRIMARY KEY (a), UNIQUE (b));")
+	require.NoError(t, err)
+	s, ok = stmt.(*CreateTableStmt)
+	require.True(t, ok, "want *CreateTableStmt, got %T", stmt)
+	assert.Equal(t, []ColumnDef{
+		{Name: "a", Type: "INT", NotNull: true, PrimaryKey: true},
+		{Name: "b", Type: "TEXT", Unique: true},
+	}, s.Columns)
+
+	stmt, err = Parse("CREATE TABLE m (id INT, team INT REFERENCES teams ON DELETE SET NULL, " +
+		"boss INT, FOREIGN KEY (boss) REFERENCES m (id) ON DELETE CASCADE);")
+	require.NoError(t, err)
+	s, ok = stmt.(*CreateTableStmt)
+	require.True(t, ok, "want *CreateTableStmt, got %T", stmt)
+	assert.Equal(t, []ColumnDef{
+		{Name: "id", Type: "INT"},
+		{Name: "team", Type: "INT", References: &ForeignKeyDef{Table: "teams", OnDelete: "SET NULL"}},
+		{Name: "boss", Type: "INT", References: &ForeignKeyDef{Table: "m", Column: "id", OnDelete: "CASCADE"}},
+	}, s.Columns)
+
+	for _, sql := range []string{
+		"CREATE TABLE t (a INT REFERENCES u (id) ON DELETE NOTHING);",
+		"CREATE TABLE t (a INT, FOREIGN KEY (a) u (id));",
+		"CREATE TABLE t (a INT PRIMARY KEY, b INT PRIMARY KEY);",
+		"CREATE TABLE t (a INT, PRIMARY KEY (c));",
+		"CREATE TABLE t (a INT, b INT, UNIQUE (a, b));",
+		"CREATE TABLE t (a INT PRIMARY);",
+	} {
+		_, err := Parse(sql)
+		require.Error(t, err, sql)
+	}
+}
+
 func TestParse_DropTable(t *testing.T) {
 	stmt, err := Parse("DROP TABLE users;")
 	require.NoError(t, err)
@@ -97,6 +209,14 @@ func TestParse_DropTable(t *testing.T) {
 	s, ok := stmt.(*DropTableStmt)
 	require.True(t, ok, "want *DropTableStmt, got %T", stmt)
 	assert.Equal(t, "users", s.TableName)
+	assert.False(t, s.Purge)
+
+	stmt, err = Parse("drop table users purge;")
+	require.NoError(t, err)
+	s, ok = stmt.(*DropTableStmt)
+	require.True(t, ok, "want *DropTableStmt, got %T", stmt)
+	assert.Equal(t, "users", s.TableName)
+	assert.True(t, s.Purge)
 }
 
 func TestParse_Insert(t *testing.T) {
@@ -149,6 +269,39 @@ func TestParse_Insert_RejectExtraTokensInTableName(t *testing.T) {
 	require.Error(t, err)
 }
 
+func TestParse_Insert_ColumnList(t *testing.T) {
+	stmt, err := Parse("INSERT INTO users (name, id) VALUES ('ann', 7);")
+	require.NoError(t, err)
+	s, ok := stmt.(*InsertStmt)
+	require.True(t, ok, "want *InsertStmt, got %T", stmt)
+	assert.Equal(t, []string{"name", "id"}, s.Columns)
+	require.Len(t, s.Values, 2)
+
+	_, err = Parse("INSERT INTO users (name, id) VALUES ('ann');")
+	require.Error(t, err)
+	_, err = Parse("INSERT INTO users (id, id) VALUES (1, 2);")
+	require.Error(t, err)
+}
+
+func TestParse_AutoIncrementAndLastInsertRowID(t *testing.T) {
+	stmt, err := Parse("CREATE TABLE t (id INT PRIMARY KEY AUTOINCREMENT, name TEXT);")
+	require.NoError(t, err)
+	s, ok := stmt.(*CreateTableStmt)
+	require.True(t, ok, "want *CreateTableStmt, got %T", stmt)
+	assert.Equal(t, ColumnDef{Name: "id", Type: "INT", NotNull: true, PrimaryKey: true, AutoIncrement: true}, s.Columns[0])
+
+	stmt, err = Parse("select last_insert_rowid();")
+	require.NoError(t, err)
+	assert.Equal(t, &LastInsertRowIDStmt{}, stmt)
+	_, err = Parse("SELECT last_insert_rowid(1);")
+	require.Error(t, err)
+
+	// Without parentheses it is an ordinary column.
+	stmt, err = Parse("SELECT last_insert_rowid FROM t;")
+	require.NoError(t, err)
+	require.IsType(t, &SelectStmt{}, stmt)
+}
+
 func TestParse_Select_NoWhere(t *testing.T) {
 	stmt, err := Parse("SELECT * FROM users;")
 	require.NoError(t, err)
@@ -219,8 +372,239 @@ func TestParse_Delete(t *testing.T) {
 	assert.Equal(t, "id", s.Where.Column)
 }
 
+func TestParse_Returning(t *testing.T) {
+	stmt, err := Parse("INSERT INTO users (name) VALUES ('a') RETURNING id, name;")
+	require.NoError(t, err)
+	assert.Equal(t, []string{"id", "name"}, stmt.(*InsertStmt).Returning)
+
+	stmt, err = Parse("UPDATE users SET name = 'b' WHERE id = 1 RETURNING *;")
+	require.NoError(t, err)
+	s := stmt.(*UpdateStmt)
+	assert.Equal(t, []string{"*"}, s.Returning)
+	assert.Equal(t, "id", s.Where.Column)
+
+	stmt, err = Parse("DELETE FROM users RETURNING name;")
+	require.NoError(t, err)
+	assert.Equal(t, []string{"name"}, stmt.(*DeleteStmt).Returning)
+
+	stmt, err = Parse("DELETE FROM users;")
+	require.NoError(t, err)
+	assert.Nil(t, stmt.(*DeleteStmt).Returning)
+
+	_, err = Parse("DELETE FROM users RETURNING;")
+	require.Error(t, err)
+}
+
+func TestParse_Vacuum(t *testing.T) {
+	stmt, err := Parse("vacuum;")
+	require.NoError(t, err)
+	assert.Equal(t, &VacuumStmt{}, stmt)
+
+	_, err = Parse("VACUUM users;")
+	require.Error(t, err)
+}
+
+func TestParse_Analyze(t *testing.T) {
+	stmt, err := Parse("ANALYZE;")
+	require.NoError(t, err)
+	assert.Equal(t, &AnalyzeStmt{}, stmt)
+
+	stmt, err = Parse("analyze users;")
+	require.NoError(t, err)
+	assert.Equal(t, &AnalyzeStmt{TableName: "users"}, stmt)
+
+	_, err = Parse("ANALYZE users orders;")
+	require.Error(t, err)
+}
+
+func TestParse_Explain(t *testing.T) {
+	stmt, err := Parse("EXPLAIN ANALYZE SELECT * FROM users WHERE id = 1;")
+	require.NoError(t, err)
+	s, ok := stmt.(*ExplainStmt)
+	require.True(t, ok, "want *ExplainStmt, got %T", stmt)
+	assert.True(t, s.Analyze)
+	sel, ok := s.Stmt.(*SelectStmt)
+	require.True(t, ok, "want *SelectStmt, got %T", s.Stmt)
+	assert.Equal(t, "users", sel.TableName)
+
+	stmt, err = Parse("explain delete from users;")
+	require.NoError(t, err)
+	assert.Equal(t, &ExplainStmt{Stmt: &DeleteStmt{TableName: "users"}}, stmt)
+
+	_, err = Parse("EXPLAIN DROP TABLE users;")
+	require.Error(t, err)
+	_, err = Parse("EXPLAIN EXPLAIN SELECT * FROM users;")
+	require.Error(t, err)
+}
+
+func TestParse_Cursors(t *testing.T) {
+	stmt, err := Parse("DECLARE c CURSOR FOR SELECT * FROM users WHERE id > ?;")
+	require.NoError(t, err)
+	dc, ok := stmt.(*DeclareCursorStmt)
+	require.True(t, ok, "want *DeclareCursorStmt, got %T", stmt)
+	assert.Equal(t, "c", dc.Name)
+	assert.Equal(t, "users", dc.Query.TableName)
+
+	for sql, want := range map[string]*FetchStmt{
+		"FETCH c;":           {Cursor: "c", Count: 1},
+		"fetch next from c;": {Cursor: "c", Count: 1},
+		"FETCH 100 IN c;":    {Cursor: "c", Count: 100},
+		"FETCH ALL FROM c;":  {Cursor: "c", Count: -1},
+	} {
+		stmt, err = Parse(sql)
+		require.NoError(t, err, sql)
+		assert.Equal(t, want, stmt, sql)
+	}
+
+	stmt, err = Parse("CLOSE c;")
+	require.NoError(t, err)
+	assert.Equal(t, &CloseCursorStmt{Name: "c"}, stmt)
+	stmt, err = Parse("CLOSE ALL;")
+	require.NoError(t, err)
+	assert.Equal(t, &CloseCursorStmt{}, stmt)
+
+	for _, sql := range []string{
+		"DECLARE c CURSOR FOR DELETE FROM users;",
+		"DECLARE c FOR SELECT * FROM users;",
+		"FETCH -1 FROM c;",
+		"FETCH 10;",
+	} {
+		_, err = Parse(sql)
+		require.Error(t, err, sql)
+	}
+}
+
+func TestParse_SetShow(t *testing.T) {
+	for sql, want := range map[string]Statement{
+		"SET sync_mode = normal;":        &SetStmt{Name: "sync_mode", Value: "normal"},
+		"set lock_wait_timeout to '2s';": &SetStmt{Name: "lock_wait_timeout", Value: "2s"},
+		"SET checkpoint_wal_bytes = -1;": &SetStmt{Name: "checkpoint_wal_bytes", Value: "-1"},
+		"SET sort_mem_bytes TO 1048576;": &SetStmt{Name: "sort_mem_bytes", Value: "1048576"},
+		"SHOW sync_mode;":                &ShowStmt{Name: "sync_mode"},
+		"show all;":                      &ShowStmt{},
+	} {
+		stmt, err := Parse(sql)
+		require.NoError(t, err, sql)
+		assert.Equal(t, want, stmt, sql)
+	}
+
+	for _, sql := range []string{
+		"SET sync_mode;",
+		"SET sync_mode normal;",
+		"SET = 1;",
+		"SET x = -y;",
+		"SHOW;",
+	} {
+		_, err := Parse(sql)
+		require.Error(t, err, sql)
+	}
+}
+
+func TestParse_AlterTable(t *testing.T) {
+	stmt, err := Parse("ALTER TABLE users ADD COLUMN age INT NOT NULL DEFAULT -1;")
+	require.NoError(t, err)
+	assert.Equal(t, &AlterTableStmt{
+		TableName: "users",
+		AddColumn: &ColumnDef{Name: "age", Type: "INT", NotNull: true, Default: &LiteralExpr{Value: int64(-1)}},
+	}, stmt)
+
+	stmt, err = Parse("alter table users add nick text unique;")
+	require.NoError(t, err)
+	assert.Equal(t, &AlterTableStmt{
+		TableName: "users",
+		AddColumn: &ColumnDef{Name: "nick", Type: "TEXT", Unique: true},
+	}, stmt)
+
+	stmt, err = Parse("ALTER TABLE users DROP COLUMN age;")
+	require.NoError(t, err)
+	assert.Equal(t, &AlterTableStmt{TableName: "users", DropColumn: "age"}, stmt)
+
+	stmt, err = Parse("ALTER TABLE users RENAME TO people;")
+	require.NoError(t, err)
+	assert.Equal(t, &AlterTableStmt{TableName: "users", RenameTo: "people"}, stmt)
+
+	stmt, err = Parse("ALTER TABLE posts ADD CONSTRAINT posts_author FOREIGN KEY (author) " +
+		"REFERENCES users (id) ON DELETE CASCADE;")
+	require.NoError(t, err)
+	assert.Equal(t, &AlterTableStmt{
+		TableName:        "posts",
+		AddForeignKey:    &ForeignKeyDef{Table: "users", Column: "id", OnDelete: "CASCADE"},
+		ForeignKeyColumn: "author",
+		ForeignKeyName:   "posts_author",
+	}, stmt)
+
+	stmt, err = Parse("ALTER TABLE posts ADD FOREIGN KEY (author) REFERENCES users;")
+	require.NoError(t, err)
+	assert.Equal(t, &AlterTableStmt{
+		TableName:        "posts",
+		AddForeignKey:    &ForeignKeyDef{Table: "users"},
+		ForeignKeyColumn: "author",
+	}, stmt)
+
+	for _, sql := range []string{
+		"ALTER users ADD age INT",
+		"ALTER TABLE users RENAME people",
+		"ALTER TABLE users TRUNCATE",
+		"ALTER TABLE users ADD COLUMN age INT DEFAULT",
+		"ALTER TABLE posts ADD CONSTRAINT FOREIGN KEY (author) REFERENCES users;",
+		"ALTER TABLE posts ADD FOREIGN KEY (a, b) REFERENCES users;",
+		"CREATE TABLE users (id INT DEFAULT 0)",
+	} {
+		_, err := Parse(sql)
+		require.Error(t, err, sql)
+	}
+}
+
+func TestParseScript(t *testing.T) {
+	stmts, err := ParseScript("-- dump\nCREATE TABLE t (s TEXT);\n;INSERT INTO t VALUES ('a;b');\nDROP TABLE t;\n")
+	require.NoError(t, err)
+	require.Len(t, stmts, 3)
+	assert.Equal(t, &InsertStmt{TableName: "t", Values: []Expr{&LiteralExpr{Value: "a;b"}}}, stmts[1])
+
+	stmts, err = ParseScript("  \n")
+	require.NoError(t, err)
+	assert.Empty(t, stmts)
+
+	_, err = ParseScript("DROP TABLE a; DROP TABLE b")
+	require.ErrorContains(t, err, "statement 2: missing ';'")
+	_, err = ParseScript("DROP TABLE a; DROP TABLE b c;")
+	require.ErrorContains(t, err, "statement 2: ")
+}
+
+func TestParse_Copy(t *testing.T) {
+	stmt, err := Parse("COPY users FROM '/tmp/users.csv' WITH (HEADER true, DELIMITER ';', NULL '\\N');")
+	require.NoError(t, err)
+	assert.Equal(t, &CopyStmt{
+		TableName: "users",
+		From:      true,
+		File:      "/tmp/users.csv",
+		Header:    true,
+		Delimiter: ";",
+		Null:      `\N`,
+	}, stmt)
+
+	stmt, err = Parse("copy users to 'out.csv' (header);")
+	require.NoError(t, err)
+	assert.Equal(t, &CopyStmt{TableName: "users", File: "out.csv", Header: true}, stmt)
+
+	stmt, err = Parse("COPY users TO 'out.csv' (HEADER FALSE);")
+	require.NoError(t, err)
+	assert.Equal(t, &CopyStmt{TableName: "users", File: "out.csv"}, stmt)
+
+	for _, sql := range []string{
+		"COPY users 'x.csv'",
+		"COPY users FROM x",
+		"COPY users FROM 'x.csv' WITH HEADER",
+		"COPY users FROM 'x.csv' (FORMAT csv)",
+		"COPY users FROM 'x.csv' (HEADER",
+	} {
+		_, err := Parse(sql)
+		require.Error(t, err, sql)
+	}
+}
+
 func TestParse_Unsupported(t *testing.T) {
-	_, err := Parse("ALTER TABLE t ADD COLUMN x INT;")
+	_, err := Parse("GRANT SELECT ON t TO bob;")
 	require.Error(t, err)
 }
 
@@ -238,7 +622,10 @@ func TestParseLiteral(t *testing.T) {
 		{"123", int64(123), true},
 		{"-7", int64(-7), true},
 		{"'a,b'", "a,b", true},
-		{"1.2", nil, false}, // unsupported
+		{"1.2", 1.2, true},
+		{"-0.5", -0.5, true},
+		{"1.", nil, false},
+		{"99999999999999999999", nil, false}, // overflows int64
 		{"abc", nil, false}, // unsupported
 		{"'unterminated", nil, false},
 	}
@@ -255,24 +642,434 @@ func TestParseLiteral(t *testing.T) {
 	}
 }
 
-func TestSplitComma(t *testing.T) {
-	in := "1,'a,b',true,NULL,'x'"
-	got := splitComma(in)
-	want := []string{"1", "'a,b'", "true", "NULL", "'x'"}
-	assert.Equal(t, want, got)
+func TestParse_Select_ColumnsOrderLimit(t *testing.T) {
+	stmt, err := Parse("select id, name from users where active = true order by name desc, id limit 10;")
+	require.NoError(t, err)
+
+	s, ok := stmt.(*SelectStmt)
+	require.True(t, ok, "want *SelectStmt, got %T", stmt)
+	assert.Equal(t, "users", s.TableName)
+	assert.Equal(t, []string{"id", "name"}, s.Columns)
+	require.NotNil(t, s.Where)
+	assert.Equal(t, "active", s.Where.Column)
+	assert.Equal(t, []OrderByItem{{Column: "name", Desc: true}, {Column: "id"}}, s.OrderBy)
+	require.NotNil(t, s.Limit)
+	assert.Equal(t, int64(10), *s.Limit)
+
+	_, err = Parse("SELECT * FROM users LIMIT -1;")
+	require.Error(t, err)
+	_, err = Parse("SELECT * FROM users ORDER name;")
+	require.Error(t, err)
+}
+
+func TestParse_Tokenizer(t *testing.T) {
+	// Keywords need no surrounding spaces, strings keep escaped quotes.
+	stmt, err := Parse("UPDATE t SET name='it''s',n=-3 WHERE id=1; -- trailing comment")
+	require.NoError(t, err)
+	s, ok := stmt.(*UpdateStmt)
+	require.True(t, ok, "want *UpdateStmt, got %T", stmt)
+	assert.Equal(t, "it's", s.Assignments[0].Value.(*LiteralExpr).Value)
+	assert.Equal(t, int64(-3), s.Assignments[1].Value.(*LiteralExpr).Value)
+
+	_, err = Parse("SELECT * FROM users; SELECT * FROM users;")
+	require.Error(t, err)
+	_, err = Parse("SELECT * FROM users WHERE name = 'open;")
+	require.Error(t, err)
+	_, err = Parse("SELECT * FROM users WHERE id ! 1;")
+	require.Error(t, err)
+}
+
+func TestParse_Select_WhereComparisons(t *testing.T) {
+	for _, op := range []string{"=", "<", "<=", ">", ">="} {
+		stmt, err := Parse("SELECT * FROM users WHERE id" + op + "-3;")
+		require.NoError(t, err, op)
+		s, ok := stmt.(*SelectStmt)
+		require.True(t, ok, "want *SelectStmt, got %T", stmt)
+		assert.Equal(t, op, s.Where.Op)
+		assert.Equal(t, int64(-3), s.Where.Value.(*LiteralExpr).Value)
+	}
+
+	// "<>" and "!=" are not index comparisons: they make an expression.
+	for _, op := range []string{"<>", "!="} {
+		stmt, err := Parse("SELECT * FROM users WHERE id " + op + " 1;")
+		require.NoError(t, err, op)
+		want := &BinaryExpr{Op: "<>", Left: &ColumnExpr{Name: "id"}, Right: &LiteralExpr{Value: int64(1)}}
+		assert.Equal(t, &Where{Expr: want}, stmt.(*SelectStmt).Where)
+	}
+}
+
+func TestParse_Expressions(t *testing.T) {
+	stmt, err := Parse("SELECT id, price * (qty + 1) AS total, UPPER(name), SUM(qty) - 1 FROM items " +
+		"WHERE a = 1 AND NOT b LIKE 'x%' OR c IS NOT NULL;")
+	require.NoError(t, err)
+	s := stmt.(*SelectStmt)
+	assert.Equal(t, []string{"id", "total", "UPPER(name)", "SUM(qty) - 1"}, s.Columns)
+	assert.Equal(t, []Aggregate{{Func: "SUM", Column: "qty"}}, s.Aggregates)
+	require.Len(t, s.Exprs, 3)
+	assert.Equal(t, SelectExpr{Index: 1, Expr: &BinaryExpr{
+		Op:    "*",
+		Left:  &ColumnExpr{Name: "price"},
+		Right: &BinaryExpr{Op: "+", Left: &ColumnExpr{Name: "qty"}, Right: &LiteralExpr{Value: int64(1)}},
+	}}, s.Exprs[0])
+	assert.Equal(t, &FuncExpr{Name: "UPPER", Args: []Expr{&ColumnExpr{Name: "name"}}}, s.Exprs[1].Expr)
+	assert.Equal(t, &BinaryExpr{Op: "-", Left: &ColumnExpr{Name: "sum(qty)"}, Right: &LiteralExpr{Value: int64(1)}},
+		s.Exprs[2].Expr)
+
+	// OR binds looser than AND, AND looser than NOT, NOT looser than LIKE.
+	like := &BinaryExpr{Op: "LIKE", Left: &ColumnExpr{Name: "b"}, Right: &LiteralExpr{Value: "x%"}}
+	assert.Equal(t, &Where{Expr: &BinaryExpr{
+		Op: "OR",
+		Left: &BinaryExpr{
+			Op:    "AND",
+			Left:  &BinaryExpr{Op: "=", Left: &ColumnExpr{Name: "a"}, Right: &LiteralExpr{Value: int64(1)}},
+			Right: &UnaryExpr{Op: "NOT", X: like},
+		},
+		Right: &UnaryExpr{Op: "IS NOT NULL", X: &ColumnExpr{Name: "c"}},
+	}}, s.Where)
+
+	stmt, err = Parse("SELECT id FROM posts WHERE body MATCH 'disk error' AND id > 1;")
+	require.NoError(t, err)
+	assert.Equal(t, &Where{Expr: &BinaryExpr{
+		Op:    "AND",
+		Left:  &BinaryExpr{Op: "MATCH", Left: &ColumnExpr{Name: "body"}, Right: &LiteralExpr{Value: "disk error"}},
+		Right: &BinaryExpr{Op: ">", Left: &ColumnExpr{Name: "id"}, Right: &LiteralExpr{Value: int64(1)}},
+	}}, stmt.(*SelectStmt).Where)
+
+	stmt, err = Parse("SELECT CASE WHEN n < 0 THEN 'neg' ELSE 'pos' END AS sign FROM t;")
+	require.NoError(t, err)
+	assert.Equal(t, &CaseExpr{
+		Whens: []CaseWhen{{
+			When: &BinaryExpr{Op: "<", Left: &ColumnExpr{Name: "n"}, Right: &LiteralExpr{Value: int64(0)}},
+			Then: &LiteralExpr{Value: "neg"},
+		}},
+		Else: &LiteralExpr{Value: "pos"},
+	}, stmt.(*SelectStmt).Exprs[0].Expr)
+
+	e, err := ParseExpr("a || '-' || LOWER(b)")
+	require.NoError(t, err)
+	assert.Equal(t, &BinaryExpr{
+		Op:    "||",
+		Left:  &BinaryExpr{Op: "||", Left: &ColumnExpr{Name: "a"}, Right: &LiteralExpr{Value: "-"}},
+		Right: &FuncExpr{Name: "LOWER", Args: []Expr{&ColumnExpr{Name: "b"}}},
+	}, e)
+
+	// The JSON operators bind tighter than arithmetic.
+	e, err = ParseExpr("doc->'tags'->>0 || 'x' = n * doc->>'$.n'")
+	require.NoError(t, err)
+	tags := &BinaryExpr{Op: "->", Left: &ColumnExpr{Name: "doc"}, Right: &LiteralExpr{Value: "tags"}}
+	assert.Equal(t, &BinaryExpr{
+		Op: "=",
+		Left: &BinaryExpr{
+			Op:    "||",
+			Left:  &BinaryExpr{Op: "->>", Left: tags, Right: &LiteralExpr{Value: int64(0)}},
+			Right: &LiteralExpr{Value: "x"},
+		},
+		Right: &BinaryExpr{
+			Op:    "*",
+			Left:  &ColumnExpr{Name: "n"},
+			Right: &BinaryExpr{Op: "->>", Left: &ColumnExpr{Name: "doc"}, Right: &LiteralExpr{Value: "$.n"}},
+		},
+	}, e)
+
+	// Typed literals are read as the values they stand for.
+	e, err = ParseExpr("d + INTERVAL 3 DAY < DATE '2024-03-09' + INTERVAL '-1 month 2 hours'")
+	require.NoError(t, err)
+	day, err := record.ParseDate("2024-03-09")
+	require.NoError(t, err)
+	assert.Equal(t, &BinaryExpr{
+		Op:   "<",
+		Left: &BinaryExpr{Op: "+", Left: &ColumnExpr{Name: "d"}, Right: &LiteralExpr{Value: record.Interval{Days: 3}}},
+		Right: &BinaryExpr{
+			Op:    "+",
+			Left:  &LiteralExpr{Value: day},
+			Right: &LiteralExpr{Value: record.Interval{Months: -1, Micros: 2 * 3600e6}},
+		},
+	}, e)
+	stmt, err = Parse("SELECT a FROM t WHERE at >= TIMESTAMP '2024-03-09 10:30:00';")
+	require.NoError(t, err)
+	at, err := record.ParseTimestamp("2024-03-09 10:30:00")
+	require.NoError(t, err)
+	assert.Equal(t, &Where{Column: "at", Op: ">=", Value: &LiteralExpr{Value: at}}, stmt.(*SelectStmt).Where)
+
+	e, err = ParseExpr("CAST(price * 2 AS DECIMAL(10, 2)) + DECIMAL '0.05'")
+	require.NoError(t, err)
+	assert.Equal(t, &BinaryExpr{
+		Op: "+",
+		Left: &CastExpr{
+			X:         &BinaryExpr{Op: "*", Left: &ColumnExpr{Name: "price"}, Right: &LiteralExpr{Value: int64(2)}},
+			Type:      "DECIMAL",
+			Precision: 10,
+			Scale:     2,
+		},
+		Right: &LiteralExpr{Value: record.Decimal{Coef: 5, Scale: 2}},
+	}, e)
+
+	for _, bad := range []string{
+		"SELECT a + FROM t;",
+		"SELECT a FROM t WHERE a + ? > 1;",
+		"SELECT a FROM t WHERE SUM(a) > 1;",
+		"SELECT CASE END FROM t;",
+		"SELECT (SELECT 1 FROM t) FROM t;",
+		"SELECT DATE '2024-02-30' FROM t;",
+		"SELECT INTERVAL 3 FROM t;",
+		"SELECT INTERVAL '1 fortnight' FROM t;",
+		"SELECT DECIMAL '1.2.3' FROM t;",
+		"SELECT CAST(a INT) FROM t;",
+		"SELECT CAST(a AS DECIMAL(19, 2)) FROM t;",
+		"SELECT CAST(a AS INT(3)) FROM t;",
+	} {
+		_, err := Parse(bad)
+		require.Error(t, err, bad)
+	}
+	_, err = ParseExpr("a b")
+	require.Error(t, err)
+}
+
+func TestParse_PatternsAndCollations(t *testing.T) {
+	stmt, err := Parse("CREATE TABLE u (name TEXT COLLATE nocase NOT NULL, code TEXT);")
+	require.NoError(t, err)
+	assert.Equal(t, []ColumnDef{
+		{Name: "name", Type: "TEXT", NotNull: true, Collate: "NOCASE"},
+		{Name: "code", Type: "TEXT"},
+	}, stmt.(*CreateTableStmt).Columns)
+
+	stmt, err = Parse("SELECT name FROM u WHERE code NOT GLOB 'A[0-9]*' ORDER BY name COLLATE binary DESC, code;")
+	require.NoError(t, err)
+	s := stmt.(*SelectStmt)
+	assert.Equal(t, &Where{Expr: &BinaryExpr{
+		Op: "NOT GLOB", Left: &ColumnExpr{Name: "code"}, Right: &LiteralExpr{Value: "A[0-9]*"},
+	}}, s.Where)
+	assert.Equal(t, []OrderByItem{{Column: "name", Desc: true, Collate: "BINARY"}, {Column: "code"}}, s.OrderBy)
+
+	// COLLATE binds tightest; ESCAPE follows the pattern of a LIKE.
+	e, err := ParseExpr("name || 'x' COLLATE nocase NOT LIKE '10!%' ESCAPE '!'")
+	require.NoError(t, err)
+	assert.Equal(t, &BinaryExpr{
+		Op: "NOT LIKE",
+		Left: &BinaryExpr{
+			Op:    "||",
+			Left:  &ColumnExpr{Name: "name"},
+			Right: &CollateExpr{X: &LiteralExpr{Value: "x"}, Collation: "NOCASE"},
+		},
+		Right:  &LiteralExpr{Value: "10!%"},
+		Escape: &LiteralExpr{Value: "!"},
+	}, e)
+
+	for _, bad := range []string{
+		"SELECT * FROM u WHERE name = 'a' ESCAPE '!';",
+		"SELECT * FROM u WHERE name LIKE 'a' ESCAPE;",
+		"SELECT name COLLATE FROM u;",
+		"SELECT * FROM u ORDER BY name COLLATE;",
+		"CREATE TABLE v (name TEXT COLLATE 'nocase');",
+	} {
+		_, err := Parse(bad)
+		require.Error(t, err, bad)
+	}
 }
 
-func TestSplitKeyword(t *testing.T) {
-	left, right := splitKeyword("users WHERE id=1", "WHERE")
-	assert.Equal(t, "users", left)
-	assert.Equal(t, "id=1", right)
+func TestParse_CreateDropIndex(t *testing.T) {
+	stmt, err := Parse("create index users_name on users (name);")
+	require.NoError(t, err)
+	assert.Equal(t, &CreateIndexStmt{IndexName: "users_name", TableName: "users", Column: "name"}, stmt)
+
+	stmt, err = Parse("CREATE UNIQUE INDEX users_name ON users (name);")
+	require.NoError(t, err)
+	assert.Equal(t, &CreateIndexStmt{IndexName: "users_name", TableName: "users", Column: "name", Unique: true}, stmt)
+
+	stmt, err = Parse("CREATE INDEX users_lower ON users (LOWER(name));")
+	require.NoError(t, err)
+	assert.Equal(t, &CreateIndexStmt{
+		IndexName: "users_lower",
+		TableName: "users",
+		Expr:      &FuncExpr{Name: "LOWER", Args: []Expr{&ColumnExpr{Name: "name"}}},
+	}, stmt)
+
+	stmt, err = Parse("CREATE INDEX posts_body ON posts USING fulltext (body);")
+	require.NoError(t, err)
+	assert.Equal(t, &CreateIndexStmt{IndexName: "posts_body", TableName: "posts", Column: "body", FullText: true}, stmt)
+	stmt, err = Parse("CREATE INDEX users_name ON users USING btree (name);")
+	require.NoError(t, err)
+	assert.Equal(t, &CreateIndexStmt{IndexName: "users_name", TableName: "users", Column: "name"}, stmt)
 
-	left, right = splitKeyword("users", "WHERE")
-	assert.Equal(t, "users", left)
-	assert.Empty(t, right)
+	stmt, err = Parse("DROP INDEX users_name ON users;")
+	require.NoError(t, err)
+	assert.Equal(t, &DropIndexStmt{IndexName: "users_name", TableName: "users"}, stmt)
 
-	// limitation: requires spaces around keyword.
-	left, right = splitKeyword("users WHEREid=1", "WHERE")
-	assert.Equal(t, "users WHEREid=1", left)
-	assert.Empty(t, right)
+	_, err = Parse("CREATE INDEX users_name ON users name;")
+	require.Error(t, err)
+	_, err = Parse("CREATE INDEX users_name ON users USING hash (name);")
+	require.Error(t, err)
+	_, err = Parse("CREATE INDEX users_name ON users (id, name);")
+	require.Error(t, err)
+	_, err = Parse("DROP INDEX users_name;")
+	require.Error(t, err)
+}
+
+func TestParse_Select_Aggregates(t *testing.T) {
+	stmt, err := Parse("SELECT dept, count(*), AVG(age) FROM users WHERE age > 18 GROUP BY dept " +
+		"HAVING COUNT(*) >= 2 ORDER BY max(age) DESC, dept;")
+	require.NoError(t, err)
+
+	s, ok := stmt.(*SelectStmt)
+	require.True(t, ok, "want *SelectStmt, got %T", stmt)
+	assert.Equal(t, []string{"dept", "count(*)", "avg(age)"}, s.Columns)
+	assert.Equal(t, []string{"dept"}, s.GroupBy)
+	assert.Equal(t, []Aggregate{
+		{Func: "COUNT", Column: "*"}, {Func: "AVG", Column: "age"}, {Func: "MAX", Column: "age"},
+	}, s.Aggregates)
+	require.NotNil(t, s.Having)
+	assert.Equal(t, "count(*)", s.Having.Column)
+	assert.Equal(t, ">=", s.Having.Op)
+	assert.Equal(t, []OrderByItem{{Column: "max(age)", Desc: true}, {Column: "dept"}}, s.OrderBy)
+
+	// Without parentheses an aggregate name is a plain column.
+	stmt, err = Parse("SELECT count FROM t;")
+	require.NoError(t, err)
+	assert.Empty(t, stmt.(*SelectStmt).Aggregates)
+
+	_, err = Parse("SELECT SUM(*) FROM t;")
+	require.Error(t, err)
+	_, err = Parse("SELECT * FROM t WHERE COUNT(*) > 1;")
+	require.Error(t, err)
+	_, err = Parse("SELECT a FROM t GROUP a;")
+	require.Error(t, err)
+}
+
+func TestParse_Select_Joins(t *testing.T) {
+	stmt, err := Parse("SELECT u.name, o.total FROM users u JOIN orders AS o ON u.id = o.user_id " +
+		"LEFT OUTER JOIN notes ON notes.order_id = o.id AND notes.n > o.total WHERE o.total >= 10;")
+	require.NoError(t, err)
+
+	s, ok := stmt.(*SelectStmt)
+	require.True(t, ok, "want *SelectStmt, got %T", stmt)
+	assert.Equal(t, "users", s.TableName)
+	assert.Equal(t, "u", s.Alias)
+	assert.Equal(t, []string{"u.name", "o.total"}, s.Columns)
+	assert.Equal(t, []JoinClause{
+		{TableName: "orders", Alias: "o", On: []JoinCond{{Left: "u.id", Op: "=", Right: "o.user_id"}}},
+		{TableName: "notes", Left: true, On: []JoinCond{
+			{Left: "notes.order_id", Op: "=", Right: "o.id"},
+			{Left: "notes.n", Op: ">", Right: "o.total"},
+		}},
+	}, s.Joins)
+	require.NotNil(t, s.Where)
+	assert.Equal(t, "o.total", s.Where.Column)
+
+	stmt, err = Parse("SELECT COUNT(o.id) FROM users INNER JOIN orders o ON id = o.user_id GROUP BY users.name;")
+	require.NoError(t, err)
+	s = stmt.(*SelectStmt)
+	assert.Equal(t, []Aggregate{{Func: "COUNT", Column: "o.id"}}, s.Aggregates)
+	assert.Equal(t, []string{"users.name"}, s.GroupBy)
+	assert.Empty(t, s.Alias)
+
+	_, err = Parse("SELECT * FROM a JOIN b;")
+	require.Error(t, err)
+	_, err = Parse("SELECT * FROM a JOIN b ON a.x = 1;")
+	require.Error(t, err)
+	_, err = Parse("SELECT * FROM a LEFT b ON a.x = b.x;")
+	require.Error(t, err)
+	_, err = Parse("SELECT a. FROM a;")
+	require.Error(t, err)
+}
+
+func TestParse_Placeholders(t *testing.T) {
+	stmt, err := Parse("INSERT INTO t VALUES (?, 'x', ?);")
+	require.NoError(t, err)
+	assert.Equal(t, []Expr{&ParamExpr{Index: 1}, &LiteralExpr{Value: "x"}, &ParamExpr{Index: 2}},
+		stmt.(*InsertStmt).Values)
+
+	stmt, err = Parse("UPDATE t SET name = $2 WHERE id >= $1;")
+	require.NoError(t, err)
+	u := stmt.(*UpdateStmt)
+	assert.Equal(t, &ParamExpr{Index: 2}, u.Assignments[0].Value)
+	assert.Equal(t, &ParamExpr{Index: 1}, u.Where.Value)
+
+	_, err = Parse("UPDATE t SET a = ? WHERE b = $1;")
+	require.Error(t, err)
+	_, err = Parse("UPDATE t SET a = $1 WHERE b = ?;")
+	require.Error(t, err)
+	_, err = Parse("SELECT * FROM t WHERE a = $0;")
+	require.Error(t, err)
+	_, err = Parse("SELECT * FROM t WHERE ? = 1;")
+	require.Error(t, err)
+}
+
+func TestParse_Subqueries(t *testing.T) {
+	stmt, err := Parse("SELECT name FROM users WHERE id NOT IN (SELECT user_id FROM orders WHERE total > ?);")
+	require.NoError(t, err)
+	w := stmt.(*SelectStmt).Where
+	assert.Equal(t, "id", w.Column)
+	assert.Equal(t, "NOT IN", w.Op)
+	require.NotNil(t, w.Subquery)
+	assert.Equal(t, "orders", w.Subquery.TableName)
+	assert.Equal(t, &ParamExpr{Index: 1}, w.Subquery.Where.Value)
+
+	stmt, err = Parse("SELECT * FROM users u WHERE NOT EXISTS (SELECT * FROM orders o WHERE o.user_id = u.id);")
+	require.NoError(t, err)
+	w = stmt.(*SelectStmt).Where
+	assert.Empty(t, w.Column)
+	assert.Equal(t, "NOT EXISTS", w.Op)
+	assert.Equal(t, &Where{Column: "o.user_id", Op: "=", Value: &ColumnExpr{Name: "u.id"}}, w.Subquery.Where)
+
+	stmt, err = Parse("SELECT * FROM t WHERE score > (SELECT AVG(score) FROM t) ORDER BY id;")
+	require.NoError(t, err)
+	s := stmt.(*SelectStmt)
+	assert.Equal(t, ">", s.Where.Op)
+	assert.Equal(t, []Aggregate{{Func: "AVG", Column: "score"}}, s.Where.Subquery.Aggregates)
+	assert.Equal(t, []OrderByItem{{Column: "id"}}, s.OrderBy)
+
+	stmt, err = Parse("SELECT * FROM t WHERE ok = TRUE;")
+	require.NoError(t, err)
+	assert.Equal(t, &LiteralExpr{Value: true}, stmt.(*SelectStmt).Where.Value)
+
+	_, err = Parse("SELECT * FROM t WHERE id IN (1, 2);")
+	require.Error(t, err)
+	_, err = Parse("SELECT * FROM t WHERE id IN (SELECT id FROM u;")
+	require.Error(t, err)
+	_, err = Parse("SELECT * FROM t WHERE EXISTS SELECT id FROM u;")
+	require.Error(t, err)
+}
+
+func TestParse_CreateDropView(t *testing.T) {
+	stmt, err := Parse("CREATE VIEW big AS SELECT id, 'it''s' FROM t WHERE n > 3 ORDER BY id -- trailing\n;")
+	require.NoError(t, err)
+	cv := stmt.(*CreateViewStmt)
+	assert.Equal(t, "big", cv.Name)
+	assert.Equal(t, "SELECT id, 'it''s' FROM t WHERE n > 3 ORDER BY id", cv.SQL)
+	assert.Equal(t, "t", cv.Query.TableName)
+
+	stmt, err = Parse("DROP VIEW big;")
+	require.NoError(t, err)
+	assert.Equal(t, &DropViewStmt{Name: "big"}, stmt)
+
+	_, err = Parse("CREATE VIEW big AS SELECT * FROM t WHERE id = ?;")
+	require.Error(t, err)
+	_, err = Parse("CREATE VIEW big SELECT * FROM t;")
+	require.Error(t, err)
+	_, err = Parse("CREATE VIEW big AS DELETE FROM t;")
+	require.Error(t, err)
+}
+
+// FuzzParse checks that no input makes the parser panic, and that a syntax
+// error points into the input.
+func FuzzParse(f *testing.F) {
+	for _, sql := range []string{
+		"SELECT * FROM users WHERE id = 1;",
+		"SELECT name, COUNT(*) FROM t GROUP BY name HAVING COUNT(*) > 1 ORDER BY name COLLATE NOCASE DESC LIMIT 3;",
+		"INSERT INTO t (id, name) VALUES (1, 'it''s'), (2, NULL);",
+		"UPDATE t SET n = n + 1 WHERE name LIKE 'a!%' ESCAPE '!' OR name GLOB '[a-c]*';",
+		"DELETE FROM t WHERE id IN (SELECT id FROM u);",
+		"CREATE TABLE t (id INT PRIMARY KEY, name TEXT COLLATE NOCASE NOT NULL);",
+		"BEGIN; SELECT 'abc; COMMIT;",
+	} {
+		f.Add(sql)
+	}
+	f.Fuzz(func(t *testing.T, sql string) {
+		_, err := ParseScript(sql)
+		var se *SyntaxError
+		if errors.As(err, &se) {
+			require.GreaterOrEqual(t, se.Pos, 0)
+			require.LessOrEqual(t, se.Pos, len(sql))
+		}
+	})
 }
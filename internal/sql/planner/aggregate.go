@@ -0,0 +1,73 @@
+package planner
+
+import (
+	"fmt"
+	"slices"
+
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+// OutputSchema is the schema of the rows the aggregation makes of rows of in:
+// the GROUP BY columns, then one column per function, named by its Name.
+func (a *Aggregate) OutputSchema(in record.Schema) (record.Schema, error) {
+	var out record.Schema
+	for _, g := range a.GroupBy {
+		pos := columnIndex(in, g)
+		if pos < 0 {
+			return record.Schema{}, fmt.Errorf("planner: unknown column in GROUP BY: %s", g)
+		}
+		out.Cols = append(out.Cols, in.Cols[pos])
+	}
+
+	for _, f := range a.Funcs {
+		col := record.Column{Name: f.Name(), Type: record.ColInt64}
+		if f.Column == "*" {
+			if f.Func != "COUNT" {
+				return record.Schema{}, fmt.Errorf("planner: %s(*) is not supported", f.Func)
+			}
+			out.Cols = append(out.Cols, col)
+			continue
+		}
+		pos := columnIndex(in, f.Column)
+		if pos < 0 {
+			return record.Schema{}, fmt.Errorf("planner: unknown column in %s: %s", f.Func, f.Column)
+		}
+		arg := in.Cols[pos].Type
+		numeric := isNumeric(arg)
+
+		// Everything but COUNT is NULL for a group without non-NULL values.
+		col.Nullable = f.Func != "COUNT"
+		switch f.Func {
+		case "COUNT":
+		case "SUM", "AVG":
+			if !numeric {
+				return record.Schema{}, fmt.Errorf("planner: %s needs a numeric column, %s is not", f.Func, f.Column)
+			}
+			// The AVG of INTs is a FLOAT, that of DECIMALs exact.
+			col.Type = arg
+			if f.Func == "AVG" && arg != record.ColDecimal {
+				col.Type = record.ColFloat64
+			}
+		case "MIN", "MAX":
+			col.Type = arg
+		default:
+			custom, ok := a.Custom[f.Func]
+			if !ok {
+				return record.Schema{}, fmt.Errorf("planner: unsupported aggregate: %s", f.Func)
+			}
+			if arg == record.ColInt32 {
+				arg = record.ColInt64
+			}
+			if arg != custom.Arg && (custom.Arg != record.ColFloat64 || !isNumeric(arg)) {
+				return record.Schema{}, fmt.Errorf("planner: %s cannot take column %s: types differ", f.Func, f.Column)
+			}
+			col.Type = custom.Result
+		}
+		out.Cols = append(out.Cols, col)
+	}
+	return out, nil
+}
+
+func columnIndex(schema record.Schema, name string) int {
+	return slices.IndexFunc(schema.Cols, func(c record.Column) bool { return c.Name == name })
+}
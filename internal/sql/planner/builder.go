@@ -1,11 +1,13 @@
 package planner
 
 import (
+	"cmp"
 	"fmt"
 	"strings"
 
 	"github.com/tuannm99/novasql"
 	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/expr"
 	"github.com/tuannm99/novasql/internal/sql/parser"
 )
 
@@ -17,17 +19,71 @@ func BuildPlan(stmt parser.Statement, db *novasql.Database) (Plan, error) {
 		return &DropDatabasePlan{Name: s.Name}, nil
 	case *parser.UseDatabaseStmt:
 		return &UseDatabasePlan{Name: s.Name}, nil
+	case *parser.SetStmt:
+		return &SetPlan{Name: s.Name, Value: s.Value}, nil
+	case *parser.ShowStmt:
+		return &ShowPlan{Name: s.Name}, nil
 
 	case *parser.CreateTableStmt:
 		return buildCreateTablePlan(s)
 	case *parser.DropTableStmt:
-		return &DropTablePlan{TableName: s.TableName}, nil
+		return &DropTablePlan{TableName: s.TableName, Purge: s.Purge}, nil
+	case *parser.CreateViewStmt:
+		if _, _, err := planView(db, s.Query); err != nil {
+			return nil, err
+		}
+		return &CreateViewPlan{Name: s.Name, Query: s.SQL}, nil
+	case *parser.DropViewStmt:
+		return &DropViewPlan{Name: s.Name}, nil
+	case *parser.AlterTableStmt:
+		return buildAlterTablePlan(s)
+
+	case *parser.CreateIndexStmt:
+		return buildCreateIndexPlan(s, db)
+	case *parser.DropIndexStmt:
+		return &DropIndexPlan{TableName: s.TableName, IndexName: s.IndexName}, nil
+
+	case *parser.VacuumStmt:
+		return &VacuumPlan{}, nil
+	case *parser.AnalyzeStmt:
+		return &AnalyzePlan{TableName: s.TableName}, nil
+	case *parser.CopyStmt:
+		return buildCopyPlan(s)
+	case *parser.LastInsertRowIDStmt:
+		return &LastInsertRowIDPlan{}, nil
+	case *parser.ExplainStmt:
+		plan, err := BuildPlan(s.Stmt, db)
+		if err != nil {
+			return nil, err
+		}
+		return &ExplainPlan{Analyze: s.Analyze, Plan: plan}, nil
+
+	case *parser.DeclareCursorStmt:
+		plan, err := buildSelectPlan(s.Query, db, nil)
+		if err != nil {
+			return nil, err
+		}
+		return &DeclareCursorPlan{Name: s.Name, Query: plan}, nil
+	case *parser.FetchStmt:
+		return &FetchPlan{Cursor: s.Cursor, Count: s.Count}, nil
+	case *parser.CloseCursorStmt:
+		return &CloseCursorPlan{Name: s.Name}, nil
 
 	case *parser.InsertStmt:
-		return &InsertPlan{TableName: s.TableName, Values: s.Values}, nil
+		plan := &InsertPlan{TableName: s.TableName, Columns: s.Columns, Values: s.Values}
+		if s.Returning != nil {
+			tbl, err := db.OpenTable(s.TableName)
+			if err != nil {
+				return nil, err
+			}
+			if plan.Returning, err = bindReturning(tbl.Schema, s.Returning); err != nil {
+				return nil, err
+			}
+		}
+		return plan, nil
 
 	case *parser.SelectStmt:
-		return buildSelectPlan(s, db)
+		return buildSelectPlan(s, db, nil)
 
 	case *parser.UpdateStmt:
 		return buildUpdatePlan(s, db)
@@ -41,44 +97,209 @@ func BuildPlan(stmt parser.Statement, db *novasql.Database) (Plan, error) {
 }
 
 func buildCreateTablePlan(s *parser.CreateTableStmt) (Plan, error) {
+	plan := &CreateTablePlan{TableName: s.TableName, Temp: s.Temp, TTLColumn: s.TTLColumn}
 	var cols []record.Column
 	for _, c := range s.Columns {
-		colType, err := mapSQLType(c.Type)
+		col, err := expr.SQLType(c.Type, c.Precision, c.Scale)
 		if err != nil {
 			return nil, err
 		}
-		cols = append(cols, record.Column{
-			Name:     c.Name,
-			Type:     colType,
-			Nullable: true, // default
-		})
+		col.Name, col.Nullable = c.Name, !c.NotNull && !c.PrimaryKey
+		if col.Collation, err = columnCollation(c, col); err != nil {
+			return nil, err
+		}
+		cols = append(cols, col)
+		if c.AutoIncrement {
+			if !c.PrimaryKey || col.Type != record.ColInt64 {
+				return nil, fmt.Errorf("planner: AUTOINCREMENT column %s must be an INT PRIMARY KEY", c.Name)
+			}
+			plan.AutoIncrement = c.Name
+		}
+		switch {
+		case c.PrimaryKey:
+			plan.PrimaryKey = c.Name
+		case c.Unique:
+			plan.Unique = append(plan.Unique, c.Name)
+		}
+		if ref := c.References; ref != nil {
+			plan.ForeignKeys = append(plan.ForeignKeys, *foreignKey(s.TableName, c.Name, ref))
+		}
+	}
+	if s.Temp && (plan.PrimaryKey != "" || len(plan.Unique) > 0 || len(plan.ForeignKeys) > 0) {
+		return nil, fmt.Errorf("planner: temporary table %s cannot have constraints", s.TableName)
+	}
+	if s.Temp && s.TTLColumn != "" {
+		return nil, fmt.Errorf("planner: temporary table %s cannot have a TTL column", s.TableName)
+	}
+	plan.Schema = record.Schema{Cols: cols}
+	return plan, nil
+}
+
+// columnCollation is the collation of column c, of type col. Only TEXT
+// columns have one, and only a built-in one: the indexes of the column are
+// ordered by it, alike for every handle.
+func columnCollation(c parser.ColumnDef, col record.Column) (string, error) {
+	if c.Collate == "" {
+		return "", nil
+	}
+	if col.Type != record.ColText {
+		return "", fmt.Errorf("planner: COLLATE needs a TEXT column, %s is not", c.Name)
+	}
+	if _, ok := expr.LookupCollation(c.Collate); !ok {
+		return "", fmt.Errorf("planner: column %s: %s is not a built-in collation", c.Name, c.Collate)
+	}
+	return c.Collate, nil
+}
+
+func buildAlterTablePlan(s *parser.AlterTableStmt) (Plan, error) {
+	plan := &AlterTablePlan{TableName: s.TableName, DropColumn: s.DropColumn, RenameTo: s.RenameTo}
+	if ref := s.AddForeignKey; ref != nil {
+		plan.ForeignKey = foreignKey(s.TableName, s.ForeignKeyColumn, ref)
+		if s.ForeignKeyName != "" {
+			plan.ForeignKey.Name = s.ForeignKeyName
+		}
+		return plan, nil
+	}
+	c := s.AddColumn
+	if c == nil {
+		return plan, nil
+	}
+	col, err := expr.SQLType(c.Type, c.Precision, c.Scale)
+	if err != nil {
+		return nil, err
+	}
+	if c.AutoIncrement {
+		return nil, fmt.Errorf("planner: AUTOINCREMENT column %s cannot be added to an existing table", c.Name)
+	}
+	col.Name, col.Nullable = c.Name, true
+	if c.Default != nil {
+		// NULL for a NOT NULL column is left to the database to reject.
+		schema := record.Schema{Cols: []record.Column{col}}
+		if plan.Default, err = coerceLiteralToColumn(schema, c.Name, c.Default.Value); err != nil {
+			return nil, err
+		}
+	}
+	col.Nullable = !c.NotNull && !c.PrimaryKey
+	if col.Collation, err = columnCollation(*c, col); err != nil {
+		return nil, err
+	}
+	plan.AddColumn = &col
+	plan.PrimaryKey, plan.Unique = c.PrimaryKey, c.Unique && !c.PrimaryKey
+	if ref := c.References; ref != nil {
+		plan.ForeignKey = foreignKey(s.TableName, c.Name, ref)
+	}
+	return plan, nil
+}
+
+// foreignKey is the ForeignKey of ref on column of table, under its default
+// name.
+func foreignKey(table, column string, ref *parser.ForeignKeyDef) *novasql.ForeignKey {
+	return &novasql.ForeignKey{
+		Name:      novasql.ForeignKeyName(table, column),
+		Column:    column,
+		RefTable:  ref.Table,
+		RefColumn: ref.Column,
+		OnDelete:  novasql.ForeignKeyAction(ref.OnDelete),
 	}
-	return &CreateTablePlan{
-		TableName: s.TableName,
-		Schema:    record.Schema{Cols: cols},
-	}, nil
 }
 
-func buildSelectPlan(s *parser.SelectStmt, db *novasql.Database) (Plan, error) {
+func buildCopyPlan(s *parser.CopyStmt) (Plan, error) {
+	opts := novasql.CSVOptions{Header: s.Header, Null: s.Null}
+	if s.Delimiter != "" {
+		d := []rune(s.Delimiter)
+		if len(d) != 1 || d[0] == '"' || d[0] == '\n' || d[0] == '\r' {
+			return nil, fmt.Errorf("planner: COPY delimiter must be a single character other than a quote or newline")
+		}
+		opts.Delimiter = d[0]
+	}
+	return &CopyPlan{TableName: s.TableName, File: s.File, From: s.From, Options: opts}, nil
+}
+
+// buildSelectPlan plans a query, or a subquery of the query whose columns
+// outer holds (nil at the top level).
+func buildSelectPlan(s *parser.SelectStmt, db *novasql.Database, outer *scope) (Plan, error) {
+	if len(s.Joins) > 0 {
+		return buildJoinPlan(s, db, outer)
+	}
+
+	// Columns may be qualified by the table's name, or its alias if it has one.
+	unqualify := qualifiedBy(cmp.Or(s.Alias, s.TableName))
+	s, err := resolveNames(s, unqualify)
+	if err != nil {
+		return nil, err
+	}
+
+	view, schema, err := expandView(db, s.TableName)
+	if err != nil {
+		return nil, err
+	}
+	if view != nil {
+		p := &ViewScanPlan{Name: s.TableName, Plan: view, Schema: schema}
+		if s.Where != nil {
+			if p.Where, err = bindQueryWhere(db, s.Where, tableScope(schema, unqualify), outer); err != nil {
+				return nil, err
+			}
+		}
+		if p.Shape, err = bindShape(db, schema, s); err != nil {
+			return nil, err
+		}
+		return p, nil
+	}
+
 	// Bind schema to coerce WHERE and choose index if possible
 	tbl, err := db.OpenTable(s.TableName)
 	if err != nil {
 		return nil, err
 	}
 
-	var where *WhereEq
+	var where *Where
 	if s.Where != nil {
-		w, err := bindWhereEq(tbl.Schema, s.Where)
+		w, err := bindQueryWhere(db, s.Where, tableScope(tbl.Schema, unqualify), outer)
 		if err != nil {
 			return nil, err
 		}
 		where = w
 	}
 
+	shape, err := bindShape(db, tbl.Schema, s)
+	if err != nil {
+		return nil, err
+	}
+	st, err := db.TableStats(s.TableName)
+	if err != nil {
+		return nil, err
+	}
+
+	// A MATCH on a column with a full-text index reads the rows through it,
+	// best match first, whatever the statistics say.
+	if where != nil && where.Expr != nil {
+		if p := fullTextScan(db, s.TableName, where, shape); p != nil {
+			return p, nil
+		}
+	}
+
+	// A condition comparing an indexed expression with a value may use that
+	// index the same way.
+	if where != nil && where.Expr != nil && useIndex(st, tbl.PageCount, where) {
+		if p := exprIndexScan(db, s.TableName, where, shape); p != nil {
+			return p, nil
+		}
+	}
+
+	// A comparison with a non-NULL literal, a placeholder or an outer column
+	// on a column with a KVTree index becomes an index range scan, unless the
+	// statistics tell a sequential scan is cheaper (see useIndex).
+	hasValue := where != nil && (where.Value != nil || where.Param != nil || where.Outer != nil)
+	if hasValue && useIndex(st, tbl.PageCount, where) {
+		if base, ok := findIndexBaseByColumn(db, s.TableName, where.Column, novasql.IndexKindKVTree); ok {
+			return &IndexScanPlan{TableName: s.TableName, IndexFileBase: base, Where: where, Shape: shape}, nil
+		}
+	}
+
 	// Optional: if WHERE is "col=int64" and there's btree index on that column => IndexLookupPlan
-	if where != nil {
+	if where != nil && where.Op == "=" && useIndex(st, tbl.PageCount, where) {
 		if key, ok := where.Value.(int64); ok {
-			base, ok := findBTreeIndexBaseByColumn(db, s.TableName, where.Column)
+			base, ok := findIndexBaseByColumn(db, s.TableName, where.Column, novasql.IndexKindBTree)
 			if ok && base != "" {
 				return &IndexLookupPlan{
 					TableName:     s.TableName,
@@ -86,12 +307,152 @@ func buildSelectPlan(s *parser.SelectStmt, db *novasql.Database) (Plan, error) {
 					Column:        where.Column,
 					Key:           key,
 					Where:         where,
+					Shape:         shape,
 				}, nil
 			}
 		}
 	}
 
-	return &SeqScanPlan{TableName: s.TableName, Where: where}, nil
+	return &SeqScanPlan{TableName: s.TableName, Where: where, Shape: shape}, nil
+}
+
+// qualifiedBy resolves the column names of a single-table query, which may
+// be qualified by qualifier.
+func qualifiedBy(qualifier string) func(string) (string, error) {
+	return func(name string) (string, error) {
+		q, col, ok := strings.Cut(name, ".")
+		if !ok {
+			return name, nil
+		}
+		if q != qualifier {
+			return "", fmt.Errorf("planner: unknown column: %s", name)
+		}
+		return col, nil
+	}
+}
+
+// bindShape checks the SELECT list and ORDER BY columns against schema.
+func bindShape(db *novasql.Database, schema record.Schema, s *parser.SelectStmt) (Shape, error) {
+	s, custom, err := bindUserAggregates(s, db.Functions())
+	if err != nil {
+		return Shape{}, err
+	}
+	if len(s.Aggregates) > 0 || len(s.GroupBy) > 0 || s.Having != nil {
+		return bindAggregateShape(db, schema, s, custom)
+	}
+
+	shape := Shape{Columns: s.Columns, Limit: s.Limit}
+	err = bindSelectExprs(&shape, schema, s, func(name string) (int, error) {
+		if pos := columnIndex(schema, name); pos >= 0 {
+			return pos, nil
+		}
+		return -1, fmt.Errorf("planner: unknown column in SELECT: %s", name)
+	}, db.Functions())
+	if err != nil {
+		return Shape{}, err
+	}
+	for i, c := range s.Columns {
+		if !isComputed(s, i) && columnIndex(schema, c) < 0 {
+			return Shape{}, fmt.Errorf("planner: unknown column in SELECT: %s", c)
+		}
+	}
+	for _, o := range s.OrderBy {
+		col := outputColumn(s, o.Column)
+		if col == "" && columnIndex(schema, o.Column) < 0 {
+			return Shape{}, fmt.Errorf("planner: unknown column in ORDER BY: %s", o.Column)
+		}
+		ob, err := orderBy(db, o, cmp.Or(col, o.Column))
+		if err != nil {
+			return Shape{}, err
+		}
+		shape.OrderBy = append(shape.OrderBy, ob)
+	}
+	return shape, nil
+}
+
+// orderBy is the OrderBy of o, which sorts column col, with the collation o
+// names: a built-in one or one of db.
+func orderBy(db *novasql.Database, o parser.OrderByItem, col string) (OrderBy, error) {
+	ob := OrderBy{Column: col, Desc: o.Desc}
+	if o.Collate == "" {
+		return ob, nil
+	}
+	coll, ok := db.Functions().Collation(o.Collate)
+	if !ok {
+		return OrderBy{}, fmt.Errorf("planner: unknown collation %s", o.Collate)
+	}
+	ob.Collation = coll
+	return ob, nil
+}
+
+// bindAggregateShape binds a SELECT with aggregates or GROUP BY. Its rows are
+// the groups, so the SELECT list, HAVING and ORDER BY may only name GROUP BY
+// columns and aggregates. custom holds the added aggregates s calls.
+func bindAggregateShape(
+	db *novasql.Database, schema record.Schema, s *parser.SelectStmt, custom map[string]*expr.Aggregate,
+) (Shape, error) {
+	if s.Columns == nil {
+		return Shape{}, fmt.Errorf("planner: SELECT * cannot be combined with aggregates or GROUP BY")
+	}
+	agg := &Aggregate{GroupBy: s.GroupBy, Funcs: s.Aggregates, Custom: custom}
+	out, err := agg.OutputSchema(schema)
+	if err != nil {
+		return Shape{}, err
+	}
+	check := func(clause, name string) error {
+		switch {
+		case columnIndex(out, name) >= 0:
+			return nil
+		case columnIndex(schema, name) >= 0:
+			return fmt.Errorf("planner: column %s in %s must appear in GROUP BY or be aggregated", name, clause)
+		default:
+			return fmt.Errorf("planner: unknown column in %s: %s", clause, name)
+		}
+	}
+
+	shape := Shape{Aggregate: agg, Columns: s.Columns, Limit: s.Limit}
+	err = bindSelectExprs(&shape, out, s, func(name string) (int, error) {
+		if err := check("SELECT", name); err != nil {
+			return -1, err
+		}
+		return columnIndex(out, name), nil
+	}, db.Functions())
+	if err != nil {
+		return Shape{}, err
+	}
+	for i, c := range s.Columns {
+		if isComputed(s, i) {
+			continue
+		}
+		if err := check("SELECT", c); err != nil {
+			return Shape{}, err
+		}
+	}
+	if s.Having != nil {
+		if s.Having.Subquery != nil {
+			return Shape{}, fmt.Errorf("planner: HAVING cannot have a subquery")
+		}
+		if err := check("HAVING", s.Having.Column); err != nil {
+			return Shape{}, err
+		}
+		if agg.Having, err = bindWhere(out, s.Having); err != nil {
+			return Shape{}, err
+		}
+	}
+	for _, o := range s.OrderBy {
+		col := outputColumn(s, o.Column)
+		if col == "" {
+			if err := check("ORDER BY", o.Column); err != nil {
+				return Shape{}, err
+			}
+		}
+		ob, err := orderBy(db, o, cmp.Or(col, o.Column))
+		if err != nil {
+			return Shape{}, err
+		}
+		shape.OrderBy = append(shape.OrderBy, ob)
+	}
+	return shape, nil
 }
 
 func buildUpdatePlan(s *parser.UpdateStmt, db *novasql.Database) (Plan, error) {
@@ -102,33 +463,40 @@ func buildUpdatePlan(s *parser.UpdateStmt, db *novasql.Database) (Plan, error) {
 
 	assigns := make([]Assignment, 0, len(s.Assignments))
 	for _, a := range s.Assignments {
-		lit, ok := a.Value.(*parser.LiteralExpr)
-		if !ok {
-			return nil, fmt.Errorf("planner: only literal supported in UPDATE SET")
+		if lit, ok := a.Value.(*parser.LiteralExpr); ok && lit.Value == nil {
+			if pos := columnIndex(tbl.Schema, a.Column); pos >= 0 && !tbl.Schema.Cols[pos].Nullable {
+				return nil, novasql.NotNullError(s.TableName, a.Column)
+			}
 		}
-		v, err := coerceLiteralToColumn(tbl.Schema, a.Column, lit.Value)
+		v, prm, err := bindValue(tbl.Schema, a.Column, a.Value)
 		if err != nil {
 			return nil, err
 		}
 		assigns = append(assigns, Assignment{
 			Column: a.Column,
 			Value:  v,
+			Param:  prm,
 		})
 	}
 
-	var where *WhereEq
+	var where *Where
 	if s.Where != nil {
-		w, err := bindWhereEq(tbl.Schema, s.Where)
+		w, err := bindQueryWhere(db, s.Where, tableScope(tbl.Schema, qualifiedBy(s.TableName)), nil)
 		if err != nil {
 			return nil, err
 		}
 		where = w
 	}
 
+	ret, err := bindReturning(tbl.Schema, s.Returning)
+	if err != nil {
+		return nil, err
+	}
 	return &UpdatePlan{
 		TableName: s.TableName,
 		Assigns:   assigns,
 		Where:     where,
+		Returning: ret,
 	}, nil
 }
 
@@ -138,27 +506,70 @@ func buildDeletePlan(s *parser.DeleteStmt, db *novasql.Database) (Plan, error) {
 		return nil, err
 	}
 
-	var where *WhereEq
+	var where *Where
 	if s.Where != nil {
-		w, err := bindWhereEq(tbl.Schema, s.Where)
+		w, err := bindQueryWhere(db, s.Where, tableScope(tbl.Schema, qualifiedBy(s.TableName)), nil)
 		if err != nil {
 			return nil, err
 		}
 		where = w
 	}
-	return &DeletePlan{TableName: s.TableName, Where: where}, nil
+	ret, err := bindReturning(tbl.Schema, s.Returning)
+	if err != nil {
+		return nil, err
+	}
+	return &DeletePlan{TableName: s.TableName, Where: where, Returning: ret}, nil
 }
 
-func bindWhereEq(schema record.Schema, w *parser.WhereEq) (*WhereEq, error) {
-	lit, ok := w.Value.(*parser.LiteralExpr)
-	if !ok {
-		return nil, fmt.Errorf("planner: only literal supported in WHERE")
+// bindReturning resolves the RETURNING columns of a statement on a table
+// with schema; "*" is every column, in table order.
+func bindReturning(schema record.Schema, cols []string) (*Returning, error) {
+	if cols == nil {
+		return nil, nil
+	}
+	ret := &Returning{}
+	for _, c := range cols {
+		if c == "*" {
+			for i, col := range schema.Cols {
+				ret.Positions = append(ret.Positions, i)
+				ret.Columns = append(ret.Columns, col.Name)
+			}
+			continue
+		}
+		pos := columnIndex(schema, c)
+		if pos < 0 {
+			return nil, fmt.Errorf("planner: unknown column in RETURNING: %s", c)
+		}
+		ret.Positions = append(ret.Positions, pos)
+		ret.Columns = append(ret.Columns, c)
 	}
-	v, err := coerceLiteralToColumn(schema, w.Column, lit.Value)
+	return ret, nil
+}
+
+func bindWhere(schema record.Schema, w *parser.Where) (*Where, error) {
+	v, prm, err := bindValue(schema, w.Column, w.Value)
 	if err != nil {
 		return nil, err
 	}
-	return &WhereEq{Column: w.Column, Value: v}, nil
+	return &Where{Column: w.Column, Op: w.Op, Value: v, Param: prm}, nil
+}
+
+// bindValue coerces a literal to column colName, or describes the
+// placeholder that stands for its value.
+func bindValue(schema record.Schema, colName string, e parser.Expr) (any, *Param, error) {
+	switch x := e.(type) {
+	case *parser.LiteralExpr:
+		v, err := coerceLiteralToColumn(schema, colName, x.Value)
+		return v, nil, err
+	case *parser.ParamExpr:
+		pos := columnIndex(schema, colName)
+		if pos < 0 {
+			return nil, nil, fmt.Errorf("planner: unknown column: %s", colName)
+		}
+		return nil, &Param{Index: x.Index, Column: schema.Cols[pos]}, nil
+	default:
+		return nil, nil, fmt.Errorf("planner: only literals and placeholders supported, got %T", e)
+	}
 }
 
 func coerceLiteralToColumn(schema record.Schema, colName string, v any) (any, error) {
@@ -194,6 +605,17 @@ func coerceLiteralToColumn(schema record.Schema, colName string, v any) (any, er
 		default:
 			return nil, fmt.Errorf("planner: column %s expects INT64, got %T", colName, v)
 		}
+	case record.ColFloat64:
+		switch x := v.(type) {
+		case float64:
+			return x, nil
+		case int64:
+			return float64(x), nil
+		case int:
+			return float64(x), nil
+		default:
+			return nil, fmt.Errorf("planner: column %s expects FLOAT, got %T", colName, v)
+		}
 	case record.ColText:
 		s, ok := v.(string)
 		if !ok {
@@ -206,27 +628,36 @@ func coerceLiteralToColumn(schema record.Schema, colName string, v any) (any, er
 			return nil, fmt.Errorf("planner: column %s expects BOOL, got %T", colName, v)
 		}
 		return b, nil
+	case record.ColJSON:
+		s, ok := v.(string)
+		if !ok {
+			return nil, fmt.Errorf("planner: column %s expects JSON, got %T", colName, v)
+		}
+		doc, err := expr.NormalizeJSON(s)
+		if err != nil {
+			return nil, fmt.Errorf("planner: column %s: %w", colName, err)
+		}
+		return doc, nil
+	case record.ColDate, record.ColTime, record.ColTimestamp:
+		t, err := record.TemporalValue(col.Type, v)
+		if err != nil {
+			return nil, fmt.Errorf("planner: column %s: %w", colName, err)
+		}
+		return t, nil
+	case record.ColDecimal:
+		d, err := record.DecimalValue(col, v)
+		if err != nil {
+			return nil, fmt.Errorf("planner: column %s: %w", colName, err)
+		}
+		return d, nil
 	default:
 		return nil, fmt.Errorf("planner: unsupported column type: %s", colName)
 	}
 }
 
-func mapSQLType(t string) (record.ColumnType, error) {
-	switch strings.ToUpper(t) {
-	case "INT", "INTEGER":
-		return record.ColInt64, nil
-	case "TEXT":
-		return record.ColText, nil
-	case "BOOL", "BOOLEAN":
-		return record.ColBool, nil
-	default:
-		return 0, fmt.Errorf("unsupported column type: %s", t)
-	}
-}
-
-// findBTreeIndexBaseByColumn tries to locate a btree index for (table, column).
-// NOTE: uses dynamic json decoding to avoid tight coupling to IndexMeta fields.
-func findBTreeIndexBaseByColumn(db *novasql.Database, table, col string) (string, bool) {
+// findIndexBaseByColumn tries to locate an index of the given kind for
+// (table, column).
+func findIndexBaseByColumn(db *novasql.Database, table, col string, kind novasql.IndexKind) (string, bool) {
 	metas, err := db.ListTables()
 	if err != nil {
 		return "", false
@@ -244,10 +675,10 @@ func findBTreeIndexBaseByColumn(db *novasql.Database, table, col string) (string
 	}
 
 	for _, im := range tm.Indexes {
-		if im.Kind != novasql.IndexKindBTree {
+		if im.Kind != kind {
 			continue
 		}
-		if im.KeyColumn != col {
+		if im.KeyColumn != col || im.Expression != "" {
 			continue
 		}
 
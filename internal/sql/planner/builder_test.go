@@ -5,6 +5,7 @@ import (
 
 	"github.com/stretchr/testify/require"
 
+	"github.com/tuannm99/novasql"
 	"github.com/tuannm99/novasql/internal/record"
 	"github.com/tuannm99/novasql/internal/sql/parser"
 )
@@ -80,6 +81,16 @@ func TestBuildPlan_CreateDropTable_NoDBNeeded(t *testing.T) {
 	}
 }
 
+func TestBuildPlan_CreateDropIndex_NoDBNeeded(t *testing.T) {
+	p, err := BuildPlan(&parser.CreateIndexStmt{IndexName: "users_age", TableName: "users", Column: "age"}, nil)
+	require.NoError(t, err)
+	require.Equal(t, &CreateIndexPlan{TableName: "users", IndexName: "users_age", Column: "age"}, p)
+
+	p, err = BuildPlan(&parser.DropIndexStmt{IndexName: "users_age", TableName: "users"}, nil)
+	require.NoError(t, err)
+	require.Equal(t, &DropIndexPlan{TableName: "users", IndexName: "users_age"}, p)
+}
+
 func TestBuildPlan_Insert_NoDBNeeded(t *testing.T) {
 	stmt := &parser.InsertStmt{
 		TableName: "users",
@@ -98,39 +109,6 @@ func TestBuildPlan_Insert_NoDBNeeded(t *testing.T) {
 	require.Len(t, plan.Values, 2)
 }
 
-func TestMapSQLType(t *testing.T) {
-	t.Run("int_variants", func(t *testing.T) {
-		got, err := mapSQLType("INT")
-		require.NoError(t, err)
-		require.Equal(t, record.ColInt64, got)
-
-		got, err = mapSQLType("integer")
-		require.NoError(t, err)
-		require.Equal(t, record.ColInt64, got)
-	})
-
-	t.Run("text", func(t *testing.T) {
-		got, err := mapSQLType("TeXt")
-		require.NoError(t, err)
-		require.Equal(t, record.ColText, got)
-	})
-
-	t.Run("bool_variants", func(t *testing.T) {
-		got, err := mapSQLType("BOOL")
-		require.NoError(t, err)
-		require.Equal(t, record.ColBool, got)
-
-		got, err = mapSQLType("boolean")
-		require.NoError(t, err)
-		require.Equal(t, record.ColBool, got)
-	})
-
-	t.Run("unsupported", func(t *testing.T) {
-		_, err := mapSQLType("FLOAT")
-		require.Error(t, err)
-	})
-}
-
 func TestCoerceLiteralToColumn(t *testing.T) {
 	schema := record.Schema{Cols: []record.Column{
 		{Name: "id", Type: record.ColInt64, Nullable: false},
@@ -177,6 +155,20 @@ func TestCoerceLiteralToColumn(t *testing.T) {
 		require.Error(t, err)
 	})
 
+	t.Run("float_accepts_ints", func(t *testing.T) {
+		fs := record.Schema{Cols: []record.Column{{Name: "score", Type: record.ColFloat64}}}
+		got, err := coerceLiteralToColumn(fs, "score", 1.5)
+		require.NoError(t, err)
+		require.InDelta(t, 1.5, got, 0)
+
+		got, err = coerceLiteralToColumn(fs, "score", int64(2))
+		require.NoError(t, err)
+		require.InDelta(t, 2.0, got, 0)
+
+		_, err = coerceLiteralToColumn(fs, "score", "x")
+		require.Error(t, err)
+	})
+
 	t.Run("text_ok", func(t *testing.T) {
 		got, err := coerceLiteralToColumn(schema, "name", "abc")
 		require.NoError(t, err)
@@ -200,24 +192,26 @@ func TestCoerceLiteralToColumn(t *testing.T) {
 	})
 }
 
-func TestBindWhereEq(t *testing.T) {
+func TestBindWhere(t *testing.T) {
 	schema := record.Schema{Cols: []record.Column{
 		{Name: "id", Type: record.ColInt64, Nullable: false},
 		{Name: "name", Type: record.ColText, Nullable: true},
 	}}
 
 	t.Run("ok_literal_is_coerced", func(t *testing.T) {
-		w, err := bindWhereEq(schema, &parser.WhereEq{
+		w, err := bindWhere(schema, &parser.Where{
 			Column: "id",
+			Op:     ">=",
 			Value:  &parser.LiteralExpr{Value: int64(1)},
 		})
 		require.NoError(t, err)
 		require.Equal(t, "id", w.Column)
+		require.Equal(t, ">=", w.Op)
 		require.Equal(t, int64(1), w.Value)
 	})
 
 	t.Run("unknown_column", func(t *testing.T) {
-		_, err := bindWhereEq(schema, &parser.WhereEq{
+		_, err := bindWhere(schema, &parser.Where{
 			Column: "nope",
 			Value:  &parser.LiteralExpr{Value: int64(1)},
 		})
@@ -226,11 +220,36 @@ func TestBindWhereEq(t *testing.T) {
 	})
 }
 
+func TestBindShape(t *testing.T) {
+	schema := record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64, Nullable: false},
+		{Name: "name", Type: record.ColText, Nullable: true},
+	}}
+	limit := int64(5)
+
+	shape, err := bindShape(schema, &parser.SelectStmt{
+		Columns: []string{"name"},
+		OrderBy: []parser.OrderByItem{{Column: "id", Desc: true}},
+		Limit:   &limit,
+	})
+	require.NoError(t, err)
+	require.Equal(t, Shape{
+		Columns: []string{"name"},
+		OrderBy: []OrderBy{{Column: "id", Desc: true}},
+		Limit:   &limit,
+	}, shape)
+
+	_, err = bindShape(schema, &parser.SelectStmt{Columns: []string{"nope"}})
+	require.ErrorContains(t, err, "unknown column in SELECT")
+	_, err = bindShape(schema, &parser.SelectStmt{OrderBy: []parser.OrderByItem{{Column: "nope"}}})
+	require.ErrorContains(t, err, "unknown column in ORDER BY")
+}
+
 func TestBuildCreateTablePlan(t *testing.T) {
 	stmt := &parser.CreateTableStmt{
 		TableName: "t",
 		Columns: []parser.ColumnDef{
-			{Name: "id", Type: "INT"},
+			{Name: "id", Type: "INT", NotNull: true},
 			{Name: "name", Type: "TEXT"},
 			{Name: "ok", Type: "BOOL"},
 		},
@@ -245,6 +264,107 @@ func TestBuildCreateTablePlan(t *testing.T) {
 	require.Equal(t, record.ColInt64, plan.Schema.Cols[0].Type)
 	require.Equal(t, record.ColText, plan.Schema.Cols[1].Type)
 	require.Equal(t, record.ColBool, plan.Schema.Cols[2].Type)
+	require.False(t, plan.Schema.Cols[0].Nullable)
+	require.True(t, plan.Schema.Cols[1].Nullable)
+	require.Empty(t, plan.PrimaryKey)
+	require.Empty(t, plan.Unique)
+
+	stmt.Columns[1].PrimaryKey = true
+	stmt.Columns[2].Unique = true
+	stmt.Columns[2].References = &parser.ForeignKeyDef{Table: "flags", OnDelete: "CASCADE"}
+	p, err = buildCreateTablePlan(stmt)
+	require.NoError(t, err)
+	plan, ok = p.(*CreateTablePlan)
+	require.True(t, ok)
+	require.False(t, plan.Schema.Cols[1].Nullable)
+	require.Equal(t, "name", plan.PrimaryKey)
+	require.Equal(t, []string{"ok"}, plan.Unique)
+	require.Equal(t, []novasql.ForeignKey{{
+		Name:     "t_ok_fkey",
+		Column:   "ok",
+		RefTable: "flags",
+		OnDelete: novasql.ForeignKeyCascade,
+	}}, plan.ForeignKeys)
+}
+
+func TestBuildCreateTablePlan_AutoIncrement(t *testing.T) {
+	stmt := &parser.CreateTableStmt{
+		TableName: "t",
+		Columns: []parser.ColumnDef{
+			{Name: "id", Type: "INT", NotNull: true, PrimaryKey: true, AutoIncrement: true},
+			{Name: "name", Type: "TEXT", AutoIncrement: true},
+		},
+	}
+	_, err := buildCreateTablePlan(stmt)
+	require.Error(t, err)
+
+	stmt.Columns[1].AutoIncrement = false
+	p, err := buildCreateTablePlan(stmt)
+	require.NoError(t, err)
+	plan, ok := p.(*CreateTablePlan)
+	require.True(t, ok)
+	require.Equal(t, "id", plan.AutoIncrement)
+}
+
+func TestBuildAlterTablePlan(t *testing.T) {
+	stmt := &parser.AlterTableStmt{
+		TableName: "t",
+		AddColumn: &parser.ColumnDef{
+			Name:       "score",
+			Type:       "FLOAT",
+			NotNull:    true,
+			Unique:     true,
+			Default:    &parser.LiteralExpr{Value: int64(1)},
+			References: &parser.ForeignKeyDef{Table: "scores"},
+		},
+	}
+	p, err := buildAlterTablePlan(stmt)
+	require.NoError(t, err)
+	require.Equal(t, &AlterTablePlan{
+		TableName:  "t",
+		AddColumn:  &record.Column{Name: "score", Type: record.ColFloat64},
+		Default:    float64(1),
+		Unique:     true,
+		ForeignKey: &novasql.ForeignKey{Name: "t_score_fkey", Column: "score", RefTable: "scores"},
+	}, p)
+
+	stmt.AddColumn.Default.Value = "high"
+	_, err = buildAlterTablePlan(stmt)
+	require.Error(t, err)
+	stmt.AddColumn.Default = nil
+	stmt.AddColumn.AutoIncrement = true
+	_, err = buildAlterTablePlan(stmt)
+	require.Error(t, err)
+
+	p, err = buildAlterTablePlan(&parser.AlterTableStmt{
+		TableName:        "posts",
+		AddForeignKey:    &parser.ForeignKeyDef{Table: "users", OnDelete: "SET NULL"},
+		ForeignKeyColumn: "author",
+		ForeignKeyName:   "posts_author",
+	})
+	require.NoError(t, err)
+	require.Equal(t, &AlterTablePlan{TableName: "posts", ForeignKey: &novasql.ForeignKey{
+		Name:     "posts_author",
+		Column:   "author",
+		RefTable: "users",
+		OnDelete: novasql.ForeignKeySetNull,
+	}}, p)
+}
+
+func TestBuildCopyPlan(t *testing.T) {
+	p, err := buildCopyPlan(&parser.CopyStmt{TableName: "t", From: true, File: "t.csv", Header: true, Delimiter: "|"})
+	require.NoError(t, err)
+	require.Equal(t, &CopyPlan{
+		TableName: "t",
+		File:      "t.csv",
+		From:      true,
+		Options:   novasql.CSVOptions{Header: true, Delimiter: '|'},
+	}, p)
+
+	for _, d := range []string{";;", `"`, "\n"} {
+		_, err := buildCopyPlan(&parser.CopyStmt{TableName: "t", File: "t.csv", Delimiter: d})
+		require.Error(t, err, d)
+	}
 }
 
 func TestBuildCreateTablePlan_UnsupportedType(t *testing.T) {
@@ -257,3 +377,44 @@ func TestBuildCreateTablePlan_UnsupportedType(t *testing.T) {
 	_, err := buildCreateTablePlan(stmt)
 	require.Error(t, err)
 }
+
+func TestBindShape_Aggregates(t *testing.T) {
+	schema := record.Schema{Cols: []record.Column{
+		{Name: "dept", Type: record.ColText, Nullable: true},
+		{Name: "age", Type: record.ColInt64, Nullable: false},
+		{Name: "name", Type: record.ColText, Nullable: true},
+	}}
+	count := parser.Aggregate{Func: "COUNT", Column: "*"}
+	avg := parser.Aggregate{Func: "AVG", Column: "age"}
+
+	shape, err := bindShape(schema, &parser.SelectStmt{
+		Columns:    []string{"dept", "avg(age)"},
+		GroupBy:    []string{"dept"},
+		Having:     &parser.Where{Column: "count(*)", Op: ">", Value: &parser.LiteralExpr{Value: int64(1)}},
+		Aggregates: []parser.Aggregate{avg, count},
+		OrderBy:    []parser.OrderByItem{{Column: "count(*)", Desc: true}},
+	})
+	require.NoError(t, err)
+	require.Equal(t, &Aggregate{
+		GroupBy: []string{"dept"},
+		Funcs:   []parser.Aggregate{avg, count},
+		Having:  &Where{Column: "count(*)", Op: ">", Value: int64(1)},
+	}, shape.Aggregate)
+
+	out, err := shape.Aggregate.OutputSchema(schema)
+	require.NoError(t, err)
+	require.Equal(t, []record.Column{
+		{Name: "dept", Type: record.ColText, Nullable: true},
+		{Name: "avg(age)", Type: record.ColFloat64, Nullable: true},
+		{Name: "count(*)", Type: record.ColInt64},
+	}, out.Cols)
+
+	_, err = bindShape(schema, &parser.SelectStmt{Columns: []string{"name", "count(*)"},
+		Aggregates: []parser.Aggregate{count}})
+	require.ErrorContains(t, err, "must appear in GROUP BY")
+	_, err = bindShape(schema, &parser.SelectStmt{Columns: []string{"sum(name)"},
+		Aggregates: []parser.Aggregate{{Func: "SUM", Column: "name"}}})
+	require.ErrorContains(t, err, "numeric")
+	_, err = bindShape(schema, &parser.SelectStmt{GroupBy: []string{"dept"}})
+	require.ErrorContains(t, err, "SELECT *")
+}
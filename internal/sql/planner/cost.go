@@ -0,0 +1,332 @@
+package planner
+
+import (
+	"cmp"
+	"slices"
+	"strings"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+// Cost model
+//
+// With the statistics of ANALYZE the planner estimates what a plan costs,
+// in units of one sequential page read: a sequential scan reads every page
+// of the heap once, an index scan fetches each row it finds from a heap page
+// out of order, and a join handles the rows of both inputs. Without
+// statistics it keeps its fixed rules: an index whenever one fits, joins in
+// query order.
+
+const (
+	randomPageCost = 4.0     // one heap page read out of order
+	cpuTupleCost   = 0.01    // handling one row
+	hashBuildCost  = 0.02    // adding one row to a hash table
+	defaultSel     = 1.0 / 3 // comparison with a value not known yet
+
+	// maxReorderTables bounds the join orders tried (n! of them).
+	maxReorderTables = 5
+)
+
+// useIndex reports whether reading the rows w matches through an index is
+// estimated to be cheaper than a sequential scan of pages heap pages.
+func useIndex(st *novasql.TableStats, pages uint32, w *Where) bool {
+	if st == nil {
+		return true
+	}
+	rows := float64(st.Rows)
+	indexCost := selectivity(st, w) * rows * (randomPageCost + cpuTupleCost)
+	seqCost := float64(pages) + rows*cpuTupleCost
+	return indexCost < seqCost
+}
+
+// selectivity estimates the fraction of the rows of a table w matches.
+func selectivity(st *novasql.TableStats, w *Where) float64 {
+	if st.Rows == 0 {
+		return 0
+	}
+	cs, ok := st.Columns[w.Column]
+	if !ok {
+		return defaultSel
+	}
+	nulls := float64(cs.Nulls) / float64(st.Rows)
+	eq := 0.0
+	if cs.Distinct > 0 {
+		eq = 1 / float64(cs.Distinct)
+	}
+	if w.Param == nil && w.Outer == nil && w.Value == nil {
+		if w.Op == "" || w.Op == "=" {
+			return nulls
+		}
+		return 0
+	}
+	if w.Param != nil || w.Outer != nil {
+		if w.Op == "" || w.Op == "=" {
+			return (1 - nulls) * eq
+		}
+		return (1 - nulls) * defaultSel
+	}
+
+	below := histogramBelow(cs.Bounds, w.Value)
+	var frac float64
+	switch w.Op {
+	case "", "=":
+		frac = eq
+	case "<":
+		frac = below
+	case "<=":
+		frac = below + eq
+	case ">":
+		frac = 1 - below - eq
+	case ">=":
+		frac = 1 - below
+	default:
+		frac = defaultSel
+	}
+	return (1 - nulls) * min(max(frac, 0), 1)
+}
+
+// histogramBelow estimates the fraction of the values in an equi-depth
+// histogram that are less than v, interpolating within numeric buckets.
+func histogramBelow(bounds []any, v any) float64 {
+	switch {
+	case len(bounds) == 0 || compareStat(v, bounds[0]) <= 0:
+		return 0
+	case compareStat(v, bounds[len(bounds)-1]) > 0:
+		return 1
+	}
+	n := len(bounds) - 1
+	for i := 1; i < n; i++ {
+		if compareStat(v, bounds[i]) <= 0 {
+			return (float64(i-1) + within(bounds[i-1], bounds[i], v)) / float64(n)
+		}
+	}
+	return (float64(n-1) + within(bounds[n-1], bounds[n], v)) / float64(n)
+}
+
+// within estimates how far into the bucket [lo, hi] v lies.
+func within(lo, hi, v any) float64 {
+	l, lok := toFloat(lo)
+	h, hok := toFloat(hi)
+	x, xok := toFloat(v)
+	if !lok || !hok || !xok || h <= l {
+		return 0.5
+	}
+	return min(max((x-l)/(h-l), 0), 1)
+}
+
+func toFloat(v any) (float64, bool) {
+	switch x := statValue(v).(type) {
+	case int64:
+		return float64(x), true
+	case float64:
+		return x, true
+	}
+	return 0, false
+}
+
+// compareStat orders values of the same column type; it matches the order
+// of the index keys the histograms were built in.
+func compareStat(a, b any) int {
+	a, b = statValue(a), statValue(b)
+	switch x := a.(type) {
+	case int64:
+		if y, ok := b.(int64); ok {
+			return cmp.Compare(x, y)
+		}
+	case float64:
+		if y, ok := b.(float64); ok {
+			return cmp.Compare(x, y)
+		}
+	case string:
+		if y, ok := b.(string); ok {
+			return strings.Compare(x, y)
+		}
+	case bool:
+		if y, ok := b.(bool); ok && x != y {
+			if x {
+				return 1
+			}
+			return -1
+		}
+	}
+	return 0
+}
+
+// statValue is v, with a date or a time as the int64 it is stored as and a
+// decimal as a float.
+func statValue(v any) any {
+	switch x := v.(type) {
+	case record.Date:
+		return int64(x)
+	case record.Time:
+		return int64(x)
+	case record.Timestamp:
+		return int64(x)
+	case record.Decimal:
+		return x.Float64()
+	}
+	return v
+}
+
+// joinTable is what reordering knows about one table of a JoinPlan.
+type joinTable struct {
+	ref   TableRef
+	start int      // position of its first column in JoinPlan.Schema
+	cols  []string // its column names, unqualified
+	stats *novasql.TableStats
+}
+
+// globalCond is a join condition between two columns of JoinPlan.Schema.
+type globalCond struct {
+	left  int
+	op    string
+	right int
+}
+
+// reorderJoins picks the cheapest order to join the tables of an inner-join
+// plan in, when every table has statistics, and rewrites p for it: From,
+// Joins and their conditions follow the new order and Layout restores the
+// column order of the query.
+func reorderJoins(p *JoinPlan, tables []joinTable) {
+	if len(tables) < 2 || len(tables) > maxReorderTables {
+		return
+	}
+	for _, j := range p.Joins {
+		if j.Left {
+			return
+		}
+	}
+	for _, t := range tables {
+		if t.stats == nil {
+			return
+		}
+	}
+
+	var conds []globalCond
+	for i, j := range p.Joins {
+		for _, c := range j.On {
+			conds = append(conds, globalCond{left: c.Left, op: c.Op, right: tables[i+1].start + c.Right})
+		}
+	}
+	tableOf := func(pos int) int {
+		for i, t := range tables {
+			if pos < t.start+len(t.cols) {
+				return i
+			}
+		}
+		return len(tables) - 1
+	}
+
+	best := make([]int, len(tables))
+	for i := range best {
+		best[i] = i
+	}
+	bestCost := joinOrderCost(best, tables, conds, tableOf)
+	permute(slices.Clone(best), 0, func(order []int) {
+		if c := joinOrderCost(order, tables, conds, tableOf); c < bestCost {
+			bestCost = c
+			copy(best, order)
+		}
+	})
+	if isIdentity(best) {
+		return
+	}
+
+	// New column positions, table by table in the new order.
+	newStart := make([]int, len(tables))
+	pos := 0
+	for _, t := range best {
+		newStart[t] = pos
+		pos += len(tables[t].cols)
+	}
+	moved := func(g int) int {
+		t := tableOf(g)
+		return newStart[t] + g - tables[t].start
+	}
+
+	added := make([]bool, len(tables))
+	added[best[0]] = true
+	p.From = tables[best[0]].ref
+	p.Joins = p.Joins[:0]
+	for _, t := range best[1:] {
+		j := Join{Table: tables[t].ref}
+		for _, c := range conds {
+			l, r, op := c.left, c.right, c.op
+			if tableOf(l) == t {
+				l, r, op = r, l, mirrorOps[op]
+			}
+			if tableOf(r) != t || !added[tableOf(l)] {
+				continue
+			}
+			j.On = append(j.On, JoinCond{Left: moved(l), Op: op, Right: r - tables[t].start})
+		}
+		added[t] = true
+		p.Joins = append(p.Joins, j)
+	}
+	p.Layout = make([]int, len(p.Schema.Cols))
+	for g := range p.Layout {
+		p.Layout[g] = moved(g)
+	}
+}
+
+// joinOrderCost estimates joining the tables in order: each step builds a
+// hash table of the next table and probes it with the rows so far when an
+// equality connects them, or tries every pair of rows when none does.
+func joinOrderCost(order []int, tables []joinTable, conds []globalCond, tableOf func(int) int) float64 {
+	added := make([]bool, len(tables))
+	added[order[0]] = true
+	rows := float64(tables[order[0]].stats.Rows)
+	cost := 0.0
+	for _, t := range order[1:] {
+		inner := float64(tables[t].stats.Rows)
+		sel, equi := 1.0, false
+		for _, c := range conds {
+			lt, rt := tableOf(c.left), tableOf(c.right)
+			if !((lt == t && added[rt]) || (rt == t && added[lt])) {
+				continue
+			}
+			if c.op != "=" {
+				sel *= defaultSel
+				continue
+			}
+			equi = true
+			sel /= float64(max(distinct(tables[lt], c.left), distinct(tables[rt], c.right), 1))
+		}
+		if equi {
+			cost += inner*hashBuildCost + rows*cpuTupleCost
+		} else {
+			cost += rows * inner * cpuTupleCost
+		}
+		rows *= inner * sel
+		cost += rows * cpuTupleCost
+		added[t] = true
+	}
+	return cost
+}
+
+func distinct(t joinTable, pos int) int64 {
+	return t.stats.Columns[t.cols[pos-t.start]].Distinct
+}
+
+// permute calls f with every ordering of order[k:].
+func permute(order []int, k int, f func([]int)) {
+	if k == len(order) {
+		f(order)
+		return
+	}
+	for i := k; i < len(order); i++ {
+		order[k], order[i] = order[i], order[k]
+		permute(order, k+1, f)
+		order[k], order[i] = order[i], order[k]
+	}
+}
+
+func isIdentity(order []int) bool {
+	for i, t := range order {
+		if i != t {
+			return false
+		}
+	}
+	return true
+}
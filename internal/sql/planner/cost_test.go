@@ -0,0 +1,33 @@
+package planner
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql"
+)
+
+func TestSelectivity(t *testing.T) {
+	st := &novasql.TableStats{
+		Rows: 1000,
+		Columns: map[string]novasql.ColumnStats{
+			"age": {Nulls: 200, Distinct: 80, Bounds: []any{int64(0), int64(20), int64(40), int64(60), int64(80)}},
+		},
+	}
+	sel := func(op string, v any) float64 { return selectivity(st, &Where{Column: "age", Op: op, Value: v}) }
+
+	require.InDelta(t, 0.2, sel("=", nil), 1e-9)
+	require.InDelta(t, 0.8/80, sel("=", int64(5)), 1e-9)
+	require.InDelta(t, 0.4, sel("<", int64(40)), 1e-9)
+	require.InDelta(t, 0.2, sel("<", int64(20)), 1e-9)
+	require.InDelta(t, 0.1, sel("<", int64(10)), 1e-9)
+	require.InDelta(t, 0.8, sel(">=", int64(-5)), 1e-9)
+	require.InDelta(t, 0.0, sel(">", int64(100)), 1e-9)
+	require.InDelta(t, 0.8/3, selectivity(st, &Where{Column: "age", Op: ">", Param: &Param{Index: 1}}), 1e-9)
+	require.InDelta(t, defaultSel, selectivity(st, &Where{Column: "other", Op: "="}), 1e-9)
+
+	require.True(t, useIndex(nil, 100, &Where{Column: "age", Op: ">", Value: int64(0)}))
+	require.True(t, useIndex(st, 100, &Where{Column: "age", Op: "=", Value: int64(5)}))
+	require.False(t, useIndex(st, 100, &Where{Column: "age", Op: ">", Value: int64(0)}))
+}
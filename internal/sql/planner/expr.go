@@ -0,0 +1,267 @@
+package planner
+
+import (
+	"fmt"
+	"slices"
+	"strconv"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/expr"
+	"github.com/tuannm99/novasql/internal/sql/parser"
+)
+
+// RowSchema is the schema of the rows of a query over schema once they are
+// grouped and have the computed columns, which Columns and OrderBy name.
+func (s Shape) RowSchema(schema record.Schema) (record.Schema, error) {
+	if s.Aggregate != nil {
+		var err error
+		if schema, err = s.Aggregate.OutputSchema(schema); err != nil {
+			return record.Schema{}, err
+		}
+	}
+	if len(s.Exprs) == 0 {
+		return schema, nil
+	}
+	out := record.Schema{Cols: slices.Clone(schema.Cols)}
+	for _, c := range s.Exprs {
+		out.Cols = append(out.Cols, record.Column{Name: c.Name, Type: c.Expr.Type(), Nullable: true})
+	}
+	return out, nil
+}
+
+// computedName is the name of the computed column of the i-th SELECT entry.
+func computedName(i int) string { return "#" + strconv.Itoa(i+1) }
+
+// bindSelectExprs binds the expressions of the SELECT list over schema, the
+// rows as Aggregate leaves them, where resolve finds their columns. Their
+// entries of shape.Columns become the computed columns, and Names keeps the
+// names the result shows.
+func bindSelectExprs(
+	shape *Shape, schema record.Schema, s *parser.SelectStmt, resolve func(string) (int, error), funcs *expr.Registry,
+) error {
+	if len(s.Exprs) == 0 {
+		return nil
+	}
+	shape.Columns = slices.Clone(s.Columns)
+	shape.Names = s.Columns
+	for _, se := range s.Exprs {
+		e, err := expr.Bind(se.Expr, schema, resolve, funcs)
+		if err != nil {
+			return err
+		}
+		name := computedName(se.Index)
+		shape.Exprs = append(shape.Exprs, Computed{Name: name, Expr: e})
+		shape.Columns[se.Index] = name
+	}
+	return nil
+}
+
+// isComputed reports whether entry i of the SELECT list of s is an
+// expression.
+func isComputed(s *parser.SelectStmt, i int) bool {
+	return slices.ContainsFunc(s.Exprs, func(se parser.SelectExpr) bool { return se.Index == i })
+}
+
+// outputColumn is the computed column an ORDER BY name refers to, the name of
+// an expression of the SELECT list; "" when it names none.
+func outputColumn(s *parser.SelectStmt, name string) string {
+	for _, se := range s.Exprs {
+		if s.Columns[se.Index] == name {
+			return computedName(se.Index)
+		}
+	}
+	return ""
+}
+
+// renameColumns returns a copy of e with every column renamed by f.
+func renameColumns(e parser.Expr, f func(string) (string, error)) (parser.Expr, error) {
+	return rewriteExpr(e, func(e parser.Expr) (parser.Expr, bool, error) {
+		c, ok := e.(*parser.ColumnExpr)
+		if !ok {
+			return nil, false, nil
+		}
+		name, err := f(c.Name)
+		if err != nil {
+			return nil, true, err
+		}
+		return &parser.ColumnExpr{Name: name}, true, nil
+	})
+}
+
+// rewriteExpr returns a copy of e in which every node that f takes (its
+// second result) is replaced by what f makes of it.
+func rewriteExpr(e parser.Expr, f func(parser.Expr) (parser.Expr, bool, error)) (parser.Expr, error) {
+	if e == nil {
+		return nil, nil
+	}
+	if out, ok, err := f(e); ok || err != nil {
+		return out, err
+	}
+	var err error
+	rewrite := func(e parser.Expr) parser.Expr {
+		if err != nil {
+			return e
+		}
+		var out parser.Expr
+		out, err = rewriteExpr(e, f)
+		return out
+	}
+	switch x := e.(type) {
+	case *parser.BinaryExpr:
+		e = &parser.BinaryExpr{Op: x.Op, Left: rewrite(x.Left), Right: rewrite(x.Right), Escape: rewrite(x.Escape)}
+	case *parser.UnaryExpr:
+		e = &parser.UnaryExpr{Op: x.Op, X: rewrite(x.X)}
+	case *parser.CollateExpr:
+		e = &parser.CollateExpr{X: rewrite(x.X), Collation: x.Collation}
+	case *parser.FuncExpr:
+		call := &parser.FuncExpr{Name: x.Name}
+		for _, a := range x.Args {
+			call.Args = append(call.Args, rewrite(a))
+		}
+		e = call
+	case *parser.CaseExpr:
+		c := &parser.CaseExpr{Operand: rewrite(x.Operand), Else: rewrite(x.Else)}
+		for _, w := range x.Whens {
+			c.Whens = append(c.Whens, parser.CaseWhen{When: rewrite(w.When), Then: rewrite(w.Then)})
+		}
+		e = c
+	}
+	return e, err
+}
+
+// bindUserAggregates returns s with the calls of the aggregates of funcs in
+// its SELECT list replaced by references to them, which join s.Aggregates
+// like those of COUNT and SUM. The map holds the aggregates called, by name
+// (nil when there are none).
+func bindUserAggregates(
+	s *parser.SelectStmt, funcs *expr.Registry,
+) (*parser.SelectStmt, map[string]*expr.Aggregate, error) {
+	var custom map[string]*expr.Aggregate
+	q := *s
+	q.Exprs = slices.Clone(s.Exprs)
+	for i := range q.Exprs {
+		var err error
+		q.Exprs[i].Expr, err = rewriteExpr(q.Exprs[i].Expr, func(e parser.Expr) (parser.Expr, bool, error) {
+			call, ok := e.(*parser.FuncExpr)
+			if !ok {
+				return nil, false, nil
+			}
+			a, ok := funcs.Aggregate(call.Name)
+			if !ok {
+				return nil, false, nil
+			}
+			var col *parser.ColumnExpr
+			if len(call.Args) == 1 {
+				col, _ = call.Args[0].(*parser.ColumnExpr)
+			}
+			if col == nil {
+				return nil, true, fmt.Errorf("planner: aggregate %s takes one column", a.Name)
+			}
+			agg := parser.Aggregate{Func: a.Name, Column: col.Name}
+			if !slices.Contains(q.Aggregates, agg) {
+				q.Aggregates = append(slices.Clip(q.Aggregates), agg)
+			}
+			if custom == nil {
+				custom = make(map[string]*expr.Aggregate)
+			}
+			custom[a.Name] = a
+			return &parser.ColumnExpr{Name: agg.Name()}, true, nil
+		})
+		if err != nil {
+			return nil, nil, err
+		}
+	}
+	return &q, custom, nil
+}
+
+// bindExprWhere binds a WHERE condition that is an expression over the
+// columns of self.
+func bindExprWhere(db *novasql.Database, w *parser.Where, self *scope) (*Where, error) {
+	e, err := expr.Bind(w.Expr, self.schema, self.resolve, db.Functions())
+	if err != nil {
+		return nil, err
+	}
+	if e.Type() != record.ColBool {
+		return nil, fmt.Errorf("planner: WHERE must be a condition, not %s", e)
+	}
+	return &Where{Expr: e}, nil
+}
+
+// exprIndexScan returns the plan reading the rows of table matching where,
+// an expression, through a KVTree index on an expression that one of its
+// conditions compares with a value; nil when there is none.
+func exprIndexScan(db *novasql.Database, table string, where *Where, shape Shape) *IndexScanPlan {
+	metas, err := db.ListIndexes(table)
+	if err != nil {
+		return nil
+	}
+	for _, c := range where.Expr.Conjuncts() {
+		left, op, v, ok := c.Comparison()
+		if !ok {
+			continue
+		}
+		for _, im := range metas {
+			if im.Kind == novasql.IndexKindKVTree && im.Expression != "" && im.Expression == left.String() {
+				w := &Where{Op: op, Value: v, Expr: where.Expr}
+				return &IndexScanPlan{TableName: table, IndexFileBase: im.FileBase, Where: w, Shape: shape}
+			}
+		}
+	}
+	return nil
+}
+
+// fullTextScan returns the plan reading the rows of table matching where, an
+// expression, through a full-text index on a column that one of its
+// conditions MATCHes with a query; nil when there is none.
+func fullTextScan(db *novasql.Database, table string, where *Where, shape Shape) *IndexScanPlan {
+	metas, err := db.ListIndexes(table)
+	if err != nil {
+		return nil
+	}
+	for _, c := range where.Expr.Conjuncts() {
+		col, query, ok := c.FullTextMatch()
+		if !ok {
+			continue
+		}
+		for _, im := range metas {
+			if im.Kind == novasql.IndexKindFullText && im.KeyColumn == col.String() {
+				w := &Where{Column: im.KeyColumn, Op: "MATCH", Value: query, Expr: where.Expr}
+				return &IndexScanPlan{TableName: table, IndexFileBase: im.FileBase, Where: w, Shape: shape}
+			}
+		}
+	}
+	return nil
+}
+
+func buildCreateIndexPlan(s *parser.CreateIndexStmt, db *novasql.Database) (Plan, error) {
+	plan := &CreateIndexPlan{
+		TableName: s.TableName,
+		IndexName: s.IndexName,
+		Column:    s.Column,
+		Unique:    s.Unique,
+		FullText:  s.FullText,
+	}
+	if s.FullText && (s.Unique || s.Expr != nil) {
+		return nil, fmt.Errorf("planner: a full-text index must be on a column and cannot be UNIQUE")
+	}
+	if s.Expr == nil {
+		return plan, nil
+	}
+	if s.Unique {
+		return nil, fmt.Errorf("planner: a UNIQUE index must be on a column, not an expression")
+	}
+	tbl, err := db.OpenTable(s.TableName)
+	if err != nil {
+		return nil, err
+	}
+	e, err := expr.Bind(s.Expr, tbl.Schema, tableScope(tbl.Schema, qualifiedBy(s.TableName)).resolve, nil)
+	if err != nil {
+		return nil, err
+	}
+	if e.Volatile() {
+		return nil, fmt.Errorf("planner: cannot index %s: its value changes from call to call", e)
+	}
+	plan.Expression = e.String()
+	return plan, nil
+}
@@ -0,0 +1,234 @@
+package planner
+
+import (
+	"cmp"
+	"fmt"
+	"slices"
+	"strings"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/parser"
+)
+
+// mirrorOps flips a comparison for swapped operands: a < b is b > a.
+var mirrorOps = map[string]string{"=": "=", "<": ">", "<=": ">=", ">": "<", ">=": "<="}
+
+func buildJoinPlan(s *parser.SelectStmt, db *novasql.Database, outer *scope) (Plan, error) {
+	p := &JoinPlan{From: TableRef{Name: s.TableName, Alias: cmp.Or(s.Alias, s.TableName)}}
+	var aliases []string
+	var tables []joinTable
+	addTable := func(ref *TableRef, nullable bool) error {
+		if slices.Contains(aliases, ref.Alias) {
+			return fmt.Errorf("planner: table name %q specified more than once", ref.Alias)
+		}
+		aliases = append(aliases, ref.Alias)
+		view, schema, err := expandView(db, ref.Name)
+		if err != nil {
+			return err
+		}
+		var st *novasql.TableStats // none for a view
+		if view != nil {
+			ref.View = view
+		} else {
+			tbl, err := db.OpenTable(ref.Name)
+			if err != nil {
+				return err
+			}
+			if st, err = db.TableStats(ref.Name); err != nil {
+				return err
+			}
+			schema = tbl.Schema
+		}
+		jt := joinTable{ref: *ref, start: len(p.Schema.Cols), stats: st}
+		for _, c := range schema.Cols {
+			jt.cols = append(jt.cols, c.Name)
+			c.Name = ref.Alias + "." + c.Name
+			c.Nullable = c.Nullable || nullable
+			p.Schema.Cols = append(p.Schema.Cols, c)
+		}
+		tables = append(tables, jt)
+		return nil
+	}
+	if err := addTable(&p.From, false); err != nil {
+		return nil, err
+	}
+
+	for _, jc := range s.Joins {
+		j := Join{Table: TableRef{Name: jc.TableName, Alias: cmp.Or(jc.Alias, jc.TableName)}, Left: jc.Left}
+		start := len(p.Schema.Cols)
+		if err := addTable(&j.Table, j.Left); err != nil {
+			return nil, err
+		}
+		for _, c := range jc.On {
+			cond, err := bindJoinCond(p.Schema, start, c)
+			if err != nil {
+				return nil, fmt.Errorf("planner: JOIN %s: %w", j.Table.Alias, err)
+			}
+			j.On = append(j.On, cond)
+		}
+		p.Joins = append(p.Joins, j)
+	}
+
+	q, err := resolveNames(s, func(name string) (string, error) {
+		pos, err := resolveColumn(p.Schema, name)
+		if err != nil {
+			return "", err
+		}
+		return p.Schema.Cols[pos].Name, nil
+	})
+	if err != nil {
+		return nil, err
+	}
+	if q.Where != nil {
+		self := &scope{schema: p.Schema, resolve: func(name string) (int, error) { return resolveColumn(p.Schema, name) }}
+		if p.Where, err = bindQueryWhere(db, q.Where, self, outer); err != nil {
+			return nil, err
+		}
+	}
+	if p.Shape, err = bindShape(db, p.Schema, q); err != nil {
+		return nil, err
+	}
+	reorderJoins(p, tables)
+	return p, nil
+}
+
+// bindJoinCond binds an ON comparison of the table whose columns start at
+// start in schema: one side must be a column of that table, the other a
+// column of a table before it.
+func bindJoinCond(schema record.Schema, start int, c parser.JoinCond) (JoinCond, error) {
+	l, err := resolveColumn(schema, c.Left)
+	if err != nil {
+		return JoinCond{}, err
+	}
+	r, err := resolveColumn(schema, c.Right)
+	if err != nil {
+		return JoinCond{}, err
+	}
+	op := c.Op
+	if l >= start && r < start {
+		l, r, op = r, l, mirrorOps[op]
+	}
+	if l >= start || r < start {
+		return JoinCond{}, fmt.Errorf("ON must compare one of its columns with a column of an earlier table")
+	}
+	if schema.Cols[l].Type != schema.Cols[r].Type {
+		return JoinCond{}, fmt.Errorf("cannot compare %s with %s: column types differ",
+			schema.Cols[l].Name, schema.Cols[r].Name)
+	}
+	return JoinCond{Left: l, Op: op, Right: r - start}, nil
+}
+
+// resolveColumn finds a column of a joined schema, where every column is
+// named "<alias>.<column>". An unqualified name must match exactly one.
+func resolveColumn(schema record.Schema, name string) (int, error) {
+	if strings.Contains(name, ".") {
+		if pos := columnIndex(schema, name); pos >= 0 {
+			return pos, nil
+		}
+		return -1, fmt.Errorf("planner: unknown column: %s", name)
+	}
+	found := -1
+	for i, c := range schema.Cols {
+		if strings.HasSuffix(c.Name, "."+name) {
+			if found >= 0 {
+				return -1, fmt.Errorf("planner: column reference %q is ambiguous", name)
+			}
+			found = i
+		}
+	}
+	if found < 0 {
+		return -1, fmt.Errorf("planner: unknown column: %s", name)
+	}
+	return found, nil
+}
+
+// resolveNames returns a copy of s with every column reference replaced by
+// resolve(name), including the aggregate arguments, the references to those
+// aggregates by name and the columns of the SELECT list expressions.
+func resolveNames(s *parser.SelectStmt, resolve func(string) (string, error)) (*parser.SelectStmt, error) {
+	q := *s
+	q.Aggregates = nil
+	renamed := make(map[string]string, len(s.Aggregates))
+	for _, a := range s.Aggregates {
+		r := a
+		if a.Column != "*" {
+			var err error
+			if r.Column, err = resolve(a.Column); err != nil {
+				return nil, err
+			}
+		}
+		renamed[a.Name()] = r.Name()
+		if !slices.Contains(q.Aggregates, r) {
+			q.Aggregates = append(q.Aggregates, r)
+		}
+	}
+	term := func(name string) (string, error) {
+		if n, ok := renamed[name]; ok {
+			return n, nil
+		}
+		return resolve(name)
+	}
+
+	// The names of the SELECT list expressions are left as they are: ORDER BY
+	// may use them, over a column of that name.
+	var err error
+	q.Columns = slices.Clone(s.Columns)
+	for i, c := range s.Columns {
+		if isComputed(s, i) {
+			continue
+		}
+		if q.Columns[i], err = term(c); err != nil {
+			return nil, err
+		}
+	}
+	q.Exprs = slices.Clone(s.Exprs)
+	for i := range q.Exprs {
+		if q.Exprs[i].Expr, err = renameColumns(q.Exprs[i].Expr, term); err != nil {
+			return nil, err
+		}
+	}
+	if q.GroupBy, err = mapNames(s.GroupBy, resolve); err != nil {
+		return nil, err
+	}
+	q.OrderBy = slices.Clone(s.OrderBy)
+	for i, o := range q.OrderBy {
+		if outputColumn(s, o.Column) != "" {
+			continue
+		}
+		if q.OrderBy[i].Column, err = term(o.Column); err != nil {
+			return nil, err
+		}
+	}
+	if s.Where != nil {
+		w := *s.Where
+		if w.Column != "" { // EXISTS has none
+			if w.Column, err = resolve(w.Column); err != nil {
+				return nil, err
+			}
+		}
+		q.Where = &w
+	}
+	if s.Having != nil {
+		h := *s.Having
+		if h.Column, err = term(h.Column); err != nil {
+			return nil, err
+		}
+		q.Having = &h
+	}
+	return &q, nil
+}
+
+func mapNames(names []string, f func(string) (string, error)) ([]string, error) {
+	if names == nil {
+		return nil, nil
+	}
+	out := make([]string, len(names))
+	for i, n := range names {
+		var err error
+		if out[i], err = f(n); err != nil {
+			return nil, err
+		}
+	}
+	return out, nil
+}
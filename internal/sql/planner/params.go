@@ -0,0 +1,202 @@
+package planner
+
+import (
+	"fmt"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/parser"
+)
+
+// NumParams is the number of values the placeholders of p need: the highest
+// placeholder number, subqueries included.
+func NumParams(p Plan) int {
+	n := 0
+	where := func(w *Where) {
+		switch {
+		case w == nil:
+		case w.Param != nil:
+			n = max(n, w.Param.Index)
+		case w.Sub != nil:
+			n = max(n, NumParams(w.Sub.Plan))
+		}
+	}
+	shape := func(s Shape) {
+		if s.Aggregate != nil {
+			where(s.Aggregate.Having)
+		}
+	}
+	switch x := p.(type) {
+	case *InsertPlan:
+		for _, e := range x.Values {
+			if pe, ok := e.(*parser.ParamExpr); ok {
+				n = max(n, pe.Index)
+			}
+		}
+	case *SeqScanPlan:
+		where(x.Where)
+		shape(x.Shape)
+	case *IndexLookupPlan:
+		where(x.Where)
+		shape(x.Shape)
+	case *IndexScanPlan:
+		where(x.Where)
+		shape(x.Shape)
+	case *JoinPlan:
+		where(x.Where)
+		shape(x.Shape)
+	case *ViewScanPlan:
+		where(x.Where)
+		shape(x.Shape)
+	case *UpdatePlan:
+		where(x.Where)
+		for _, a := range x.Assigns {
+			if a.Param != nil {
+				n = max(n, a.Param.Index)
+			}
+		}
+	case *DeletePlan:
+		where(x.Where)
+	case *ExplainPlan:
+		return NumParams(x.Plan)
+	case *DeclareCursorPlan:
+		return NumParams(x.Query)
+	}
+	return n
+}
+
+// BindParams returns p with its placeholders replaced by args, coerced to
+// the types of their columns. p is left as it is, so a prepared plan can be
+// bound again with other values.
+func BindParams(p Plan, args []any) (Plan, error) {
+	if n := NumParams(p); len(args) != n {
+		return nil, fmt.Errorf("planner: statement has %d parameters, got %d values", n, len(args))
+	}
+	return bindParams(p, args)
+}
+
+// bindParams is BindParams once the number of args is checked. A subquery
+// may use fewer of them than the statement.
+func bindParams(p Plan, args []any) (Plan, error) {
+	if len(args) == 0 {
+		return p, nil
+	}
+
+	b := &binder{args: args}
+	var out Plan
+	switch x := p.(type) {
+	case *InsertPlan:
+		q := *x
+		q.Values = make([]parser.Expr, len(x.Values))
+		for i, e := range x.Values {
+			q.Values[i] = e
+			if pe, ok := e.(*parser.ParamExpr); ok {
+				q.Values[i] = &parser.LiteralExpr{Value: args[pe.Index-1]}
+			}
+		}
+		out = &q
+	case *SeqScanPlan:
+		q := *x
+		q.Where, q.Shape = b.where(x.Where), b.shape(x.Shape)
+		out = &q
+	case *IndexLookupPlan:
+		q := *x
+		q.Where, q.Shape = b.where(x.Where), b.shape(x.Shape)
+		out = &q
+	case *IndexScanPlan:
+		q := *x
+		q.Where, q.Shape = b.where(x.Where), b.shape(x.Shape)
+		out = &q
+		// A comparison with NULL has no key range.
+		if q.Where.Value == nil && q.Where.Outer == nil {
+			out = &SeqScanPlan{TableName: q.TableName, Where: q.Where, Shape: q.Shape}
+		}
+	case *JoinPlan:
+		q := *x
+		q.Where, q.Shape = b.where(x.Where), b.shape(x.Shape)
+		out = &q
+	case *ViewScanPlan:
+		q := *x
+		q.Where, q.Shape = b.where(x.Where), b.shape(x.Shape)
+		out = &q
+	case *UpdatePlan:
+		q := *x
+		q.Where = b.where(x.Where)
+		q.Assigns = make([]Assignment, len(x.Assigns))
+		for i, a := range x.Assigns {
+			if a.Param != nil {
+				if args[a.Param.Index-1] == nil && !a.Param.Column.Nullable && b.err == nil {
+					b.err = novasql.NotNullError(x.TableName, a.Column)
+				}
+				a = Assignment{Column: a.Column, Value: b.value(a.Param)}
+			}
+			q.Assigns[i] = a
+		}
+		out = &q
+	case *DeletePlan:
+		q := *x
+		q.Where = b.where(x.Where)
+		out = &q
+	case *ExplainPlan:
+		inner, err := BindParams(x.Plan, args)
+		if err != nil {
+			return nil, err
+		}
+		out = &ExplainPlan{Analyze: x.Analyze, Plan: inner}
+	case *DeclareCursorPlan:
+		inner, err := BindParams(x.Query, args)
+		if err != nil {
+			return nil, err
+		}
+		out = &DeclareCursorPlan{Name: x.Name, Query: inner}
+	default:
+		out = p
+	}
+	if b.err != nil {
+		return nil, b.err
+	}
+	return out, nil
+}
+
+// binder substitutes placeholder values and keeps the first coercion error.
+type binder struct {
+	args []any
+	err  error
+}
+
+func (b *binder) value(prm *Param) any {
+	schema := record.Schema{Cols: []record.Column{prm.Column}}
+	v, err := coerceLiteralToColumn(schema, prm.Column.Name, b.args[prm.Index-1])
+	if err != nil && b.err == nil {
+		b.err = fmt.Errorf("%w (parameter $%d)", err, prm.Index)
+	}
+	return v
+}
+
+func (b *binder) where(w *Where) *Where {
+	switch {
+	case w == nil:
+		return w
+	case w.Param != nil:
+		return &Where{Column: w.Column, Op: w.Op, Value: b.value(w.Param)}
+	case w.Sub != nil:
+		plan, err := bindParams(w.Sub.Plan, b.args)
+		if err != nil && b.err == nil {
+			b.err = err
+		}
+		q := *w
+		q.Sub = &Subquery{Plan: plan, Outer: w.Sub.Outer}
+		return &q
+	}
+	return w
+}
+
+func (b *binder) shape(s Shape) Shape {
+	if s.Aggregate == nil || s.Aggregate.Having == nil || s.Aggregate.Having.Param == nil {
+		return s
+	}
+	agg := *s.Aggregate
+	agg.Having = b.where(agg.Having)
+	s.Aggregate = &agg
+	return s
+}
@@ -0,0 +1,66 @@
+package planner
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func TestBindParams(t *testing.T) {
+	id := record.Column{Name: "id", Type: record.ColInt64}
+	p := &UpdatePlan{
+		TableName: "t",
+		Assigns: []Assignment{
+			{Column: "name", Value: "x"},
+			{Column: "id", Param: &Param{Index: 2, Column: id}},
+		},
+		Where: &Where{Column: "id", Op: ">", Param: &Param{Index: 1, Column: id}},
+	}
+	require.Equal(t, 2, NumParams(p))
+
+	got, err := BindParams(p, []any{7, int64(8)})
+	require.NoError(t, err)
+	u, ok := got.(*UpdatePlan)
+	require.True(t, ok)
+	require.Equal(t, &Where{Column: "id", Op: ">", Value: int64(7)}, u.Where)
+	require.Equal(t, []Assignment{{Column: "name", Value: "x"}, {Column: "id", Value: int64(8)}}, u.Assigns)
+
+	// The prepared plan keeps its placeholders.
+	require.Nil(t, p.Where.Value)
+	require.NotNil(t, p.Assigns[1].Param)
+
+	_, err = BindParams(p, []any{1})
+	require.Error(t, err)
+	_, err = BindParams(p, []any{"a", 1})
+	require.Error(t, err)
+	_, err = BindParams(p, []any{nil, 1})
+	require.Error(t, err, "id is NOT NULL")
+
+	// Bound to NULL, an index scan has no key range and becomes a seq scan.
+	id.Nullable = true
+	scan := &IndexScanPlan{TableName: "t", Where: &Where{Column: "id", Op: "=", Param: &Param{Index: 1, Column: id}}}
+	got, err = BindParams(scan, []any{nil})
+	require.NoError(t, err)
+	require.IsType(t, &SeqScanPlan{}, got)
+
+	// The placeholders of a subquery are the statement's.
+	sub := &SeqScanPlan{TableName: "o", Where: &Where{Column: "id", Op: "<", Param: &Param{Index: 2, Column: id}}}
+	sel := &SeqScanPlan{TableName: "t", Where: &Where{Column: "id", Op: "IN", Sub: &Subquery{Plan: sub}}}
+	require.Equal(t, 2, NumParams(sel))
+	got, err = BindParams(sel, []any{int64(1), int64(5)})
+	require.NoError(t, err)
+	bound := got.(*SeqScanPlan).Where.Sub.Plan.(*SeqScanPlan)
+	require.Equal(t, int64(5), bound.Where.Value)
+	require.NotNil(t, sub.Where.Param)
+
+	// A correlated subquery is bound to each value of the enclosing row.
+	corr := &IndexScanPlan{TableName: "o", Where: &Where{Column: "id", Op: "=", Outer: &OuterRef{Column: "t.id"}}}
+	require.Equal(t, &Where{Column: "id", Op: "=", Value: int64(3)}, BindOuter(corr, int64(3)).(*IndexScanPlan).Where)
+	require.NotNil(t, corr.Where.Outer)
+
+	same, err := BindParams(&DropTablePlan{TableName: "t"}, nil)
+	require.NoError(t, err)
+	require.Equal(t, &DropTablePlan{TableName: "t"}, same)
+}
@@ -1,7 +1,9 @@
 package planner
 
 import (
+	"github.com/tuannm99/novasql"
 	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/expr"
 	"github.com/tuannm99/novasql/internal/sql/parser"
 )
 
@@ -24,38 +26,260 @@ type UseDatabasePlan struct{ Name string }
 
 func (*UseDatabasePlan) planNode() {}
 
+// SetPlan changes configuration parameter Name to Value.
+type SetPlan struct {
+	Name  string
+	Value string
+}
+
+func (*SetPlan) planNode() {}
+
+// ShowPlan reports configuration parameter Name, or every one when it is
+// empty.
+type ShowPlan struct{ Name string }
+
+func (*ShowPlan) planNode() {}
+
 // ----- Table plans -----
 
 type CreateTablePlan struct {
 	TableName string
 	Schema    record.Schema
+	Temp      bool // private to the handle, discarded when it closes
+
+	// Constraints, each on one column: the executor backs them with unique
+	// indexes once the table exists.
+	PrimaryKey    string   // "" for none
+	AutoIncrement string   // the PrimaryKey, if it is AUTOINCREMENT
+	Unique        []string // columns declared UNIQUE
+	ForeignKeys   []novasql.ForeignKey
+
+	// TTLColumn is the TIMESTAMP column past which rows expire ("" = none).
+	TTLColumn string
 }
 
 func (*CreateTablePlan) planNode() {}
 
 type DropTablePlan struct {
 	TableName string
+	Purge     bool
 }
 
 func (*DropTablePlan) planNode() {}
 
+// CreateViewPlan stores Query, the text of a SELECT the planner checked, as
+// view Name.
+type CreateViewPlan struct {
+	Name  string
+	Query string
+}
+
+func (*CreateViewPlan) planNode() {}
+
+type DropViewPlan struct{ Name string }
+
+func (*DropViewPlan) planNode() {}
+
+// AlterTablePlan changes one thing about a table: it adds AddColumn,
+// filling the existing rows with Default, adds ForeignKey alone (AddColumn
+// nil), drops DropColumn or renames the table to RenameTo.
+type AlterTablePlan struct {
+	TableName string
+
+	AddColumn *record.Column
+	Default   any
+
+	// Constraints of AddColumn, added once the rows have it.
+	PrimaryKey bool
+	Unique     bool
+	ForeignKey *novasql.ForeignKey
+
+	DropColumn string
+	RenameTo   string
+}
+
+func (*AlterTablePlan) planNode() {}
+
+// ----- Index plans -----
+
+// CreateIndexPlan indexes Column, or else the values of Expression, an
+// expression over the table's columns as expr.Expr.String writes it. With
+// FullText set, the index holds the terms of Column instead.
+type CreateIndexPlan struct {
+	TableName  string
+	IndexName  string
+	Column     string
+	Expression string
+	Unique     bool
+	FullText   bool
+}
+
+func (*CreateIndexPlan) planNode() {}
+
+type DropIndexPlan struct {
+	TableName string
+	IndexName string
+}
+
+func (*DropIndexPlan) planNode() {}
+
+type VacuumPlan struct{}
+
+func (*VacuumPlan) planNode() {}
+
+// AnalyzePlan collects the statistics of TableName, or of every table when
+// it is empty.
+type AnalyzePlan struct{ TableName string }
+
+func (*AnalyzePlan) planNode() {}
+
+// CopyPlan imports the CSV file File into TableName (From) or exports the
+// table to it.
+type CopyPlan struct {
+	TableName string
+	File      string
+	From      bool
+	Options   novasql.CSVOptions
+}
+
+func (*CopyPlan) planNode() {}
+
+// LastInsertRowIDPlan returns the AUTOINCREMENT value of the session's last
+// INSERT.
+type LastInsertRowIDPlan struct{}
+
+func (*LastInsertRowIDPlan) planNode() {}
+
+// ExplainPlan describes the operators that run Plan, a query or DML plan.
+// With Analyze they are also run and annotated with what they did.
+type ExplainPlan struct {
+	Analyze bool
+	Plan    Plan
+}
+
+func (*ExplainPlan) planNode() {}
+
+// ----- Cursor plans -----
+
+// DeclareCursorPlan opens cursor Name over the rows of Query, a query plan.
+type DeclareCursorPlan struct {
+	Name  string
+	Query Plan
+}
+
+func (*DeclareCursorPlan) planNode() {}
+
+// FetchPlan reads the next Count rows of Cursor, all of the rest when Count
+// is -1.
+type FetchPlan struct {
+	Cursor string
+	Count  int
+}
+
+func (*FetchPlan) planNode() {}
+
+// CloseCursorPlan closes cursor Name, or every cursor when it is empty.
+type CloseCursorPlan struct{ Name string }
+
+func (*CloseCursorPlan) planNode() {}
+
 // ----- DML plans -----
 
 type InsertPlan struct {
 	TableName string
+	Columns   []string // nil = every column, in table order
 	Values    []parser.Expr
+	Returning *Returning // nil without RETURNING
 }
 
 func (*InsertPlan) planNode() {}
 
-type WhereEq struct {
+// Returning is the RETURNING clause of a DML plan: the columns at Positions
+// of each row written, named Columns.
+type Returning struct {
+	Positions []int
+	Columns   []string
+}
+
+// Where compares Column with Value using Op ("=", "<", "<=", ">", ">="). In
+// a correlated subquery, Outer stands for Value until BindOuter. With Sub
+// set, the rows of a subquery are tested instead: Op is "IN", "NOT IN",
+// "EXISTS" or "NOT EXISTS" (no Column), or a comparison with its one value.
+// With Expr set, Expr is the condition and there is no Column; the Op and
+// Value of an IndexScanPlan are then the key range of its index, or with Op
+// "MATCH" the query its full-text index on Column is searched for.
+type Where struct {
 	Column string
-	Value  any // already coerced
+	Op     string
+	Value  any       // already coerced
+	Param  *Param    // set: Value is bound at execution (see BindParams)
+	Outer  *OuterRef // set: Value is a column of the enclosing query's row
+	Sub    *Subquery
+	Expr   *expr.Expr
+}
+
+// Subquery is a SELECT of one column, or of any for EXISTS, nested in the
+// WHERE of another query. Outer is set when it is correlated: its Where
+// compares with a column of the enclosing query's row, and it is run again
+// for each value of that column.
+type Subquery struct {
+	Plan  Plan
+	Outer *OuterRef
+}
+
+// OuterRef is the column at Pos in the rows of an enclosing query.
+type OuterRef struct {
+	Pos    int
+	Column string // as the subquery names it
+}
+
+// Param is a placeholder of a prepared statement and the column its value
+// is coerced to.
+type Param struct {
+	Index  int // 1-based
+	Column record.Column
+}
+
+type OrderBy struct {
+	Column    string
+	Desc      bool
+	Collation *expr.Collation // of COLLATE; nil: that of the column
+}
+
+// Shape is what SELECT does to the rows after filtering: aggregate, compute
+// the expressions of the SELECT list, sort, limit, then project. Column
+// names are already checked against the schema (see RowSchema).
+type Shape struct {
+	Aggregate *Aggregate // nil = no grouping
+	Exprs     []Computed
+	Columns   []string // nil = all columns
+	Names     []string // of the result columns; nil = Columns
+	OrderBy   []OrderBy
+	Limit     *int64
+}
+
+// Computed is a column of the SELECT list that Expr computes from the rows
+// Aggregate leaves. Columns and OrderBy refer to it by Name, "#<n>" for the
+// n-th entry of the list.
+type Computed struct {
+	Name string
+	Expr *expr.Expr
+}
+
+// Aggregate groups rows by GroupBy and computes Funcs for every group. With
+// no GROUP BY, all rows form one group, even when there are none. Having
+// filters the groups.
+type Aggregate struct {
+	GroupBy []string
+	Funcs   []parser.Aggregate
+	Having  *Where
+	Custom  map[string]*expr.Aggregate // the added aggregates of Funcs, by Func
 }
 
 type SeqScanPlan struct {
 	TableName string
-	Where     *WhereEq
+	Where     *Where
+	Shape     Shape
 }
 
 func (*SeqScanPlan) planNode() {}
@@ -65,27 +289,95 @@ type IndexLookupPlan struct {
 	IndexFileBase string
 	Column        string
 	Key           int64
-	Where         *WhereEq // safety re-check
+	Where         *Where // safety re-check
+	Shape         Shape
 }
 
 func (*IndexLookupPlan) planNode() {}
 
+// IndexScanPlan reads the rows matching Where through a KVTree index on
+// Where.Column, or on an expression: the comparison becomes a key range of
+// the index. With Where.Op "MATCH" the index is a full-text one, and the
+// rows come best match first.
+type IndexScanPlan struct {
+	TableName     string
+	IndexFileBase string
+	Where         *Where // also re-checked against each heap row
+	Shape         Shape
+}
+
+func (*IndexScanPlan) planNode() {}
+
+// ViewScanPlan reads the rows of view Name, which Plan, the plan of its
+// query, computes, then filters and shapes them like SeqScanPlan. Schema
+// describes them: the columns of the query, without table qualifiers.
+type ViewScanPlan struct {
+	Name   string
+	Plan   Plan
+	Schema record.Schema
+	Where  *Where
+	Shape  Shape
+}
+
+func (*ViewScanPlan) planNode() {}
+
+// JoinPlan joins From with each of Joins in turn, then filters and shapes
+// the joined rows like SeqScanPlan. Schema describes the rows of the query:
+// the columns of every table in the order the query names the tables,
+// "<alias>.<column>". When the planner joins the tables in another order,
+// Layout holds the position in the joined row of each Schema column.
+type JoinPlan struct {
+	From   TableRef
+	Joins  []Join
+	Schema record.Schema
+	Layout []int // nil = joined rows are in Schema order
+	Where  *Where
+	Shape  Shape
+}
+
+func (*JoinPlan) planNode() {}
+
+type TableRef struct {
+	Name  string
+	Alias string // the table name when the query gives none
+	View  Plan   // set: Name is a view whose rows this plan computes
+}
+
+// Join adds Table to the rows joined so far. With Left set, rows without a
+// match are kept and get NULLs for the columns of Table.
+type Join struct {
+	Table TableRef
+	Left  bool
+	On    []JoinCond
+}
+
+// JoinCond compares column Left of the rows joined so far with column Right
+// of Join.Table using Op.
+type JoinCond struct {
+	Left  int
+	Op    string
+	Right int
+}
+
 type Assignment struct {
 	Column string
-	Value  any // already coerced
+	Value  any    // already coerced
+	Param  *Param // set: Value is bound at execution (see BindParams)
 }
 
 type UpdatePlan struct {
 	TableName string
 	Assigns   []Assignment
-	Where     *WhereEq
+	Where     *Where
+	Returning *Returning // nil without RETURNING
 }
 
 func (*UpdatePlan) planNode() {}
 
 type DeletePlan struct {
 	TableName string
-	Where     *WhereEq
+	Where     *Where
+	Returning *Returning // nil without RETURNING
 }
 
 func (*DeletePlan) planNode() {}
@@ -0,0 +1,200 @@
+package planner
+
+import (
+	"fmt"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/parser"
+)
+
+// scope is the columns of a query, as a subquery nested in its WHERE may
+// refer to them: resolve finds a name in schema.
+type scope struct {
+	schema  record.Schema
+	resolve func(name string) (int, error)
+}
+
+// tableScope is the scope of a single-table query; unqualify strips the
+// qualifier its column names may have.
+func tableScope(schema record.Schema, unqualify func(string) (string, error)) *scope {
+	return &scope{schema: schema, resolve: func(name string) (int, error) {
+		col, err := unqualify(name)
+		if err != nil {
+			return -1, err
+		}
+		if pos := columnIndex(schema, col); pos >= 0 {
+			return pos, nil
+		}
+		return -1, fmt.Errorf("planner: unknown column: %s", name)
+	}}
+}
+
+// bindQueryWhere binds the WHERE of a query over the columns of self. outer
+// is the enclosing query when this one is a subquery: a column compared
+// with a column, rather than a value, is compared with that column of the
+// enclosing query's row, or else with one of the same row. Only the query a
+// subquery is nested in can be referred to, not the ones around it.
+func bindQueryWhere(db *novasql.Database, w *parser.Where, self, outer *scope) (*Where, error) {
+	if w.Subquery != nil {
+		return bindSubqueryWhere(db, w, self)
+	}
+	if w.Expr != nil {
+		return bindExprWhere(db, w, self)
+	}
+	ref, ok := w.Value.(*parser.ColumnExpr)
+	if !ok {
+		return bindWhere(self.schema, w)
+	}
+	pos := columnIndex(self.schema, w.Column)
+	if pos < 0 {
+		return nil, fmt.Errorf("planner: unknown column: %s", w.Column)
+	}
+	opos, err := -1, fmt.Errorf("planner: unknown column: %s", ref.Name)
+	if outer != nil {
+		opos, err = outer.resolve(ref.Name)
+	}
+	if err != nil {
+		if _, serr := self.resolve(ref.Name); serr != nil {
+			return nil, err
+		}
+		cond := &parser.BinaryExpr{Op: w.Op, Left: &parser.ColumnExpr{Name: w.Column}, Right: ref}
+		return bindExprWhere(db, &parser.Where{Expr: cond}, self)
+	}
+	if self.schema.Cols[pos].Type != outer.schema.Cols[opos].Type {
+		return nil, fmt.Errorf("planner: cannot compare %s with %s: column types differ", w.Column, ref.Name)
+	}
+	return &Where{Column: w.Column, Op: w.Op, Outer: &OuterRef{Pos: opos, Column: ref.Name}}, nil
+}
+
+// bindSubqueryWhere plans the subquery of w, which self encloses, and checks
+// that IN and comparisons get one column of the type of w.Column.
+func bindSubqueryWhere(db *novasql.Database, w *parser.Where, self *scope) (*Where, error) {
+	plan, err := buildSelectPlan(w.Subquery, db, self)
+	if err != nil {
+		return nil, err
+	}
+	sub := &Subquery{Plan: plan}
+	if sw := queryWhere(plan); sw != nil {
+		sub.Outer = sw.Outer
+	}
+	where := &Where{Column: w.Column, Op: w.Op, Sub: sub}
+	if w.Column == "" {
+		return where, nil
+	}
+
+	pos := columnIndex(self.schema, w.Column)
+	if pos < 0 {
+		return nil, fmt.Errorf("planner: unknown column: %s", w.Column)
+	}
+	out, err := outputSchema(db, plan)
+	if err != nil {
+		return nil, err
+	}
+	if len(out.Cols) != 1 {
+		return nil, fmt.Errorf("planner: subquery compared with %s must return one column, not %d", w.Column, len(out.Cols))
+	}
+	// A comparison may mix numeric types, such as an INT with an AVG; IN
+	// looks values up as they are.
+	colType, subType := self.schema.Cols[pos].Type, out.Cols[0].Type
+	mixed := isNumeric(colType) && isNumeric(subType) && w.Op != "IN" && w.Op != "NOT IN"
+	if colType != subType && !mixed {
+		return nil, fmt.Errorf("planner: cannot compare %s with %s: column types differ", w.Column, out.Cols[0].Name)
+	}
+	return where, nil
+}
+
+func isNumeric(t record.ColumnType) bool {
+	return t == record.ColInt64 || t == record.ColFloat64 || t == record.ColDecimal
+}
+
+// queryWhere is the Where of a query plan.
+func queryWhere(p Plan) *Where {
+	switch x := p.(type) {
+	case *SeqScanPlan:
+		return x.Where
+	case *IndexLookupPlan:
+		return x.Where
+	case *IndexScanPlan:
+		return x.Where
+	case *JoinPlan:
+		return x.Where
+	case *ViewScanPlan:
+		return x.Where
+	}
+	return nil
+}
+
+// outputSchema is the schema of the rows of a query plan.
+func outputSchema(db *novasql.Database, p Plan) (record.Schema, error) {
+	var table string
+	var schema record.Schema
+	var shape Shape
+	switch x := p.(type) {
+	case *SeqScanPlan:
+		table, shape = x.TableName, x.Shape
+	case *IndexLookupPlan:
+		table, shape = x.TableName, x.Shape
+	case *IndexScanPlan:
+		table, shape = x.TableName, x.Shape
+	case *JoinPlan:
+		schema, shape = x.Schema, x.Shape
+	case *ViewScanPlan:
+		schema, shape = x.Schema, x.Shape
+	default:
+		return record.Schema{}, fmt.Errorf("planner: %T is not a query", p)
+	}
+	if table != "" {
+		var err error
+		if schema, err = db.TableSchema(table); err != nil {
+			return record.Schema{}, err
+		}
+	}
+	schema, err := shape.RowSchema(schema)
+	if err != nil {
+		return record.Schema{}, err
+	}
+	if shape.Columns == nil {
+		return schema, nil
+	}
+	var out record.Schema
+	for i, c := range shape.Columns {
+		col := schema.Cols[columnIndex(schema, c)]
+		if shape.Names != nil {
+			col.Name = shape.Names[i]
+		}
+		out.Cols = append(out.Cols, col)
+	}
+	return out, nil
+}
+
+// BindOuter returns the plan of a correlated subquery for the enclosing row
+// whose outer column is v; v is not NULL, which no row would match. p is
+// left as it is, to be bound again for the next row.
+func BindOuter(p Plan, v any) Plan {
+	bind := func(w *Where) *Where {
+		if w == nil || w.Outer == nil {
+			return w
+		}
+		return &Where{Column: w.Column, Op: w.Op, Value: v}
+	}
+	switch x := p.(type) {
+	case *SeqScanPlan:
+		q := *x
+		q.Where = bind(x.Where)
+		return &q
+	case *IndexScanPlan:
+		q := *x
+		q.Where = bind(x.Where)
+		return &q
+	case *JoinPlan:
+		q := *x
+		q.Where = bind(x.Where)
+		return &q
+	case *ViewScanPlan:
+		q := *x
+		q.Where = bind(x.Where)
+		return &q
+	}
+	return p
+}
@@ -0,0 +1,73 @@
+package planner
+
+import (
+	"errors"
+	"fmt"
+	"slices"
+	"strings"
+	"unicode"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/parser"
+)
+
+// expandView plans the query of view name in place of a table, and returns
+// nil when name is no view. The query is planned anew each time, so it sees
+// the tables as they are now.
+func expandView(db *novasql.Database, name string) (Plan, record.Schema, error) {
+	v, err := db.View(name)
+	if errors.Is(err, novasql.ErrViewNotFound) {
+		return nil, record.Schema{}, nil
+	}
+	if err != nil {
+		return nil, record.Schema{}, err
+	}
+	stmt, err := parser.Parse(v.Query + ";")
+	if err != nil {
+		return nil, record.Schema{}, fmt.Errorf("planner: view %s: %w", name, err)
+	}
+	q, ok := stmt.(*parser.SelectStmt)
+	if !ok {
+		return nil, record.Schema{}, fmt.Errorf("planner: view %s is not a query", name)
+	}
+	plan, schema, err := planView(db, q)
+	if err != nil {
+		return nil, record.Schema{}, fmt.Errorf("planner: view %s: %w", name, err)
+	}
+	return plan, schema, nil
+}
+
+// planView plans q, the query of a view, and returns the schema of its rows:
+// the columns of q without their table qualifiers, which must leave every
+// name distinct.
+func planView(db *novasql.Database, q *parser.SelectStmt) (Plan, record.Schema, error) {
+	plan, err := buildSelectPlan(q, db, nil)
+	if err != nil {
+		return nil, record.Schema{}, err
+	}
+	schema, err := outputSchema(db, plan)
+	if err != nil {
+		return nil, record.Schema{}, err
+	}
+	schema.Cols = slices.Clone(schema.Cols)
+	seen := make(map[string]bool, len(schema.Cols))
+	for i, c := range schema.Cols {
+		if q, col, ok := strings.Cut(c.Name, "."); ok && isWord(q) && isWord(col) {
+			schema.Cols[i].Name = col
+		}
+		if seen[schema.Cols[i].Name] {
+			return nil, record.Schema{}, fmt.Errorf("planner: view has more than one column named %s", schema.Cols[i].Name)
+		}
+		seen[schema.Cols[i].Name] = true
+	}
+	return plan, schema, nil
+}
+
+// isWord reports whether s is a plain name, not an aggregate or an
+// expression such as "1.5 * a".
+func isWord(s string) bool {
+	return s != "" && !strings.ContainsFunc(s, func(r rune) bool {
+		return r != '_' && !unicode.IsLetter(r) && !unicode.IsDigit(r)
+	})
+}
@@ -0,0 +1,149 @@
+package storage
+
+import (
+	"fmt"
+	"io"
+)
+
+// ReadPages reads the len(dst)/PageSize pages from first into dst, each
+// like ReadPage. In Classic mode the pages of one segment come with a
+// single read; mapped and in-memory pages are copied one by one.
+func (sm *StorageManager) ReadPages(fs FileSet, first int32, dst []byte) error {
+	if first < 0 {
+		return fmt.Errorf("pageID must be >= 0, got %d", first)
+	}
+	if len(dst)%PageSize != 0 {
+		return fmt.Errorf("dst must be a multiple of %d bytes", PageSize)
+	}
+	if err := sm.readRun(fs, first, dst); err != nil {
+		return err
+	}
+	for i := 0; i < len(dst)/PageSize; i++ {
+		if err := sm.decodePage(fs, first+int32(i), dst[i*PageSize:(i+1)*PageSize]); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// readRun reads the stored slots of the pages from first into dst.
+func (sm *StorageManager) readRun(fs FileSet, first int32, dst []byte) error {
+	_, isLocal := fs.(LocalFileSet)
+	if isLocal && (sm.mem != nil || sm.maps != nil || sm.engine != nil) {
+		for i := 0; i < len(dst)/PageSize; i++ {
+			if err := sm.readPage(fs, first+int32(i), dst[i*PageSize:(i+1)*PageSize]); err != nil {
+				return err
+			}
+		}
+		return nil
+	}
+	return sm.eachSegmentRun(first, len(dst)/PageSize, func(segNo, off int32, from, to int) error {
+		buf := dst[from*PageSize : to*PageSize]
+		f, err := sm.openSegment(fs, segNo)
+		if sm.missingSegment(err) {
+			clear(buf)
+			return nil
+		}
+		if err != nil {
+			return err
+		}
+		defer func() { _ = f.Close() }()
+
+		n, err := sm.readAt(f, buf, int64(off))
+		if err != nil && err != io.EOF {
+			return err
+		}
+		clear(buf[n:])
+		return nil
+	})
+}
+
+// eachSegmentRun splits the n pages from first at segment boundaries and
+// calls fn with the segment, the byte offset in it and the range of pages.
+func (sm *StorageManager) eachSegmentRun(first int32, n int, fn func(segNo, off int32, from, to int) error) error {
+	pps := sm.pagesPerSegment()
+	for from := 0; from < n; {
+		segNo, off := sm.locate(first + int32(from))
+		to := min(n, from+pps-int(off)/PageSize)
+		if err := fn(segNo, off, from, to); err != nil {
+			return err
+		}
+		from = to
+	}
+	return nil
+}
+
+// LoadPages is LoadPage for the n pages from first.
+func (sm *StorageManager) LoadPages(fs FileSet, first uint32, n int) ([]*Page, error) {
+	buf := make([]byte, n*PageSize)
+	if err := sm.ReadPages(fs, int32(first), buf); err != nil {
+		return nil, err
+	}
+	pages := make([]*Page, n)
+	for i := range pages {
+		p, err := sm.framePage(fs, first+uint32(i), buf[i*PageSize:(i+1)*PageSize:(i+1)*PageSize])
+		if err != nil {
+			return nil, err
+		}
+		pages[i] = p
+	}
+	return pages, nil
+}
+
+// SavePages is SavePage for pages, the pages from first. In Classic mode
+// without compression the pages of one segment are written with a single
+// write; otherwise they are saved one by one.
+func (sm *StorageManager) SavePages(fs FileSet, first uint32, pages []Page) error {
+	if sm.readOnly {
+		return ErrReadOnly
+	}
+	_, isLocal := fs.(LocalFileSet)
+	if sm.compresses() || (isLocal && (sm.mem != nil || sm.maps != nil || sm.engine != nil)) {
+		for i, p := range pages {
+			if err := sm.SavePage(fs, first+uint32(i), p); err != nil {
+				return err
+			}
+		}
+		return nil
+	}
+
+	buf := make([]byte, len(pages)*PageSize)
+	for i, p := range pages {
+		if len(p.Buf) != PageSize {
+			return fmt.Errorf("page buffer must be %d bytes", PageSize)
+		}
+		p.SealChecksum()
+		src := p.Buf
+		if sm.crypt != nil {
+			sealed, err := sm.crypt.Seal(first+uint32(i), src)
+			if err != nil {
+				return fmt.Errorf("page %d: %w", first+uint32(i), err)
+			}
+			src = sealed
+		}
+		copy(buf[i*PageSize:], src)
+	}
+	err := sm.eachSegmentRun(int32(first), len(pages), func(segNo, off int32, from, to int) error {
+		f, err := sm.openSegment(fs, segNo)
+		if err != nil {
+			return err
+		}
+		defer func() { _ = f.Close() }()
+
+		n, err := sm.writeAt(f, buf[from*PageSize:to*PageSize], int64(off))
+		if err != nil {
+			return err
+		}
+		if n != (to-from)*PageSize {
+			return io.ErrShortWrite
+		}
+		return nil
+	})
+	if err != nil {
+		return err
+	}
+	for i := range pages {
+		sm.noteWrite(fs, int32(first)+int32(i))
+	}
+	return nil
+}
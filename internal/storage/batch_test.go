@@ -0,0 +1,59 @@
+package storage
+
+import (
+	"fmt"
+	"os"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+// countingFS counts the segment opens, one per read or write call.
+type countingFS struct {
+	LocalFileSet
+	opens int
+}
+
+func (c *countingFS) OpenSegment(segNo int32) (*os.File, error) {
+	c.opens++
+	return c.LocalFileSet.OpenSegment(segNo)
+}
+
+func TestSaveLoadPages_OneCallPerSegment(t *testing.T) {
+	sm := NewStorageManager()
+	fs := &countingFS{LocalFileSet: LocalFileSet{Dir: t.TempDir(), Base: "rel"}}
+
+	// Five pages across the boundary of the first two segments.
+	first := uint32(MaxPagePerSegment - 2)
+	pages := make([]Page, 5)
+	for i := range pages {
+		p, err := NewPage(make([]byte, PageSize), first+uint32(i))
+		require.NoError(t, err)
+		_, err = p.InsertTuple([]byte(fmt.Sprintf("tuple-%d", i)))
+		require.NoError(t, err)
+		pages[i] = *p
+	}
+	require.NoError(t, sm.SavePages(fs, first, pages))
+	require.Equal(t, 2, fs.opens)
+
+	fs.opens = 0
+	loaded, err := sm.LoadPages(fs, first-1, 7)
+	require.NoError(t, err)
+	require.Equal(t, 2, fs.opens)
+	require.Equal(t, 0, loaded[0].NumSlots()) // never written
+	for i := range pages {
+		got, err := loaded[i+1].ReadTuple(0)
+		require.NoError(t, err)
+		require.Equal(t, fmt.Sprintf("tuple-%d", i), string(got))
+	}
+
+	// Damage is reported for the page it is in.
+	raw := make([]byte, PageSize)
+	require.NoError(t, sm.ReadRawPage(fs, int32(first+1), raw))
+	raw[100] ^= 0xff
+	require.NoError(t, sm.WritePage(fs, int32(first+1), raw))
+	_, err = sm.LoadPages(fs, first, 3)
+	var ce *CorruptedPageError
+	require.ErrorAs(t, err, &ce)
+	require.Equal(t, first+1, ce.PageID)
+}
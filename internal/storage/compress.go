@@ -0,0 +1,258 @@
+package storage
+
+import (
+	"errors"
+	"fmt"
+	"hash/crc32"
+
+	"github.com/tuannm99/novasql/pkg/bx"
+)
+
+// Compressed pages
+//
+// With page compression on, SavePage stores a page whose LZ4 encoding frees
+// at least one filesystem block (holeBlock) as a frame at the start of its
+// slot and punches the rest of the slot out of the file, so the page takes
+// fewer blocks on disk; pages that do not compress that well are stored as
+// they are. ReadPage recognizes frames in any mode, whatever the current
+// codec, so the setting can change at any time.
+//
+// Frame layout (the rest of the slot reads as zeros):
+//
+//	[0..3]   frameMagic
+//	[4]      codec
+//	[5]      reserved
+//	[6..9]   zero
+//	[10..11] payload length
+//	[12..15] crc32 (IEEE) of the payload
+//	[16..]   payload
+//
+// Bytes 6..9 are where a slotted page keeps lower and upper; only an
+// all-zero page has both at 0, so a frame is never mistaken for a page.
+
+var ErrCompressionUnsupported = errors.New("storage: page compression codec not supported")
+
+type Compression uint8
+
+const (
+	CompressionNone Compression = iota
+	CompressionLZ4
+)
+
+func (c Compression) String() string {
+	switch c {
+	case CompressionNone:
+		return "none"
+	case CompressionLZ4:
+		return "lz4"
+	default:
+		return "unknown"
+	}
+}
+
+// ParseCompression parses storage.compression. zstd is recognized but this
+// build has no zstd codec.
+func ParseCompression(s string) (Compression, error) {
+	switch s {
+	case "none":
+		return CompressionNone, nil
+	case "lz4":
+		return CompressionLZ4, nil
+	case "zstd":
+		return 0, fmt.Errorf("%w: zstd is not built in", ErrCompressionUnsupported)
+	default:
+		return 0, fmt.Errorf("invalid page compression: %s", s)
+	}
+}
+
+const (
+	frameMagic      uint32 = 0x5A50564E // "NVPZ"
+	frameHeaderSize        = 16
+
+	// holeBlock is the filesystem block size hole punching assumes.
+	holeBlock = 4096
+)
+
+// compressPage returns the frame of page and how many bytes of its slot
+// the frame needs (a multiple of holeBlock), or nil when compressing would
+// not free a block.
+func compressPage(c Compression, page []byte) ([]byte, int) {
+	if c != CompressionLZ4 {
+		return nil, 0
+	}
+	payload := lz4Compress(make([]byte, 0, PageSize), page)
+	used := (frameHeaderSize + len(payload) + holeBlock - 1) / holeBlock * holeBlock
+	if used >= PageSize {
+		return nil, 0
+	}
+	frame := make([]byte, PageSize)
+	bx.PutU32At(frame, 0, frameMagic)
+	frame[4] = byte(c)
+	bx.PutU16At(frame, 10, uint16(len(payload)))
+	bx.PutU32At(frame, 12, crc32.ChecksumIEEE(payload))
+	copy(frame[frameHeaderSize:], payload)
+	return frame, used
+}
+
+func isFrame(buf []byte) bool {
+	return bx.U32At(buf, 0) == frameMagic && bx.U32At(buf, 6) == 0
+}
+
+// decodeFrame replaces the frame in buf by the page it holds. It returns a
+// reason when the frame is damaged.
+func decodeFrame(buf []byte) string {
+	n := int(bx.U16At(buf, 10))
+	if frameHeaderSize+n > PageSize {
+		return "compressed frame length out of range"
+	}
+	payload := buf[frameHeaderSize : frameHeaderSize+n]
+	if crc32.ChecksumIEEE(payload) != bx.U32At(buf, 12) {
+		return "compressed frame checksum mismatch"
+	}
+	if Compression(buf[4]) != CompressionLZ4 {
+		return fmt.Sprintf("unknown compression codec %d", buf[4])
+	}
+	page := make([]byte, PageSize)
+	if err := lz4Decompress(page, payload); err != nil {
+		return err.Error()
+	}
+	copy(buf, page)
+	return ""
+}
+
+// LZ4 block format, as specified by lz4_Block_format.md: a sequence is a
+// token (literal length << 4 | match length - 4), the literal length
+// overflow, the literals, a little-endian 2-byte offset and the match
+// length overflow. The last sequence has literals only; the last match
+// starts at least lz4MFLimit bytes before the end and ends at least
+// lz4LastLiterals bytes before it.
+
+const (
+	lz4MinMatch     = 4
+	lz4LastLiterals = 5
+	lz4MFLimit      = 12
+	lz4HashLog      = 12
+	lz4MaxOffset    = 65535
+)
+
+var errLZ4Corrupt = errors.New("lz4: corrupt block")
+
+func lz4Hash(v uint32) uint32 { return (v * 2654435761) >> (32 - lz4HashLog) }
+
+// lz4Compress appends the LZ4 block of src to dst, matching greedily
+// through a hash table of 4-byte prefixes.
+func lz4Compress(dst, src []byte) []byte {
+	var table [1 << lz4HashLog]int32 // position + 1, 0 = empty
+	anchor := 0
+	for i := 0; i <= len(src)-lz4MFLimit; {
+		v := bx.U32(src[i:])
+		h := lz4Hash(v)
+		ref := int(table[h]) - 1
+		table[h] = int32(i + 1)
+		if ref < 0 || i-ref > lz4MaxOffset || bx.U32(src[ref:]) != v {
+			i++
+			continue
+		}
+		n := lz4MinMatch
+		for i+n < len(src)-lz4LastLiterals && src[ref+n] == src[i+n] {
+			n++
+		}
+		dst = lz4Sequence(dst, src[anchor:i], i-ref, n)
+		i += n
+		anchor = i
+	}
+	return lz4Sequence(dst, src[anchor:], 0, 0)
+}
+
+// lz4Sequence appends a sequence; offset 0 is the final, literal-only one.
+func lz4Sequence(dst, lits []byte, offset, matchLen int) []byte {
+	token := byte(min(len(lits), 15)) << 4
+	if offset > 0 {
+		token |= byte(min(matchLen-lz4MinMatch, 15))
+	}
+	dst = append(dst, token)
+	if len(lits) >= 15 {
+		dst = lz4Length(dst, len(lits)-15)
+	}
+	dst = append(dst, lits...)
+	if offset == 0 {
+		return dst
+	}
+	dst = append(dst, byte(offset), byte(offset>>8))
+	if matchLen-lz4MinMatch >= 15 {
+		dst = lz4Length(dst, matchLen-lz4MinMatch-15)
+	}
+	return dst
+}
+
+func lz4Length(dst []byte, n int) []byte {
+	for ; n >= 255; n -= 255 {
+		dst = append(dst, 255)
+	}
+	return append(dst, byte(n))
+}
+
+// lz4Decompress decodes the block src, which must fill dst exactly.
+func lz4Decompress(dst, src []byte) error {
+	di, si := 0, 0
+	for si < len(src) {
+		token := src[si]
+		si++
+
+		lits := int(token >> 4)
+		if lits == 15 {
+			n, next, ok := lz4ReadLength(src, si)
+			if !ok {
+				return errLZ4Corrupt
+			}
+			lits, si = lits+n, next
+		}
+		if si+lits > len(src) || di+lits > len(dst) {
+			return errLZ4Corrupt
+		}
+		di += copy(dst[di:], src[si:si+lits])
+		si += lits
+		if si == len(src) {
+			break
+		}
+
+		if si+2 > len(src) {
+			return errLZ4Corrupt
+		}
+		offset := int(src[si]) | int(src[si+1])<<8
+		si += 2
+		matchLen := int(token & 15)
+		if matchLen == 15 {
+			n, next, ok := lz4ReadLength(src, si)
+			if !ok {
+				return errLZ4Corrupt
+			}
+			matchLen, si = matchLen+n, next
+		}
+		matchLen += lz4MinMatch
+		if offset == 0 || offset > di || di+matchLen > len(dst) {
+			return errLZ4Corrupt
+		}
+		// Byte by byte: the match may overlap the bytes it produces.
+		for k := range matchLen {
+			dst[di+k] = dst[di-offset+k]
+		}
+		di += matchLen
+	}
+	if di != len(dst) {
+		return errLZ4Corrupt
+	}
+	return nil
+}
+
+func lz4ReadLength(src []byte, si int) (n, next int, ok bool) {
+	for si < len(src) {
+		b := src[si]
+		si++
+		n += int(b)
+		if b != 255 {
+			return n, si, true
+		}
+	}
+	return 0, 0, false
+}
@@ -0,0 +1,108 @@
+package storage
+
+import (
+	"bytes"
+	"os"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestLZ4_RoundTrip(t *testing.T) {
+	noise := make([]byte, PageSize)
+	x := uint32(1)
+	for i := range noise {
+		x = x*1664525 + 1013904223
+		noise[i] = byte(x >> 24)
+	}
+	inputs := [][]byte{
+		{},
+		[]byte("short"),
+		make([]byte, PageSize),
+		bytes.Repeat([]byte("novasql stores rows in slotted pages. "), 200),
+		noise,
+		append(bytes.Repeat([]byte{'a'}, 300), noise[:300]...),
+	}
+	for _, in := range inputs {
+		block := lz4Compress(nil, in)
+		out := make([]byte, len(in))
+		require.NoError(t, lz4Decompress(out, block))
+		require.Equal(t, in, out)
+	}
+
+	text := lz4Compress(nil, inputs[3])
+	require.Less(t, len(text), len(inputs[3])/4)
+
+	// Truncated, too long or too short blocks are rejected.
+	out := make([]byte, len(inputs[3]))
+	require.Error(t, lz4Decompress(out, text[:len(text)-3]))
+	require.Error(t, lz4Decompress(out[:len(out)-1], text))
+	require.Error(t, lz4Decompress(make([]byte, len(out)+1), text))
+	require.Error(t, lz4Decompress(out, []byte{0x00, 0x01, 0x00}), "offset before the start")
+}
+
+func TestStorageManager_Compression(t *testing.T) {
+	if !holePunchSupported {
+		t.Skip("pages are only compressed where holes can be punched")
+	}
+	sm := NewStorageManager()
+	sm.SetCompression(CompressionLZ4)
+	fs := LocalFileSet{Dir: t.TempDir(), Base: "seg"}
+
+	p, err := NewPage(make([]byte, PageSize), 0)
+	require.NoError(t, err)
+	for {
+		if _, err := p.InsertTuple([]byte("a fairly repetitive row of text")); err != nil {
+			break
+		}
+	}
+	require.NoError(t, sm.SavePage(fs, 0, *p))
+	want := bytes.Clone(p.Buf) // as sealed by SavePage
+
+	raw, err := os.ReadFile(fs.SegmentPath(0))
+	require.NoError(t, err)
+	require.Len(t, raw, PageSize)
+	require.True(t, isFrame(raw))
+
+	// Compressed pages read back as they were, codec or not.
+	got, err := sm.LoadPage(fs, 0)
+	require.NoError(t, err)
+	require.Equal(t, want, got.Buf)
+	got, err = NewStorageManager().LoadPage(fs, 0)
+	require.NoError(t, err)
+	require.Equal(t, want, got.Buf)
+
+	// A page that does not compress is stored as it is.
+	noise, err := NewPage(make([]byte, PageSize), 1)
+	require.NoError(t, err)
+	x := uint32(7)
+	tup := make([]byte, 6000)
+	for i := range tup {
+		x = x*1664525 + 1013904223
+		tup[i] = byte(x >> 24)
+	}
+	_, err = noise.InsertTuple(tup)
+	require.NoError(t, err)
+	require.NoError(t, sm.SavePage(fs, 1, *noise))
+	raw, err = os.ReadFile(fs.SegmentPath(0))
+	require.NoError(t, err)
+	require.False(t, isFrame(raw[PageSize:]))
+
+	// A damaged frame is a corrupted page.
+	raw[frameHeaderSize+3] ^= 0xFF
+	require.NoError(t, os.WriteFile(fs.SegmentPath(0), raw, 0o644))
+	_, err = sm.LoadPage(fs, 0)
+	require.ErrorIs(t, err, ErrPageCorrupted)
+}
+
+func TestParseCompression(t *testing.T) {
+	c, err := ParseCompression("lz4")
+	require.NoError(t, err)
+	require.Equal(t, CompressionLZ4, c)
+	require.Equal(t, "lz4", c.String())
+
+	_, err = ParseCompression("zstd")
+	require.ErrorIs(t, err, ErrCompressionUnsupported)
+	_, err = ParseCompression("snappy")
+	require.Error(t, err)
+}
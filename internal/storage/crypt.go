@@ -0,0 +1,181 @@
+package storage
+
+import (
+	"crypto/aes"
+	"crypto/cipher"
+	"crypto/rand"
+	"encoding/hex"
+	"errors"
+	"fmt"
+	"os"
+	"strings"
+
+	"github.com/tuannm99/novasql/internal/wal"
+	"github.com/tuannm99/novasql/pkg/bx"
+)
+
+// Encrypted pages
+//
+// With an encryption key every page is sealed with AES-256-GCM on its way
+// to the segment file (and to the WAL) and opened on its way back, with the
+// page number as additional data so a page cannot be moved to another slot
+// unnoticed. A fresh random nonce is drawn for every write.
+//
+// GCM adds a nonce and a tag (EncryptionReserve bytes) that have to fit in
+// the 8 KiB slot, so pages of an encrypted database keep the
+// EncryptionReserve bytes before the checksum trailer free: slotted pages
+// are formatted with their special area that much lower, overflow pages
+// carry that much less payload. The slot holds
+//
+//	[0..8179]    ciphertext + tag of the page without its reserved bytes
+//	[8180..8191] nonce
+//
+// An all-zero slot is a page never written and reads as zeros.
+
+var (
+	ErrEncryptionKey      = errors.New("storage: invalid encryption key")
+	ErrPageNotEncryptable = errors.New("storage: page has no room for encryption")
+	ErrDecrypt            = errors.New("storage: page decryption failed")
+)
+
+const (
+	EncryptionReserve = gcmNonceSize + gcmTagSize
+
+	gcmNonceSize = 12
+	gcmTagSize   = 16
+
+	// KeyCheckSize is the length of a KeyCheck value.
+	KeyCheckSize = gcmNonceSize + 16 + gcmTagSize
+
+	// The reserved bytes of a page: right before the checksum trailer.
+	cryptGapStart = offPageChecksum - EncryptionReserve
+	cryptGapEnd   = offPageChecksum
+)
+
+// ParseEncryptionKey decodes a key given as 64 hex digits.
+func ParseEncryptionKey(s string) ([]byte, error) {
+	key, err := hex.DecodeString(strings.TrimSpace(s))
+	if err != nil || len(key) != 32 {
+		return nil, fmt.Errorf("%w: want 64 hex digits (32 bytes)", ErrEncryptionKey)
+	}
+	return key, nil
+}
+
+// ReadEncryptionKeyFile reads a key file holding either the 32 key bytes or
+// their 64 hex digits.
+func ReadEncryptionKeyFile(path string) ([]byte, error) {
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return nil, err
+	}
+	if len(data) == 32 {
+		return data, nil
+	}
+	return ParseEncryptionKey(string(data))
+}
+
+func newGCM(key []byte) (cipher.AEAD, error) {
+	if len(key) != 32 {
+		return nil, fmt.Errorf("%w: AES-256 needs 32 bytes, got %d", ErrEncryptionKey, len(key))
+	}
+	block, err := aes.NewCipher(key)
+	if err != nil {
+		return nil, err
+	}
+	return cipher.NewGCM(block)
+}
+
+// keyCheckAD is the additional data of a key check, so that one cannot be
+// confused with a page.
+var keyCheckAD = []byte("novasql key check")
+
+// KeyCheck returns a value that VerifyKeyCheck accepts only for key, to be
+// stored next to encrypted data.
+func KeyCheck(key []byte) ([]byte, error) {
+	aead, err := newGCM(key)
+	if err != nil {
+		return nil, err
+	}
+	nonce := make([]byte, gcmNonceSize)
+	if _, err := rand.Read(nonce); err != nil {
+		return nil, err
+	}
+	return aead.Seal(nonce, nonce, make([]byte, 16), keyCheckAD), nil
+}
+
+// VerifyKeyCheck reports whether check was made by KeyCheck with key.
+func VerifyKeyCheck(key, check []byte) error {
+	aead, err := newGCM(key)
+	if err != nil {
+		return err
+	}
+	if len(check) < gcmNonceSize {
+		return ErrEncryptionKey
+	}
+	if _, err := aead.Open(nil, check[:gcmNonceSize], check[gcmNonceSize:], keyCheckAD); err != nil {
+		return fmt.Errorf("%w: key does not match the database", ErrEncryptionKey)
+	}
+	return nil
+}
+
+// pageCipher seals and opens page slots. It also encrypts the page images
+// of the WAL (wal.PageCipher).
+type pageCipher struct {
+	aead cipher.AEAD
+}
+
+var _ wal.PageCipher = (*pageCipher)(nil)
+
+func (c *pageCipher) Seal(pageID uint32, page []byte) ([]byte, error) {
+	if len(page) != PageSize {
+		return nil, ErrWrongSize
+	}
+	for _, b := range page[cryptGapStart:cryptGapEnd] {
+		if b != 0 {
+			return nil, ErrPageNotEncryptable
+		}
+	}
+	msg := make([]byte, 0, PageSize-EncryptionReserve)
+	msg = append(append(msg, page[:cryptGapStart]...), page[cryptGapEnd:]...)
+
+	slot := make([]byte, 0, PageSize)
+	nonce := make([]byte, gcmNonceSize)
+	if _, err := rand.Read(nonce); err != nil {
+		return nil, err
+	}
+	slot = c.aead.Seal(slot, nonce, msg, pageAD(pageID))
+	return append(slot, nonce...), nil
+}
+
+func (c *pageCipher) Open(pageID uint32, slot []byte) ([]byte, error) {
+	if len(slot) != PageSize {
+		return nil, ErrWrongSize
+	}
+	page := make([]byte, PageSize)
+	if isZero(slot) {
+		return page, nil
+	}
+	nonce := slot[PageSize-gcmNonceSize:]
+	msg, err := c.aead.Open(nil, nonce, slot[:PageSize-gcmNonceSize], pageAD(pageID))
+	if err != nil {
+		return nil, ErrDecrypt
+	}
+	copy(page, msg[:cryptGapStart])
+	copy(page[cryptGapEnd:], msg[cryptGapStart:])
+	return page, nil
+}
+
+func pageAD(pageID uint32) []byte {
+	var ad [4]byte
+	bx.PutU32(ad[:], pageID)
+	return ad[:]
+}
+
+func isZero(b []byte) bool {
+	for _, x := range b {
+		if x != 0 {
+			return false
+		}
+	}
+	return true
+}
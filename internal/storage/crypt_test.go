@@ -0,0 +1,106 @@
+package storage
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func testKey(b byte) []byte { return bytes.Repeat([]byte{b}, 32) }
+
+func TestStorageManager_Encryption(t *testing.T) {
+	sm := NewStorageManager()
+	require.NoError(t, sm.SetEncryptionKey(testKey(1)))
+	fs := LocalFileSet{Dir: t.TempDir(), Base: "seg"}
+
+	p, err := sm.LoadPage(fs, 0)
+	require.NoError(t, err)
+	secret := []byte("a secret row nobody should read on disk")
+	for {
+		if _, err := p.InsertTuple(secret); err != nil {
+			break
+		}
+	}
+	require.NoError(t, sm.SavePage(fs, 0, *p))
+	want := bytes.Clone(p.Buf)
+
+	raw, err := os.ReadFile(fs.SegmentPath(0))
+	require.NoError(t, err)
+	require.Len(t, raw, PageSize)
+	require.NotContains(t, string(raw), string(secret[:8]))
+
+	got, err := sm.LoadPage(fs, 0)
+	require.NoError(t, err)
+	require.Equal(t, want, got.Buf)
+
+	// Unwritten pages read as new pages; the reserve survives Reset.
+	fresh, err := sm.LoadPage(fs, 3)
+	require.NoError(t, err)
+	fresh.Reset(3)
+	require.Equal(t, PageSize-HeaderSize-20-EncryptionReserve, fresh.FreeSpace())
+
+	// Another key, or the same page in another slot, does not decrypt.
+	other := NewStorageManager()
+	require.NoError(t, other.SetEncryptionKey(testKey(2)))
+	_, err = other.LoadPage(fs, 0)
+	require.ErrorIs(t, err, ErrPageCorrupted)
+
+	moved := bytes.Clone(raw)
+	require.NoError(t, os.WriteFile(fs.SegmentPath(0), append(make([]byte, PageSize), moved...), 0o644))
+	_, err = sm.LoadPage(fs, 1)
+	require.ErrorIs(t, err, ErrPageCorrupted)
+
+	// Pages formatted without the reserve cannot be encrypted.
+	plain, err := NewPage(make([]byte, PageSize), 2)
+	require.NoError(t, err)
+	for {
+		if _, err := plain.InsertTuple(secret); err != nil {
+			break
+		}
+	}
+	require.ErrorIs(t, sm.SavePage(fs, 2, *plain), ErrPageNotEncryptable)
+
+	require.ErrorIs(t, sm.SetEncryptionKey(make([]byte, 16)), ErrEncryptionKey)
+}
+
+func TestOverflow_Encryption(t *testing.T) {
+	sm := NewStorageManager()
+	require.NoError(t, sm.SetEncryptionKey(testKey(3)))
+	fs := LocalFileSet{Dir: t.TempDir(), Base: "ovf"}
+	ovf := NewOverflowManager(fs)
+	ovf.SetStorage(sm)
+
+	data := bytes.Repeat([]byte("overflowing secret "), 2000)
+	ref, err := ovf.Write(data)
+	require.NoError(t, err)
+	got, err := ovf.Read(ref)
+	require.NoError(t, err)
+	require.Equal(t, data, got)
+
+	raw, err := os.ReadFile(fs.SegmentPath(0))
+	require.NoError(t, err)
+	require.NotContains(t, string(raw), "overflowing")
+}
+
+func TestEncryptionKey(t *testing.T) {
+	key, err := ParseEncryptionKey("0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20\n")
+	require.NoError(t, err)
+	require.Len(t, key, 32)
+	_, err = ParseEncryptionKey("0102")
+	require.ErrorIs(t, err, ErrEncryptionKey)
+
+	path := filepath.Join(t.TempDir(), "key")
+	require.NoError(t, os.WriteFile(path, key, 0o600))
+	fromFile, err := ReadEncryptionKeyFile(path)
+	require.NoError(t, err)
+	require.Equal(t, key, fromFile)
+
+	check, err := KeyCheck(key)
+	require.NoError(t, err)
+	require.Len(t, check, KeyCheckSize)
+	require.NoError(t, VerifyKeyCheck(key, check))
+	require.ErrorIs(t, VerifyKeyCheck(testKey(9), check), ErrEncryptionKey)
+}
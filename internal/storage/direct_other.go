@@ -0,0 +1,9 @@
+//go:build !(linux || darwin)
+
+package storage
+
+import "os"
+
+const directIOSupported = false
+
+func openDirect(string) (*os.File, error) { return nil, ErrDirectIO }
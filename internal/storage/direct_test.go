@@ -0,0 +1,48 @@
+package storage
+
+import (
+	"errors"
+	"testing"
+	"unsafe"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestDirectIO(t *testing.T) {
+	for _, n := range []int{PageSize, 3 * PageSize} {
+		buf := alignedBuf(n)
+		require.Len(t, buf, n)
+		require.Zero(t, uintptr(unsafe.Pointer(&buf[0]))%directAlign)
+	}
+
+	dir := t.TempDir()
+	sm := NewStorageManager()
+	err := sm.SetDirectIO(dir)
+	if errors.Is(err, ErrDirectIO) {
+		t.Skip(err)
+	}
+	require.NoError(t, err)
+	require.True(t, sm.DirectIO())
+
+	fs := LocalFileSet{Dir: dir, Base: "rel"}
+	pages := make([]Page, 3)
+	for i := range pages {
+		p, err := NewPage(make([]byte, PageSize), uint32(i))
+		require.NoError(t, err)
+		_, err = p.InsertTuple([]byte{byte(i)})
+		require.NoError(t, err)
+		pages[i] = *p
+	}
+	require.NoError(t, sm.SavePages(fs, 0, pages[:2]))
+	require.NoError(t, sm.SavePage(fs, 2, pages[2]))
+
+	// What went around the page cache reads back the same, direct or not.
+	for _, r := range []*StorageManager{sm, NewStorageManager()} {
+		loaded, err := r.LoadPages(fs, 0, 4)
+		require.NoError(t, err)
+		for i := range pages {
+			require.Equal(t, pages[i].Buf, loaded[i].Buf)
+		}
+		require.Equal(t, 0, loaded[3].NumSlots())
+	}
+}
@@ -0,0 +1,90 @@
+package storage
+
+import (
+	"io"
+	"os"
+	"path/filepath"
+)
+
+// Engine stores the pages of a StorageManager made with
+// NewStorageManagerWithEngine, in place of segment files: an object store,
+// a remote block device, a wrapper adding its own encryption, and so on.
+// The manager keeps framing, checksums and (with SetEncryptionKey) page
+// encryption; the engine only sees whole PageSize slots.
+//
+// A file names one relation (heap, index, overflow): the Dir and Base of its
+// LocalFileSet joined, e.g. "/data/default/tables/users". The engine must be
+// safe for concurrent use.
+type Engine interface {
+	// ReadPage fills dst, PageSize bytes, with page pageID of file. Pages
+	// past the end or never written read as zeros.
+	ReadPage(file string, pageID uint32, dst []byte) error
+	// WritePage stores src, PageSize bytes, as page pageID of file. The
+	// manager never writes past the end without calling Allocate first.
+	WritePage(file string, pageID uint32, src []byte) error
+	// Allocate grows file to n pages if it is shorter, creating it if
+	// needed; new pages read as zeros.
+	Allocate(file string, n uint32) error
+	// Len is the number of pages of file, 0 for a missing file.
+	Len(file string) (uint32, error)
+	// Sync makes every page written so far durable.
+	Sync() error
+	// Remove deletes file; a missing file is not an error.
+	Remove(file string) error
+	// Rename moves oldFile to newFile, which must not exist.
+	Rename(oldFile, newFile string) error
+	// RemoveDir deletes every file under dir (a dropped database or trash
+	// entry).
+	RemoveDir(dir string) error
+}
+
+// File is an open log file of a database, the WAL or the transaction
+// status log; *os.File is one.
+type File interface {
+	io.Writer
+	io.WriterAt
+	Sync() error
+	Truncate(size int64) error
+	Stat() (os.FileInfo, error)
+	Close() error
+}
+
+// FileOpener is implemented by an Engine that also handles the log files of
+// the databases it keeps, which are OS files otherwise. OpenFile opens them
+// as os.OpenFile does; recovery and backups still read them at their path,
+// so the engine must keep them there.
+type FileOpener interface {
+	OpenFile(name string, flag int, perm os.FileMode) (File, error)
+}
+
+// NewStorageManagerWithEngine returns a StorageManager that keeps the pages
+// of every LocalFileSet in e. Compression does not apply to it.
+func NewStorageManagerWithEngine(e Engine) *StorageManager {
+	return &StorageManager{engine: e}
+}
+
+// Engine is the engine of the manager, nil for the built-in modes.
+func (sm *StorageManager) Engine() Engine { return sm.engine }
+
+// EngineFile is the file name an Engine sees for lfs.
+func EngineFile(lfs LocalFileSet) string {
+	return filepath.Join(lfs.Dir, lfs.Base)
+}
+
+func (sm *StorageManager) engineRead(lfs LocalFileSet, pageID int32, dst []byte) error {
+	return sm.engine.ReadPage(EngineFile(lfs), uint32(pageID), dst)
+}
+
+func (sm *StorageManager) engineWrite(lfs LocalFileSet, pageID int32, src []byte) error {
+	file := EngineFile(lfs)
+	n, err := sm.engine.Len(file)
+	if err != nil {
+		return err
+	}
+	if uint32(pageID) >= n {
+		if err := sm.engine.Allocate(file, uint32(pageID)+1); err != nil {
+			return err
+		}
+	}
+	return sm.engine.WritePage(file, uint32(pageID), src)
+}
@@ -0,0 +1,124 @@
+package storage
+
+import (
+	"maps"
+	"path/filepath"
+	"strings"
+	"sync"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+// mapEngine keeps pages in maps and records the calls made to it.
+type mapEngine struct {
+	mu     sync.Mutex
+	files  map[string][][]byte
+	allocs []uint32
+	syncs  int
+}
+
+func newMapEngine() *mapEngine { return &mapEngine{files: make(map[string][][]byte)} }
+
+func (e *mapEngine) ReadPage(file string, pageID uint32, dst []byte) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	clear(dst)
+	if pages := e.files[file]; pageID < uint32(len(pages)) {
+		copy(dst, pages[pageID])
+	}
+	return nil
+}
+
+func (e *mapEngine) WritePage(file string, pageID uint32, src []byte) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	e.files[file][pageID] = append([]byte(nil), src...)
+	return nil
+}
+
+func (e *mapEngine) Allocate(file string, n uint32) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	e.allocs = append(e.allocs, n)
+	for uint32(len(e.files[file])) < n {
+		e.files[file] = append(e.files[file], make([]byte, PageSize))
+	}
+	return nil
+}
+
+func (e *mapEngine) Len(file string) (uint32, error) {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	return uint32(len(e.files[file])), nil
+}
+
+func (e *mapEngine) Sync() error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	e.syncs++
+	return nil
+}
+
+func (e *mapEngine) Remove(file string) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	delete(e.files, file)
+	return nil
+}
+
+func (e *mapEngine) Rename(oldFile, newFile string) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	if pages, ok := e.files[oldFile]; ok {
+		e.files[newFile] = pages
+		delete(e.files, oldFile)
+	}
+	return nil
+}
+
+func (e *mapEngine) RemoveDir(dir string) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	maps.DeleteFunc(e.files, func(file string, _ [][]byte) bool {
+		return strings.HasPrefix(file, dir+string(filepath.Separator))
+	})
+	return nil
+}
+
+func TestStorageManager_Engine(t *testing.T) {
+	e := newMapEngine()
+	sm := NewStorageManagerWithEngine(e)
+	require.Same(t, e, sm.Engine())
+	dir := t.TempDir()
+	fs := LocalFileSet{Dir: dir, Base: "users"}
+
+	p, err := sm.LoadPage(fs, 2)
+	require.NoError(t, err)
+	_, err = p.InsertTuple([]byte("ann"))
+	require.NoError(t, err)
+	require.NoError(t, sm.SavePage(fs, 2, *p))
+	require.NoError(t, sm.SavePage(fs, 1, *p))
+	require.Equal(t, []uint32{3}, e.allocs, "only writes past the end allocate")
+	n, err := sm.CountPages(fs)
+	require.NoError(t, err)
+	require.Equal(t, uint32(3), n)
+
+	got, err := sm.LoadPage(fs, 2)
+	require.NoError(t, err)
+	raw, err := got.ReadTuple(0)
+	require.NoError(t, err)
+	require.Equal(t, []byte("ann"), raw)
+	require.Contains(t, e.files, filepath.Join(dir, "users"))
+	require.NoFileExists(t, fs.SegmentPath(0))
+
+	require.NoError(t, sm.Sync())
+	require.Equal(t, 1, e.syncs)
+	moved := LocalFileSet{Dir: dir, Base: "people"}
+	require.NoError(t, sm.RenameSegments(fs, moved))
+	n, err = sm.CountPages(moved)
+	require.NoError(t, err)
+	require.Equal(t, uint32(3), n)
+	require.NoError(t, sm.RemoveDir(dir))
+	require.Empty(t, e.files)
+}
@@ -0,0 +1,76 @@
+package storage
+
+import (
+	"errors"
+	"slices"
+)
+
+var (
+	ErrPageAlreadyFree  = errors.New("storage: page already free")
+	ErrPageNotAllocated = errors.New("storage: page was never allocated")
+)
+
+// PageAllocator hands out page IDs within one FileSet. AllocatePage reuses
+// pages returned by FreePage (most recently freed first) before growing the
+// file, so delete-heavy structures stop growing without bound.
+//
+// It does no I/O: the owner persists NextPageID and FreePages next to its
+// own metadata and rebuilds the allocator from them on open.
+type PageAllocator struct {
+	next uint32
+	free []uint32 // stack; top is reused first
+}
+
+// NewPageAllocator resumes an allocator whose next fresh page is next and
+// whose free list is free. Duplicates and IDs >= next are dropped.
+func NewPageAllocator(next uint32, free []uint32) *PageAllocator {
+	a := &PageAllocator{next: next}
+	for _, id := range free {
+		if id < next && !slices.Contains(a.free, id) {
+			a.free = append(a.free, id)
+		}
+	}
+	return a
+}
+
+// AllocatePage returns a free page if there is one, otherwise the next page
+// past the end of the file.
+func (a *PageAllocator) AllocatePage() uint32 {
+	if n := len(a.free); n > 0 {
+		id := a.free[n-1]
+		a.free = a.free[:n-1]
+		return id
+	}
+	id := a.next
+	a.next++
+	return id
+}
+
+// FreePage returns id to the free list. The page's content is left as is;
+// the next owner overwrites it.
+func (a *PageAllocator) FreePage(id uint32) error {
+	if id >= a.next {
+		return ErrPageNotAllocated
+	}
+	if slices.Contains(a.free, id) {
+		return ErrPageAlreadyFree
+	}
+	a.free = append(a.free, id)
+	return nil
+}
+
+// NextPageID is the page the allocator grows into once the free list is empty.
+func (a *PageAllocator) NextPageID() uint32 { return a.next }
+
+// FreePages returns a copy of the free list, in reuse order reversed
+// (the last element is handed out first).
+func (a *PageAllocator) FreePages() []uint32 { return slices.Clone(a.free) }
+
+// FreeCount is the number of pages waiting for reuse.
+func (a *PageAllocator) FreeCount() int { return len(a.free) }
+
+// Grow moves the end of the allocator forward to at least next, e.g. when
+// the file turns out to hold more pages than the persisted meta recorded.
+func (a *PageAllocator) Grow(next uint32) {
+	a.next = max(a.next, next)
+}
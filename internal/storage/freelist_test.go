@@ -0,0 +1,41 @@
+package storage
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestPageAllocator_ReusesFreedPages(t *testing.T) {
+	a := NewPageAllocator(1, nil)
+	require.Equal(t, uint32(1), a.AllocatePage())
+	require.Equal(t, uint32(2), a.AllocatePage())
+	require.Equal(t, uint32(3), a.AllocatePage())
+
+	require.NoError(t, a.FreePage(1))
+	require.NoError(t, a.FreePage(3))
+	require.ErrorIs(t, a.FreePage(3), ErrPageAlreadyFree)
+	require.ErrorIs(t, a.FreePage(9), ErrPageNotAllocated)
+
+	// Most recently freed first, and the file does not grow while any remain.
+	require.Equal(t, uint32(3), a.AllocatePage())
+	require.Equal(t, uint32(1), a.AllocatePage())
+	require.Equal(t, uint32(4), a.AllocatePage())
+	require.Equal(t, uint32(5), a.NextPageID())
+}
+
+func TestPageAllocator_Resume(t *testing.T) {
+	a := NewPageAllocator(4, []uint32{2, 2, 7, 1})
+	require.Equal(t, []uint32{2, 1}, a.FreePages())
+
+	a.Grow(10)
+	a.Grow(3)
+	require.Equal(t, uint32(10), a.NextPageID())
+
+	// FreePages is a copy.
+	free := a.FreePages()
+	free[0] = 99
+	require.Equal(t, uint32(1), a.AllocatePage())
+	require.Equal(t, uint32(2), a.AllocatePage())
+	require.Equal(t, uint32(10), a.AllocatePage())
+}
@@ -0,0 +1,27 @@
+//go:build linux
+
+package storage
+
+import (
+	"errors"
+	"os"
+	"syscall"
+)
+
+const holePunchSupported = true
+
+const (
+	fallocKeepSize  = 0x01 // FALLOC_FL_KEEP_SIZE
+	fallocPunchHole = 0x02 // FALLOC_FL_PUNCH_HOLE
+)
+
+// punchHole deallocates n bytes of f at off; they read back as zeros and
+// the file size stays the same. Filesystems without hole support keep the
+// blocks, which only costs the space compression would have saved.
+func punchHole(f *os.File, off, n int64) error {
+	err := syscall.Fallocate(int(f.Fd()), fallocPunchHole|fallocKeepSize, off, n)
+	if errors.Is(err, syscall.EOPNOTSUPP) {
+		return nil
+	}
+	return err
+}
@@ -0,0 +1,79 @@
+package storage
+
+// PageInfo describes a slotted page as its bytes have it, for inspection
+// and debugging. InspectPage does not trust the header, so that it can
+// describe corrupted pages too.
+type PageInfo struct {
+	PageID                uint32
+	Flags                 uint16
+	Lower, Upper, Special uint16
+	Uninitialized         bool // never formatted: no header, no slots
+	FreeSpace             int
+
+	Checksum   uint32 // 0 without a checksum
+	ChecksumOK bool   // also true for pages without a checksum
+	Version    uint64
+	LSN        uint64
+
+	Slots []SlotInfo
+
+	// Problem is why the page is not valid, "" for a valid page. The slots
+	// of a page whose header is invalid are not read.
+	Problem string
+}
+
+// SlotInfo is a line pointer and, for a normal slot, the tuple it points to.
+type SlotInfo struct {
+	Slot
+	Tuple []byte // nil for a dead or moved slot, or one pointing off the page
+}
+
+// InspectPage describes the page in buf. Tuples alias buf.
+func InspectPage(buf []byte) PageInfo {
+	if len(buf) != PageSize {
+		return PageInfo{Problem: ErrWrongSize.Error()}
+	}
+	p := &Page{Buf: buf}
+	info := PageInfo{
+		PageID:        p.PageID(),
+		Flags:         p.flags(),
+		Lower:         p.lower(),
+		Upper:         p.upper(),
+		Special:       p.special(),
+		Uninitialized: p.IsUninitialized(),
+		ChecksumOK:    p.VerifyChecksum(),
+		LSN:           p.PageLSN(),
+	}
+	if info.Uninitialized {
+		return info
+	}
+	if err := p.Validate(); err != nil {
+		info.Problem = err.Error()
+		return info
+	}
+	info.FreeSpace = p.FreeSpace()
+	if p.HasChecksum() {
+		info.Checksum = p.Checksum()
+	}
+	if p.HasVersion() {
+		info.Version = p.Version()
+	}
+	if !info.ChecksumOK {
+		info.Problem = "checksum mismatch"
+	}
+
+	for i := range p.NumSlots() {
+		s, err := p.getSlot(i)
+		if err != nil {
+			info.Problem = err.Error()
+			break
+		}
+		si := SlotInfo{Slot: s}
+		start, end := int(s.Offset), int(s.Offset)+int(s.Length)
+		if s.Flags == SlotFlagNormal && s.Length > 0 && start >= int(info.Upper) && end <= int(info.Special) {
+			si.Tuple = buf[start:end]
+		}
+		info.Slots = append(info.Slots, si)
+	}
+	return info
+}
@@ -0,0 +1,101 @@
+package storage
+
+import (
+	"errors"
+	"os"
+	"path/filepath"
+	"sync"
+	"syscall"
+)
+
+// ErrLocked is returned by LockDir when another process holds the lock.
+var ErrLocked = errors.New("storage: directory is locked by another process")
+
+// LockFileName is the file in a locked directory that LockDir locks.
+const LockFileName = "LOCK"
+
+// DirLock is an advisory lock on a directory, taken with LockDir: an
+// exclusive lock for a process that writes the files of the directory, a
+// shared one for processes that only read them. The lock is held by the
+// process, so handles of one process share it; it ends with the last Unlock
+// or with the process.
+type DirLock struct {
+	path string
+	once sync.Once
+}
+
+// heldLock is the lock of one directory held by this process.
+type heldLock struct {
+	f      *os.File
+	shared bool
+	refs   int
+}
+
+var dirLocks = struct {
+	sync.Mutex
+	held map[string]*heldLock
+}{held: make(map[string]*heldLock)}
+
+// LockDir takes the lock of dir, shared or exclusive, failing at once with
+// ErrLocked rather than waiting for another process. Both create the lock
+// file when it does not exist yet; only a shared lock in a directory nobody
+// may create it in, such as one on read-only media, is not taken.
+func LockDir(dir string, shared bool) (*DirLock, error) {
+	path, err := filepath.Abs(filepath.Join(dir, LockFileName))
+	if err != nil {
+		return nil, err
+	}
+	dirLocks.Lock()
+	defer dirLocks.Unlock()
+
+	if h, ok := dirLocks.held[path]; ok {
+		if h.shared && !shared {
+			if err := flockFile(h.f, false); err != nil {
+				return nil, err
+			}
+			h.shared = false
+		}
+		h.refs++
+		return &DirLock{path: path}, nil
+	}
+
+	var f *os.File
+	if shared {
+		f, err = os.OpenFile(path, os.O_RDONLY|os.O_CREATE, 0o644)
+		if errors.Is(err, os.ErrPermission) || errors.Is(err, syscall.EROFS) {
+			if f, err = os.Open(path); errors.Is(err, os.ErrNotExist) {
+				return &DirLock{}, nil // unwritable: no writer can lock it either
+			}
+		}
+	} else {
+		f, err = os.OpenFile(path, os.O_RDWR|os.O_CREATE, 0o644)
+	}
+	if err != nil {
+		return nil, err
+	}
+	if err := flockFile(f, shared); err != nil {
+		_ = f.Close()
+		return nil, err
+	}
+	dirLocks.held[path] = &heldLock{f: f, shared: shared, refs: 1}
+	return &DirLock{path: path}, nil
+}
+
+// Unlock gives the lock back; the process releases it once no handle holds
+// it any more. Unlocking again does nothing.
+func (l *DirLock) Unlock() error {
+	if l == nil || l.path == "" {
+		return nil
+	}
+	var err error
+	l.once.Do(func() {
+		dirLocks.Lock()
+		defer dirLocks.Unlock()
+		h := dirLocks.held[l.path]
+		if h.refs--; h.refs == 0 {
+			delete(dirLocks.held, l.path)
+			err = h.f.Close() // closing the file releases the lock
+		}
+	})
+	return err
+}
@@ -0,0 +1,8 @@
+//go:build !(linux || darwin)
+
+package storage
+
+import "os"
+
+// flockFile does nothing: directories are not locked on this platform.
+func flockFile(*os.File, bool) error { return nil }
@@ -0,0 +1,71 @@
+//go:build linux || darwin
+
+package storage
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestLockDir(t *testing.T) {
+	dir := t.TempDir()
+	path := filepath.Join(dir, LockFileName)
+
+	a, err := LockDir(dir, false)
+	require.NoError(t, err)
+	b, err := LockDir(dir, true) // same process: shares the lock
+	require.NoError(t, err)
+
+	// other stands for another process, whose flock conflicts.
+	other, err := os.Open(path)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = other.Close() })
+	require.ErrorIs(t, flockFile(other, true), ErrLocked)
+
+	require.NoError(t, a.Unlock())
+	require.NoError(t, a.Unlock())
+	require.ErrorIs(t, flockFile(other, true), ErrLocked)
+	require.NoError(t, b.Unlock())
+	require.NoError(t, flockFile(other, true))
+
+	// A shared lock held elsewhere keeps this process from writing.
+	_, err = LockDir(dir, false)
+	require.ErrorIs(t, err, ErrLocked)
+	ro, err = LockDir(dir, true)
+	require.NoError(t, err)
+	require.NoError(t, ro.Unlock())
+}
+
+func TestLockDir_SharedCreatesLockFile(t *testing.T) {
+	dir := t.TempDir()
+	path := filepath.Join(dir, LockFileName)
+
+	// A reader that comes first still keeps writers out.
+	ro, err := LockDir(dir, true)
+	require.NoError(t, err)
+	require.FileExists(t, path)
+	other, err := os.Open(path)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = other.Close() })
+	require.ErrorIs(t, flockFile(other, false), ErrLocked)
+	require.NoError(t, ro.Unlock())
+	require.NoError(t, flockFile(other, false))
+	require.NoError(t, other.Close())
+
+	// Without the lock file, a directory that cannot take one is read unlocked.
+	if os.Geteuid() == 0 {
+		t.Skip("root can write to any directory")
+	}
+	ronly := t.TempDir()
+	require.NoError(t, os.Chmod(ronly, 0o555))
+	t.Cleanup(func() { _ = os.Chmod(ronly, 0o755) })
+	ro, err = LockDir(ronly, true)
+	require.NoError(t, err)
+	require.NoFileExists(t, filepath.Join(ronly, LockFileName))
+	require.NoError(t, ro.Unlock())
+	_, err = LockDir(ronly, false)
+	require.ErrorIs(t, err, os.ErrPermission)
+}
@@ -0,0 +1,132 @@
+package storage
+
+import (
+	"fmt"
+	"path/filepath"
+	"strings"
+	"sync"
+)
+
+// memStore holds the pages of a Memory-mode StorageManager: one slice of
+// pages per LocalFileSet, keyed by Dir/Base. Page i of a file set is pages[i]
+// whatever segment it would live in on disk; a nil entry is a hole that
+// reads as zeros. Nothing is ever written to disk.
+type memStore struct {
+	mu    sync.Mutex
+	files map[string]*memFile
+}
+
+type memFile struct {
+	pages [][]byte
+}
+
+func newMemStore() *memStore {
+	return &memStore{files: make(map[string]*memFile)}
+}
+
+func memKey(lfs LocalFileSet) string {
+	return filepath.Join(lfs.Dir, lfs.Base)
+}
+
+func (m *memStore) read(lfs LocalFileSet, off int64, dst []byte) int {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	f := m.files[memKey(lfs)]
+	if f == nil {
+		clear(dst)
+		return 0
+	}
+	return f.readAt(dst, off)
+}
+
+func (m *memStore) write(lfs LocalFileSet, off int64, src []byte) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	key := memKey(lfs)
+	f := m.files[key]
+	if f == nil {
+		f = &memFile{}
+		m.files[key] = f
+	}
+	f.writeAt(src, off)
+}
+
+func (m *memStore) count(lfs LocalFileSet) uint32 {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	if f := m.files[memKey(lfs)]; f != nil {
+		return uint32(len(f.pages))
+	}
+	return 0
+}
+
+func (m *memStore) remove(lfs LocalFileSet) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	delete(m.files, memKey(lfs))
+}
+
+func (m *memStore) rename(oldLFS, newLFS LocalFileSet) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	oldKey, newKey := memKey(oldLFS), memKey(newLFS)
+	f := m.files[oldKey]
+	if f == nil {
+		return nil
+	}
+	if m.files[newKey] != nil {
+		return fmt.Errorf("rename segments: target exists: %s", newKey)
+	}
+	delete(m.files, oldKey)
+	m.files[newKey] = f
+	return nil
+}
+
+// removeDir drops every file set under dir.
+func (m *memStore) removeDir(dir string) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	prefix := filepath.Clean(dir) + string(filepath.Separator)
+	for key := range m.files {
+		if strings.HasPrefix(key, prefix) {
+			delete(m.files, key)
+		}
+	}
+}
+
+// readAt copies the bytes at off into dst, zero-filling holes and the part
+// past the end, and returns how many bytes were inside the file.
+func (f *memFile) readAt(dst []byte, off int64) int {
+	size := int64(len(f.pages)) * PageSize
+	n := 0
+	for n < len(dst) {
+		pos := off + int64(n)
+		if pos >= size {
+			clear(dst[n:])
+			return n
+		}
+		page, inPage := f.pages[pos/PageSize], pos%PageSize
+		if page == nil {
+			end := min(len(dst), n+int(PageSize-inPage))
+			clear(dst[n:end])
+			n = end
+		} else {
+			n += copy(dst[n:], page[inPage:])
+		}
+	}
+	return n
+}
+
+func (f *memFile) writeAt(src []byte, off int64) {
+	for n := 0; n < len(src); {
+		pos := off + int64(n)
+		idx := int(pos / PageSize)
+		for len(f.pages) <= idx {
+			f.pages = append(f.pages, nil)
+		}
+		if f.pages[idx] == nil {
+			f.pages[idx] = make([]byte, PageSize)
+		}
+		n += copy(f.pages[idx][pos%PageSize:], src[n:])
+	}
+}
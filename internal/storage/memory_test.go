@@ -0,0 +1,89 @@
+package storage
+
+import (
+	"bytes"
+	"os"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestStorageManager_Memory(t *testing.T) {
+	sm, err := NewStorageManagerFor(Memory)
+	require.NoError(t, err)
+	require.True(t, sm.InMemory())
+	dir := t.TempDir()
+	fs := LocalFileSet{Dir: dir, Base: "seg"}
+
+	page := func(b byte) []byte { return bytes.Repeat([]byte{b}, PageSize) }
+	buf := make([]byte, PageSize)
+
+	require.NoError(t, sm.ReadPage(fs, 0, buf))
+	require.Equal(t, page(0), buf)
+
+	// Pages are counted like file pages, holes included.
+	require.NoError(t, sm.WritePage(fs, 0, page(1)))
+	require.NoError(t, sm.WritePage(fs, MaxPagePerSegment+1, page(3)))
+	n, err := sm.CountPages(fs)
+	require.NoError(t, err)
+	require.Equal(t, uint32(MaxPagePerSegment+2), n)
+	require.NoError(t, sm.ReadPage(fs, MaxPagePerSegment+1, buf))
+	require.Equal(t, page(3), buf)
+	require.NoError(t, sm.ReadPage(fs, 1, buf))
+	require.Equal(t, page(0), buf)
+	require.NoError(t, sm.VerifySegments(fs))
+
+	// Rename and remove move the pages, never the files.
+	moved := LocalFileSet{Dir: dir, Base: "moved"}
+	require.NoError(t, sm.RenameSegments(fs, moved))
+	require.Error(t, sm.RenameSegments(moved, moved))
+	require.NoError(t, sm.ReadPage(moved, 0, buf))
+	require.Equal(t, page(1), buf)
+	n, err = sm.CountPages(fs)
+	require.NoError(t, err)
+	require.Zero(t, n)
+
+	require.NoError(t, sm.RemoveSegments(moved))
+	require.NoError(t, sm.ReadPage(moved, 0, buf))
+	require.Equal(t, page(0), buf)
+
+	require.NoError(t, sm.WritePage(fs, 0, page(5)))
+	require.NoError(t, sm.RemoveDir(dir))
+	n, err = sm.CountPages(fs)
+	require.NoError(t, err)
+	require.Zero(t, n)
+
+	ents, err := os.ReadDir(dir)
+	require.NoError(t, err)
+	require.Empty(t, ents)
+}
+
+func TestOverflow_Memory(t *testing.T) {
+	sm, err := NewStorageManagerFor(Memory)
+	require.NoError(t, err)
+	dir := t.TempDir()
+	fs := LocalFileSet{Dir: dir, Base: "ovf_test"}
+	ovf := NewOverflowManager(fs)
+	ovf.SetStorage(sm)
+
+	payload := bytes.Repeat([]byte("0123456789"), 2000)
+	ref, err := ovf.Write(payload)
+	require.NoError(t, err)
+	out, err := ovf.Read(ref)
+	require.NoError(t, err)
+	require.Equal(t, payload, out)
+
+	// Freed pages are reused by the next chain.
+	before, err := sm.CountPages(fs)
+	require.NoError(t, err)
+	require.NoError(t, ovf.Free(ref))
+	_, err = ovf.Write(payload[:100])
+	require.NoError(t, err)
+	after, err := sm.CountPages(fs)
+	require.NoError(t, err)
+	require.Equal(t, before, after)
+
+	ents, err := os.ReadDir(dir)
+	require.NoError(t, err)
+	require.Empty(t, ents)
+}
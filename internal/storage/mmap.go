@@ -0,0 +1,152 @@
+package storage
+
+import (
+	"errors"
+	"os"
+	"path/filepath"
+	"strings"
+	"sync"
+)
+
+// mappings holds the memory-mapped segments of a Mmap-mode StorageManager,
+// by path. A segment is mapped once at its largest size (SegmentSize) while
+// the file itself only grows, with ftruncate, as pages are written; bytes
+// past the end of the file are never touched, so growth needs no remap and
+// slices into the mapping stay valid.
+//
+// Every access stats the path first: a file that was removed, replaced or
+// truncated behind the manager's back is noticed and remapped, and reads
+// past its end return zeros like file reads do. Writes to a mapping cannot
+// report I/O errors: a full disk surfaces as SIGBUS rather than an error.
+type mappings struct {
+	mu   sync.Mutex
+	segs map[string]*mappedSegment
+}
+
+type mappedSegment struct {
+	f    *os.File
+	info os.FileInfo // identity of the mapped file
+	data []byte      // SegmentSize bytes
+}
+
+func newMappings() *mappings {
+	return &mappings{segs: make(map[string]*mappedSegment)}
+}
+
+func (m *mappings) read(path string, off int64, dst []byte) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	seg, size, err := m.segment(path, false)
+	if err != nil {
+		return err
+	}
+	n := 0
+	if seg != nil && off < size {
+		n = copy(dst, seg.data[off:min(off+int64(len(dst)), size)])
+	}
+	clear(dst[n:])
+	return nil
+}
+
+func (m *mappings) write(path string, off int64, src []byte) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	seg, size, err := m.segment(path, true)
+	if err != nil {
+		return err
+	}
+	if end := off + int64(len(src)); end > size {
+		if err := seg.f.Truncate(end); err != nil {
+			return err
+		}
+	}
+	copy(seg.data[off:], src)
+	return nil
+}
+
+// segment returns the mapping of path and the current size of the file,
+// mapping the file first if it is not the one mapped. A missing file is
+// created when create is set and yields a nil segment otherwise.
+func (m *mappings) segment(path string, create bool) (*mappedSegment, int64, error) {
+	info, err := os.Stat(path)
+	switch {
+	case err == nil:
+		if seg := m.segs[path]; seg != nil {
+			if os.SameFile(seg.info, info) {
+				return seg, info.Size(), nil
+			}
+			if err := m.unmap(path); err != nil {
+				return nil, 0, err
+			}
+		}
+	case errors.Is(err, os.ErrNotExist):
+		if err := m.unmap(path); err != nil {
+			return nil, 0, err
+		}
+		if !create {
+			return nil, 0, nil
+		}
+		if err := os.MkdirAll(filepath.Dir(path), FileMode0755); err != nil {
+			return nil, 0, err
+		}
+	default:
+		return nil, 0, err
+	}
+
+	f, err := os.OpenFile(path, os.O_RDWR|os.O_CREATE, FileMode0644)
+	if err != nil {
+		return nil, 0, err
+	}
+	info, err = f.Stat()
+	if err != nil {
+		_ = f.Close()
+		return nil, 0, err
+	}
+	data, err := mmapFile(f, SegmentSize)
+	if err != nil {
+		_ = f.Close()
+		return nil, 0, err
+	}
+	seg := &mappedSegment{f: f, info: info, data: data}
+	m.segs[path] = seg
+	return seg, info.Size(), nil
+}
+
+func (m *mappings) sync() error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	for _, seg := range m.segs {
+		info, err := seg.f.Stat()
+		if err != nil {
+			return err
+		}
+		if size := min(info.Size(), int64(len(seg.data))); size > 0 {
+			if err := msync(seg.data[:size]); err != nil {
+				return err
+			}
+		}
+	}
+	return nil
+}
+
+// release unmaps the segments of the file set at base ("" = all).
+func (m *mappings) release(base string) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	var errs []error
+	for path := range m.segs {
+		if base == "" || path == base || strings.HasPrefix(path, base+".") {
+			errs = append(errs, m.unmap(path))
+		}
+	}
+	return errors.Join(errs...)
+}
+
+func (m *mappings) unmap(path string) error {
+	seg := m.segs[path]
+	if seg == nil {
+		return nil
+	}
+	delete(m.segs, path)
+	return errors.Join(munmap(seg.data), seg.f.Close())
+}
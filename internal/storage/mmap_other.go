@@ -0,0 +1,16 @@
+//go:build !(linux || darwin)
+
+package storage
+
+import (
+	"errors"
+	"os"
+)
+
+const mmapSupported = false
+
+var errNoMmap = errors.New("storage: mmap is not supported on this platform")
+
+func mmapFile(*os.File, int) ([]byte, error) { return nil, errNoMmap }
+func munmap([]byte) error                    { return errNoMmap }
+func msync([]byte) error                     { return errNoMmap }
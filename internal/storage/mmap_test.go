@@ -0,0 +1,73 @@
+package storage
+
+import (
+	"bytes"
+	"os"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestStorageManager_Mmap(t *testing.T) {
+	if !mmapSupported {
+		t.Skip("mmap is not supported on this platform")
+	}
+	sm, err := NewStorageManagerFor(Mmap)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = sm.Close() })
+	fs := LocalFileSet{Dir: t.TempDir(), Base: "seg"}
+
+	page := func(b byte) []byte { return bytes.Repeat([]byte{b}, PageSize) }
+	buf := make([]byte, PageSize)
+
+	// Missing files read as zeros and are not created by reads.
+	require.NoError(t, sm.ReadPage(fs, 0, buf))
+	require.Equal(t, page(0), buf)
+	require.NoFileExists(t, fs.SegmentPath(0))
+
+	// Writes grow the file page by page, like file writes.
+	require.NoError(t, sm.WritePage(fs, 0, page(1)))
+	require.NoError(t, sm.WritePage(fs, 2, page(3)))
+	n, err := sm.CountPages(fs)
+	require.NoError(t, err)
+	require.Equal(t, uint32(3), n)
+	require.NoError(t, sm.ReadPage(fs, 2, buf))
+	require.Equal(t, page(3), buf)
+	require.NoError(t, sm.ReadPage(fs, 1, buf))
+	require.Equal(t, page(0), buf)
+	require.NoError(t, sm.Sync())
+
+	// The mapping and plain file reads see the same bytes.
+	raw, err := os.ReadFile(fs.SegmentPath(0))
+	require.NoError(t, err)
+	require.Equal(t, page(1), raw[:PageSize])
+	require.NoError(t, NewStorageManager().ReadPage(fs, 2, buf))
+	require.Equal(t, page(3), buf)
+
+	// A file replaced behind the manager is mapped again.
+	require.NoError(t, os.Remove(fs.SegmentPath(0)))
+	require.NoError(t, NewStorageManager().WritePage(fs, 0, page(7)))
+	require.NoError(t, sm.ReadPage(fs, 0, buf))
+	require.Equal(t, page(7), buf)
+	require.NoError(t, sm.ReadPage(fs, 2, buf))
+	require.Equal(t, page(0), buf, "past the end of the new file")
+
+	require.NoError(t, sm.Release(fs))
+	require.NoError(t, sm.ReadPage(fs, 0, buf))
+	require.Equal(t, page(7), buf)
+}
+
+func TestNewStorageManagerFor(t *testing.T) {
+	sm, err := NewStorageManagerFor(Classic)
+	require.NoError(t, err)
+	require.NoError(t, sm.Sync())
+	require.NoError(t, sm.Close())
+
+	_, err = NewStorageManagerFor(Document)
+	require.Error(t, err)
+
+	mode, err := GetStorageMode("mmap")
+	require.NoError(t, err)
+	require.Equal(t, Mmap, mode)
+	require.Equal(t, "mmap", mode.String())
+}
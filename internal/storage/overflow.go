@@ -3,6 +3,7 @@ package storage
 import (
 	"errors"
 	"fmt"
+	"io"
 	"log/slog"
 	"os"
 
@@ -61,6 +62,7 @@ type OverflowRef struct {
 type OverflowManager struct {
 	fs  FileSet
 	wal *wal.Manager
+	sm  *StorageManager // optional; set for Memory mode and encryption
 }
 
 func NewOverflowManager(fs FileSet) *OverflowManager {
@@ -75,6 +77,120 @@ func (ovf *OverflowManager) SetWAL(w *wal.Manager) {
 	ovf.wal = w
 }
 
+// SetStorage makes ovf keep its pages where sm does: a Memory-mode manager
+// holds them in memory instead of the segment file, an encrypting one
+// encrypts them.
+func (ovf *OverflowManager) SetStorage(sm *StorageManager) {
+	ovf.sm = sm
+}
+
+// payloadSize is how many bytes of data one page carries; encrypted pages
+// keep EncryptionReserve bytes before the checksum trailer free.
+func (ovf *OverflowManager) payloadSize() int {
+	if ovf.sm != nil && ovf.sm.Encrypted() {
+		return cryptGapStart - overflowHeaderSize
+	}
+	return overflowPayloadSize
+}
+
+// segmentFile is segment 0 of the overflow file set: the file itself, or
+// its pages as the storage manager sees them.
+type segmentFile interface {
+	io.ReaderAt
+	io.WriterAt
+	io.Closer
+	size() (int64, error)
+}
+
+// osSegment tells sm (if set) about the pages it writes.
+type osSegment struct {
+	*os.File
+	sm *StorageManager
+	fs FileSet
+}
+
+func (s osSegment) WriteAt(p []byte, off int64) (int, error) {
+	n, err := s.File.WriteAt(p, off)
+	if s.sm != nil {
+		for pos := off / PageSize * PageSize; pos < off+int64(n); pos += PageSize {
+			s.sm.noteWrite(s.fs, int32(pos/PageSize))
+		}
+	}
+	return n, err
+}
+
+func (s osSegment) size() (int64, error) {
+	info, err := s.Stat()
+	if err != nil {
+		return 0, err
+	}
+	return info.Size(), nil
+}
+
+func (ovf *OverflowManager) openSegment() (segmentFile, error) {
+	if ovf.sm != nil && ovf.sm.transforms() {
+		return smSegment{sm: ovf.sm, fs: ovf.fs}, nil
+	}
+	var f *os.File
+	var err error
+	if lfs, ok := ovf.fs.(LocalFileSet); ok && ovf.sm != nil && ovf.sm.readOnly {
+		f, err = os.Open(lfs.SegmentPath(0))
+	} else {
+		f, err = ovf.fs.OpenSegment(0)
+	}
+	if err != nil {
+		return nil, err
+	}
+	return osSegment{File: f, sm: ovf.sm, fs: ovf.fs}, nil
+}
+
+// smSegment goes through the storage manager page by page. Writes must
+// cover whole pages, which the overflow manager always writes.
+type smSegment struct {
+	sm *StorageManager
+	fs FileSet
+}
+
+func (s smSegment) ReadAt(p []byte, off int64) (int, error) {
+	count, err := s.sm.CountPages(s.fs)
+	if err != nil {
+		return 0, err
+	}
+	page := make([]byte, PageSize)
+	n := 0
+	for n < len(p) {
+		pos := off + int64(n)
+		pageID := pos / PageSize
+		if pageID >= int64(count) {
+			return n, io.EOF
+		}
+		if err := s.sm.ReadPage(s.fs, int32(pageID), page); err != nil {
+			return n, err
+		}
+		n += copy(p[n:], page[pos%PageSize:])
+	}
+	return n, nil
+}
+
+func (s smSegment) WriteAt(p []byte, off int64) (int, error) {
+	if off%PageSize != 0 || len(p)%PageSize != 0 {
+		return 0, fmt.Errorf("overflow: write of %d bytes at %d is not page aligned", len(p), off)
+	}
+	for n := 0; n < len(p); n += PageSize {
+		if err := s.sm.WritePage(s.fs, int32((off+int64(n))/PageSize), p[n:n+PageSize]); err != nil {
+			return n, err
+		}
+	}
+	return len(p), nil
+}
+
+func (s smSegment) size() (int64, error) {
+	count, err := s.sm.CountPages(s.fs)
+	return int64(count) * PageSize, err
+}
+
+func (smSegment) Close() error { return nil }
+
 func (ovf *OverflowManager) walBeforeWrite(pageID uint32, fullPage []byte) error {
 	if ovf == nil || ovf.wal == nil {
 		return nil
@@ -97,7 +213,7 @@ func (ovf *OverflowManager) Write(data []byte) (OverflowRef, error) {
 		return OverflowRef{}, ErrOverflowEmptyData
 	}
 
-	f, err := ovf.fs.OpenSegment(0)
+	f, err := ovf.openSegment()
 	if err != nil {
 		return OverflowRef{}, err
 	}
@@ -117,7 +233,7 @@ func (ovf *OverflowManager) Write(data []byte) (OverflowRef, error) {
 	hasPrev := false
 
 	for remaining > 0 {
-		chunk := min(remaining, overflowPayloadSize)
+		chunk := min(remaining, ovf.payloadSize())
 
 		pageID, nh, na, err := ovf.allocDataPage(f, freeHead, nextAlloc)
 		if err != nil {
@@ -193,7 +309,7 @@ func (ovf *OverflowManager) Read(ref OverflowRef) ([]byte, error) {
 		return nil, ErrOverflowBadRef
 	}
 
-	f, err := ovf.fs.OpenSegment(0)
+	f, err := ovf.openSegment()
 	if err != nil {
 		return nil, err
 	}
@@ -204,7 +320,8 @@ func (ovf *OverflowManager) Read(ref OverflowRef) ([]byte, error) {
 	pageID := ref.FirstPageID
 
 	// expected upper bound pages for this ref (plus a small slack)
-	maxPages := (remaining + overflowPayloadSize - 1) / overflowPayloadSize
+	payloadMax := ovf.payloadSize()
+	maxPages := (remaining + payloadMax - 1) / payloadMax
 	maxPages += 4
 
 	for range maxPages {
@@ -221,13 +338,13 @@ func (ovf *OverflowManager) Read(ref OverflowRef) ([]byte, error) {
 		next := bx.U32(buf[0:4])
 		used := int(bx.U16(buf[4:6]))
 
-		if used < 0 || used > overflowPayloadSize {
+		if used < 0 || used > payloadMax {
 			slog.Warn("overflow: used too large, clamping",
 				"pageID", pageID,
 				"used_raw", used,
-				"payload_max", overflowPayloadSize,
+				"payload_max", payloadMax,
 			)
-			used = overflowPayloadSize
+			used = payloadMax
 		}
 		if used > remaining {
 			slog.Warn("overflow: used > remaining, clamping",
@@ -268,7 +385,7 @@ func (ovf *OverflowManager) Free(ref OverflowRef) error {
 		return ErrOverflowBadRef
 	}
 
-	f, err := ovf.fs.OpenSegment(0)
+	f, err := ovf.openSegment()
 	if err != nil {
 		return err
 	}
@@ -280,7 +397,8 @@ func (ovf *OverflowManager) Free(ref OverflowRef) error {
 	}
 
 	remaining := int(ref.Length)
-	maxPages := (remaining + overflowPayloadSize - 1) / overflowPayloadSize
+	payloadMax := ovf.payloadSize()
+	maxPages := (remaining + payloadMax - 1) / payloadMax
 	maxPages += 4
 
 	pageID := ref.FirstPageID
@@ -299,8 +417,8 @@ func (ovf *OverflowManager) Free(ref OverflowRef) error {
 
 		next := bx.U32(buf[0:4])
 		used := int(bx.U16(buf[4:6]))
-		if used < 0 || used > overflowPayloadSize {
-			used = overflowPayloadSize
+		if used < 0 || used > payloadMax {
+			used = payloadMax
 		}
 		if used > remaining {
 			used = remaining
@@ -343,14 +461,14 @@ func (ovf *OverflowManager) Free(ref OverflowRef) error {
 
 // ---- meta / alloc helpers ----
 
-func (ovf *OverflowManager) ensureMeta(f *os.File) (freeHead uint32, nextAlloc uint32, err error) {
-	info, err := f.Stat()
+func (ovf *OverflowManager) ensureMeta(f segmentFile) (freeHead uint32, nextAlloc uint32, err error) {
+	size, err := f.size()
 	if err != nil {
 		return 0, 0, err
 	}
 
 	// If file is empty, initialize meta page at page 0.
-	if info.Size() < int64(PageSize) {
+	if size < int64(PageSize) {
 		buf := make([]byte, PageSize)
 		bx.PutU32At(buf, ovfMetaFreeHeadOff, 0)
 		bx.PutU32At(buf, ovfMetaNextAllocOff, ovfFirstDataPageID)
@@ -377,7 +495,7 @@ func (ovf *OverflowManager) ensureMeta(f *os.File) (freeHead uint32, nextAlloc u
 	return freeHead, nextAlloc, nil
 }
 
-func (ovf *OverflowManager) writeMeta(f *os.File, freeHead, nextAlloc uint32) error {
+func (ovf *OverflowManager) writeMeta(f segmentFile, freeHead, nextAlloc uint32) error {
 	buf := make([]byte, PageSize)
 	if _, err := f.ReadAt(buf, 0); err != nil {
 		return err
@@ -395,7 +513,7 @@ func (ovf *OverflowManager) writeMeta(f *os.File, freeHead, nextAlloc uint32) er
 }
 
 func (ovf *OverflowManager) allocDataPage(
-	f *os.File,
+	f segmentFile,
 	freeHead, nextAlloc uint32,
 ) (pageID uint32, newFreeHead uint32, newNextAlloc uint32, err error) {
 	if freeHead != 0 {
@@ -2,6 +2,8 @@ package storage
 
 import (
 	"errors"
+	"fmt"
+	"hash/crc32"
 
 	"github.com/tuannm99/novasql/pkg/bx"
 )
@@ -15,9 +17,11 @@ const (
 	offSpecial = 10
 )
 
-// tail (special)
+// tail (special): [checksum u32][version u64][pageLSN u64]
 const (
-	offPageLSN = PageSize - 8
+	offPageChecksum = PageSize - 20
+	offPageVersion  = PageSize - 16
+	offPageLSN      = PageSize - 8
 )
 
 // Slot flags (similar to Postgres)
@@ -51,8 +55,10 @@ type Slot struct {
 // +------------------+ <-- pd_upper
 // |  Tuple Data      |
 // |  (grows down)    |
-// +------------------+ <-- pd_special (unused)
-// |  Special Space   |
+// +------------------+ <-- pd_special
+// |  Checksum (u32)  |
+// |  Version (u64)   |
+// |  PageLSN (u64)   |
 // +------------------+ Block/Page Size (8192)
 type Page struct {
 	Buf []byte // fixed-size 8KB
@@ -82,17 +88,110 @@ func (p *Page) setSpecial(v uint16) { bx.PutU16At(p.Buf, offSpecial, v) }
 func (p *Page) PageLSN() uint64       { return bx.U64At(p.Buf, offPageLSN) }
 func (p *Page) SetPageLSN(lsn uint64) { bx.PutU64At(p.Buf, offPageLSN, lsn) }
 
+// HasVersion reports whether the page reserves room for a version counter.
+// Pages formatted before versions existed keep tuple data there.
+func (p *Page) HasVersion() bool { return int(p.special()) <= offPageVersion }
+
+// Version returns the page's write counter (0 for unversioned pages).
+func (p *Page) Version() uint64 {
+	if !p.HasVersion() {
+		return 0
+	}
+	return bx.U64At(p.Buf, offPageVersion)
+}
+
+// BumpVersion increments the write counter and returns the new value.
+// It is a no-op returning 0 on unversioned pages.
+func (p *Page) BumpVersion() uint64 {
+	if !p.HasVersion() {
+		return 0
+	}
+	v := bx.U64At(p.Buf, offPageVersion) + 1
+	bx.PutU64At(p.Buf, offPageVersion, v)
+	return v
+}
+
+// HasChecksum reports whether the page reserves room for a checksum. Pages
+// formatted before checksums existed keep tuple data there.
+func (p *Page) HasChecksum() bool { return int(p.special()) <= offPageChecksum }
+
+// computeChecksum is the CRC32 (IEEE) of the whole page except the checksum
+// field itself.
+func (p *Page) computeChecksum() uint32 {
+	sum := crc32.ChecksumIEEE(p.Buf[:offPageChecksum])
+	return crc32.Update(sum, crc32.IEEETable, p.Buf[offPageChecksum+4:])
+}
+
+// Checksum returns the stored checksum (0 for pages without one).
+func (p *Page) Checksum() uint32 {
+	if !p.HasChecksum() {
+		return 0
+	}
+	return bx.U32At(p.Buf, offPageChecksum)
+}
+
+// SealChecksum stores the checksum of the current contents. It must run after
+// the last change and before the bytes leave memory (WAL image, disk write).
+func (p *Page) SealChecksum() {
+	if p.HasChecksum() && !p.IsUninitialized() {
+		bx.PutU32At(p.Buf, offPageChecksum, p.computeChecksum())
+	}
+}
+
+// VerifyChecksum reports whether the stored checksum matches the contents.
+// Uninitialized pages and pages without a checksum always pass.
+func (p *Page) VerifyChecksum() bool {
+	if !p.HasChecksum() || p.IsUninitialized() {
+		return true
+	}
+	return p.Checksum() == p.computeChecksum()
+}
+
+// SetContents overwrites header, slots and tuple data with data's, keeping
+// this page's version and LSN trailer. The checksum is left stale until the
+// next SealChecksum.
+func (p *Page) SetContents(data []byte) error {
+	if len(data) != PageSize {
+		return ErrWrongSize
+	}
+	copy(p.Buf[:offPageVersion], data[:offPageVersion])
+	return nil
+}
+
 func (p *Page) IsUninitialized() bool { return p.lower() == 0 && p.upper() == 0 }
 func (p *Page) FreeSpace() int        { return int(p.upper() - p.lower()) }
 func (p *Page) NumSlots() int         { return int(p.lower()-HeaderSize) / SlotSize }
 func (p *Page) slotOff(idx int) int   { return HeaderSize + idx*SlotSize }
 
+// Validate performs cheap structural checks on the header. Uninitialized
+// (all-zero) pages are valid.
+func (p *Page) Validate() error {
+	if len(p.Buf) != PageSize {
+		return ErrWrongSize
+	}
+	if p.IsUninitialized() {
+		return nil
+	}
+	lo, up, sp := p.lower(), p.upper(), p.special()
+	if lo < HeaderSize || lo > up || up > sp || int(sp) > PageSize || (lo-HeaderSize)%SlotSize != 0 {
+		return fmt.Errorf("%w: page %d: lower=%d upper=%d special=%d",
+			ErrPageCorrupted, p.PageID(), lo, up, sp)
+	}
+	return nil
+}
+
 func (p *Page) markRedirect(old, nw int) error {
 	// Flags=MOVED, Offset=target slot, Length=0
 	return p.putSlot(old, Slot{Offset: uint16(nw), Length: 0, Flags: SlotFlagMoved})
 }
 
 func (p *Page) init(pageID uint32) {
+	p.initReserved(pageID, 0)
+}
+
+// initReserved formats the page with reserve bytes left unused before the
+// trailer (EncryptionReserve on encrypted storage).
+func (p *Page) initReserved(pageID uint32, reserve int) {
 	for i := range p.Buf {
 		p.Buf[i] = 0
 	}
@@ -100,8 +199,8 @@ func (p *Page) init(pageID uint32) {
 	p.setPageID(pageID)
 	p.setLower(HeaderSize)
 
-	// reserve last 8 bytes for PageLSN
-	special := uint16(PageSize - 8)
+	// reserve last 20 bytes for checksum + version + PageLSN
+	special := uint16(offPageChecksum - reserve)
 	p.setSpecial(special)
 	p.setUpper(special)
 
@@ -197,7 +296,14 @@ func (p *Page) InsertTuple(tup []byte) (slot int, err error) {
 	}
 	need := len(tup) + SlotSize
 	if p.FreeSpace() < need {
-		return -1, ErrNoSpace
+		if p.FreeSpace()+p.reclaimable() < need {
+			return -1, ErrNoSpace
+		}
+		// tup may point into this page (e.g. a ReadTuple result).
+		tup = append([]byte(nil), tup...)
+		if _, err := p.Compact(); err != nil {
+			return -1, err
+		}
 	}
 	u := int(p.upper()) - len(tup)
 	copy(p.Buf[u:], tup)
@@ -243,8 +349,15 @@ func (p *Page) ReadTuple(slot int) ([]byte, error) {
 	}
 }
 
+// UpdateTuple replaces the tuple of slot (following a redirect). A tuple that
+// no longer fits in place is stored anew, compacting the page if needed, and
+// slot is redirected to it, so its TID stays valid.
 func (p *Page) UpdateTuple(slot int, newTuple []byte) error {
-	s, err := p.getSlot(slot)
+	target, err := p.resolveSlot(slot)
+	if err != nil {
+		return err
+	}
+	s, err := p.getSlot(target)
 	if err != nil {
 		return err
 	}
@@ -255,7 +368,7 @@ func (p *Page) UpdateTuple(slot int, newTuple []byte) error {
 	// In-place shrink or equal
 	if len(newTuple) <= int(s.Length) {
 		copy(p.Buf[int(s.Offset):], newTuple)
-		return p.putSlot(slot, Slot{
+		return p.putSlot(target, Slot{
 			Offset: s.Offset,
 			Length: uint16(len(newTuple)),
 			Flags:  SlotFlagNormal,
@@ -267,19 +380,145 @@ func (p *Page) UpdateTuple(slot int, newTuple []byte) error {
 	if err != nil {
 		return err
 	}
+	if target != slot {
+		// Keep redirect chains one hop long: drop the previous copy.
+		if err := p.putSlot(target, Slot{Flags: SlotFlagDeleted}); err != nil {
+			return err
+		}
+	}
 	return p.markRedirect(slot, newSlot)
 }
 
+// resolveSlot follows redirects from slot to the slot holding the tuple.
+func (p *Page) resolveSlot(slot int) (int, error) {
+	for visited := 0; ; visited++ {
+		s, err := p.getSlot(slot)
+		if err != nil {
+			return -1, err
+		}
+		if s.Flags != SlotFlagMoved {
+			return slot, nil
+		}
+		if s.Length != 0 || s.Offset == 0 || visited >= p.NumSlots() {
+			return -1, ErrCorruption
+		}
+		slot = int(s.Offset)
+	}
+}
+
+// liveBytes sums the tuple data of normal slots.
+func (p *Page) liveBytes() int {
+	n := 0
+	for i := range p.NumSlots() {
+		s, err := p.getSlot(i)
+		if err == nil && s.Flags == SlotFlagNormal && s.Offset != 0 {
+			n += int(s.Length)
+		}
+	}
+	return n
+}
+
+// reclaimable is the tuple space Compact would give back: deleted tuples,
+// the unused tail of shrunk tuples and the old copies left by redirects.
+func (p *Page) reclaimable() int {
+	r := int(p.special()) - int(p.upper()) - p.liveBytes()
+	if r < 0 {
+		return 0
+	}
+	return r
+}
+
+// Compact packs the data of live tuples against the special area so all
+// free space is contiguous again, and returns the bytes reclaimed.
+// Slot numbers (and therefore TIDs) do not change; deleted and redirect
+// slots stay in the directory. On a corrupt slot the page is left untouched.
+func (p *Page) Compact() (int, error) {
+	n := p.NumSlots()
+	slots := make([]Slot, n)
+	total := 0
+	for i := range n {
+		s, err := p.getSlot(i)
+		if err != nil {
+			return 0, err
+		}
+		if s.Flags == SlotFlagNormal && s.Offset != 0 {
+			start, end := int(s.Offset), int(s.Offset)+int(s.Length)
+			if start < int(p.upper()) || end > int(p.special()) {
+				return 0, ErrCorruption
+			}
+			total += int(s.Length)
+		}
+		slots[i] = s
+	}
+
+	// Tuple regions may overlap their destinations; stage them first.
+	scratch := make([]byte, 0, total)
+	for _, s := range slots {
+		if s.Flags == SlotFlagNormal && s.Offset != 0 {
+			scratch = append(scratch, p.Buf[s.Offset:int(s.Offset)+int(s.Length)]...)
+		}
+	}
+
+	before := int(p.upper())
+	up := int(p.special())
+	pos := 0
+	for i, s := range slots {
+		if s.Flags != SlotFlagNormal || s.Offset == 0 {
+			continue
+		}
+		l := int(s.Length)
+		up -= l
+		copy(p.Buf[up:], scratch[pos:pos+l])
+		pos += l
+		_ = p.putSlot(i, Slot{Offset: uint16(up), Length: s.Length, Flags: SlotFlagNormal})
+	}
+	clear(p.Buf[p.lower():up])
+	p.setUpper(uint16(up))
+	return up - before, nil
+}
+
+// DeleteTuple marks slot deleted; a redirected slot takes its tuple with it.
 func (p *Page) DeleteTuple(slot int) error {
-	_, err := p.getSlot(slot)
+	s, err := p.getSlot(slot)
 	if err != nil {
 		return err
 	}
+	if s.Flags == SlotFlagMoved {
+		target, err := p.resolveSlot(slot)
+		if err != nil {
+			return err
+		}
+		if err := p.putSlot(target, Slot{Flags: SlotFlagDeleted}); err != nil {
+			return err
+		}
+	}
 	return p.putSlot(slot, Slot{Offset: 0, Length: 0, Flags: SlotFlagDeleted})
 }
 
+// RedirectTargets reports, per slot, whether the slot only holds the new copy
+// of a redirected tuple. Scans skip those: the tuple is reached via its
+// original slot.
+func (p *Page) RedirectTargets() []bool {
+	out := make([]bool, p.NumSlots())
+	for i := range out {
+		s, err := p.getSlot(i)
+		if err == nil && s.Flags == SlotFlagMoved && int(s.Offset) < len(out) {
+			out[s.Offset] = true
+		}
+	}
+	return out
+}
+
 // Reset clears page content and re-initializes header.
 // Useful for "rebuild page in-place" (e.g. BTree node rewrite).
+// The version counter survives so a rewrite is still seen as a change, and
+// so do the bytes reserved for encryption.
 func (p *Page) Reset(pageID uint32) {
-	p.init(pageID)
+	v := p.Version()
+	reserve := 0
+	if sp := int(p.special()); !p.IsUninitialized() && sp < offPageChecksum {
+		reserve = offPageChecksum - sp
+	}
+	p.initReserved(pageID, reserve)
+	bx.PutU64At(p.Buf, offPageVersion, v)
 }
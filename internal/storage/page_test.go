@@ -35,7 +35,7 @@ func newPage(t *testing.T) *Page {
 	require.NoError(t, err)
 
 	// default after init page
-	assert.Equal(t, uint16(PageSize), p.upper())
+	assert.Equal(t, uint16(offPageChecksum), p.upper())
 	assert.Equal(t, uint16(HeaderSize), p.lower())
 	assert.Equal(t, 0, p.NumSlots())
 
@@ -50,7 +50,7 @@ func newPage(t *testing.T) *Page {
 	assert.Equal(t, 1, slot)
 
 	// after inserting two tuples
-	assert.Equal(t, uint16(0x1fd6), p.upper())
+	assert.Equal(t, uint16(offPageChecksum-2*21), p.upper())
 	assert.Equal(t, uint16(0x18), p.lower())
 	assert.Equal(t, 2, p.NumSlots())
 
@@ -85,3 +85,205 @@ func TestCRUDTuple(t *testing.T) {
 	require.NoError(t, err)
 	assert.Equal(t, byteData, byteData2)
 }
+
+func TestPageVersion(t *testing.T) {
+	p := newPage(t)
+	require.True(t, p.HasVersion())
+	assert.Equal(t, uint64(0), p.Version())
+	assert.Equal(t, uint64(1), p.BumpVersion())
+	assert.Equal(t, uint64(2), p.BumpVersion())
+
+	// Reset keeps the counter, SetContents keeps the whole trailer.
+	p.Reset(p.PageID())
+	assert.Equal(t, uint64(2), p.Version())
+	assert.Equal(t, 0, p.NumSlots())
+
+	other := newPage(t)
+	require.NoError(t, p.SetContents(other.Buf))
+	assert.Equal(t, uint64(2), p.Version())
+	assert.Equal(t, other.NumSlots(), p.NumSlots())
+
+	// Legacy layout (special right below the LSN) has no room for a version.
+	p.setSpecial(uint16(PageSize - 8))
+	assert.False(t, p.HasVersion())
+	assert.Equal(t, uint64(0), p.BumpVersion())
+}
+
+func TestPageChecksum(t *testing.T) {
+	p := newPage(t)
+	require.True(t, p.HasChecksum())
+	_, err := p.InsertTuple(slot1Data)
+	require.NoError(t, err)
+	require.False(t, p.VerifyChecksum(), "not sealed yet")
+
+	p.SealChecksum()
+	require.True(t, p.VerifyChecksum())
+	p.BumpVersion()
+	require.False(t, p.VerifyChecksum(), "the trailer is covered too")
+	p.SealChecksum()
+
+	p.Buf[PageSize-100] ^= 0x01
+	require.False(t, p.VerifyChecksum())
+	p.Buf[PageSize-100] ^= 0x01
+	require.True(t, p.VerifyChecksum())
+
+	// Pages formatted before checksums existed are never reported.
+	p.setSpecial(uint16(offPageVersion))
+	require.False(t, p.HasChecksum())
+	require.True(t, p.HasVersion())
+	p.Buf[PageSize-100] ^= 0x01
+	require.True(t, p.VerifyChecksum())
+}
+
+func TestLoadPage_ChecksumMismatch(t *testing.T) {
+	fs := LocalFileSet{Dir: t.TempDir(), Base: "data"}
+	sm := NewStorageManager()
+
+	p := newPage(t)
+	_, err := p.InsertTuple(slot1Data)
+	require.NoError(t, err)
+	require.NoError(t, sm.SavePage(fs, 3, *p))
+
+	got, err := sm.LoadPage(fs, 3)
+	require.NoError(t, err)
+	tup, err := got.ReadTuple(0)
+	require.NoError(t, err)
+	require.Equal(t, slot1Data, tup)
+
+	raw := make([]byte, PageSize)
+	require.NoError(t, sm.ReadPage(fs, 3, raw))
+	raw[PageSize-30] ^= 0xff
+	require.NoError(t, sm.WritePage(fs, 3, raw))
+
+	_, err = sm.LoadPage(fs, 3)
+	var corrupted *CorruptedPageError
+	require.ErrorAs(t, err, &corrupted)
+	require.Equal(t, uint32(3), corrupted.PageID)
+	require.Equal(t, "data", corrupted.Base)
+	require.ErrorIs(t, err, ErrPageCorrupted)
+}
+
+func TestCompact(t *testing.T) {
+	p := newPage(t)
+	s2, err := p.InsertTuple([]byte("third"))
+	require.NoError(t, err)
+
+	free := p.FreeSpace()
+	require.NoError(t, p.DeleteTuple(0))
+	require.NoError(t, p.UpdateTuple(1, []byte("short")))
+
+	n, err := p.Compact()
+	require.NoError(t, err)
+	assert.Equal(t, len(slot1Data)+len(slot2Data)-len("short"), n)
+	assert.Equal(t, free+n, p.FreeSpace())
+
+	// Slots keep their numbers.
+	_, err = p.ReadTuple(0)
+	require.ErrorIs(t, err, ErrBadSlot)
+	got, err := p.ReadTuple(1)
+	require.NoError(t, err)
+	assert.Equal(t, []byte("short"), got)
+	got, err = p.ReadTuple(s2)
+	require.NoError(t, err)
+	assert.Equal(t, []byte("third"), got)
+
+	n, err = p.Compact()
+	require.NoError(t, err)
+	assert.Equal(t, 0, n)
+}
+
+func TestInsertCompactsWhenFragmented(t *testing.T) {
+	buf := make([]byte, PageSize)
+	p, err := NewPage(buf, 0)
+	require.NoError(t, err)
+
+	big := make([]byte, 1000)
+	var slots []int
+	for {
+		s, err := p.InsertTuple(big)
+		if err != nil {
+			require.ErrorIs(t, err, ErrNoSpace)
+			break
+		}
+		slots = append(slots, s)
+	}
+	require.Greater(t, len(slots), 2)
+	for _, s := range slots[:2] {
+		require.NoError(t, p.DeleteTuple(s))
+	}
+
+	// Only fits once the two deleted tuples are reclaimed.
+	s, err := p.InsertTuple(make([]byte, 1500))
+	require.NoError(t, err)
+	got, err := p.ReadTuple(s)
+	require.NoError(t, err)
+	assert.Len(t, got, 1500)
+	got, err = p.ReadTuple(slots[2])
+	require.NoError(t, err)
+	assert.Equal(t, big, got)
+}
+
+func TestUpdateRedirectChain(t *testing.T) {
+	p := newPage(t)
+
+	// Grow twice: slot 1 keeps redirecting, one hop, to the latest copy.
+	require.NoError(t, p.UpdateTuple(1, longData))
+	require.NoError(t, p.UpdateTuple(1, append([]byte("x"), longData...)))
+	got, err := p.ReadTuple(1)
+	require.NoError(t, err)
+	assert.Equal(t, append([]byte("x"), longData...), got)
+
+	targets := p.RedirectTargets()
+	live := 0
+	for i := range p.NumSlots() {
+		if ok, _ := p.IsLiveSlot(i); ok && !targets[i] {
+			live++
+		}
+	}
+	assert.Equal(t, 1, live) // slot 0; slot 1 is a redirect
+
+	// Deleting the redirect deletes the tuple it points to.
+	require.NoError(t, p.DeleteTuple(1))
+	for i := 1; i < p.NumSlots(); i++ {
+		_, err := p.ReadTuple(i)
+		require.ErrorIs(t, err, ErrBadSlot)
+	}
+	_, err = p.Compact()
+	require.NoError(t, err)
+	assert.Equal(t, offPageChecksum-len(slot1Data), int(p.upper()))
+}
+
+func TestInspectPage(t *testing.T) {
+	p := newPage(t)
+	for _, tup := range [][]byte{slot1Data, slot2Data} {
+		_, err := p.InsertTuple(tup)
+		require.NoError(t, err)
+	}
+	require.NoError(t, p.DeleteTuple(0))
+	p.BumpVersion()
+	p.SealChecksum()
+
+	info := InspectPage(p.Buf)
+	require.Empty(t, info.Problem)
+	require.False(t, info.Uninitialized)
+	require.True(t, info.ChecksumOK)
+	require.Equal(t, p.Checksum(), info.Checksum)
+	require.Equal(t, uint64(1), info.Version)
+	require.Equal(t, p.FreeSpace(), info.FreeSpace)
+	require.Len(t, info.Slots, 2)
+	require.Equal(t, SlotFlagDeleted, info.Slots[0].Flags)
+	require.Nil(t, info.Slots[0].Tuple)
+	require.Equal(t, slot2Data, info.Slots[1].Tuple)
+
+	p.Buf[PageSize-100] ^= 0x01
+	require.Equal(t, "checksum mismatch", InspectPage(p.Buf).Problem)
+
+	// A broken header is reported, not followed.
+	p.setLower(p.upper() + 1)
+	info = InspectPage(p.Buf)
+	require.Contains(t, info.Problem, ErrPageCorrupted.Error())
+	require.Empty(t, info.Slots)
+
+	require.True(t, InspectPage(make([]byte, PageSize)).Uninitialized)
+	require.Equal(t, ErrWrongSize.Error(), InspectPage(nil).Problem)
+}
@@ -107,6 +107,87 @@ func RenameAllSegments(oldLFS, newLFS LocalFileSet) error {
 	return nil
 }
 
+// ReplaceAllSegments makes dst the file set src is, with n segments: it
+// removes the segments of dst from n on, renames those of src over the
+// others, one atomic rename each, removes what is left of src and fsyncs
+// the directory. Calling it again with the same n after a crash part way
+// finishes the replacement.
+func ReplaceAllSegments(src, dst LocalFileSet, n int) error {
+	old, err := listSegmentsLocal(dst)
+	if err != nil {
+		return err
+	}
+	for _, segNo := range old {
+		if int(segNo) < n {
+			continue
+		}
+		if err := os.Remove(filepath.Join(dst.Dir, SegFileName(dst.Base, segNo))); err != nil &&
+			!errors.Is(err, os.ErrNotExist) {
+			return err
+		}
+	}
+
+	segs, err := listSegmentsLocal(src)
+	if err != nil {
+		return err
+	}
+	for _, segNo := range segs {
+		oldPath := filepath.Join(src.Dir, SegFileName(src.Base, segNo))
+		if int(segNo) >= n {
+			if err := os.Remove(oldPath); err != nil && !errors.Is(err, os.ErrNotExist) {
+				return err
+			}
+			continue
+		}
+		if err := os.Rename(oldPath, filepath.Join(dst.Dir, SegFileName(dst.Base, segNo))); err != nil {
+			return err
+		}
+	}
+	return SyncDirEntries(dst.Dir)
+}
+
+// SyncDirEntries fsyncs dir itself, making the files created, renamed and
+// removed in it durable. Unlike SyncDir it does not fsync the files.
+func SyncDirEntries(dir string) error {
+	d, err := os.Open(dir)
+	if err != nil {
+		return err
+	}
+	serr := d.Sync()
+	cerr := d.Close()
+	if serr != nil {
+		return serr
+	}
+	return cerr
+}
+
+// VerifySegments checks the on-disk shape of lfs: segments must be numbered
+// without gaps, hold a whole number of pages, and all but the last must be full.
+func VerifySegments(lfs LocalFileSet) error {
+	segs, err := listSegmentsLocal(lfs)
+	if err != nil {
+		return err
+	}
+	for i, segNo := range segs {
+		if segNo != int32(i) {
+			return fmt.Errorf("%w: %s: missing segment %d", ErrPageCorrupted, lfs.Base, i)
+		}
+		path := filepath.Join(lfs.Dir, SegFileName(lfs.Base, segNo))
+		info, err := os.Stat(path)
+		if err != nil {
+			return err
+		}
+		size := info.Size()
+		if size%PageSize != 0 {
+			return fmt.Errorf("%w: %s: size %d is not a multiple of %d", ErrPageCorrupted, path, size, PageSize)
+		}
+		if i < len(segs)-1 && size != SegmentSize {
+			return fmt.Errorf("%w: %s: non-final segment is not full", ErrPageCorrupted, path)
+		}
+	}
+	return nil
+}
+
 func FsKeyOf(fs FileSet) (string, LocalFileSet, bool) {
 	lfs, ok := fs.(LocalFileSet)
 	if !ok {
@@ -116,3 +197,32 @@ func FsKeyOf(fs FileSet) (string, LocalFileSet, bool) {
 	dir := filepath.Clean(lfs.Dir)
 	return dir + "|" + lfs.Base, LocalFileSet{Dir: dir, Base: lfs.Base}, true
 }
+
+// SyncDir fsyncs every regular file and directory under dir (recursively),
+// so pages written with WritePage are durable. A missing dir is not an error.
+func SyncDir(dir string) error {
+	return filepath.WalkDir(dir, func(path string, d os.DirEntry, err error) error {
+		if err != nil {
+			if errors.Is(err, os.ErrNotExist) {
+				return nil
+			}
+			return err
+		}
+		if !d.IsDir() && !d.Type().IsRegular() {
+			return nil
+		}
+		f, err := os.Open(path)
+		if err != nil {
+			if errors.Is(err, os.ErrNotExist) {
+				return nil
+			}
+			return err
+		}
+		serr := f.Sync()
+		cerr := f.Close()
+		if serr != nil {
+			return serr
+		}
+		return cerr
+	})
+}
@@ -6,6 +6,10 @@ import (
 	"io"
 	"os"
 	"path/filepath"
+	"runtime"
+	"sync/atomic"
+
+	"github.com/tuannm99/novasql/internal/wal"
 )
 
 var (
@@ -13,6 +17,20 @@ var (
 	ErrPageFull     = errors.New("storage_manager: write would exceed page data length")
 )
 
+// CorruptedPageError is returned by LoadPage when a page's checksum does not
+// match its contents. It matches ErrPageCorrupted with errors.Is.
+type CorruptedPageError struct {
+	Base   string // file set base name ("" if not a LocalFileSet)
+	PageID uint32
+	Reason string
+}
+
+func (e *CorruptedPageError) Error() string {
+	return fmt.Sprintf("storage: page %d of %q is corrupted: %s", e.PageID, e.Base, e.Reason)
+}
+
+func (e *CorruptedPageError) Unwrap() error { return ErrPageCorrupted }
+
 type FileSet interface {
 	OpenSegment(segNo int32) (*os.File, error)
 }
@@ -24,22 +42,281 @@ type LocalFileSet struct {
 	Base string
 }
 
-func (lfs LocalFileSet) OpenSegment(segNo int32) (*os.File, error) {
+// SegmentPath is the path of segment segNo: Base, then Base.1, Base.2, ...
+func (lfs LocalFileSet) SegmentPath(segNo int32) string {
 	name := lfs.Base
 	if segNo > 0 {
 		name = fmt.Sprintf("%s.%d", lfs.Base, segNo)
 	}
-	path := filepath.Join(lfs.Dir, name)
+	return filepath.Join(lfs.Dir, name)
+}
+
+func (lfs LocalFileSet) OpenSegment(segNo int32) (*os.File, error) {
+	path := lfs.SegmentPath(segNo)
 	if err := os.MkdirAll(lfs.Dir, 0o755); err != nil {
 		return nil, err
 	}
 	return os.OpenFile(path, os.O_RDWR|os.O_CREATE, 0o644)
 }
 
-type StorageManager struct{}
+// StorageManager reads and writes pages of segment files. In Classic mode
+// every call opens the segment and uses pread/pwrite; in Mmap mode segments
+// of a LocalFileSet are memory-mapped (see mappings) and other file sets
+// fall back to file I/O. In Memory mode the pages of a LocalFileSet never
+// reach a file (see memStore).
+//
+// Code that removes, renames or checks segments goes through the manager
+// (RemoveSegments, RenameSegments, VerifySegments, RemoveDir) so that it
+// works in every mode.
+//
+// With SetCompression, SavePage stores compressible pages as compressed
+// frames (see compress.go); ReadPage always decodes them. With
+// SetEncryptionKey every page is encrypted on write and decrypted on read
+// (see crypt.go). SetWriteObserver lets a backup follow the pages written
+// while it runs.
+//
+// A manager made with NewStorageManagerWithEngine keeps the pages of every
+// LocalFileSet in its Engine instead (see engine.go).
+type StorageManager struct {
+	maps     *mappings   // nil unless Mmap mode
+	mem      *memStore   // nil unless Memory mode
+	codec    Compression // codec of SavePage
+	crypt    *pageCipher // nil unless encrypted
+	direct   bool        // bypass the page cache, see SetDirectIO
+	readOnly bool        // open segments read-only, see SetReadOnly
+	engine   Engine      // nil unless NewStorageManagerWithEngine
+	observer atomic.Pointer[WriteObserver]
+}
+
+// WriteObserver is told about every page written to a LocalFileSet, and
+// with pageID -1 about every file set removed or renamed (old and new name).
+type WriteObserver func(lfs LocalFileSet, pageID int32)
 
 func NewStorageManager() *StorageManager { return &StorageManager{} }
 
+// NewStorageManagerFor returns a StorageManager for storage.mode. Classic,
+// Mmap and Memory are implemented.
+func NewStorageManagerFor(mode StorageMode) (*StorageManager, error) {
+	switch mode {
+	case Classic:
+		return NewStorageManager(), nil
+	case Mmap:
+		if !mmapSupported {
+			return nil, fmt.Errorf("storage: mode mmap is not supported on %s", runtime.GOOS)
+		}
+		return &StorageManager{maps: newMappings()}, nil
+	case Memory:
+		return &StorageManager{mem: newMemStore()}, nil
+	default:
+		return nil, fmt.Errorf("storage: mode %s is not implemented", mode)
+	}
+}
+
+// SetReadOnly makes the manager open segment files read-only and never
+// create them: a missing segment reads as zero pages, and writes fail with
+// ErrReadOnly. Mmap mode reads with pread instead, since mapping a segment
+// needs it writable. Call it before the first page is read.
+func (sm *StorageManager) SetReadOnly(v bool) {
+	sm.readOnly = v
+	if v {
+		sm.maps = nil
+	}
+}
+
+// ReadOnly reports whether SetReadOnly was called.
+func (sm *StorageManager) ReadOnly() bool { return sm.readOnly }
+
+// Sync msyncs every mapped segment (Mmap mode; no-op otherwise). Files
+// still need an fsync for their metadata, see SyncDir.
+func (sm *StorageManager) Sync() error {
+	if sm.engine != nil {
+		return sm.engine.Sync()
+	}
+	if sm.maps == nil {
+		return nil
+	}
+	return sm.maps.sync()
+}
+
+// Release unmaps the segments of fs, e.g. before its files are removed or
+// renamed. They are mapped again on next use.
+func (sm *StorageManager) Release(fs FileSet) error {
+	lfs, ok := fs.(LocalFileSet)
+	if sm.maps == nil || !ok {
+		return nil
+	}
+	return sm.maps.release(filepath.Join(lfs.Dir, lfs.Base))
+}
+
+// Close unmaps every segment. The manager stays usable. In Memory mode it
+// keeps the pages: they live as long as the manager.
+func (sm *StorageManager) Close() error {
+	if sm.maps == nil {
+		return nil
+	}
+	return sm.maps.release("")
+}
+
+// Writable returns a Classic manager that writes pages as sm does (codec,
+// key, direct I/O) but not read-only, for the code that keeps the files of
+// a read-only handle up to date: a replica applying its primary's log.
+func (sm *StorageManager) Writable() *StorageManager {
+	return &StorageManager{codec: sm.codec, crypt: sm.crypt, direct: sm.direct}
+}
+
+// SetCompression sets the codec SavePage compresses pages with. Pages are
+// only compressed where the freed space can be given back to the
+// filesystem: Classic mode on Linux.
+func (sm *StorageManager) SetCompression(c Compression) {
+	sm.codec = c
+}
+
+func (sm *StorageManager) compresses() bool {
+	return sm.codec != CompressionNone && sm.maps == nil && sm.mem == nil && sm.engine == nil && sm.crypt == nil &&
+		holePunchSupported
+}
+
+// SetEncryptionKey encrypts every page written from now on with the 32-byte
+// AES-256 key and decrypts every page read; nil turns encryption off. Pages
+// formatted by LoadPage keep EncryptionReserve bytes free for it. Encrypted
+// pages are never compressed.
+func (sm *StorageManager) SetEncryptionKey(key []byte) error {
+	if key == nil {
+		sm.crypt = nil
+		return nil
+	}
+	aead, err := newGCM(key)
+	if err != nil {
+		return err
+	}
+	sm.crypt = &pageCipher{aead: aead}
+	return nil
+}
+
+// Encrypted reports whether pages are encrypted.
+func (sm *StorageManager) Encrypted() bool {
+	return sm.crypt != nil
+}
+
+// PageCipher is the cipher for the WAL's page images, nil when pages are not
+// encrypted.
+func (sm *StorageManager) PageCipher() wal.PageCipher {
+	if sm.crypt == nil {
+		return nil
+	}
+	return sm.crypt
+}
+
+// pageReserve is how many bytes new pages keep free before their trailer.
+func (sm *StorageManager) pageReserve() int {
+	if sm.crypt == nil {
+		return 0
+	}
+	return EncryptionReserve
+}
+
+// transforms reports whether pages differ from their bytes on disk, so that
+// code writing its own page layout (overflow) must go through the manager.
+func (sm *StorageManager) transforms() bool {
+	return sm.mem != nil || sm.engine != nil || sm.crypt != nil
+}
+
+// InMemory reports whether the manager keeps pages in memory only.
+func (sm *StorageManager) InMemory() bool {
+	return sm.mem != nil
+}
+
+// RemoveSegments is RemoveAllSegments for any mode.
+func (sm *StorageManager) RemoveSegments(lfs LocalFileSet) error {
+	defer sm.noteWrite(lfs, -1)
+	if sm.mem != nil {
+		sm.mem.remove(lfs)
+		return nil
+	}
+	if sm.engine != nil {
+		return sm.engine.Remove(EngineFile(lfs))
+	}
+	return RemoveAllSegments(lfs)
+}
+
+// RenameSegments is RenameAllSegments for any mode.
+func (sm *StorageManager) RenameSegments(oldLFS, newLFS LocalFileSet) error {
+	defer sm.noteWrite(newLFS, -1)
+	defer sm.noteWrite(oldLFS, -1)
+	if sm.mem != nil {
+		return sm.mem.rename(oldLFS, newLFS)
+	}
+	if sm.engine != nil {
+		return sm.engine.Rename(EngineFile(oldLFS), EngineFile(newLFS))
+	}
+	return RenameAllSegments(oldLFS, newLFS)
+}
+
+// ReplaceSegments is ReplaceAllSegments for any mode, with n the segments
+// of pages pages. In memory and in an engine a file set is one file, which
+// replaces dst at once.
+func (sm *StorageManager) ReplaceSegments(src, dst LocalFileSet, pages uint32) error {
+	defer sm.noteWrite(dst, -1)
+	defer sm.noteWrite(src, -1)
+	switch {
+	case sm.mem != nil:
+		sm.mem.remove(dst)
+		return sm.mem.rename(src, dst)
+	case sm.engine != nil:
+		// src is gone once renamed: only then may dst stay as it is.
+		if n, err := sm.engine.Len(EngineFile(src)); err != nil || (n == 0 && pages > 0) {
+			return err
+		}
+		if err := sm.engine.Remove(EngineFile(dst)); err != nil {
+			return err
+		}
+		return sm.engine.Rename(EngineFile(src), EngineFile(dst))
+	}
+	n := int((pages + MaxPagePerSegment - 1) / MaxPagePerSegment)
+	return ReplaceAllSegments(src, dst, n)
+}
+
+// SetWriteObserver installs fn (nil removes it). fn runs on the writing
+// goroutine after the write.
+func (sm *StorageManager) SetWriteObserver(fn WriteObserver) {
+	if fn == nil {
+		sm.observer.Store(nil)
+		return
+	}
+	sm.observer.Store(&fn)
+}
+
+func (sm *StorageManager) noteWrite(fs FileSet, pageID int32) {
+	lfs, ok := fs.(LocalFileSet)
+	if !ok {
+		return
+	}
+	if fn := sm.observer.Load(); fn != nil {
+		(*fn)(lfs, pageID)
+	}
+}
+
+// VerifySegments is the package-level VerifySegments for any mode; pages in
+// memory or in an engine have no file shape to check.
+func (sm *StorageManager) VerifySegments(lfs LocalFileSet) error {
+	if sm.mem != nil || sm.engine != nil {
+		return nil
+	}
+	return VerifySegments(lfs)
+}
+
+// RemoveDir drops the in-memory or engine pages of every file set under
+// dir, for callers about to remove dir itself (no-op for segment files).
+func (sm *StorageManager) RemoveDir(dir string) error {
+	if sm.engine != nil {
+		return sm.engine.RemoveDir(filepath.Clean(dir))
+	}
+	if sm.mem != nil {
+		sm.mem.removeDir(dir)
+	}
+	return nil
+}
+
 func (sm *StorageManager) pagesPerSegment() int {
 	return SegmentSize / PageSize
 }
@@ -52,21 +329,77 @@ func (sm *StorageManager) locate(pageID int32) (segNo int32, offset int32) {
 	return segNo, offset
 }
 
+// ReadRawPage reads the slot of pageID as stored: encrypted and compressed
+// pages come back as they are, to be copied to another file set.
+func (sm *StorageManager) ReadRawPage(fs FileSet, pageID int32, dst []byte) error {
+	return sm.readPage(fs, pageID, dst)
+}
+
+// ReadPage reads page pageID of fs into dst, decrypting it and decoding it
+// if it is stored compressed. A page that fails to decrypt or a damaged
+// compressed frame is a *CorruptedPageError.
 func (sm *StorageManager) ReadPage(fs FileSet, pageID int32, dst []byte) error {
+	if err := sm.readPage(fs, pageID, dst); err != nil {
+		return err
+	}
+	return sm.decodePage(fs, pageID, dst)
+}
+
+// decodePage turns the stored slot of pageID in dst into the page.
+func (sm *StorageManager) decodePage(fs FileSet, pageID int32, dst []byte) error {
+	if sm.crypt != nil {
+		page, err := sm.crypt.Open(uint32(pageID), dst)
+		if err != nil {
+			return corrupted(fs, uint32(pageID), err.Error())
+		}
+		copy(dst, page)
+	}
+	if !isFrame(dst) {
+		return nil
+	}
+	if reason := decodeFrame(dst); reason != "" {
+		return corrupted(fs, uint32(pageID), reason)
+	}
+	return nil
+}
+
+func corrupted(fs FileSet, pageID uint32, reason string) *CorruptedPageError {
+	e := &CorruptedPageError{PageID: pageID, Reason: reason}
+	if lfs, ok := fs.(LocalFileSet); ok {
+		e.Base = lfs.Base
+	}
+	return e
+}
+
+func (sm *StorageManager) readPage(fs FileSet, pageID int32, dst []byte) error {
 	if pageID < 0 {
 		return fmt.Errorf("pageID must be >= 0, got %d", pageID)
 	}
 	if len(dst) != PageSize {
 		return fmt.Errorf("dst must be exactly %d bytes", PageSize)
 	}
+	if lfs, ok := fs.(LocalFileSet); ok && sm.mem != nil {
+		sm.mem.read(lfs, int64(pageID)*PageSize, dst)
+		return nil
+	}
+	if lfs, ok := fs.(LocalFileSet); ok && sm.engine != nil {
+		return sm.engineRead(lfs, pageID, dst)
+	}
 	segNo, off := sm.locate(pageID)
-	f, err := fs.OpenSegment(segNo)
+	if lfs, ok := fs.(LocalFileSet); ok && sm.maps != nil {
+		return sm.maps.read(lfs.SegmentPath(segNo), int64(off), dst)
+	}
+	f, err := sm.openSegment(fs, segNo)
+	if sm.missingSegment(err) {
+		clear(dst)
+		return nil
+	}
 	if err != nil {
 		return err
 	}
 	defer func() { _ = f.Close() }()
 
-	n, err := f.ReadAt(dst, int64(off))
+	n, err := sm.readAt(f, dst, int64(off))
 	if err != nil && err != io.EOF {
 		return err
 	}
@@ -77,26 +410,64 @@ func (sm *StorageManager) ReadPage(fs FileSet, pageID int32, dst []byte) error {
 }
 
 func (sm *StorageManager) WritePage(fs FileSet, pageID int32, src []byte) error {
+	return sm.writePage(fs, pageID, src, PageSize)
+}
+
+func (sm *StorageManager) writePage(fs FileSet, pageID int32, src []byte, used int) error {
+	if err := sm.writeSlot(fs, pageID, src, used); err != nil {
+		return err
+	}
+	sm.noteWrite(fs, pageID)
+	return nil
+}
+
+// writeSlot writes src, encrypted if a key is set, to the slot of pageID
+// and, in Classic mode, punches the bytes of the slot past used out of the
+// file.
+func (sm *StorageManager) writeSlot(fs FileSet, pageID int32, src []byte, used int) error {
+	if sm.readOnly {
+		return ErrReadOnly
+	}
 	if pageID < 0 {
 		return fmt.Errorf("pageID must be >= 0, got %d", pageID)
 	}
 	if len(src) != PageSize {
 		return fmt.Errorf("src must be exactly %d bytes", PageSize)
 	}
+	if sm.crypt != nil {
+		sealed, err := sm.crypt.Seal(uint32(pageID), src)
+		if err != nil {
+			return fmt.Errorf("page %d: %w", pageID, err)
+		}
+		src = sealed
+	}
+	if lfs, ok := fs.(LocalFileSet); ok && sm.mem != nil {
+		sm.mem.write(lfs, int64(pageID)*PageSize, src)
+		return nil
+	}
+	if lfs, ok := fs.(LocalFileSet); ok && sm.engine != nil {
+		return sm.engineWrite(lfs, pageID, src)
+	}
 	segNo, off := sm.locate(pageID)
-	f, err := fs.OpenSegment(segNo)
+	if lfs, ok := fs.(LocalFileSet); ok && sm.maps != nil {
+		return sm.maps.write(lfs.SegmentPath(segNo), int64(off), src)
+	}
+	f, err := sm.openSegment(fs, segNo)
 	if err != nil {
 		return err
 	}
 	defer func() { _ = f.Close() }()
 
-	n, err := f.WriteAt(src, int64(off))
+	n, err := sm.writeAt(f, src, int64(off))
 	if err != nil {
 		return err
 	}
 	if n != PageSize {
 		return io.ErrShortWrite
 	}
+	if used < PageSize {
+		return punchHole(f, int64(off+int32(used)), int64(PageSize-used))
+	}
 	return nil
 }
 
@@ -105,9 +476,26 @@ func (sm *StorageManager) LoadPage(fs FileSet, pageID uint32) (*Page, error) {
 	if err := sm.ReadPage(fs, int32(pageID), buf); err != nil {
 		return nil, err
 	}
+	return sm.framePage(fs, pageID, buf)
+}
+
+// NewPage formats page pageID as LoadPage does a page never written.
+func (sm *StorageManager) NewPage(pageID uint32) *Page {
+	p := &Page{Buf: make([]byte, PageSize)}
+	p.initReserved(pageID, sm.pageReserve())
+	return p
+}
+
+// framePage checks buf, page pageID as read, and wraps it; a page never
+// written is initialized.
+func (sm *StorageManager) framePage(fs FileSet, pageID uint32, buf []byte) (*Page, error) {
 	p := &Page{Buf: buf}
 	if p.IsUninitialized() {
-		p.init(pageID)
+		p.initReserved(pageID, sm.pageReserve())
+		return p, nil
+	}
+	if !p.VerifyChecksum() {
+		return nil, corrupted(fs, pageID, "checksum mismatch")
 	}
 	return p, nil
 }
@@ -116,20 +504,35 @@ func (sm *StorageManager) SavePage(fs FileSet, pageID uint32, p Page) error {
 	if len(p.Buf) != PageSize {
 		return fmt.Errorf("page buffer must be %d bytes", PageSize)
 	}
+	p.SealChecksum()
+	if sm.compresses() {
+		if frame, used := compressPage(sm.codec, p.Buf); frame != nil {
+			return sm.writePage(fs, int32(pageID), frame, used)
+		}
+	}
 	return sm.WritePage(fs, int32(pageID), p.Buf)
 }
 
 func (sm *StorageManager) CountPages(fs FileSet) (uint32, error) {
-	if lfs, ok := fs.(LocalFileSet); ok {
-		return countPagesLocalFileSet(lfs)
+	lfs, ok := fs.(LocalFileSet)
+	if !ok {
+		return 0, nil
 	}
-	return 0, nil
+	if sm.mem != nil {
+		return sm.mem.count(lfs), nil
+	}
+	if sm.engine != nil {
+		return sm.engine.Len(EngineFile(lfs))
+	}
+	if !sm.readOnly {
+		if err := os.MkdirAll(lfs.Dir, 0o755); err != nil {
+			return 0, err
+		}
+	}
+	return countPagesLocalFileSet(lfs)
 }
 
 func countPagesLocalFileSet(lfs LocalFileSet) (uint32, error) {
-	if err := os.MkdirAll(lfs.Dir, 0o755); err != nil {
-		return 0, err
-	}
 
 	segs, err := listSegmentsLocal(lfs)
 	if err != nil {
@@ -1,6 +1,7 @@
 package storage
 
 import (
+	"path/filepath"
 	"testing"
 
 	"github.com/stretchr/testify/assert"
@@ -17,3 +18,60 @@ func TestStorageManager(t *testing.T) {
 	assert.NotNil(t, pg)
 	assert.IsType(t, &Page{}, pg)
 }
+
+func TestStorageManager_ReadOnly(t *testing.T) {
+	fs := LocalFileSet{Dir: t.TempDir(), Base: "segment"}
+	rw := NewStorageManager()
+	page := make([]byte, PageSize)
+	page[0] = 7
+	require.NoError(t, rw.WritePage(fs, 0, page))
+
+	sm := NewStorageManager()
+	sm.SetReadOnly(true)
+	require.True(t, sm.ReadOnly())
+	buf := make([]byte, PageSize)
+	require.NoError(t, sm.ReadPage(fs, 0, buf))
+	require.Equal(t, page, buf)
+
+	// Missing files read as zero pages and are not created.
+	missing := LocalFileSet{Dir: filepath.Join(fs.Dir, "none"), Base: "segment"}
+	require.NoError(t, sm.ReadPage(missing, 3, buf))
+	require.Equal(t, make([]byte, PageSize), buf)
+	n, err := sm.CountPages(missing)
+	require.NoError(t, err)
+	require.Zero(t, n)
+	require.NoDirExists(t, missing.Dir)
+
+	require.ErrorIs(t, sm.WritePage(fs, 1, page), ErrReadOnly)
+	require.ErrorIs(t, sm.SavePages(fs, 1, []Page{{Buf: page}}), ErrReadOnly)
+}
+
+
+func BenchmarkStorageManager_WritePage(b *testing.B) {
+	fs := LocalFileSet{Dir: b.TempDir(), Base: "segment"}
+	sm := NewStorageManager()
+	page := make([]byte, PageSize)
+	const pages = 256
+
+	b.SetBytes(PageSize)
+	for i := range b.N {
+		page[0] = byte(i)
+		require.NoError(b, sm.WritePage(fs, int32(i%pages), page))
+	}
+}
+
+func BenchmarkStorageManager_ReadPage(b *testing.B) {
+	fs := LocalFileSet{Dir: b.TempDir(), Base: "segment"}
+	sm := NewStorageManager()
+	page := make([]byte, PageSize)
+	const pages = 256
+	for i := range int32(pages) {
+		require.NoError(b, sm.WritePage(fs, i, page))
+	}
+
+	b.SetBytes(PageSize)
+	b.ResetTimer()
+	for i := range b.N {
+		require.NoError(b, sm.ReadPage(fs, int32(i%pages), page))
+	}
+}
@@ -0,0 +1,296 @@
+// Package storagetest provides a storage.Engine for tests of what happens
+// when storage fails: FaultEngine keeps pages in memory, fails a chosen I/O
+// of them or of the log files it opens, and then behaves like a machine
+// that lost power.
+package storagetest
+
+import (
+	"errors"
+	"fmt"
+	"maps"
+	"os"
+	"path/filepath"
+	"slices"
+	"strings"
+	"sync"
+
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+// Fault is what happens to the I/O a FaultEngine fails.
+type Fault int
+
+const (
+	// FailIO fails the I/O without doing any of it.
+	FailIO Fault = iota
+	// ShortWrite stores the first half of the page being written; the rest
+	// of the page keeps its old bytes. A read or sync fails as with FailIO.
+	ShortWrite
+	// TornWrite stores every other sector of the page being written, as a
+	// disk that loses power halfway through a write may. A read or sync
+	// fails as with FailIO.
+	TornWrite
+)
+
+func (f Fault) String() string {
+	switch f {
+	case FailIO:
+		return "fail"
+	case ShortWrite:
+		return "short write"
+	case TornWrite:
+		return "torn write"
+	}
+	return fmt.Sprintf("Fault(%d)", int(f))
+}
+
+// SectorSize is the unit a TornWrite keeps or loses.
+const SectorSize = 512
+
+var (
+	ErrInjected = errors.New("storagetest: injected fault")
+	ErrCrashed  = errors.New("storagetest: engine crashed")
+)
+
+// FaultEngine is a storage.Engine keeping pages in memory, written pages
+// becoming durable at Sync. It is a storage.FileOpener too, so a database
+// on it writes its WAL and transaction log through it (see OpenFile).
+// InjectAt arms a fault at the nth I/O to come (ReadPage, WritePage or
+// Sync, or a Write, WriteAt or Sync of a log file); at that I/O the engine
+// crashes: the I/O fails as the Fault says and every call after it fails
+// with ErrCrashed until Restart, which drops whatever was written since the
+// last Sync as a power loss does. A partial write of the faulted I/O is
+// durable, like a torn sector on disk. Removes and renames are durable at
+// once.
+type FaultEngine struct {
+	mu      sync.Mutex
+	files   map[string][][]byte // what reads see
+	durable map[string][][]byte // as of the last Sync
+	logs    map[string][]byte   // log files opened by OpenFile, as of their last Sync
+
+	ops     int // I/Os so far
+	at      int // the I/O to fail, 0 for none
+	fault   Fault
+	crashed bool
+}
+
+// NewFaultEngine returns an empty engine with no fault armed.
+func NewFaultEngine() *FaultEngine {
+	return &FaultEngine{
+		files:   make(map[string][][]byte),
+		durable: make(map[string][][]byte),
+		logs:    make(map[string][]byte),
+	}
+}
+
+// InjectAt arms f at the nth I/O from now, n >= 1; n <= 0 disarms it.
+func (e *FaultEngine) InjectAt(n int, f Fault) {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	e.at, e.fault = 0, f
+	if n > 0 {
+		e.at = e.ops + n
+	}
+}
+
+// Ops is the number of I/Os done so far, the faulted one included.
+func (e *FaultEngine) Ops() int {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	return e.ops
+}
+
+// Crashed reports whether the armed fault has happened since the last
+// Restart.
+func (e *FaultEngine) Crashed() bool {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	return e.crashed
+}
+
+// Crash crashes the engine as a fault does, without failing an I/O.
+func (e *FaultEngine) Crash() {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	e.crashed, e.at = true, 0
+}
+
+// Restart brings a crashed engine back with its durable pages only, and
+// puts back in each log file what it held at its last Sync. A running engine
+// restarts too, losing its unsynced writes. Log files should be closed.
+func (e *FaultEngine) Restart() {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	e.files = cloneFiles(e.durable)
+	for name, data := range e.logs {
+		_ = os.WriteFile(name, data, 0o644) // fails only if its directory was removed
+	}
+	e.crashed, e.at = false, 0
+}
+
+func cloneFiles(files map[string][][]byte) map[string][][]byte {
+	out := make(map[string][][]byte, len(files))
+	for name, pages := range files {
+		out[name] = slices.Clone(pages) // pages are replaced, never changed
+	}
+	return out
+}
+
+// io counts an I/O and reports whether it is the faulted one. Caller holds
+// mu.
+func (e *FaultEngine) io() (bool, error) {
+	if e.crashed {
+		return false, ErrCrashed
+	}
+	e.ops++
+	if e.at == 0 || e.ops != e.at {
+		return false, nil
+	}
+	e.crashed, e.at = true, 0
+	return true, nil
+}
+
+// check fails the calls that are not I/Os once crashed. Caller holds mu.
+func (e *FaultEngine) check() error {
+	if e.crashed {
+		return ErrCrashed
+	}
+	return nil
+}
+
+func (e *FaultEngine) ReadPage(file string, pageID uint32, dst []byte) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	faulted, err := e.io()
+	if err != nil {
+		return err
+	}
+	if faulted {
+		return fmt.Errorf("%w: %s reading page %d of %s", ErrInjected, e.fault, pageID, file)
+	}
+	clear(dst)
+	if pages := e.files[file]; pageID < uint32(len(pages)) {
+		copy(dst, pages[pageID])
+	}
+	return nil
+}
+
+func (e *FaultEngine) WritePage(file string, pageID uint32, src []byte) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	faulted, err := e.io()
+	if err != nil {
+		return err
+	}
+	pages := e.files[file]
+	if pageID >= uint32(len(pages)) {
+		return fmt.Errorf("storagetest: write of page %d past the end of %s", pageID, file)
+	}
+	if !faulted {
+		pages[pageID] = append([]byte(nil), src...)
+		return nil
+	}
+	if e.fault == FailIO {
+		return fmt.Errorf("%w: %s writing page %d of %s", ErrInjected, e.fault, pageID, file)
+	}
+
+	// What reaches the disk is the old page with part of the new one.
+	old := e.durable[file]
+	page := make([]byte, storage.PageSize)
+	if pageID < uint32(len(old)) {
+		copy(page, old[pageID])
+	}
+	if e.fault == ShortWrite {
+		copy(page[:storage.PageSize/2], src)
+	} else {
+		for off := 0; off < storage.PageSize; off += 2 * SectorSize {
+			copy(page[off:min(off+SectorSize, storage.PageSize)], src[off:])
+		}
+	}
+	for uint32(len(old)) <= pageID {
+		old = append(old, make([]byte, storage.PageSize))
+	}
+	old[pageID] = page
+	e.durable[file] = old
+	return fmt.Errorf("%w: %s of page %d of %s", ErrInjected, e.fault, pageID, file)
+}
+
+func (e *FaultEngine) Allocate(file string, n uint32) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	if err := e.check(); err != nil {
+		return err
+	}
+	for uint32(len(e.files[file])) < n {
+		e.files[file] = append(e.files[file], make([]byte, storage.PageSize))
+	}
+	return nil
+}
+
+func (e *FaultEngine) Len(file string) (uint32, error) {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	if err := e.check(); err != nil {
+		return 0, err
+	}
+	return uint32(len(e.files[file])), nil
+}
+
+func (e *FaultEngine) Sync() error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	faulted, err := e.io()
+	if err != nil {
+		return err
+	}
+	if faulted {
+		return fmt.Errorf("%w: %s syncing", ErrInjected, e.fault)
+	}
+	e.durable = cloneFiles(e.files)
+	return nil
+}
+
+func (e *FaultEngine) Remove(file string) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	if err := e.check(); err != nil {
+		return err
+	}
+	delete(e.files, file)
+	delete(e.durable, file)
+	return nil
+}
+
+func (e *FaultEngine) Rename(oldFile, newFile string) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	if err := e.check(); err != nil {
+		return err
+	}
+	for _, files := range []map[string][][]byte{e.files, e.durable} {
+		if pages, ok := files[oldFile]; ok {
+			files[newFile] = pages
+			delete(files, oldFile)
+		}
+	}
+	return nil
+}
+
+func (e *FaultEngine) RemoveDir(dir string) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	if err := e.check(); err != nil {
+		return err
+	}
+	under := func(file string, _ [][]byte) bool {
+		return strings.HasPrefix(file, dir+string(filepath.Separator))
+	}
+	maps.DeleteFunc(e.files, under)
+	maps.DeleteFunc(e.durable, under)
+	return nil
+}
+
+var (
+	_ storage.Engine     = (*FaultEngine)(nil)
+	_ storage.FileOpener = (*FaultEngine)(nil)
+)
@@ -0,0 +1,149 @@
+package storagetest
+
+import (
+	"errors"
+	"fmt"
+	"os"
+
+	"github.com/tuannm99/novasql/internal/storage"
+)
+
+// OpenFile opens name as os.OpenFile does, with its I/O going through the
+// engine: a Write, WriteAt or Sync of the file is an I/O that InjectAt can
+// fail, and Restart puts back what the file held at its last Sync. Truncate
+// is durable at once. The file stays an OS file, so readers that open it at
+// its path (WAL recovery, backups) see what it holds.
+func (e *FaultEngine) OpenFile(name string, flag int, perm os.FileMode) (storage.File, error) {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	if err := e.check(); err != nil {
+		return nil, err
+	}
+	_, statErr := os.Stat(name)
+	f, err := os.OpenFile(name, flag, perm)
+	if err != nil {
+		return nil, err
+	}
+	// A file removed since it was last opened, with its database, starts
+	// over.
+	if _, ok := e.logs[name]; !ok || errors.Is(statErr, os.ErrNotExist) {
+		data, err := os.ReadFile(name)
+		if err != nil {
+			_ = f.Close()
+			return nil, err
+		}
+		e.logs[name] = data
+	}
+	return &faultFile{e: e, f: f, name: name}, nil
+}
+
+// faultFile is a log file opened by FaultEngine.OpenFile.
+type faultFile struct {
+	e    *FaultEngine
+	f    *os.File
+	name string
+}
+
+func (f *faultFile) Write(b []byte) (int, error) { return f.write(b, -1) }
+
+func (f *faultFile) WriteAt(b []byte, off int64) (int, error) { return f.write(b, off) }
+
+// write writes b at off, or appends it for off -1, as an I/O of the engine.
+func (f *faultFile) write(b []byte, off int64) (int, error) {
+	e := f.e
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	faulted, err := e.io()
+	if err != nil {
+		return 0, err
+	}
+	if !faulted {
+		if off < 0 {
+			return f.f.Write(b)
+		}
+		return f.f.WriteAt(b, off)
+	}
+	if e.fault != FailIO {
+		if off < 0 {
+			info, err := f.f.Stat()
+			if err != nil {
+				return 0, err
+			}
+			off = info.Size()
+		}
+		e.logs[f.name] = tear(e.logs[f.name], b, off, e.fault)
+	}
+	return 0, fmt.Errorf("%w: %s of %d bytes at %d of %s", ErrInjected, e.fault, len(b), off, f.name)
+}
+
+// tear returns data, the durable bytes of a file, with what fault lets reach
+// the disk of a write of b at off: the first half of b for a ShortWrite,
+// every other sector of it for a TornWrite. Bytes between the end of data
+// and off read as zeros.
+func tear(data, b []byte, off int64, fault Fault) []byte {
+	n := len(b)
+	if fault == ShortWrite {
+		n /= 2
+	}
+	if end := off + int64(n); end > int64(len(data)) {
+		data = append(data, make([]byte, end-int64(len(data)))...)
+	}
+	if fault == ShortWrite {
+		copy(data[off:], b[:n])
+		return data
+	}
+	for i := 0; i < n; i += 2 * SectorSize {
+		copy(data[off+int64(i):], b[i:min(i+SectorSize, n)])
+	}
+	return data
+}
+
+// Sync makes what the file holds durable. It does not fsync: the engine
+// only models a power loss.
+func (f *faultFile) Sync() error {
+	e := f.e
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	faulted, err := e.io()
+	if err != nil {
+		return err
+	}
+	if faulted {
+		return fmt.Errorf("%w: %s syncing %s", ErrInjected, e.fault, f.name)
+	}
+	data, err := os.ReadFile(f.name)
+	if err != nil {
+		return err
+	}
+	e.logs[f.name] = data
+	return nil
+}
+
+func (f *faultFile) Truncate(size int64) error {
+	e := f.e
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	if err := e.check(); err != nil {
+		return err
+	}
+	if err := f.f.Truncate(size); err != nil {
+		return err
+	}
+	if data := e.logs[f.name]; int64(len(data)) > size {
+		e.logs[f.name] = data[:size]
+	}
+	return nil
+}
+
+func (f *faultFile) Stat() (os.FileInfo, error) {
+	e := f.e
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	if err := e.check(); err != nil {
+		return nil, err
+	}
+	return f.f.Stat()
+}
+
+// Close closes the file, crashed or not.
+func (f *faultFile) Close() error { return f.f.Close() }
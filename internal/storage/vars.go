@@ -39,6 +39,8 @@ const (
 	Classic                           // postgres
 	Document                          // mongodb
 	WideColumn                        // cassandra
+	Mmap                              // classic, with memory-mapped segment files
+	Memory                            // pages kept in memory, nothing persisted
 )
 
 func (s StorageMode) String() string {
@@ -51,6 +53,10 @@ func (s StorageMode) String() string {
 		return "document"
 	case WideColumn:
 		return "wide_column"
+	case Mmap:
+		return "mmap"
+	case Memory:
+		return "memory"
 	default:
 		return "unknown"
 	}
@@ -66,6 +72,10 @@ func GetStorageMode(s string) (StorageMode, error) {
 		return Document, nil
 	case "wide_column":
 		return WideColumn, nil
+	case "mmap":
+		return Mmap, nil
+	case "memory":
+		return Memory, nil
 	default:
 		return 0, fmt.Errorf("invalid storage mode: %s", s)
 	}
@@ -79,4 +89,5 @@ var (
 	ErrStorageIO           = errors.New("storage: I/O error")
 	ErrInvalidOperation    = errors.New("storage: invalid operation")
 	ErrNoFreeBuffer        = errors.New("storage: no free buffer available")
+	ErrReadOnly            = errors.New("storage: files are opened read-only")
 )
@@ -0,0 +1,76 @@
+package wal
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestManager_ArchiveReplay(t *testing.T) {
+	archive := filepath.Join(t.TempDir(), "archive")
+	m, err := Open(t.TempDir())
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = m.Close() })
+	m.SetArchiveDir(archive)
+
+	_, err = m.AppendPageImage("d", "rel", 0, pageWith(1))
+	require.NoError(t, err)
+	_, err = m.AppendPageImage("d", "rel", 1, pageWith(2))
+	require.NoError(t, err)
+	require.NoError(t, m.Checkpoint())
+	// Nothing new: no segment.
+	require.NoError(t, m.Checkpoint())
+	between := time.Now()
+
+	_, err = m.AppendTx([]PageImage{
+		{Dir: "d", Base: "rel", PageID: 0, Page: pageWith(3)},
+		{Dir: "d", Base: "rel", PageID: 2, Page: pageWith(4)},
+	})
+	require.NoError(t, err)
+	_, err = m.AppendPageImage("d", "rel", 1, pageWith(5))
+	require.NoError(t, err)
+	require.Equal(t, uint64(7), m.LastLSN())
+	require.NoError(t, m.Checkpoint())
+
+	entries, err := os.ReadDir(archive)
+	require.NoError(t, err)
+	require.Len(t, entries, 2)
+	require.Equal(t, "0000000000000001-0000000000000002.wal", entries[0].Name())
+	require.Equal(t, "0000000000000003-0000000000000007.wal", entries[1].Name())
+
+	all := []redo{
+		{dir: "d", base: "rel", pageID: 0, first: 1},
+		{dir: "d", base: "rel", pageID: 1, first: 2},
+		{dir: "d", base: "rel", pageID: 0, first: 3},
+		{dir: "d", base: "rel", pageID: 2, first: 4},
+		{dir: "d", base: "rel", pageID: 1, first: 5},
+	}
+	w := &recordingWriter{}
+	last, err := ReplayArchive(archive, 0, Target{}, w, nil)
+	require.NoError(t, err)
+	require.Equal(t, uint64(7), last)
+	require.Equal(t, all, w.pages)
+
+	// A group is replayed whole or not at all.
+	w = &recordingWriter{}
+	last, err = ReplayArchive(archive, 0, Target{LSN: 5}, w, nil)
+	require.NoError(t, err)
+	require.Equal(t, uint64(2), last)
+	require.Equal(t, all[:2], w.pages)
+
+	w = &recordingWriter{}
+	last, err = ReplayArchive(archive, 0, Target{Time: between}, w, nil)
+	require.NoError(t, err)
+	require.Equal(t, uint64(2), last)
+	require.Equal(t, all[:2], w.pages)
+
+	// Records up to after are already in the base backup.
+	w = &recordingWriter{}
+	last, err = ReplayArchive(archive, 2, Target{LSN: 6}, w, nil)
+	require.NoError(t, err)
+	require.Equal(t, uint64(6), last)
+	require.Equal(t, all[2:4], w.pages)
+}
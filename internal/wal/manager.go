@@ -3,12 +3,17 @@ package wal
 import (
 	"bufio"
 	"errors"
+	"fmt"
 	"hash/crc32"
 	"io"
+	"log/slog"
 	"os"
 	"path/filepath"
+	"strings"
 	"sync"
+	"time"
 
+	"github.com/tuannm99/novasql/internal/metrics"
 	"github.com/tuannm99/novasql/pkg/bx"
 )
 
@@ -18,13 +23,59 @@ var (
 	ErrBadRecord = errors.New("wal: bad record")
 	ErrShortRead = errors.New("wal: short read")
 	ErrNoWALFile = errors.New("wal: wal file not found")
+	ErrSyncMode  = errors.New("wal: unknown sync mode")
 )
 
+// SyncMode is how hard the log works to make commits durable.
+type SyncMode uint8
+
+const (
+	// SyncFull makes AppendTx return only after its commit record is on
+	// disk. Concurrent commits share fsyncs (group commit).
+	SyncFull SyncMode = iota
+	// SyncNormal does not wait for the disk on commit; the log is fsynced
+	// only before data pages are written (Flush) and at checkpoints. A crash
+	// loses the last commits but never leaves torn or half-applied groups.
+	SyncNormal
+	// SyncOff never fsyncs on commit or Flush and leaves durability to the
+	// OS. A crash of the machine (not just the process) can corrupt data.
+	SyncOff
+)
+
+// ParseSyncMode parses storage.sync_mode; "" is SyncFull.
+func ParseSyncMode(s string) (SyncMode, error) {
+	switch strings.ToLower(s) {
+	case "", "full":
+		return SyncFull, nil
+	case "normal":
+		return SyncNormal, nil
+	case "off":
+		return SyncOff, nil
+	default:
+		return 0, fmt.Errorf("%w %q (want off, normal or full)", ErrSyncMode, s)
+	}
+}
+
+func (m SyncMode) String() string {
+	switch m {
+	case SyncNormal:
+		return "normal"
+	case SyncOff:
+		return "off"
+	default:
+		return "full"
+	}
+}
+
 const (
 	magicU32   uint32 = 0x4C41574E // "NWAL"
 	versionU16        = 1
 
-	recPageImage uint8 = 1
+	recPageImage  uint8 = 1
+	recCheckpoint uint8 = 2 // log truncated; carries the LSN reached so far
+	recTxBegin    uint8 = 3 // starts a transaction group (lsn = tx id)
+	recTxPage     uint8 = 4 // page image that belongs to the open group
+	recTxCommit   uint8 = 5 // ends the group; its pages are replayed only now
 
 	// Keep WAL independent from storage package.
 	PageSize = 8192
@@ -35,20 +86,66 @@ type PageWriter interface {
 	WritePage(dir, base string, pageID uint32, pageBytes []byte) error
 }
 
+// PageCipher encrypts the page images of the log. Sealed images keep the
+// page size.
+type PageCipher interface {
+	Seal(pageID uint32, page []byte) ([]byte, error)
+	Open(pageID uint32, sealed []byte) ([]byte, error)
+}
+
+// File is the log file as Manager writes it; *os.File is one.
+type File interface {
+	io.Writer
+	Sync() error
+	Truncate(size int64) error
+	Stat() (os.FileInfo, error)
+	Close() error
+}
+
+// OpenFunc opens the log file as os.OpenFile does.
+type OpenFunc func(name string, flag int, perm os.FileMode) (File, error)
+
 type Manager struct {
 	mu      sync.Mutex
-	f       *os.File
+	f       File
 	path    string
 	lsn     uint64
 	flushed uint64
+	mode    SyncMode
+	cipher  PageCipher // nil = images are logged as they are
+
+	// archiveDir receives the log before each checkpoint truncates it ("" =
+	// no archiving); base is the LSN of the checkpoint the log starts at.
+	archiveDir string
+	base       uint64
+
+	// syncMu serializes fsyncs. Writers append under mu while a sync runs
+	// and queue on syncMu; the next sync covers all of them at once.
+	syncMu sync.Mutex
+	syncs  uint64
+
+	metrics *metrics.Registry // bytes appended and fsyncs; see SetMetrics
 }
 
 func Open(dir string) (*Manager, error) {
+	return OpenWith(dir, func(name string, flag int, perm os.FileMode) (File, error) {
+		f, err := os.OpenFile(name, flag, perm)
+		if err != nil {
+			return nil, err
+		}
+		return f, nil
+	})
+}
+
+// OpenWith is Open with the log file opened by open, such as a storage
+// engine that injects faults. Recovery and archiving still read the file at
+// its path.
+func OpenWith(dir string, open OpenFunc) (*Manager, error) {
 	if err := os.MkdirAll(dir, 0o755); err != nil {
 		return nil, err
 	}
 	path := filepath.Join(dir, "wal.log")
-	f, err := os.OpenFile(path, os.O_RDWR|os.O_CREATE|os.O_APPEND, 0o644)
+	f, err := open(path, os.O_RDWR|os.O_CREATE|os.O_APPEND, 0o644)
 	if err != nil {
 		return nil, err
 	}
@@ -57,16 +154,84 @@ func Open(dir string) (*Manager, error) {
 	return m, nil
 }
 
+// SetMetrics makes the log count the bytes it appends and its fsyncs into
+// r. Call it before the log is used.
+func (m *Manager) SetMetrics(r *metrics.Registry) {
+	if m == nil {
+		return
+	}
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	m.metrics = r
+}
+
+// SetSyncMode changes how commits and Flush reach the disk.
+func (m *Manager) SetSyncMode(mode SyncMode) {
+	if m == nil {
+		return
+	}
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	m.mode = mode
+}
+
+// SetPageCipher makes the log store page images sealed by c; Recover opens
+// them again before handing them to the PageWriter.
+func (m *Manager) SetPageCipher(c PageCipher) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	m.cipher = c
+}
+
+func (m *Manager) seal(pageID uint32, page []byte) ([]byte, error) {
+	if m.cipher == nil {
+		return page, nil
+	}
+	return m.cipher.Seal(pageID, page)
+}
+
+// SetArchiveDir makes every checkpoint first copy the log it truncates into
+// dir, as a segment that ReplayArchive can replay; "" stops archiving.
+func (m *Manager) SetArchiveDir(dir string) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	m.archiveDir = dir
+}
+
+// LastLSN is the LSN of the last record logged.
+func (m *Manager) LastLSN() uint64 {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	return m.lsn
+}
+
+// SyncCount is the number of fsyncs issued for commits and Flush so far.
+func (m *Manager) SyncCount() uint64 {
+	m.syncMu.Lock()
+	defer m.syncMu.Unlock()
+	return m.syncs
+}
+
+// Close closes the log, first fsyncing what was appended since the last
+// sync unless the mode is SyncOff.
 func (m *Manager) Close() error {
 	if m == nil {
 		return nil
 	}
+	m.syncMu.Lock()
+	defer m.syncMu.Unlock()
 	m.mu.Lock()
 	defer m.mu.Unlock()
 	if m.f == nil {
 		return nil
 	}
-	err := m.f.Close()
+	var err error
+	if m.mode != SyncOff && m.flushed < m.lsn {
+		err = m.f.Sync()
+	}
+	if cerr := m.f.Close(); err == nil {
+		err = cerr
+	}
 	m.f = nil
 	return err
 }
@@ -85,17 +250,165 @@ func (m *Manager) AppendPageImage(dir, base string, pageID uint32, pageBytes []b
 		return 0, ErrNoWALFile
 	}
 
+	sealed, err := m.seal(pageID, pageBytes)
+	if err != nil {
+		return 0, err
+	}
 	m.lsn++
 	lsn := m.lsn
 
-	dirB := []byte(filepath.Clean(dir))
+	buf, err := encodeRecord(recPageImage, lsn, filepath.Clean(dir), base, pageID, sealed, time.Now())
+	if err != nil {
+		return 0, err
+	}
+	if _, err := m.f.Write(buf); err != nil {
+		return 0, err
+	}
+	m.metrics.Add(metrics.WALBytes, uint64(len(buf)))
+	return lsn, nil
+}
+
+// PageImage is one page of a transaction group.
+type PageImage struct {
+	Dir    string
+	Base   string
+	PageID uint32
+	Page   []byte
+}
+
+// AppendTx logs pages as one atomic group (begin, images, commit) with a
+// single write. Recovery replays the images only if the commit record made it
+// to disk. With SyncFull it waits for that, sharing the fsync with commits
+// that arrive meanwhile. It returns the LSN of the commit record.
+func (m *Manager) AppendTx(pages []PageImage) (uint64, error) {
+	lsn, err := m.LogTx(pages)
+	if err != nil {
+		return 0, err
+	}
+	if err := m.WaitCommit(lsn); err != nil {
+		return 0, err
+	}
+	return lsn, nil
+}
+
+// LogTx writes pages as AppendTx does but returns without waiting for the
+// disk; WaitCommit then waits for the group. A caller that logs under its
+// own locks waits after releasing them, so that the commits queued behind
+// them share its fsync.
+func (m *Manager) LogTx(pages []PageImage) (uint64, error) {
+	for _, p := range pages {
+		if len(p.Page) != PageSize {
+			return 0, ErrBadRecord
+		}
+	}
+
+	m.mu.Lock()
+	defer m.mu.Unlock()
+
+	if m.f == nil {
+		return 0, ErrNoWALFile
+	}
+
+	lsn, now := m.lsn, time.Now()
+	var buf []byte
+	appendRec := func(typ uint8, dir, base string, pageID uint32, page []byte) error {
+		lsn++
+		rec, err := encodeRecord(typ, lsn, dir, base, pageID, page, now)
+		if err != nil {
+			return err
+		}
+		buf = append(buf, rec...)
+		return nil
+	}
+
+	if err := appendRec(recTxBegin, "", "", 0, nil); err != nil {
+		return 0, err
+	}
+	for _, p := range pages {
+		sealed, err := m.seal(p.PageID, p.Page)
+		if err != nil {
+			return 0, err
+		}
+		if err := appendRec(recTxPage, filepath.Clean(p.Dir), p.Base, p.PageID, sealed); err != nil {
+			return 0, err
+		}
+	}
+	if err := appendRec(recTxCommit, "", "", 0, nil); err != nil {
+		return 0, err
+	}
+
+	if _, err := m.f.Write(buf); err != nil {
+		return 0, err
+	}
+	m.metrics.Add(metrics.WALBytes, uint64(len(buf)))
+	m.lsn = lsn
+	return lsn, nil
+}
+
+// WaitCommit returns once the group LogTx logged up to lsn is as durable as
+// the sync mode makes commits: on disk with SyncFull, at once otherwise.
+func (m *Manager) WaitCommit(lsn uint64) error {
+	m.mu.Lock()
+	mode := m.mode
+	m.mu.Unlock()
+	if mode != SyncFull {
+		return nil
+	}
+	return m.syncTo(lsn)
+}
+
+// syncTo makes the log durable up to lsn. Whoever holds syncMu fsyncs
+// everything written so far, so callers queued behind it usually find their
+// records already flushed and return without a second fsync.
+func (m *Manager) syncTo(lsn uint64) error {
+	m.syncMu.Lock()
+	defer m.syncMu.Unlock()
+
+	m.mu.Lock()
+	f, target, reg := m.f, m.lsn, m.metrics
+	done := m.flushed >= lsn
+	m.mu.Unlock()
+	if done {
+		return nil
+	}
+	if f == nil {
+		return ErrNoWALFile
+	}
+
+	start := time.Now()
+	if err := f.Sync(); err != nil {
+		return err
+	}
+	m.syncs++
+	reg.Add(metrics.WALSyncs, 1)
+	slog.Debug("wal: fsync", "lsn", target, "duration", time.Since(start))
+
+	m.mu.Lock()
+	m.flushed = max(m.flushed, target)
+	m.mu.Unlock()
+	return nil
+}
+
+// encodeRecord builds one WAL record. page is nil for records that carry no
+// page image (checkpoint). The record ends with the time it was logged,
+// which readers that predate it skip as trailing bytes.
+func encodeRecord(
+	typ uint8,
+	lsn uint64,
+	dir, base string,
+	pageID uint32,
+	page []byte,
+	at time.Time,
+) ([]byte, error) {
+	dirB := []byte(dir)
 	baseB := []byte(base)
 
 	// fixed fields:
 	// magic(4) ver(2) typ(1) rsv(1) totalLen(4) crc(4)
 	// lsn(8) dirLen(2) baseLen(2) pageID(4)
+	// then dir, base, page and the time (unix nanoseconds, 8)
 	fixed := 4 + 2 + 1 + 1 + 4 + 4 + 8 + 2 + 2 + 4
-	totalLen := fixed + len(dirB) + len(baseB) + PageSize
+	totalLen := fixed + len(dirB) + len(baseB) + len(page) + 8
 
 	buf := make([]byte, totalLen)
 	off := 0
@@ -107,7 +420,7 @@ func (m *Manager) AppendPageImage(dir, base string, pageID uint32, pageBytes []b
 
 	putU32(magicU32)
 	putU16(versionU16)
-	putU8(recPageImage)
+	putU8(typ)
 	putU8(0)
 
 	putU32(uint32(totalLen))
@@ -125,48 +438,95 @@ func (m *Manager) AppendPageImage(dir, base string, pageID uint32, pageBytes []b
 	copy(buf[off:], baseB)
 	off += len(baseB)
 
-	copy(buf[off:], pageBytes)
-	off += PageSize
+	copy(buf[off:], page)
+	off += len(page)
+	putU64(uint64(at.UnixNano()))
 
 	if off != totalLen {
-		return 0, ErrBadRecord
+		return nil, ErrBadRecord
 	}
 
 	crc := crc32.ChecksumIEEE(buf[crcOff+4:])
 	bx.PutU32(buf[crcOff:crcOff+4], crc)
-
-	if _, err := m.f.Write(buf); err != nil {
-		return 0, err
-	}
-	return lsn, nil
+	return buf, nil
 }
 
-func (m *Manager) Flush(upto uint64) error {
+// Checkpoint truncates the log. The caller must have written every logged
+// page to the data files and made them durable first; afterwards recovery
+// has nothing to replay. The LSN sequence continues across the truncation.
+func (m *Manager) Checkpoint() error {
 	if m == nil {
 		return nil
 	}
 	m.mu.Lock()
 	defer m.mu.Unlock()
 	if m.f == nil {
-		return nil
+		return ErrNoWALFile
 	}
-	if upto == 0 || upto <= m.flushed {
-		return nil
+
+	buf, err := encodeRecord(recCheckpoint, m.lsn, "", "", 0, nil, time.Now())
+	if err != nil {
+		return err
+	}
+	if err := m.archiveLocked(); err != nil {
+		return fmt.Errorf("wal: archive: %w", err)
+	}
+	if err := m.f.Truncate(0); err != nil {
+		return err
+	}
+	if _, err := m.f.Write(buf); err != nil {
+		return err
 	}
 	if err := m.f.Sync(); err != nil {
 		return err
 	}
-	m.flushed = upto
+	m.metrics.Add(metrics.WALBytes, uint64(len(buf)))
+	m.metrics.Add(metrics.WALSyncs, 1)
+	slog.Debug("wal: checkpoint", "lsn", m.lsn)
+	m.flushed = m.lsn
+	m.base = m.lsn
 	return nil
 }
 
+// Size returns the current size of the log file in bytes.
+func (m *Manager) Size() (int64, error) {
+	if m == nil {
+		return 0, nil
+	}
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	if m.f == nil {
+		return 0, ErrNoWALFile
+	}
+	info, err := m.f.Stat()
+	if err != nil {
+		return 0, err
+	}
+	return info.Size(), nil
+}
+
+// Flush makes the log durable up to upto before pages carrying that LSN are
+// written to the data files. It is a no-op with SyncOff.
+func (m *Manager) Flush(upto uint64) error {
+	if m == nil {
+		return nil
+	}
+	m.mu.Lock()
+	skip := m.f == nil || m.mode == SyncOff || upto == 0 || upto <= m.flushed
+	m.mu.Unlock()
+	if skip {
+		return nil
+	}
+	return m.syncTo(upto)
+}
+
 // Recover replays WAL page images (redo) using writer.
 func (m *Manager) Recover(writer PageWriter) error {
 	if m == nil {
 		return nil
 	}
 	m.mu.Lock()
-	path := m.path
+	path, c := m.path, m.cipher
 	m.mu.Unlock()
 
 	f, err := os.Open(path)
@@ -178,9 +538,38 @@ func (m *Manager) Recover(writer PageWriter) error {
 	}
 	defer func() { _ = f.Close() }()
 
-	r := bufio.NewReaderSize(f, 1<<20)
+	p := &replayer{apply: func(rec *decodedRecord) error { return applyRecord(rec, writer, c) }}
+	return p.replay(bufio.NewReaderSize(f, 1<<20))
+}
 
-	for {
+// applyRecord hands the page of rec to writer, opened with c if set.
+func applyRecord(rec *decodedRecord, writer PageWriter, c PageCipher) error {
+	page := rec.page
+	if c != nil {
+		var err error
+		if page, err = c.Open(rec.pageID, page); err != nil {
+			return fmt.Errorf("wal: page %d of %s: %w", rec.pageID, rec.base, err)
+		}
+	}
+	return writer.WritePage(rec.dir, rec.base, rec.pageID, page)
+}
+
+// replayer applies log records in order: page images as they come, the
+// pages of a transaction group at its commit record. A group without its
+// commit record (torn tail) is dropped. With past set, replay stops at the
+// first page image or commit record past the recovery target.
+type replayer struct {
+	apply   func(*decodedRecord) error
+	past    func(*decodedRecord) bool
+	stopped bool
+	last    uint64 // LSN of the last record applied
+}
+
+func (p *replayer) replay(r *bufio.Reader) error {
+	// Pages of the open transaction group; nil when none is open.
+	var group []*decodedRecord
+
+	for !p.stopped {
 		rec, err := readOne(r)
 		if err != nil {
 			if errors.Is(err, io.EOF) {
@@ -192,13 +581,42 @@ func (m *Manager) Recover(writer PageWriter) error {
 			}
 			return err
 		}
-		if rec.typ != recPageImage {
-			continue
-		}
-		if err := writer.WritePage(rec.dir, rec.base, rec.pageID, rec.page); err != nil {
-			return err
+
+		switch rec.typ {
+		case recPageImage:
+			group = nil
+			if p.past != nil && p.past(rec) {
+				p.stopped = true
+				return nil
+			}
+			if err := p.apply(rec); err != nil {
+				return err
+			}
+			p.last = max(p.last, rec.lsn)
+		case recTxBegin:
+			group = make([]*decodedRecord, 0, 8)
+		case recTxPage:
+			if group != nil {
+				group = append(group, rec)
+			}
+		case recTxCommit:
+			if group == nil {
+				continue
+			}
+			if p.past != nil && p.past(rec) {
+				p.stopped = true
+				return nil
+			}
+			for _, g := range group {
+				if err := p.apply(g); err != nil {
+					return err
+				}
+			}
+			group = nil
+			p.last = max(p.last, rec.lsn)
 		}
 	}
+	return nil
 }
 
 type decodedRecord struct {
@@ -208,6 +626,8 @@ type decodedRecord struct {
 	base   string
 	pageID uint32
 	page   []byte
+	at     time.Time // zero for records that predate the time trailer
+	size   int       // encoded length
 }
 
 func readOne(r *bufio.Reader) (*decodedRecord, error) {
@@ -275,7 +695,11 @@ func readOne(r *bufio.Reader) (*decodedRecord, error) {
 	baseLen := int(getU16())
 	pageID := getU32()
 
-	if off+dirLen+baseLen+PageSize > len(rest) {
+	pageLen := 0
+	if tp == recPageImage || tp == recTxPage {
+		pageLen = PageSize
+	}
+	if off+dirLen+baseLen+pageLen > len(rest) {
 		return nil, ErrBadRecord
 	}
 
@@ -284,8 +708,16 @@ func readOne(r *bufio.Reader) (*decodedRecord, error) {
 	base := string(rest[off : off+baseLen])
 	off += baseLen
 
-	page := make([]byte, PageSize)
-	copy(page, rest[off:off+PageSize])
+	var page []byte
+	if pageLen > 0 {
+		page = make([]byte, PageSize)
+		copy(page, rest[off:off+PageSize])
+		off += PageSize
+	}
+	var at time.Time
+	if off+8 <= len(rest) {
+		at = time.Unix(0, int64(getU64()))
+	}
 
 	return &decodedRecord{
 		typ:    tp,
@@ -294,9 +726,15 @@ func readOne(r *bufio.Reader) (*decodedRecord, error) {
 		base:   base,
 		pageID: pageID,
 		page:   page,
+		at:     at,
+		size:   int(totalLen),
 	}, nil
 }
 
+// initLastLSN reads the log up to its last whole record and cuts off what
+// follows it: a record torn by a crash, or the zeros of an append that did
+// not reach the disk. Appends then go right after that record, where
+// recovery reads them.
 func (m *Manager) initLastLSN() error {
 	f, err := os.Open(m.path)
 	if err != nil {
@@ -306,12 +744,25 @@ func (m *Manager) initLastLSN() error {
 
 	r := bufio.NewReaderSize(f, 1<<20)
 	var last uint64
+	var end int64 // offset past the last whole record
+	var tail error
 
-	for {
+	for first := true; ; first = false {
+		if _, err := r.Peek(1); err != nil {
+			break // end of the log
+		}
 		rec, err := readOne(r)
 		if err != nil {
+			// A record of another version is whole; leave it to Recover.
+			if !errors.Is(err, ErrBadRecord) {
+				tail = err
+			}
 			break
 		}
+		end += int64(rec.size)
+		if first && rec.typ == recCheckpoint {
+			m.base = rec.lsn
+		}
 		if rec.lsn > last {
 			last = rec.lsn
 		}
@@ -321,5 +772,9 @@ func (m *Manager) initLastLSN() error {
 		m.lsn = last
 		m.flushed = last
 	}
+	if tail != nil {
+		slog.Warn("wal: cutting off torn tail", "path", m.path, "offset", end, "err", tail)
+		return m.f.Truncate(end)
+	}
 	return nil
 }
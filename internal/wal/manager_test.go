@@ -0,0 +1,273 @@
+package wal
+
+import (
+	"os"
+	"path/filepath"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+type redo struct {
+	dir, base string
+	pageID    uint32
+	first     byte
+}
+
+type recordingWriter struct {
+	pages []redo
+}
+
+func (w *recordingWriter) WritePage(dir, base string, pageID uint32, pageBytes []byte) error {
+	w.pages = append(w.pages, redo{dir: dir, base: base, pageID: pageID, first: pageBytes[0]})
+	return nil
+}
+
+func pageWith(b byte) []byte {
+	p := make([]byte, PageSize)
+	p[0] = b
+	return p
+}
+
+func TestManager_AppendRecover(t *testing.T) {
+	dir := t.TempDir()
+	m, err := Open(dir)
+	require.NoError(t, err)
+
+	for i := range 3 {
+		lsn, err := m.AppendPageImage("/data/t", "users", uint32(i), pageWith(byte(i+1)))
+		require.NoError(t, err)
+		require.Equal(t, uint64(i+1), lsn)
+	}
+	_, err = m.AppendPageImage("/data/t", "users", 0, []byte("short"))
+	require.ErrorIs(t, err, ErrBadRecord)
+	require.NoError(t, m.Flush(3))
+	require.NoError(t, m.Close())
+
+	m, err = Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = m.Close() })
+
+	w := &recordingWriter{}
+	require.NoError(t, m.Recover(w))
+	require.Equal(t, []redo{
+		{dir: "/data/t", base: "users", pageID: 0, first: 1},
+		{dir: "/data/t", base: "users", pageID: 1, first: 2},
+		{dir: "/data/t", base: "users", pageID: 2, first: 3},
+	}, w.pages)
+
+	// LSNs continue after reopen.
+	lsn, err := m.AppendPageImage("/data/t", "users", 0, pageWith(9))
+	require.NoError(t, err)
+	require.Equal(t, uint64(4), lsn)
+}
+
+func TestManager_RecoverToleratesTornTail(t *testing.T) {
+	dir := t.TempDir()
+	m, err := Open(dir)
+	require.NoError(t, err)
+	_, err = m.AppendPageImage("d", "rel", 0, pageWith(1))
+	require.NoError(t, err)
+	_, err = m.AppendPageImage("d", "rel", 1, pageWith(2))
+	require.NoError(t, err)
+	require.NoError(t, m.Close())
+
+	// Cut the second record in half, as a crash mid-append would.
+	path := filepath.Join(dir, "wal.log")
+	info, err := os.Stat(path)
+	require.NoError(t, err)
+	require.NoError(t, os.Truncate(path, info.Size()-PageSize/2))
+
+	m, err = Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = m.Close() })
+
+	w := &recordingWriter{}
+	require.NoError(t, m.Recover(w))
+	require.Len(t, w.pages, 1)
+	require.Equal(t, byte(1), w.pages[0].first)
+}
+
+func TestManager_AppendsAfterTornTail(t *testing.T) {
+	dir := t.TempDir()
+	m, err := Open(dir)
+	require.NoError(t, err)
+	_, err = m.AppendPageImage("d", "rel", 0, pageWith(1))
+	require.NoError(t, err)
+	require.NoError(t, m.Close())
+
+	// Zeros after the record, as an append whose data never reached the
+	// disk leaves.
+	path := filepath.Join(dir, "wal.log")
+	info, err := os.Stat(path)
+	require.NoError(t, err)
+	require.NoError(t, os.Truncate(path, info.Size()+100))
+
+	m, err = Open(dir)
+	require.NoError(t, err)
+	size, err := m.Size()
+	require.NoError(t, err)
+	require.Equal(t, info.Size(), size)
+	_, err = m.AppendPageImage("d", "rel", 1, pageWith(2))
+	require.NoError(t, err)
+	require.NoError(t, m.Close())
+
+	m, err = Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = m.Close() })
+	w := &recordingWriter{}
+	require.NoError(t, m.Recover(w))
+	require.Equal(t, []redo{
+		{dir: "d", base: "rel", pageID: 0, first: 1},
+		{dir: "d", base: "rel", pageID: 1, first: 2},
+	}, w.pages)
+}
+
+func TestManager_Checkpoint(t *testing.T) {
+	dir := t.TempDir()
+	m, err := Open(dir)
+	require.NoError(t, err)
+	for i := range 5 {
+		_, err := m.AppendPageImage("d", "rel", uint32(i), pageWith(1))
+		require.NoError(t, err)
+	}
+	before, err := m.Size()
+	require.NoError(t, err)
+
+	require.NoError(t, m.Checkpoint())
+	after, err := m.Size()
+	require.NoError(t, err)
+	require.Less(t, after, before)
+	require.Less(t, after, int64(PageSize))
+
+	w := &recordingWriter{}
+	require.NoError(t, m.Recover(w))
+	require.Empty(t, w.pages)
+	require.NoError(t, m.Close())
+
+	// The checkpoint record keeps the LSN sequence monotonic across reopen.
+	m, err = Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = m.Close() })
+	lsn, err := m.AppendPageImage("d", "rel", 0, pageWith(7))
+	require.NoError(t, err)
+	require.Equal(t, uint64(6), lsn)
+
+	w = &recordingWriter{}
+	require.NoError(t, m.Recover(w))
+	require.Len(t, w.pages, 1)
+	require.Equal(t, byte(7), w.pages[0].first)
+}
+
+func TestManager_TxGroupIsAtomic(t *testing.T) {
+	dir := t.TempDir()
+	m, err := Open(dir)
+	require.NoError(t, err)
+
+	_, err = m.AppendTx([]PageImage{
+		{Dir: "d", Base: "rel", PageID: 0, Page: pageWith(1)},
+		{Dir: "d", Base: "rel", PageID: 1, Page: pageWith(2)},
+	})
+	require.NoError(t, err)
+	lsn, err := m.AppendTx([]PageImage{
+		{Dir: "d", Base: "rel", PageID: 2, Page: pageWith(3)},
+		{Dir: "d", Base: "rel", PageID: 3, Page: pageWith(4)},
+	})
+	require.NoError(t, err)
+	require.Equal(t, uint64(8), lsn) // begin + 2 pages + commit, twice
+	require.NoError(t, m.Close())
+
+	w := &recordingWriter{}
+	m, err = Open(dir)
+	require.NoError(t, err)
+	require.NoError(t, m.Recover(w))
+	require.Len(t, w.pages, 4)
+	require.NoError(t, m.Close())
+
+	// Lose the second group's commit record: none of its pages are replayed.
+	path := filepath.Join(dir, "wal.log")
+	info, err := os.Stat(path)
+	require.NoError(t, err)
+	require.NoError(t, os.Truncate(path, info.Size()-1))
+
+	w = &recordingWriter{}
+	m, err = Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = m.Close() })
+	require.NoError(t, m.Recover(w))
+	require.Equal(t, []redo{
+		{dir: "d", base: "rel", pageID: 0, first: 1},
+		{dir: "d", base: "rel", pageID: 1, first: 2},
+	}, w.pages)
+}
+
+func TestManager_SyncModes(t *testing.T) {
+	_, err := ParseSyncMode("always")
+	require.ErrorIs(t, err, ErrSyncMode)
+	mode, err := ParseSyncMode("NORMAL")
+	require.NoError(t, err)
+	require.Equal(t, SyncNormal, mode)
+
+	tx := []PageImage{{Dir: "d", Base: "rel", PageID: 0, Page: pageWith(1)}}
+	for _, tc := range []struct {
+		mode                    SyncMode
+		afterCommit, afterFlush uint64
+	}{
+		{SyncFull, 1, 1},
+		{SyncNormal, 0, 1}, // only the WAL-before-data flush syncs
+		{SyncOff, 0, 0},
+	} {
+		m, err := Open(t.TempDir())
+		require.NoError(t, err)
+		m.SetSyncMode(tc.mode)
+
+		lsn, err := m.AppendTx(tx)
+		require.NoError(t, err)
+		require.Equal(t, tc.afterCommit, m.SyncCount(), tc.mode.String())
+		require.NoError(t, m.Flush(lsn))
+		require.NoError(t, m.Flush(lsn))
+		require.Equal(t, tc.afterFlush, m.SyncCount(), tc.mode.String())
+		require.NoError(t, m.Close())
+	}
+}
+
+func TestManager_GroupCommit(t *testing.T) {
+	dir := t.TempDir()
+	m, err := Open(dir)
+	require.NoError(t, err)
+
+	// Hold off the first fsync until every writer has logged its group, so
+	// that one sync has to cover all of them.
+	const writers = 32
+	start, synced := m.LastLSN(), m.SyncCount()
+	m.syncMu.Lock()
+	var wg sync.WaitGroup
+	errs := make(chan error, writers)
+	for i := range writers {
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			_, err := m.AppendTx([]PageImage{{Dir: "d", Base: "rel", PageID: uint32(i), Page: pageWith(byte(i))}})
+			errs <- err
+		}()
+	}
+	require.Eventually(t, func() bool { return m.LastLSN() == start+3*writers }, 5*time.Second, time.Millisecond)
+	m.syncMu.Unlock()
+	wg.Wait()
+	close(errs)
+	for err := range errs {
+		require.NoError(t, err)
+	}
+	// Every commit returned durable, all of them on one fsync.
+	require.Equal(t, synced+1, m.SyncCount())
+	require.NoError(t, m.Close())
+
+	w := &recordingWriter{}
+	m, err = Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = m.Close() })
+	require.NoError(t, m.Recover(w))
+	require.Len(t, w.pages, writers)
+}
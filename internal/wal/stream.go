@@ -0,0 +1,250 @@
+package wal
+
+import (
+	"bufio"
+	"bytes"
+	"errors"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"time"
+)
+
+// Log streaming
+//
+// A Tailer follows a log as it grows, for replication: Read returns the
+// records appended since the previous Read, encoded as in the file, and the
+// receiving end replays them with ApplyRecords. Transaction groups are
+// returned whole, once their commit record is logged. A checkpoint
+// truncates the log: records the tailer had not read by then are taken from
+// the archive when one is set; otherwise Read reports ErrLogGap and goes on
+// from the start of the truncated log.
+
+var ErrLogGap = errors.New("wal: records were checkpointed before they were read")
+
+// Tailer reads the records of one log as they are appended. It only reads
+// the file, so any process may tail the log of a database open elsewhere.
+type Tailer struct {
+	path       string
+	archiveDir string
+	head       logHead // checkpoint the log started with at the last Read
+	off        int64   // bytes of the log read so far
+	last       uint64  // LSN of the last record returned
+	at         time.Time
+	fill       bool // records after last were truncated before the first Read
+}
+
+// logHead identifies one truncation of the log: the checkpoint record it
+// starts with. The zero head is a log that was never checkpointed.
+type logHead struct {
+	lsn uint64
+	at  time.Time
+}
+
+// NewTailer returns a tailer of the log in dir (the directory given to
+// Open), which archives to archiveDir ("" = not archived). It starts after
+// LSN after; with after 0, at the start of the log as it is now, whose
+// checkpoint vouches that every earlier record is in the data files.
+func NewTailer(dir, archiveDir string, after uint64) (*Tailer, error) {
+	t := &Tailer{path: filepath.Join(dir, "wal.log"), archiveDir: archiveDir, last: after}
+	head, err := t.readHead()
+	if err != nil {
+		return nil, err
+	}
+	t.head = head
+	switch {
+	case after == 0:
+		t.last = head.lsn
+	case head.lsn > after:
+		t.fill = true
+	}
+	return t, nil
+}
+
+// LSN is the LSN of the last record returned.
+func (t *Tailer) LSN() uint64 { return t.last }
+
+// Time is when the last record returned was logged.
+func (t *Tailer) Time() time.Time { return t.at }
+
+// Start is the LSN of the checkpoint the log started with at the last Read:
+// it changes whenever the log was truncated.
+func (t *Tailer) Start() uint64 { return t.head.lsn }
+
+// Read returns the records logged since the previous Read, nil when there
+// are none yet.
+func (t *Tailer) Read() ([]byte, error) {
+	if t.fill {
+		t.fill = false
+		return t.readArchive(t.head)
+	}
+	for {
+		head, err := t.readHead()
+		if err != nil {
+			return nil, err
+		}
+		if head != t.head {
+			// Truncated by a checkpoint since the last Read.
+			lost := head.lsn > t.last
+			t.head, t.off = head, 0
+			if lost {
+				return t.readArchive(head)
+			}
+		}
+		data, err := t.readFrom(t.off)
+		if err != nil {
+			return nil, err
+		}
+		// A checkpoint while reading leaves data torn: read again.
+		if again, err := t.readHead(); err != nil || again != head {
+			continue
+		}
+		out, last, at, n := scanRecords(data, t.last)
+		t.off += int64(n)
+		if out != nil {
+			t.last, t.at = last, at
+		}
+		return out, nil
+	}
+}
+
+// Reread returns every record read since the log was last truncated, for a
+// receiver that has to apply them again; nil once it was truncated since.
+func (t *Tailer) Reread() ([]byte, error) {
+	data, err := t.readFrom(0)
+	if err != nil {
+		return nil, err
+	}
+	if head, err := t.readHead(); err != nil || head != t.head {
+		return nil, err
+	}
+	out, _, _, _ := scanRecords(data[:min(int64(len(data)), t.off)], 0)
+	return out, nil
+}
+
+func (t *Tailer) readHead() (logHead, error) {
+	f, err := os.Open(t.path)
+	if errors.Is(err, os.ErrNotExist) {
+		return logHead{}, nil
+	}
+	if err != nil {
+		return logHead{}, err
+	}
+	defer func() { _ = f.Close() }()
+	rec, err := readOne(bufio.NewReader(f))
+	if err != nil || rec.typ != recCheckpoint {
+		return logHead{}, nil
+	}
+	return logHead{lsn: rec.lsn, at: rec.at}, nil
+}
+
+func (t *Tailer) readFrom(off int64) ([]byte, error) {
+	f, err := os.Open(t.path)
+	if errors.Is(err, os.ErrNotExist) {
+		return nil, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = f.Close() }()
+	if _, err := f.Seek(off, io.SeekStart); err != nil {
+		return nil, err
+	}
+	return io.ReadAll(f)
+}
+
+// readArchive returns the records after the last one read from the archived
+// segments, up to head, where the current log starts. Without an archive,
+// or with segments missing, they are lost: it returns ErrLogGap and the
+// next Read starts at head.
+func (t *Tailer) readArchive(head logHead) ([]byte, error) {
+	var segs []string
+	if t.archiveDir != "" {
+		entries, err := os.ReadDir(t.archiveDir)
+		if err != nil && !errors.Is(err, os.ErrNotExist) {
+			return nil, err
+		}
+		for _, e := range entries {
+			if !e.IsDir() && strings.HasSuffix(e.Name(), archiveExt) {
+				segs = append(segs, e.Name())
+			}
+		}
+		sort.Strings(segs)
+	}
+
+	var out []byte
+	for _, name := range segs {
+		var first, last uint64
+		if _, err := fmt.Sscanf(name, "%016x-%016x"+archiveExt, &first, &last); err != nil || last <= t.last {
+			continue
+		}
+		if first > t.last+1 {
+			break
+		}
+		data, err := os.ReadFile(filepath.Join(t.archiveDir, name))
+		if err != nil {
+			return nil, err
+		}
+		recs, lsn, at, _ := scanRecords(data, t.last)
+		out = append(out, recs...)
+		if recs != nil {
+			t.last, t.at = lsn, at
+		}
+		t.last = max(t.last, last)
+	}
+	if t.last < head.lsn {
+		t.last = head.lsn
+		return out, ErrLogGap
+	}
+	return out, nil
+}
+
+// scanRecords returns the records of data logged after LSN after, whole
+// groups only, with the LSN and time of the last one, and how many bytes of
+// data it went through: up to an open group or a torn record at the end,
+// which a later scan reads again.
+func scanRecords(data []byte, after uint64) (out []byte, last uint64, at time.Time, n int) {
+	r := bufio.NewReader(bytes.NewReader(data))
+	group := -1 // offset of the begin record of the open group
+	for pos := 0; ; {
+		rec, err := readOne(r)
+		if err != nil {
+			return out, last, at, n
+		}
+		end := pos + rec.size
+		switch rec.typ {
+		case recPageImage:
+			group = -1
+			if rec.lsn > after {
+				out = append(out, data[pos:end]...)
+				last, at = rec.lsn, rec.at
+			}
+			n = end
+		case recTxBegin:
+			group = pos
+		case recTxCommit:
+			if group >= 0 && rec.lsn > after {
+				out = append(out, data[group:end]...)
+				last, at = rec.lsn, rec.at
+			}
+			group = -1
+			n = end
+		case recCheckpoint:
+			group = -1
+			n = end
+		}
+		pos = end
+	}
+}
+
+// ApplyRecords replays records, as Tailer.Read returns them, through
+// writer, opening page images with c if set. It returns the LSN of the last
+// record applied, 0 when there was none.
+func ApplyRecords(records []byte, writer PageWriter, c PageCipher) (uint64, error) {
+	p := &replayer{apply: func(rec *decodedRecord) error { return applyRecord(rec, writer, c) }}
+	err := p.replay(bufio.NewReader(bytes.NewReader(records)))
+	return p.last, err
+}
@@ -0,0 +1,108 @@
+package wal
+
+import (
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestTailer(t *testing.T) {
+	dir := t.TempDir()
+	m, err := Open(dir)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = m.Close() })
+
+	_, err = m.AppendPageImage("d", "rel", 0, pageWith(1))
+	require.NoError(t, err)
+	require.NoError(t, m.Checkpoint())
+
+	// The tailer starts at the checkpoint: page 0 is in the data files.
+	tl, err := NewTailer(dir, "", 0)
+	require.NoError(t, err)
+	recs, err := tl.Read()
+	require.NoError(t, err)
+	require.Nil(t, recs)
+
+	_, err = m.AppendTx([]PageImage{
+		{Dir: "d", Base: "rel", PageID: 0, Page: pageWith(2)},
+		{Dir: "d", Base: "rel", PageID: 1, Page: pageWith(3)},
+	})
+	require.NoError(t, err)
+	_, err = m.AppendPageImage("d", "rel", 2, pageWith(4))
+	require.NoError(t, err)
+
+	recs, err = tl.Read()
+	require.NoError(t, err)
+	require.Equal(t, uint64(6), tl.LSN())
+	w := &recordingWriter{}
+	last, err := ApplyRecords(recs, w, nil)
+	require.NoError(t, err)
+	require.Equal(t, uint64(6), last)
+	require.Equal(t, []redo{
+		{dir: "d", base: "rel", pageID: 0, first: 2},
+		{dir: "d", base: "rel", pageID: 1, first: 3},
+		{dir: "d", base: "rel", pageID: 2, first: 4},
+	}, w.pages)
+
+	recs, err = tl.Read()
+	require.NoError(t, err)
+	require.Nil(t, recs)
+	again, err := tl.Reread()
+	require.NoError(t, err)
+	w = &recordingWriter{}
+	_, err = ApplyRecords(again, w, nil)
+	require.NoError(t, err)
+	require.Len(t, w.pages, 3)
+
+	// A checkpoint after everything was read loses nothing.
+	require.NoError(t, m.Checkpoint())
+	_, err = m.AppendPageImage("d", "rel", 3, pageWith(5))
+	require.NoError(t, err)
+	recs, err = tl.Read()
+	require.NoError(t, err)
+	w = &recordingWriter{}
+	_, err = ApplyRecords(recs, w, nil)
+	require.NoError(t, err)
+	require.Equal(t, []redo{{dir: "d", base: "rel", pageID: 3, first: 5}}, w.pages)
+
+	// Records checkpointed before they were read are a gap...
+	_, err = m.AppendPageImage("d", "rel", 4, pageWith(6))
+	require.NoError(t, err)
+	require.NoError(t, m.Checkpoint())
+	_, err = tl.Read()
+	require.ErrorIs(t, err, ErrLogGap)
+	_, err = m.AppendPageImage("d", "rel", 5, pageWith(7))
+	require.NoError(t, err)
+	recs, err = tl.Read()
+	require.NoError(t, err)
+	w = &recordingWriter{}
+	_, err = ApplyRecords(recs, w, nil)
+	require.NoError(t, err)
+	require.Equal(t, []redo{{dir: "d", base: "rel", pageID: 5, first: 7}}, w.pages)
+
+	// ...unless they were archived.
+	archive := filepath.Join(t.TempDir(), "archive")
+	m.SetArchiveDir(archive)
+	last = m.LastLSN()
+	_, err = m.AppendPageImage("d", "rel", 6, pageWith(8))
+	require.NoError(t, err)
+	require.NoError(t, m.Checkpoint())
+	_, err = m.AppendPageImage("d", "rel", 7, pageWith(9))
+	require.NoError(t, err)
+
+	tl, err = NewTailer(dir, archive, last)
+	require.NoError(t, err)
+	w = &recordingWriter{}
+	for range 2 {
+		recs, err = tl.Read()
+		require.NoError(t, err)
+		_, err = ApplyRecords(recs, w, nil)
+		require.NoError(t, err)
+	}
+	require.Equal(t, []redo{
+		{dir: "d", base: "rel", pageID: 6, first: 8},
+		{dir: "d", base: "rel", pageID: 7, first: 9},
+	}, w.pages)
+	require.Equal(t, m.LastLSN(), tl.LSN())
+}
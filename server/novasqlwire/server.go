@@ -2,14 +2,19 @@ package novasqlwire
 
 import (
 	"context"
+	"errors"
 	"fmt"
 	"log"
+	"log/slog"
 	"net"
 	"os/signal"
+	"sync"
 	"syscall"
 	"time"
 
 	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/metrics"
 	"github.com/tuannm99/novasql/internal/sql/executor"
 )
 
@@ -17,23 +22,57 @@ type ServerConfig struct {
 	Addr    string
 	Workdir string
 	CfgPath string
+	Config  *internal.NovaSqlConfig // passed to every session database (may be nil)
+	Metrics *metrics.Registry       // shared by every session database (nil = one each)
+
+	// Current returns the config as last reloaded (nil = Config is never
+	// reloaded). Sessions apply a new one before their next statement, see
+	// novasql.Database.ApplyConfig.
+	Current func() *internal.NovaSqlConfig
 }
 
+// Run listens on sc.Addr and serves until SIGINT/SIGTERM.
 func Run(sc ServerConfig) error {
 	ln, err := net.Listen("tcp", sc.Addr)
 	if err != nil {
 		return fmt.Errorf("listen: %w", err)
 	}
-	defer func() { _ = ln.Close() }()
 
 	log.Printf("novasql tcp server listening on %s (workdir=%s)", sc.Addr, sc.Workdir)
 
 	ctx, stop := signal.NotifyContext(context.Background(), syscall.SIGINT, syscall.SIGTERM)
 	defer stop()
 
+	return Serve(ctx, ln, sc)
+}
+
+// Serve accepts connections on ln until ctx is done, then shuts down
+// gracefully: it stops accepting, lets every connection finish the request it
+// is executing, and returns once all sessions have closed their databases.
+// ln is closed on return.
+func Serve(ctx context.Context, ln net.Listener, sc ServerConfig) error {
+	var (
+		wg    sync.WaitGroup
+		mu    sync.Mutex
+		conns = make(map[net.Conn]struct{})
+	)
+
 	go func() {
 		<-ctx.Done()
 		_ = ln.Close()
+
+		// Unblock sessions waiting for their next request; one that is
+		// executing notices on its next read, after sending the response.
+		mu.Lock()
+		for c := range conns {
+			_ = c.SetReadDeadline(time.Now())
+		}
+		mu.Unlock()
+	}()
+
+	defer func() {
+		wg.Wait()
+		log.Printf("novasql tcp server stopped")
 	}()
 
 	for {
@@ -44,22 +83,92 @@ func Run(sc ServerConfig) error {
 				return nil
 			default:
 			}
+			if errors.Is(err, net.ErrClosed) {
+				return err
+			}
 			log.Printf("accept: %v", err)
 			continue
 		}
-		go handleConn(ctx, conn, sc.Workdir)
+
+		mu.Lock()
+		if ctx.Err() != nil {
+			mu.Unlock()
+			_ = conn.Close()
+			continue
+		}
+		conns[conn] = struct{}{}
+		wg.Add(1)
+		mu.Unlock()
+
+		go func() {
+			defer wg.Done()
+			handleConn(ctx, conn, sc)
+			mu.Lock()
+			delete(conns, conn)
+			mu.Unlock()
+		}()
 	}
 }
 
-func handleConn(ctx context.Context, conn net.Conn, workdir string) {
+func handleConn(ctx context.Context, conn net.Conn, sc ServerConfig) {
 	defer func() { _ = conn.Close() }()
 
-	// No global deadline; you can set per-request deadline if needed.
-	_ = conn.SetDeadline(time.Time{})
-
-	executor, cleanup := newSessionExecutor(workdir)
-	defer func() { _ = cleanup() }()
+	// No deadlines here: Serve sets a read deadline to end the session on shutdown.
+	db, executor, cleanup, err := newSessionExecutor(sc)
+	if err != nil {
+		// Tell the client why, in answer to its first request.
+		log.Printf("session open: %v", err)
+		var req ExecuteRequest
+		if ReadFrame(conn, &req) == nil {
+			_ = WriteFrame(conn, ExecuteResponse{ID: req.ID, Error: err.Error(), Code: novasql.CodeOf(err)})
+		}
+		return
+	}
+	defer func() {
+		if err := cleanup(); err != nil {
+			log.Printf("session close: %v", err)
+		}
+	}()
+	logger := slog.With("conn", conn.RemoteAddr().String())
+	executor.SetLogger(logger)
+	logger.Debug("session open")
+	defer logger.Debug("session closed")
+
+	// Requests are read ahead by their own goroutine, so that a cancel
+	// request is seen while the one it cancels executes.
+	var (
+		mu      sync.Mutex
+		running uint64 // ID of the executing request, 0 = none
+		cancel  context.CancelFunc
+	)
+	reqs := make(chan ExecuteRequest)
+	done := make(chan struct{})
+	defer close(done)
+	go func() {
+		defer close(reqs)
+		for {
+			var req ExecuteRequest
+			if err := ReadFrame(conn, &req); err != nil {
+				// Client closed, bad frame, or shutdown.
+				return
+			}
+			if req.Cancel {
+				mu.Lock()
+				if cancel != nil && running == req.ID {
+					cancel()
+				}
+				mu.Unlock()
+				continue
+			}
+			select {
+			case reqs <- req:
+			case <-done:
+				return
+			}
+		}
+	}()
 
+	applied := sc.Config
 	for {
 		select {
 		case <-ctx.Done():
@@ -67,17 +176,34 @@ func handleConn(ctx context.Context, conn net.Conn, workdir string) {
 		default:
 		}
 
-		var req ExecuteRequest
-		if err := ReadFrame(conn, &req); err != nil {
-			// Client closed or bad frame.
+		req, ok := <-reqs
+		if !ok {
 			return
 		}
+		if sc.Current != nil {
+			if cfg := sc.Current(); cfg != applied {
+				applied = cfg
+				if err := db.ApplyConfig(cfg); err != nil {
+					logger.Warn("config reload", "err", err)
+				}
+			}
+		}
 
-		res, err := executor.ExecSQL(req.SQL)
+		// Not derived from ctx: on shutdown the request still completes.
+		reqCtx, cancelReq := context.WithCancel(context.Background())
+		mu.Lock()
+		running, cancel = req.ID, cancelReq
+		mu.Unlock()
+		res, err := executor.ExecContext(reqCtx, req.SQL)
+		mu.Lock()
+		running, cancel = 0, nil
+		mu.Unlock()
+		cancelReq()
 		if err != nil {
 			_ = WriteFrame(conn, ExecuteResponse{
 				ID:    req.ID,
 				Error: err.Error(),
+				Code:  novasql.CodeOf(err),
 			})
 			continue
 		}
@@ -90,9 +216,12 @@ func handleConn(ctx context.Context, conn net.Conn, workdir string) {
 }
 
 // newSessionExecutor returns a fresh DB per connection so USE <db> is session-scoped.
-func newSessionExecutor(workdir string) (*executor.Executor, func() error) {
-	db := novasql.NewDatabase(workdir)
+func newSessionExecutor(sc ServerConfig) (*novasql.Database, *executor.Executor, func() error, error) {
+	db, err := novasql.Options().Config(sc.Config).Upgrade(true).Metrics(sc.Metrics).Open(sc.Workdir)
+	if err != nil {
+		return nil, nil, nil, err
+	}
 	ex := executor.NewExecutor(db)
-	cleanup := func() error { return db.Close() }
-	return ex, cleanup
+	cleanup := func() error { return errors.Join(ex.CloseCursors(), db.Close()) }
+	return db, ex, cleanup, nil
 }
@@ -0,0 +1,132 @@
+package novasqlwire
+
+import (
+	"context"
+	"net"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/heap"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+func exec(t *testing.T, conn net.Conn, id uint64, sql string) ExecuteResponse {
+	t.Helper()
+
+	require.NoError(t, WriteFrame(conn, ExecuteRequest{ID: id, SQL: sql}))
+	var resp ExecuteResponse
+	require.NoError(t, ReadFrame(conn, &resp))
+	require.Equal(t, id, resp.ID)
+	return resp
+}
+
+func TestServe_SessionsAndGracefulShutdown(t *testing.T) {
+	workdir := t.TempDir()
+	ln, err := net.Listen("tcp", "127.0.0.1:0")
+	require.NoError(t, err)
+
+	ctx, cancel := context.WithCancel(context.Background())
+	done := make(chan error, 1)
+	go func() { done <- Serve(ctx, ln, ServerConfig{Workdir: workdir}) }()
+
+	c1, err := net.Dial("tcp", ln.Addr().String())
+	require.NoError(t, err)
+	defer func() { _ = c1.Close() }()
+	c2, err := net.Dial("tcp", ln.Addr().String())
+	require.NoError(t, err)
+	defer func() { _ = c2.Close() }()
+
+	require.Empty(t, exec(t, c1, 1, "CREATE TABLE users (id INT, name TEXT);").Error)
+	require.Empty(t, exec(t, c1, 2, "INSERT INTO users VALUES (1, 'a');").Error)
+	require.Contains(t, exec(t, c1, 3, "SELEC 1;").Error, "unsupported statement")
+	require.Equal(t, novasql.CodeSyntax, exec(t, c1, 4, "SELEC 1;").Code)
+	require.Equal(t, novasql.CodeTableNotFound, exec(t, c1, 5, "SELECT * FROM missing;").Code)
+
+	// USE is per session.
+	require.Empty(t, exec(t, c2, 1, "CREATE DATABASE other;").Error)
+	require.Empty(t, exec(t, c2, 2, "USE other;").Error)
+	require.NotEmpty(t, exec(t, c2, 3, "SELECT * FROM users;").Error)
+
+	cancel()
+	select {
+	case err := <-done:
+		require.NoError(t, err)
+	case <-time.After(5 * time.Second):
+		t.Fatal("Serve did not return after shutdown")
+	}
+	_, err = net.Dial("tcp", ln.Addr().String())
+	require.Error(t, err)
+
+	// Sessions closed their databases: the insert is on disk.
+	db, err := novasql.Options().Open(workdir)
+	require.NoError(t, err)
+	defer func() { _ = db.Close() }()
+	tbl, err := db.OpenTable("users")
+	require.NoError(t, err)
+	var rows [][]any
+	require.NoError(t, tbl.Scan(func(_ heap.TID, row []any) error {
+		rows = append(rows, row)
+		return nil
+	}))
+	require.Equal(t, [][]any{{int64(1), "a"}}, rows)
+}
+
+func TestServe_Cancel(t *testing.T) {
+	workdir := t.TempDir()
+	db, err := novasql.Options().Open(workdir)
+	require.NoError(t, err)
+	_, err = db.CreateTable("big", record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64}, {Name: "v", Type: record.ColInt64},
+	}})
+	require.NoError(t, err)
+	rows := make([][]any, 1000)
+	for i := range rows {
+		rows[i] = []any{int64(i), int64(-1)}
+	}
+	_, err = db.InsertRows("big", rows)
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+
+	ln, err := net.Listen("tcp", "127.0.0.1:0")
+	require.NoError(t, err)
+	ctx, cancel := context.WithCancel(context.Background())
+	defer cancel()
+	go func() { _ = Serve(ctx, ln, ServerConfig{Workdir: workdir}) }()
+	conn, err := net.Dial("tcp", ln.Addr().String())
+	require.NoError(t, err)
+	defer func() { _ = conn.Close() }()
+
+	// No match among a million pairs: long enough to be cancelled midway.
+	const slowJoin = "SELECT COUNT(*) FROM big a JOIN big b ON a.id < b.v;"
+	require.NoError(t, WriteFrame(conn, ExecuteRequest{ID: 1, SQL: slowJoin}))
+	done, stopped := make(chan struct{}), make(chan struct{})
+	go func() {
+		defer close(stopped)
+		for {
+			select {
+			case <-done:
+				return
+			case <-time.After(time.Millisecond):
+				_ = WriteFrame(conn, ExecuteRequest{ID: 1, Cancel: true})
+			}
+		}
+	}()
+	var resp ExecuteResponse
+	require.NoError(t, ReadFrame(conn, &resp))
+	close(done)
+	<-stopped
+	require.Equal(t, uint64(1), resp.ID)
+	require.Equal(t, novasql.CodeCancelled, resp.Code)
+
+	// A cancel for a request that is not executing is ignored.
+	require.NoError(t, WriteFrame(conn, ExecuteRequest{ID: 7, Cancel: true}))
+	require.Empty(t, exec(t, conn, 2, "SELECT COUNT(*) FROM big;").Error)
+
+	require.Empty(t, exec(t, conn, 3, "SET statement_timeout = 1;").Error)
+	resp = exec(t, conn, 4, slowJoin)
+	require.Equal(t, novasql.CodeCancelled, resp.Code)
+	require.Contains(t, resp.Error, "statement_timeout")
+}
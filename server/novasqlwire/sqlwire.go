@@ -1,16 +1,24 @@
 package novasqlwire
 
-import "github.com/tuannm99/novasql/internal/sql/executor"
+import (
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/sql/executor"
+)
 
-// ExecuteRequest is a single SQL command request.
+// ExecuteRequest is a single SQL command request. One with Cancel set
+// instead asks to cancel request ID, if it is still executing, which then
+// fails with novasql.ErrQueryCancelled; it gets no response of its own.
 type ExecuteRequest struct {
-	ID  uint64 `json:"id"`
-	SQL string `json:"sql"`
+	ID     uint64 `json:"id"`
+	SQL    string `json:"sql,omitempty"`
+	Cancel bool   `json:"cancel,omitempty"`
 }
 
-// ExecuteResponse is the response for a request ID.
+// ExecuteResponse is the response for a request ID. A failed request has
+// the message of its error in Error and its novasql.ErrorCode in Code.
 type ExecuteResponse struct {
-	ID     uint64           `json:"id"`
-	Result *executor.Result `json:"result,omitempty"`
-	Error  string           `json:"error,omitempty"`
+	ID     uint64            `json:"id"`
+	Result *executor.Result  `json:"result,omitempty"`
+	Error  string            `json:"error,omitempty"`
+	Code   novasql.ErrorCode `json:"code,omitempty"`
 }
@@ -0,0 +1,631 @@
+// Package pgwire serves novasql over the PostgreSQL v3 frontend/backend
+// protocol, so psql and Postgres drivers that use the simple query protocol
+// can connect, and cancel their queries with a CancelRequest. There is no
+// authentication, TLS or extended query protocol (Parse/Bind/Execute are
+// answered with an error).
+package pgwire
+
+import (
+	"bufio"
+	"context"
+	"crypto/rand"
+	"encoding/binary"
+	"errors"
+	"fmt"
+	"io"
+	"log"
+	"log/slog"
+	"net"
+	"slices"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal"
+	"github.com/tuannm99/novasql/internal/metrics"
+	"github.com/tuannm99/novasql/internal/record"
+	"github.com/tuannm99/novasql/internal/sql/executor"
+	"github.com/tuannm99/novasql/internal/sql/parser"
+)
+
+const (
+	protocolVersion3 = 196608   // 3.0
+	sslRequestCode   = 80877103 // SSLRequest
+	gssRequestCode   = 80877104 // GSSENCRequest
+	cancelCode       = 80877102 // CancelRequest
+
+	// maxMessageSize limits memory usage on malformed/hostile input.
+	maxMessageSize = 8 << 20
+
+	serverVersion = "14.0 (novasql)"
+)
+
+// Type OIDs used in RowDescription (pg_type.oid).
+const (
+	oidBool      = 16
+	oidInt8      = 20
+	oidInt4      = 23
+	oidText      = 25
+	oidFloat8    = 701
+	oidDate      = 1082
+	oidTime      = 1083
+	oidTimestamp = 1114
+	oidNumeric   = 1700
+)
+
+var ErrProtocol = errors.New("pgwire: protocol violation")
+
+type Config struct {
+	Workdir string
+	Config  *internal.NovaSqlConfig // passed to every session database (may be nil)
+	Metrics *metrics.Registry       // shared by every session database (nil = one each)
+
+	// Current returns the config as last reloaded (nil = Config is never
+	// reloaded); sessions apply a new one before their next query.
+	Current func() *internal.NovaSqlConfig
+}
+
+// Serve accepts connections on ln until ctx is done, then waits for every
+// session to finish its current query and close its database. ln is closed
+// on return.
+func Serve(ctx context.Context, ln net.Listener, cfg Config) error {
+	var (
+		wg    sync.WaitGroup
+		mu    sync.Mutex
+		conns = make(map[net.Conn]struct{})
+		keys  = &cancelKeys{sessions: make(map[cancelKey]*executor.Executor)}
+	)
+
+	go func() {
+		<-ctx.Done()
+		_ = ln.Close()
+		mu.Lock()
+		for c := range conns {
+			_ = c.SetReadDeadline(time.Now())
+		}
+		mu.Unlock()
+	}()
+	defer wg.Wait()
+
+	for {
+		conn, err := ln.Accept()
+		if err != nil {
+			select {
+			case <-ctx.Done():
+				return nil
+			default:
+			}
+			if errors.Is(err, net.ErrClosed) {
+				return err
+			}
+			log.Printf("pgwire accept: %v", err)
+			continue
+		}
+
+		mu.Lock()
+		if ctx.Err() != nil {
+			mu.Unlock()
+			_ = conn.Close()
+			continue
+		}
+		conns[conn] = struct{}{}
+		wg.Add(1)
+		mu.Unlock()
+
+		go func() {
+			defer wg.Done()
+			if err := serveConn(ctx, conn, cfg, keys); err != nil && !errors.Is(err, io.EOF) {
+				log.Printf("pgwire session %s: %v", conn.RemoteAddr(), err)
+			}
+			mu.Lock()
+			delete(conns, conn)
+			mu.Unlock()
+		}()
+	}
+}
+
+// cancelKey is the process ID and secret key of a session's BackendKeyData.
+type cancelKey struct{ pid, secret uint32 }
+
+// cancelKeys finds the session a CancelRequest, which comes on a connection
+// of its own, is for.
+type cancelKeys struct {
+	mu       sync.Mutex
+	next     uint32
+	sessions map[cancelKey]*executor.Executor
+}
+
+// add registers ex under a new key.
+func (k *cancelKeys) add(ex *executor.Executor) cancelKey {
+	var secret [4]byte
+	_, _ = rand.Read(secret[:])
+	k.mu.Lock()
+	defer k.mu.Unlock()
+	k.next++
+	key := cancelKey{pid: k.next, secret: binary.BigEndian.Uint32(secret[:])}
+	k.sessions[key] = ex
+	return key
+}
+
+func (k *cancelKeys) remove(key cancelKey) {
+	k.mu.Lock()
+	defer k.mu.Unlock()
+	delete(k.sessions, key)
+}
+
+// cancel cancels the query the session with key is running, if any.
+func (k *cancelKeys) cancel(key cancelKey) {
+	k.mu.Lock()
+	defer k.mu.Unlock()
+	if ex := k.sessions[key]; ex != nil {
+		ex.Cancel()
+	}
+}
+
+// session is one client connection.
+type session struct {
+	r    *bufio.Reader
+	w    *bufio.Writer
+	db   *novasql.Database
+	ex   *executor.Executor
+	keys *cancelKeys
+}
+
+func serveConn(ctx context.Context, conn net.Conn, cfg Config, keys *cancelKeys) error {
+	defer func() { _ = conn.Close() }()
+
+	s := &session{r: bufio.NewReader(conn), w: bufio.NewWriter(conn), keys: keys}
+	params, err := s.startup()
+	if err != nil || params == nil {
+		return err
+	}
+
+	s.db, err = novasql.Options().Config(cfg.Config).Upgrade(true).Metrics(cfg.Metrics).Open(cfg.Workdir)
+	if err != nil {
+		return s.fatal(sqlState(err), err.Error())
+	}
+	defer func() { _ = s.db.Close() }()
+	s.ex = executor.NewExecutor(s.db)
+	defer func() { _ = s.ex.CloseCursors() }()
+	logger := slog.With("pgconn", conn.RemoteAddr().String())
+	s.ex.SetLogger(logger)
+	logger.Debug("session open", "user", params["user"], "database", params["database"])
+	defer logger.Debug("session closed")
+
+	// psql defaults the database to the user name; only switch when it exists.
+	if name := params["database"]; name != "" {
+		if names, err := s.db.ListDatabase(); err == nil && slices.Contains(names, name) {
+			if _, err := s.db.SelectDatabase(name); err != nil {
+				return s.fatal("3D000", err.Error())
+			}
+		}
+	}
+
+	s.msg('R', u32(0)) // AuthenticationOk
+	for _, kv := range [][2]string{
+		{"server_version", serverVersion},
+		{"server_encoding", "UTF8"},
+		{"client_encoding", "UTF8"},
+		{"DateStyle", "ISO, MDY"},
+		{"integer_datetimes", "on"},
+		{"standard_conforming_strings", "on"},
+	} {
+		s.msg('S', cstr(kv[0]), cstr(kv[1]))
+	}
+	key := keys.add(s.ex)
+	defer keys.remove(key)
+	s.msg('K', u32(key.pid), u32(key.secret)) // BackendKeyData, for CancelRequest
+	s.ready()
+	if err := s.w.Flush(); err != nil {
+		return err
+	}
+
+	// inExtended skips messages until Sync after an extended-protocol error.
+	inExtended := false
+	applied := cfg.Config
+	for ctx.Err() == nil {
+		typ, body, err := s.readMessage()
+		if err != nil {
+			if ctx.Err() != nil {
+				return nil // read deadline set by shutdown
+			}
+			return err
+		}
+		if cfg.Current != nil {
+			if c := cfg.Current(); c != applied {
+				applied = c
+				if err := s.db.ApplyConfig(c); err != nil {
+					logger.Warn("config reload", "err", err)
+				}
+			}
+		}
+		switch typ {
+		case 'Q':
+			s.simpleQuery(strings.TrimRight(string(body), "\x00"))
+			s.ready()
+		case 'X': // Terminate
+			return nil
+		case 'S': // Sync
+			inExtended = false
+			s.ready()
+		case 'P', 'B', 'E', 'D', 'C', 'H':
+			if !inExtended {
+				s.errorResponse("0A000", "extended query protocol is not supported; use simple queries")
+				inExtended = true
+			}
+		default:
+			return s.fatal("08P01", fmt.Sprintf("unexpected message type %q", typ))
+		}
+		if err := s.w.Flush(); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// startup handles SSL/GSS negotiation and the StartupMessage. A nil map with
+// a nil error means the client sent a CancelRequest, which has been passed
+// on, and hung up.
+func (s *session) startup() (map[string]string, error) {
+	for {
+		body, err := s.readUntyped()
+		if err != nil {
+			return nil, err
+		}
+		if len(body) < 4 {
+			return nil, ErrProtocol
+		}
+		code := binary.BigEndian.Uint32(body)
+		switch code {
+		case sslRequestCode, gssRequestCode:
+			// Not supported: the client continues in plain text.
+			if err := s.w.WriteByte('N'); err != nil {
+				return nil, err
+			}
+			if err := s.w.Flush(); err != nil {
+				return nil, err
+			}
+			continue
+		case cancelCode:
+			if len(body) == 12 {
+				s.keys.cancel(cancelKey{pid: binary.BigEndian.Uint32(body[4:]), secret: binary.BigEndian.Uint32(body[8:])})
+			}
+			return nil, nil
+		case protocolVersion3:
+		default:
+			return nil, s.fatal("0A000", fmt.Sprintf("unsupported protocol version %d.%d", code>>16, code&0xffff))
+		}
+
+		params := make(map[string]string)
+		fields := strings.Split(string(body[4:]), "\x00")
+		for i := 0; i+1 < len(fields); i += 2 {
+			if fields[i] == "" {
+				break
+			}
+			params[fields[i]] = fields[i+1]
+		}
+		return params, nil
+	}
+}
+
+// simpleQuery runs every statement of a Query message in order and stops at
+// the first error, like PostgreSQL does.
+func (s *session) simpleQuery(q string) {
+	stmts := splitStatements(q)
+	if len(stmts) == 0 {
+		s.msg('I') // EmptyQueryResponse
+		return
+	}
+	for _, sql := range stmts {
+		stmt, err := parser.Parse(sql)
+		if err != nil {
+			s.errorResponse("42601", err.Error())
+			return
+		}
+		res, err := s.ex.ExecSQL(sql)
+		if err != nil {
+			s.errorResponse(sqlState(err), err.Error())
+			return
+		}
+		hasRows := false
+		switch stmt.(type) {
+		case *parser.SelectStmt, *parser.ExplainStmt, *parser.LastInsertRowIDStmt, *parser.FetchStmt,
+			*parser.ShowStmt:
+			hasRows = true
+		case *parser.InsertStmt, *parser.UpdateStmt, *parser.DeleteStmt:
+			hasRows = res.Columns != nil // RETURNING
+		}
+		if hasRows {
+			s.rowDescription(res)
+			for _, row := range res.Rows {
+				s.dataRow(row)
+			}
+		}
+		s.msg('C', cstr(commandTag(stmt, res)))
+	}
+}
+
+// sqlState is the SQLSTATE reported for err, from its novasql.ErrorCode.
+func sqlState(err error) string {
+	var ce *novasql.ConstraintError
+	if errors.As(err, &ce) {
+		switch ce.Kind {
+		case novasql.ConstraintNotNull:
+			return "23502"
+		case novasql.ConstraintForeignKey:
+			return "23503"
+		}
+		return "23505"
+	}
+	switch {
+	case errors.Is(err, executor.ErrNoCursor):
+		return "34000"
+	case errors.Is(err, executor.ErrCursorExists):
+		return "42P03"
+	case errors.Is(err, novasql.ErrSettingFixed):
+		return "55P02"
+	}
+	switch novasql.CodeOf(err) {
+	case novasql.CodeSyntax:
+		return "42601"
+	case novasql.CodeTableNotFound:
+		return "42P01"
+	case novasql.CodeNotFound:
+		return "42704"
+	case novasql.CodeExists:
+		return "42710"
+	case novasql.CodeInvalid:
+		return "22023"
+	case novasql.CodeConstraint:
+		return "23000"
+	case novasql.CodeConflict:
+		return "40001"
+	case novasql.CodeBusy:
+		return "55006"
+	case novasql.CodeReadOnly:
+		return "25006"
+	case novasql.CodeIO:
+		return "58030"
+	case novasql.CodeCorrupt:
+		return "XX001"
+	case novasql.CodeCancelled:
+		return "57014"
+	}
+	return "XX000"
+}
+
+// commandTag is the CommandComplete tag psql prints ("INSERT 0 1", ...).
+func commandTag(stmt parser.Statement, res *executor.Result) string {
+	n := strconv.FormatInt(res.AffectedRows, 10)
+	switch stmt.(type) {
+	case *parser.SelectStmt, *parser.LastInsertRowIDStmt:
+		return "SELECT " + strconv.Itoa(len(res.Rows))
+	case *parser.FetchStmt:
+		return "FETCH " + strconv.Itoa(len(res.Rows))
+	case *parser.DeclareCursorStmt:
+		return "DECLARE CURSOR"
+	case *parser.CloseCursorStmt:
+		return "CLOSE CURSOR"
+	case *parser.InsertStmt:
+		return "INSERT 0 " + n
+	case *parser.UpdateStmt:
+		return "UPDATE " + n
+	case *parser.DeleteStmt:
+		return "DELETE " + n
+	case *parser.CopyStmt:
+		return "COPY " + n
+	case *parser.CreateTableStmt:
+		return "CREATE TABLE"
+	case *parser.DropTableStmt:
+		return "DROP TABLE"
+	case *parser.AlterTableStmt:
+		return "ALTER TABLE"
+	case *parser.CreateIndexStmt:
+		return "CREATE INDEX"
+	case *parser.DropIndexStmt:
+		return "DROP INDEX"
+	case *parser.CreateDatabaseStmt:
+		return "CREATE DATABASE"
+	case *parser.DropDatabaseStmt:
+		return "DROP DATABASE"
+	case *parser.UseDatabaseStmt, *parser.SetStmt:
+		return "SET"
+	case *parser.ShowStmt:
+		return "SHOW"
+	case *parser.VacuumStmt:
+		return "VACUUM"
+	case *parser.ExplainStmt:
+		return "EXPLAIN"
+	case *parser.AnalyzeStmt:
+		return "ANALYZE"
+	default:
+		return "OK"
+	}
+}
+
+// columnOID guesses a column's type from its first non-NULL value; Result
+// carries no type information. All-NULL columns are reported as text.
+func columnOID(res *executor.Result, col int) uint32 {
+	for _, row := range res.Rows {
+		if col >= len(row) || row[col] == nil {
+			continue
+		}
+		switch row[col].(type) {
+		case int64, int:
+			return oidInt8
+		case int32:
+			return oidInt4
+		case float64:
+			return oidFloat8
+		case bool:
+			return oidBool
+		case record.Date:
+			return oidDate
+		case record.Time:
+			return oidTime
+		case record.Timestamp:
+			return oidTimestamp
+		case record.Decimal:
+			return oidNumeric
+		default:
+			return oidText
+		}
+	}
+	return oidText
+}
+
+func (s *session) rowDescription(res *executor.Result) {
+	parts := [][]byte{u16(uint16(len(res.Columns)))}
+	for i, name := range res.Columns {
+		oid := columnOID(res, i)
+		size := int16(-1)
+		switch oid {
+		case oidInt8, oidFloat8, oidTime, oidTimestamp:
+			size = 8
+		case oidInt4, oidDate:
+			size = 4
+		case oidBool:
+			size = 1
+		}
+		parts = append(parts,
+			cstr(name),
+			u32(0), u16(0), // no table OID / attribute number
+			u32(oid), u16(uint16(size)),
+			u32(0xffffffff), // type modifier -1
+			u16(0),          // text format
+		)
+	}
+	s.msg('T', parts...)
+}
+
+func (s *session) dataRow(row []any) {
+	parts := [][]byte{u16(uint16(len(row)))}
+	for _, v := range row {
+		if v == nil {
+			parts = append(parts, u32(0xffffffff)) // NULL
+			continue
+		}
+		text := textValue(v)
+		parts = append(parts, u32(uint32(len(text))), []byte(text))
+	}
+	s.msg('D', parts...)
+}
+
+// textValue is the PostgreSQL text output format of v.
+func textValue(v any) string {
+	switch x := v.(type) {
+	case bool:
+		if x {
+			return "t"
+		}
+		return "f"
+	case float64:
+		return strconv.FormatFloat(x, 'g', -1, 64)
+	case []byte:
+		return `\x` + fmt.Sprintf("%x", x)
+	default:
+		return fmt.Sprint(x)
+	}
+}
+
+func (s *session) ready() { s.msg('Z', []byte{'I'}) }
+
+func (s *session) errorResponse(code, message string) {
+	s.msg('E',
+		[]byte{'S'}, cstr("ERROR"),
+		[]byte{'V'}, cstr("ERROR"),
+		[]byte{'C'}, cstr(code),
+		[]byte{'M'}, cstr(message),
+		[]byte{0},
+	)
+}
+
+// fatal reports a connection-ending error to the client and returns it.
+func (s *session) fatal(code, message string) error {
+	s.msg('E',
+		[]byte{'S'}, cstr("FATAL"),
+		[]byte{'V'}, cstr("FATAL"),
+		[]byte{'C'}, cstr(code),
+		[]byte{'M'}, cstr(message),
+		[]byte{0},
+	)
+	_ = s.w.Flush()
+	return fmt.Errorf("%w: %s", ErrProtocol, message)
+}
+
+// ---- framing ----
+
+// msg buffers one backend message: type byte, int32 length, then parts.
+func (s *session) msg(typ byte, parts ...[]byte) {
+	n := 4
+	for _, p := range parts {
+		n += len(p)
+	}
+	_ = s.w.WriteByte(typ)
+	_, _ = s.w.Write(u32(uint32(n)))
+	for _, p := range parts {
+		_, _ = s.w.Write(p)
+	}
+}
+
+func (s *session) readMessage() (byte, []byte, error) {
+	typ, err := s.r.ReadByte()
+	if err != nil {
+		return 0, nil, err
+	}
+	body, err := s.readUntyped()
+	return typ, body, err
+}
+
+// readUntyped reads an int32 length (which counts itself) and the body.
+func (s *session) readUntyped() ([]byte, error) {
+	var hdr [4]byte
+	if _, err := io.ReadFull(s.r, hdr[:]); err != nil {
+		return nil, err
+	}
+	n := binary.BigEndian.Uint32(hdr[:])
+	if n < 4 || n > maxMessageSize {
+		return nil, fmt.Errorf("%w: message length %d", ErrProtocol, n)
+	}
+	body := make([]byte, n-4)
+	if _, err := io.ReadFull(s.r, body); err != nil {
+		return nil, err
+	}
+	return body, nil
+}
+
+func u16(v uint16) []byte { return binary.BigEndian.AppendUint16(nil, v) }
+func u32(v uint32) []byte { return binary.BigEndian.AppendUint32(nil, v) }
+func cstr(v string) []byte {
+	return append([]byte(v), 0)
+}
+
+// splitStatements splits a simple Query into statements, each terminated by
+// ';' as the novasql parser requires. Semicolons inside quotes do not split.
+func splitStatements(q string) []string {
+	var (
+		out     []string
+		start   int
+		inQuote bool
+	)
+	for i := 0; i < len(q); i++ {
+		switch q[i] {
+		case '\'':
+			inQuote = !inQuote
+		case ';':
+			if inQuote {
+				continue
+			}
+			if stmt := strings.TrimSpace(q[start:i]); stmt != "" {
+				out = append(out, stmt+";")
+			}
+			start = i + 1
+		}
+	}
+	if stmt := strings.TrimSpace(q[start:]); stmt != "" {
+		out = append(out, stmt+";")
+	}
+	return out
+}
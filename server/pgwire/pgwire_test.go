@@ -0,0 +1,258 @@
+package pgwire
+
+import (
+	"bufio"
+	"context"
+	"encoding/binary"
+	"io"
+	"net"
+	"strconv"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+
+	"github.com/tuannm99/novasql"
+	"github.com/tuannm99/novasql/internal/record"
+)
+
+type pgMsg struct {
+	typ  byte
+	body []byte
+}
+
+// pgClient is just enough of a frontend to drive the simple query protocol.
+type pgClient struct {
+	t    *testing.T
+	conn net.Conn
+	r    *bufio.Reader
+	key  []byte // BackendKeyData: process ID and secret key
+}
+
+func dialPG(t *testing.T, addr string, params ...string) *pgClient {
+	t.Helper()
+
+	conn, err := net.Dial("tcp", addr)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = conn.Close() })
+	c := &pgClient{t: t, conn: conn, r: bufio.NewReader(conn)}
+
+	// psql asks for TLS first and falls back to plain text on 'N'.
+	_, err = conn.Write(binary.BigEndian.AppendUint32(u32(8), sslRequestCode))
+	require.NoError(t, err)
+	b, err := c.r.ReadByte()
+	require.NoError(t, err)
+	require.Equal(t, byte('N'), b)
+
+	body := u32(protocolVersion3)
+	for _, p := range append([]string{"user", "test"}, params...) {
+		body = append(body, cstr(p)...)
+	}
+	body = append(body, 0)
+	_, err = conn.Write(append(u32(uint32(len(body)+4)), body...))
+	require.NoError(t, err)
+
+	msgs := c.untilReady()
+	require.Equal(t, byte('R'), msgs[0].typ)
+	require.Equal(t, u32(0), msgs[0].body)
+	require.Equal(t, byte('K'), msgs[len(msgs)-2].typ)
+	c.key = msgs[len(msgs)-2].body
+	return c
+}
+
+func (c *pgClient) read() pgMsg {
+	c.t.Helper()
+
+	typ, err := c.r.ReadByte()
+	require.NoError(c.t, err)
+	var hdr [4]byte
+	_, err = io.ReadFull(c.r, hdr[:])
+	require.NoError(c.t, err)
+	body := make([]byte, binary.BigEndian.Uint32(hdr[:])-4)
+	_, err = io.ReadFull(c.r, body)
+	require.NoError(c.t, err)
+	return pgMsg{typ: typ, body: body}
+}
+
+func (c *pgClient) untilReady() []pgMsg {
+	c.t.Helper()
+
+	var out []pgMsg
+	for {
+		m := c.read()
+		out = append(out, m)
+		if m.typ == 'Z' {
+			return out
+		}
+	}
+}
+
+func (c *pgClient) query(sql string) []pgMsg {
+	c.t.Helper()
+
+	_, err := c.conn.Write(append([]byte{'Q'}, append(u32(uint32(len(sql)+5)), cstr(sql)...)...))
+	require.NoError(c.t, err)
+	return c.untilReady()
+}
+
+// summarize renders messages compactly: T(col:oid,...) D(v,...) C(tag) E(code) Z.
+func summarize(msgs []pgMsg) []string {
+	var out []string
+	for _, m := range msgs {
+		switch m.typ {
+		case 'T':
+			b := m.body[2:]
+			var cols []string
+			for range binary.BigEndian.Uint16(m.body) {
+				name, rest, _ := strings.Cut(string(b), "\x00")
+				b = []byte(rest)
+				cols = append(cols, name+":"+strconv.Itoa(int(binary.BigEndian.Uint32(b[6:]))))
+				b = b[18:]
+			}
+			out = append(out, "T("+strings.Join(cols, ",")+")")
+		case 'D':
+			b := m.body[2:]
+			var vals []string
+			for range binary.BigEndian.Uint16(m.body) {
+				n := int32(binary.BigEndian.Uint32(b))
+				b = b[4:]
+				if n < 0 {
+					vals = append(vals, "NULL")
+					continue
+				}
+				vals = append(vals, string(b[:n]))
+				b = b[n:]
+			}
+			out = append(out, "D("+strings.Join(vals, ",")+")")
+		case 'C':
+			out = append(out, "C("+strings.TrimRight(string(m.body), "\x00")+")")
+		case 'E':
+			code := ""
+			for _, f := range strings.Split(string(m.body), "\x00") {
+				if strings.HasPrefix(f, "C") {
+					code = f[1:]
+				}
+			}
+			out = append(out, "E("+code+")")
+		default:
+			out = append(out, string(m.typ))
+		}
+	}
+	return out
+}
+
+func TestServe_SimpleQueries(t *testing.T) {
+	ln, err := net.Listen("tcp", "127.0.0.1:0")
+	require.NoError(t, err)
+	ctx, cancel := context.WithCancel(context.Background())
+	done := make(chan error, 1)
+	go func() { done <- Serve(ctx, ln, Config{Workdir: t.TempDir()}) }()
+
+	c := dialPG(t, ln.Addr().String())
+	require.Equal(t, []string{"C(CREATE TABLE)", "C(INSERT 0 1)", "C(INSERT 0 1)", "Z"},
+		summarize(c.query("CREATE TABLE users (id INT, name TEXT, ok BOOL); "+
+			"INSERT INTO users VALUES (1, 'a;b', true); INSERT INTO users VALUES (2, 'c', false)")))
+	require.Equal(t, []string{
+		"T(id:20,name:25,ok:16)", "D(1,a;b,t)", "D(2,c,f)", "C(SELECT 2)", "Z",
+	}, summarize(c.query("SELECT * FROM users ORDER BY id;")))
+	require.Equal(t, []string{"C(UPDATE 1)", "C(DELETE 1)", "Z"},
+		summarize(c.query("UPDATE users SET name = 'z' WHERE id = 2; DELETE FROM users WHERE id = 1;")))
+	require.Equal(t, []string{
+		"T(id:20)", "D(3)", "C(INSERT 0 1)", "T(id:20,name:25,ok:16)", "D(3,d,t)", "C(DELETE 1)", "Z",
+	}, summarize(c.query("INSERT INTO users VALUES (3, 'd', true) RETURNING id; "+
+		"DELETE FROM users WHERE id = 3 RETURNING *;")))
+
+	// Empty queries, syntax errors (which abort the rest of the batch) and
+	// the extended protocol, which is refused until Sync.
+	require.Equal(t, []string{"I", "Z"}, summarize(c.query("  ;  ")))
+	require.Equal(t, []string{"E(42601)", "Z"}, summarize(c.query("SELEC 1; DELETE FROM users;")))
+	_, err = c.conn.Write([]byte{'P', 0, 0, 0, 8, 0, 0, 0, 0, 'S', 0, 0, 0, 4})
+	require.NoError(t, err)
+	require.Equal(t, []string{"E(0A000)", "Z"}, summarize(c.untilReady()))
+	require.Equal(t, []string{"T(id:20,name:25,ok:16)", "D(2,z,f)", "C(SELECT 1)", "Z"},
+		summarize(c.query("SELECT * FROM users")))
+
+	// A cursor pages through a result; with no rows left the types are unknown.
+	require.Equal(t, []string{
+		"C(DECLARE CURSOR)", "T(id:20,name:25,ok:16)", "D(2,z,f)", "C(FETCH 1)",
+		"T(id:25,name:25,ok:25)", "C(FETCH 0)", "C(CLOSE CURSOR)", "E(34000)", "Z",
+	}, summarize(c.query("DECLARE c CURSOR FOR SELECT * FROM users; FETCH 5 FROM c; FETCH c; CLOSE c; FETCH c;")))
+
+	// The startup "database" parameter selects an existing database.
+	require.Equal(t, []string{"C(CREATE DATABASE)", "Z"}, summarize(c.query("CREATE DATABASE other;")))
+	other := dialPG(t, ln.Addr().String(), "database", "other")
+	require.Equal(t, "E(42P01)", summarize(other.query("SELECT * FROM users;"))[0])
+
+	cancel()
+	select {
+	case err := <-done:
+		require.NoError(t, err)
+	case <-time.After(5 * time.Second):
+		t.Fatal("Serve did not return after shutdown")
+	}
+}
+
+func TestServe_CancelRequest(t *testing.T) {
+	workdir := t.TempDir()
+	db, err := novasql.Options().Open(workdir)
+	require.NoError(t, err)
+	_, err = db.CreateTable("big", record.Schema{Cols: []record.Column{
+		{Name: "id", Type: record.ColInt64}, {Name: "v", Type: record.ColInt64},
+	}})
+	require.NoError(t, err)
+	rows := make([][]any, 1000)
+	for i := range rows {
+		rows[i] = []any{int64(i), int64(-1)}
+	}
+	_, err = db.InsertRows("big", rows)
+	require.NoError(t, err)
+	require.NoError(t, db.Close())
+
+	ln, err := net.Listen("tcp", "127.0.0.1:0")
+	require.NoError(t, err)
+	ctx, cancel := context.WithCancel(context.Background())
+	defer cancel()
+	go func() { _ = Serve(ctx, ln, Config{Workdir: workdir}) }()
+	c := dialPG(t, ln.Addr().String())
+	require.Len(t, c.key, 8)
+
+	// cancelRequest sends a CancelRequest for key on a connection of its own
+	// and waits for the server to hang up, having passed it on.
+	cancelRequest := func(key []byte) {
+		conn, err := net.Dial("tcp", ln.Addr().String())
+		if err != nil {
+			return
+		}
+		defer func() { _ = conn.Close() }()
+		_, _ = conn.Write(append(u32(16), append(u32(cancelCode), key...)...))
+		_, _ = io.Copy(io.Discard, conn)
+	}
+
+	// A nested-loop join of a million pairs, none matching, cancelled as
+	// psql does on ^C.
+	sql := "SELECT COUNT(*) FROM big a JOIN big b ON a.id < b.v;"
+	_, err = c.conn.Write(append([]byte{'Q'}, append(u32(uint32(len(sql)+5)), cstr(sql)...)...))
+	require.NoError(t, err)
+	done, stopped := make(chan struct{}), make(chan struct{})
+	go func() {
+		defer close(stopped)
+		for {
+			select {
+			case <-done:
+				return
+			case <-time.After(time.Millisecond):
+				cancelRequest(c.key)
+			}
+		}
+	}()
+	msgs := c.untilReady()
+	close(done)
+	<-stopped
+	require.Equal(t, []string{"E(57014)", "Z"}, summarize(msgs))
+
+	// The session goes on, and an unknown key is ignored.
+	cancelRequest(make([]byte, 8))
+	require.Equal(t, []string{"T(count(*):20)", "D(1000)", "C(SELECT 1)", "Z"},
+		summarize(c.query("SELECT COUNT(*) FROM big;")))
+}
@@ -9,6 +9,7 @@ import (
 	"sync/atomic"
 	"time"
 
+	"github.com/tuannm99/novasql"
 	"github.com/tuannm99/novasql/internal/sql/executor"
 	"github.com/tuannm99/novasql/server/novasqlwire"
 )
@@ -21,6 +22,11 @@ type Client struct {
 	mu   sync.Mutex
 	id   atomic.Uint64
 
+	// wmu serializes writes, as a cancel request is written while Exec
+	// waits for its response; inFlight is the ID of that request (0 = none).
+	wmu      sync.Mutex
+	inFlight atomic.Uint64
+
 	// Optional per-request timeout (0 = no timeout).
 	rwTimeout time.Duration
 }
@@ -83,9 +89,15 @@ func (c *Client) ExecContext(ctx context.Context, sql string) (*executor.Result,
 	}()
 
 	req := novasqlwire.ExecuteRequest{ID: reqID, SQL: sql}
-	if err := novasqlwire.WriteFrame(c.conn, req); err != nil {
+	if err := c.write(req); err != nil {
 		return nil, err
 	}
+	c.inFlight.Store(reqID)
+	defer c.inFlight.Store(0)
+	// Once ctx is done the server cancels the request, whose response is
+	// then the cancellation error.
+	stop := context.AfterFunc(ctx, func() { _ = c.cancel(reqID) })
+	defer stop()
 
 	var resp novasqlwire.ExecuteResponse
 	if err := novasqlwire.ReadFrame(c.conn, &resp); err != nil {
@@ -96,11 +108,36 @@ func (c *Client) ExecContext(ctx context.Context, sql string) (*executor.Result,
 		return nil, fmt.Errorf("sqlclient: response id mismatch: got=%d want=%d", resp.ID, reqID)
 	}
 	if resp.Error != "" {
-		return nil, errors.New(resp.Error)
+		// novasql.CodeOf and novasql.AsError see the server's code.
+		return nil, novasql.NewError(resp.Code, errors.New(resp.Error))
 	}
 	return resp.Result, nil
 }
 
+// Cancel asks the server to cancel the request in flight, if any, from
+// another goroutine than the one waiting in Exec: that Exec then fails with
+// an error of code novasql.CodeCancelled, unless the request completed
+// first.
+func (c *Client) Cancel() error {
+	if c == nil || c.conn == nil {
+		return fmt.Errorf("sqlclient: nil client")
+	}
+	if id := c.inFlight.Load(); id != 0 {
+		return c.cancel(id)
+	}
+	return nil
+}
+
+func (c *Client) cancel(id uint64) error {
+	return c.write(novasqlwire.ExecuteRequest{ID: id, Cancel: true})
+}
+
+func (c *Client) write(req novasqlwire.ExecuteRequest) error {
+	c.wmu.Lock()
+	defer c.wmu.Unlock()
+	return novasqlwire.WriteFrame(c.conn, req)
+}
+
 func (c *Client) applyDeadline(ctx context.Context) error {
 	// Prefer context deadline if present; otherwise use rwTimeout.
 	if dl, ok := ctx.Deadline(); ok {